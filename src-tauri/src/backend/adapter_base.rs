@@ -1,19 +1,33 @@
 use serde_json::{json, Value};
-use std::collections::HashMap;
-use std::path::PathBuf;
-use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Child;
-use tokio::sync::{mpsc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout};
+use tokio::sync::Mutex;
 
 use crate::backend::app_server::{
-    build_codex_command_with_bin, check_cli_installation, CliAdapter, CliSpawnConfig,
-    WorkspaceSession,
+    build_codex_command_with_bin, CliAdapter, CliSpawnConfig, WorkspaceSession,
 };
-use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::background_queue::BackgroundSender;
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink, ThreadItem};
+use crate::backend::exec_target;
+use crate::backend::file_context::build_file_context;
+use crate::backend::rate_limits::{
+    self, detect_rate_limit, stream_error_text, RateLimitHit, RateLimits,
+};
+use crate::backend::review::{self, ReviewTarget};
+use crate::backend::turn_hooks::{TurnHooks, TurnObserverSink};
+use crate::shared::ansi::{strip_ansi, strip_ansi_value};
+use crate::shared::atomic_write::{read_with_backup, write_with_backup};
+use crate::shared::logging_core;
 use crate::shared::process_core::kill_child_process_tree;
+use crate::shared::redaction_core::{DeltaRedactor, SecretRedactor};
+use crate::shared::turn_snapshot_core;
+use crate::shared::workspace_env_core::{resolve_workspace_env, workspace_secret_values};
 use crate::types::WorkspaceEntry;
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
@@ -24,6 +38,39 @@ pub(crate) struct ThreadMetadata {
     pub(crate) created_at: u64,
     pub(crate) updated_at: u64,
     pub(crate) archived: bool,
+    /// Transcript of a session imported from the CLI's own history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) source_path: Option<PathBuf>,
+    /// Where the CLI records this thread's session, removed on delete.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) transcript_path: Option<PathBuf>,
+    /// The thread's history as summarized by the last compaction. Sent ahead
+    /// of the prompt that starts the next CLI session.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) compacted_summary: Option<String>,
+    /// How the thread's last turn failed; cleared when a turn succeeds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) last_turn_failure: Option<TurnFailure>,
+}
+
+/// A turn whose CLI exited with a non-zero code.
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnFailure {
+    pub(crate) turn_id: String,
+    pub(crate) exit_code: i32,
+    /// The last stderr lines the CLI wrote.
+    pub(crate) stderr: Vec<String>,
+    pub(crate) failed_at: u64,
+}
+
+/// A session the CLI recorded outside the monitor (e.g. run from a terminal).
+pub(crate) struct ImportedSession {
+    pub(crate) cli_session_id: String,
+    pub(crate) name: Option<String>,
+    pub(crate) created_at: u64,
+    pub(crate) updated_at: u64,
+    pub(crate) source_path: PathBuf,
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
@@ -33,20 +80,88 @@ pub(crate) struct ThreadStore {
 
 impl ThreadStore {
     pub(crate) fn load(path: &PathBuf) -> Self {
-        std::fs::read_to_string(path)
-            .ok()
-            .and_then(|content| serde_json::from_str(&content).ok())
-            .unwrap_or_default()
+        read_with_backup(path, |content| {
+            serde_json::from_str(content).map_err(|e| e.to_string())
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default()
     }
 
     pub(crate) fn save(&self, path: &PathBuf) -> Result<(), String> {
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)
-                .map_err(|e| format!("Failed to create thread store directory: {e}"))?;
-        }
         let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
-        std::fs::write(path, json).map_err(|e| format!("Failed to write thread store: {e}"))
+        write_with_backup(path, json.as_bytes())
+            .map_err(|e| format!("Failed to write thread store: {e}"))
+    }
+}
+
+/// `turn/start` input, given either as a plain string or as Codex-style
+/// input items.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct TurnInput {
+    pub(crate) text: String,
+    pub(crate) images: Vec<PathBuf>,
+    /// Workspace-relative paths from `fileRef` items.
+    pub(crate) files: Vec<String>,
+}
+
+pub(crate) fn parse_turn_input(input: Option<&Value>) -> Result<TurnInput, String> {
+    let input = input.ok_or("missing input")?;
+    if let Some(text) = input.as_str() {
+        return Ok(TurnInput {
+            text: text.to_string(),
+            ..TurnInput::default()
+        });
+    }
+    let items = input
+        .as_array()
+        .ok_or("input must be a string or a list of items")?;
+    let mut texts = Vec::new();
+    let mut images = Vec::new();
+    let mut files = Vec::new();
+    for item in items {
+        match item.get("type").and_then(|v| v.as_str()) {
+            Some("text") => {
+                if let Some(text) = item.get("text").and_then(|v| v.as_str()) {
+                    texts.push(text.to_string());
+                }
+            }
+            Some("localImage") => {
+                let path = item
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or("localImage input is missing path")?;
+                images.push(PathBuf::from(path));
+            }
+            Some("fileRef") => {
+                let path = item
+                    .get("path")
+                    .and_then(|v| v.as_str())
+                    .ok_or("fileRef input is missing path")?;
+                files.push(path.to_string());
+            }
+            Some("image") => {
+                return Err(
+                    "Image URLs aren't supported by this CLI; attach a local image file instead."
+                        .to_string(),
+                )
+            }
+            other => {
+                return Err(format!(
+                    "unsupported input item type: {}",
+                    other.unwrap_or("(none)")
+                ))
+            }
+        }
     }
+    if texts.is_empty() && images.is_empty() && files.is_empty() {
+        return Err("missing input".to_string());
+    }
+    Ok(TurnInput {
+        text: texts.join("\n\n"),
+        images,
+        files,
+    })
 }
 
 pub(crate) fn now_epoch() -> u64 {
@@ -56,31 +171,670 @@ pub(crate) fn now_epoch() -> u64 {
         .unwrap_or(0)
 }
 
-pub(crate) fn thread_store_path(workspace_id: &str) -> PathBuf {
-    let data_dir = dirs_next::data_dir()
+/// Holds each workspace's adapter thread store and turn metrics.
+pub(crate) fn adapter_threads_dir() -> PathBuf {
+    dirs_next::data_dir()
         .unwrap_or_else(|| PathBuf::from("."))
         .join("agent-monitor")
-        .join("adapter-threads");
-    data_dir.join(format!("{workspace_id}.json"))
+        .join("adapter-threads")
+}
+
+pub(crate) fn thread_store_path(workspace_id: &str) -> PathBuf {
+    adapter_threads_dir().join(format!("{workspace_id}.json"))
+}
+
+pub(crate) fn turn_metrics_path(workspace_id: &str) -> PathBuf {
+    thread_store_path(workspace_id).with_file_name(format!("{workspace_id}.metrics.json"))
+}
+
+/// Returns the in-memory store for `path`, shared with any live session of
+/// that workspace so background jobs don't overwrite each other's changes.
+pub(crate) fn open_thread_store(path: &PathBuf) -> Arc<Mutex<ThreadStore>> {
+    static OPEN_STORES: OnceLock<std::sync::Mutex<HashMap<PathBuf, Weak<Mutex<ThreadStore>>>>> =
+        OnceLock::new();
+    let stores = OPEN_STORES.get_or_init(|| std::sync::Mutex::new(HashMap::new()));
+    let Ok(mut stores) = stores.lock() else {
+        return Arc::new(Mutex::new(ThreadStore::load(path)));
+    };
+    if let Some(store) = stores.get(path).and_then(Weak::upgrade) {
+        return store;
+    }
+    stores.retain(|_, store| store.strong_count() > 0);
+    let store = Arc::new(Mutex::new(ThreadStore::load(path)));
+    stores.insert(path.clone(), Arc::downgrade(&store));
+    store
+}
+
+/// Removes the files kept for a deleted thread: the CLI transcript (or the
+/// imported one) and its turn snapshot.
+pub(crate) fn purge_thread_files(workspace_id: &str, thread_id: &str, meta: &ThreadMetadata) {
+    remove_session_transcripts(workspace_id, thread_id, meta);
+    if let Ok(store_dir) = turn_snapshot_core::snapshot_store_dir(workspace_id) {
+        if let Err(err) = turn_snapshot_core::remove_thread_snapshot(&store_dir, thread_id) {
+            tracing::warn!(workspace_id, thread_id, "{err}");
+        }
+    }
+}
+
+/// Removes the CLI's own records of the thread's session.
+fn remove_session_transcripts(workspace_id: &str, thread_id: &str, meta: &ThreadMetadata) {
+    for path in [&meta.transcript_path, &meta.source_path]
+        .into_iter()
+        .flatten()
+    {
+        if let Err(err) = std::fs::remove_file(path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!(
+                    workspace_id,
+                    thread_id,
+                    "failed to remove {}: {err}",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+const COMPACT_PROMPT: &str = "\
+Summarize this conversation so it can continue in a fresh session. Keep the \
+goals, decisions and open questions, the files, commands and code that \
+matter (with exact names), and what was being worked on last. Reply with the \
+summary only.";
+
+/// Rough token count for `bytes` of text.
+fn estimate_tokens(bytes: u64) -> u64 {
+    bytes.div_ceil(4)
+}
+
+/// The first prompt of the session that follows a compaction.
+fn prompt_with_summary(summary: &str, prompt: &str) -> String {
+    format!(
+        "<conversation-summary>\nEarlier history of this conversation was compacted into this summary:\n{summary}\n</conversation-summary>\n\n{prompt}"
+    )
+}
+
+fn compaction_item_event(
+    completed: bool,
+    thread_id: &str,
+    item_id: &str,
+    status: &str,
+    error: Option<&str>,
+) -> Value {
+    let thread_id = thread_id.to_string();
+    let turn_id = item_id.to_string();
+    let item = ThreadItem::ContextCompaction {
+        id: item_id.to_string(),
+        status: status.to_string(),
+        error: error.map(str::to_string),
+    };
+    let event = if completed {
+        AppEvent::ItemCompleted {
+            thread_id,
+            turn_id,
+            item,
+        }
+    } else {
+        AppEvent::ItemStarted {
+            thread_id,
+            turn_id,
+            item,
+        }
+    };
+    event.into_message()
+}
+
+/// What the installed CLI supports, probed once when the session starts.
+#[derive(serde::Serialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliCapabilities {
+    /// `--version` output.
+    pub(crate) version: Option<String>,
+    /// Whether `--help` lists each of the profile's [`CliProfile::probed_flags`].
+    /// Empty when the help text couldn't be read.
+    pub(crate) flags: BTreeMap<String, bool>,
+}
+
+impl CliCapabilities {
+    /// Flags the probe couldn't check are assumed to be supported.
+    pub(crate) fn supports(&self, flag: &str) -> bool {
+        self.flags.get(flag).copied().unwrap_or(true)
+    }
 }
 
+/// Whether `flag` appears in `help` as a whole option, so `--resume` isn't
+/// found in `--resume-last`.
+fn help_lists_flag(help: &str, flag: &str) -> bool {
+    help.match_indices(flag).any(|(start, _)| {
+        let before = help[..start].chars().next_back();
+        let after = help[start + flag.len()..].chars().next();
+        !before.is_some_and(|ch| ch.is_alphanumeric() || ch == '-')
+            && !after.is_some_and(|ch| ch.is_alphanumeric() || ch == '-')
+    })
+}
+
+async fn probe_capabilities(
+    config: &CliSpawnConfig,
+    cwd: &str,
+    run_name: &str,
+    version: Option<String>,
+    flags: &[&str],
+) -> CliCapabilities {
+    let mut capabilities = CliCapabilities {
+        version,
+        flags: BTreeMap::new(),
+    };
+    if flags.is_empty() {
+        return capabilities;
+    }
+    let Ok(mut command) =
+        build_codex_command_with_bin(config.cli_bin.clone(), None, vec!["--help".to_string()])
+    else {
+        return capabilities;
+    };
+    command.envs(resolve_workspace_env(&config.env).unwrap_or_default());
+    let probe_name = format!("{run_name}-probe");
+    let Ok(mut command) = config.target.wrap(command, cwd, &probe_name) else {
+        return capabilities;
+    };
+    command.stdin(std::process::Stdio::null());
+    command.kill_on_drop(true);
+    let help = match tokio::time::timeout(CAPABILITY_PROBE_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) => format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ),
+        _ => return capabilities,
+    };
+    if help.trim().is_empty() {
+        return capabilities;
+    }
+    capabilities.flags = flags
+        .iter()
+        .map(|flag| (flag.to_string(), help_lists_flag(&help, flag)))
+        .collect();
+    capabilities
+}
+
+#[async_trait::async_trait]
 pub(crate) trait CliProfile: Send + Sync + 'static {
+    /// The turn's command line, leaving out or working around flags that
+    /// `capabilities` says the installed CLI lacks.
     fn build_turn_command(
         &self,
         config: &CliSpawnConfig,
         session_id: Option<&str>,
-        prompt: &str,
+        input: &TurnInput,
         cwd: &str,
-        params: &Value,
+        effort: Option<&str>,
+        capabilities: &CliCapabilities,
     ) -> Result<tokio::process::Command, String>;
 
+    /// Flags whose support is checked in the CLI's `--help` at session
+    /// start, for `build_turn_command` to adapt to.
+    fn probed_flags(&self) -> &'static [&'static str] {
+        &[]
+    }
+
     fn parse_stream_line(&self, line: &str, thread_id: &str, turn_id: &str) -> Option<Value>;
 
     fn extract_session_id(&self, line: &str) -> Option<String>;
 
-    fn model_list(&self) -> Value;
+    async fn model_list(&self, config: &CliSpawnConfig) -> Value;
 
     fn provider_name(&self) -> &str;
+
+    /// Sessions recorded by the CLI for `cwd` whose ids aren't in `known`.
+    fn discover_sessions(&self, _cwd: &str, _known: &HashSet<String>) -> Vec<ImportedSession> {
+        Vec::new()
+    }
+
+    /// `thread/resume` turns for an imported session transcript.
+    fn read_session_turns(&self, _source: &Path) -> Result<Vec<Value>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Whether the CLI reads `@path` mentions in the prompt itself. Otherwise
+    /// `fileRef` input is inlined into the prompt.
+    fn supports_file_mentions(&self) -> bool {
+        false
+    }
+
+    /// File where the CLI records `session_id` when run in `cwd`.
+    fn session_transcript_path(&self, _cwd: &str, _session_id: &str) -> Option<PathBuf> {
+        None
+    }
+
+    /// The line to write to a running turn's stdin to steer it, for CLIs
+    /// that read follow-up input there (their `build_turn_command` pipes
+    /// stdin). Otherwise follow-up input waits for the CLI to exit and is
+    /// sent as another run within the same turn.
+    fn steer_stdin_line(&self, _input: &TurnInput) -> Option<String> {
+        None
+    }
+
+    /// The account the CLI is signed in to, from its credential files and
+    /// environment. `None` when it isn't signed in.
+    fn read_account(&self, _config: &CliSpawnConfig) -> Option<CliAccount> {
+        None
+    }
+
+    /// Files whose changes can mean a different account; watched for
+    /// `account/changed`.
+    fn credential_paths(&self, _config: &CliSpawnConfig) -> Vec<PathBuf> {
+        Vec::new()
+    }
+}
+
+/// The account an adapter CLI is signed in to, in the shape `account/read`
+/// returns for Codex.
+#[derive(serde::Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliAccount {
+    /// `oauth` for a subscription sign-in, `apikey` for an API key.
+    #[serde(rename = "type")]
+    pub(crate) account_type: String,
+    /// The auth method as the CLI names it, e.g. `oauth-personal`.
+    pub(crate) auth_mode: Option<String>,
+    pub(crate) email: Option<String>,
+    pub(crate) plan_type: Option<String>,
+}
+
+/// Reads a credential or config file as JSON; `None` when missing or
+/// unreadable.
+pub(crate) fn read_json_file(path: &Path) -> Option<Value> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Whether `name` is set in the workspace env or the monitor's own.
+pub(crate) fn env_var_set(config: &CliSpawnConfig, name: &str) -> bool {
+    config
+        .env
+        .get(name)
+        .is_some_and(|value| !value.trim().is_empty())
+        || std::env::var(name).is_ok_and(|value| !value.trim().is_empty())
+}
+
+fn account_value(account: Option<CliAccount>) -> Value {
+    account
+        .and_then(|account| serde_json::to_value(account).ok())
+        .unwrap_or(Value::Null)
+}
+
+/// Polls the profile's credential files while the session lives, emitting
+/// `account/changed` when any of them is written, created or removed.
+async fn watch_credentials<P: CliProfile>(
+    profile: Arc<P>,
+    config: CliSpawnConfig,
+    workspace_id: String,
+    emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
+    session: Weak<WorkspaceSession>,
+) {
+    let paths = profile.credential_paths(&config);
+    if paths.is_empty() {
+        return;
+    }
+    let stamp = |paths: &[PathBuf]| {
+        paths
+            .iter()
+            .map(|path| {
+                std::fs::metadata(path)
+                    .ok()
+                    .map(|metadata| (metadata.len(), metadata.modified().ok()))
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut last = stamp(&paths);
+    loop {
+        tokio::time::sleep(CREDENTIALS_POLL_INTERVAL).await;
+        if session.upgrade().is_none() {
+            break;
+        }
+        let current = stamp(&paths);
+        if current == last {
+            continue;
+        }
+        last = current;
+        let account = account_value(profile.read_account(&config));
+        emitter(AppServerEvent::new(
+            workspace_id.clone(),
+            AppEvent::AccountChanged {
+                workspace_id: workspace_id.clone(),
+                provider: profile.provider_name().to_string(),
+                account,
+            },
+        ));
+    }
+}
+
+/// How often an adapter CLI's credential files are checked for changes.
+const CREDENTIALS_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// How long `--help` may take when probing a CLI's capabilities.
+const CAPABILITY_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long to wait for stderr to drain after the CLI exits.
+const STDERR_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+/// Stderr lines added to the error of a turn whose CLI exits non-zero.
+const STDERR_TAIL_LINES: usize = 20;
+
+/// The app-wide adapter settings. Clones share them, so a settings change
+/// reaches running sessions.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct AdapterSettings {
+    /// `adapterTurnTimeoutSecs` (0 = no timeout).
+    turn_timeout_secs: Arc<AtomicU64>,
+    /// `claudeShowThinking`.
+    show_thinking: Arc<AtomicBool>,
+}
+
+impl AdapterSettings {
+    pub(crate) fn set_turn_timeout_secs(&self, secs: u64) {
+        self.turn_timeout_secs.store(secs, Ordering::Relaxed);
+    }
+
+    /// Whether Claude `thinking` blocks are streamed as
+    /// `item/reasoning/textDelta`.
+    pub(crate) fn set_show_thinking(&self, enabled: bool) {
+        self.show_thinking.store(enabled, Ordering::Relaxed);
+    }
+
+    pub(crate) fn show_thinking(&self) -> bool {
+        self.show_thinking.load(Ordering::Relaxed)
+    }
+
+    /// How long a turn may run: the workspace's `turnTimeoutSecs` when set,
+    /// otherwise the app default. Zero means no limit.
+    fn turn_timeout(&self, workspace_secs: Option<u64>) -> Option<Duration> {
+        let secs = workspace_secs.unwrap_or_else(|| self.turn_timeout_secs.load(Ordering::Relaxed));
+        (secs > 0).then(|| Duration::from_secs(secs))
+    }
+}
+
+type BackgroundCallbacks = Mutex<HashMap<String, BackgroundSender>>;
+
+/// Sends a thread's event to its background callback if one is registered,
+/// otherwise to the UI.
+async fn send_thread_event(
+    callbacks: &BackgroundCallbacks,
+    emitter: &(dyn Fn(AppServerEvent) + Send + Sync),
+    workspace_id: &str,
+    thread_id: &str,
+    event: Value,
+) {
+    {
+        let callbacks = callbacks.lock().await;
+        if let Some(tx) = callbacks.get(thread_id) {
+            tx.send(event);
+            return;
+        }
+    }
+    emitter(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: event,
+    });
+}
+
+fn emit_rate_limits(
+    emitter: &(dyn Fn(AppServerEvent) + Send + Sync),
+    rate_limits: &RateLimits,
+    workspace_id: &str,
+    provider: &str,
+    now: u64,
+) {
+    emitter(AppServerEvent::new(
+        workspace_id,
+        AppEvent::RateLimitsUpdated(rate_limits.snapshot(provider, now)),
+    ));
+}
+
+fn note_rate_limit(slot: &StdMutex<Option<RateLimitHit>>, text: &str) {
+    if let Some(hit) = detect_rate_limit(text, now_epoch()) {
+        if let Ok(mut slot) = slot.lock() {
+            *slot = Some(hit);
+        }
+    }
+}
+
+/// Logs a turn's stderr and streams it as `cli/stderr` events. Returns the
+/// last [`STDERR_TAIL_LINES`] lines once the CLI closes stderr.
+async fn read_turn_stderr(
+    stderr: ChildStderr,
+    workspace_id: String,
+    thread_id: String,
+    turn_id: String,
+    emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
+    redactor: Arc<SecretRedactor>,
+    rate_limit: Arc<StdMutex<Option<RateLimitHit>>>,
+) -> VecDeque<String> {
+    let mut tail = VecDeque::with_capacity(STDERR_TAIL_LINES);
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if !line.trim().is_empty() {
+            let line = strip_ansi(&line);
+            let line = redactor.redact(&line).into_owned();
+            note_rate_limit(&rate_limit, &line);
+            tracing::warn!(workspace_id = %workspace_id, stream = "stderr", "{line}");
+            emitter(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::CliStderr {
+                    thread_id: thread_id.clone(),
+                    turn_id: turn_id.clone(),
+                    message: line.clone(),
+                },
+            ));
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
+    }
+    tail
+}
+
+/// Error for a CLI that exited with `code`, followed by its last stderr
+/// lines.
+fn exit_error(provider: &str, code: i32, stderr_tail: &VecDeque<String>) -> String {
+    let mut error = format!("{provider} exited with code {code}");
+    if !stderr_tail.is_empty() {
+        error.push_str(":\n");
+        error.push_str(&Vec::from(stderr_tail.clone()).join("\n"));
+    }
+    error
+}
+
+fn exited_review_event(thread_id: &str, turn_id: &str, item_id: &str, review: &str) -> Value {
+    AppEvent::ItemCompleted {
+        thread_id: thread_id.to_string(),
+        turn_id: turn_id.to_string(),
+        item: ThreadItem::ExitedReviewMode {
+            id: item_id.to_string(),
+            review: review.to_string(),
+        },
+    }
+    .into_message()
+}
+
+/// Text streamed as `item/agentMessage/delta`, by item in the order the
+/// items started, so the turn can end with each message's full text.
+#[derive(Default)]
+struct MessageTexts {
+    items: Vec<(String, String)>,
+    /// Items the CLI completed itself.
+    completed: HashSet<String>,
+}
+
+impl MessageTexts {
+    fn observe(&mut self, event: &Value) {
+        let params = &event["params"];
+        match event.get("method").and_then(|m| m.as_str()) {
+            Some("item/agentMessage/delta") => {
+                let (Some(item_id), Some(delta)) =
+                    (params["itemId"].as_str(), params["delta"].as_str())
+                else {
+                    return;
+                };
+                match self.items.iter_mut().find(|(id, _)| id == item_id) {
+                    Some((_, text)) => text.push_str(delta),
+                    None => self.items.push((item_id.to_string(), delta.to_string())),
+                }
+            }
+            Some("item/completed") => {
+                if let Some(item_id) = params["item"]["id"].as_str() {
+                    self.completed.insert(item_id.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// `item/completed` for each streamed message the CLI didn't complete.
+    fn completed_events(&mut self, thread_id: &str, turn_id: &str) -> Vec<Value> {
+        std::mem::take(&mut self.items)
+            .into_iter()
+            .filter(|(item_id, _)| !self.completed.contains(item_id))
+            .map(|(id, text)| {
+                AppEvent::ItemCompleted {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.to_string(),
+                    item: ThreadItem::AgentMessage { id, text },
+                }
+                .into_message()
+            })
+            .collect()
+    }
+}
+
+/// The turn that `turn/start` requests for its thread steer, and the input
+/// they queued while it runs.
+#[derive(Default)]
+struct SteerQueue {
+    /// Thread, turn and epoch of the running turn.
+    target: Option<(String, String, u64)>,
+    stdin: Option<ChildStdin>,
+    pending: Vec<TurnInput>,
+}
+
+impl SteerQueue {
+    fn start(&mut self, thread_id: &str, turn_id: &str, epoch: u64) {
+        *self = Self {
+            target: Some((thread_id.to_string(), turn_id.to_string(), epoch)),
+            ..Self::default()
+        };
+    }
+
+    /// The turn id a `turn/start` for `thread_id` steers, if its turn runs.
+    fn running_turn(&self, thread_id: &str) -> Option<String> {
+        self.target
+            .as_ref()
+            .filter(|(running, _, _)| running == thread_id)
+            .map(|(_, turn_id, _)| turn_id.clone())
+    }
+
+    /// Called when the CLI run of the turn started at `epoch` exits. Returns
+    /// the queued input to run next when the turn should go on; otherwise
+    /// the turn stops accepting follow-up input.
+    fn finish_run(&mut self, epoch: u64, go_on: bool) -> Option<TurnInput> {
+        if self.target.as_ref().is_none_or(|target| target.2 != epoch) {
+            return None;
+        }
+        self.stdin = None;
+        if go_on && !self.pending.is_empty() {
+            return Some(merge_follow_ups(std::mem::take(&mut self.pending)));
+        }
+        *self = Self::default();
+        None
+    }
+}
+
+fn merge_follow_ups(inputs: Vec<TurnInput>) -> TurnInput {
+    let mut merged = TurnInput::default();
+    let mut texts = Vec::new();
+    for input in inputs {
+        if !input.text.is_empty() {
+            texts.push(input.text);
+        }
+        merged.images.extend(input.images);
+        merged.files.extend(input.files);
+    }
+    merged.text = texts.join("\n\n");
+    merged
+}
+
+/// Adds the cost and duration that a steered turn's earlier CLI runs
+/// reported to the `turn/completed` of its last run.
+fn add_earlier_runs(completed: &mut Value, earlier: &Value) {
+    for key in ["costUsd", "durationMs"] {
+        let Some(extra) = earlier["params"][key].as_f64() else {
+            continue;
+        };
+        let total = completed["params"][key].as_f64().unwrap_or(0.0) + extra;
+        completed["params"][key] = if key == "durationMs" {
+            json!(total as u64)
+        } else {
+            json!(total)
+        };
+    }
+}
+
+/// Kills a turn's CLI along with what it started on the workspace's
+/// execution target.
+async fn kill_turn_child(child: &mut Child, config: &CliSpawnConfig, workspace_id: &str) {
+    kill_child_process_tree(child).await;
+    let run_name = exec_target::run_name(workspace_id);
+    config.target.stop(&run_name).await;
+}
+
+/// What a turn needs to start the CLI again when it is retried.
+struct TurnLaunch<P: CliProfile> {
+    profile: Arc<P>,
+    config: CliSpawnConfig,
+    workspace_id: String,
+    thread_id: String,
+    cwd: String,
+    /// The session the turn continues. Retries start from here again rather
+    /// than from the session the failed attempt created.
+    session_id: Option<String>,
+    input: TurnInput,
+    effort: Option<String>,
+    capabilities: Arc<CliCapabilities>,
+    active_child: Arc<Mutex<Option<Child>>>,
+    steer: Arc<Mutex<SteerQueue>>,
+    redactor: Arc<SecretRedactor>,
+}
+
+impl<P: CliProfile> TurnLaunch<P> {
+    async fn spawn(&self) -> Result<(ChildStdout, Option<ChildStderr>), String> {
+        let command = self.profile.build_turn_command(
+            &self.config,
+            self.session_id.as_deref(),
+            &self.input,
+            &self.cwd,
+            self.effort.as_deref(),
+            &self.capabilities,
+        )?;
+        let run_name = exec_target::run_name(&self.workspace_id);
+        self.config.target.prepare(&run_name).await;
+        let mut command = self.config.target.wrap(command, &self.cwd, &run_name)?;
+        tracing::info!(
+            workspace_id = %self.workspace_id,
+            thread_id = %self.thread_id,
+            "spawning {}",
+            self.redactor
+                .redact(&logging_core::command_line_for_log(command.as_std()))
+        );
+        let mut child = command.spawn().map_err(|e| {
+            tracing::error!(workspace_id = %self.workspace_id, "spawn failed: {e}");
+            format!("Failed to spawn CLI: {e}")
+        })?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or("Failed to capture CLI stdout")?;
+        let stderr = child.stderr.take();
+        self.steer.lock().await.stdin = child.stdin.take();
+        *self.active_child.lock().await = Some(child);
+        Ok((stdout, stderr))
+    }
 }
 
 pub(crate) struct GenericAdapterSession<P: CliProfile> {
@@ -88,11 +842,21 @@ pub(crate) struct GenericAdapterSession<P: CliProfile> {
     workspace_id: String,
     cwd: String,
     config: CliSpawnConfig,
+    /// The workspace's `reasoningEffort`, which wins over the turn's `effort`.
+    reasoning_effort: Option<String>,
+    /// The workspace's `turnTimeoutSecs`, which wins over the app default.
+    turn_timeout_secs: Option<u64>,
+    capabilities: Arc<CliCapabilities>,
     thread_store_path: PathBuf,
     thread_store: Arc<Mutex<ThreadStore>>,
     active_child: Arc<Mutex<Option<Child>>>,
+    /// Bumped by every turn start, interrupt and kill, so a turn waiting to
+    /// retry can tell it has been cancelled.
+    turn_epoch: Arc<AtomicU64>,
+    steer: Arc<Mutex<SteerQueue>>,
     event_emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
-    background_callbacks: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>,
+    background_callbacks: Arc<Mutex<HashMap<String, BackgroundSender>>>,
+    redactor: Arc<SecretRedactor>,
 }
 
 impl<P: CliProfile> GenericAdapterSession<P> {
@@ -101,38 +865,62 @@ impl<P: CliProfile> GenericAdapterSession<P> {
         entry: &WorkspaceEntry,
         config: CliSpawnConfig,
         event_emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
-        background_callbacks: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>,
+        background_callbacks: Arc<Mutex<HashMap<String, BackgroundSender>>>,
     ) -> Self {
         let store_path = thread_store_path(&entry.id);
-        let store = ThreadStore::load(&store_path);
+        let store = open_thread_store(&store_path);
+        let secrets = resolve_workspace_env(&config.env)
+            .map(|resolved| workspace_secret_values(&config.env, &resolved))
+            .unwrap_or_default();
+        let redactor = SecretRedactor::new(secrets, config.services.redaction_patterns.clone());
         Self {
             profile: Arc::new(profile),
             workspace_id: entry.id.clone(),
             cwd: entry.path.clone(),
             config,
+            reasoning_effort: entry.settings.reasoning_effort.clone(),
+            turn_timeout_secs: entry.settings.turn_timeout_secs,
+            capabilities: Arc::new(CliCapabilities::default()),
             thread_store_path: store_path,
-            thread_store: Arc::new(Mutex::new(store)),
+            thread_store: store,
             active_child: Arc::new(Mutex::new(None)),
+            turn_epoch: Arc::new(AtomicU64::new(0)),
+            steer: Arc::new(Mutex::new(SteerQueue::default())),
             event_emitter,
             background_callbacks,
+            redactor: Arc::new(redactor),
         }
     }
 
-    async fn handle_thread_start(&self) -> Result<Value, String> {
+    pub(crate) fn with_capabilities(mut self, capabilities: CliCapabilities) -> Self {
+        self.capabilities = Arc::new(capabilities);
+        self
+    }
+
+    async fn create_thread(&self, name: Option<String>) -> Result<String, String> {
         let thread_id = uuid::Uuid::new_v4().to_string();
         let now = now_epoch();
         let meta = ThreadMetadata {
             cli_session_id: None,
-            name: None,
+            name,
             created_at: now,
             updated_at: now,
             archived: false,
+            source_path: None,
+            transcript_path: None,
+            compacted_summary: None,
+            last_turn_failure: None,
         };
         {
             let mut store = self.thread_store.lock().await;
             store.threads.insert(thread_id.clone(), meta);
             store.save(&self.thread_store_path)?;
         }
+        Ok(thread_id)
+    }
+
+    async fn handle_thread_start(&self) -> Result<Value, String> {
+        let thread_id = self.create_thread(None).await?;
         Ok(json!({
             "result": {
                 "threadId": thread_id,
@@ -146,24 +934,96 @@ impl<P: CliProfile> GenericAdapterSession<P> {
             .get("threadId")
             .and_then(|v| v.as_str())
             .ok_or("missing threadId")?;
-        let store = self.thread_store.lock().await;
-        if !store.threads.contains_key(thread_id) {
-            return Err("thread not found".to_string());
+        let (source_path, compacted_summary) = {
+            let store = self.thread_store.lock().await;
+            let meta = store.threads.get(thread_id).ok_or("thread not found")?;
+            (meta.source_path.clone(), meta.compacted_summary.clone())
+        };
+        let mut thread = json!({ "id": thread_id });
+        if let Some(summary) = compacted_summary {
+            thread["turns"] = json!([{
+                "id": "turn-0",
+                "items": [{ "id": "compacted-summary", "type": "agentMessage", "text": summary }],
+            }]);
+            self.redactor.redact_value(&mut thread);
+        } else if let Some(source_path) = source_path {
+            let profile = Arc::clone(&self.profile);
+            let turns =
+                tokio::task::spawn_blocking(move || profile.read_session_turns(&source_path))
+                    .await
+                    .map_err(|err| err.to_string())??;
+            thread["turns"] = Value::Array(turns);
+            self.redactor.redact_value(&mut thread);
         }
         Ok(json!({
             "result": {
                 "threadId": thread_id,
-                "thread": { "id": thread_id }
+                "thread": thread
             }
         }))
     }
 
-    async fn handle_thread_list(&self) -> Result<Value, String> {
+    /// Adds threads for CLI sessions started outside the monitor so their
+    /// history shows up in `thread/list`.
+    async fn import_cli_sessions(&self) {
+        let known: HashSet<String> = {
+            let store = self.thread_store.lock().await;
+            store
+                .threads
+                .values()
+                .filter_map(|meta| meta.cli_session_id.clone())
+                .collect()
+        };
+        let profile = Arc::clone(&self.profile);
+        let cwd = self.cwd.clone();
+        let Ok(sessions) =
+            tokio::task::spawn_blocking(move || profile.discover_sessions(&cwd, &known)).await
+        else {
+            return;
+        };
+        if sessions.is_empty() {
+            return;
+        }
+        let mut store = self.thread_store.lock().await;
+        for session in sessions {
+            let already_imported = store
+                .threads
+                .values()
+                .any(|meta| meta.cli_session_id.as_deref() == Some(&session.cli_session_id));
+            if already_imported {
+                continue;
+            }
+            store.threads.insert(
+                uuid::Uuid::new_v4().to_string(),
+                ThreadMetadata {
+                    cli_session_id: Some(session.cli_session_id),
+                    name: session.name,
+                    created_at: session.created_at,
+                    updated_at: session.updated_at,
+                    archived: false,
+                    source_path: Some(session.source_path),
+                    transcript_path: None,
+                    compacted_summary: None,
+                    last_turn_failure: None,
+                },
+            );
+        }
+        if let Err(err) = store.save(&self.thread_store_path) {
+            tracing::warn!(workspace_id = %self.workspace_id, "failed to save imported threads: {err}");
+        }
+    }
+
+    async fn handle_thread_list(&self, params: &Value) -> Result<Value, String> {
+        let archived = params
+            .get("archived")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        self.import_cli_sessions().await;
         let store = self.thread_store.lock().await;
         let threads: Vec<Value> = store
             .threads
             .iter()
-            .filter(|(_, meta)| !meta.archived)
+            .filter(|(_, meta)| meta.archived == archived)
             .map(|(id, meta)| {
                 json!({
                     "id": id,
@@ -171,6 +1031,7 @@ impl<P: CliProfile> GenericAdapterSession<P> {
                     "createdAt": meta.created_at,
                     "updatedAt": meta.updated_at,
                     "archived": meta.archived,
+                    "lastTurnFailure": meta.last_turn_failure,
                 })
             })
             .collect();
@@ -196,6 +1057,40 @@ impl<P: CliProfile> GenericAdapterSession<P> {
         Ok(json!({ "result": {} }))
     }
 
+    async fn handle_thread_unarchive(&self, params: &Value) -> Result<Value, String> {
+        let thread_id = params
+            .get("threadId")
+            .and_then(|v| v.as_str())
+            .ok_or("missing threadId")?;
+        let mut store = self.thread_store.lock().await;
+        let meta = store.threads.get_mut(thread_id).ok_or("thread not found")?;
+        meta.archived = false;
+        meta.updated_at = now_epoch();
+        store.save(&self.thread_store_path)?;
+        Ok(json!({ "result": { "thread": { "id": thread_id } } }))
+    }
+
+    async fn handle_thread_delete(&self, params: &Value) -> Result<Value, String> {
+        let thread_id = params
+            .get("threadId")
+            .and_then(|v| v.as_str())
+            .ok_or("missing threadId")?;
+        let meta = {
+            let mut store = self.thread_store.lock().await;
+            let meta = store.threads.remove(thread_id).ok_or("thread not found")?;
+            store.save(&self.thread_store_path)?;
+            meta
+        };
+        let workspace_id = self.workspace_id.clone();
+        let thread_id_owned = thread_id.to_string();
+        tokio::task::spawn_blocking(move || {
+            purge_thread_files(&workspace_id, &thread_id_owned, &meta)
+        })
+        .await
+        .map_err(|err| err.to_string())?;
+        Ok(json!({ "result": {} }))
+    }
+
     async fn handle_thread_name_set(&self, params: &Value) -> Result<Value, String> {
         let thread_id = params
             .get("threadId")
@@ -233,6 +1128,10 @@ impl<P: CliProfile> GenericAdapterSession<P> {
             created_at: now,
             updated_at: now,
             archived: false,
+            source_path: None,
+            transcript_path: None,
+            compacted_summary: None,
+            last_turn_failure: None,
         };
         store.threads.insert(new_id.clone(), meta);
         store.save(&self.thread_store_path)?;
@@ -244,141 +1143,793 @@ impl<P: CliProfile> GenericAdapterSession<P> {
         }))
     }
 
+    async fn emit_thread_event(&self, thread_id: &str, event: Value) {
+        send_thread_event(
+            &self.background_callbacks,
+            &*self.event_emitter,
+            &self.workspace_id,
+            thread_id,
+            event,
+        )
+        .await;
+    }
+
+    /// Resolves `fileRef` input. CLIs with native `@path` mentions keep the
+    /// paths; for the rest the file contents are inlined ahead of the prompt.
+    /// Emits `item/context` listing what was attached.
+    async fn attach_file_context(
+        &self,
+        thread_id: &str,
+        turn_id: &str,
+        input: &mut TurnInput,
+    ) -> Result<(), String> {
+        let (mode, files) = if self.profile.supports_file_mentions() {
+            let files = input
+                .files
+                .iter()
+                .map(|path| json!({ "path": path }))
+                .collect::<Vec<_>>();
+            ("mention", files)
+        } else {
+            let root = PathBuf::from(&self.cwd);
+            let paths = std::mem::take(&mut input.files);
+            let context = tokio::task::spawn_blocking(move || build_file_context(&root, &paths))
+                .await
+                .map_err(|err| err.to_string())?;
+            input.text = format!("{}{}", context.prompt, input.text);
+            ("inline", context.files)
+        };
+        let event = AppEvent::ItemContext {
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+            item: ThreadItem::Context {
+                id: format!("context_{turn_id}"),
+                mode: mode.to_string(),
+                files,
+            },
+        };
+        self.emit_thread_event(thread_id, event.into_message())
+            .await;
+        Ok(())
+    }
+
     async fn handle_turn_start(&self, params: &Value) -> Result<Value, String> {
         let thread_id = params
             .get("threadId")
             .and_then(|v| v.as_str())
             .ok_or("missing threadId")?
             .to_string();
-        let prompt = params
-            .get("input")
+        let mut input = parse_turn_input(params.get("input"))?;
+        if let Some(missing) = input.images.iter().find(|path| !path.is_file()) {
+            return Err(format!("Image not found: {}", missing.display()));
+        }
+        if let Some(turn_id) = self.steer_running_turn(&thread_id, &mut input).await? {
+            return Ok(json!({
+                "result": {
+                    "turn": { "id": turn_id },
+                    "threadId": thread_id
+                }
+            }));
+        }
+        let turn_id = uuid::Uuid::new_v4().to_string();
+        if !input.files.is_empty() {
+            self.attach_file_context(&thread_id, &turn_id, &mut input)
+                .await?;
+        }
+        let effort = params.get("effort").and_then(Value::as_str);
+        self.start_turn(thread_id, turn_id, input, effort, None)
+            .await
+    }
+
+    /// Steers the thread's running turn with `input`, when it has one: the
+    /// input goes to the CLI's stdin if the CLI reads follow-ups there, and
+    /// is otherwise queued and sent as a further run of the CLI within the
+    /// same turn once the current run exits. Returns the steered turn's id.
+    async fn steer_running_turn(
+        &self,
+        thread_id: &str,
+        input: &mut TurnInput,
+    ) -> Result<Option<String>, String> {
+        let Some(turn_id) = self.steer.lock().await.running_turn(thread_id) else {
+            return Ok(None);
+        };
+        if !input.files.is_empty() {
+            self.attach_file_context(thread_id, &turn_id, input).await?;
+        }
+        let mut steer = self.steer.lock().await;
+        if steer.running_turn(thread_id).as_deref() != Some(turn_id.as_str()) {
+            return Ok(None);
+        }
+        if let (Some(line), Some(stdin)) =
+            (self.profile.steer_stdin_line(input), steer.stdin.as_mut())
+        {
+            let written = stdin.write_all(format!("{line}\n").as_bytes()).await;
+            match written.and(stdin.flush().await) {
+                Ok(()) => return Ok(Some(turn_id)),
+                Err(err) => {
+                    tracing::warn!(
+                        workspace_id = %self.workspace_id,
+                        "steering through stdin failed: {err}"
+                    );
+                    steer.stdin = None;
+                }
+            }
+        }
+        steer.pending.push(std::mem::take(input));
+        Ok(Some(turn_id))
+    }
+
+    /// Reviews changes by running a turn with the diff in the prompt. The
+    /// turn is wrapped in `enteredReviewMode` / `exitedReviewMode` items, the
+    /// latter carrying the CLI's reply as the review. A `detached` review
+    /// runs in a new thread.
+    async fn handle_review_start(&self, params: &Value) -> Result<Value, String> {
+        let thread_id = params
+            .get("threadId")
             .and_then(|v| v.as_str())
-            .ok_or("missing input")?
+            .ok_or("missing threadId")?
             .to_string();
+        let target = ReviewTarget::parse(params.get("target").unwrap_or(&Value::Null))?;
+        let prompt = review::review_prompt(&self.cwd, &target).await?;
+        let detached = params.get("delivery").and_then(Value::as_str) == Some("detached");
+        let thread_id = if detached {
+            self.create_thread(Some(format!("Review: {}", target.hint())))
+                .await?
+        } else {
+            thread_id
+        };
+        let input = TurnInput {
+            text: prompt,
+            ..TurnInput::default()
+        };
         let turn_id = uuid::Uuid::new_v4().to_string();
+        let mut response = self
+            .start_turn(thread_id.clone(), turn_id, input, None, Some(target))
+            .await?;
+        response["result"]["reviewThreadId"] = json!(thread_id);
+        Ok(response)
+    }
 
-        let session_id = {
+    /// Has the CLI summarize the thread's session, then starts the thread
+    /// over from that summary: the session id and transcript are dropped and
+    /// the summary is sent ahead of the next prompt. Runs in the background
+    /// as a `contextCompaction` item and ends with `thread/compacted`, which
+    /// carries token estimates from the transcript and summary sizes.
+    async fn handle_thread_compact(&self, params: &Value) -> Result<Value, String> {
+        let thread_id = params
+            .get("threadId")
+            .and_then(|v| v.as_str())
+            .ok_or("missing threadId")?
+            .to_string();
+        let meta = {
             let store = self.thread_store.lock().await;
             store
                 .threads
                 .get(&thread_id)
-                .and_then(|meta| meta.cli_session_id.clone())
+                .cloned()
+                .ok_or("thread not found")?
+        };
+        let session_id = meta
+            .cli_session_id
+            .clone()
+            .ok_or("Nothing to compact: the thread has no CLI session yet")?;
+        if self.active_child.lock().await.is_some() {
+            return Err("Wait for the running turn to finish before compacting".to_string());
+        }
+        let transcript = meta.transcript_path.as_ref().or(meta.source_path.as_ref());
+        let tokens_before = match transcript {
+            Some(path) => tokio::fs::metadata(path)
+                .await
+                .ok()
+                .map(|metadata| estimate_tokens(metadata.len())),
+            None => None,
         };
 
-        {
-            let mut guard = self.active_child.lock().await;
-            if let Some(mut prev) = guard.take() {
-                kill_child_process_tree(&mut prev).await;
+        let epoch = self.turn_epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        let launch = TurnLaunch {
+            profile: self.profile.clone(),
+            config: self.config.clone(),
+            workspace_id: self.workspace_id.clone(),
+            thread_id: thread_id.clone(),
+            cwd: self.cwd.clone(),
+            session_id: Some(session_id),
+            input: TurnInput {
+                text: COMPACT_PROMPT.to_string(),
+                ..TurnInput::default()
+            },
+            effort: None,
+            capabilities: self.capabilities.clone(),
+            active_child: self.active_child.clone(),
+            // Compaction isn't a turn, so nothing can steer it.
+            steer: Arc::new(Mutex::new(SteerQueue::default())),
+            redactor: self.redactor.clone(),
+        };
+        let (stdout, stderr) = launch.spawn().await?;
+        let item_id = format!("compact_{}", uuid::Uuid::new_v4());
+        self.emit_thread_event(
+            &thread_id,
+            compaction_item_event(false, &thread_id, &item_id, "inProgress", None),
+        )
+        .await;
+
+        let emitter = self.event_emitter.clone();
+        let ws_id = self.workspace_id.clone();
+        let store = self.thread_store.clone();
+        let store_path = self.thread_store_path.clone();
+        let bg_callbacks = self.background_callbacks.clone();
+        let redactor = self.redactor.clone();
+        let turn_epoch = self.turn_epoch.clone();
+
+        tokio::spawn(async move {
+            let stderr_task = stderr.map(|stderr| {
+                tokio::spawn(read_turn_stderr(
+                    stderr,
+                    ws_id.clone(),
+                    thread_id.clone(),
+                    item_id.clone(),
+                    emitter.clone(),
+                    redactor.clone(),
+                    Arc::new(StdMutex::new(None)),
+                ))
+            });
+            let mut summary = String::new();
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let Some(event) = launch
+                    .profile
+                    .parse_stream_line(&line, &thread_id, &item_id)
+                else {
+                    continue;
+                };
+                if event.get("method").and_then(|m| m.as_str()) == Some("item/agentMessage/delta") {
+                    if let Some(delta) = event["params"]["delta"].as_str() {
+                        summary.push_str(delta);
+                    }
+                }
             }
-        }
+            let exit_code = if turn_epoch.load(Ordering::SeqCst) != epoch {
+                None
+            } else {
+                match launch.active_child.lock().await.take() {
+                    Some(mut child) => child.wait().await.ok().and_then(|status| status.code()),
+                    None => None,
+                }
+            };
+            let stderr_tail = match stderr_task {
+                Some(task) => tokio::time::timeout(STDERR_DRAIN_TIMEOUT, task)
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .unwrap_or_default(),
+                None => VecDeque::new(),
+            };
 
-        let mut command = self.profile.build_turn_command(
-            &self.config,
-            session_id.as_deref(),
-            &prompt,
-            &self.cwd,
-            params,
-        )?;
-        let mut child = command
-            .spawn()
-            .map_err(|e| format!("Failed to spawn CLI: {e}"))?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or("Failed to capture CLI stdout")?;
-        let stderr = child.stderr.take();
+            let summary = strip_ansi(summary.trim()).into_owned();
+            let error = match exit_code {
+                None => Some("Compaction was interrupted".to_string()),
+                Some(0) if summary.is_empty() => Some("The CLI returned no summary".to_string()),
+                Some(0) => None,
+                Some(code) => Some(exit_error(
+                    launch.profile.provider_name(),
+                    code,
+                    &stderr_tail,
+                )),
+            };
+            if let Some(error) = error {
+                tracing::warn!(workspace_id = %ws_id, thread_id = %thread_id, "compaction failed: {error}");
+                let event = compaction_item_event(
+                    true,
+                    &thread_id,
+                    &item_id,
+                    "failed",
+                    Some(error.as_str()),
+                );
+                send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id, event).await;
+                return;
+            }
+
+            let replaced = {
+                let mut store = store.lock().await;
+                let replaced = store.threads.get_mut(&thread_id).map(|meta| {
+                    let replaced = meta.clone();
+                    meta.cli_session_id = None;
+                    meta.source_path = None;
+                    meta.transcript_path = None;
+                    meta.compacted_summary = Some(summary.clone());
+                    meta.updated_at = now_epoch();
+                    replaced
+                });
+                if let Err(err) = store.save(&store_path) {
+                    tracing::warn!(workspace_id = %ws_id, "failed to save compacted thread: {err}");
+                }
+                replaced
+            };
+            if let Some(replaced) = replaced {
+                let workspace_id = ws_id.clone();
+                let thread_id = thread_id.clone();
+                let _ = tokio::task::spawn_blocking(move || {
+                    remove_session_transcripts(&workspace_id, &thread_id, &replaced)
+                })
+                .await;
+            }
+
+            let event = compaction_item_event(true, &thread_id, &item_id, "completed", None);
+            send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id, event).await;
+            let event = AppEvent::ThreadCompacted {
+                thread_id: thread_id.clone(),
+                turn_id: item_id,
+                tokens_before,
+                tokens_after: estimate_tokens(summary.len() as u64),
+            }
+            .into_message();
+            send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id, event).await;
+        });
+
+        Ok(json!({ "result": {} }))
+    }
+
+    async fn start_turn(
+        &self,
+        thread_id: String,
+        turn_id: String,
+        mut input: TurnInput,
+        effort: Option<&str>,
+        review: Option<ReviewTarget>,
+    ) -> Result<Value, String> {
+        let (session_id, compacted_summary) = {
+            let store = self.thread_store.lock().await;
+            store
+                .threads
+                .get(&thread_id)
+                .map(|meta| (meta.cli_session_id.clone(), meta.compacted_summary.clone()))
+                .unwrap_or_default()
+        };
+        if let (None, Some(summary)) = (&session_id, compacted_summary) {
+            input.text = prompt_with_summary(&summary, &input.text);
+        }
 
         {
             let mut guard = self.active_child.lock().await;
-            *guard = Some(child);
+            if let Some(mut prev) = guard.take() {
+                kill_turn_child(&mut prev, &self.config, &self.workspace_id).await;
+            }
         }
 
-        let profile = self.profile.clone();
+        let effort = self
+            .reasoning_effort
+            .as_deref()
+            .or(effort)
+            .map(str::to_string);
+        let epoch = self.turn_epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        self.steer.lock().await.start(&thread_id, &turn_id, epoch);
+        let mut launch = TurnLaunch {
+            profile: self.profile.clone(),
+            config: self.config.clone(),
+            workspace_id: self.workspace_id.clone(),
+            thread_id: thread_id.clone(),
+            cwd: self.cwd.clone(),
+            session_id,
+            input,
+            effort,
+            capabilities: self.capabilities.clone(),
+            active_child: self.active_child.clone(),
+            steer: self.steer.clone(),
+            redactor: self.redactor.clone(),
+        };
+        let first_attempt = launch.spawn().await?;
+        let review_item_id = match review {
+            Some(target) => {
+                let item_id = format!("review_{turn_id}");
+                let event = AppEvent::ItemStarted {
+                    thread_id: thread_id.clone(),
+                    turn_id: turn_id.clone(),
+                    item: ThreadItem::EnteredReviewMode {
+                        id: item_id.clone(),
+                        review: target.hint(),
+                    },
+                }
+                .into_message();
+                self.emit_thread_event(&thread_id, event).await;
+                Some(item_id)
+            }
+            None => None,
+        };
+
         let emitter = self.event_emitter.clone();
         let ws_id = self.workspace_id.clone();
         let store = self.thread_store.clone();
         let store_path = self.thread_store_path.clone();
-        let active_child = self.active_child.clone();
         let bg_callbacks = self.background_callbacks.clone();
-        let thread_id_bg = thread_id.clone();
         let turn_id_bg = turn_id.clone();
+        let redactor = self.redactor.clone();
+        let turn_epoch = self.turn_epoch.clone();
+        let rate_limits = self.config.services.rate_limits.clone();
+        let timeout = self
+            .config
+            .services
+            .adapter_settings
+            .turn_timeout(self.turn_timeout_secs);
+        let deadline = timeout.map(|timeout| tokio::time::Instant::now() + timeout);
 
         tokio::spawn(async move {
-            let mut lines = BufReader::new(stdout).lines();
-            let mut got_result = false;
+            let thread_id_bg = launch.thread_id.clone();
+            let provider = launch.profile.provider_name().to_string();
+            let mut next_attempt = Some(first_attempt);
+            let mut retries = 0;
+            let mut review_text = String::new();
+            let mut messages = MessageTexts::default();
+            let mut earlier_runs: Option<Value> = None;
+            loop {
+                let (stdout, stderr) = match next_attempt.take() {
+                    Some(attempt) => attempt,
+                    None => match launch.spawn().await {
+                        Ok(attempt) => attempt,
+                        Err(err) => {
+                            for event in messages.completed_events(&thread_id_bg, &turn_id_bg) {
+                                send_thread_event(
+                                    &bg_callbacks,
+                                    &*emitter,
+                                    &ws_id,
+                                    &thread_id_bg,
+                                    event,
+                                )
+                                .await;
+                            }
+                            if let Some(item_id) = &review_item_id {
+                                let event = exited_review_event(
+                                    &thread_id_bg,
+                                    &turn_id_bg,
+                                    item_id,
+                                    &format!("Review failed: {err}"),
+                                );
+                                send_thread_event(
+                                    &bg_callbacks,
+                                    &*emitter,
+                                    &ws_id,
+                                    &thread_id_bg,
+                                    event,
+                                )
+                                .await;
+                            }
+                            let event = AppEvent::TurnCompleted {
+                                thread_id: thread_id_bg.clone(),
+                                turn_id: turn_id_bg.clone(),
+                                cost_usd: None,
+                                duration_ms: None,
+                                exit_code: None,
+                                error: Some(err),
+                            }
+                            .into_message();
+                            send_thread_event(
+                                &bg_callbacks,
+                                &*emitter,
+                                &ws_id,
+                                &thread_id_bg,
+                                event,
+                            )
+                            .await;
+                            break;
+                        }
+                    },
+                };
+                review_text.clear();
+                let rate_limit = Arc::new(StdMutex::new(None));
+                let stderr_task = stderr.map(|stderr| {
+                    tokio::spawn(read_turn_stderr(
+                        stderr,
+                        ws_id.clone(),
+                        thread_id_bg.clone(),
+                        turn_id_bg.clone(),
+                        emitter.clone(),
+                        redactor.clone(),
+                        rate_limit.clone(),
+                    ))
+                });
 
-            while let Ok(Some(line)) = lines.next_line().await {
-                if let Some(sid) = profile.extract_session_id(&line) {
-                    let mut s = store.lock().await;
-                    if let Some(meta) = s.threads.get_mut(&thread_id_bg) {
-                        meta.cli_session_id = Some(sid);
-                        meta.updated_at = now_epoch();
-                        if let Err(e) = s.save(&store_path) {
-                            eprintln!("adapter: failed to persist session id: {e}");
+                let mut lines = BufReader::new(stdout).lines();
+                let mut delta_redactor = DeltaRedactor::new(redactor.clone());
+                let mut completed_event: Option<Value> = None;
+                let mut stream_failed = false;
+                let mut timed_out = false;
+                loop {
+                    let next = match deadline {
+                        Some(deadline) => {
+                            match tokio::time::timeout_at(deadline, lines.next_line()).await {
+                                Ok(next) => next,
+                                Err(_) => {
+                                    timed_out = true;
+                                    break;
+                                }
+                            }
+                        }
+                        None => lines.next_line().await,
+                    };
+                    let Ok(Some(line)) = next else {
+                        break;
+                    };
+                    if let Some(sid) = launch.profile.extract_session_id(&line) {
+                        let mut s = store.lock().await;
+                        if let Some(meta) = s.threads.get_mut(&thread_id_bg) {
+                            meta.transcript_path =
+                                launch.profile.session_transcript_path(&launch.cwd, &sid);
+                            meta.cli_session_id = Some(sid);
+                            meta.compacted_summary = None;
+                            meta.updated_at = now_epoch();
+                            if let Err(e) = s.save(&store_path) {
+                                eprintln!("adapter: failed to persist session id: {e}");
+                            }
                         }
                     }
-                }
 
-                if let Some(event) =
-                    profile.parse_stream_line(&line, &thread_id_bg, &turn_id_bg)
-                {
-                    if event.get("method").and_then(|m| m.as_str()) == Some("turn/completed") {
-                        got_result = true;
+                    if let Some(text) = stream_error_text(&line) {
+                        stream_failed = true;
+                        note_rate_limit(&rate_limit, &redactor.redact(&strip_ansi(&text)));
+                    }
+
+                    if let Some(mut event) =
+                        launch
+                            .profile
+                            .parse_stream_line(&line, &thread_id_bg, &turn_id_bg)
+                    {
+                        strip_ansi_value(&mut event);
+                        let mut ready = delta_redactor.redact_event(&mut event);
+                        // Held until the CLI exits so it can carry the exit code.
+                        if event.get("method").and_then(|m| m.as_str()) == Some("turn/completed") {
+                            completed_event = Some(event);
+                        } else {
+                            ready.push(event);
+                        }
+                        for event in ready {
+                            messages.observe(&event);
+                            if review_item_id.is_some()
+                                && event["method"].as_str() == Some("item/agentMessage/delta")
+                            {
+                                if let Some(delta) = event["params"]["delta"].as_str() {
+                                    review_text.push_str(delta);
+                                }
+                            }
+                            send_thread_event(
+                                &bg_callbacks,
+                                &*emitter,
+                                &ws_id,
+                                &thread_id_bg,
+                                event,
+                            )
+                            .await;
+                        }
                     }
-                    let mut sent_to_background = false;
+                }
+                for event in delta_redactor.flush() {
+                    messages.observe(&event);
+                    if review_item_id.is_some()
+                        && event["method"].as_str() == Some("item/agentMessage/delta")
                     {
-                        let callbacks = bg_callbacks.lock().await;
-                        if let Some(tx) = callbacks.get(&thread_id_bg) {
-                            let _ = tx.send(event.clone());
-                            sent_to_background = true;
+                        if let Some(delta) = event["params"]["delta"].as_str() {
+                            review_text.push_str(delta);
                         }
                     }
-                    if !sent_to_background {
-                        (emitter)(AppServerEvent {
-                            workspace_id: ws_id.clone(),
-                            message: event,
-                        });
+                    send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id_bg, event).await;
+                }
+
+                if timed_out && turn_epoch.load(Ordering::SeqCst) == epoch {
+                    if let Some(mut child) = launch.active_child.lock().await.take() {
+                        kill_turn_child(&mut child, &launch.config, &launch.workspace_id).await;
                     }
                 }
-            }
+                // A newer turn owns the child slot; an interrupt has emptied it.
+                let superseded = turn_epoch.load(Ordering::SeqCst) != epoch;
+                let (exit_code, interrupted) = if superseded {
+                    (None, true)
+                } else {
+                    let mut guard = launch.active_child.lock().await;
+                    match guard.take() {
+                        Some(mut child) => (
+                            child.wait().await.ok().and_then(|status| status.code()),
+                            false,
+                        ),
+                        None => (None, true),
+                    }
+                };
+                let stderr_tail = match stderr_task {
+                    Some(task) => tokio::time::timeout(STDERR_DRAIN_TIMEOUT, task)
+                        .await
+                        .ok()
+                        .and_then(Result::ok)
+                        .unwrap_or_default(),
+                    None => VecDeque::new(),
+                };
 
-            if !got_result {
-                let fallback_event = json!({
-                    "method": "turn/completed",
-                    "params": {
-                        "threadId": thread_id_bg,
-                        "turnId": turn_id_bg
+                let failed = stream_failed || exit_code != Some(0);
+                let hit = rate_limit.lock().ok().and_then(|mut hit| hit.take());
+                let now = now_epoch();
+                match &hit {
+                    Some(hit) if failed => {
+                        rate_limits.record(&provider, hit.clone(), now);
+                        emit_rate_limits(&*emitter, &rate_limits, &ws_id, &provider, now);
                     }
+                    _ if !failed && rate_limits.clear(&provider) => {
+                        emit_rate_limits(&*emitter, &rate_limits, &ws_id, &provider, now);
+                    }
+                    _ => {}
+                }
+
+                let retry = hit
+                    .filter(|_| failed && !interrupted && retries < rate_limits.max_retries())
+                    .and_then(|hit| {
+                        rate_limits::retry_delay(&hit, retries + 1, now).map(|delay| (hit, delay))
+                    });
+                if let Some((hit, delay)) = retry {
+                    retries += 1;
+                    tracing::warn!(
+                        workspace_id = %ws_id,
+                        thread_id = %thread_id_bg,
+                        "rate limited, retrying in {}s ({retries}/{})",
+                        delay.as_secs(),
+                        rate_limits.max_retries()
+                    );
+                    let event = AppEvent::TurnRetrying {
+                        thread_id: thread_id_bg.clone(),
+                        turn_id: turn_id_bg.clone(),
+                        attempt: retries,
+                        max_attempts: rate_limits.max_retries(),
+                        delay_ms: u64::try_from(delay.as_millis()).unwrap_or(u64::MAX),
+                        reason: "rateLimit".to_string(),
+                        message: hit.message,
+                    }
+                    .into_message();
+                    send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id_bg, event).await;
+                    tokio::time::sleep(delay).await;
+                    if turn_epoch.load(Ordering::SeqCst) == epoch {
+                        continue;
+                    }
+                }
+
+                let follow_up = launch
+                    .steer
+                    .lock()
+                    .await
+                    .finish_run(epoch, !interrupted && !failed);
+                if let Some(follow_up) = follow_up {
+                    let mut run = completed_event.unwrap_or_else(|| json!({ "params": {} }));
+                    if let Some(earlier) = &earlier_runs {
+                        add_earlier_runs(&mut run, earlier);
+                    }
+                    earlier_runs = Some(run);
+                    let session_id = store
+                        .lock()
+                        .await
+                        .threads
+                        .get(&thread_id_bg)
+                        .and_then(|meta| meta.cli_session_id.clone());
+                    launch.session_id = session_id.or(launch.session_id.take());
+                    launch.input = follow_up;
+                    retries = 0;
+                    continue;
+                }
+
+                for event in messages.completed_events(&thread_id_bg, &turn_id_bg) {
+                    send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id_bg, event).await;
+                }
+                if let Some(item_id) = &review_item_id {
+                    let event =
+                        exited_review_event(&thread_id_bg, &turn_id_bg, item_id, &review_text);
+                    send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id_bg, event).await;
+                }
+                let mut completed_event = completed_event.unwrap_or_else(|| {
+                    AppEvent::TurnCompleted {
+                        thread_id: thread_id_bg.clone(),
+                        turn_id: turn_id_bg.clone(),
+                        cost_usd: None,
+                        duration_ms: None,
+                        exit_code: None,
+                        error: None,
+                    }
+                    .into_message()
                 });
-                let mut sent_to_background = false;
+                if let Some(timeout) = timeout.filter(|_| timed_out) {
+                    tracing::warn!(
+                        workspace_id = %ws_id,
+                        thread_id = %thread_id_bg,
+                        "turn timed out after {}s",
+                        timeout.as_secs()
+                    );
+                    let event = AppEvent::TurnTimeout {
+                        thread_id: thread_id_bg.clone(),
+                        turn_id: turn_id_bg.clone(),
+                        timeout_secs: timeout.as_secs(),
+                    }
+                    .into_message();
+                    send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id_bg, event).await;
+                    if let Some(params) = completed_event
+                        .get_mut("params")
+                        .and_then(|params| params.as_object_mut())
+                    {
+                        params.insert(
+                            "error".to_string(),
+                            json!(format!("Turn timed out after {}s", timeout.as_secs())),
+                        );
+                    }
+                }
+                let failure = exit_code.filter(|code| *code != 0).map(|code| TurnFailure {
+                    turn_id: turn_id_bg.clone(),
+                    exit_code: code,
+                    stderr: stderr_tail.iter().cloned().collect(),
+                    failed_at: now_epoch(),
+                });
+                if let Some(params) = completed_event
+                    .get_mut("params")
+                    .and_then(|params| params.as_object_mut())
                 {
-                    let callbacks = bg_callbacks.lock().await;
-                    if let Some(tx) = callbacks.get(&thread_id_bg) {
-                        let _ = tx.send(fallback_event.clone());
-                        sent_to_background = true;
+                    params.insert("exitCode".to_string(), json!(exit_code));
+                    let has_error = params.get("error").is_some_and(|error| !error.is_null());
+                    if let Some(failure) = failure.as_ref().filter(|_| !has_error) {
+                        params.insert(
+                            "error".to_string(),
+                            json!(exit_error(&provider, failure.exit_code, &stderr_tail)),
+                        );
+                    }
+                }
+                if let Some(failure) = &failure {
+                    let event = AppEvent::TurnFailed {
+                        thread_id: thread_id_bg.clone(),
+                        turn_id: turn_id_bg.clone(),
+                        exit_code: failure.exit_code,
+                        stderr: failure.stderr.clone(),
+                        error: exit_error(&provider, failure.exit_code, &stderr_tail),
                     }
+                    .into_message();
+                    send_thread_event(&bg_callbacks, &*emitter, &ws_id, &thread_id_bg, event).await;
                 }
-                if !sent_to_background {
-                    (emitter)(AppServerEvent {
-                        workspace_id: ws_id,
-                        message: fallback_event,
+                if !interrupted {
+                    let mut s = store.lock().await;
+                    if let Some(meta) = s.threads.get_mut(&thread_id_bg) {
+                        if meta.last_turn_failure.is_some() || failure.is_some() {
+                            meta.last_turn_failure = failure;
+                            if let Err(e) = s.save(&store_path) {
+                                tracing::warn!("adapter: failed to record turn outcome: {e}");
+                            }
+                        }
+                    }
+                }
+                // Lets `autoCompact` judge how full the context is.
+                let transcript = store
+                    .lock()
+                    .await
+                    .threads
+                    .get(&thread_id_bg)
+                    .and_then(|meta| {
+                        meta.transcript_path
+                            .clone()
+                            .or_else(|| meta.source_path.clone())
                     });
+                if let Some(path) = transcript {
+                    if let (Ok(metadata), Some(params)) = (
+                        tokio::fs::metadata(&path).await,
+                        completed_event
+                            .get_mut("params")
+                            .and_then(|params| params.as_object_mut()),
+                    ) {
+                        params.insert(
+                            "contextTokens".to_string(),
+                            json!(estimate_tokens(metadata.len())),
+                        );
+                    }
                 }
-            }
-
-            let mut guard = active_child.lock().await;
-            if let Some(mut child) = guard.take() {
-                let _ = child.wait().await;
+                if let Some(earlier) = &earlier_runs {
+                    add_earlier_runs(&mut completed_event, earlier);
+                }
+                send_thread_event(
+                    &bg_callbacks,
+                    &*emitter,
+                    &ws_id,
+                    &thread_id_bg,
+                    completed_event,
+                )
+                .await;
+                break;
             }
         });
 
-        if let Some(stderr) = stderr {
-            tokio::spawn(async move {
-                let mut lines = BufReader::new(stderr).lines();
-                while let Ok(Some(_)) = lines.next_line().await {}
-            });
-        }
-
         Ok(json!({
             "result": {
                 "turn": { "id": turn_id },
@@ -390,6 +1941,10 @@ impl<P: CliProfile> GenericAdapterSession<P> {
 
 #[async_trait::async_trait]
 impl<P: CliProfile> CliAdapter for GenericAdapterSession<P> {
+    fn cli_type(&self) -> &str {
+        self.profile.provider_name()
+    }
+
     async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
         let provider = self.profile.provider_name();
         match method {
@@ -399,27 +1954,39 @@ impl<P: CliProfile> CliAdapter for GenericAdapterSession<P> {
                         "name": format!("{provider}-adapter"),
                         "version": "0.1.0"
                     },
-                    "capabilities": {}
+                    "capabilities": &*self.capabilities
                 }
             })),
             "thread/start" => self.handle_thread_start().await,
             "thread/resume" => self.handle_thread_resume(&params).await,
             "thread/fork" => self.handle_thread_fork(&params).await,
-            "thread/list" => self.handle_thread_list().await,
+            "thread/list" => self.handle_thread_list(&params).await,
             "thread/archive" => self.handle_thread_archive(&params).await,
-            "thread/compact/start" => Ok(json!({ "result": {} })),
+            "thread/unarchive" => self.handle_thread_unarchive(&params).await,
+            "thread/delete" => self.handle_thread_delete(&params).await,
+            "thread/compact/start" => self.handle_thread_compact(&params).await,
             "thread/name/set" => self.handle_thread_name_set(&params).await,
             "turn/start" => self.handle_turn_start(&params).await,
+            "review/start" => self.handle_review_start(&params).await,
             "turn/interrupt" => {
+                self.turn_epoch.fetch_add(1, Ordering::SeqCst);
+                *self.steer.lock().await = SteerQueue::default();
                 let mut child_guard = self.active_child.lock().await;
                 if let Some(mut child) = child_guard.take() {
-                    kill_child_process_tree(&mut child).await;
+                    kill_turn_child(&mut child, &self.config, &self.workspace_id).await;
                 }
                 Ok(json!({ "result": {} }))
             }
-            "model/list" => Ok(self.profile.model_list()),
-            "account/read" => Ok(json!({ "result": { "provider": provider } })),
-            "account/rateLimits/read" => Ok(json!({ "result": Value::Null })),
+            "model/list" => Ok(self.profile.model_list(&self.config).await),
+            "account/read" => Ok(json!({
+                "result": {
+                    "provider": provider,
+                    "account": account_value(self.profile.read_account(&self.config))
+                }
+            })),
+            "account/rateLimits/read" => Ok(json!({
+                "result": self.config.services.rate_limits.snapshot(provider, now_epoch())
+            })),
             "collaborationMode/list" => Ok(json!({ "result": { "modes": [] } })),
             "skills/list" => Ok(json!({ "result": { "skills": [] } })),
             "app/list" => Ok(json!({ "result": { "apps": [] } })),
@@ -437,11 +2004,21 @@ impl<P: CliProfile> CliAdapter for GenericAdapterSession<P> {
     }
 
     async fn kill(&self) {
+        self.turn_epoch.fetch_add(1, Ordering::SeqCst);
+        *self.steer.lock().await = SteerQueue::default();
         let mut child_guard = self.active_child.lock().await;
         if let Some(mut child) = child_guard.take() {
-            kill_child_process_tree(&mut child).await;
+            kill_turn_child(&mut child, &self.config, &self.workspace_id).await;
         }
     }
+
+    async fn flush(&self) -> Result<(), String> {
+        self.thread_store.lock().await.save(&self.thread_store_path)
+    }
+
+    async fn child_pid(&self) -> Option<u32> {
+        self.active_child.lock().await.as_ref()?.id()
+    }
 }
 
 pub(crate) async fn spawn_adapter_session<P: CliProfile, E: EventSink>(
@@ -451,29 +2028,74 @@ pub(crate) async fn spawn_adapter_session<P: CliProfile, E: EventSink>(
     config: CliSpawnConfig,
     event_sink: E,
 ) -> Result<Arc<WorkspaceSession>, String> {
-    let _ = check_cli_installation(config.cli_bin.clone(), cli_name).await?;
+    let run_name = exec_target::run_name(&entry.id);
+    let version = config
+        .target
+        .check_installation(config.cli_bin.clone(), cli_name, &entry.path, &run_name)
+        .await?;
+    let capabilities = probe_capabilities(
+        &config,
+        &entry.path,
+        &run_name,
+        version,
+        profile.probed_flags(),
+    )
+    .await;
+    let unsupported = capabilities
+        .flags
+        .iter()
+        .filter(|(_, supported)| !**supported)
+        .map(|(flag, _)| flag.as_str())
+        .collect::<Vec<_>>();
+    if !unsupported.is_empty() {
+        tracing::warn!(
+            workspace_id = %entry.id,
+            "{cli_name} CLI {} lacks {}; adapting turn commands",
+            capabilities.version.as_deref().unwrap_or("(unknown version)"),
+            unsupported.join(", ")
+        );
+    }
 
+    let turn_hooks = Arc::new(TurnHooks::new(&entry, config.active_turns.clone()));
+    let event_sink = TurnObserverSink::new(event_sink, Arc::clone(&turn_hooks));
     let event_sink_clone = event_sink.clone();
     let emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync> = Arc::new(move |event| {
         event_sink_clone.emit_app_server_event(event);
     });
 
     let shared_callbacks = Arc::new(Mutex::new(HashMap::new()));
-    let adapter =
-        GenericAdapterSession::new(profile, &entry, config, emitter, shared_callbacks.clone());
+    let adapter = GenericAdapterSession::new(
+        profile,
+        &entry,
+        config.clone(),
+        Arc::clone(&emitter),
+        shared_callbacks.clone(),
+    )
+    .with_capabilities(capabilities);
+    let profile = Arc::clone(&adapter.profile);
     let session = Arc::new(WorkspaceSession::new_with_adapter(
         entry.clone(),
         Box::new(adapter),
         shared_callbacks,
+        turn_hooks,
+        Arc::clone(&config.services.turn_limiter),
+        Arc::clone(&emitter),
+    ));
+    session.turn_hooks.attach_session(&session);
+    tokio::spawn(watch_credentials(
+        profile,
+        config,
+        entry.id.clone(),
+        emitter,
+        Arc::downgrade(&session),
     ));
 
-    event_sink.emit_app_server_event(AppServerEvent {
-        workspace_id: entry.id.clone(),
-        message: json!({
-            "method": "codex/connected",
-            "params": { "workspaceId": entry.id }
-        }),
-    });
+    event_sink.emit_app_server_event(AppServerEvent::new(
+        entry.id.clone(),
+        AppEvent::Connected {
+            workspace_id: entry.id.clone(),
+        },
+    ));
 
     Ok(session)
 }
@@ -491,6 +2113,7 @@ pub(crate) fn build_adapter_command(
         args,
     )?;
     command.current_dir(cwd);
+    command.envs(resolve_workspace_env(&config.env)?);
     if let Some((var_name, home_path)) = home_env_var {
         command.env(var_name, home_path);
     }
@@ -504,6 +2127,105 @@ pub(crate) fn build_adapter_command(
 mod tests {
     use super::*;
 
+    #[test]
+    fn parses_string_and_item_turn_input() {
+        assert_eq!(
+            parse_turn_input(Some(&json!("hello"))).unwrap().text,
+            "hello"
+        );
+        let input = parse_turn_input(Some(&json!([
+            { "type": "text", "text": "What is in" },
+            { "type": "localImage", "path": "/tmp/shot.png" },
+            { "type": "text", "text": "this screenshot?" }
+        ])))
+        .unwrap();
+        assert_eq!(input.text, "What is in\n\nthis screenshot?");
+        assert_eq!(input.images, vec![PathBuf::from("/tmp/shot.png")]);
+        let input = parse_turn_input(Some(&json!([
+            { "type": "fileRef", "path": "src/main.rs" }
+        ])))
+        .unwrap();
+        assert_eq!(input.files, vec!["src/main.rs".to_string()]);
+        assert!(input.text.is_empty());
+        assert!(parse_turn_input(Some(&json!([
+            { "type": "image", "url": "https://example.com/a.png" }
+        ])))
+        .unwrap_err()
+        .contains("local image"));
+        assert!(parse_turn_input(Some(&json!([]))).is_err());
+        assert!(parse_turn_input(None).is_err());
+    }
+
+    #[test]
+    fn streamed_messages_complete_with_their_full_text() {
+        let delta = |item_id: &str, delta: &str| {
+            json!({
+                "method": "item/agentMessage/delta",
+                "params": { "itemId": item_id, "delta": delta }
+            })
+        };
+        let mut messages = MessageTexts::default();
+        messages.observe(&delta("msg_1", "Hello, "));
+        messages.observe(&delta("msg_2", "Done."));
+        messages.observe(&delta("msg_1", "world."));
+        messages.observe(&json!({
+            "method": "item/completed",
+            "params": { "item": { "id": "msg_2", "type": "agentMessage", "text": "Done." } }
+        }));
+
+        let events = messages.completed_events("thread-1", "turn-1");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["method"], "item/completed");
+        assert_eq!(
+            events[0]["params"]["item"],
+            json!({ "type": "agentMessage", "id": "msg_1", "text": "Hello, world." })
+        );
+        assert!(messages.completed_events("thread-1", "turn-1").is_empty());
+    }
+
+    #[test]
+    fn exit_error_ends_with_the_stderr_tail() {
+        assert_eq!(
+            exit_error("claude", 1, &VecDeque::new()),
+            "claude exited with code 1"
+        );
+        let tail = VecDeque::from([
+            "Error: invalid model".to_string(),
+            "Usage: claude".to_string(),
+        ]);
+        assert_eq!(
+            exit_error("claude", 2, &tail),
+            "claude exited with code 2:\nError: invalid model\nUsage: claude"
+        );
+    }
+
+    #[test]
+    fn capabilities_come_from_whole_flags_in_help() {
+        let help = "Options:\n  -p, --prompt <text>\n  --resume-last  Resume\n  --output-format <format>\n";
+        assert!(help_lists_flag(help, "--output-format"));
+        assert!(!help_lists_flag(help, "--resume"));
+        assert!(!help_lists_flag(help, "--prompt-file"));
+        let capabilities = CliCapabilities {
+            version: Some("0.1.5".to_string()),
+            flags: [("--resume".to_string(), false)].into_iter().collect(),
+        };
+        assert!(!capabilities.supports("--resume"));
+        assert!(capabilities.supports("--output-format"));
+    }
+
+    #[test]
+    fn workspace_turn_timeout_overrides_the_default() {
+        let settings = AdapterSettings::default();
+        assert_eq!(settings.turn_timeout(None), None);
+        settings.set_turn_timeout_secs(600);
+        assert_eq!(settings.turn_timeout(None), Some(Duration::from_secs(600)));
+        assert_eq!(
+            settings.turn_timeout(Some(30)),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(settings.turn_timeout(Some(0)), None);
+    }
+
     #[test]
     fn thread_store_roundtrip() {
         let temp_dir = std::env::temp_dir().join(format!(
@@ -522,6 +2244,15 @@ mod tests {
                 created_at: 1000,
                 updated_at: 2000,
                 archived: false,
+                source_path: None,
+                transcript_path: None,
+                compacted_summary: None,
+                last_turn_failure: Some(TurnFailure {
+                    turn_id: "turn-1".to_string(),
+                    exit_code: 1,
+                    stderr: vec!["Error: not logged in".to_string()],
+                    failed_at: 2000,
+                }),
             },
         );
         store.save(&path).unwrap();
@@ -532,6 +2263,8 @@ mod tests {
         assert_eq!(meta.cli_session_id.as_deref(), Some("s1"));
         assert_eq!(meta.name.as_deref(), Some("Test Thread"));
         assert!(!meta.archived);
+        let failure = meta.last_turn_failure.as_ref().expect("failure kept");
+        assert_eq!(failure.exit_code, 1);
 
         let _ = std::fs::remove_dir_all(temp_dir);
     }
@@ -543,6 +2276,51 @@ mod tests {
         assert!(store.threads.is_empty());
     }
 
+    #[test]
+    fn compacted_thread_starts_its_next_session_from_the_summary() {
+        let meta: ThreadMetadata = serde_json::from_value(json!({
+            "cli_session_id": null,
+            "name": null,
+            "created_at": 0,
+            "updated_at": 0,
+            "archived": false,
+            "compacted_summary": "Fixing the parser."
+        }))
+        .unwrap();
+        let summary = meta.compacted_summary.as_deref().unwrap();
+        let prompt = prompt_with_summary(summary, "Carry on");
+        assert!(prompt.contains("\nFixing the parser.\n</conversation-summary>"));
+        assert!(prompt.ends_with("\n\nCarry on"));
+        assert_eq!(estimate_tokens(10), 3);
+    }
+
+    #[test]
+    fn steered_input_runs_after_the_current_cli_run() {
+        let mut steer = SteerQueue::default();
+        steer.start("thread-1", "turn-1", 4);
+        assert_eq!(steer.running_turn("thread-1").as_deref(), Some("turn-1"));
+        assert_eq!(steer.running_turn("thread-2"), None);
+        for text in ["also update the tests", "and the docs"] {
+            steer.pending.push(TurnInput {
+                text: text.to_string(),
+                ..TurnInput::default()
+            });
+        }
+
+        assert_eq!(steer.finish_run(3, true), None);
+        let follow_up = steer.finish_run(4, true).expect("queued input");
+        assert_eq!(follow_up.text, "also update the tests\n\nand the docs");
+        assert_eq!(steer.running_turn("thread-1").as_deref(), Some("turn-1"));
+        assert_eq!(steer.finish_run(4, true), None);
+        assert_eq!(steer.running_turn("thread-1"), None);
+
+        let mut completed = json!({ "params": { "costUsd": 0.5, "durationMs": 200 } });
+        let earlier = json!({ "params": { "costUsd": 0.25, "durationMs": 100 } });
+        add_earlier_runs(&mut completed, &earlier);
+        assert_eq!(completed["params"]["costUsd"], 0.75);
+        assert_eq!(completed["params"]["durationMs"], 300);
+    }
+
     #[test]
     fn now_epoch_returns_nonzero() {
         assert!(now_epoch() > 0);
@@ -1,24 +1,38 @@
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::env;
 use std::io::ErrorKind;
-use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, ChildStdin, Command};
-use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::sync::{oneshot, watch, Mutex};
 use tokio::time::timeout;
 
-use crate::backend::events::{AppServerEvent, EventSink};
+use crate::backend::approvals::{watch_approval_request, ApprovalTracker};
+use crate::backend::background_queue::{BackgroundCallbackInfo, BackgroundSender};
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::backend::exec_target::{self, ExecutionTarget};
+use crate::backend::turn_hooks::{TurnHooks, TurnObserverSink};
+use crate::shared::app_error::{AppError, AppResult};
+use crate::shared::logging_core::{self, RPC_LOG_MAX_CHARS};
+use crate::shared::maintenance_core::ActiveTurns;
+use crate::shared::path_env::build_cli_path_env;
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
+use crate::shared::redaction_core::{DeltaRedactor, SecretRedactor};
+use crate::shared::runtime_services::RuntimeServices;
+use crate::shared::turn_limit_core::TurnLimiter;
+use crate::shared::workspace_env_core::{resolve_workspace_env, workspace_secret_values};
 use crate::codex::args::parse_codex_args;
 use crate::types::WorkspaceEntry;
 
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
+#[cfg(target_os = "windows")]
+use std::path::Path;
 
 #[derive(Clone, Debug)]
 pub(crate) struct CliSpawnConfig {
@@ -26,21 +40,98 @@ pub(crate) struct CliSpawnConfig {
     pub cli_bin: Option<String>,
     pub cli_args: Option<String>,
     pub cli_home: Option<PathBuf>,
+    /// Workspace variables; values may be keychain references.
+    pub env: HashMap<String, String>,
+    /// Where the CLI runs: here, over SSH or in a Docker container.
+    pub target: ExecutionTarget,
+    /// Counts the session's running turns for the maintenance coordinator.
+    pub active_turns: ActiveTurns,
+    /// The app's or daemon's shared services (turn limit, redaction
+    /// patterns, rate limits, adapter settings).
+    pub services: RuntimeServices,
 }
 
 #[async_trait::async_trait]
 pub(crate) trait CliAdapter: Send + Sync {
+    /// The CLI behind the adapter, as in the `cliType` setting.
+    fn cli_type(&self) -> &str;
     async fn send_request(&self, method: &str, params: Value) -> Result<Value, String>;
     async fn send_notification(&self, method: &str, params: Option<Value>) -> Result<(), String>;
     async fn send_response(&self, id: Value, result: Value) -> Result<(), String>;
     async fn kill(&self);
+    /// Writes state kept in memory (thread stores) to disk, before a kill at
+    /// shutdown.
+    async fn flush(&self) -> Result<(), String> {
+        Ok(())
+    }
+    /// Pid of the CLI process running the current turn, if any.
+    async fn child_pid(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// How long an app-server request waits for its response unless the caller
+/// picks another limit.
+pub(crate) const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// Tells the app-server a request was abandoned, so it can stop working on it.
+const CANCEL_REQUEST_METHOD: &str = "$/cancelRequest";
+
+struct PendingRequest {
+    tx: oneshot::Sender<Value>,
+    method: String,
+    started: Instant,
+}
+
+/// An app-server request still waiting for its response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PendingRequestInfo {
+    pub(crate) id: u64,
+    pub(crate) method: String,
+    pub(crate) elapsed_ms: u64,
 }
 
 struct AppServerTransport {
     child: Mutex<Child>,
     stdin: Mutex<ChildStdin>,
-    pending: Mutex<HashMap<u64, oneshot::Sender<Value>>>,
+    pending: Mutex<HashMap<u64, PendingRequest>>,
     next_id: AtomicU64,
+    event_emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
+    target: ExecutionTarget,
+    run_name: String,
+}
+
+impl AppServerTransport {
+    /// Mirrors a JSON-RPC message as a `debug/rpc` event when the inspector
+    /// is enabled in settings.
+    fn emit_rpc_debug(
+        &self,
+        workspace_id: &str,
+        direction: &str,
+        message: Value,
+        latency_ms: Option<u64>,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis() as u64)
+            .unwrap_or(0);
+        (self.event_emitter)(AppServerEvent::new(
+            workspace_id,
+            AppEvent::DebugRpc {
+                workspace_id: workspace_id.to_string(),
+                direction: direction.to_string(),
+                timestamp,
+                latency_ms,
+                message,
+            },
+        ));
+    }
+
+    /// Returns how long ago request `id` was sent, if it is still pending.
+    async fn take_latency(&self, id: u64) -> Option<u64> {
+        let pending = self.pending.lock().await;
+        Some(pending.get(&id)?.started.elapsed().as_millis() as u64)
+    }
 }
 
 enum SessionTransport {
@@ -48,10 +139,24 @@ enum SessionTransport {
     Adapter(Box<dyn CliAdapter>),
 }
 
+/// How an app-server child ended. `expected` is set when the session was
+/// stopped through `kill`.
+#[derive(Debug, Clone)]
+pub(crate) struct SessionExit {
+    pub(crate) code: Option<i32>,
+    pub(crate) expected: bool,
+}
+
 pub(crate) struct WorkspaceSession {
     pub(crate) entry: WorkspaceEntry,
-    pub(crate) background_thread_callbacks: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>,
+    pub(crate) background_thread_callbacks: Arc<Mutex<HashMap<String, BackgroundSender>>>,
+    pub(crate) approvals: ApprovalTracker,
+    pub(crate) turn_hooks: Arc<TurnHooks>,
+    turn_limiter: Arc<TurnLimiter>,
     transport: SessionTransport,
+    event_emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
+    stopping: AtomicBool,
+    exit_rx: watch::Receiver<Option<SessionExit>>,
 }
 
 impl WorkspaceSession {
@@ -60,6 +165,15 @@ impl WorkspaceSession {
             SessionTransport::AppServer(t) => {
                 let mut stdin = t.stdin.lock().await;
                 let mut line = serde_json::to_string(&value).map_err(|e| e.to_string())?;
+                if logging_core::rpc_inspector_enabled() {
+                    t.emit_rpc_debug(&self.entry.id, "out", value.clone(), None);
+                }
+                tracing::debug!(
+                    workspace_id = %self.entry.id,
+                    direction = "out",
+                    "{}",
+                    logging_core::truncate_for_log(&line, RPC_LOG_MAX_CHARS)
+                );
                 line.push('\n');
                 stdin
                     .write_all(line.as_bytes())
@@ -72,20 +186,204 @@ impl WorkspaceSession {
         }
     }
 
+    /// A `turn/start` for a thread whose turn is still running steers that
+    /// turn, which already holds its concurrency slot and ran its hooks.
+    fn is_steering(&self, params: &Value) -> bool {
+        params
+            .get("threadId")
+            .and_then(|value| value.as_str())
+            .is_some_and(|thread_id| self.turn_hooks.is_turn_running(thread_id))
+    }
+
+    /// The CLI this session runs, as in the `cliType` setting.
+    pub(crate) fn cli_type(&self) -> &str {
+        match &self.transport {
+            SessionTransport::AppServer(_) => "codex",
+            SessionTransport::Adapter(adapter) => adapter.cli_type(),
+        }
+    }
+
+    async fn is_background_turn(&self, params: &Value) -> bool {
+        let Some(thread_id) = params.get("threadId").and_then(|value| value.as_str()) else {
+            return false;
+        };
+        self.background_thread_callbacks
+            .lock()
+            .await
+            .contains_key(thread_id)
+    }
+
     pub(crate) async fn send_request(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.send_request_with_timeout(method, params, REQUEST_TIMEOUT)
+            .await
+    }
+
+    /// Like `send_request`, but gives up on an app-server response after
+    /// `limit`, cancelling the request. Adapter sessions run each request
+    /// to completion.
+    pub(crate) async fn send_request_with_timeout(
+        &self,
+        method: &str,
+        params: Value,
+        limit: Duration,
+    ) -> Result<Value, String> {
+        if method == "turn/start"
+            && !self.is_background_turn(&params).await
+            && !self.is_steering(&params)
+        {
+            return self.start_foreground_turn(params, limit).await;
+        }
+        self.dispatch_request(method, params, limit).await
+    }
+
+    /// Refuses the turn when the workspace's monthly budget is used up, then
+    /// waits for a slot under `maxConcurrentTurns` (emitting `turn/queued`
+    /// while it waits), runs the turn hooks and forwards `turn/start` unless
+    /// a pre-turn hook refused it.
+    async fn start_foreground_turn(&self, params: Value, limit: Duration) -> Result<Value, String> {
+        let thread_id = params
+            .get("threadId")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+        if let Some(status) = self.turn_hooks.exhausted_budget().await {
+            let month = status.month.clone();
+            (self.event_emitter)(AppServerEvent::new(
+                self.entry.id.clone(),
+                AppEvent::BudgetExceeded {
+                    workspace_id: self.entry.id.clone(),
+                    thread_id,
+                    status,
+                    turn_refused: true,
+                },
+            ));
+            return Err(format!(
+                "Monthly budget exceeded for {month}; override it to keep running turns"
+            ));
+        }
+        let permit = match self.turn_limiter.try_acquire() {
+            Ok(permit) => permit,
+            Err(queued) => {
+                (self.event_emitter)(AppServerEvent::new(
+                    self.entry.id.clone(),
+                    AppEvent::TurnQueued {
+                        workspace_id: self.entry.id.clone(),
+                        thread_id: thread_id.clone(),
+                        position: queued.position,
+                    },
+                ));
+                queued.wait().await
+            }
+        };
+        self.turn_hooks
+            .before_turn_start(&params, permit, Arc::clone(&self.event_emitter))
+            .await?;
+        let result = self.dispatch_request("turn/start", params, limit).await;
+        let started = result
+            .as_ref()
+            .is_ok_and(|response| response.get("error").is_none());
+        if !started {
+            self.turn_hooks.turn_start_failed(&thread_id);
+        }
+        result
+    }
+
+    async fn dispatch_request(
+        &self,
+        method: &str,
+        params: Value,
+        limit: Duration,
+    ) -> Result<Value, String> {
         match &self.transport {
             SessionTransport::AppServer(t) => {
                 let id = t.next_id.fetch_add(1, Ordering::SeqCst);
                 let (tx, rx) = oneshot::channel();
-                t.pending.lock().await.insert(id, tx);
-                self.write_message(json!({ "id": id, "method": method, "params": params }))
-                    .await?;
-                rx.await.map_err(|_| "request canceled".to_string())
+                let pending = PendingRequest {
+                    tx,
+                    method: method.to_string(),
+                    started: Instant::now(),
+                };
+                t.pending.lock().await.insert(id, pending);
+                let message = json!({ "id": id, "method": method, "params": params });
+                if let Err(err) = self.write_message(message).await {
+                    t.pending.lock().await.remove(&id);
+                    return Err(err);
+                }
+                match timeout(limit, rx).await {
+                    Ok(response) => response.map_err(|_| "request canceled".to_string()),
+                    Err(_) => {
+                        self.abandon_request(t, id).await;
+                        Err(format!(
+                            "`{method}` got no response within {}s",
+                            limit.as_secs()
+                        ))
+                    }
+                }
             }
             SessionTransport::Adapter(adapter) => adapter.send_request(method, params).await,
         }
     }
 
+    /// Drops request `id`'s pending entry, failing its caller with "request
+    /// canceled", and tells the app-server. False when it already finished.
+    async fn abandon_request(&self, t: &AppServerTransport, id: u64) -> bool {
+        if t.pending.lock().await.remove(&id).is_none() {
+            return false;
+        }
+        let _ = self
+            .send_notification(CANCEL_REQUEST_METHOD, Some(json!({ "id": id })))
+            .await;
+        true
+    }
+
+    /// Cancels in-flight app-server request `id`. Returns whether it was
+    /// still pending.
+    pub(crate) async fn request_cancel(&self, id: u64) -> Result<bool, String> {
+        match &self.transport {
+            SessionTransport::AppServer(t) => Ok(self.abandon_request(t, id).await),
+            SessionTransport::Adapter(_) => {
+                Err("This CLI doesn't support cancelling requests".to_string())
+            }
+        }
+    }
+
+    /// App-server requests still waiting for a response, oldest first.
+    pub(crate) async fn pending_requests(&self) -> Vec<PendingRequestInfo> {
+        let SessionTransport::AppServer(t) = &self.transport else {
+            return Vec::new();
+        };
+        let mut requests = t
+            .pending
+            .lock()
+            .await
+            .iter()
+            .map(|(id, pending)| PendingRequestInfo {
+                id: *id,
+                method: pending.method.clone(),
+                elapsed_ms: pending.started.elapsed().as_millis() as u64,
+            })
+            .collect::<Vec<_>>();
+        requests.sort_by_key(|request| request.id);
+        requests
+    }
+
+    /// Queue depth of each registered background thread callback.
+    pub(crate) async fn background_callbacks(&self) -> Vec<BackgroundCallbackInfo> {
+        let mut callbacks = self
+            .background_thread_callbacks
+            .lock()
+            .await
+            .iter()
+            .map(|(thread_id, tx)| BackgroundCallbackInfo {
+                thread_id: thread_id.clone(),
+                depth: tx.depth(),
+                dropped: tx.dropped(),
+            })
+            .collect::<Vec<_>>();
+        callbacks.sort_by(|a, b| a.thread_id.cmp(&b.thread_id));
+        callbacks
+    }
+
     pub(crate) async fn send_notification(
         &self,
         method: &str,
@@ -105,6 +403,7 @@ impl WorkspaceSession {
     }
 
     pub(crate) async fn send_response(&self, id: Value, result: Value) -> Result<(), String> {
+        self.approvals.resolve(&id).await;
         match &self.transport {
             SessionTransport::AppServer(_) => {
                 self.write_message(json!({ "id": id, "result": result }))
@@ -114,11 +413,44 @@ impl WorkspaceSession {
         }
     }
 
+    /// Resolves when the app-server child exits. Returns `None` for adapter
+    /// sessions, which spawn a process per turn instead.
+    pub(crate) async fn wait_for_exit(&self) -> Option<SessionExit> {
+        let mut exit_rx = self.exit_rx.clone();
+        loop {
+            if let Some(exit) = exit_rx.borrow().clone() {
+                return Some(exit);
+            }
+            if exit_rx.changed().await.is_err() {
+                return exit_rx.borrow().clone();
+            }
+        }
+    }
+
+    /// Pid of the session's CLI process: the long-lived app-server, or the
+    /// per-turn process of an adapter session while a turn runs.
+    pub(crate) async fn child_pid(&self) -> Option<u32> {
+        match &self.transport {
+            SessionTransport::AppServer(t) => t.child.lock().await.id(),
+            SessionTransport::Adapter(adapter) => adapter.child_pid().await,
+        }
+    }
+
+    pub(crate) async fn flush(&self) -> Result<(), String> {
+        match &self.transport {
+            SessionTransport::AppServer(_) => Ok(()),
+            SessionTransport::Adapter(adapter) => adapter.flush().await,
+        }
+    }
+
     pub(crate) async fn kill(&self) {
+        self.stopping.store(true, Ordering::SeqCst);
+        self.approvals.clear().await;
         match &self.transport {
             SessionTransport::AppServer(t) => {
                 let mut child = t.child.lock().await;
                 kill_child_process_tree(&mut child).await;
+                t.target.stop(&t.run_name).await;
             }
             SessionTransport::Adapter(adapter) => {
                 adapter.kill().await;
@@ -129,14 +461,42 @@ impl WorkspaceSession {
     pub(crate) fn new_with_adapter(
         entry: WorkspaceEntry,
         adapter: Box<dyn CliAdapter>,
-        callbacks: Arc<Mutex<HashMap<String, mpsc::UnboundedSender<Value>>>>,
+        callbacks: Arc<Mutex<HashMap<String, BackgroundSender>>>,
+        turn_hooks: Arc<TurnHooks>,
+        turn_limiter: Arc<TurnLimiter>,
+        event_emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync>,
     ) -> Self {
+        let approvals = ApprovalTracker::new(entry.settings.approval_timeout.clone());
+        let (_, exit_rx) = watch::channel(None);
         Self {
             entry,
             background_thread_callbacks: callbacks,
+            approvals,
+            turn_hooks,
+            turn_limiter,
             transport: SessionTransport::Adapter(adapter),
+            event_emitter,
+            stopping: AtomicBool::new(false),
+            exit_rx,
+        }
+    }
+}
+
+/// Collects the exit status once stdout closes and fails any requests still
+/// waiting on a response.
+async fn session_exit(session: &WorkspaceSession) -> SessionExit {
+    let mut code = None;
+    if let SessionTransport::AppServer(t) = &session.transport {
+        t.pending.lock().await.clear();
+        let mut child = t.child.lock().await;
+        if let Ok(Ok(status)) = timeout(Duration::from_secs(5), child.wait()).await {
+            code = status.code();
         }
     }
+    SessionExit {
+        code,
+        expected: session.stopping.load(Ordering::SeqCst),
+    }
 }
 
 fn extract_thread_id(value: &Value) -> Option<String> {
@@ -156,6 +516,27 @@ fn extract_thread_id(value: &Value) -> Option<String> {
         })
 }
 
+/// Hands a notification to its thread's background callback when one is
+/// registered, otherwise to the sink.
+async fn route_notification<E: EventSink>(
+    session: &WorkspaceSession,
+    event_sink: &E,
+    workspace_id: &str,
+    value: Value,
+) {
+    if let Some(thread_id) = extract_thread_id(&value) {
+        let callbacks = session.background_thread_callbacks.lock().await;
+        if let Some(tx) = callbacks.get(&thread_id) {
+            tx.send(value);
+            return;
+        }
+    }
+    event_sink.emit_app_server_event(AppServerEvent {
+        workspace_id: workspace_id.to_string(),
+        message: value,
+    });
+}
+
 fn build_initialize_params(client_version: &str) -> Value {
     json!({
         "clientInfo": {
@@ -169,85 +550,6 @@ fn build_initialize_params(client_version: &str) -> Value {
     })
 }
 
-pub(crate) fn build_codex_path_env(codex_bin: Option<&str>) -> Option<String> {
-    let mut paths: Vec<PathBuf> = env::var_os("PATH")
-        .map(|value| env::split_paths(&value).collect())
-        .unwrap_or_default();
-
-    let mut extras: Vec<PathBuf> = Vec::new();
-
-    #[cfg(not(target_os = "windows"))]
-    {
-        extras.extend([
-            "/opt/homebrew/bin",
-            "/usr/local/bin",
-            "/usr/bin",
-            "/bin",
-            "/usr/sbin",
-            "/sbin",
-        ].into_iter().map(PathBuf::from));
-
-        if let Ok(home) = env::var("HOME") {
-            let home_path = Path::new(&home);
-            extras.push(home_path.join(".local/bin"));
-            extras.push(home_path.join(".local/share/mise/shims"));
-            extras.push(home_path.join(".cargo/bin"));
-            extras.push(home_path.join(".bun/bin"));
-            let nvm_root = home_path.join(".nvm/versions/node");
-            if let Ok(entries) = std::fs::read_dir(nvm_root) {
-                for entry in entries.flatten() {
-                    let bin_path = entry.path().join("bin");
-                    if bin_path.is_dir() {
-                        extras.push(bin_path);
-                    }
-                }
-            }
-        }
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        if let Ok(appdata) = env::var("APPDATA") {
-            extras.push(Path::new(&appdata).join("npm"));
-        }
-        if let Ok(local_app_data) = env::var("LOCALAPPDATA") {
-            extras.push(
-                Path::new(&local_app_data)
-                    .join("Microsoft")
-                    .join("WindowsApps"),
-            );
-        }
-        if let Ok(home) = env::var("USERPROFILE").or_else(|_| env::var("HOME")) {
-            let home_path = Path::new(&home);
-            extras.push(home_path.join(".cargo").join("bin"));
-            extras.push(home_path.join("scoop").join("shims"));
-        }
-        if let Ok(program_data) = env::var("PROGRAMDATA") {
-            extras.push(Path::new(&program_data).join("chocolatey").join("bin"));
-        }
-    }
-
-    if let Some(bin_path) = codex_bin.filter(|value| !value.trim().is_empty()) {
-        if let Some(parent) = Path::new(bin_path).parent() {
-            extras.push(parent.to_path_buf());
-        }
-    }
-
-    for extra in extras {
-        if !paths.iter().any(|path| path == &extra) {
-            paths.push(extra);
-        }
-    }
-
-    if paths.is_empty() {
-        return None;
-    }
-
-    env::join_paths(paths)
-        .ok()
-        .map(|joined| joined.to_string_lossy().to_string())
-}
-
 pub(crate) fn build_codex_command_with_bin(
     codex_bin: Option<String>,
     codex_args: Option<&str>,
@@ -258,7 +560,7 @@ pub(crate) fn build_codex_command_with_bin(
         .filter(|value| !value.trim().is_empty())
         .unwrap_or_else(|| "codex".into());
 
-    let path_env = build_codex_path_env(codex_bin.as_deref());
+    let path_env = build_cli_path_env(codex_bin.as_deref());
     let mut command_args = parse_codex_args(codex_args)?;
     command_args.extend(args);
 
@@ -305,7 +607,7 @@ pub(crate) fn build_codex_command_with_bin(
 pub(crate) async fn check_cli_installation(
     cli_bin: Option<String>,
     cli_name: &str,
-) -> Result<Option<String>, String> {
+) -> AppResult<Option<String>> {
     let mut command =
         build_codex_command_with_bin(cli_bin, None, vec!["--version".to_string()])?;
     command.stdout(std::process::Stdio::piped());
@@ -314,19 +616,19 @@ pub(crate) async fn check_cli_installation(
     let output = match timeout(Duration::from_secs(5), command.output()).await {
         Ok(result) => result.map_err(|e| {
             if e.kind() == ErrorKind::NotFound {
-                format!(
-                    "{cli_name} CLI not found. Install {cli_name} and ensure `{bin}` is on your PATH.",
-                    bin = cli_name.to_lowercase()
-                )
+                AppError::CliNotFound {
+                    name: cli_name.to_string(),
+                    bin: cli_name.to_lowercase(),
+                }
             } else {
-                e.to_string()
+                AppError::Io(e)
             }
         })?,
         Err(_) => {
-            return Err(format!(
+            return Err(AppError::Timeout(format!(
                 "Timed out while checking {cli_name} CLI. Make sure `{bin} --version` runs in Terminal.",
                 bin = cli_name.to_lowercase()
-            ));
+            )));
         }
     };
 
@@ -339,27 +641,21 @@ pub(crate) async fn check_cli_installation(
             stderr.trim()
         };
         if detail.is_empty() {
-            return Err(format!(
+            return Err(AppError::Internal(format!(
                 "{cli_name} CLI failed to start. Try running `{bin} --version` in Terminal.",
                 bin = cli_name.to_lowercase()
-            ));
+            )));
         }
-        return Err(format!(
+        return Err(AppError::Internal(format!(
             "{cli_name} CLI failed to start: {detail}. Try running `{bin} --version` in Terminal.",
             bin = cli_name.to_lowercase()
-        ));
+        )));
     }
 
     let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
     Ok(if version.is_empty() { None } else { Some(version) })
 }
 
-pub(crate) async fn check_codex_installation(
-    codex_bin: Option<String>,
-) -> Result<Option<String>, String> {
-    check_cli_installation(codex_bin, "Codex").await
-}
-
 pub(crate) async fn spawn_workspace_session<E: EventSink>(
     entry: WorkspaceEntry,
     config: CliSpawnConfig,
@@ -384,6 +680,15 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
         )
         .await;
     }
+    if let Some(manifest) = crate::backend::plugin_adapter::plugin(&config.cli_type) {
+        return crate::backend::plugin_adapter::spawn_plugin_session(
+            manifest, entry, config, event_sink,
+        )
+        .await;
+    }
+
+    let turn_hooks = Arc::new(TurnHooks::new(&entry, config.active_turns.clone()));
+    let event_sink = TurnObserverSink::new(event_sink, Arc::clone(&turn_hooks));
 
     let codex_bin = config
         .cli_bin
@@ -394,7 +699,11 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                 .clone()
                 .filter(|value| !value.trim().is_empty())
         });
-    let _ = check_codex_installation(codex_bin.clone()).await?;
+    let run_name = exec_target::run_name(&entry.id);
+    config
+        .target
+        .check_installation(codex_bin.clone(), "Codex", &entry.path, &run_name)
+        .await?;
 
     let mut command = build_codex_command_with_bin(
         codex_bin,
@@ -405,47 +714,103 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     if let Some(codex_home) = config.cli_home {
         command.env("CODEX_HOME", codex_home);
     }
+    let workspace_env = resolve_workspace_env(&config.env)?;
+    let redactor = Arc::new(SecretRedactor::new(
+        workspace_secret_values(&config.env, &workspace_env),
+        config.services.redaction_patterns.clone(),
+    ));
+    command.envs(workspace_env);
+    config.target.prepare(&run_name).await;
+    let mut command = config.target.wrap(command, &entry.path, &run_name)?;
     command.stdin(std::process::Stdio::piped());
     command.stdout(std::process::Stdio::piped());
     command.stderr(std::process::Stdio::piped());
 
-    let mut child = command.spawn().map_err(|e| e.to_string())?;
+    tracing::info!(
+        workspace_id = %entry.id,
+        cwd = %entry.path,
+        "spawning {}",
+        redactor.redact(&logging_core::command_line_for_log(command.as_std()))
+    );
+    let mut child = command.spawn().map_err(|e| {
+        tracing::error!(workspace_id = %entry.id, "spawn failed: {e}");
+        e.to_string()
+    })?;
     let stdin = child.stdin.take().ok_or("missing stdin")?;
     let stdout = child.stdout.take().ok_or("missing stdout")?;
     let stderr = child.stderr.take().ok_or("missing stderr")?;
 
+    let emitter_sink = event_sink.clone();
+    let event_emitter: Arc<dyn Fn(AppServerEvent) + Send + Sync> =
+        Arc::new(move |event| emitter_sink.emit_app_server_event(event));
     let transport = AppServerTransport {
         child: Mutex::new(child),
         stdin: Mutex::new(stdin),
         pending: Mutex::new(HashMap::new()),
         next_id: AtomicU64::new(1),
+        event_emitter: Arc::clone(&event_emitter),
+        target: config.target,
+        run_name,
     };
 
+    let (exit_tx, exit_rx) = watch::channel(None);
     let session = Arc::new(WorkspaceSession {
         entry: entry.clone(),
         background_thread_callbacks: Arc::new(Mutex::new(HashMap::new())),
+        approvals: ApprovalTracker::new(entry.settings.approval_timeout.clone()),
+        turn_hooks,
+        turn_limiter: Arc::clone(&config.services.turn_limiter),
         transport: SessionTransport::AppServer(transport),
+        event_emitter,
+        stopping: AtomicBool::new(false),
+        exit_rx,
     });
+    session.turn_hooks.attach_session(&session);
 
     let session_clone = Arc::clone(&session);
     let workspace_id = entry.id.clone();
     let event_sink_clone = event_sink.clone();
+    let stdout_redactor = Arc::clone(&redactor);
     tokio::spawn(async move {
+        let mut delta_redactor = DeltaRedactor::new(Arc::clone(&stdout_redactor));
         let mut lines = BufReader::new(stdout).lines();
         while let Ok(Some(line)) = lines.next_line().await {
             if line.trim().is_empty() {
                 continue;
             }
+            tracing::debug!(
+                workspace_id = %workspace_id,
+                direction = "in",
+                "{}",
+                logging_core::truncate_for_log(&stdout_redactor.redact(&line), RPC_LOG_MAX_CHARS)
+            );
             let value: Value = match serde_json::from_str(&line) {
-                Ok(value) => value,
+                Ok(mut value) => {
+                    for held in delta_redactor.redact_event(&mut value) {
+                        route_notification(&session_clone, &event_sink_clone, &workspace_id, held)
+                            .await;
+                    }
+                    value
+                }
                 Err(err) => {
-                    let payload = AppServerEvent {
-                        workspace_id: workspace_id.clone(),
-                        message: json!({
-                            "method": "codex/parseError",
-                            "params": { "error": err.to_string(), "raw": line },
-                        }),
-                    };
+                    let line = stdout_redactor.redact(&line).into_owned();
+                    if logging_core::rpc_inspector_enabled() {
+                        if let SessionTransport::AppServer(t) = &session_clone.transport {
+                            t.emit_rpc_debug(
+                                &workspace_id,
+                                "in",
+                                Value::String(line.clone()),
+                                None,
+                            );
+                        }
+                    }
+                    let payload = AppServerEvent::new(
+                        workspace_id.clone(),
+                        AppEvent::ParseError {
+                            error: err.to_string(),
+                            raw: line,
+                        },
+                    );
                     event_sink_clone.emit_app_server_event(payload);
                     continue;
                 }
@@ -457,11 +822,21 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
 
             let thread_id = extract_thread_id(&value);
 
+            if let SessionTransport::AppServer(t) = &session_clone.transport {
+                let latency_ms = match maybe_id {
+                    Some(id) if has_result_or_error => t.take_latency(id).await,
+                    _ => None,
+                };
+                if logging_core::rpc_inspector_enabled() {
+                    t.emit_rpc_debug(&workspace_id, "in", value.clone(), latency_ms);
+                }
+            }
+
             if let Some(id) = maybe_id {
                 if has_result_or_error {
                     if let SessionTransport::AppServer(t) = &session_clone.transport {
-                        if let Some(tx) = t.pending.lock().await.remove(&id) {
-                            let _ = tx.send(value);
+                        if let Some(pending) = t.pending.lock().await.remove(&id) {
+                            let _ = pending.tx.send(value);
                         }
                     }
                 } else if has_method {
@@ -469,11 +844,17 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                     if let Some(ref tid) = thread_id {
                         let callbacks = session_clone.background_thread_callbacks.lock().await;
                         if let Some(tx) = callbacks.get(tid) {
-                            let _ = tx.send(value.clone());
+                            tx.send(value.clone());
                             sent_to_background = true;
                         }
                     }
                     if !sent_to_background {
+                        watch_approval_request(
+                            Arc::clone(&session_clone),
+                            event_sink_clone.clone(),
+                            &value,
+                        )
+                        .await;
                         let payload = AppServerEvent {
                             workspace_id: workspace_id.clone(),
                             message: value,
@@ -481,28 +862,34 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
                         event_sink_clone.emit_app_server_event(payload);
                     }
                 } else if let SessionTransport::AppServer(t) = &session_clone.transport {
-                    if let Some(tx) = t.pending.lock().await.remove(&id) {
-                        let _ = tx.send(value);
+                    if let Some(pending) = t.pending.lock().await.remove(&id) {
+                        let _ = pending.tx.send(value);
                     }
                 }
             } else if has_method {
-                let mut sent_to_background = false;
-                if let Some(ref tid) = thread_id {
-                    let callbacks = session_clone.background_thread_callbacks.lock().await;
-                    if let Some(tx) = callbacks.get(tid) {
-                        let _ = tx.send(value.clone());
-                        sent_to_background = true;
-                    }
-                }
-                if !sent_to_background {
-                    let payload = AppServerEvent {
-                        workspace_id: workspace_id.clone(),
-                        message: value,
-                    };
-                    event_sink_clone.emit_app_server_event(payload);
-                }
+                route_notification(&session_clone, &event_sink_clone, &workspace_id, value).await;
             }
         }
+        for held in delta_redactor.flush() {
+            route_notification(&session_clone, &event_sink_clone, &workspace_id, held).await;
+        }
+
+        let exit = session_exit(&session_clone).await;
+        if !exit.expected {
+            tracing::warn!(
+                workspace_id = %workspace_id,
+                "app-server exited unexpectedly (code {:?})",
+                exit.code
+            );
+            event_sink_clone.emit_app_server_event(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::Disconnected {
+                    workspace_id: workspace_id.clone(),
+                    exit_code: exit.code,
+                },
+            ));
+        }
+        let _ = exit_tx.send(Some(exit));
     });
 
     let workspace_id = entry.id.clone();
@@ -513,13 +900,10 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
             if line.trim().is_empty() {
                 continue;
             }
-            let payload = AppServerEvent {
-                workspace_id: workspace_id.clone(),
-                message: json!({
-                    "method": "codex/stderr",
-                    "params": { "message": line },
-                }),
-            };
+            let line = redactor.redact(&line).into_owned();
+            tracing::warn!(workspace_id = %workspace_id, stream = "stderr", "{line}");
+            let payload =
+                AppServerEvent::new(workspace_id.clone(), AppEvent::Stderr { message: line });
             event_sink_clone.emit_app_server_event(payload);
         }
     });
@@ -543,13 +927,12 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
     init_response?;
     session.send_notification("initialized", None).await?;
 
-    let payload = AppServerEvent {
-        workspace_id: entry.id.clone(),
-        message: json!({
-            "method": "codex/connected",
-            "params": { "workspaceId": entry.id.clone() }
-        }),
-    };
+    let payload = AppServerEvent::new(
+        entry.id.clone(),
+        AppEvent::Connected {
+            workspace_id: entry.id.clone(),
+        },
+    );
     event_sink.emit_app_server_event(payload);
 
     Ok(session)
@@ -557,8 +940,12 @@ pub(crate) async fn spawn_workspace_session<E: EventSink>(
 
 #[cfg(test)]
 mod tests {
-    use super::{build_initialize_params, extract_thread_id, CliSpawnConfig};
+    use super::{
+        build_initialize_params, extract_thread_id, ActiveTurns, CliSpawnConfig, ExecutionTarget,
+        RuntimeServices,
+    };
     use serde_json::json;
+    use std::collections::HashMap;
 
     #[test]
     fn extract_thread_id_reads_camel_case() {
@@ -597,6 +984,10 @@ mod tests {
             cli_bin: None,
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
         assert_eq!(config.cli_type, "codex");
         assert!(config.cli_bin.is_none());
@@ -0,0 +1,336 @@
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::types::{ApprovalTimeoutAction, ApprovalTimeoutPolicy};
+
+const MAX_ESCALATION_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Commands that only read, whatever their flags. `rg` (`--pre` runs a
+/// program per file) and `tree` (`-o` writes a file) aren't among them.
+const LOW_RISK_COMMANDS: &[&str] = &[
+    "cat", "head", "tail", "ls", "pwd", "wc", "grep", "which", "echo", "stat", "file", "du",
+];
+
+/// `git branch` is left out: `-D`, `-f` and `-m` change refs.
+const LOW_RISK_GIT_SUBCOMMANDS: &[&str] = &["status", "diff", "log", "show", "blame"];
+
+/// Flags that make the read-only git subcommands write a file or run a
+/// program.
+const RISKY_GIT_FLAGS: &[&str] = &["--output", "--ext-diff", "--textconv"];
+
+/// Tracks unanswered approval requests for a session so a per-workspace
+/// timeout policy can resolve or escalate them.
+pub(crate) struct ApprovalTracker {
+    policy: Mutex<Option<ApprovalTimeoutPolicy>>,
+    pending: Mutex<HashSet<String>>,
+}
+
+impl ApprovalTracker {
+    pub(crate) fn new(policy: Option<ApprovalTimeoutPolicy>) -> Self {
+        Self {
+            policy: Mutex::new(policy),
+            pending: Mutex::new(HashSet::new()),
+        }
+    }
+
+    pub(crate) async fn set_policy(&self, policy: Option<ApprovalTimeoutPolicy>) {
+        *self.policy.lock().await = policy;
+    }
+
+    async fn policy(&self) -> Option<ApprovalTimeoutPolicy> {
+        self.policy.lock().await.clone()
+    }
+
+    async fn track(&self, id: &Value) {
+        self.pending.lock().await.insert(id.to_string());
+    }
+
+    async fn is_pending(&self, id: &Value) -> bool {
+        self.pending.lock().await.contains(&id.to_string())
+    }
+
+    /// Marks a request as answered. Returns `false` when it was already
+    /// resolved (or never tracked).
+    pub(crate) async fn resolve(&self, id: &Value) -> bool {
+        self.pending.lock().await.remove(&id.to_string())
+    }
+
+    pub(crate) async fn clear(&self) {
+        self.pending.lock().await.clear();
+    }
+}
+
+pub(crate) fn is_approval_request_method(method: &str) -> bool {
+    method.ends_with("requestApproval")
+        || method == "execCommandApproval"
+        || method == "applyPatchApproval"
+}
+
+fn request_command_tokens(params: &Value) -> Option<Vec<String>> {
+    let command = params.get("command")?;
+    if let Some(parts) = command.as_array() {
+        let tokens = parts
+            .iter()
+            .filter_map(|part| part.as_str().map(|value| value.to_string()))
+            .collect::<Vec<_>>();
+        return Some(tokens);
+    }
+    let command = command.as_str()?;
+    Some(
+        command
+            .split_whitespace()
+            .map(|part| part.to_string())
+            .collect(),
+    )
+}
+
+fn unwrap_shell_wrapper(tokens: Vec<String>) -> Vec<String> {
+    let is_shell = matches!(
+        tokens.first().map(|value| value.as_str()),
+        Some("bash")
+            | Some("sh")
+            | Some("zsh")
+            | Some("/bin/bash")
+            | Some("/bin/sh")
+            | Some("/bin/zsh")
+    );
+    if is_shell && tokens.len() == 3 && matches!(tokens[1].as_str(), "-c" | "-lc") {
+        return tokens[2]
+            .split_whitespace()
+            .map(|part| part.to_string())
+            .collect();
+    }
+    tokens
+}
+
+/// Low-risk requests are read-only commands with no redirection or chaining.
+/// File changes and anything else always require an explicit answer.
+pub(crate) fn is_low_risk_request(method: &str, params: &Value) -> bool {
+    if method.contains("fileChange") || method == "applyPatchApproval" {
+        return false;
+    }
+    let Some(tokens) = request_command_tokens(params) else {
+        return false;
+    };
+    let tokens = unwrap_shell_wrapper(tokens);
+    if tokens.is_empty() {
+        return false;
+    }
+    let has_shell_control = tokens.iter().any(|token| {
+        ["|", ">", "<", ";", "&", "`", "$("]
+            .iter()
+            .any(|needle| token.contains(needle))
+    });
+    if has_shell_control {
+        return false;
+    }
+    let program = tokens[0].rsplit('/').next().unwrap_or(tokens[0].as_str());
+    if program == "git" {
+        let risky_flag = tokens[1..].iter().any(|token| {
+            RISKY_GIT_FLAGS
+                .iter()
+                .any(|flag| token == flag || token.starts_with(&format!("{flag}=")))
+        });
+        return !risky_flag
+            && tokens
+                .get(1)
+                .map(|sub| LOW_RISK_GIT_SUBCOMMANDS.contains(&sub.as_str()))
+                .unwrap_or(false);
+    }
+    LOW_RISK_COMMANDS.contains(&program)
+}
+
+fn timeout_decision(
+    action: ApprovalTimeoutAction,
+    method: &str,
+    params: &Value,
+) -> Option<&'static str> {
+    match action {
+        ApprovalTimeoutAction::Deny => Some("decline"),
+        ApprovalTimeoutAction::ApproveLowRisk if is_low_risk_request(method, params) => {
+            Some("accept")
+        }
+        _ => None,
+    }
+}
+
+/// Starts the timeout watcher for a server-initiated approval request. No-op
+/// unless the workspace has an approval timeout configured.
+pub(crate) async fn watch_approval_request<E: EventSink>(
+    session: Arc<WorkspaceSession>,
+    event_sink: E,
+    request: &Value,
+) {
+    let (Some(id), Some(method)) = (
+        request.get("id").cloned(),
+        request.get("method").and_then(|value| value.as_str()),
+    ) else {
+        return;
+    };
+    if !is_approval_request_method(method) {
+        return;
+    }
+    let Some(policy) = session.approvals.policy().await else {
+        return;
+    };
+    let method = method.to_string();
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+    session.approvals.track(&id).await;
+
+    tokio::spawn(async move {
+        let mut interval = Duration::from_secs(policy.timeout_seconds.max(1));
+        let mut waited = Duration::ZERO;
+        let mut escalation = 0u32;
+        loop {
+            tokio::time::sleep(interval).await;
+            waited += interval;
+            if !session.approvals.is_pending(&id).await {
+                return;
+            }
+            let Some(policy) = session.approvals.policy().await else {
+                return;
+            };
+            if let Some(decision) = timeout_decision(policy.action, &method, &params) {
+                if !session.approvals.resolve(&id).await {
+                    return;
+                }
+                if let Err(error) = session
+                    .send_response(id.clone(), json!({ "decision": decision }))
+                    .await
+                {
+                    tracing::warn!(
+                        workspace_id = %session.entry.id,
+                        "approval timeout: failed to send {decision} for {id}: {error}"
+                    );
+                    return;
+                }
+                event_sink.emit_app_server_event(AppServerEvent::new(
+                    session.entry.id.clone(),
+                    AppEvent::ApprovalAutoResolved {
+                        request_id: id,
+                        method,
+                        decision: decision.to_string(),
+                        waited_seconds: waited.as_secs(),
+                    },
+                ));
+                return;
+            }
+            escalation += 1;
+            event_sink.emit_app_server_event(AppServerEvent::new(
+                session.entry.id.clone(),
+                AppEvent::ApprovalTimeout {
+                    request_id: id.clone(),
+                    method: method.clone(),
+                    waited_seconds: waited.as_secs(),
+                    escalation,
+                },
+            ));
+            interval = (interval * 2).min(MAX_ESCALATION_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_approval_request_method, is_low_risk_request, timeout_decision};
+    use crate::types::ApprovalTimeoutAction;
+    use serde_json::json;
+
+    #[test]
+    fn matches_approval_request_methods() {
+        assert!(is_approval_request_method(
+            "item/commandExecution/requestApproval"
+        ));
+        assert!(is_approval_request_method(
+            "item/fileChange/requestApproval"
+        ));
+        assert!(is_approval_request_method("execCommandApproval"));
+        assert!(!is_approval_request_method("item/tool/requestUserInput"));
+    }
+
+    #[test]
+    fn read_only_commands_are_low_risk() {
+        let method = "item/commandExecution/requestApproval";
+        assert!(is_low_risk_request(
+            method,
+            &json!({ "command": "git status" })
+        ));
+        assert!(is_low_risk_request(
+            method,
+            &json!({ "command": ["bash", "-lc", "ls -la src"] })
+        ));
+        assert!(!is_low_risk_request(
+            method,
+            &json!({ "command": "rm -rf target" })
+        ));
+        assert!(!is_low_risk_request(
+            method,
+            &json!({ "command": "git push" })
+        ));
+        assert!(!is_low_risk_request(
+            method,
+            &json!({ "command": "cat secrets > /tmp/out" })
+        ));
+    }
+
+    #[test]
+    fn commands_that_write_or_exec_through_flags_are_not_low_risk() {
+        let method = "item/commandExecution/requestApproval";
+        for command in [
+            "git branch -D main",
+            "git branch -f main HEAD~3",
+            "git branch",
+            "rg --pre ./run.sh TODO",
+            "rg --pre-glob '*.rs' --pre ./run.sh TODO",
+            "tree -o listing.txt",
+            "git diff --output=patch.diff",
+            "git log -p --ext-diff",
+        ] {
+            assert!(
+                !is_low_risk_request(method, &json!({ "command": command })),
+                "{command}"
+            );
+        }
+        assert!(is_low_risk_request(
+            method,
+            &json!({ "command": "git log --oneline -5" })
+        ));
+    }
+
+    #[test]
+    fn file_changes_are_never_low_risk() {
+        assert!(!is_low_risk_request(
+            "item/fileChange/requestApproval",
+            &json!({ "command": "ls" })
+        ));
+    }
+
+    #[test]
+    fn timeout_decision_follows_policy_action() {
+        let method = "item/commandExecution/requestApproval";
+        let safe = json!({ "command": "git diff" });
+        let risky = json!({ "command": "npm publish" });
+        assert_eq!(
+            timeout_decision(ApprovalTimeoutAction::Deny, method, &risky),
+            Some("decline")
+        );
+        assert_eq!(
+            timeout_decision(ApprovalTimeoutAction::ApproveLowRisk, method, &safe),
+            Some("accept")
+        );
+        assert_eq!(
+            timeout_decision(ApprovalTimeoutAction::ApproveLowRisk, method, &risky),
+            None
+        );
+        assert_eq!(
+            timeout_decision(ApprovalTimeoutAction::Wait, method, &safe),
+            None
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::Notify;
+
+/// Events a background thread callback holds before the oldest are dropped.
+pub(crate) const BACKGROUND_QUEUE_CAPACITY: usize = 1024;
+
+/// Method of the marker a receiver gets in place of dropped events.
+pub(crate) const EVENTS_DROPPED_METHOD: &str = "events/dropped";
+
+#[derive(Default)]
+struct QueueState {
+    events: VecDeque<Value>,
+    /// Dropped since the receiver last saw a marker.
+    unreported: u64,
+    dropped: u64,
+    closed: bool,
+}
+
+struct Queue {
+    state: Mutex<QueueState>,
+    notify: Notify,
+    capacity: usize,
+}
+
+impl Queue {
+    fn state(&self) -> std::sync::MutexGuard<'_, QueueState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Sending half of a background thread callback. Never blocks: when the
+/// queue is full the oldest event is dropped, and the receiver gets an
+/// `events/dropped` marker with the count where those events would have been.
+pub(crate) struct BackgroundSender {
+    queue: Arc<Queue>,
+}
+
+pub(crate) struct BackgroundReceiver {
+    queue: Arc<Queue>,
+}
+
+/// Queue depth of one background thread callback, for diagnostics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackgroundCallbackInfo {
+    pub(crate) thread_id: String,
+    pub(crate) depth: usize,
+    pub(crate) dropped: u64,
+}
+
+pub(crate) fn background_channel(capacity: usize) -> (BackgroundSender, BackgroundReceiver) {
+    let queue = Arc::new(Queue {
+        state: Mutex::new(QueueState::default()),
+        notify: Notify::new(),
+        capacity: capacity.max(1),
+    });
+    (
+        BackgroundSender {
+            queue: Arc::clone(&queue),
+        },
+        BackgroundReceiver { queue },
+    )
+}
+
+impl BackgroundSender {
+    pub(crate) fn send(&self, event: Value) {
+        {
+            let mut state = self.queue.state();
+            if state.events.len() >= self.queue.capacity {
+                state.events.pop_front();
+                state.unreported += 1;
+                state.dropped += 1;
+            }
+            state.events.push_back(event);
+        }
+        self.queue.notify.notify_one();
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.queue.state().events.len()
+    }
+
+    /// Events dropped over the callback's lifetime.
+    pub(crate) fn dropped(&self) -> u64 {
+        self.queue.state().dropped
+    }
+}
+
+impl Drop for BackgroundSender {
+    fn drop(&mut self) {
+        self.queue.state().closed = true;
+        self.queue.notify.notify_one();
+    }
+}
+
+impl BackgroundReceiver {
+    /// Next event, or `None` once the sender is gone and the queue is empty.
+    pub(crate) async fn recv(&mut self) -> Option<Value> {
+        loop {
+            if let Some(event) = self.try_recv() {
+                return Some(event);
+            }
+            if self.queue.state().closed {
+                return None;
+            }
+            // A notify with no waiter leaves a permit, so a send between the
+            // check above and this await isn't missed.
+            self.queue.notify.notified().await;
+        }
+    }
+
+    fn try_recv(&mut self) -> Option<Value> {
+        let mut state = self.queue.state();
+        if state.unreported > 0 {
+            let count = std::mem::take(&mut state.unreported);
+            return Some(json!({
+                "method": EVENTS_DROPPED_METHOD,
+                "params": { "count": count },
+            }));
+        }
+        state.events.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{background_channel, EVENTS_DROPPED_METHOD};
+    use serde_json::json;
+
+    #[test]
+    fn drops_oldest_and_reports_a_marker_when_full() {
+        let (tx, mut rx) = background_channel(2);
+        for n in 0..5 {
+            tx.send(json!({ "n": n }));
+        }
+        assert_eq!(tx.depth(), 2);
+        assert_eq!(tx.dropped(), 3);
+
+        let marker = rx.try_recv().expect("marker");
+        assert_eq!(marker["method"], EVENTS_DROPPED_METHOD);
+        assert_eq!(marker["params"]["count"], 3);
+        assert_eq!(rx.try_recv(), Some(json!({ "n": 3 })));
+        assert_eq!(rx.try_recv(), Some(json!({ "n": 4 })));
+        assert_eq!(rx.try_recv(), None);
+        assert_eq!(tx.dropped(), 3);
+    }
+}
@@ -1,82 +1,194 @@
-use serde_json::{json, Value};
-use std::sync::Arc;
-
-use crate::backend::adapter_base::{build_adapter_command, spawn_adapter_session, CliProfile};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::backend::adapter_base::{
+    build_adapter_command, env_var_set, read_json_file, spawn_adapter_session, AdapterSettings,
+    CliAccount, CliCapabilities, CliProfile, ImportedSession, TurnInput,
+};
 use crate::backend::app_server::{CliSpawnConfig, WorkspaceSession};
-use crate::backend::events::EventSink;
+use crate::backend::events::{AppEvent, EventSink, ThreadItem};
+use crate::claude::{models, sessions, settings};
 use crate::types::WorkspaceEntry;
 
-pub(crate) struct ClaudeProfile;
+/// Largest tool input or output carried on a completed tool item; longer
+/// text is cut.
+const TOOL_CAPTURE_MAX_BYTES: usize = 16 * 1024;
+
+#[derive(Default)]
+pub(crate) struct ClaudeProfile {
+    /// Stream state of each thread's running turn.
+    streams: Mutex<HashMap<String, ClaudeStreamState>>,
+    /// Read for `claudeShowThinking`.
+    settings: AdapterSettings,
+}
 
+/// What a turn's stream has said about tool calls that haven't returned.
+/// Arguments arrive as `input_json_delta` pieces of a content block; the
+/// result arrives later as `tool_result`.
+#[derive(Default)]
+pub(crate) struct ClaudeStreamState {
+    /// Tool id and argument JSON received so far, by content block index.
+    blocks: HashMap<u64, (String, String)>,
+    /// Arguments of finished tool_use blocks, by tool id.
+    inputs: HashMap<String, Value>,
+}
+
+#[async_trait::async_trait]
 impl CliProfile for ClaudeProfile {
     fn build_turn_command(
         &self,
         config: &CliSpawnConfig,
         session_id: Option<&str>,
-        prompt: &str,
+        input: &TurnInput,
         cwd: &str,
-        params: &Value,
+        effort: Option<&str>,
+        capabilities: &CliCapabilities,
     ) -> Result<tokio::process::Command, String> {
-        let effort = params.get("effort").and_then(|v| v.as_str());
-        build_claude_command(config, session_id, prompt, cwd, effort)
+        if !input.images.is_empty() && !capabilities.supports("--image") {
+            let prompt = mention_images(&input.text, &input.images);
+            return build_claude_command(config, session_id, &prompt, &[], cwd, effort);
+        }
+        build_claude_command(config, session_id, &input.text, &input.images, cwd, effort)
+    }
+
+    fn probed_flags(&self) -> &'static [&'static str] {
+        &["--image"]
     }
 
     fn parse_stream_line(&self, line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
-        parse_stream_json_line(line, thread_id, turn_id)
+        let mut streams = self.streams.lock().ok()?;
+        let state = streams.entry(thread_id.to_string()).or_default();
+        let show_thinking = self.settings.show_thinking();
+        parse_stream_json_line(line, thread_id, turn_id, state, show_thinking)
     }
 
     fn extract_session_id(&self, line: &str) -> Option<String> {
         extract_session_id_from_line(line)
     }
 
-    fn model_list(&self) -> Value {
-        let standard_efforts = json!([
-            { "reasoningEffort": "low", "description": "Fast, minimal thinking" },
-            { "reasoningEffort": "medium", "description": "Balanced speed and depth" },
-            { "reasoningEffort": "high", "description": "Deep thinking (default)" }
-        ]);
-        let opus_efforts = json!([
-            { "reasoningEffort": "low", "description": "Fast, minimal thinking" },
-            { "reasoningEffort": "medium", "description": "Balanced speed and depth" },
-            { "reasoningEffort": "high", "description": "Deep thinking (default)" },
-            { "reasoningEffort": "max", "description": "Maximum depth, no token limit" }
-        ]);
-        json!({
-            "result": {
-                "models": [
-                    {
-                        "id": "claude-sonnet-4-20250514",
-                        "name": "Claude Sonnet 4",
-                        "supportedReasoningEfforts": standard_efforts,
-                        "defaultReasoningEffort": "high"
-                    },
-                    {
-                        "id": "claude-opus-4-20250514",
-                        "name": "Claude Opus 4",
-                        "supportedReasoningEfforts": opus_efforts,
-                        "defaultReasoningEffort": "high"
-                    },
-                    {
-                        "id": "claude-haiku-4-20250514",
-                        "name": "Claude Haiku 4",
-                        "supportedReasoningEfforts": standard_efforts,
-                        "defaultReasoningEffort": "high"
-                    }
-                ],
-                "defaultModel": "claude-sonnet-4-20250514"
-            }
-        })
+    async fn model_list(&self, config: &CliSpawnConfig) -> Value {
+        models::model_list(config).await
     }
 
     fn provider_name(&self) -> &str {
         "claude"
     }
+
+    fn discover_sessions(&self, cwd: &str, known: &HashSet<String>) -> Vec<ImportedSession> {
+        sessions::list_workspace_sessions(cwd, known)
+    }
+
+    fn read_session_turns(&self, source: &Path) -> Result<Vec<Value>, String> {
+        sessions::read_session_turns(source)
+    }
+
+    fn session_transcript_path(&self, cwd: &str, session_id: &str) -> Option<PathBuf> {
+        sessions::session_path(cwd, session_id)
+    }
+
+    fn read_account(&self, config: &CliSpawnConfig) -> Option<CliAccount> {
+        let [credentials, global_config] = claude_credential_paths(config)?;
+        claude_account(
+            read_json_file(&credentials).as_ref(),
+            read_json_file(&global_config).as_ref(),
+            env_var_set(config, "ANTHROPIC_API_KEY"),
+        )
+    }
+
+    fn credential_paths(&self, config: &CliSpawnConfig) -> Vec<PathBuf> {
+        claude_credential_paths(config)
+            .map(Vec::from)
+            .unwrap_or_default()
+    }
+}
+
+/// `.credentials.json` in the Claude home, where the CLI keeps its OAuth
+/// tokens outside macOS, and `.claude.json`, which names the signed-in
+/// account. The latter sits next to `~/.claude` for the default home.
+fn claude_credential_paths(config: &CliSpawnConfig) -> Option<[PathBuf; 2]> {
+    let home = config
+        .cli_home
+        .clone()
+        .or_else(settings::resolve_default_claude_home)?;
+    let inside = home.join(".claude.json");
+    let global_config = if config.cli_home.is_none()
+        && !inside.exists()
+        && home.file_name().is_some_and(|name| name == ".claude")
+    {
+        home.with_file_name(".claude.json")
+    } else {
+        inside
+    };
+    Some([home.join(".credentials.json"), global_config])
+}
+
+/// An API key wins over a subscription sign-in, as it does in the CLI.
+fn claude_account(
+    credentials: Option<&Value>,
+    global_config: Option<&Value>,
+    api_key: bool,
+) -> Option<CliAccount> {
+    let stored_key = global_config.is_some_and(|config| config.get("primaryApiKey").is_some());
+    if api_key || stored_key {
+        return Some(CliAccount {
+            account_type: "apikey".to_string(),
+            auth_mode: Some("apiKey".to_string()),
+            email: None,
+            plan_type: None,
+        });
+    }
+    let oauth = credentials.and_then(|credentials| credentials.get("claudeAiOauth"));
+    let oauth_account = global_config.and_then(|config| config.get("oauthAccount"));
+    if oauth.is_none() && oauth_account.is_none() {
+        return None;
+    }
+    let text = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+    };
+    Some(CliAccount {
+        account_type: "oauth".to_string(),
+        auth_mode: Some("claudeAi".to_string()),
+        email: text(oauth_account.and_then(|account| account.get("emailAddress"))),
+        plan_type: text(oauth.and_then(|oauth| oauth.get("subscriptionType"))),
+    })
+}
+
+/// Images as `@path` mentions for a CLI without `--image`, which reads the
+/// files itself.
+fn mention_images(prompt: &str, images: &[PathBuf]) -> String {
+    let mut prompt = prompt.to_string();
+    for image in images {
+        if !prompt.is_empty() {
+            prompt.push(' ');
+        }
+        prompt.push('@');
+        prompt.push_str(&image.to_string_lossy().replace(' ', "\\ "));
+    }
+    prompt
+}
+
+/// Extended-thinking token budget for a reasoning effort level.
+fn thinking_budget(effort: &str) -> Option<u32> {
+    match effort {
+        "low" => Some(4_000),
+        "medium" => Some(10_000),
+        "high" => Some(31_999),
+        "max" => Some(128_000),
+        _ => None,
+    }
 }
 
 pub(crate) fn build_claude_command(
     config: &CliSpawnConfig,
     session_id: Option<&str>,
     prompt: &str,
+    images: &[PathBuf],
     cwd: &str,
     effort: Option<&str>,
 ) -> Result<tokio::process::Command, String> {
@@ -90,46 +202,101 @@ pub(crate) fn build_claude_command(
         args.push("--resume".to_string());
         args.push(sid.to_string());
     }
+    for image in images {
+        args.push("--image".to_string());
+        args.push(image.to_string_lossy().to_string());
+    }
     args.push(prompt.to_string());
 
     let home_env = config.cli_home.as_ref().map(|h| ("CLAUDE_HOME", h));
     let mut command = build_adapter_command(config, args, cwd, home_env)?;
+    // Claude Code reads its credentials and settings from here; an account
+    // profile only takes effect with it set.
+    if let Some(home) = config.cli_home.as_ref() {
+        command.env("CLAUDE_CONFIG_DIR", home);
+    }
 
     if let Some(effort_value) = effort {
-        if effort_value == "max" {
-            command.env("CLAUDE_CODE_EFFORT_LEVEL", "high");
-            command.env("CLAUDE_CODE_MAX_THINKING_TOKENS", "128000");
+        let level = if effort_value == "max" {
+            "high"
         } else {
-            command.env("CLAUDE_CODE_EFFORT_LEVEL", effort_value);
+            effort_value
+        };
+        command.env("CLAUDE_CODE_EFFORT_LEVEL", level);
+        if let Some(budget) = thinking_budget(effort_value) {
+            command.env("CLAUDE_CODE_MAX_THINKING_TOKENS", budget.to_string());
         }
     }
 
     Ok(command)
 }
 
+/// Cuts `text` to [`TOOL_CAPTURE_MAX_BYTES`] on a character boundary.
+fn capture_text(text: &str) -> String {
+    if text.len() <= TOOL_CAPTURE_MAX_BYTES {
+        return text.to_string();
+    }
+    let mut end = TOOL_CAPTURE_MAX_BYTES;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}... ({} bytes truncated)", &text[..end], text.len() - end)
+}
+
+/// A tool call's arguments as JSON, or as the raw (possibly cut) text when
+/// they are too large or didn't parse.
+fn tool_input(json: &str) -> Value {
+    if json.trim().is_empty() {
+        return Value::Object(Default::default());
+    }
+    match serde_json::from_str(json) {
+        Ok(input) if json.len() <= TOOL_CAPTURE_MAX_BYTES => input,
+        _ => Value::String(capture_text(json)),
+    }
+}
+
+/// Text of a `tool_result` content: a string, or text blocks.
+fn tool_output(content: &Value) -> Option<String> {
+    let text = match content {
+        Value::Null => return None,
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => blocks
+            .iter()
+            .filter_map(|block| match block {
+                Value::String(text) => Some(text.as_str()),
+                _ => block.get("text").and_then(|text| text.as_str()),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        other => other.to_string(),
+    };
+    Some(capture_text(&text))
+}
+
+/// `show_thinking` streams `thinking` blocks as `item/reasoning/textDelta`.
 pub(crate) fn parse_stream_json_line(
     line: &str,
     thread_id: &str,
     turn_id: &str,
+    state: &mut ClaudeStreamState,
+    show_thinking: bool,
 ) -> Option<Value> {
     let event: Value = serde_json::from_str(line).ok()?;
     let event_type = event.get("type")?.as_str()?;
 
     let msg_item_id = format!("msg_{turn_id}");
 
-    match event_type {
+    let app_event = match event_type {
         "system" => {
             let subtype = event.get("subtype").and_then(|s| s.as_str()).unwrap_or("");
             if subtype == "init" {
-                Some(json!({
-                    "method": "turn/started",
-                    "params": {
-                        "threadId": thread_id,
-                        "turnId": turn_id
-                    }
-                }))
+                *state = ClaudeStreamState::default();
+                AppEvent::TurnStarted {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.to_string(),
+                }
             } else {
-                None
+                return None;
             }
         }
         "content_block_delta" => {
@@ -138,18 +305,32 @@ pub(crate) fn parse_stream_json_line(
             match delta_type {
                 "text_delta" => {
                     let text = delta.get("text")?.as_str()?;
-                    Some(json!({
-                        "method": "item/agentMessage/delta",
-                        "params": {
-                            "threadId": thread_id,
-                            "turnId": turn_id,
-                            "itemId": msg_item_id,
-                            "delta": text
-                        }
-                    }))
+                    AppEvent::AgentMessageDelta {
+                        thread_id: thread_id.to_string(),
+                        turn_id: turn_id.to_string(),
+                        item_id: msg_item_id,
+                        delta: text.to_string(),
+                    }
+                }
+                "thinking_delta" if show_thinking => {
+                    let thinking = delta.get("thinking")?.as_str()?;
+                    let index = event.get("index").and_then(|i| i.as_u64()).unwrap_or(0);
+                    AppEvent::ReasoningTextDelta {
+                        thread_id: thread_id.to_string(),
+                        turn_id: turn_id.to_string(),
+                        item_id: format!("reasoning_{turn_id}_{index}"),
+                        delta: thinking.to_string(),
+                    }
                 }
-                "input_json_delta" => None,
-                _ => None,
+                "input_json_delta" => {
+                    let index = event.get("index").and_then(|i| i.as_u64())?;
+                    let partial = delta.get("partial_json").and_then(|p| p.as_str())?;
+                    if let Some((_, json)) = state.blocks.get_mut(&index) {
+                        json.push_str(partial);
+                    }
+                    return None;
+                }
+                _ => return None,
             }
         }
         "content_block_start" => {
@@ -158,49 +339,75 @@ pub(crate) fn parse_stream_json_line(
             if block_type == "tool_use" {
                 let tool_name = block.get("name").and_then(|n| n.as_str()).unwrap_or("tool");
                 let tool_id = block.get("id").and_then(|i| i.as_str()).unwrap_or("");
-                Some(json!({
-                    "method": "item/started",
-                    "params": {
-                        "threadId": thread_id,
-                        "turnId": turn_id,
-                        "item": {
-                            "id": tool_id,
-                            "type": "tool_use",
-                            "name": tool_name
-                        }
-                    }
-                }))
+                if let Some(index) = event.get("index").and_then(|i| i.as_u64()) {
+                    state
+                        .blocks
+                        .insert(index, (tool_id.to_string(), String::new()));
+                }
+                AppEvent::ItemStarted {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.to_string(),
+                    item: ThreadItem::ToolUse {
+                        id: tool_id.to_string(),
+                        name: Some(tool_name.to_string()),
+                        input: None,
+                        output: None,
+                    },
+                }
             } else {
-                None
+                return None;
             }
         }
+        "content_block_stop" => {
+            let index = event.get("index").and_then(|i| i.as_u64())?;
+            let (tool_id, json) = state.blocks.remove(&index)?;
+            state.inputs.insert(tool_id, tool_input(&json));
+            return None;
+        }
         "tool_result" => {
-            let tool_use_id = event.get("tool_use_id").and_then(|i| i.as_str()).unwrap_or("");
-            Some(json!({
-                "method": "item/completed",
-                "params": {
-                    "threadId": thread_id,
-                    "turnId": turn_id,
-                    "item": {
-                        "id": tool_use_id,
-                        "type": "tool_use"
-                    }
-                }
-            }))
+            let tool_use_id = event
+                .get("tool_use_id")
+                .and_then(|i| i.as_str())
+                .unwrap_or("");
+            let input = state.inputs.remove(tool_use_id).or_else(|| {
+                let index = state
+                    .blocks
+                    .iter()
+                    .find(|(_, (id, _))| id == tool_use_id)
+                    .map(|(index, _)| *index)?;
+                state
+                    .blocks
+                    .remove(&index)
+                    .map(|(_, json)| tool_input(&json))
+            });
+            AppEvent::ItemCompleted {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+                item: ThreadItem::ToolUse {
+                    id: tool_use_id.to_string(),
+                    name: None,
+                    input,
+                    output: event.get("content").and_then(tool_output),
+                },
+            }
         }
         "result" => {
-            Some(json!({
-                "method": "turn/completed",
-                "params": {
-                    "threadId": thread_id,
-                    "turnId": turn_id,
-                    "costUsd": event.get("cost_usd"),
-                    "durationMs": event.get("duration_ms")
-                }
-            }))
+            *state = ClaudeStreamState::default();
+            AppEvent::TurnCompleted {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+                cost_usd: event
+                    .get("total_cost_usd")
+                    .or_else(|| event.get("cost_usd"))
+                    .and_then(|c| c.as_f64()),
+                duration_ms: event.get("duration_ms").and_then(|d| d.as_u64()),
+                exit_code: None,
+                error: None,
+            }
         }
-        _ => None,
-    }
+        _ => return None,
+    };
+    Some(app_event.into_message())
 }
 
 fn extract_session_id_from_line(line: &str) -> Option<String> {
@@ -222,7 +429,11 @@ pub(crate) async fn spawn_claude_session<E: EventSink>(
     config: CliSpawnConfig,
     event_sink: E,
 ) -> Result<Arc<WorkspaceSession>, String> {
-    spawn_adapter_session(ClaudeProfile, "Claude", entry, config, event_sink).await
+    let profile = ClaudeProfile {
+        settings: config.services.adapter_settings.clone(),
+        ..ClaudeProfile::default()
+    };
+    spawn_adapter_session(profile, "Claude", entry, config, event_sink).await
 }
 
 #[cfg(test)]
@@ -231,9 +442,19 @@ mod tests {
     use crate::backend::adapter_base::{GenericAdapterSession, ThreadStore};
     use crate::backend::app_server::CliAdapter;
     use crate::backend::events::AppServerEvent;
+    use crate::backend::exec_target::ExecutionTarget;
+    use crate::shared::maintenance_core::ActiveTurns;
+    use crate::shared::runtime_services::RuntimeServices;
+    use serde_json::json;
     use std::collections::HashMap;
     use tokio::sync::Mutex;
 
+    /// Parses one line with a fresh stream state.
+    fn parse_stream_json_line(line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
+        let mut state = ClaudeStreamState::default();
+        super::parse_stream_json_line(line, thread_id, turn_id, &mut state, false)
+    }
+
     fn test_emitter() -> Arc<dyn Fn(AppServerEvent) + Send + Sync> {
         Arc::new(|_| {})
     }
@@ -254,9 +475,13 @@ mod tests {
             cli_bin: None,
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
         GenericAdapterSession::new(
-            ClaudeProfile,
+            ClaudeProfile::default(),
             &entry,
             config,
             test_emitter(),
@@ -264,6 +489,21 @@ mod tests {
         )
     }
 
+    #[test]
+    fn account_comes_from_credentials_unless_an_api_key_is_set() {
+        let credentials = json!({ "claudeAiOauth": { "subscriptionType": "max" } });
+        let global_config = json!({ "oauthAccount": { "emailAddress": "me@example.com" } });
+        let account =
+            claude_account(Some(&credentials), Some(&global_config), false).expect("signed in");
+        assert_eq!(account.account_type, "oauth");
+        assert_eq!(account.email.as_deref(), Some("me@example.com"));
+        assert_eq!(account.plan_type.as_deref(), Some("max"));
+
+        let account = claude_account(Some(&credentials), None, true).expect("api key");
+        assert_eq!(account.account_type, "apikey");
+        assert!(claude_account(None, Some(&json!({})), false).is_none());
+    }
+
     #[test]
     fn build_claude_command_basic() {
         let config = CliSpawnConfig {
@@ -271,8 +511,12 @@ mod tests {
             cli_bin: Some("claude".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
-        let result = build_claude_command(&config, None, "hello world", "/tmp", None);
+        let result = build_claude_command(&config, None, "hello world", &[], "/tmp", None);
         assert!(result.is_ok());
     }
 
@@ -283,8 +527,12 @@ mod tests {
             cli_bin: Some("claude".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
-        let result = build_claude_command(&config, Some("session-123"), "hello", "/tmp", None);
+        let result = build_claude_command(&config, Some("session-123"), "hello", &[], "/tmp", None);
         assert!(result.is_ok());
     }
 
@@ -295,9 +543,20 @@ mod tests {
             cli_bin: Some("claude".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
-        let result = build_claude_command(&config, None, "hello", "/tmp", Some("low"));
-        assert!(result.is_ok());
+        let command =
+            build_claude_command(&config, None, "hello", &[], "/tmp", Some("low")).unwrap();
+        let envs: HashMap<_, _> = command
+            .as_std()
+            .get_envs()
+            .filter_map(|(key, value)| Some((key.to_str()?, value?.to_str()?)))
+            .collect();
+        assert_eq!(envs.get("CLAUDE_CODE_EFFORT_LEVEL"), Some(&"low"));
+        assert_eq!(envs.get("CLAUDE_CODE_MAX_THINKING_TOKENS"), Some(&"4000"));
     }
 
     #[test]
@@ -307,11 +566,40 @@ mod tests {
             cli_bin: Some("claude".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
-        let result = build_claude_command(&config, None, "hello", "/tmp", Some("max"));
+        let result = build_claude_command(&config, None, "hello", &[], "/tmp", Some("max"));
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn build_claude_command_with_images() {
+        let config = CliSpawnConfig {
+            cli_type: "claude".to_string(),
+            cli_bin: Some("claude".to_string()),
+            cli_args: None,
+            cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
+        };
+        let images = [PathBuf::from("/tmp/shot.png")];
+        let command = build_claude_command(&config, None, "describe", &images, "/tmp", None)
+            .expect("command");
+        let args: Vec<String> = command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        let image_flag = args.iter().position(|arg| arg == "--image").expect("flag");
+        assert_eq!(args[image_flag + 1], "/tmp/shot.png");
+        assert_eq!(args.last().map(String::as_str), Some("describe"));
+    }
+
     #[test]
     fn parse_stream_json_init() {
         let line = r#"{"type":"system","subtype":"init","session_id":"s1","tools":[],"model":"claude-4"}"#;
@@ -359,6 +647,47 @@ mod tests {
         assert!(parse_stream_json_line(line, "t1", "turn1").is_none());
     }
 
+    #[test]
+    fn tool_input_and_result_are_captured_on_completion() {
+        let mut state = ClaudeStreamState::default();
+        let lines = [
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","name":"Read","id":"tool-1","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"file_path\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"src/main.rs\"}"}}"#,
+            r#"{"type":"content_block_stop","index":1}"#,
+        ];
+        for line in lines {
+            super::parse_stream_json_line(line, "t1", "turn1", &mut state, false);
+        }
+        let long = "x".repeat(TOOL_CAPTURE_MAX_BYTES + 10);
+        let result = json!({
+            "type": "tool_result",
+            "tool_use_id": "tool-1",
+            "content": [{ "type": "text", "text": long }]
+        })
+        .to_string();
+        let event =
+            super::parse_stream_json_line(&result, "t1", "turn1", &mut state, false).unwrap();
+        let item = &event["params"]["item"];
+        assert_eq!(item["input"], json!({ "file_path": "src/main.rs" }));
+        let output = item["output"].as_str().unwrap();
+        assert!(output.starts_with(&long[..TOOL_CAPTURE_MAX_BYTES]));
+        assert!(output.ends_with("... (10 bytes truncated)"));
+        assert!(state.inputs.is_empty() && state.blocks.is_empty());
+    }
+
+    #[test]
+    fn thinking_deltas_stream_as_reasoning_when_enabled() {
+        let line = r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Check the tests."}}"#;
+        assert!(parse_stream_json_line(line, "t1", "turn1").is_none());
+
+        let mut state = ClaudeStreamState::default();
+        let event = super::parse_stream_json_line(line, "t1", "turn1", &mut state, true).unwrap();
+        assert_eq!(event["method"], "item/reasoning/textDelta");
+        assert_eq!(event["params"]["itemId"], "reasoning_turn1_0");
+        assert_eq!(event["params"]["delta"], "Check the tests.");
+    }
+
     #[test]
     fn parse_stream_json_tool_result_emits_item_completed() {
         let line = r#"{"type":"tool_result","tool_use_id":"tool-1","content":"done"}"#;
@@ -374,6 +703,7 @@ mod tests {
     const SUPPORTED_METHODS: &[&str] = &[
         "item/agentMessage/delta",
         "item/completed",
+        "item/reasoning/textDelta",
         "item/started",
         "turn/completed",
         "turn/started",
@@ -452,6 +782,10 @@ mod tests {
                 created_at: 1000,
                 updated_at: 2000,
                 archived: false,
+                source_path: None,
+                transcript_path: None,
+                compacted_summary: None,
+                last_turn_failure: None,
             },
         );
         store.save(&path).unwrap();
@@ -531,19 +865,15 @@ mod tests {
         let result = adapter.send_request("model/list", json!({})).await.unwrap();
         let models = result["result"]["models"].as_array().unwrap();
 
+        assert!(!models.is_empty());
         for model in models {
-            assert!(model.get("supportedReasoningEfforts").is_some());
             assert!(model.get("defaultReasoningEffort").is_some());
+            // Discovery may return any model set, so check the effort rules
+            // rather than specific ids.
+            let efforts = model["supportedReasoningEfforts"].as_array().unwrap();
+            let is_opus = model["id"].as_str().unwrap().contains("opus");
+            assert_eq!(efforts.len(), if is_opus { 4 } else { 3 });
+            assert_eq!(efforts.iter().any(|e| e["reasoningEffort"] == "max"), is_opus);
         }
-
-        let opus = models.iter().find(|m| m["id"] == "claude-opus-4-20250514").unwrap();
-        let opus_efforts = opus["supportedReasoningEfforts"].as_array().unwrap();
-        assert_eq!(opus_efforts.len(), 4);
-        assert!(opus_efforts.iter().any(|e| e["reasoningEffort"] == "max"));
-
-        let sonnet = models.iter().find(|m| m["id"] == "claude-sonnet-4-20250514").unwrap();
-        let sonnet_efforts = sonnet["supportedReasoningEfforts"].as_array().unwrap();
-        assert_eq!(sonnet_efforts.len(), 3);
-        assert!(!sonnet_efforts.iter().any(|e| e["reasoningEffort"] == "max"));
     }
 }
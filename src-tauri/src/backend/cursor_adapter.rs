@@ -1,23 +1,36 @@
 use serde_json::{json, Value};
 use std::sync::Arc;
 
-use crate::backend::adapter_base::{build_adapter_command, spawn_adapter_session, CliProfile};
+use crate::backend::adapter_base::{
+    build_adapter_command, spawn_adapter_session, CliCapabilities, CliProfile, TurnInput,
+};
 use crate::backend::app_server::{CliSpawnConfig, WorkspaceSession};
-use crate::backend::events::EventSink;
+use crate::backend::events::{AppEvent, EventSink, ThreadItem};
 use crate::types::WorkspaceEntry;
 
 pub(crate) struct CursorProfile;
 
+#[async_trait::async_trait]
 impl CliProfile for CursorProfile {
     fn build_turn_command(
         &self,
         config: &CliSpawnConfig,
         session_id: Option<&str>,
-        prompt: &str,
+        input: &TurnInput,
         cwd: &str,
-        _params: &Value,
+        _effort: Option<&str>,
+        capabilities: &CliCapabilities,
     ) -> Result<tokio::process::Command, String> {
-        build_cursor_command(config, session_id, prompt, cwd)
+        if !input.images.is_empty() {
+            return Err("The Cursor CLI doesn't accept image attachments.".to_string());
+        }
+        // Without `--resume` each turn starts a new CLI session.
+        let session_id = session_id.filter(|_| capabilities.supports("--resume"));
+        build_cursor_command(config, session_id, &input.text, cwd)
+    }
+
+    fn probed_flags(&self) -> &'static [&'static str] {
+        &["--resume"]
     }
 
     fn parse_stream_line(&self, line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
@@ -28,7 +41,7 @@ impl CliProfile for CursorProfile {
         extract_cursor_session_id(line)
     }
 
-    fn model_list(&self) -> Value {
+    async fn model_list(&self, _config: &CliSpawnConfig) -> Value {
         json!({
             "result": {
                 "models": [],
@@ -72,19 +85,16 @@ pub(crate) fn parse_cursor_stream_line(
 
     let msg_item_id = format!("msg_{turn_id}");
 
-    match event_type {
+    let app_event = match event_type {
         "system" => {
             let subtype = event.get("subtype").and_then(|s| s.as_str()).unwrap_or("");
             if subtype == "init" {
-                Some(json!({
-                    "method": "turn/started",
-                    "params": {
-                        "threadId": thread_id,
-                        "turnId": turn_id
-                    }
-                }))
+                AppEvent::TurnStarted {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.to_string(),
+                }
             } else {
-                None
+                return None;
             }
         }
         "assistant" => {
@@ -95,15 +105,12 @@ pub(crate) fn parse_cursor_stream_line(
                 .and_then(|arr| arr.first())
                 .and_then(|item| item.get("text"))
                 .and_then(|t| t.as_str())?;
-            Some(json!({
-                "method": "item/agentMessage/delta",
-                "params": {
-                    "threadId": thread_id,
-                    "turnId": turn_id,
-                    "itemId": msg_item_id,
-                    "delta": text
-                }
-            }))
+            AppEvent::AgentMessageDelta {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+                item_id: msg_item_id,
+                delta: text.to_string(),
+            }
         }
         "tool_call" => {
             let subtype = event.get("subtype").and_then(|s| s.as_str()).unwrap_or("");
@@ -115,43 +122,41 @@ pub(crate) fn parse_cursor_stream_line(
             match subtype {
                 "started" => {
                     let tool_name = extract_tool_name_from_cursor_event(&event);
-                    Some(json!({
-                        "method": "item/started",
-                        "params": {
-                            "threadId": thread_id,
-                            "turnId": turn_id,
-                            "item": {
-                                "id": call_id,
-                                "type": "tool_use",
-                                "name": tool_name
-                            }
-                        }
-                    }))
-                }
-                "completed" => Some(json!({
-                    "method": "item/completed",
-                    "params": {
-                        "threadId": thread_id,
-                        "turnId": turn_id,
-                        "item": {
-                            "id": call_id,
-                            "type": "tool_use"
-                        }
+                    AppEvent::ItemStarted {
+                        thread_id: thread_id.to_string(),
+                        turn_id: turn_id.to_string(),
+                        item: ThreadItem::ToolUse {
+                            id: call_id.to_string(),
+                            name: Some(tool_name.to_string()),
+                            input: None,
+                            output: None,
+                        },
                     }
-                })),
-                _ => None,
+                }
+                "completed" => AppEvent::ItemCompleted {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.to_string(),
+                    item: ThreadItem::ToolUse {
+                        id: call_id.to_string(),
+                        name: None,
+                        input: None,
+                        output: None,
+                    },
+                },
+                _ => return None,
             }
         }
-        "result" => Some(json!({
-            "method": "turn/completed",
-            "params": {
-                "threadId": thread_id,
-                "turnId": turn_id,
-                "durationMs": event.get("duration_ms")
-            }
-        })),
-        _ => None,
-    }
+        "result" => AppEvent::TurnCompleted {
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+            cost_usd: None,
+            duration_ms: event.get("duration_ms").and_then(|d| d.as_u64()),
+            exit_code: None,
+            error: None,
+        },
+        _ => return None,
+    };
+    Some(app_event.into_message())
 }
 
 fn extract_tool_name_from_cursor_event(event: &Value) -> &str {
@@ -193,6 +198,10 @@ pub(crate) async fn spawn_cursor_session<E: EventSink>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::exec_target::ExecutionTarget;
+    use crate::shared::maintenance_core::ActiveTurns;
+    use crate::shared::runtime_services::RuntimeServices;
+    use std::collections::HashMap;
 
     #[test]
     fn build_cursor_command_basic() {
@@ -201,6 +210,10 @@ mod tests {
             cli_bin: Some("cursor".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
         let result = build_cursor_command(&config, None, "hello", "/tmp");
         assert!(result.is_ok());
@@ -213,6 +226,10 @@ mod tests {
             cli_bin: Some("cursor".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
         let result = build_cursor_command(&config, Some("sess-1"), "hello", "/tmp");
         assert!(result.is_ok());
@@ -0,0 +1,218 @@
+use std::ffi::OsString;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use crate::backend::exec_target::LOCAL_ONLY_ENV;
+use crate::shared::process_core::tokio_command;
+use crate::types::DockerTarget;
+
+const DEFAULT_WORKDIR: &str = "/workspace";
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn workdir(target: &DockerTarget) -> &str {
+    target
+        .workdir
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .unwrap_or(DEFAULT_WORKDIR)
+}
+
+fn container(target: &DockerTarget) -> Option<&str> {
+    target
+        .container
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+fn image(target: &DockerTarget) -> Option<&str> {
+    target
+        .image
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+}
+
+/// Where a `docker exec`'d CLI records its pid, so it can be signalled.
+fn pid_file(run_name: &str) -> String {
+    format!("/tmp/{run_name}.pid")
+}
+
+/// Variables to set in the container: those set on `local` that aren't
+/// local paths, then the target's own.
+fn container_env(
+    local: &std::process::Command,
+    target: &DockerTarget,
+) -> Vec<(OsString, OsString)> {
+    let mut env = local
+        .get_envs()
+        .filter_map(|(name, value)| Some((name.to_os_string(), value?.to_os_string())))
+        .filter(|(name, _)| !LOCAL_ONLY_ENV.contains(&name.to_string_lossy().as_ref()))
+        .collect::<Vec<_>>();
+    for (name, value) in &target.env {
+        env.retain(|(existing, _)| existing != name.as_str());
+        env.push((name.into(), value.into()));
+    }
+    env
+}
+
+/// Turns a CLI command built for this machine into one that runs it in the
+/// target's container, started with the workspace (`cwd`) mounted at the
+/// working directory or exec'd into a running container. Variables are
+/// passed with `-e NAME` and read from the docker client's environment, so
+/// their values stay off the command line. `run_name` names the container
+/// (or the pid file of an exec) so [`stop_container_run`] can end it.
+pub(crate) fn over_docker(
+    local: &std::process::Command,
+    target: &DockerTarget,
+    cwd: &str,
+    run_name: &str,
+) -> Result<Command, String> {
+    let workdir = workdir(target);
+    let env = container_env(local, target);
+    let mut command = tokio_command("docker");
+    match (container(target), image(target)) {
+        (Some(container), _) => {
+            command.args(["exec", "-i", "-w", workdir]);
+            for (name, _) in &env {
+                command.arg("-e").arg(name);
+            }
+            command.arg(container);
+            // Killing the `docker exec` client leaves the CLI running, so it
+            // records its pid for `stop_container_run`.
+            command.args(["sh", "-c", "echo $$ > \"$0\" && exec \"$@\""]);
+            command.arg(pid_file(run_name));
+        }
+        (None, Some(image)) => {
+            command.args(["run", "--rm", "-i", "--name", run_name]);
+            command.arg("-v").arg(format!("{cwd}:{workdir}"));
+            for mount in target
+                .mounts
+                .iter()
+                .filter(|mount| !mount.trim().is_empty())
+            {
+                command.arg("-v").arg(mount.trim());
+            }
+            command.args(["-w", workdir]);
+            for (name, _) in &env {
+                command.arg("-e").arg(name);
+            }
+            command.arg(image);
+        }
+        (None, None) => {
+            return Err("The Docker target needs an image or a container".to_string());
+        }
+    }
+    command.arg(local.get_program());
+    command.args(local.get_args());
+    command.envs(env);
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    Ok(command)
+}
+
+async fn run_docker(args: Vec<String>) {
+    let mut command = tokio_command("docker");
+    command.args(args);
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::null());
+    command.stderr(Stdio::null());
+    command.kill_on_drop(true);
+    let _ = tokio::time::timeout(STOP_TIMEOUT, command.status()).await;
+}
+
+/// Removes a container a crashed run left behind, which would otherwise
+/// block the next `docker run --name`.
+pub(crate) async fn clear_container_run(target: &DockerTarget, run_name: &str) {
+    if container(target).is_none() {
+        run_docker(vec!["rm".into(), "-f".into(), run_name.into()]).await;
+    }
+}
+
+/// Ends what `run_name` started in the container: the run's own container,
+/// or the exec'd CLI, signalled through its pid file.
+pub(crate) async fn stop_container_run(target: &DockerTarget, run_name: &str) {
+    match container(target) {
+        Some(container) => {
+            run_docker(vec![
+                "exec".into(),
+                container.into(),
+                "sh".into(),
+                "-c".into(),
+                "kill -TERM \"$(cat \"$0\")\" 2>/dev/null; rm -f \"$0\"".into(),
+                pid_file(run_name),
+            ])
+            .await;
+        }
+        None => clear_container_run(target, run_name).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::over_docker;
+    use crate::types::DockerTarget;
+
+    fn args(command: &tokio::process::Command) -> Vec<String> {
+        command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn runs_or_execs_the_cli_in_the_container() {
+        let mut local = std::process::Command::new("claude");
+        local.args(["-p", "hi"]);
+        local.env("PATH", "/usr/bin");
+        local.env("API_TOKEN", "secret");
+        let mut target = DockerTarget {
+            image: Some("node:22".to_string()),
+            mounts: vec!["/cache:/root/.cache".to_string()],
+            env: HashMap::from([("CI".to_string(), "1".to_string())]),
+            ..DockerTarget::default()
+        };
+
+        let run = over_docker(&local, &target, "/src/app", "agent-monitor-ws").expect("run");
+        assert_eq!(
+            args(&run),
+            [
+                "run",
+                "--rm",
+                "-i",
+                "--name",
+                "agent-monitor-ws",
+                "-v",
+                "/src/app:/workspace",
+                "-v",
+                "/cache:/root/.cache",
+                "-w",
+                "/workspace",
+                "-e",
+                "API_TOKEN",
+                "-e",
+                "CI",
+                "node:22",
+                "claude",
+                "-p",
+                "hi"
+            ]
+        );
+        assert!(!args(&run).iter().any(|arg| arg.contains("secret")));
+
+        target.container = Some("devbox".to_string());
+        let exec = over_docker(&local, &target, "/src/app", "agent-monitor-ws").expect("exec");
+        assert_eq!(args(&exec)[..4], ["exec", "-i", "-w", "/workspace"]);
+        assert!(args(&exec).contains(&"/tmp/agent-monitor-ws.pid".to_string()));
+
+        target = DockerTarget::default();
+        assert!(over_docker(&local, &target, "/src/app", "agent-monitor-ws").is_err());
+    }
+}
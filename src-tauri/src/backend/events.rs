@@ -1,12 +1,438 @@
 use serde::Serialize;
 use serde_json::Value;
 
+use crate::backend::process_monitor::ProcessStats;
+use crate::backend::turn_metrics::TurnMetrics;
+use crate::shared::budget_core::BudgetStatus;
+use crate::shared::file_changes_core::FileChangeSummary;
+use crate::shared::patch_core::PatchFile;
+use crate::types::GuardrailAction;
+
 #[derive(Serialize, Clone)]
 pub(crate) struct AppServerEvent {
     pub(crate) workspace_id: String,
     pub(crate) message: Value,
 }
 
+impl AppServerEvent {
+    pub(crate) fn new(workspace_id: impl Into<String>, event: AppEvent) -> Self {
+        Self {
+            workspace_id: workspace_id.into(),
+            message: event.into_message(),
+        }
+    }
+}
+
+/// An item shown in a thread's turn, as emitted by the CLI adapters.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type")]
+pub(crate) enum ThreadItem {
+    /// A tool call. `name` is only known when the call starts; `input` and
+    /// `output` when it completes, for CLIs that stream them.
+    #[serde(rename = "tool_use")]
+    ToolUse {
+        id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        input: Option<Value>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+    },
+    /// An assistant message, completed with the text its deltas streamed.
+    #[serde(rename = "agentMessage")]
+    AgentMessage { id: String, text: String },
+    /// Workspace files attached to the prompt, or mentioned for the CLI to read.
+    #[serde(rename = "context")]
+    Context {
+        id: String,
+        mode: String,
+        files: Vec<Value>,
+    },
+    #[serde(rename = "contextCompaction")]
+    ContextCompaction {
+        id: String,
+        status: String,
+        error: Option<String>,
+    },
+    #[serde(rename = "enteredReviewMode")]
+    EnteredReviewMode { id: String, review: String },
+    #[serde(rename = "exitedReviewMode")]
+    ExitedReviewMode { id: String, review: String },
+    /// Outcome of the workspace's post-turn hooks; `status` is `verified`
+    /// or `failed`.
+    #[serde(rename = "verification")]
+    Verification {
+        id: String,
+        status: String,
+        hooks: Vec<HookResult>,
+    },
+    /// A unified diff found in agent message `sourceItemId`, split into
+    /// files and hunks. `diff` is what `patch_apply` applies.
+    #[serde(rename = "patch")]
+    Patch {
+        id: String,
+        #[serde(rename = "sourceItemId")]
+        source_item_id: String,
+        files: Vec<PatchFile>,
+        diff: String,
+    },
+    /// Outcome of the workspace's pre-turn hooks; `status` is `ready`,
+    /// `failed` (a failing hook was set to continue) or `aborted`.
+    #[serde(rename = "preparation")]
+    Preparation {
+        id: String,
+        status: String,
+        hooks: Vec<HookResult>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HookResult {
+    pub(crate) command: String,
+    /// `None` when the command couldn't start or was killed.
+    pub(crate) exit_code: Option<i32>,
+    pub(crate) timed_out: bool,
+    pub(crate) duration_ms: u64,
+    pub(crate) error: Option<String>,
+}
+
+impl HookResult {
+    pub(crate) fn passed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// Notifications the monitor itself emits as `app-server-event` messages,
+/// serialized as `{ "method": ..., "params": {...} }`. Messages forwarded
+/// verbatim from the Codex app-server stay raw JSON.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "method", content = "params")]
+pub(crate) enum AppEvent {
+    #[serde(rename = "turn/started", rename_all = "camelCase")]
+    TurnStarted { thread_id: String, turn_id: String },
+    #[serde(rename = "item/agentMessage/delta", rename_all = "camelCase")]
+    AgentMessageDelta {
+        thread_id: String,
+        turn_id: String,
+        item_id: String,
+        delta: String,
+    },
+    /// Sent ahead of `turn/completed` when an adapter turn ran past its
+    /// timeout and its CLI was killed.
+    #[serde(rename = "turn/timeout", rename_all = "camelCase")]
+    TurnTimeout {
+        thread_id: String,
+        turn_id: String,
+        timeout_secs: u64,
+    },
+    /// Sent ahead of `turn/completed` when an adapter CLI exits non-zero.
+    #[serde(rename = "turn/failed", rename_all = "camelCase")]
+    TurnFailed {
+        thread_id: String,
+        turn_id: String,
+        exit_code: i32,
+        /// The last stderr lines the CLI wrote.
+        stderr: Vec<String>,
+        error: String,
+    },
+    #[serde(rename = "item/reasoning/textDelta", rename_all = "camelCase")]
+    ReasoningTextDelta {
+        thread_id: String,
+        turn_id: String,
+        item_id: String,
+        delta: String,
+    },
+    #[serde(rename = "item/started", rename_all = "camelCase")]
+    ItemStarted {
+        thread_id: String,
+        turn_id: String,
+        item: ThreadItem,
+    },
+    #[serde(rename = "item/completed", rename_all = "camelCase")]
+    ItemCompleted {
+        thread_id: String,
+        turn_id: String,
+        item: ThreadItem,
+    },
+    #[serde(rename = "item/patch", rename_all = "camelCase")]
+    ItemPatch {
+        thread_id: String,
+        turn_id: Option<String>,
+        item: ThreadItem,
+    },
+    #[serde(rename = "item/context", rename_all = "camelCase")]
+    ItemContext {
+        thread_id: String,
+        turn_id: String,
+        item: ThreadItem,
+    },
+    /// Every adapter reports the same fields; ones its CLI doesn't provide
+    /// are `null`.
+    #[serde(rename = "turn/completed", rename_all = "camelCase")]
+    TurnCompleted {
+        thread_id: String,
+        turn_id: String,
+        cost_usd: Option<f64>,
+        duration_ms: Option<u64>,
+        exit_code: Option<i32>,
+        error: Option<String>,
+    },
+    #[serde(rename = "turn/retrying", rename_all = "camelCase")]
+    TurnRetrying {
+        thread_id: String,
+        turn_id: String,
+        attempt: u32,
+        max_attempts: u32,
+        delay_ms: u64,
+        reason: String,
+        message: String,
+    },
+    #[serde(rename = "turn/queued", rename_all = "camelCase")]
+    TurnQueued {
+        workspace_id: String,
+        thread_id: String,
+        position: usize,
+    },
+    #[serde(rename = "turn/metrics", rename_all = "camelCase")]
+    TurnMetrics {
+        workspace_id: String,
+        metrics: TurnMetrics,
+    },
+    #[serde(rename = "thread/compacted", rename_all = "camelCase")]
+    ThreadCompacted {
+        thread_id: String,
+        turn_id: String,
+        /// Estimated from the transcript size; unknown without one.
+        tokens_before: Option<u64>,
+        tokens_after: u64,
+    },
+    #[serde(rename = "account/rateLimits/updated")]
+    RateLimitsUpdated(Value),
+    /// An adapter CLI's credential files changed on disk. `account` is the
+    /// new `account/read` account, `null` when signed out.
+    #[serde(rename = "account/changed", rename_all = "camelCase")]
+    AccountChanged {
+        workspace_id: String,
+        provider: String,
+        account: Value,
+    },
+    #[serde(rename = "budget/warning", rename_all = "camelCase")]
+    BudgetWarning {
+        workspace_id: String,
+        thread_id: String,
+        status: BudgetStatus,
+    },
+    /// `turn_refused` is set when the turn about to start was not run.
+    #[serde(rename = "budget/exceeded", rename_all = "camelCase")]
+    BudgetExceeded {
+        workspace_id: String,
+        thread_id: String,
+        status: BudgetStatus,
+        turn_refused: bool,
+    },
+    #[serde(rename = "files/changedByAgent", rename_all = "camelCase")]
+    FilesChangedByAgent {
+        workspace_id: String,
+        thread_id: String,
+        turn_id: Option<String>,
+        paths: Vec<String>,
+        added: Vec<String>,
+        modified: Vec<String>,
+        deleted: Vec<String>,
+    },
+    /// Files changed since the previous `files/changed` event of the same
+    /// running turn.
+    #[serde(rename = "files/changed", rename_all = "camelCase")]
+    FilesChanged {
+        workspace_id: String,
+        thread_id: String,
+        turn_id: Option<String>,
+        files: Vec<FileChangeSummary>,
+    },
+    /// Content appended to a followed file since the last event. `reset`
+    /// means the file shrank and is followed again from the start; `error`
+    /// ends the subscription.
+    #[serde(rename = "file/tail", rename_all = "camelCase")]
+    FileTail {
+        workspace_id: String,
+        subscription_id: String,
+        path: String,
+        offset: u64,
+        content: String,
+        reset: bool,
+        error: Option<String>,
+    },
+    #[serde(rename = "git/committed", rename_all = "camelCase")]
+    GitCommitted {
+        workspace_id: String,
+        thread_id: String,
+        turn_id: Option<String>,
+        sha: String,
+        message: String,
+    },
+    #[serde(rename = "git/prProgress", rename_all = "camelCase")]
+    GitPrProgress {
+        workspace_id: String,
+        stage: String,
+        message: String,
+    },
+    #[serde(rename = "codex/connected", rename_all = "camelCase")]
+    Connected { workspace_id: String },
+    #[serde(rename = "codex/disconnected", rename_all = "camelCase")]
+    Disconnected {
+        workspace_id: String,
+        exit_code: Option<i32>,
+    },
+    #[serde(rename = "codex/restarting", rename_all = "camelCase")]
+    Restarting {
+        workspace_id: String,
+        attempt: u32,
+        delay_ms: u64,
+    },
+    #[serde(rename = "codex/restartFailed", rename_all = "camelCase")]
+    RestartFailed { workspace_id: String, attempts: u32 },
+    #[serde(rename = "codex/parseError")]
+    ParseError { error: String, raw: String },
+    #[serde(rename = "codex/stderr")]
+    Stderr { message: String },
+    /// A line a Claude/Gemini/Cursor CLI wrote to stderr during a turn.
+    #[serde(rename = "cli/stderr", rename_all = "camelCase")]
+    CliStderr {
+        thread_id: String,
+        turn_id: String,
+        message: String,
+    },
+    /// Hides a helper thread (commit messages, PR text, run metadata) from
+    /// the sidebar.
+    #[serde(rename = "codex/backgroundThread", rename_all = "camelCase")]
+    BackgroundThread { thread_id: String, action: String },
+    #[serde(rename = "debug/rpc", rename_all = "camelCase")]
+    DebugRpc {
+        workspace_id: String,
+        direction: String,
+        timestamp: u64,
+        latency_ms: Option<u64>,
+        message: Value,
+    },
+    #[serde(rename = "approval/autoResolved", rename_all = "camelCase")]
+    ApprovalAutoResolved {
+        request_id: Value,
+        method: String,
+        decision: String,
+        waited_seconds: u64,
+    },
+    #[serde(rename = "approval/timeout", rename_all = "camelCase")]
+    ApprovalTimeout {
+        request_id: Value,
+        method: String,
+        waited_seconds: u64,
+        escalation: u32,
+    },
+    #[serde(rename = "process/stats", rename_all = "camelCase")]
+    ProcessStats {
+        workspace_id: String,
+        stats: ProcessStats,
+        turn_cpu_time_ms: u64,
+    },
+    /// A tool call's input matched one of the workspace's guardrails; with
+    /// the `interrupt` action the turn was interrupted.
+    #[serde(rename = "guardrail/triggered", rename_all = "camelCase")]
+    GuardrailTriggered {
+        workspace_id: String,
+        thread_id: String,
+        turn_id: Option<String>,
+        item_id: String,
+        pattern: String,
+        action: GuardrailAction,
+        /// The input line the pattern matched in.
+        excerpt: String,
+    },
+    #[serde(rename = "process/limitExceeded", rename_all = "camelCase")]
+    ProcessLimitExceeded {
+        workspace_id: String,
+        thread_ids: Vec<String>,
+        limit: String,
+        value: u64,
+        max: u64,
+        action: String,
+    },
+    #[serde(rename = "maintenance/progress")]
+    MaintenanceProgress(Value),
+    /// A line of installer output from `cli_update`; the last one has
+    /// `done` set.
+    #[serde(rename = "cli/updateProgress")]
+    CliUpdateProgress {
+        name: String,
+        message: String,
+        done: bool,
+    },
+    #[serde(rename = "remote/connection")]
+    RemoteConnection {
+        status: String,
+        queued: usize,
+        error: Option<String>,
+    },
+    /// Sent while quitting waits for running turns. `phase` is `draining`,
+    /// `interrupting` or `stopping`.
+    #[serde(rename = "shutdown/progress", rename_all = "camelCase")]
+    ShutdownProgress {
+        phase: String,
+        running_turns: usize,
+        remaining_secs: u64,
+    },
+    /// The app settings were replaced, e.g. by switching settings profile.
+    #[serde(rename = "settings/changed")]
+    SettingsChanged {
+        profile: Option<String>,
+        settings: Value,
+    },
+    /// A line a post-turn hook wrote; `stream` is `stdout` or `stderr`.
+    #[serde(rename = "hook/output", rename_all = "camelCase")]
+    HookOutput {
+        workspace_id: String,
+        thread_id: String,
+        turn_id: Option<String>,
+        command: String,
+        stream: String,
+        line: String,
+    },
+    /// A workflow step changed state. `status` is `started`, `retrying`,
+    /// `completed` or `failed`; `output` is set once completed.
+    #[serde(rename = "workflow/step", rename_all = "camelCase")]
+    WorkflowStep {
+        run_id: String,
+        workflow: String,
+        step_index: usize,
+        attempt: u32,
+        status: String,
+        output: Option<String>,
+        error: Option<String>,
+    },
+    /// A workflow run ended; `status` is `completed` or `failed`.
+    #[serde(rename = "workflow/completed", rename_all = "camelCase")]
+    WorkflowCompleted {
+        run_id: String,
+        workflow: String,
+        status: String,
+        error: Option<String>,
+    },
+}
+
+impl AppEvent {
+    pub(crate) fn background_thread_hidden(thread_id: impl Into<String>) -> Self {
+        Self::BackgroundThread {
+            thread_id: thread_id.into(),
+            action: "hide".to_string(),
+        }
+    }
+
+    pub(crate) fn into_message(self) -> Value {
+        serde_json::to_value(self).unwrap_or_default()
+    }
+}
+
 #[derive(Debug, Serialize, Clone)]
 pub(crate) struct TerminalOutput {
     #[serde(rename = "workspaceId")]
@@ -29,3 +455,144 @@ pub(crate) trait EventSink: Clone + Send + Sync + 'static {
     fn emit_terminal_output(&self, event: TerminalOutput);
     fn emit_terminal_exit(&self, event: TerminalExit);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{AppEvent, AppServerEvent, ThreadItem};
+    use crate::backend::claude_adapter::parse_stream_json_line;
+    use crate::backend::cursor_adapter::parse_cursor_stream_line;
+    use crate::backend::gemini_adapter::parse_gemini_stream_line;
+    use serde_json::{json, Value};
+
+    type StreamParser = fn(&str, &str, &str) -> Option<Value>;
+
+    /// The event's keys, with every leaf value replaced by `null`.
+    fn shape(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), shape(value)))
+                    .collect(),
+            ),
+            _ => Value::Null,
+        }
+    }
+
+    #[test]
+    fn adapters_emit_identical_event_shapes() {
+        let streams: [(&str, StreamParser, [&str; 5]); 3] = [
+            (
+                "claude",
+                |line, thread_id, turn_id| {
+                    parse_stream_json_line(line, thread_id, turn_id, &mut Default::default(), false)
+                },
+                [
+                    r#"{"type":"system","subtype":"init","session_id":"s1"}"#,
+                    r#"{"type":"content_block_delta","delta":{"type":"text_delta","text":"hi"}}"#,
+                    r#"{"type":"content_block_start","content_block":{"type":"tool_use","id":"t1","name":"Read"}}"#,
+                    r#"{"type":"tool_result","tool_use_id":"t1"}"#,
+                    r#"{"type":"result","total_cost_usd":0.01,"duration_ms":5}"#,
+                ],
+            ),
+            (
+                "gemini",
+                parse_gemini_stream_line,
+                [
+                    r#"{"type":"init","session_id":"s1"}"#,
+                    r#"{"type":"message","role":"assistant","content":"hi"}"#,
+                    r#"{"type":"tool_use","tool_name":"Read","tool_id":"t1"}"#,
+                    r#"{"type":"tool_result","tool_id":"t1","status":"success"}"#,
+                    r#"{"type":"result","stats":{"duration_ms":5}}"#,
+                ],
+            ),
+            (
+                "cursor",
+                parse_cursor_stream_line,
+                [
+                    r#"{"type":"system","subtype":"init","session_id":"s1"}"#,
+                    r#"{"type":"assistant","message":{"content":[{"text":"hi"}]}}"#,
+                    r#"{"type":"tool_call","subtype":"started","call_id":"t1","readToolCall":{}}"#,
+                    r#"{"type":"tool_call","subtype":"completed","call_id":"t1"}"#,
+                    r#"{"type":"result","duration_ms":5}"#,
+                ],
+            ),
+        ];
+        let shapes = streams.map(|(name, parse, lines)| {
+            lines.map(|line| {
+                let event = parse(line, "thread-1", "turn-1")
+                    .unwrap_or_else(|| panic!("{name} dropped {line}"));
+                shape(&event)
+            })
+        });
+        for (index, (name, _, _)) in streams.iter().enumerate().skip(1) {
+            assert_eq!(shapes[index], shapes[0], "{name} differs from claude");
+        }
+
+        let [started, delta, tool_started, tool_completed, completed] = &shapes[0];
+        assert_eq!(
+            started,
+            &json!({ "method": null, "params": { "threadId": null, "turnId": null } })
+        );
+        assert_eq!(
+            delta["params"],
+            json!({ "threadId": null, "turnId": null, "itemId": null, "delta": null })
+        );
+        assert_eq!(
+            tool_started["params"]["item"],
+            json!({ "id": null, "type": null, "name": null })
+        );
+        assert_eq!(
+            tool_completed["params"]["item"],
+            json!({ "id": null, "type": null })
+        );
+        assert_eq!(
+            completed["params"],
+            json!({
+                "threadId": null,
+                "turnId": null,
+                "costUsd": null,
+                "durationMs": null,
+                "exitCode": null,
+                "error": null
+            })
+        );
+    }
+
+    #[test]
+    fn serializes_method_and_camel_case_params() {
+        let event = AppServerEvent::new(
+            "ws-1",
+            AppEvent::ItemStarted {
+                thread_id: "thread-1".to_string(),
+                turn_id: "turn-1".to_string(),
+                item: ThreadItem::EnteredReviewMode {
+                    id: "review_turn-1".to_string(),
+                    review: "uncommitted changes".to_string(),
+                },
+            },
+        );
+        assert_eq!(event.workspace_id, "ws-1");
+        assert_eq!(
+            event.message,
+            json!({
+                "method": "item/started",
+                "params": {
+                    "threadId": "thread-1",
+                    "turnId": "turn-1",
+                    "item": {
+                        "type": "enteredReviewMode",
+                        "id": "review_turn-1",
+                        "review": "uncommitted changes"
+                    }
+                }
+            })
+        );
+        assert_eq!(
+            AppEvent::background_thread_hidden("thread-2").into_message(),
+            json!({
+                "method": "codex/backgroundThread",
+                "params": { "threadId": "thread-2", "action": "hide" }
+            })
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use std::time::Duration;
+
+use tokio::process::Command;
+
+use crate::backend::app_server::{build_codex_command_with_bin, check_cli_installation};
+use crate::backend::{docker, ssh};
+use crate::types::{DockerTarget, SshTarget, WorkspaceSettings};
+
+/// Variables the spawn layer sets to local paths; they mean nothing on a
+/// remote host or in a container, which use their own PATH and CLI homes.
+pub(crate) const LOCAL_ONLY_ENV: [&str; 6] = [
+    "PATH",
+    "CODEX_HOME",
+    "CLAUDE_HOME",
+    "CLAUDE_CONFIG_DIR",
+    "GEMINI_HOME",
+    "GEMINI_CLI_SYSTEM_SETTINGS_PATH",
+];
+const SSH_CHECK_TIMEOUT: Duration = Duration::from_secs(20);
+/// Long enough for `docker run` to pull the image first.
+const DOCKER_CHECK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Where a workspace's CLI runs. Commands are built for this machine and
+/// then wrapped for the target.
+#[derive(Clone, Debug, Default)]
+pub(crate) enum ExecutionTarget {
+    #[default]
+    Local,
+    Ssh(SshTarget),
+    Docker(DockerTarget),
+}
+
+/// Names the container (or exec pid file) of a workspace's CLI. A
+/// workspace runs one CLI process at a time.
+pub(crate) fn run_name(workspace_id: &str) -> String {
+    let id = workspace_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("agent-monitor-{id}")
+}
+
+impl ExecutionTarget {
+    pub(crate) fn for_workspace(settings: &WorkspaceSettings) -> Self {
+        if let Some(docker) = settings.docker.clone() {
+            return Self::Docker(docker);
+        }
+        match settings.ssh.clone() {
+            Some(ssh) => Self::Ssh(ssh),
+            None => Self::Local,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Self::Local => "this machine".to_string(),
+            Self::Ssh(target) => target.destination.clone(),
+            Self::Docker(target) => target
+                .container
+                .clone()
+                .or_else(|| target.image.clone())
+                .unwrap_or_else(|| "Docker".to_string()),
+        }
+    }
+
+    /// Turns `command`, built to run the CLI here in `cwd`, into one that
+    /// runs it on the target. Wrapped commands have stdin closed and
+    /// stdout/stderr piped.
+    pub(crate) fn wrap(
+        &self,
+        command: Command,
+        cwd: &str,
+        run_name: &str,
+    ) -> Result<Command, String> {
+        match self {
+            Self::Local => Ok(command),
+            Self::Ssh(target) => ssh::over_ssh(command.as_std(), target),
+            Self::Docker(target) => docker::over_docker(command.as_std(), target, cwd, run_name),
+        }
+    }
+
+    /// Runs `<bin> --version` where the CLI will run.
+    pub(crate) async fn check_installation(
+        &self,
+        cli_bin: Option<String>,
+        cli_name: &str,
+        cwd: &str,
+        run_name: &str,
+    ) -> Result<Option<String>, String> {
+        let limit = match self {
+            Self::Local => {
+                return check_cli_installation(cli_bin, cli_name)
+                    .await
+                    .map_err(String::from)
+            }
+            Self::Ssh(_) => SSH_CHECK_TIMEOUT,
+            Self::Docker(_) => DOCKER_CHECK_TIMEOUT,
+        };
+        let version = build_codex_command_with_bin(cli_bin, None, vec!["--version".to_string()])?;
+        let mut command = self.wrap(version, cwd, &format!("{run_name}-check"))?;
+        command.kill_on_drop(true);
+        let label = self.label();
+        let output = tokio::time::timeout(limit, command.output())
+            .await
+            .map_err(|_| format!("Timed out checking the {cli_name} CLI on {label}"))?
+            .map_err(|err| format!("Failed to reach {label}: {err}"))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            // ssh itself exits with 255 when it can't connect or authenticate.
+            let what = if matches!(self, Self::Ssh(_)) && output.status.code() == Some(255) {
+                format!("Could not connect to {label}")
+            } else {
+                format!("{cli_name} CLI failed on {label}")
+            };
+            return Err(format!("{what}: {}", stderr.trim()));
+        }
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok(Some(version).filter(|version| !version.is_empty()))
+    }
+
+    /// Called before a CLI starts.
+    pub(crate) async fn prepare(&self, run_name: &str) {
+        if let Self::Docker(target) = self {
+            docker::clear_container_run(target, run_name).await;
+        }
+    }
+
+    /// Called after the local process of a run was killed, to end what it
+    /// started on the target. Over SSH, closing the connection ends it.
+    pub(crate) async fn stop(&self, run_name: &str) {
+        if let Self::Docker(target) = self {
+            docker::stop_container_run(target, run_name).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_name, ExecutionTarget};
+    use crate::types::{DockerTarget, SshTarget, WorkspaceSettings};
+
+    #[test]
+    fn docker_takes_precedence_over_ssh() {
+        let mut settings = WorkspaceSettings::default();
+        assert!(matches!(
+            ExecutionTarget::for_workspace(&settings),
+            ExecutionTarget::Local
+        ));
+        settings.ssh = Some(SshTarget {
+            destination: "me@build:/srv".to_string(),
+            port: None,
+            identity_file: None,
+        });
+        assert!(matches!(
+            ExecutionTarget::for_workspace(&settings),
+            ExecutionTarget::Ssh(_)
+        ));
+        settings.docker = Some(DockerTarget::default());
+        assert!(matches!(
+            ExecutionTarget::for_workspace(&settings),
+            ExecutionTarget::Docker(_)
+        ));
+        assert_eq!(run_name("ws 1/a"), "agent-monitor-ws-1-a");
+    }
+}
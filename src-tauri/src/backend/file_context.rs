@@ -0,0 +1,107 @@
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::files::io::read_text_file_within;
+use crate::files::policy::workspace_read_policy;
+
+/// Total bytes of file content inlined into one prompt.
+const FILE_CONTEXT_BUDGET_BYTES: usize = 100_000;
+
+/// Files read for a turn's `fileRef` input items.
+#[derive(Debug, Default)]
+pub(crate) struct FileContext {
+    /// Text to put ahead of the user's prompt.
+    pub(crate) prompt: String,
+    /// `{ path, bytes, truncated }` for each attached file, or `{ path, error }`.
+    pub(crate) files: Vec<Value>,
+}
+
+fn truncate_to_char_boundary(content: &str, max_bytes: usize) -> &str {
+    if content.len() <= max_bytes {
+        return content;
+    }
+    let mut end = max_bytes;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    &content[..end]
+}
+
+/// Reads workspace files under `workspace_read_policy` (no paths or symlinks
+/// leading outside the workspace) and inlines them, truncated to a shared
+/// budget. Files that can't be read are reported rather than failing the
+/// turn.
+pub(crate) fn build_file_context(workspace_root: &Path, paths: &[String]) -> FileContext {
+    let policy = workspace_read_policy();
+    let mut context = FileContext::default();
+    let mut remaining = FILE_CONTEXT_BUDGET_BYTES;
+    for path in paths {
+        let result = read_text_file_within(
+            workspace_root,
+            path,
+            policy.root_may_be_missing,
+            policy.root_context,
+            path,
+            policy.allow_external_symlink_target,
+        );
+        let response = match result {
+            Ok(response) if response.exists => response,
+            Ok(_) => {
+                context
+                    .files
+                    .push(json!({ "path": path, "error": "File not found" }));
+                continue;
+            }
+            Err(error) => {
+                context.files.push(json!({ "path": path, "error": error }));
+                continue;
+            }
+        };
+        let included = truncate_to_char_boundary(&response.content, remaining);
+        let truncated = included.len() < response.content.len();
+        remaining -= included.len();
+        context.prompt.push_str(&format!(
+            "<file path=\"{path}\"{}>\n{included}\n</file>\n\n",
+            if truncated { " truncated=\"true\"" } else { "" }
+        ));
+        context.files.push(json!({
+            "path": path,
+            "bytes": included.len(),
+            "truncated": truncated,
+        }));
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_file_context, truncate_to_char_boundary};
+    use uuid::Uuid;
+
+    #[test]
+    fn inlines_workspace_files_and_reports_failures() {
+        let root = std::env::temp_dir().join(format!("file-context-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(root.join("src")).expect("create workspace");
+        std::fs::write(root.join("src/lib.rs"), "pub fn answer() -> u32 { 42 }").expect("write");
+
+        let context = build_file_context(
+            &root,
+            &[
+                "src/lib.rs".to_string(),
+                "missing.rs".to_string(),
+                "../outside.txt".to_string(),
+            ],
+        );
+
+        assert!(context
+            .prompt
+            .starts_with("<file path=\"src/lib.rs\">\npub fn answer()"));
+        assert_eq!(context.files.len(), 3);
+        assert_eq!(context.files[0]["truncated"], false);
+        assert_eq!(context.files[1]["error"], "File not found");
+        assert!(context.files[2].get("error").is_some());
+        assert_eq!(truncate_to_char_boundary("héllo", 2), "h");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
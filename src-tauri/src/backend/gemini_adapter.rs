@@ -1,26 +1,57 @@
 use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use crate::backend::adapter_base::{build_adapter_command, spawn_adapter_session, CliProfile};
+use crate::backend::adapter_base::{
+    build_adapter_command, env_var_set, read_json_file, spawn_adapter_session, CliAccount,
+    CliCapabilities, CliProfile, TurnInput,
+};
 use crate::backend::app_server::{CliSpawnConfig, WorkspaceSession};
-use crate::backend::events::EventSink;
+use crate::backend::events::{AppEvent, EventSink, ThreadItem};
+use crate::shared::atomic_write::write_atomic;
 use crate::types::WorkspaceEntry;
 
-pub(crate) struct GeminiProfile;
+#[derive(Default)]
+pub(crate) struct GeminiProfile {
+    /// The CLI predates `--output-format stream-json`, so its stdout is the
+    /// reply as plain text.
+    plain_output: AtomicBool,
+}
 
+#[async_trait::async_trait]
 impl CliProfile for GeminiProfile {
     fn build_turn_command(
         &self,
         config: &CliSpawnConfig,
         session_id: Option<&str>,
-        prompt: &str,
+        input: &TurnInput,
         cwd: &str,
-        _params: &Value,
+        effort: Option<&str>,
+        capabilities: &CliCapabilities,
     ) -> Result<tokio::process::Command, String> {
-        build_gemini_command(config, session_id, prompt, cwd)
+        let stream_json = capabilities.supports("--output-format");
+        self.plain_output.store(!stream_json, Ordering::Relaxed);
+        // Without `--resume` each turn starts a new CLI session.
+        let session_id = session_id.filter(|_| capabilities.supports("--resume"));
+        build_gemini_command(
+            config,
+            session_id,
+            &gemini_prompt(input),
+            cwd,
+            effort,
+            stream_json,
+        )
+    }
+
+    fn probed_flags(&self) -> &'static [&'static str] {
+        &["--output-format", "--resume"]
     }
 
     fn parse_stream_line(&self, line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
+        if self.plain_output.load(Ordering::Relaxed) {
+            return parse_gemini_text_line(line, thread_id, turn_id);
+        }
         parse_gemini_stream_line(line, thread_id, turn_id)
     }
 
@@ -28,12 +59,27 @@ impl CliProfile for GeminiProfile {
         extract_gemini_session_id(line)
     }
 
-    fn model_list(&self) -> Value {
+    async fn model_list(&self, _config: &CliSpawnConfig) -> Value {
+        let efforts = json!([
+            { "reasoningEffort": "low", "description": "Small thinking budget" },
+            { "reasoningEffort": "medium", "description": "Moderate thinking budget" },
+            { "reasoningEffort": "high", "description": "Model decides (default)" }
+        ]);
         json!({
             "result": {
                 "models": [
-                    { "id": "gemini-2.5-flash", "name": "Gemini 2.5 Flash" },
-                    { "id": "gemini-2.5-pro", "name": "Gemini 2.5 Pro" }
+                    {
+                        "id": "gemini-2.5-flash",
+                        "name": "Gemini 2.5 Flash",
+                        "supportedReasoningEfforts": efforts,
+                        "defaultReasoningEffort": "high"
+                    },
+                    {
+                        "id": "gemini-2.5-pro",
+                        "name": "Gemini 2.5 Pro",
+                        "supportedReasoningEfforts": efforts,
+                        "defaultReasoningEffort": "high"
+                    }
                 ],
                 "defaultModel": "gemini-2.5-flash"
             }
@@ -43,6 +89,136 @@ impl CliProfile for GeminiProfile {
     fn provider_name(&self) -> &str {
         "gemini"
     }
+
+    fn supports_file_mentions(&self) -> bool {
+        true
+    }
+
+    fn read_account(&self, config: &CliSpawnConfig) -> Option<CliAccount> {
+        let home = gemini_home(config)?;
+        gemini_account(
+            read_json_file(&home.join("settings.json")).as_ref(),
+            read_json_file(&home.join("google_accounts.json")).as_ref(),
+            home.join("oauth_creds.json").is_file(),
+            env_var_set(config, "GEMINI_API_KEY"),
+        )
+    }
+
+    fn credential_paths(&self, config: &CliSpawnConfig) -> Vec<PathBuf> {
+        gemini_home(config)
+            .map(|home| {
+                ["settings.json", "google_accounts.json", "oauth_creds.json"]
+                    .into_iter()
+                    .map(|name| home.join(name))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn gemini_home(config: &CliSpawnConfig) -> Option<PathBuf> {
+    config
+        .cli_home
+        .clone()
+        .or_else(|| dirs_next::home_dir().map(|home| home.join(".gemini")))
+}
+
+/// The account from the auth type picked in the CLI's settings, the Google
+/// account it last signed in with, and whether OAuth tokens are stored.
+fn gemini_account(
+    settings: Option<&Value>,
+    google_accounts: Option<&Value>,
+    oauth_creds: bool,
+    api_key: bool,
+) -> Option<CliAccount> {
+    let selected = settings
+        .and_then(|settings| {
+            settings
+                .pointer("/security/auth/selectedType")
+                .or_else(|| settings.get("selectedAuthType"))
+        })
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let account_type = match selected.as_deref() {
+        Some("gemini-api-key") | Some("vertex-ai") => "apikey",
+        Some("oauth-personal") if !oauth_creds => return None,
+        Some(_) => "oauth",
+        None if oauth_creds => "oauth",
+        None if api_key => "apikey",
+        None => return None,
+    };
+    let email = google_accounts
+        .filter(|_| account_type == "oauth")
+        .and_then(|accounts| accounts.get("active"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    Some(CliAccount {
+        account_type: account_type.to_string(),
+        auth_mode: selected,
+        email,
+        plan_type: None,
+    })
+}
+
+/// Attaches images and `fileRef` files as `@path` references, which the
+/// Gemini CLI reads and sends as file parts. Spaces in paths are escaped the
+/// way the CLI expects.
+fn gemini_prompt(input: &TurnInput) -> String {
+    let images = input.images.iter().map(|image| image.to_string_lossy());
+    let files = input.files.iter().map(|file| file.as_str().into());
+    let mut prompt = input.text.clone();
+    for path in images.chain(files) {
+        if !prompt.is_empty() {
+            prompt.push(' ');
+        }
+        prompt.push('@');
+        prompt.push_str(&path.replace(' ', "\\ "));
+    }
+    prompt
+}
+
+/// Lets the thinking settings below replace the CLI's system settings file,
+/// which takes precedence over user and workspace settings.
+const SYSTEM_SETTINGS_ENV: &str = "GEMINI_CLI_SYSTEM_SETTINGS_PATH";
+
+/// Thinking token budget for a reasoning effort level; -1 lets the model
+/// decide.
+fn thinking_budget(effort: &str) -> Option<i64> {
+    match effort {
+        "low" => Some(1_024),
+        "medium" => Some(8_192),
+        "high" => Some(-1),
+        _ => None,
+    }
+}
+
+/// Settings that apply the thinking budget to every model's generation config.
+fn thinking_settings(budget: i64) -> Value {
+    json!({
+        "modelConfigs": {
+            "overrides": [{
+                "match": {},
+                "modelConfig": {
+                    "generateContentConfig": {
+                        "thinkingConfig": { "thinkingBudget": budget }
+                    }
+                }
+            }]
+        }
+    })
+}
+
+fn write_thinking_settings(effort: &str, budget: i64) -> Result<PathBuf, String> {
+    let dir = dirs_next::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("agent-monitor")
+        .join("gemini-settings");
+    std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+    let path = dir.join(format!("thinking-{effort}.json"));
+    let contents =
+        serde_json::to_string_pretty(&thinking_settings(budget)).map_err(|err| err.to_string())?;
+    write_atomic(&path, contents.as_bytes()).map_err(|err| err.to_string())?;
+    Ok(path)
 }
 
 pub(crate) fn build_gemini_command(
@@ -50,12 +226,15 @@ pub(crate) fn build_gemini_command(
     session_id: Option<&str>,
     prompt: &str,
     cwd: &str,
+    effort: Option<&str>,
+    stream_json: bool,
 ) -> Result<tokio::process::Command, String> {
-    let mut args = vec![
-        "--output-format".to_string(),
-        "stream-json".to_string(),
-        "-p".to_string(),
-    ];
+    let mut args = Vec::new();
+    if stream_json {
+        args.push("--output-format".to_string());
+        args.push("stream-json".to_string());
+    }
+    args.push("-p".to_string());
     if let Some(sid) = session_id {
         args.push("--resume".to_string());
         args.push(sid.to_string());
@@ -63,7 +242,33 @@ pub(crate) fn build_gemini_command(
     args.push(prompt.to_string());
 
     let home_env = config.cli_home.as_ref().map(|h| ("GEMINI_HOME", h));
-    build_adapter_command(config, args, cwd, home_env)
+    let mut command = build_adapter_command(config, args, cwd, home_env)?;
+
+    // A system settings file set in the workspace env is left in place.
+    if let Some(effort_value) = effort {
+        if let Some(budget) = thinking_budget(effort_value) {
+            if !config.env.contains_key(SYSTEM_SETTINGS_ENV) {
+                let path = write_thinking_settings(effort_value, budget)?;
+                command.env(SYSTEM_SETTINGS_ENV, path);
+            }
+        }
+    }
+
+    Ok(command)
+}
+
+/// A line of plain-text output from a CLI without stream-json, as part of
+/// the reply.
+fn parse_gemini_text_line(line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
+    Some(
+        AppEvent::AgentMessageDelta {
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+            item_id: format!("msg_{turn_id}"),
+            delta: format!("{line}\n"),
+        }
+        .into_message(),
+    )
 }
 
 pub(crate) fn parse_gemini_stream_line(
@@ -76,29 +281,23 @@ pub(crate) fn parse_gemini_stream_line(
 
     let msg_item_id = format!("msg_{turn_id}");
 
-    match event_type {
-        "init" => Some(json!({
-            "method": "turn/started",
-            "params": {
-                "threadId": thread_id,
-                "turnId": turn_id
-            }
-        })),
+    let app_event = match event_type {
+        "init" => AppEvent::TurnStarted {
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+        },
         "message" => {
             let role = event.get("role").and_then(|r| r.as_str()).unwrap_or("");
             if role != "assistant" {
                 return None;
             }
             let content = event.get("content").and_then(|c| c.as_str())?;
-            Some(json!({
-                "method": "item/agentMessage/delta",
-                "params": {
-                    "threadId": thread_id,
-                    "turnId": turn_id,
-                    "itemId": msg_item_id,
-                    "delta": content
-                }
-            }))
+            AppEvent::AgentMessageDelta {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+                item_id: msg_item_id,
+                delta: content.to_string(),
+            }
         }
         "tool_use" => {
             let tool_name = event
@@ -109,46 +308,47 @@ pub(crate) fn parse_gemini_stream_line(
                 .get("tool_id")
                 .and_then(|i| i.as_str())
                 .unwrap_or("");
-            Some(json!({
-                "method": "item/started",
-                "params": {
-                    "threadId": thread_id,
-                    "turnId": turn_id,
-                    "item": {
-                        "id": tool_id,
-                        "type": "tool_use",
-                        "name": tool_name
-                    }
-                }
-            }))
+            AppEvent::ItemStarted {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+                item: ThreadItem::ToolUse {
+                    id: tool_id.to_string(),
+                    name: Some(tool_name.to_string()),
+                    input: None,
+                    output: None,
+                },
+            }
         }
         "tool_result" => {
             let tool_id = event
                 .get("tool_id")
                 .and_then(|i| i.as_str())
                 .unwrap_or("");
-            Some(json!({
-                "method": "item/completed",
-                "params": {
-                    "threadId": thread_id,
-                    "turnId": turn_id,
-                    "item": {
-                        "id": tool_id,
-                        "type": "tool_use"
-                    }
-                }
-            }))
-        }
-        "result" => Some(json!({
-            "method": "turn/completed",
-            "params": {
-                "threadId": thread_id,
-                "turnId": turn_id,
-                "durationMs": event.get("stats").and_then(|s| s.get("duration_ms"))
+            AppEvent::ItemCompleted {
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.to_string(),
+                item: ThreadItem::ToolUse {
+                    id: tool_id.to_string(),
+                    name: None,
+                    input: None,
+                    output: None,
+                },
             }
-        })),
-        _ => None,
-    }
+        }
+        "result" => AppEvent::TurnCompleted {
+            thread_id: thread_id.to_string(),
+            turn_id: turn_id.to_string(),
+            cost_usd: None,
+            duration_ms: event
+                .get("stats")
+                .and_then(|s| s.get("duration_ms"))
+                .and_then(|d| d.as_u64()),
+            exit_code: None,
+            error: None,
+        },
+        _ => return None,
+    };
+    Some(app_event.into_message())
 }
 
 fn extract_gemini_session_id(line: &str) -> Option<String> {
@@ -167,12 +367,23 @@ pub(crate) async fn spawn_gemini_session<E: EventSink>(
     config: CliSpawnConfig,
     event_sink: E,
 ) -> Result<Arc<WorkspaceSession>, String> {
-    spawn_adapter_session(GeminiProfile, "Gemini", entry, config, event_sink).await
+    spawn_adapter_session(
+        GeminiProfile::default(),
+        "Gemini",
+        entry,
+        config,
+        event_sink,
+    )
+    .await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::backend::exec_target::ExecutionTarget;
+    use crate::shared::maintenance_core::ActiveTurns;
+    use crate::shared::runtime_services::RuntimeServices;
+    use std::collections::HashMap;
 
     #[test]
     fn build_gemini_command_basic() {
@@ -181,8 +392,12 @@ mod tests {
             cli_bin: Some("gemini".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
-        let result = build_gemini_command(&config, None, "hello", "/tmp");
+        let result = build_gemini_command(&config, None, "hello", "/tmp", None, true);
         assert!(result.is_ok());
     }
 
@@ -193,11 +408,112 @@ mod tests {
             cli_bin: Some("gemini".to_string()),
             cli_args: None,
             cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
         };
-        let result = build_gemini_command(&config, Some("sess-1"), "hello", "/tmp");
+        let result = build_gemini_command(&config, Some("sess-1"), "hello", "/tmp", None, true);
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn account_follows_the_selected_auth_type() {
+        let settings = json!({ "security": { "auth": { "selectedType": "oauth-personal" } } });
+        let accounts = json!({ "active": "me@example.com", "old": [] });
+        let account = gemini_account(Some(&settings), Some(&accounts), true, false).expect("oauth");
+        assert_eq!(account.account_type, "oauth");
+        assert_eq!(account.auth_mode.as_deref(), Some("oauth-personal"));
+        assert_eq!(account.email.as_deref(), Some("me@example.com"));
+        assert!(gemini_account(Some(&settings), Some(&accounts), false, false).is_none());
+
+        let legacy = json!({ "selectedAuthType": "gemini-api-key" });
+        let account = gemini_account(Some(&legacy), Some(&accounts), false, true).expect("key");
+        assert_eq!(account.account_type, "apikey");
+        assert!(account.email.is_none());
+        assert!(gemini_account(None, None, false, false).is_none());
+    }
+
+    #[test]
+    fn older_cli_without_stream_json_streams_plain_text() {
+        let config = CliSpawnConfig {
+            cli_type: "gemini".to_string(),
+            cli_bin: Some("gemini".to_string()),
+            cli_args: None,
+            cli_home: None,
+            env: HashMap::new(),
+            target: ExecutionTarget::Local,
+            active_turns: ActiveTurns::default(),
+            services: RuntimeServices::default(),
+        };
+        let capabilities = CliCapabilities {
+            version: Some("0.1.5".to_string()),
+            flags: [
+                ("--output-format".to_string(), false),
+                ("--resume".to_string(), false),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let profile = GeminiProfile::default();
+        let command = profile
+            .build_turn_command(
+                &config,
+                Some("sess-1"),
+                &TurnInput {
+                    text: "hello".to_string(),
+                    ..TurnInput::default()
+                },
+                "/tmp",
+                None,
+                &capabilities,
+            )
+            .expect("command");
+        let args: Vec<String> = command
+            .as_std()
+            .get_args()
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect();
+        assert!(!args
+            .iter()
+            .any(|arg| arg == "--output-format" || arg == "--resume"));
+
+        let event = profile
+            .parse_stream_line("Hi there", "t1", "turn1")
+            .expect("delta");
+        assert_eq!(event["method"], "item/agentMessage/delta");
+        assert_eq!(event["params"]["delta"], "Hi there\n");
+    }
+
+    #[test]
+    fn thinking_settings_follow_effort() {
+        assert_eq!(thinking_budget("low"), Some(1_024));
+        assert_eq!(thinking_budget("high"), Some(-1));
+        assert_eq!(thinking_budget("max"), None);
+        let settings = thinking_settings(8_192);
+        assert_eq!(
+            settings["modelConfigs"]["overrides"][0]["modelConfig"]["generateContentConfig"]
+                ["thinkingConfig"]["thinkingBudget"],
+            8_192
+        );
+    }
+
+    #[test]
+    fn gemini_prompt_references_images() {
+        let input = TurnInput {
+            text: "What changed?".to_string(),
+            images: vec![
+                std::path::PathBuf::from("/tmp/before.png"),
+                std::path::PathBuf::from("/tmp/my shots/after.png"),
+            ],
+            files: vec!["src/app.ts".to_string()],
+        };
+        assert_eq!(
+            gemini_prompt(&input),
+            "What changed? @/tmp/before.png @/tmp/my\\ shots/after.png @src/app.ts"
+        );
+    }
+
     #[test]
     fn parse_init_event() {
         let line = r#"{"type":"init","session_id":"gs-1","model":"gemini-2.5-flash"}"#;
@@ -0,0 +1,166 @@
+use regex::Regex;
+use serde_json::Value;
+
+use crate::backend::approvals::is_approval_request_method;
+use crate::types::{GuardrailAction, GuardrailRule};
+
+/// Item and request fields that carry what a tool call is about to do.
+const INPUT_FIELDS: &[&str] = &[
+    "command",
+    "input",
+    "arguments",
+    "changes",
+    "fileChanges",
+    "patch",
+];
+/// Longest excerpt of the matched input reported in `guardrail/triggered`.
+const EXCERPT_MAX_CHARS: usize = 200;
+
+pub(crate) struct Guardrail {
+    regex: Regex,
+    pub(crate) pattern: String,
+    pub(crate) action: GuardrailAction,
+}
+
+/// A tool call input that matched a guardrail.
+pub(crate) struct GuardrailHit<'a> {
+    pub(crate) guardrail: &'a Guardrail,
+    pub(crate) excerpt: String,
+}
+
+/// Rejects the first pattern that doesn't compile.
+pub(crate) fn validate_guardrails(rules: &[GuardrailRule]) -> Result<(), String> {
+    for rule in rules {
+        Regex::new(&rule.pattern)
+            .map_err(|err| format!("Invalid guardrail pattern `{}`: {err}", rule.pattern))?;
+    }
+    Ok(())
+}
+
+/// Compiles the rules, skipping blank patterns and ones that don't compile.
+pub(crate) fn compile_guardrails(rules: &[GuardrailRule]) -> Vec<Guardrail> {
+    rules
+        .iter()
+        .filter(|rule| !rule.pattern.trim().is_empty())
+        .filter_map(|rule| {
+            Some(Guardrail {
+                regex: Regex::new(&rule.pattern).ok()?,
+                pattern: rule.pattern.clone(),
+                action: rule.action,
+            })
+        })
+        .collect()
+}
+
+fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(parts) if parts.iter().all(Value::is_string) => parts
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" "),
+        other => other.to_string(),
+    }
+}
+
+/// The id and input of the tool call an `item/started`, `item/completed` or
+/// approval request carries. Items without input (messages, reasoning)
+/// give `None`. Claude reports a tool's input only once it completes.
+pub(crate) fn tool_call_input(message: &Value) -> Option<(String, String)> {
+    let method = message.get("method")?.as_str()?;
+    let params = message.get("params")?;
+    let (id, fields) = if method == "item/started" || method == "item/completed" {
+        let item = params.get("item")?;
+        (item.get("id")?.as_str()?.to_string(), item)
+    } else if is_approval_request_method(method) {
+        let id = params
+            .get("itemId")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| message.get("id").map(Value::to_string).unwrap_or_default());
+        (id, params)
+    } else {
+        return None;
+    };
+    let input = INPUT_FIELDS
+        .iter()
+        .filter_map(|field| fields.get(*field))
+        .filter(|value| !value.is_null())
+        .map(field_text)
+        .collect::<Vec<_>>()
+        .join("\n");
+    (!input.trim().is_empty()).then_some((id, input))
+}
+
+/// The guardrail `input` trips, preferring ones that interrupt the turn.
+pub(crate) fn match_guardrails<'a>(
+    guardrails: &'a [Guardrail],
+    input: &str,
+) -> Option<GuardrailHit<'a>> {
+    let (guardrail, found) = guardrails
+        .iter()
+        .filter_map(|guardrail| Some((guardrail, guardrail.regex.find(input)?)))
+        .min_by_key(|(guardrail, _)| guardrail.action != GuardrailAction::Interrupt)?;
+    let line = input[..found.start()]
+        .rfind('\n')
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let excerpt = input[line..].lines().next().unwrap_or_default();
+    Some(GuardrailHit {
+        guardrail,
+        excerpt: excerpt.chars().take(EXCERPT_MAX_CHARS).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compile_guardrails, match_guardrails, tool_call_input, validate_guardrails};
+    use crate::types::{GuardrailAction, GuardrailRule};
+    use serde_json::json;
+
+    #[test]
+    fn matches_tool_call_inputs_preferring_interrupts() {
+        let rules = vec![
+            GuardrailRule {
+                pattern: r"rm\s+-rf".to_string(),
+                action: GuardrailAction::Warn,
+            },
+            GuardrailRule {
+                pattern: r"git push (-f|--force)".to_string(),
+                action: GuardrailAction::Interrupt,
+            },
+        ];
+        let guardrails = compile_guardrails(&rules);
+
+        let started = json!({
+            "method": "item/started",
+            "params": {
+                "threadId": "t-1",
+                "item": {
+                    "id": "cmd-1",
+                    "type": "commandExecution",
+                    "command": ["bash", "-lc", "rm -rf target && git push --force"]
+                }
+            }
+        });
+        let (id, input) = tool_call_input(&started).expect("tool call input");
+        assert_eq!(id, "cmd-1");
+        let hit = match_guardrails(&guardrails, &input).expect("guardrail hit");
+        assert_eq!(hit.guardrail.action, GuardrailAction::Interrupt);
+        assert_eq!(hit.excerpt, "bash -lc rm -rf target && git push --force");
+
+        let message = json!({
+            "method": "item/completed",
+            "params": { "item": { "id": "msg-1", "type": "agentMessage", "text": "rm -rf" } }
+        });
+        assert!(tool_call_input(&message).is_none());
+
+        let invalid = vec![GuardrailRule {
+            pattern: "(".to_string(),
+            action: GuardrailAction::Warn,
+        }];
+        assert!(validate_guardrails(&invalid).is_err());
+        assert!(compile_guardrails(&invalid).is_empty());
+    }
+}
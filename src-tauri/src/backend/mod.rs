@@ -1,6 +1,20 @@
 pub(crate) mod adapter_base;
 pub(crate) mod app_server;
+pub(crate) mod approvals;
+pub(crate) mod background_queue;
 pub(crate) mod claude_adapter;
 pub(crate) mod cursor_adapter;
+pub(crate) mod docker;
 pub(crate) mod events;
+pub(crate) mod exec_target;
+pub(crate) mod file_context;
 pub(crate) mod gemini_adapter;
+pub(crate) mod guardrails;
+pub(crate) mod plugin_adapter;
+pub(crate) mod process_monitor;
+pub(crate) mod rate_limits;
+pub(crate) mod review;
+pub(crate) mod ssh;
+pub(crate) mod turn_hooks;
+pub(crate) mod turn_metrics;
+pub(crate) mod workspace_hooks;
@@ -0,0 +1,540 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use crate::backend::adapter_base::{
+    build_adapter_command, spawn_adapter_session, CliCapabilities, CliProfile, TurnInput,
+};
+use crate::backend::app_server::{build_codex_command_with_bin, CliSpawnConfig, WorkspaceSession};
+use crate::backend::events::{AppEvent, EventSink, ThreadItem};
+use crate::types::WorkspaceEntry;
+
+const PLUGINS_DIR: &str = "plugins";
+const MODEL_LIST_TIMEOUT: Duration = Duration::from_secs(10);
+/// CLI types with a built-in adapter, which plugins can't replace.
+const BUILTIN_CLI_TYPES: &[&str] = &["codex", "claude", "gemini", "cursor"];
+
+/// A CLI adapter described by a `.toml` or `.json` manifest in the
+/// plugins directory.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PluginManifest {
+    /// The `cliType` that selects the plugin.
+    pub(crate) id: String,
+    /// Display name; defaults to `id`.
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    /// The CLI binary.
+    pub(crate) command: String,
+    /// Arguments for each turn. `{prompt}` and `{cwd}` are filled in; the
+    /// prompt is appended when no argument mentions it.
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    /// Flag that resumes a CLI session, passed with the session id before
+    /// `args`. Without it every turn starts a new CLI session.
+    #[serde(default)]
+    pub(crate) resume_flag: Option<String>,
+    /// Arguments that make the CLI print its models, one id per line.
+    #[serde(default)]
+    pub(crate) model_list_command: Option<Vec<String>>,
+    #[serde(default)]
+    pub(crate) stream: StreamMapping,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum StreamFormat {
+    /// One JSON event per line, mapped by `rules`.
+    #[default]
+    Jsonl,
+    /// Plain text; every line is assistant message text.
+    Text,
+}
+
+/// How the CLI's stdout maps onto turn events.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StreamMapping {
+    #[serde(default)]
+    pub(crate) format: StreamFormat,
+    /// JSON pointer to the CLI session id, read from any event that has it.
+    #[serde(default)]
+    pub(crate) session_id: Option<String>,
+    /// Checked in order; the first rule whose `match` fits an event maps it.
+    #[serde(default)]
+    pub(crate) rules: Vec<StreamRule>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum RuleEvent {
+    TurnStarted,
+    MessageDelta,
+    ToolStarted,
+    ToolCompleted,
+    TurnCompleted,
+    /// Matched events are dropped.
+    Ignore,
+}
+
+/// Maps matching events to `emit`. The other fields are JSON pointers into
+/// the event for the emitted event's values.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct StreamRule {
+    /// JSON pointers and the values the event must have there.
+    #[serde(default, rename = "match")]
+    pub(crate) matches: BTreeMap<String, Value>,
+    pub(crate) emit: RuleEvent,
+    #[serde(default)]
+    pub(crate) text: Option<String>,
+    #[serde(default)]
+    pub(crate) id: Option<String>,
+    #[serde(default)]
+    pub(crate) name: Option<String>,
+    #[serde(default)]
+    pub(crate) input: Option<String>,
+    #[serde(default)]
+    pub(crate) output: Option<String>,
+    #[serde(default)]
+    pub(crate) error: Option<String>,
+}
+
+impl StreamRule {
+    fn matches(&self, event: &Value) -> bool {
+        self.matches
+            .iter()
+            .all(|(pointer, expected)| event.pointer(pointer) == Some(expected))
+    }
+
+    fn pointers(&self) -> impl Iterator<Item = &String> {
+        self.matches.keys().chain(
+            [
+                &self.text,
+                &self.id,
+                &self.name,
+                &self.input,
+                &self.output,
+                &self.error,
+            ]
+            .into_iter()
+            .flatten(),
+        )
+    }
+}
+
+impl PluginManifest {
+    pub(crate) fn display_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .filter(|name| !name.trim().is_empty())
+            .unwrap_or(&self.id)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.id.trim().is_empty() {
+            return Err("`id` is required".to_string());
+        }
+        if BUILTIN_CLI_TYPES.contains(&self.id.as_str()) {
+            return Err(format!("`{}` is a built-in CLI type", self.id));
+        }
+        if self.command.trim().is_empty() {
+            return Err("`command` is required".to_string());
+        }
+        let session_pointer = self.stream.session_id.iter();
+        let rule_pointers = self.stream.rules.iter().flat_map(StreamRule::pointers);
+        if let Some(pointer) = session_pointer
+            .chain(rule_pointers)
+            .find(|pointer| !pointer.is_empty() && !pointer.starts_with('/'))
+        {
+            return Err(format!("`{pointer}` is not a JSON pointer"));
+        }
+        for (index, rule) in self.stream.rules.iter().enumerate() {
+            let missing = match rule.emit {
+                RuleEvent::MessageDelta => rule.text.is_none().then_some("text"),
+                RuleEvent::ToolStarted | RuleEvent::ToolCompleted => {
+                    rule.id.is_none().then_some("id")
+                }
+                _ => None,
+            };
+            if let Some(field) = missing {
+                return Err(format!("rule {} needs `{field}`", index + 1));
+            }
+        }
+        Ok(())
+    }
+
+    fn turn_args(&self, session_id: Option<&str>, prompt: &str, cwd: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        if let (Some(flag), Some(session_id)) = (&self.resume_flag, session_id) {
+            args.push(flag.clone());
+            args.push(session_id.to_string());
+        }
+        let mut mentions_prompt = false;
+        for arg in &self.args {
+            mentions_prompt |= arg.contains("{prompt}");
+            args.push(arg.replace("{cwd}", cwd).replace("{prompt}", prompt));
+        }
+        if !mentions_prompt {
+            args.push(prompt.to_string());
+        }
+        args
+    }
+
+    fn parse_stream_line(&self, line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
+        let thread_id = thread_id.to_string();
+        let turn_id = turn_id.to_string();
+        let message_id = format!("msg_{turn_id}");
+        if self.stream.format == StreamFormat::Text {
+            if line.trim().is_empty() {
+                return None;
+            }
+            return Some(
+                AppEvent::AgentMessageDelta {
+                    thread_id,
+                    turn_id,
+                    item_id: message_id,
+                    delta: format!("{line}\n"),
+                }
+                .into_message(),
+            );
+        }
+
+        let event: Value = serde_json::from_str(line).ok()?;
+        let rule = self.stream.rules.iter().find(|rule| rule.matches(&event))?;
+        let field = |pointer: &Option<String>| -> Option<Value> {
+            event.pointer(pointer.as_deref()?).cloned()
+        };
+        let text = |pointer: &Option<String>| field(pointer).map(value_text);
+        let app_event = match rule.emit {
+            RuleEvent::TurnStarted => AppEvent::TurnStarted { thread_id, turn_id },
+            RuleEvent::MessageDelta => AppEvent::AgentMessageDelta {
+                thread_id,
+                turn_id,
+                item_id: message_id,
+                delta: text(&rule.text)?,
+            },
+            RuleEvent::ToolStarted => AppEvent::ItemStarted {
+                thread_id,
+                turn_id,
+                item: ThreadItem::ToolUse {
+                    id: text(&rule.id)?,
+                    name: text(&rule.name),
+                    input: field(&rule.input),
+                    output: None,
+                },
+            },
+            RuleEvent::ToolCompleted => AppEvent::ItemCompleted {
+                thread_id,
+                turn_id,
+                item: ThreadItem::ToolUse {
+                    id: text(&rule.id)?,
+                    name: text(&rule.name),
+                    input: field(&rule.input),
+                    output: text(&rule.output),
+                },
+            },
+            RuleEvent::TurnCompleted => AppEvent::TurnCompleted {
+                thread_id,
+                turn_id,
+                cost_usd: None,
+                duration_ms: None,
+                exit_code: None,
+                error: text(&rule.error).filter(|error| !error.is_empty()),
+            },
+            RuleEvent::Ignore => return None,
+        };
+        Some(app_event.into_message())
+    }
+
+    fn extract_session_id(&self, line: &str) -> Option<String> {
+        let pointer = self.stream.session_id.as_deref()?;
+        let event: Value = serde_json::from_str(line).ok()?;
+        event
+            .pointer(pointer)
+            .map(|value| value_text(value.clone()))
+            .filter(|id| !id.is_empty())
+    }
+}
+
+fn value_text(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliPluginInfo {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) command: String,
+    pub(crate) path: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliPluginError {
+    pub(crate) path: String,
+    pub(crate) error: String,
+}
+
+/// Plugins loaded at startup, and the manifests that failed to load.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliPlugins {
+    pub(crate) dir: String,
+    pub(crate) plugins: Vec<CliPluginInfo>,
+    pub(crate) errors: Vec<CliPluginError>,
+}
+
+#[derive(Default)]
+struct Registry {
+    manifests: HashMap<String, Arc<PluginManifest>>,
+    listing: CliPlugins,
+}
+
+fn registry() -> &'static RwLock<Registry> {
+    static REGISTRY: OnceLock<RwLock<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| RwLock::new(Registry::default()))
+}
+
+fn read_manifest(path: &Path) -> Result<PluginManifest, String> {
+    let contents = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let manifest: PluginManifest = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&contents).map_err(|err| err.to_string())?,
+        _ => serde_json::from_str(&contents).map_err(|err| err.to_string())?,
+    };
+    manifest.validate()?;
+    Ok(manifest)
+}
+
+/// Loads the CLI plugin manifests in `<data_dir>/plugins`, replacing any
+/// loaded before. Manifests that don't parse are skipped and reported by
+/// `cli_plugins`.
+pub(crate) fn load_plugins(data_dir: &Path) {
+    let dir = data_dir.join(PLUGINS_DIR);
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    matches!(
+                        path.extension().and_then(|ext| ext.to_str()),
+                        Some("toml") | Some("json")
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+
+    let mut loaded = Registry::default();
+    loaded.listing.dir = dir.to_string_lossy().to_string();
+    for path in paths {
+        let display_path = path.to_string_lossy().to_string();
+        let manifest = read_manifest(&path).and_then(|manifest| {
+            if loaded.manifests.contains_key(&manifest.id) {
+                Err(format!("Another plugin already uses id `{}`", manifest.id))
+            } else {
+                Ok(manifest)
+            }
+        });
+        match manifest {
+            Ok(manifest) => {
+                loaded.listing.plugins.push(CliPluginInfo {
+                    id: manifest.id.clone(),
+                    name: manifest.display_name().to_string(),
+                    command: manifest.command.clone(),
+                    path: display_path,
+                });
+                loaded
+                    .manifests
+                    .insert(manifest.id.clone(), Arc::new(manifest));
+            }
+            Err(error) => {
+                tracing::warn!("skipping CLI plugin {display_path}: {error}");
+                loaded.listing.errors.push(CliPluginError {
+                    path: display_path,
+                    error,
+                });
+            }
+        }
+    }
+    if let Ok(mut registry) = registry().write() {
+        *registry = loaded;
+    }
+}
+
+pub(crate) fn cli_plugins() -> CliPlugins {
+    registry()
+        .read()
+        .map(|registry| registry.listing.clone())
+        .unwrap_or_default()
+}
+
+/// The loaded plugin for `cli_type`.
+pub(crate) fn plugin(cli_type: &str) -> Option<Arc<PluginManifest>> {
+    registry().read().ok()?.manifests.get(cli_type).cloned()
+}
+
+/// Whether `cli_type` has a built-in adapter or a loaded plugin.
+pub(crate) fn is_known_cli_type(cli_type: &str) -> bool {
+    BUILTIN_CLI_TYPES.contains(&cli_type) || plugin(cli_type).is_some()
+}
+
+pub(crate) struct PluginProfile {
+    manifest: Arc<PluginManifest>,
+}
+
+#[async_trait::async_trait]
+impl CliProfile for PluginProfile {
+    fn build_turn_command(
+        &self,
+        config: &CliSpawnConfig,
+        session_id: Option<&str>,
+        input: &TurnInput,
+        cwd: &str,
+        _effort: Option<&str>,
+        _capabilities: &CliCapabilities,
+    ) -> Result<tokio::process::Command, String> {
+        if !input.images.is_empty() {
+            return Err(format!(
+                "The {} CLI doesn't accept image attachments.",
+                self.manifest.display_name()
+            ));
+        }
+        let args = self.manifest.turn_args(session_id, &input.text, cwd);
+        build_adapter_command(config, args, cwd, None)
+    }
+
+    fn parse_stream_line(&self, line: &str, thread_id: &str, turn_id: &str) -> Option<Value> {
+        self.manifest.parse_stream_line(line, thread_id, turn_id)
+    }
+
+    fn extract_session_id(&self, line: &str) -> Option<String> {
+        self.manifest.extract_session_id(line)
+    }
+
+    async fn model_list(&self, config: &CliSpawnConfig) -> Value {
+        let models = match &self.manifest.model_list_command {
+            Some(args) => list_models(config, args.clone()).await,
+            None => Vec::new(),
+        };
+        let models = models
+            .iter()
+            .map(|id| json!({ "id": id, "name": id }))
+            .collect::<Vec<_>>();
+        json!({
+            "result": {
+                "models": models,
+                "defaultModel": null
+            }
+        })
+    }
+
+    fn provider_name(&self) -> &str {
+        &self.manifest.id
+    }
+}
+
+/// Runs the manifest's model list command, giving up quietly when it fails.
+async fn list_models(config: &CliSpawnConfig, args: Vec<String>) -> Vec<String> {
+    let Ok(mut command) = build_codex_command_with_bin(config.cli_bin.clone(), None, args) else {
+        return Vec::new();
+    };
+    command.stdin(std::process::Stdio::null());
+    command.kill_on_drop(true);
+    let output = match tokio::time::timeout(MODEL_LIST_TIMEOUT, command.output()).await {
+        Ok(Ok(output)) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Starts a session for a plugin CLI. The manifest's command is used in
+/// place of the Codex bin and args the settings fall back to.
+pub(crate) async fn spawn_plugin_session<E: EventSink>(
+    manifest: Arc<PluginManifest>,
+    entry: WorkspaceEntry,
+    mut config: CliSpawnConfig,
+    event_sink: E,
+) -> Result<Arc<WorkspaceSession>, String> {
+    config.cli_bin = Some(manifest.command.clone());
+    config.cli_args = None;
+    let cli_name = manifest.display_name().to_string();
+    let profile = PluginProfile { manifest };
+    spawn_adapter_session(profile, &cli_name, entry, config, event_sink).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PluginManifest;
+
+    const MANIFEST: &str = r#"
+id = "mycli"
+command = "mycli"
+args = ["run", "--json", "--cwd", "{cwd}"]
+resumeFlag = "--session"
+
+[stream]
+sessionId = "/session"
+
+[[stream.rules]]
+match = { "/type" = "start" }
+emit = "turnStarted"
+
+[[stream.rules]]
+match = { "/type" = "text" }
+emit = "messageDelta"
+text = "/content"
+
+[[stream.rules]]
+match = { "/type" = "tool", "/phase" = "end" }
+emit = "toolCompleted"
+id = "/callId"
+output = "/result"
+"#;
+
+    #[test]
+    fn maps_stream_lines_and_builds_turn_args() {
+        let manifest: PluginManifest = toml::from_str(MANIFEST).expect("manifest");
+        assert!(manifest.validate().is_ok());
+        assert_eq!(
+            manifest.turn_args(Some("s1"), "hi", "/repo"),
+            vec!["--session", "s1", "run", "--json", "--cwd", "/repo", "hi"]
+        );
+
+        let delta = manifest
+            .parse_stream_line(r#"{"type":"text","content":"Hello"}"#, "t1", "turn1")
+            .expect("delta");
+        assert_eq!(delta["method"], "item/agentMessage/delta");
+        assert_eq!(delta["params"]["delta"], "Hello");
+        let tool = manifest
+            .parse_stream_line(
+                r#"{"type":"tool","phase":"end","callId":"c1","result":"ok"}"#,
+                "t1",
+                "turn1",
+            )
+            .expect("tool");
+        assert_eq!(tool["method"], "item/completed");
+        assert_eq!(tool["params"]["item"]["output"], "ok");
+        assert!(manifest
+            .parse_stream_line(r#"{"type":"tool","phase":"start"}"#, "t1", "turn1")
+            .is_none());
+        assert_eq!(
+            manifest.extract_session_id(r#"{"type":"start","session":"s2"}"#),
+            Some("s2".to_string())
+        );
+    }
+}
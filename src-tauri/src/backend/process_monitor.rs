@@ -0,0 +1,321 @@
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+use tokio::sync::Mutex;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::types::ProcessLimits;
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+/// Resource usage of a session's CLI process and all of its descendants.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ProcessStats {
+    pub(crate) pid: u32,
+    pub(crate) process_count: usize,
+    pub(crate) cpu_percent: f32,
+    pub(crate) memory_bytes: u64,
+    pub(crate) cpu_time_ms: u64,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct LimitBreach {
+    limit: &'static str,
+    value: u64,
+    max: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+enum Enforcement {
+    #[default]
+    None,
+    Interrupted,
+    Killed,
+}
+
+#[derive(Default)]
+struct WorkspaceSample {
+    root_pid: Option<u32>,
+    turn_baseline_ms: Option<u64>,
+    enforcement: Enforcement,
+}
+
+impl WorkspaceSample {
+    /// CPU time the tree has used since the running turn began. A root pid
+    /// not seen before is a fresh process (adapters spawn one per turn), so
+    /// all of its CPU time counts toward the turn.
+    fn turn_cpu_ms(&mut self, pid: u32, cpu_time_ms: u64, turn_running: bool) -> u64 {
+        let new_process = self.root_pid != Some(pid);
+        self.root_pid = Some(pid);
+        if new_process {
+            self.turn_baseline_ms = None;
+        }
+        if !turn_running {
+            self.turn_baseline_ms = None;
+            self.enforcement = Enforcement::None;
+            return 0;
+        }
+        let initial = if new_process { 0 } else { cpu_time_ms };
+        let baseline = *self.turn_baseline_ms.get_or_insert(initial);
+        cpu_time_ms.saturating_sub(baseline)
+    }
+}
+
+/// `root` followed by all of its descendants, given a child -> parent map.
+fn process_tree(parents: &HashMap<u32, u32>, root: u32) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (&pid, &parent) in parents {
+        children.entry(parent).or_default().push(pid);
+    }
+    let mut tree = vec![root];
+    let mut index = 0;
+    while let Some(&pid) = tree.get(index) {
+        for &kid in children.get(&pid).into_iter().flatten() {
+            if !tree.contains(&kid) {
+                tree.push(kid);
+            }
+        }
+        index += 1;
+    }
+    tree
+}
+
+fn tree_pids(system: &System, root: u32) -> Vec<u32> {
+    let parents = system
+        .processes()
+        .iter()
+        .filter(|(_, process)| process.thread_kind().is_none())
+        .filter_map(|(pid, process)| {
+            process
+                .parent()
+                .map(|parent| (pid.as_u32(), parent.as_u32()))
+        })
+        .collect::<HashMap<_, _>>();
+    process_tree(&parents, root)
+}
+
+fn sample_tree(system: &System, root: u32) -> Option<ProcessStats> {
+    system.process(Pid::from_u32(root))?;
+    let mut stats = ProcessStats {
+        pid: root,
+        process_count: 0,
+        cpu_percent: 0.0,
+        memory_bytes: 0,
+        cpu_time_ms: 0,
+    };
+    for pid in tree_pids(system, root) {
+        let Some(process) = system.process(Pid::from_u32(pid)) else {
+            continue;
+        };
+        stats.process_count += 1;
+        stats.cpu_percent += process.cpu_usage();
+        stats.memory_bytes += process.memory();
+        stats.cpu_time_ms += process.accumulated_cpu_time();
+    }
+    Some(stats)
+}
+
+fn check_limits(
+    limits: &ProcessLimits,
+    memory_bytes: u64,
+    turn_cpu_ms: u64,
+) -> Option<LimitBreach> {
+    if let Some(max) = limits.max_memory_mb {
+        let value = memory_bytes / BYTES_PER_MB;
+        if value > max {
+            return Some(LimitBreach {
+                limit: "maxMemoryMb",
+                value,
+                max,
+            });
+        }
+    }
+    if let Some(max) = limits.max_cpu_seconds {
+        let value = turn_cpu_ms / 1000;
+        if value > max {
+            return Some(LimitBreach {
+                limit: "maxCpuSeconds",
+                value,
+                max,
+            });
+        }
+    }
+    None
+}
+
+/// Samples every session's process tree, emits `process/stats`, and stops
+/// turns that exceed the workspace's `processLimits`: first with
+/// `turn/interrupt`, then, if the tree is still over the limit on the next
+/// sample, by killing the CLI's child processes.
+pub(crate) async fn run_process_monitor<E: EventSink>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    event_sink: E,
+) {
+    let mut system = System::new();
+    let mut samples: HashMap<String, WorkspaceSample> = HashMap::new();
+    loop {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+        let live = sessions
+            .lock()
+            .await
+            .iter()
+            .map(|(id, session)| (id.clone(), Arc::clone(session)))
+            .collect::<Vec<_>>();
+        let mut roots = Vec::new();
+        for (workspace_id, session) in live {
+            if let Some(pid) = session.child_pid().await {
+                roots.push((workspace_id, session, pid));
+            }
+        }
+        samples.retain(|id, _| roots.iter().any(|(workspace_id, _, _)| workspace_id == id));
+        if roots.is_empty() {
+            continue;
+        }
+
+        system = match tokio::task::spawn_blocking(move || {
+            system.refresh_processes_specifics(
+                ProcessesToUpdate::All,
+                true,
+                ProcessRefreshKind::nothing().with_cpu().with_memory(),
+            );
+            system
+        })
+        .await
+        {
+            Ok(system) => system,
+            Err(error) => {
+                tracing::warn!("process monitor: refresh task failed: {error}");
+                System::new()
+            }
+        };
+
+        for (workspace_id, session, pid) in roots {
+            let Some(stats) = sample_tree(&system, pid) else {
+                continue;
+            };
+            let running = session.turn_hooks.running_turns();
+            let sample = samples.entry(workspace_id.clone()).or_default();
+            let turn_cpu_ms = sample.turn_cpu_ms(pid, stats.cpu_time_ms, !running.is_empty());
+            event_sink.emit_app_server_event(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::ProcessStats {
+                    workspace_id: workspace_id.clone(),
+                    stats: stats.clone(),
+                    turn_cpu_time_ms: turn_cpu_ms,
+                },
+            ));
+
+            if running.is_empty() || sample.enforcement == Enforcement::Killed {
+                continue;
+            }
+            let Some(breach) = session
+                .turn_hooks
+                .process_limits()
+                .and_then(|limits| check_limits(&limits, stats.memory_bytes, turn_cpu_ms))
+            else {
+                continue;
+            };
+            let action = if sample.enforcement == Enforcement::None {
+                sample.enforcement = Enforcement::Interrupted;
+                for (thread_id, turn_id) in &running {
+                    let session = Arc::clone(&session);
+                    let params = json!({ "threadId": thread_id, "turnId": turn_id });
+                    tokio::spawn(async move {
+                        let _ = session.send_request("turn/interrupt", params).await;
+                    });
+                }
+                "interrupted"
+            } else {
+                sample.enforcement = Enforcement::Killed;
+                // The root is the app-server itself for Codex sessions, so
+                // only its children (the runaway tool processes) are killed.
+                for child in tree_pids(&system, pid).into_iter().skip(1) {
+                    if let Some(process) = system.process(Pid::from_u32(child)) {
+                        process.kill();
+                    }
+                }
+                "killed"
+            };
+            tracing::warn!(
+                workspace_id = %workspace_id,
+                "process limit {} exceeded ({} > {}), turn {action}",
+                breach.limit,
+                breach.value,
+                breach.max
+            );
+            event_sink.emit_app_server_event(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::ProcessLimitExceeded {
+                    workspace_id: workspace_id.clone(),
+                    thread_ids: running
+                        .iter()
+                        .map(|(thread_id, _)| thread_id.clone())
+                        .collect(),
+                    limit: breach.limit.to_string(),
+                    value: breach.value,
+                    max: breach.max,
+                    action: action.to_string(),
+                },
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_limits, process_tree, LimitBreach, WorkspaceSample, BYTES_PER_MB};
+    use crate::types::ProcessLimits;
+    use std::collections::HashMap;
+
+    #[test]
+    fn process_tree_collects_descendants_and_turn_cpu_time() {
+        let parents = HashMap::from([(2, 1), (3, 2), (4, 2), (5, 9)]);
+        let mut tree = process_tree(&parents, 1);
+        tree.sort();
+        assert_eq!(tree, vec![1, 2, 3, 4]);
+        assert_eq!(process_tree(&parents, 7), vec![7]);
+
+        let mut sample = WorkspaceSample::default();
+        assert_eq!(sample.turn_cpu_ms(10, 5_000, false), 0);
+        assert_eq!(sample.turn_cpu_ms(10, 6_000, true), 0);
+        assert_eq!(sample.turn_cpu_ms(10, 9_000, true), 3_000);
+        // A new per-turn process counts from zero.
+        assert_eq!(sample.turn_cpu_ms(11, 2_000, true), 2_000);
+    }
+
+    #[test]
+    fn check_limits_reports_memory_then_cpu() {
+        let limits = ProcessLimits {
+            max_memory_mb: Some(512),
+            max_cpu_seconds: Some(60),
+        };
+        assert_eq!(check_limits(&limits, 100 * BYTES_PER_MB, 30_000), None);
+        assert_eq!(
+            check_limits(&limits, 600 * BYTES_PER_MB, 90_000),
+            Some(LimitBreach {
+                limit: "maxMemoryMb",
+                value: 600,
+                max: 512,
+            })
+        );
+        assert_eq!(
+            check_limits(&limits, 0, 61_000),
+            Some(LimitBreach {
+                limit: "maxCpuSeconds",
+                value: 61,
+                max: 60,
+            })
+        );
+        assert_eq!(
+            check_limits(&ProcessLimits::default(), u64::MAX, u64::MAX),
+            None
+        );
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use regex::Regex;
+use serde_json::{json, Value};
+
+/// Delay before the first retry; doubled for each further attempt.
+const BASE_RETRY_DELAY: Duration = Duration::from_secs(5);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Longest wait worth retrying for. Limits that lift later, such as a plan's
+/// usage cap, fail the turn instead.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5 * 60);
+/// How long a limit without a known reset time is reported as active.
+const UNKNOWN_RESET_WINDOW_SECS: u64 = 60;
+
+/// A rate-limit error reported by a CLI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RateLimitHit {
+    pub(crate) message: String,
+    /// Unix seconds when the limit lifts, if the CLI said.
+    pub(crate) resets_at: Option<u64>,
+}
+
+fn rate_limit_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r#"(?i)(?:status|code|error)["':\s]*429\b|\b429 too many|rate[ _-]?limit|too many requests|resource[ _]exhausted|quota exceeded|exceeded your current quota|usage limit reached|overloaded"#,
+        )
+        .expect("valid rate limit pattern")
+    })
+}
+
+/// `retry-after: 30`, `"retryDelay": "30s"`, `try again in 2 minutes`.
+fn retry_hint_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(
+            r#"(?i)(?:retry[-_ ]?after|retry ?delay|try again in|retrying in)["':\s]*(\d+(?:\.\d+)?)\s*(ms|milliseconds?|s|secs?|seconds?|m|mins?|minutes?)?\b"#,
+        )
+        .expect("valid retry hint pattern")
+    })
+}
+
+/// Claude prints `Claude AI usage limit reached|<unix seconds>`.
+fn usage_reset_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| {
+        Regex::new(r"(?i)usage limit reached\|(\d{10})").expect("valid usage reset pattern")
+    })
+}
+
+fn retry_hint_secs(text: &str) -> Option<u64> {
+    let captures = retry_hint_pattern().captures(text)?;
+    let amount: f64 = captures.get(1)?.as_str().parse().ok()?;
+    let unit = captures
+        .get(2)
+        .map(|unit| unit.as_str().to_ascii_lowercase())
+        .unwrap_or_default();
+    let secs = if unit.starts_with("ms") || unit.starts_with("milli") {
+        amount / 1000.0
+    } else if unit.starts_with('m') {
+        amount * 60.0
+    } else {
+        amount
+    };
+    Some(secs.ceil().max(1.0) as u64)
+}
+
+/// Recognizes rate-limit and overload errors from Claude, Gemini and Cursor
+/// output. `now` is in Unix seconds.
+pub(crate) fn detect_rate_limit(text: &str, now: u64) -> Option<RateLimitHit> {
+    if !rate_limit_pattern().is_match(text) {
+        return None;
+    }
+    let resets_at = usage_reset_pattern()
+        .captures(text)
+        .and_then(|captures| captures.get(1)?.as_str().parse().ok())
+        .or_else(|| retry_hint_secs(text).map(|secs| now + secs));
+    Some(RateLimitHit {
+        message: text.trim().to_string(),
+        resets_at,
+    })
+}
+
+/// The error text of a stream-json line that reports a failed turn: a Claude
+/// `result` with `is_error`, a Gemini `result` with `status: "error"`, or an
+/// `error` event.
+pub(crate) fn stream_error_text(line: &str) -> Option<String> {
+    let event: Value = serde_json::from_str(line).ok()?;
+    let message = |value: &Value| -> Option<String> {
+        match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(_) => value
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .or_else(|| Some(value.to_string())),
+            _ => None,
+        }
+    };
+    match event.get("type").and_then(Value::as_str)? {
+        "result" if event.get("is_error").and_then(Value::as_bool) == Some(true) => event
+            .get("result")
+            .and_then(message)
+            .or_else(|| event.get("error").and_then(message)),
+        "result" if event.get("status").and_then(Value::as_str) == Some("error") => {
+            event.get("error").and_then(message)
+        }
+        "error" => event
+            .get("error")
+            .or_else(|| event.get("message"))
+            .and_then(message),
+        _ => None,
+    }
+}
+
+/// Wait before retry number `attempt` (from 1): the CLI's reset time when it
+/// gave one, otherwise exponential backoff. `None` when the wait is too long
+/// to be worth retrying.
+pub(crate) fn retry_delay(hit: &RateLimitHit, attempt: u32, now: u64) -> Option<Duration> {
+    let delay = match hit.resets_at {
+        Some(resets_at) => Duration::from_secs(resets_at.saturating_sub(now).max(1)),
+        None => BASE_RETRY_DELAY
+            .saturating_mul(1 << attempt.saturating_sub(1).min(8))
+            .min(MAX_BACKOFF),
+    };
+    (delay <= MAX_RETRY_DELAY).then_some(delay)
+}
+
+struct LimitRecord {
+    hit: RateLimitHit,
+    seen_at: u64,
+}
+
+/// The last rate limit hit per provider, shared by all of its workspaces,
+/// and the `adapterRateLimitRetries` setting. Clones share both.
+#[derive(Clone, Default)]
+pub(crate) struct RateLimits {
+    /// 0 turns automatic retries off.
+    max_retries: Arc<AtomicU32>,
+    limits: Arc<Mutex<HashMap<String, LimitRecord>>>,
+}
+
+impl RateLimits {
+    pub(crate) fn set_max_retries(&self, retries: u32) {
+        self.max_retries.store(retries, Ordering::Relaxed);
+    }
+
+    pub(crate) fn max_retries(&self) -> u32 {
+        self.max_retries.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn record(&self, provider: &str, hit: RateLimitHit, now: u64) {
+        if let Ok(mut limits) = self.limits.lock() {
+            limits.insert(provider.to_string(), LimitRecord { hit, seen_at: now });
+        }
+    }
+
+    /// Called after a successful turn; the provider is evidently not limited.
+    /// Returns whether a limit was on record.
+    pub(crate) fn clear(&self, provider: &str) -> bool {
+        self.limits
+            .lock()
+            .map(|mut limits| limits.remove(provider).is_some())
+            .unwrap_or(false)
+    }
+
+    /// `{ rateLimits, message }` in the `account/rateLimits/read` shape. While
+    /// a limit is active the primary window reads as fully used until it
+    /// resets.
+    pub(crate) fn snapshot(&self, provider: &str, now: u64) -> Value {
+        let active = self.limits.lock().ok().and_then(|limits| {
+            let record = limits.get(provider)?;
+            let until = record
+                .hit
+                .resets_at
+                .unwrap_or(record.seen_at + UNKNOWN_RESET_WINDOW_SECS);
+            (now < until).then(|| (record.hit.message.clone(), record.hit.resets_at))
+        });
+        let primary = active.as_ref().map(|(_, resets_at)| {
+            json!({
+                "usedPercent": 100,
+                "windowDurationMins": Value::Null,
+                "resetsAt": resets_at,
+            })
+        });
+        json!({
+            "rateLimits": {
+                "primary": primary,
+                "secondary": Value::Null,
+                "credits": Value::Null,
+                "planType": Value::Null,
+            },
+            "message": active.map(|(message, _)| message),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_rate_limit, retry_delay, stream_error_text, RateLimits};
+    use std::time::Duration;
+
+    #[test]
+    fn detects_limits_and_plans_retries() {
+        let now = 1_700_000_000;
+        let claude = r#"{"type":"result","subtype":"success","is_error":true,"result":"API Error: 429 {\"type\":\"error\",\"error\":{\"type\":\"rate_limit_error\"}}"}"#;
+        let text = stream_error_text(claude).expect("error text");
+        let hit = detect_rate_limit(&text, now).expect("rate limit");
+        assert_eq!(hit.resets_at, None);
+        assert_eq!(retry_delay(&hit, 1, now), Some(Duration::from_secs(5)));
+        assert_eq!(retry_delay(&hit, 3, now), Some(Duration::from_secs(20)));
+        assert_eq!(retry_delay(&hit, 6, now), Some(Duration::from_secs(60)));
+
+        let gemini = r#"[API Error: RESOURCE_EXHAUSTED {"retryDelay": "30s"}]"#;
+        let hit = detect_rate_limit(gemini, now).expect("rate limit");
+        assert_eq!(hit.resets_at, Some(now + 30));
+
+        let usage_cap = format!("Claude AI usage limit reached|{}", now + 3 * 60 * 60);
+        let hit = detect_rate_limit(&usage_cap, now).expect("rate limit");
+        assert_eq!(retry_delay(&hit, 1, now), None);
+
+        assert!(detect_rate_limit("Ran 429 tests", now).is_none());
+        assert!(stream_error_text(r#"{"type":"result","is_error":false}"#).is_none());
+
+        let limits = RateLimits::default();
+        limits.record("test-provider", hit, now);
+        let snapshot = limits.snapshot("test-provider", now);
+        assert_eq!(snapshot["rateLimits"]["primary"]["usedPercent"], 100);
+        assert_eq!(
+            limits.snapshot("test-provider", now + 4 * 60 * 60)["rateLimits"]["primary"],
+            serde_json::Value::Null
+        );
+        assert!(limits.clear("test-provider"));
+        assert!(limits.snapshot("test-provider", now)["message"].is_null());
+    }
+}
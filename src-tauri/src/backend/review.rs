@@ -0,0 +1,179 @@
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::shared::git_core::{run_git_command, run_git_diff};
+
+/// Bytes of diff put into one review prompt.
+const REVIEW_DIFF_BUDGET_BYTES: usize = 200_000;
+
+const REVIEW_INSTRUCTIONS: &str = "\
+You are reviewing code changes in this repository. Look for bugs, security \
+problems, missing error handling, and changes that don't do what they appear \
+to intend. Skip style nits. For each finding, name the file and line, say \
+what is wrong and why it matters, and suggest a fix. End with a one-line \
+verdict. If nothing needs changing, say so. Do not modify any files.";
+
+/// A `review/start` target, as sent by the UI.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum ReviewTarget {
+    UncommittedChanges,
+    BaseBranch { branch: String },
+    Commit { sha: String, title: Option<String> },
+    Custom { instructions: String },
+}
+
+impl ReviewTarget {
+    pub(crate) fn parse(target: &Value) -> Result<Self, String> {
+        let field = |name: &str| {
+            target
+                .get(name)
+                .and_then(Value::as_str)
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        match target.get("type").and_then(Value::as_str) {
+            Some("uncommittedChanges") => Ok(Self::UncommittedChanges),
+            Some("baseBranch") => Ok(Self::BaseBranch {
+                branch: field("branch").ok_or("baseBranch review is missing branch")?,
+            }),
+            Some("commit") => Ok(Self::Commit {
+                sha: field("sha").ok_or("commit review is missing sha")?,
+                title: field("title"),
+            }),
+            Some("custom") => Ok(Self::Custom {
+                instructions: field("instructions")
+                    .ok_or("custom review is missing instructions")?,
+            }),
+            other => Err(format!(
+                "unsupported review target: {}",
+                other.unwrap_or("missing type")
+            )),
+        }
+    }
+
+    /// Short description shown on the `enteredReviewMode` item.
+    pub(crate) fn hint(&self) -> String {
+        match self {
+            Self::UncommittedChanges => "uncommitted changes".to_string(),
+            Self::BaseBranch { branch } => format!("changes against {branch}"),
+            Self::Commit { sha, title } => {
+                let short: String = sha.chars().take(7).collect();
+                match title {
+                    Some(title) => format!("commit {short}: {title}"),
+                    None => format!("commit {short}"),
+                }
+            }
+            Self::Custom { instructions } => instructions.clone(),
+        }
+    }
+}
+
+fn truncate_diff(diff: &str) -> (&str, bool) {
+    if diff.len() <= REVIEW_DIFF_BUDGET_BYTES {
+        return (diff, false);
+    }
+    let mut end = REVIEW_DIFF_BUDGET_BYTES;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    (&diff[..end], true)
+}
+
+/// The prompt sent to the CLI: review instructions, then the diff.
+pub(crate) fn build_review_prompt(
+    target: &ReviewTarget,
+    diff: &str,
+    untracked: &[String],
+) -> String {
+    if let ReviewTarget::Custom { instructions } = target {
+        return format!(
+            "{REVIEW_INSTRUCTIONS}\n\nReview the code in this repository. {instructions}"
+        );
+    }
+    let mut prompt = format!("{REVIEW_INSTRUCTIONS}\n\nReview the {}.\n\n", target.hint());
+    let (included, truncated) = truncate_diff(diff);
+    prompt.push_str(&format!(
+        "<diff{}>\n{included}\n</diff>\n",
+        if truncated { " truncated=\"true\"" } else { "" }
+    ));
+    if !untracked.is_empty() {
+        prompt.push_str(&format!(
+            "\nNew files not shown in the diff (read them from the workspace):\n{}\n",
+            untracked
+                .iter()
+                .map(|path| format!("- {path}"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ));
+    }
+    prompt
+}
+
+/// Collects the diff for `target` from the workspace's git repo and builds
+/// the review prompt. Fails when there is nothing to review.
+pub(crate) async fn review_prompt(cwd: &str, target: &ReviewTarget) -> Result<String, String> {
+    let repo = PathBuf::from(cwd);
+    let (diff, untracked) = match target {
+        ReviewTarget::Custom { .. } => return Ok(build_review_prompt(target, "", &[])),
+        ReviewTarget::UncommittedChanges => {
+            let diff = run_git_diff(&repo, &["diff", "HEAD", "--no-color"]).await?;
+            let untracked =
+                run_git_command(&repo, &["ls-files", "--others", "--exclude-standard"]).await?;
+            (
+                diff,
+                untracked.lines().map(str::to_string).collect::<Vec<_>>(),
+            )
+        }
+        ReviewTarget::BaseBranch { branch } => {
+            let base = run_git_command(&repo, &["merge-base", "HEAD", branch]).await?;
+            (
+                run_git_diff(&repo, &["diff", &base, "--no-color"]).await?,
+                Vec::new(),
+            )
+        }
+        ReviewTarget::Commit { sha, .. } => (
+            run_git_diff(&repo, &["show", "--no-color", "--format=medium", sha]).await?,
+            Vec::new(),
+        ),
+    };
+    let diff = String::from_utf8_lossy(&diff);
+    if diff.trim().is_empty() && untracked.is_empty() {
+        return Err(format!("Nothing to review: no {}", target.hint()));
+    }
+    Ok(build_review_prompt(target, &diff, &untracked))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_review_prompt, ReviewTarget};
+    use serde_json::json;
+
+    #[test]
+    fn parses_targets_and_wraps_the_diff() {
+        let target = ReviewTarget::parse(&json!({
+            "type": "commit",
+            "sha": "0123456789abcdef",
+            "title": "Fix parser"
+        }))
+        .expect("target");
+        assert_eq!(target.hint(), "commit 0123456: Fix parser");
+        assert!(ReviewTarget::parse(&json!({ "type": "baseBranch" })).is_err());
+        assert!(ReviewTarget::parse(&json!({ "type": "everything" })).is_err());
+
+        let prompt = build_review_prompt(
+            &ReviewTarget::UncommittedChanges,
+            "diff --git a/src/lib.rs b/src/lib.rs\n",
+            &["notes.md".to_string()],
+        );
+        assert!(prompt.contains("Review the uncommitted changes."));
+        assert!(prompt.contains("<diff>\ndiff --git a/src/lib.rs b/src/lib.rs\n"));
+        assert!(prompt.ends_with("- notes.md\n"));
+
+        let custom = ReviewTarget::Custom {
+            instructions: "Check the SQL queries.".to_string(),
+        };
+        assert!(build_review_prompt(&custom, "", &[]).ends_with("Check the SQL queries."));
+    }
+}
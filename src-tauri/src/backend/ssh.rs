@@ -0,0 +1,161 @@
+use std::ffi::OsStr;
+use std::process::Stdio;
+
+use tokio::process::Command;
+
+use crate::backend::exec_target::LOCAL_ONLY_ENV;
+use crate::shared::process_core::tokio_command;
+use crate::types::SshTarget;
+
+/// Seconds an idle control connection stays open, so the next turn reuses
+/// it instead of connecting again.
+const CONTROL_PERSIST_SECS: u64 = 600;
+/// `%C` hashes user, host and port, keeping the socket path short enough
+/// for macOS.
+const CONTROL_PATH: &str = "~/.ssh/agent-monitor-%C";
+const CONNECT_TIMEOUT_SECS: u64 = 10;
+
+/// Splits `user@host:/path` (or `user@[::1]:/path`) into host and path.
+pub(crate) fn parse_destination(destination: &str) -> Result<(String, String), String> {
+    let destination = destination.trim();
+    let split = match destination.find("]:") {
+        Some(index) => Some((&destination[..=index], &destination[index + 2..])),
+        None => destination.split_once(':'),
+    };
+    match split {
+        Some((host, path)) if !host.is_empty() && !host.starts_with('-') && !path.is_empty() => {
+            Ok((host.to_string(), path.to_string()))
+        }
+        _ => Err(format!(
+            "Invalid SSH destination \"{destination}\"; use user@host:/path"
+        )),
+    }
+}
+
+fn quote(value: &OsStr) -> String {
+    shell_words::quote(&value.to_string_lossy()).into_owned()
+}
+
+/// `cd` target for the remote path, leaving a leading `~` to the shell.
+fn remote_dir(path: &str) -> String {
+    match path.strip_prefix("~/") {
+        Some(rest) => format!("\"$HOME\"/{}", shell_words::quote(rest)),
+        None if path == "~" => "\"$HOME\"".to_string(),
+        None => shell_words::quote(path).into_owned(),
+    }
+}
+
+/// The script run on the remote host: a login shell (so the CLI is on its
+/// PATH) that enters the workspace directory and execs the CLI with the
+/// variables set on `local`.
+fn remote_script(local: &std::process::Command, path: &str) -> String {
+    let mut script = format!("cd {} && exec env", remote_dir(path));
+    for (name, value) in local.get_envs() {
+        let Some(value) = value else {
+            continue;
+        };
+        if LOCAL_ONLY_ENV.contains(&name.to_string_lossy().as_ref()) {
+            continue;
+        }
+        let mut assignment = name.to_os_string();
+        assignment.push("=");
+        assignment.push(value);
+        script.push(' ');
+        script.push_str(&quote(&assignment));
+    }
+    for part in std::iter::once(local.get_program()).chain(local.get_args()) {
+        script.push(' ');
+        script.push_str(&quote(part));
+    }
+    format!(
+        "exec \"${{SHELL:-/bin/sh}}\" -lc {}",
+        shell_words::quote(&script)
+    )
+}
+
+/// Turns a CLI command built for this machine into one that runs it on
+/// `target` over SSH. The connection is multiplexed through a persistent
+/// control master and never prompts for a password. Like
+/// `build_adapter_command`, stdin is closed and stdout/stderr are piped.
+pub(crate) fn over_ssh(
+    local: &std::process::Command,
+    target: &SshTarget,
+) -> Result<Command, String> {
+    if cfg!(target_os = "windows") {
+        return Err("SSH workspaces are not supported on Windows".to_string());
+    }
+    let (host, path) = parse_destination(&target.destination)?;
+    let mut command = tokio_command("ssh");
+    command.args([
+        "-T".to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        format!("ConnectTimeout={CONNECT_TIMEOUT_SECS}"),
+        "-o".to_string(),
+        "ControlMaster=auto".to_string(),
+        "-o".to_string(),
+        format!("ControlPath={CONTROL_PATH}"),
+        "-o".to_string(),
+        format!("ControlPersist={CONTROL_PERSIST_SECS}"),
+    ]);
+    if let Some(port) = target.port {
+        command.arg("-p").arg(port.to_string());
+    }
+    if let Some(identity) = target
+        .identity_file
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+    {
+        command.arg("-i").arg(identity);
+        command.args(["-o", "IdentitiesOnly=yes"]);
+    }
+    command.arg(host);
+    command.arg(remote_script(local, &path));
+    command.stdin(Stdio::null());
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    Ok(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_destination, remote_script};
+
+    #[test]
+    fn wraps_the_local_command_for_the_remote_shell() {
+        assert_eq!(
+            parse_destination("me@build:/srv/app"),
+            Ok(("me@build".to_string(), "/srv/app".to_string()))
+        );
+        assert_eq!(
+            parse_destination("me@[::1]:~/app"),
+            Ok(("me@[::1]".to_string(), "~/app".to_string()))
+        );
+        assert!(parse_destination("build").is_err());
+        assert!(parse_destination("-oProxyCommand=x:/srv").is_err());
+
+        let mut local = std::process::Command::new("claude");
+        local.args(["-p", "it's done"]);
+        local.env("PATH", "/usr/bin");
+        local.env("API_TOKEN", "a b");
+        let script = remote_script(&local, "~/my app");
+        let outer = shell_words::split(&script).expect("split outer");
+        assert_eq!(outer[..3], ["exec", "${SHELL:-/bin/sh}", "-lc"]);
+        assert_eq!(
+            shell_words::split(&outer[3]).expect("split inner"),
+            [
+                "cd",
+                "$HOME/my app",
+                "&&",
+                "exec",
+                "env",
+                "API_TOKEN=a b",
+                "claude",
+                "-p",
+                "it's done"
+            ]
+        );
+    }
+}
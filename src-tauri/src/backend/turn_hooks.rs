@@ -0,0 +1,1008 @@
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use crate::backend::adapter_base::turn_metrics_path;
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{
+    AppEvent, AppServerEvent, EventSink, TerminalExit, TerminalOutput, ThreadItem,
+};
+use crate::backend::guardrails::{self, Guardrail};
+use crate::backend::turn_metrics::{self, TurnMetricsTracker};
+use crate::backend::workspace_hooks::{self, HookedTurn};
+use crate::shared::budget_core::{self, BudgetStatus};
+use crate::shared::file_changes_core::FileWatch;
+use crate::shared::git_core;
+use crate::shared::maintenance_core::ActiveTurns;
+use crate::shared::patch_core::{self, Patch};
+use crate::shared::turn_limit_core::TurnPermit;
+use crate::shared::turn_snapshot_core;
+use crate::types::{
+    AutoCompactPolicy, GuardrailAction, MonthlyBudget, PostTurnHook, PreTurnHook, ProcessLimits,
+    WorkspaceEntry, WorkspaceSettings,
+};
+
+const AUTO_COMMIT_FALLBACK_MESSAGE: &str = "Agent turn checkpoint";
+const AUTO_COMMIT_SUBJECT_MAX_CHARS: usize = 72;
+/// Shortest `fileWatchDebounceMs`; each poll walks the whole workspace.
+const MIN_FILE_WATCH_DEBOUNCE_MS: u64 = 250;
+/// Patches kept for `patch_apply`; older ones are dropped.
+const MAX_KEPT_PATCHES: usize = 100;
+
+type EventEmitter = Arc<dyn Fn(AppServerEvent) + Send + Sync>;
+
+/// A running turn's `files/changed` watcher. The turn id is filled in when
+/// the CLI reports `turn/started`.
+struct RunningFileWatch {
+    stop: Arc<Notify>,
+    turn_id: Arc<Mutex<Option<String>>>,
+}
+
+/// How full a thread's context is, as last reported by the CLI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ContextUsage {
+    tokens: u64,
+    /// The model's context window, when the CLI reports it.
+    window: Option<u64>,
+}
+
+/// Per-session state for work that runs around agent turns (prompt capture,
+/// snapshots and file watches on `turn/start`; metrics and checkpoints on
+/// `turn/completed`).
+pub(crate) struct TurnHooks {
+    workspace_id: String,
+    workspace_path: PathBuf,
+    auto_commit: AtomicBool,
+    turn_snapshots: AtomicBool,
+    prompts: Mutex<HashMap<String, String>>,
+    running_turns: Mutex<HashSet<String>>,
+    /// Where running turns are counted, to hold off maintenance.
+    active_turns: ActiveTurns,
+    turn_permits: Mutex<HashMap<String, TurnPermit>>,
+    turn_ids: Mutex<HashMap<String, String>>,
+    metrics: Mutex<HashMap<String, TurnMetricsTracker>>,
+    process_limits: Mutex<Option<ProcessLimits>>,
+    monthly_budget: Mutex<Option<MonthlyBudget>>,
+    file_watch_debounce_ms: Mutex<Option<u64>>,
+    file_watches: Mutex<HashMap<String, RunningFileWatch>>,
+    auto_compact: Mutex<Option<AutoCompactPolicy>>,
+    context_usage: Mutex<HashMap<String, ContextUsage>>,
+    /// Patches found in agent messages by `item/patch` item id, oldest first.
+    patches: Mutex<VecDeque<(String, Patch)>>,
+    guardrails: Mutex<Vec<Guardrail>>,
+    /// Tool calls each thread's guardrails already fired for, so a call seen
+    /// on start and completion is reported once.
+    guardrail_hits: Mutex<HashMap<String, HashSet<String>>>,
+    post_turn_hooks: Mutex<Vec<PostTurnHook>>,
+    pre_turn_hooks: Mutex<Vec<PreTurnHook>>,
+    /// The session, to interrupt turns a guardrail stops.
+    session: OnceLock<Weak<WorkspaceSession>>,
+}
+
+impl TurnHooks {
+    pub(crate) fn new(entry: &WorkspaceEntry, active_turns: ActiveTurns) -> Self {
+        Self {
+            workspace_id: entry.id.clone(),
+            workspace_path: PathBuf::from(&entry.path),
+            auto_commit: AtomicBool::new(entry.settings.auto_commit),
+            turn_snapshots: AtomicBool::new(entry.settings.turn_snapshots),
+            prompts: Mutex::new(HashMap::new()),
+            running_turns: Mutex::new(HashSet::new()),
+            active_turns,
+            turn_permits: Mutex::new(HashMap::new()),
+            turn_ids: Mutex::new(HashMap::new()),
+            metrics: Mutex::new(HashMap::new()),
+            process_limits: Mutex::new(entry.settings.process_limits.clone()),
+            monthly_budget: Mutex::new(entry.settings.monthly_budget.clone()),
+            file_watch_debounce_ms: Mutex::new(entry.settings.file_watch_debounce_ms),
+            file_watches: Mutex::new(HashMap::new()),
+            auto_compact: Mutex::new(entry.settings.auto_compact.clone()),
+            context_usage: Mutex::new(HashMap::new()),
+            patches: Mutex::new(VecDeque::new()),
+            guardrails: Mutex::new(guardrails::compile_guardrails(&entry.settings.guardrails)),
+            guardrail_hits: Mutex::new(HashMap::new()),
+            post_turn_hooks: Mutex::new(entry.settings.post_turn_hooks.clone()),
+            pre_turn_hooks: Mutex::new(entry.settings.pre_turn_hooks.clone()),
+            session: OnceLock::new(),
+        }
+    }
+
+    pub(crate) fn attach_session(&self, session: &Arc<WorkspaceSession>) {
+        let _ = self.session.set(Arc::downgrade(session));
+    }
+
+    pub(crate) fn apply_settings(&self, settings: &WorkspaceSettings) {
+        self.auto_commit
+            .store(settings.auto_commit, Ordering::SeqCst);
+        self.turn_snapshots
+            .store(settings.turn_snapshots, Ordering::SeqCst);
+        if let Ok(mut limits) = self.process_limits.lock() {
+            *limits = settings.process_limits.clone();
+        }
+        if let Ok(mut budget) = self.monthly_budget.lock() {
+            *budget = settings.monthly_budget.clone();
+        }
+        if let Ok(mut debounce_ms) = self.file_watch_debounce_ms.lock() {
+            *debounce_ms = settings.file_watch_debounce_ms;
+        }
+        if let Ok(mut policy) = self.auto_compact.lock() {
+            *policy = settings.auto_compact.clone();
+        }
+        if let Ok(mut current) = self.guardrails.lock() {
+            *current = guardrails::compile_guardrails(&settings.guardrails);
+        }
+        if let Ok(mut hooks) = self.post_turn_hooks.lock() {
+            *hooks = settings.post_turn_hooks.clone();
+        }
+        if let Ok(mut hooks) = self.pre_turn_hooks.lock() {
+            *hooks = settings.pre_turn_hooks.clone();
+        }
+    }
+
+    fn monthly_budget(&self) -> Option<MonthlyBudget> {
+        self.monthly_budget.lock().ok()?.clone()
+    }
+
+    /// The budget status when the workspace's monthly budget is used up and
+    /// not overridden, in which case new turns are refused.
+    pub(crate) async fn exhausted_budget(&self) -> Option<BudgetStatus> {
+        let budget = self.monthly_budget()?;
+        let workspace_id = self.workspace_id.clone();
+        let status = tokio::task::spawn_blocking(move || {
+            budget_core::budget_status(&workspace_id, Some(&budget))
+        })
+        .await
+        .ok()?;
+        status.blocks_turns().then_some(status)
+    }
+
+    pub(crate) fn process_limits(&self) -> Option<ProcessLimits> {
+        self.process_limits.lock().ok()?.clone()
+    }
+
+    /// Foreground turns in flight as `(threadId, turnId)`; the turn id is
+    /// known once the CLI reports `turn/started`.
+    pub(crate) fn running_turns(&self) -> Vec<(String, Option<String>)> {
+        let Ok(running) = self.running_turns.lock() else {
+            return Vec::new();
+        };
+        let turn_ids = self.turn_ids.lock().ok();
+        running
+            .iter()
+            .map(|thread_id| {
+                let turn_id = turn_ids
+                    .as_ref()
+                    .and_then(|turn_ids| turn_ids.get(thread_id).cloned());
+                (thread_id.clone(), turn_id)
+            })
+            .collect()
+    }
+
+    /// Whether `thread_id` has a turn in flight, which a further `turn/start`
+    /// for the thread steers rather than starting another turn.
+    pub(crate) fn is_turn_running(&self, thread_id: &str) -> bool {
+        self.running_turns
+            .lock()
+            .is_ok_and(|running| running.contains(thread_id))
+    }
+
+    /// Runs before a `turn/start` request is forwarded to the CLI. The
+    /// concurrency `permit` is held until the turn completes, and `emit`
+    /// carries the turn's `files/changed` and pre-turn hook events. Fails
+    /// when a pre-turn hook set to `abort` fails, and the turn isn't started.
+    pub(crate) async fn before_turn_start(
+        &self,
+        params: &Value,
+        permit: TurnPermit,
+        emit: EventEmitter,
+    ) -> Result<(), String> {
+        let Some(thread_id) = params.get("threadId").and_then(|value| value.as_str()) else {
+            return Ok(());
+        };
+        self.run_pre_turn_hooks(thread_id, &emit).await?;
+        self.mark_turn_running(thread_id);
+        if let Ok(mut permits) = self.turn_permits.lock() {
+            permits.insert(thread_id.to_string(), permit);
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.insert(thread_id.to_string(), TurnMetricsTracker::new());
+        }
+        if let Some(prompt) = turn_prompt_text(params) {
+            if let Ok(mut prompts) = self.prompts.lock() {
+                prompts.insert(thread_id.to_string(), prompt);
+            }
+        }
+        if self.turn_snapshots.load(Ordering::SeqCst) {
+            self.capture_snapshot(thread_id).await;
+        }
+        self.start_file_watch(thread_id, emit);
+        Ok(())
+    }
+
+    /// Runs before the file watch starts, so changes the hooks make
+    /// (a `git pull`, say) aren't attributed to the agent.
+    async fn run_pre_turn_hooks(&self, thread_id: &str, emit: &EventEmitter) -> Result<(), String> {
+        let hooks = self
+            .pre_turn_hooks
+            .lock()
+            .map(|hooks| hooks.clone())
+            .unwrap_or_default();
+        if hooks.is_empty() {
+            return Ok(());
+        }
+        let turn = HookedTurn {
+            workspace_id: self.workspace_id.clone(),
+            thread_id: thread_id.to_string(),
+            turn_id: None,
+        };
+        workspace_hooks::run_pre_turn_hooks(&hooks, &self.workspace_path, &turn, &**emit).await
+    }
+
+    /// Starts emitting `files/changed`, and `files/changedByAgent` once the
+    /// turn ends, when the workspace sets `fileWatchDebounceMs`. The
+    /// workspace is only walked then, and never on the send path.
+    fn start_file_watch(&self, thread_id: &str, emit: EventEmitter) {
+        let Some(debounce_ms) = self
+            .file_watch_debounce_ms
+            .lock()
+            .ok()
+            .and_then(|debounce_ms| *debounce_ms)
+        else {
+            return;
+        };
+        let watch = RunningFileWatch {
+            stop: Arc::new(Notify::new()),
+            turn_id: Arc::new(Mutex::new(None)),
+        };
+        tokio::spawn(watch_turn_files(
+            self.workspace_id.clone(),
+            thread_id.to_string(),
+            self.workspace_path.clone(),
+            Duration::from_millis(debounce_ms.max(MIN_FILE_WATCH_DEBOUNCE_MS)),
+            Arc::clone(&watch.stop),
+            Arc::clone(&watch.turn_id),
+            emit,
+        ));
+        if let Ok(mut watches) = self.file_watches.lock() {
+            if let Some(previous) = watches.insert(thread_id.to_string(), watch) {
+                previous.stop.notify_one();
+            }
+        }
+    }
+
+    async fn capture_snapshot(&self, thread_id: &str) {
+        let store_dir = match turn_snapshot_core::snapshot_store_dir(&self.workspace_id) {
+            Ok(dir) => dir,
+            Err(error) => {
+                tracing::warn!(workspace_id = %self.workspace_id, "turn snapshot: {error}");
+                return;
+            }
+        };
+        let root = self.workspace_path.clone();
+        let thread_id = thread_id.to_string();
+        let result = tokio::task::spawn_blocking(move || {
+            turn_snapshot_core::capture_snapshot(&root, &store_dir, &thread_id)
+        })
+        .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(error)) => {
+                tracing::warn!(
+                    workspace_id = %self.workspace_id,
+                    "turn snapshot: capture failed: {error}"
+                );
+            }
+            Err(error) => {
+                tracing::warn!(
+                    workspace_id = %self.workspace_id,
+                    "turn snapshot: capture task failed: {error}"
+                );
+            }
+        }
+    }
+
+    /// Keeps the maintenance coordinator from running jobs while this
+    /// thread's turn is in flight.
+    fn mark_turn_running(&self, thread_id: &str) {
+        if let Ok(mut running) = self.running_turns.lock() {
+            if running.insert(thread_id.to_string()) {
+                self.active_turns.started();
+            }
+        }
+    }
+
+    fn mark_turn_finished(&self, thread_id: &str) {
+        if let Ok(mut running) = self.running_turns.lock() {
+            if running.remove(thread_id) {
+                self.active_turns.finished();
+            }
+        }
+        if let Ok(mut turn_ids) = self.turn_ids.lock() {
+            turn_ids.remove(thread_id);
+        }
+        if let Ok(mut permits) = self.turn_permits.lock() {
+            permits.remove(thread_id);
+        }
+        if let Ok(mut hits) = self.guardrail_hits.lock() {
+            hits.remove(thread_id);
+        }
+        if let Some(watch) = self
+            .file_watches
+            .lock()
+            .ok()
+            .and_then(|mut watches| watches.remove(thread_id))
+        {
+            watch.stop.notify_one();
+        }
+    }
+
+    /// Releases a turn whose `turn/start` request was rejected, so it
+    /// doesn't hold a concurrency slot or block maintenance.
+    pub(crate) fn turn_start_failed(&self, thread_id: &str) {
+        self.mark_turn_finished(thread_id);
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.remove(thread_id);
+        }
+    }
+
+    fn observe_event(&self, message: &Value) {
+        let Some(thread_id) = message
+            .get("params")
+            .and_then(|params| params.get("threadId"))
+            .and_then(|value| value.as_str())
+        else {
+            return;
+        };
+        if message.get("method").and_then(|value| value.as_str()) == Some("turn/started") {
+            let turn_id = message.get("params").and_then(turn_id_from_params);
+            if let (Some(turn_id), Ok(watches)) = (turn_id.clone(), self.file_watches.lock()) {
+                if let Some(mut watched) = watches
+                    .get(thread_id)
+                    .and_then(|watch| watch.turn_id.lock().ok())
+                {
+                    *watched = Some(turn_id);
+                }
+            }
+            if let (Some(turn_id), Ok(mut turn_ids)) = (turn_id, self.turn_ids.lock()) {
+                turn_ids.insert(thread_id.to_string(), turn_id);
+            }
+        }
+        match message.get("method").and_then(|value| value.as_str()) {
+            Some("thread/tokenUsage/updated") => {
+                if let (Some(usage), Ok(mut tracked)) = (
+                    message.get("params").and_then(context_usage),
+                    self.context_usage.lock(),
+                ) {
+                    tracked.insert(thread_id.to_string(), usage);
+                }
+            }
+            Some("thread/compacted") => {
+                if let Ok(mut tracked) = self.context_usage.lock() {
+                    tracked.remove(thread_id);
+                }
+            }
+            _ => {}
+        }
+        if let Ok(mut metrics) = self.metrics.lock() {
+            if let Some(tracker) = metrics.get_mut(thread_id) {
+                tracker.observe(message);
+            }
+        }
+    }
+
+    /// Emits `item/patch` for each unified diff in a completed agent message
+    /// and keeps the patch for `patch_apply`.
+    fn extract_patches<E: EventSink>(&self, message: &Value, event_sink: &E) {
+        let params = &message["params"];
+        let (Some(thread_id), Some(source_item_id), Some(text)) = (
+            params["threadId"].as_str(),
+            params["item"]["id"].as_str(),
+            params["item"]["text"].as_str(),
+        ) else {
+            return;
+        };
+        if !text.contains("+++ ") {
+            return;
+        }
+        let turn_id = turn_id_from_params(params);
+        for (index, patch) in patch_core::extract_patches(text).into_iter().enumerate() {
+            let item_id = format!("{source_item_id}_patch_{index}");
+            if let Ok(mut patches) = self.patches.lock() {
+                if patches.len() >= MAX_KEPT_PATCHES {
+                    patches.pop_front();
+                }
+                patches.push_back((item_id.clone(), patch.clone()));
+            }
+            event_sink.emit_app_server_event(AppServerEvent::new(
+                self.workspace_id.clone(),
+                AppEvent::ItemPatch {
+                    thread_id: thread_id.to_string(),
+                    turn_id: turn_id.clone(),
+                    item: ThreadItem::Patch {
+                        id: item_id,
+                        source_item_id: source_item_id.to_string(),
+                        files: patch.files,
+                        diff: patch.diff,
+                    },
+                },
+            ));
+        }
+    }
+
+    /// The patch emitted as `item/patch` item `item_id`.
+    pub(crate) fn patch(&self, item_id: &str) -> Option<Patch> {
+        self.patches
+            .lock()
+            .ok()?
+            .iter()
+            .find(|(id, _)| id == item_id)
+            .map(|(_, patch)| patch.clone())
+    }
+
+    /// Matches the input of a tool call in `message` against the workspace's
+    /// guardrails, emitting `guardrail/triggered` for the first call that
+    /// trips one and interrupting the turn when the guardrail says so.
+    fn check_guardrails<E: EventSink>(&self, message: &Value, event_sink: &E) {
+        let Some(thread_id) = message["params"]["threadId"].as_str() else {
+            return;
+        };
+        let Some((item_id, input)) = guardrails::tool_call_input(message) else {
+            return;
+        };
+        let Some((pattern, action, excerpt)) = self.guardrails.lock().ok().and_then(|current| {
+            let hit = guardrails::match_guardrails(&current, &input)?;
+            Some((
+                hit.guardrail.pattern.clone(),
+                hit.guardrail.action,
+                hit.excerpt,
+            ))
+        }) else {
+            return;
+        };
+        let first_hit = self.guardrail_hits.lock().is_ok_and(|mut hits| {
+            hits.entry(thread_id.to_string())
+                .or_default()
+                .insert(item_id.clone())
+        });
+        if !first_hit {
+            return;
+        }
+        let turn_id = turn_id_from_params(&message["params"]).or_else(|| {
+            self.turn_ids
+                .lock()
+                .ok()
+                .and_then(|turn_ids| turn_ids.get(thread_id).cloned())
+        });
+        tracing::warn!(
+            workspace_id = %self.workspace_id,
+            thread_id,
+            "guardrail `{pattern}` matched a tool call ({action:?})"
+        );
+        event_sink.emit_app_server_event(AppServerEvent::new(
+            self.workspace_id.clone(),
+            AppEvent::GuardrailTriggered {
+                workspace_id: self.workspace_id.clone(),
+                thread_id: thread_id.to_string(),
+                turn_id: turn_id.clone(),
+                item_id,
+                pattern,
+                action,
+                excerpt,
+            },
+        ));
+        if action != GuardrailAction::Interrupt {
+            return;
+        }
+        let Some(session) = self.session.get().and_then(Weak::upgrade) else {
+            return;
+        };
+        let params = json!({ "threadId": thread_id, "turnId": turn_id });
+        tokio::spawn(async move {
+            if let Err(error) = session.send_request("turn/interrupt", params).await {
+                tracing::warn!(
+                    workspace_id = %session.entry.id,
+                    "guardrail: failed to interrupt turn: {error}"
+                );
+            }
+        });
+    }
+
+    /// Emits `turn/metrics` for the finished turn and appends it to the
+    /// workspace's metrics store next to the adapter thread store. Emits
+    /// `budget/warning` or `budget/exceeded` when the turn takes the month's
+    /// usage past 80% or 100% of the workspace budget.
+    fn emit_turn_metrics<E: EventSink>(&self, thread_id: &str, params: &Value, event_sink: &E) {
+        let Some(tracker) = self
+            .metrics
+            .lock()
+            .ok()
+            .and_then(|mut metrics| metrics.remove(thread_id))
+        else {
+            return;
+        };
+        let metrics = tracker.finish(thread_id, params);
+        event_sink.emit_app_server_event(AppServerEvent::new(
+            self.workspace_id.clone(),
+            AppEvent::TurnMetrics {
+                workspace_id: self.workspace_id.clone(),
+                metrics: metrics.clone(),
+            },
+        ));
+        let path = turn_metrics_path(&self.workspace_id);
+        let budget = self.monthly_budget();
+        let workspace_id = self.workspace_id.clone();
+        let thread_id = thread_id.to_string();
+        let event_sink = event_sink.clone();
+        tokio::task::spawn_blocking(move || {
+            let before = budget
+                .as_ref()
+                .map(|budget| budget_core::budget_status(&workspace_id, Some(budget)));
+            if let Err(error) = turn_metrics::append_turn_metrics(&path, metrics) {
+                tracing::warn!(workspace_id = %workspace_id, "turn metrics: {error}");
+                return;
+            }
+            let (Some(before), Some(budget)) = (before, budget) else {
+                return;
+            };
+            let after = budget_core::budget_status(&workspace_id, Some(&budget));
+            let crossed = |threshold: f64| {
+                before.used_fraction.unwrap_or(0.0) < threshold
+                    && after
+                        .used_fraction
+                        .is_some_and(|fraction| fraction >= threshold)
+            };
+            let event = if crossed(1.0) {
+                AppEvent::BudgetExceeded {
+                    workspace_id: workspace_id.clone(),
+                    thread_id,
+                    status: after,
+                    turn_refused: false,
+                }
+            } else if crossed(budget_core::WARN_FRACTION) {
+                AppEvent::BudgetWarning {
+                    workspace_id: workspace_id.clone(),
+                    thread_id,
+                    status: after,
+                }
+            } else {
+                return;
+            };
+            event_sink.emit_app_server_event(AppServerEvent::new(workspace_id, event));
+        });
+    }
+
+    /// Starts compacting the thread (`thread/compact/start`) when the turn
+    /// left its context at or past the workspace's `autoCompact` threshold.
+    /// Codex reports usage in `thread/tokenUsage/updated`; adapter sessions
+    /// estimate it from the transcript as the `contextTokens` of
+    /// `turn/completed`.
+    fn maybe_auto_compact(&self, thread_id: &str, params: &Value) {
+        let reported = self
+            .context_usage
+            .lock()
+            .ok()
+            .and_then(|mut tracked| tracked.remove(thread_id));
+        let Some(policy) = self
+            .auto_compact
+            .lock()
+            .ok()
+            .and_then(|policy| policy.clone())
+        else {
+            return;
+        };
+        let usage = reported.or_else(|| {
+            params
+                .get("contextTokens")
+                .and_then(|value| value.as_u64())
+                .map(|tokens| ContextUsage {
+                    tokens,
+                    window: None,
+                })
+        });
+        if !usage.is_some_and(|usage| needs_compaction(&policy, usage)) {
+            return;
+        }
+        let Some(session) = self.session.get().and_then(Weak::upgrade) else {
+            return;
+        };
+        let workspace_id = self.workspace_id.clone();
+        let thread_id = thread_id.to_string();
+        tokio::spawn(async move {
+            // A turn started since keeps the context it was given.
+            if session.turn_hooks.is_turn_running(&thread_id) {
+                return;
+            }
+            tracing::info!(
+                workspace_id = %workspace_id,
+                thread_id = %thread_id,
+                "context reached {}%, compacting",
+                policy.threshold_percent
+            );
+            let params = json!({ "threadId": thread_id });
+            if let Err(error) = session.send_request("thread/compact/start", params).await {
+                tracing::warn!(
+                    workspace_id = %workspace_id,
+                    "auto-compact failed: {error}"
+                );
+            }
+        });
+    }
+
+    fn take_turn_prompt(&self, thread_id: &str) -> Option<String> {
+        self.prompts.lock().ok()?.remove(thread_id)
+    }
+
+    fn run_post_turn_hooks<E: EventSink>(
+        &self,
+        thread_id: &str,
+        turn_id: Option<String>,
+        event_sink: E,
+    ) {
+        let hooks = self
+            .post_turn_hooks
+            .lock()
+            .map(|hooks| hooks.clone())
+            .unwrap_or_default();
+        if hooks.is_empty() {
+            return;
+        }
+        let turn = HookedTurn {
+            workspace_id: self.workspace_id.clone(),
+            thread_id: thread_id.to_string(),
+            turn_id,
+        };
+        tokio::spawn(workspace_hooks::run_post_turn_hooks(
+            hooks,
+            self.workspace_path.clone(),
+            turn,
+            event_sink,
+        ));
+    }
+
+    fn on_turn_completed<E: EventSink>(&self, message: &Value, event_sink: E) {
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let thread_id = params
+            .get("threadId")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string();
+        self.mark_turn_finished(&thread_id);
+        self.emit_turn_metrics(&thread_id, &params, &event_sink);
+        self.maybe_auto_compact(&thread_id, &params);
+        let prompt = self.take_turn_prompt(&thread_id);
+        let turn_id = turn_id_from_params(&params);
+        self.run_post_turn_hooks(&thread_id, turn_id.clone(), event_sink.clone());
+        if !self.auto_commit.load(Ordering::SeqCst) {
+            return;
+        }
+        let workspace_id = self.workspace_id.clone();
+        let repo_path = self.workspace_path.clone();
+        let commit_message = auto_commit_message(prompt.as_deref());
+        tokio::spawn(async move {
+            match git_core::commit_all_changes(&repo_path, &commit_message).await {
+                Ok(Some(sha)) => {
+                    event_sink.emit_app_server_event(AppServerEvent::new(
+                        workspace_id.clone(),
+                        AppEvent::GitCommitted {
+                            workspace_id,
+                            thread_id,
+                            turn_id,
+                            sha,
+                            message: commit_message,
+                        },
+                    ));
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    tracing::warn!(workspace_id = %workspace_id, "auto-commit failed: {error}");
+                }
+            }
+        });
+    }
+}
+
+impl Drop for TurnHooks {
+    fn drop(&mut self) {
+        if let Ok(running) = self.running_turns.lock() {
+            for _ in running.iter() {
+                self.active_turns.finished();
+            }
+        }
+        if let Ok(watches) = self.file_watches.lock() {
+            for watch in watches.values() {
+                watch.stop.notify_one();
+            }
+        }
+    }
+}
+
+/// Polls the workspace every `interval` while a turn runs, emitting
+/// `files/changed` for what changed since the previous poll. Polls once
+/// more when stopped so the turn's last edits are reported, then emits
+/// `files/changedByAgent` with everything the turn changed.
+async fn watch_turn_files(
+    workspace_id: String,
+    thread_id: String,
+    root: PathBuf,
+    interval: Duration,
+    stop: Arc<Notify>,
+    turn_id: Arc<Mutex<Option<String>>>,
+    emit: EventEmitter,
+) {
+    let Ok(mut watch) = tokio::task::spawn_blocking(move || FileWatch::start(root)).await else {
+        return;
+    };
+    loop {
+        let stopped = tokio::select! {
+            _ = tokio::time::sleep(interval) => false,
+            _ = stop.notified() => true,
+        };
+        let polled = tokio::task::spawn_blocking(move || {
+            let files = watch.poll();
+            (files, watch)
+        })
+        .await;
+        let Ok((files, returned)) = polled else {
+            return;
+        };
+        watch = returned;
+        if !files.is_empty() {
+            let turn_id = turn_id.lock().ok().and_then(|turn_id| turn_id.clone());
+            emit(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::FilesChanged {
+                    workspace_id: workspace_id.clone(),
+                    thread_id: thread_id.clone(),
+                    turn_id,
+                    files,
+                },
+            ));
+        }
+        if stopped {
+            break;
+        }
+    }
+    let changes = watch.changes_since_start();
+    if changes.is_empty() {
+        return;
+    }
+    let turn_id = turn_id.lock().ok().and_then(|turn_id| turn_id.clone());
+    emit(AppServerEvent::new(
+        workspace_id.clone(),
+        AppEvent::FilesChangedByAgent {
+            workspace_id,
+            thread_id,
+            turn_id,
+            paths: changes.paths(),
+            added: changes.added,
+            modified: changes.modified,
+            deleted: changes.deleted,
+        },
+    ));
+}
+
+fn turn_prompt_text(params: &Value) -> Option<String> {
+    let input = params.get("input")?;
+    if let Some(text) = input.as_str() {
+        return Some(text.to_string());
+    }
+    input.as_array()?.iter().find_map(|item| {
+        if item.get("type").and_then(|value| value.as_str()) != Some("text") {
+            return None;
+        }
+        item.get("text")
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string())
+    })
+}
+
+/// Reads a `thread/tokenUsage/updated` event's params. The context holds
+/// the last request's tokens, or the thread's total before one is reported.
+fn context_usage(params: &Value) -> Option<ContextUsage> {
+    let usage = params
+        .get("tokenUsage")
+        .or_else(|| params.get("token_usage"))?;
+    let total_tokens = |key: &str| {
+        usage
+            .get(key)
+            .and_then(|tokens| {
+                tokens
+                    .get("totalTokens")
+                    .or_else(|| tokens.get("total_tokens"))
+            })
+            .and_then(|value| value.as_u64())
+            .filter(|tokens| *tokens > 0)
+    };
+    let tokens = total_tokens("last").or_else(|| total_tokens("total"))?;
+    let window = usage
+        .get("modelContextWindow")
+        .or_else(|| usage.get("model_context_window"))
+        .and_then(|value| value.as_u64());
+    Some(ContextUsage { tokens, window })
+}
+
+/// Whether `usage` fills the policy's share of the context window. Without
+/// a reported or configured window there is nothing to compare against.
+fn needs_compaction(policy: &AutoCompactPolicy, usage: ContextUsage) -> bool {
+    let Some(window) = usage
+        .window
+        .or(policy.context_window)
+        .filter(|window| *window > 0)
+    else {
+        return false;
+    };
+    u128::from(usage.tokens) * 100 >= u128::from(window) * u128::from(policy.threshold_percent)
+}
+
+/// Rejects an `autoCompact` threshold outside 1-100%.
+pub(crate) fn validate_auto_compact(policy: Option<&AutoCompactPolicy>) -> Result<(), String> {
+    match policy {
+        Some(policy) if !(1..=100).contains(&policy.threshold_percent) => Err(format!(
+            "autoCompact thresholdPercent must be between 1 and 100, got {}",
+            policy.threshold_percent
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn turn_id_from_params(params: &Value) -> Option<String> {
+    params
+        .get("turnId")
+        .and_then(|value| value.as_str())
+        .or_else(|| {
+            params
+                .get("turn")
+                .and_then(|turn| turn.get("id"))
+                .and_then(|value| value.as_str())
+        })
+        .map(|value| value.to_string())
+}
+
+pub(crate) fn auto_commit_message(prompt: Option<&str>) -> String {
+    let first_line = prompt
+        .and_then(|prompt| prompt.lines().map(str::trim).find(|line| !line.is_empty()))
+        .unwrap_or(AUTO_COMMIT_FALLBACK_MESSAGE);
+    if first_line.chars().count() <= AUTO_COMMIT_SUBJECT_MAX_CHARS {
+        return first_line.to_string();
+    }
+    let truncated: String = first_line
+        .chars()
+        .take(AUTO_COMMIT_SUBJECT_MAX_CHARS - 3)
+        .collect();
+    format!("{}...", truncated.trim_end())
+}
+
+/// Event sink wrapper that lets `TurnHooks` observe events before they are
+/// forwarded to the frontend.
+#[derive(Clone)]
+pub(crate) struct TurnObserverSink<E: EventSink> {
+    inner: E,
+    hooks: Arc<TurnHooks>,
+}
+
+impl<E: EventSink> TurnObserverSink<E> {
+    pub(crate) fn new(inner: E, hooks: Arc<TurnHooks>) -> Self {
+        Self { inner, hooks }
+    }
+}
+
+impl<E: EventSink> EventSink for TurnObserverSink<E> {
+    fn emit_app_server_event(&self, event: AppServerEvent) {
+        self.hooks.observe_event(&event.message);
+        let method = event.message.get("method").and_then(|value| value.as_str());
+        let completed = method == Some("turn/completed");
+        let agent_message = method == Some("item/completed")
+            && event.message["params"]["item"]["type"].as_str() == Some("agentMessage");
+        let message = (completed || agent_message).then(|| event.message.clone());
+        self.hooks.check_guardrails(&event.message, &self.inner);
+        self.inner.emit_app_server_event(event);
+        match message {
+            Some(message) if completed => {
+                self.hooks.on_turn_completed(&message, self.inner.clone())
+            }
+            Some(message) => self.hooks.extract_patches(&message, &self.inner),
+            None => {}
+        }
+    }
+
+    fn emit_terminal_output(&self, event: TerminalOutput) {
+        self.inner.emit_terminal_output(event);
+    }
+
+    fn emit_terminal_exit(&self, event: TerminalExit) {
+        self.inner.emit_terminal_exit(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        auto_commit_message, context_usage, needs_compaction, turn_id_from_params,
+        turn_prompt_text, ContextUsage,
+    };
+    use crate::types::AutoCompactPolicy;
+    use serde_json::json;
+
+    #[test]
+    fn turn_prompt_text_reads_string_and_array_input() {
+        assert_eq!(
+            turn_prompt_text(&json!({ "input": "fix the build" })).as_deref(),
+            Some("fix the build")
+        );
+        assert_eq!(
+            turn_prompt_text(&json!({
+                "input": [
+                    { "type": "image", "url": "data:image/png;base64,AAA" },
+                    { "type": "text", "text": "describe this" }
+                ]
+            }))
+            .as_deref(),
+            Some("describe this")
+        );
+        assert!(turn_prompt_text(&json!({})).is_none());
+    }
+
+    #[test]
+    fn turn_id_reads_flat_and_nested_params() {
+        assert_eq!(
+            turn_id_from_params(&json!({ "turnId": "turn-1" })).as_deref(),
+            Some("turn-1")
+        );
+        assert_eq!(
+            turn_id_from_params(&json!({ "turn": { "id": "turn-2" } })).as_deref(),
+            Some("turn-2")
+        );
+    }
+
+    #[test]
+    fn compacts_once_context_reaches_the_threshold() {
+        let usage = context_usage(&json!({
+            "threadId": "t",
+            "tokenUsage": {
+                "total": { "totalTokens": 190_000 },
+                "last": { "totalTokens": 160_000 },
+                "modelContextWindow": 200_000
+            }
+        }))
+        .expect("usage");
+        assert_eq!(
+            usage,
+            ContextUsage {
+                tokens: 160_000,
+                window: Some(200_000)
+            }
+        );
+        let policy = |threshold_percent, context_window| AutoCompactPolicy {
+            threshold_percent,
+            context_window,
+        };
+        assert!(needs_compaction(&policy(80, None), usage));
+        assert!(!needs_compaction(&policy(81, None), usage));
+
+        let estimated = ContextUsage {
+            tokens: 50_000,
+            window: None,
+        };
+        assert!(!needs_compaction(&policy(50, None), estimated));
+        assert!(needs_compaction(&policy(50, Some(100_000)), estimated));
+    }
+
+    #[test]
+    fn auto_commit_message_uses_first_prompt_line() {
+        assert_eq!(
+            auto_commit_message(Some("\n  Add retry logic  \nand tests")),
+            "Add retry logic"
+        );
+        assert_eq!(auto_commit_message(None), "Agent turn checkpoint");
+        assert_eq!(auto_commit_message(Some("   ")), "Agent turn checkpoint");
+        let long = "x".repeat(100);
+        let message = auto_commit_message(Some(&long));
+        assert_eq!(message.chars().count(), 72);
+        assert!(message.ends_with("..."));
+    }
+}
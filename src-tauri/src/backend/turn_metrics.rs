@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::backend::adapter_base::now_epoch;
+use crate::shared::atomic_write::{read_with_backup, write_with_backup};
+
+const MAX_STORED_TURNS: usize = 1_000;
+/// Item types that are conversation content rather than tool work.
+const NON_TOOL_ITEM_TYPES: &[&str] = &[
+    "agentMessage",
+    "userMessage",
+    "reasoning",
+    "plan",
+    "contextCompaction",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnMetrics {
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: Option<String>,
+    pub(crate) started_at: u64,
+    pub(crate) duration_ms: u64,
+    pub(crate) tool_calls: u32,
+    pub(crate) output_bytes: u64,
+    pub(crate) input_tokens: Option<u64>,
+    pub(crate) output_tokens: Option<u64>,
+    /// Reported by the Claude CLI.
+    #[serde(default)]
+    pub(crate) cost_usd: Option<f64>,
+    pub(crate) exit_code: Option<i32>,
+    /// The turn ran past its timeout and was stopped.
+    #[serde(default)]
+    pub(crate) timed_out: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TurnMetricsStore {
+    turns: Vec<TurnMetrics>,
+}
+
+/// Accumulates metrics from the events of one in-flight turn.
+pub(crate) struct TurnMetricsTracker {
+    started: Instant,
+    started_at: u64,
+    tool_calls: u32,
+    output_bytes: u64,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    timed_out: bool,
+}
+
+impl TurnMetricsTracker {
+    pub(crate) fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            started_at: now_epoch(),
+            tool_calls: 0,
+            output_bytes: 0,
+            input_tokens: None,
+            output_tokens: None,
+            timed_out: false,
+        }
+    }
+
+    pub(crate) fn observe(&mut self, message: &Value) {
+        let method = message
+            .get("method")
+            .and_then(|value| value.as_str())
+            .unwrap_or_default();
+        let params = message.get("params").unwrap_or(&Value::Null);
+        if let Some(delta) = params.get("delta").and_then(|value| value.as_str()) {
+            self.output_bytes += delta.len() as u64;
+        }
+        match method {
+            "item/started" => {
+                let item_type = params
+                    .get("item")
+                    .and_then(|item| item.get("type"))
+                    .and_then(|value| value.as_str())
+                    .unwrap_or_default();
+                if !item_type.is_empty() && !NON_TOOL_ITEM_TYPES.contains(&item_type) {
+                    self.tool_calls += 1;
+                }
+            }
+            "thread/tokenUsage/updated" => {
+                let last = params
+                    .get("tokenUsage")
+                    .or_else(|| params.get("token_usage"))
+                    .and_then(|usage| usage.get("last"));
+                let read = |camel: &str, snake: &str| {
+                    last.and_then(|last| last.get(camel).or_else(|| last.get(snake)))
+                        .and_then(|value| value.as_u64())
+                };
+                if let Some(tokens) = read("inputTokens", "input_tokens") {
+                    *self.input_tokens.get_or_insert(0) += tokens;
+                }
+                if let Some(tokens) = read("outputTokens", "output_tokens") {
+                    *self.output_tokens.get_or_insert(0) += tokens;
+                }
+            }
+            "turn/timeout" => self.timed_out = true,
+            _ => {}
+        }
+    }
+
+    /// `completed_params` are the `turn/completed` params; adapter sessions
+    /// include the CLI's `exitCode` (and Claude its `costUsd`) there.
+    pub(crate) fn finish(self, thread_id: &str, completed_params: &Value) -> TurnMetrics {
+        TurnMetrics {
+            thread_id: thread_id.to_string(),
+            turn_id: completed_params
+                .get("turnId")
+                .or_else(|| completed_params.get("turn").and_then(|turn| turn.get("id")))
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string()),
+            started_at: self.started_at,
+            duration_ms: self.started.elapsed().as_millis() as u64,
+            tool_calls: self.tool_calls,
+            output_bytes: self.output_bytes,
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            cost_usd: completed_params
+                .get("costUsd")
+                .and_then(|value| value.as_f64()),
+            exit_code: completed_params
+                .get("exitCode")
+                .and_then(|value| value.as_i64())
+                .map(|value| value as i32),
+            timed_out: self.timed_out,
+        }
+    }
+}
+
+pub(crate) fn append_turn_metrics(path: &Path, metrics: TurnMetrics) -> Result<(), String> {
+    let mut store = read_store(path);
+    store.turns.push(metrics);
+    if store.turns.len() > MAX_STORED_TURNS {
+        let excess = store.turns.len() - MAX_STORED_TURNS;
+        store.turns.drain(..excess);
+    }
+    let json = serde_json::to_string(&store).map_err(|e| e.to_string())?;
+    write_with_backup(path, json.as_bytes())
+        .map_err(|e| format!("Failed to write turn metrics: {e}"))
+}
+
+/// Most recent turns first, optionally limited to one thread.
+pub(crate) fn read_turn_metrics(
+    path: &Path,
+    thread_id: Option<&str>,
+    limit: Option<usize>,
+) -> Vec<TurnMetrics> {
+    read_store(path)
+        .turns
+        .into_iter()
+        .rev()
+        .filter(|turn| thread_id.is_none_or(|thread_id| turn.thread_id == thread_id))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
+fn read_store(path: &Path) -> TurnMetricsStore {
+    read_with_backup(path, |content| {
+        serde_json::from_str(content).map_err(|e| e.to_string())
+    })
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_turn_metrics, read_turn_metrics, TurnMetricsTracker};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn tracker_counts_tools_output_and_tokens() {
+        let mut tracker = TurnMetricsTracker::new();
+        tracker.observe(&json!({
+            "method": "item/started",
+            "params": { "item": { "id": "a", "type": "agentMessage" } }
+        }));
+        tracker.observe(&json!({
+            "method": "item/started",
+            "params": { "item": { "id": "b", "type": "commandExecution" } }
+        }));
+        tracker.observe(&json!({
+            "method": "item/started",
+            "params": { "item": { "id": "c", "type": "tool_use" } }
+        }));
+        tracker.observe(&json!({
+            "method": "item/agentMessage/delta",
+            "params": { "delta": "hello" }
+        }));
+        for _ in 0..2 {
+            tracker.observe(&json!({
+                "method": "thread/tokenUsage/updated",
+                "params": { "tokenUsage": { "last": { "inputTokens": 10, "outputTokens": 3 } } }
+            }));
+        }
+
+        let metrics = tracker.finish(
+            "thread-1",
+            &json!({ "turnId": "turn-1", "exitCode": 0, "costUsd": 0.25 }),
+        );
+        assert_eq!(metrics.turn_id.as_deref(), Some("turn-1"));
+        assert_eq!(metrics.tool_calls, 2);
+        assert_eq!(metrics.output_bytes, 5);
+        assert_eq!(metrics.input_tokens, Some(20));
+        assert_eq!(metrics.output_tokens, Some(6));
+        assert_eq!(metrics.cost_usd, Some(0.25));
+        assert_eq!(metrics.exit_code, Some(0));
+        assert!(!metrics.timed_out);
+    }
+
+    #[test]
+    fn stored_metrics_filter_by_thread_newest_first() {
+        let path =
+            std::env::temp_dir().join(format!("codex-monitor-metrics-{}.json", Uuid::new_v4()));
+        for (thread_id, turn_id) in [("t1", "a"), ("t2", "b"), ("t1", "c")] {
+            let metrics =
+                TurnMetricsTracker::new().finish(thread_id, &json!({ "turnId": turn_id }));
+            append_turn_metrics(&path, metrics).expect("append metrics");
+        }
+        let turns = read_turn_metrics(&path, Some("t1"), None)
+            .into_iter()
+            .map(|turn| turn.turn_id.unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(turns, vec!["c", "a"]);
+        assert_eq!(read_turn_metrics(&path, None, Some(1)).len(), 1);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink, HookResult, ThreadItem};
+use crate::shared::path_env::build_cli_path_env;
+use crate::shared::process_core::{kill_child_process_tree, shell_command};
+use crate::types::{PostTurnHook, PreTurnHook, PreTurnHookFailure};
+
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+type Emit<'a> = &'a (dyn Fn(AppServerEvent) + Sync);
+
+/// The turn the hooks run around. Pre-turn hooks run before the CLI
+/// reports a turn id.
+pub(crate) struct HookedTurn {
+    pub(crate) workspace_id: String,
+    pub(crate) thread_id: String,
+    pub(crate) turn_id: Option<String>,
+}
+
+impl HookedTurn {
+    fn emit_line(&self, emit: Emit<'_>, command: &str, stream: &str, line: String) {
+        emit(AppServerEvent::new(
+            self.workspace_id.clone(),
+            AppEvent::HookOutput {
+                workspace_id: self.workspace_id.clone(),
+                thread_id: self.thread_id.clone(),
+                turn_id: self.turn_id.clone(),
+                command: command.to_string(),
+                stream: stream.to_string(),
+                line,
+            },
+        ));
+    }
+
+    fn emit_item(&self, emit: Emit<'_>, item: ThreadItem) {
+        emit(AppServerEvent::new(
+            self.workspace_id.clone(),
+            AppEvent::ItemCompleted {
+                thread_id: self.thread_id.clone(),
+                turn_id: self.turn_id.clone().unwrap_or_default(),
+                item,
+            },
+        ));
+    }
+}
+
+async fn forward_lines<R>(reader: R, turn: &HookedTurn, emit: Emit<'_>, command: &str, stream: &str)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        turn.emit_line(emit, command, stream, line);
+    }
+}
+
+async fn run_hook(
+    command: &str,
+    timeout_secs: Option<u64>,
+    root: &Path,
+    turn: &HookedTurn,
+    emit: Emit<'_>,
+) -> HookResult {
+    let started = Instant::now();
+    let command = command.trim().to_string();
+    let limit = timeout_secs
+        .filter(|secs| *secs > 0)
+        .map_or(DEFAULT_HOOK_TIMEOUT, Duration::from_secs);
+    let mut result = HookResult {
+        command: command.clone(),
+        exit_code: None,
+        timed_out: false,
+        duration_ms: 0,
+        error: None,
+    };
+
+    let mut shell = shell_command(&command);
+    shell
+        .current_dir(root)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(path_env) = build_cli_path_env(None) {
+        shell.env("PATH", path_env);
+    }
+    let mut child = match shell.spawn() {
+        Ok(child) => child,
+        Err(err) => {
+            result.error = Some(format!("Failed to run `{command}`: {err}"));
+            return result;
+        }
+    };
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let output = async {
+        tokio::join!(
+            async {
+                if let Some(stdout) = stdout {
+                    forward_lines(stdout, turn, emit, &command, "stdout").await;
+                }
+            },
+            async {
+                if let Some(stderr) = stderr {
+                    forward_lines(stderr, turn, emit, &command, "stderr").await;
+                }
+            },
+        )
+    };
+    let waited = timeout(limit, async {
+        output.await;
+        child.wait().await
+    })
+    .await;
+    match waited {
+        Ok(Ok(status)) => result.exit_code = status.code(),
+        Ok(Err(err)) => result.error = Some(err.to_string()),
+        Err(_) => {
+            kill_child_process_tree(&mut child).await;
+            result.timed_out = true;
+            result.error = Some(format!("Timed out after {}s", limit.as_secs()));
+        }
+    }
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+/// Runs the workspace's post-turn hooks one after another, streaming their
+/// output as `hook/output`, then adds a `verification` item to the turn:
+/// `verified` when every hook exited 0, otherwise `failed`.
+pub(crate) async fn run_post_turn_hooks<E: EventSink>(
+    hooks: Vec<PostTurnHook>,
+    root: PathBuf,
+    turn: HookedTurn,
+    event_sink: E,
+) {
+    let emit = |event: AppServerEvent| event_sink.emit_app_server_event(event);
+    let mut results = Vec::with_capacity(hooks.len());
+    for hook in hooks.iter().filter(|hook| !hook.command.trim().is_empty()) {
+        results.push(run_hook(&hook.command, hook.timeout_secs, &root, &turn, &emit).await);
+    }
+    if results.is_empty() {
+        return;
+    }
+    let status = verification_status(&results);
+    let turn_id = turn.turn_id.clone().unwrap_or_default();
+    turn.emit_item(
+        &emit,
+        ThreadItem::Verification {
+            id: format!("verification-{turn_id}"),
+            status: status.to_string(),
+            hooks: results,
+        },
+    );
+}
+
+fn verification_status(results: &[HookResult]) -> &'static str {
+    if results.iter().all(HookResult::passed) {
+        "verified"
+    } else {
+        "failed"
+    }
+}
+
+/// Runs the workspace's pre-turn hooks one after another before the turn
+/// is forwarded to the CLI, streaming their output as `hook/output`, then
+/// adds a `preparation` item to the thread. A failing hook set to `abort`
+/// stops the remaining hooks and returns an error, so the turn is refused.
+pub(crate) async fn run_pre_turn_hooks(
+    hooks: &[PreTurnHook],
+    root: &Path,
+    turn: &HookedTurn,
+    emit: Emit<'_>,
+) -> Result<(), String> {
+    let mut results = Vec::with_capacity(hooks.len());
+    let mut aborted_by = None;
+    for hook in hooks.iter().filter(|hook| !hook.command.trim().is_empty()) {
+        let result = run_hook(&hook.command, hook.timeout_secs, root, turn, emit).await;
+        let abort = !result.passed() && hook.on_failure == PreTurnHookFailure::Abort;
+        results.push(result);
+        if abort {
+            aborted_by = Some(hook.command.trim().to_string());
+            break;
+        }
+    }
+    if results.is_empty() {
+        return Ok(());
+    }
+    let status = preparation_status(&results, aborted_by.is_some());
+    turn.emit_item(
+        emit,
+        ThreadItem::Preparation {
+            id: format!("preparation-{}", Uuid::new_v4()),
+            status: status.to_string(),
+            hooks: results,
+        },
+    );
+    match aborted_by {
+        Some(command) => Err(format!(
+            "Pre-turn hook `{command}` failed; turn not started"
+        )),
+        None => Ok(()),
+    }
+}
+
+fn preparation_status(results: &[HookResult], aborted: bool) -> &'static str {
+    if aborted {
+        "aborted"
+    } else if results.iter().all(HookResult::passed) {
+        "ready"
+    } else {
+        "failed"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{preparation_status, verification_status};
+    use crate::backend::events::HookResult;
+
+    fn result(exit_code: Option<i32>) -> HookResult {
+        HookResult {
+            command: "cargo test".to_string(),
+            exit_code,
+            timed_out: exit_code.is_none(),
+            duration_ms: 10,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn any_failed_or_timed_out_hook_fails_verification() {
+        assert_eq!(verification_status(&[result(Some(0))]), "verified");
+        assert_eq!(
+            verification_status(&[result(Some(0)), result(Some(101))]),
+            "failed"
+        );
+        assert_eq!(verification_status(&[result(None)]), "failed");
+    }
+
+    #[test]
+    fn preparation_reports_aborted_before_failed() {
+        assert_eq!(preparation_status(&[result(Some(0))], false), "ready");
+        assert_eq!(preparation_status(&[result(Some(1))], false), "failed");
+        assert_eq!(
+            preparation_status(&[result(Some(0)), result(None)], true),
+            "aborted"
+        );
+    }
+}
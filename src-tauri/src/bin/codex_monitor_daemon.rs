@@ -1,18 +1,28 @@
 #[allow(dead_code)]
 #[path = "../backend/mod.rs"]
 mod backend;
+#[path = "../claude/models.rs"]
+mod claude_models;
+#[path = "../claude/sessions.rs"]
+mod claude_sessions;
+#[path = "../claude/settings.rs"]
+mod claude_settings;
 #[path = "../codex/args.rs"]
 mod codex_args;
 #[path = "../codex/home.rs"]
 mod codex_home;
 #[path = "../codex/config.rs"]
 mod codex_config;
+#[path = "../files/encoding.rs"]
+mod file_encoding;
 #[path = "../files/io.rs"]
 mod file_io;
 #[path = "../files/ops.rs"]
 mod file_ops;
 #[path = "../files/policy.rs"]
 mod file_policy;
+#[path = "../files/tree.rs"]
+mod file_tree;
 #[path = "../rules.rs"]
 mod rules;
 #[path = "../storage.rs"]
@@ -28,6 +38,18 @@ mod workspace_settings;
 mod types;
 
 // Provide feature-style module paths for shared cores when compiled in the daemon.
+mod claude {
+    pub(crate) mod models {
+        pub(crate) use crate::claude_models::*;
+    }
+    pub(crate) mod sessions {
+        pub(crate) use crate::claude_sessions::*;
+    }
+    pub(crate) mod settings {
+        pub(crate) use crate::claude_settings::*;
+    }
+}
+
 mod codex {
     pub(crate) mod args {
         pub(crate) use crate::codex_args::*;
@@ -41,6 +63,9 @@ mod codex {
 }
 
 mod files {
+    pub(crate) mod encoding {
+        pub(crate) use crate::file_encoding::*;
+    }
     pub(crate) mod io {
         pub(crate) use crate::file_io::*;
     }
@@ -50,6 +75,9 @@ mod files {
     pub(crate) mod policy {
         pub(crate) use crate::file_policy::*;
     }
+    pub(crate) mod tree {
+        pub(crate) use crate::file_tree::*;
+    }
 }
 
 use serde::{Deserialize, Serialize};
@@ -59,53 +87,124 @@ use std::env;
 use std::fs::File;
 use std::io::Read;
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use ignore::WalkBuilder;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
-use tokio::net::{TcpListener, TcpStream};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
 use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
 
 use backend::app_server::{
-    spawn_workspace_session, CliSpawnConfig, WorkspaceSession,
+    spawn_workspace_session, CliSpawnConfig, PendingRequestInfo, WorkspaceSession,
 };
-use backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use backend::adapter_base::turn_metrics_path;
+use backend::background_queue::BackgroundCallbackInfo;
+use backend::events::{AppEvent, AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use backend::plugin_adapter::{self, CliPlugins};
+use backend::process_monitor;
+use backend::turn_metrics::{self, TurnMetrics};
 use storage::{read_settings, read_workspaces};
 use shared::{
-    agent_profiles_core, codex_core, files_core, git_core, settings_core, workspaces_core,
+    agent_profiles_core, agents_md_core, codex_aux_core, codex_core, file_tail_core, files_core, git_core, settings_core, workspaces_core,
     worktree_core,
 };
+use shared::budget_core::{self, BudgetStatus};
+use shared::account_profiles_core;
+use shared::activity_core::{self, ActivityTimeline};
+use shared::app_error::AppResult;
+use shared::app_state_core::{self, AppStateTransfer};
+use shared::audit_log_core::{self, AuditLogQuery, AuditLogQueryResult};
+use shared::background_turn_core;
+use shared::cli_updates_core::{self, CliUpdateStatus};
 use shared::codex_core::CodexLoginCancelState;
+use shared::codex_sessions_core;
+use shared::connection_role_core::{self, ConnectionRole};
+use shared::event_replay_core::{EventReplay, SequencedEvent};
+use shared::event_schema_core;
+use shared::headless_core::{self, HeadlessTurn};
+use shared::logging_core;
+use shared::maintenance_core::{self, ActiveTurns, MaintenanceCoordinator, MaintenanceStatus};
+use shared::mcp_servers_core;
+use shared::patch_core::{self, PatchApplyResult};
+use shared::path_env;
+use shared::runtime_services::RuntimeServices;
+use shared::session_watchdog_core;
+use shared::settings_profiles_core::{self, SettingsProfileInfo};
+use shared::shutdown_core;
+use shared::turn_snapshot_core::{self, TurnRollbackResponse};
+use shared::workflows_core;
+use shared::workspace_discovery_core;
+use shared::workspace_templates_core;
+use shared::workspace_trash_core;
 use workspace_settings::apply_workspace_settings_update;
 use types::{
-    AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceSettings, WorktreeSetupStatus,
+    AppSettings, McpServer, TrashedWorkspace, Workflow, WorkspaceEntry, WorkspaceInfo,
+    WorkspaceSettings, WorkspaceTemplate, WorktreeSetupStatus,
 };
 
 const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:4732";
+const DEFAULT_LOG_TAIL_LINES: u32 = 200;
+const DEFAULT_HEADLESS_TIMEOUT_SECS: u64 = 1800;
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_with_client(
     event_sink: DaemonEventSink,
     client_version: String,
     entry: WorkspaceEntry,
-    config: CliSpawnConfig,
+    mut config: CliSpawnConfig,
+    sessions: Arc<Mutex<HashMap<String, Arc<WorkspaceSession>>>>,
+    app_settings: Arc<Mutex<AppSettings>>,
+    active_turns: ActiveTurns,
+    services: RuntimeServices,
 ) -> impl std::future::Future<Output = Result<Arc<WorkspaceSession>, String>> {
-    spawn_workspace_session(
-        entry,
-        config,
-        client_version,
-        event_sink,
-    )
+    config.active_turns = active_turns;
+    config.services = services;
+    async move {
+        let session = spawn_workspace_session(
+            entry.clone(),
+            config.clone(),
+            client_version.clone(),
+            event_sink.clone(),
+        )
+        .await?;
+
+        let supervised = Arc::clone(&session);
+        tokio::spawn(async move {
+            session_watchdog_core::supervise_session(
+                entry.id.clone(),
+                supervised,
+                &sessions,
+                &app_settings,
+                event_sink.clone(),
+                || {
+                    spawn_workspace_session(
+                        entry.clone(),
+                        config.clone(),
+                        client_version.clone(),
+                        event_sink.clone(),
+                    )
+                },
+            )
+            .await;
+        });
+        Ok(session)
+    }
 }
 
 #[derive(Clone)]
 struct DaemonEventSink {
     tx: broadcast::Sender<DaemonEvent>,
+    services: RuntimeServices,
 }
 
 #[derive(Clone)]
 enum DaemonEvent {
-    AppServer(AppServerEvent),
+    AppServer(SequencedEvent),
     #[allow(dead_code)]
     TerminalOutput(TerminalOutput),
     #[allow(dead_code)]
@@ -113,8 +212,16 @@ enum DaemonEvent {
 }
 
 impl EventSink for DaemonEventSink {
-    fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.tx.send(DaemonEvent::AppServer(event));
+    fn emit_app_server_event(&self, mut event: AppServerEvent) {
+        event_schema_core::normalize_event(&mut event.message);
+        let tx = self.tx.clone();
+        let services = self.services.clone();
+        let deliver = move |event| {
+            let event = services.event_replay.record(event);
+            services.event_hooks.dispatch(&event);
+            let _ = tx.send(DaemonEvent::AppServer(event));
+        };
+        Arc::clone(&self.services.delta_batcher).push(event, Arc::new(deliver));
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
@@ -129,18 +236,30 @@ impl EventSink for DaemonEventSink {
 struct DaemonConfig {
     listen: SocketAddr,
     token: Option<String>,
+    /// Token that authenticates a read-only observer connection.
+    observer_token: Option<String>,
     data_dir: PathBuf,
+    tls_cert: Option<PathBuf>,
+    tls_key: Option<PathBuf>,
+}
+
+/// Applies the settings that configure process-wide state.
+fn apply_app_settings(app_settings: &AppSettings) {
+    logging_core::set_rpc_inspector_enabled(app_settings.debug_rpc_inspector);
+    path_env::set_login_shell_path_enabled(app_settings.resolve_login_shell_path);
 }
 
 struct DaemonState {
     data_dir: PathBuf,
     workspaces: Mutex<HashMap<String, WorkspaceEntry>>,
-    sessions: Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    sessions: Arc<Mutex<HashMap<String, Arc<WorkspaceSession>>>>,
     storage_path: PathBuf,
     settings_path: PathBuf,
-    app_settings: Mutex<AppSettings>,
+    app_settings: Arc<Mutex<AppSettings>>,
     event_sink: DaemonEventSink,
+    services: RuntimeServices,
     codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
+    maintenance: Arc<MaintenanceCoordinator>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -155,15 +274,28 @@ impl DaemonState {
         let settings_path = config.data_dir.join("settings.json");
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        let services = event_sink.services.clone();
+        services.apply_settings(&app_settings);
+        apply_app_settings(&app_settings);
+        services.audit_log.init(&config.data_dir, "remote");
+        plugin_adapter::load_plugins(&config.data_dir);
+        let maintenance = Arc::new(MaintenanceCoordinator::new());
+        maintenance_core::register_default_tasks(
+            &maintenance,
+            storage_path.clone(),
+            services.retention.clone(),
+        );
         Self {
             data_dir: config.data_dir.clone(),
             workspaces: Mutex::new(workspaces),
-            sessions: Mutex::new(HashMap::new()),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
             storage_path,
             settings_path,
-            app_settings: Mutex::new(app_settings),
+            app_settings: Arc::new(Mutex::new(app_settings)),
             event_sink,
+            services,
             codex_login_cancels: Mutex::new(HashMap::new()),
+            maintenance,
         }
     }
 
@@ -189,12 +321,52 @@ impl DaemonState {
             &self.sessions,
             &self.app_settings,
             &self.storage_path,
+            &self.services.audit_log,
+            move |entry, config| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    entry,
+                    config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
+                )
+            },
+        )
+        .await
+    }
+
+    async fn workspace_create_from_template(
+        &self,
+        template: String,
+        path: String,
+        git_url: Option<String>,
+        client_version: String,
+    ) -> Result<WorkspaceInfo, String> {
+        workspace_templates_core::workspace_create_from_template_core(
+            &template,
+            path,
+            git_url,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &self.storage_path,
+            &self.services.audit_log,
+            |root, args| {
+                workspaces_core::run_git_command_unit(root, args, git_core::run_git_command_owned)
+            },
             move |entry, config| {
                 spawn_with_client(
                     self.event_sink.clone(),
                     client_version.clone(),
                     entry,
                     config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
                 )
             },
         )
@@ -241,12 +413,20 @@ impl DaemonState {
                     client_version.clone(),
                     entry,
                     config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
                 )
             },
         )
         .await
     }
 
+    async fn worktree_list(&self, parent_id: String) -> Result<Vec<WorkspaceInfo>, String> {
+        workspaces_core::list_worktrees_core(parent_id, &self.workspaces, &self.sessions).await
+    }
+
     async fn worktree_setup_status(&self, workspace_id: String) -> Result<WorktreeSetupStatus, String> {
         workspaces_core::worktree_setup_status_core(&self.workspaces, &workspace_id, &self.data_dir)
             .await
@@ -262,7 +442,9 @@ impl DaemonState {
             id,
             &self.workspaces,
             &self.sessions,
+            &self.app_settings,
             &self.storage_path,
+            &self.services.audit_log,
             |root, args| {
                 workspaces_core::run_git_command_unit(root, args, git_core::run_git_command_owned)
             },
@@ -277,6 +459,20 @@ impl DaemonState {
         .await
     }
 
+    fn workspace_trash_list(&self) -> Result<Vec<TrashedWorkspace>, String> {
+        workspace_trash_core::workspace_trash_list_core(&self.storage_path)
+    }
+
+    async fn workspace_restore(&self, workspace_id: String) -> Result<WorkspaceInfo, String> {
+        workspace_trash_core::workspace_restore_core(
+            workspace_id,
+            &self.workspaces,
+            &self.storage_path,
+            &self.services.audit_log,
+        )
+        .await
+    }
+
     async fn remove_worktree(&self, id: String) -> Result<(), String> {
         workspaces_core::remove_worktree_core(
             id,
@@ -331,6 +527,10 @@ impl DaemonState {
                     client_version.clone(),
                     entry,
                     config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
                 )
             },
         )
@@ -402,6 +602,10 @@ impl DaemonState {
                     client_version.clone(),
                     entry,
                     config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
                 )
             },
         )
@@ -432,6 +636,38 @@ impl DaemonState {
         self.update_workspace_cli_bin(id, codex_bin).await
     }
 
+    async fn workspace_relocate(
+        &self,
+        workspace_id: String,
+        new_path: String,
+        client_version: String,
+    ) -> Result<WorkspaceInfo, String> {
+        workspaces_core::workspace_relocate_core(
+            workspace_id,
+            new_path,
+            &self.workspaces,
+            &self.sessions,
+            &self.app_settings,
+            &self.storage_path,
+            |root, args| {
+                workspaces_core::run_git_command_unit(root, args, git_core::run_git_command_owned)
+            },
+            move |entry, config| {
+                spawn_with_client(
+                    self.event_sink.clone(),
+                    client_version.clone(),
+                    entry,
+                    config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
+                )
+            },
+        )
+        .await
+    }
+
     async fn connect_workspace(&self, id: String, client_version: String) -> Result<(), String> {
         {
             let sessions = self.sessions.lock().await;
@@ -452,6 +688,10 @@ impl DaemonState {
                     client_version.clone(),
                     entry,
                     config,
+                    Arc::clone(&self.sessions),
+                    Arc::clone(&self.app_settings),
+                    self.maintenance.active_turns(),
+                    self.services.clone(),
                 )
             },
         )
@@ -463,8 +703,13 @@ impl DaemonState {
     }
 
     async fn update_app_settings(&self, settings: AppSettings) -> Result<AppSettings, String> {
-        settings_core::update_app_settings_core(settings, &self.app_settings, &self.settings_path)
-            .await
+        settings_core::update_app_settings_core(
+            settings,
+            &self.app_settings,
+            &self.settings_path,
+            &self.services,
+        )
+        .await
     }
 
     async fn list_workspace_files(&self, workspace_id: String) -> Result<Vec<String>, String> {
@@ -504,7 +749,147 @@ impl DaemonState {
         workspace_id: Option<String>,
         content: String,
     ) -> Result<(), String> {
-        files_core::file_write_core(&self.workspaces, scope, kind, workspace_id, content).await
+        files_core::file_write_core(
+            &self.workspaces,
+            &self.services.audit_log,
+            scope,
+            kind,
+            workspace_id,
+            content,
+        )
+        .await
+    }
+
+    async fn workspace_file_read(
+        &self,
+        workspace_id: String,
+        path: String,
+    ) -> Result<file_io::TextFileResponse, String> {
+        files_core::workspace_file_read_core(&self.workspaces, workspace_id, path).await
+    }
+
+    async fn file_read_range(
+        &self,
+        workspace_id: String,
+        path: String,
+        offset: Option<u64>,
+        length: u64,
+    ) -> Result<file_io::TextRangeResponse, String> {
+        files_core::file_read_range_core(&self.workspaces, workspace_id, path, offset, length).await
+    }
+
+    async fn file_tail_subscribe(
+        &self,
+        workspace_id: String,
+        path: String,
+        offset: Option<u64>,
+    ) -> Result<file_tail_core::FileTailSubscription, String> {
+        file_tail_core::file_tail_subscribe_core(
+            &self.workspaces,
+            self.event_sink.clone(),
+            workspace_id,
+            path,
+            offset,
+        )
+        .await
+    }
+
+    async fn workspace_file_write(
+        &self,
+        workspace_id: String,
+        path: String,
+        content: String,
+    ) -> Result<(), String> {
+        files_core::workspace_file_write_core(
+            &self.workspaces,
+            &self.services.audit_log,
+            workspace_id,
+            path,
+            content,
+        )
+        .await
+    }
+
+    async fn workspace_tree(
+        &self,
+        workspace_id: String,
+        path: Option<String>,
+        depth: Option<usize>,
+    ) -> Result<file_tree::WorkspaceTreeResponse, String> {
+        files_core::workspace_tree_core(&self.workspaces, workspace_id, path, depth).await
+    }
+
+    async fn agents_md_sections_list(
+        &self,
+        workspace_id: String,
+    ) -> Result<agents_md_core::AgentsMdSectionsResponse, String> {
+        agents_md_core::agents_md_sections_list_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn agents_md_section_update(
+        &self,
+        workspace_id: String,
+        heading: String,
+        content: String,
+    ) -> Result<agents_md_core::AgentsMdSectionsResponse, String> {
+        agents_md_core::agents_md_section_update_core(
+            &self.workspaces,
+            &self.services.audit_log,
+            workspace_id,
+            heading,
+            content,
+        )
+        .await
+    }
+
+    async fn agent_profile_status(
+        &self,
+        workspace_id: String,
+    ) -> Result<agent_profiles_core::AgentProfileStatus, String> {
+        let cli_type = {
+            let settings = self.app_settings.lock().await;
+            settings.cli_type.clone()
+        };
+        agent_profiles_core::agent_profile_status_core(&self.workspaces, workspace_id, &cli_type)
+            .await
+    }
+
+    async fn agent_profile_sync(
+        &self,
+        workspace_id: String,
+        action: agent_profiles_core::AgentProfileSyncAction,
+    ) -> Result<agent_profiles_core::AgentProfileStatus, String> {
+        let cli_type = {
+            let settings = self.app_settings.lock().await;
+            settings.cli_type.clone()
+        };
+        agent_profiles_core::agent_profile_sync_core(
+            &self.workspaces,
+            &self.services.audit_log,
+            workspace_id,
+            &cli_type,
+            action,
+        )
+        .await
+    }
+
+    async fn agent_profile_save(
+        &self,
+        workspace_id: String,
+        profile: String,
+    ) -> Result<agent_profiles_core::AgentProfileStatus, String> {
+        let cli_type = {
+            let settings = self.app_settings.lock().await;
+            settings.cli_type.clone()
+        };
+        agent_profiles_core::agent_profile_save_core(
+            &self.workspaces,
+            &self.services.audit_log,
+            workspace_id,
+            profile,
+            &cli_type,
+        )
+        .await
     }
 
     async fn agent_profiles_list(
@@ -531,6 +916,7 @@ impl DaemonState {
         };
         agent_profiles_core::apply_agent_profile_core(
             &self.workspaces,
+            &self.services.audit_log,
             workspace_id,
             profile,
             &cli_type,
@@ -557,8 +943,17 @@ impl DaemonState {
         cursor: Option<String>,
         limit: Option<u32>,
         sort_key: Option<String>,
+        archived: Option<bool>,
     ) -> Result<Value, String> {
-        codex_core::list_threads_core(&self.sessions, workspace_id, cursor, limit, sort_key).await
+        codex_core::list_threads_core(
+            &self.sessions,
+            workspace_id,
+            cursor,
+            limit,
+            sort_key,
+            archived,
+        )
+        .await
     }
 
     async fn list_mcp_server_status(
@@ -574,6 +969,22 @@ impl DaemonState {
         codex_core::archive_thread_core(&self.sessions, workspace_id, thread_id).await
     }
 
+    async fn unarchive_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<Value, String> {
+        codex_core::unarchive_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
+    async fn delete_thread(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<Value, String> {
+        codex_core::delete_thread_core(&self.sessions, workspace_id, thread_id).await
+    }
+
     async fn compact_thread(&self, workspace_id: String, thread_id: String) -> Result<Value, String> {
         codex_core::compact_thread_core(&self.sessions, workspace_id, thread_id).await
     }
@@ -621,6 +1032,160 @@ impl DaemonState {
         codex_core::turn_interrupt_core(&self.sessions, workspace_id, thread_id, turn_id).await
     }
 
+    async fn request_cancel(&self, workspace_id: String, request_id: u64) -> Result<bool, String> {
+        codex_core::request_cancel_core(&self.sessions, workspace_id, request_id).await
+    }
+
+    async fn pending_requests(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<PendingRequestInfo>, String> {
+        codex_core::pending_requests_core(&self.sessions, workspace_id).await
+    }
+
+    async fn background_callbacks(
+        &self,
+        workspace_id: String,
+    ) -> Result<Vec<BackgroundCallbackInfo>, String> {
+        codex_core::background_callbacks_core(&self.sessions, workspace_id).await
+    }
+
+    async fn turn_rollback(
+        &self,
+        workspace_id: String,
+        thread_id: String,
+    ) -> Result<TurnRollbackResponse, String> {
+        turn_snapshot_core::turn_rollback_core(
+            &self.workspaces,
+            &self.services.audit_log,
+            workspace_id,
+            thread_id,
+        )
+        .await
+    }
+
+    fn turn_metrics_list(
+        &self,
+        workspace_id: &str,
+        thread_id: Option<&str>,
+        limit: Option<usize>,
+    ) -> Vec<TurnMetrics> {
+        turn_metrics::read_turn_metrics(&turn_metrics_path(workspace_id), thread_id, limit)
+    }
+
+    async fn workspace_budget_status(&self, workspace_id: String) -> Result<BudgetStatus, String> {
+        budget_core::workspace_budget_status_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn workspace_budget_override(
+        &self,
+        workspace_id: String,
+    ) -> Result<BudgetStatus, String> {
+        budget_core::workspace_budget_override_core(&self.workspaces, workspace_id).await
+    }
+
+    async fn activity_timeline(&self, days: Option<u32>) -> Result<ActivityTimeline, String> {
+        activity_core::activity_timeline_core(&self.workspaces, &self.app_settings, days).await
+    }
+
+    async fn patch_apply(
+        &self,
+        workspace_id: String,
+        item_id: String,
+    ) -> Result<PatchApplyResult, String> {
+        patch_core::patch_apply_core(
+            &self.sessions,
+            &self.services.audit_log,
+            workspace_id,
+            item_id,
+        )
+        .await
+    }
+
+    async fn cli_updates_check(&self) -> Vec<CliUpdateStatus> {
+        let settings = self.app_settings.lock().await.clone();
+        cli_updates_core::cli_updates_check_core(&settings).await
+    }
+
+    fn cli_plugins(&self) -> CliPlugins {
+        plugin_adapter::cli_plugins()
+    }
+
+    async fn cli_update(&self, name: String) -> Result<CliUpdateStatus, String> {
+        let settings = self.app_settings.lock().await.clone();
+        cli_updates_core::cli_update_core(&settings, self.event_sink.clone(), &name).await
+    }
+
+    async fn account_profile_delete(&self, provider: String, name: String) -> Result<(), String> {
+        account_profiles_core::account_profile_delete_core(&self.workspaces, provider, name).await
+    }
+
+    async fn app_state_export(&self, path: String) -> Result<AppStateTransfer, String> {
+        app_state_core::app_state_export_core(&path, &self.app_settings, &self.workspaces).await
+    }
+
+    async fn app_state_import(&self, path: String) -> Result<AppStateTransfer, String> {
+        app_state_core::app_state_import_core(
+            &path,
+            &self.app_settings,
+            &self.workspaces,
+            &self.settings_path,
+            &self.storage_path,
+            &self.services,
+        )
+        .await
+    }
+
+    async fn audit_log_query(&self, query: AuditLogQuery) -> Result<AuditLogQueryResult, String> {
+        audit_log_core::audit_log_query_core(&self.services.audit_log, query).await
+    }
+
+    async fn settings_profiles_list(&self) -> Vec<SettingsProfileInfo> {
+        settings_profiles_core::settings_profiles_list_core(&self.app_settings, &self.settings_path)
+            .await
+    }
+
+    async fn settings_profile_save(&self, name: String) -> AppResult<Vec<SettingsProfileInfo>> {
+        settings_profiles_core::settings_profile_save_core(
+            &name,
+            &self.app_settings,
+            &self.settings_path,
+        )
+        .await
+    }
+
+    async fn settings_profile_delete(&self, name: String) -> AppResult<Vec<SettingsProfileInfo>> {
+        settings_profiles_core::settings_profile_delete_core(
+            &name,
+            &self.app_settings,
+            &self.settings_path,
+        )
+        .await
+    }
+
+    async fn settings_profile_switch(&self, name: String) -> AppResult<AppSettings> {
+        settings_profiles_core::settings_profile_switch_core(
+            &name,
+            &self.app_settings,
+            &self.settings_path,
+            &self.services,
+            &self.event_sink,
+        )
+        .await
+    }
+
+    fn maintenance_status(&self) -> MaintenanceStatus {
+        self.maintenance.status()
+    }
+
+    fn events_replay(&self, workspace_id: &str, since_seq: u64) -> EventReplay {
+        self.services.event_replay.since(workspace_id, since_seq)
+    }
+
+    fn log_tail(&self, workspace_id: Option<&str>, lines: usize) -> Result<Vec<String>, String> {
+        logging_core::log_tail_core(&logging_core::log_dir()?, workspace_id, lines)
+    }
+
     async fn start_review(
         &self,
         workspace_id: String,
@@ -657,6 +1222,47 @@ impl DaemonState {
             .await
     }
 
+    async fn generate_run_metadata(
+        &self,
+        workspace_id: String,
+        prompt: String,
+    ) -> Result<Value, String> {
+        codex_aux_core::generate_run_metadata_core(
+            &self.sessions,
+            workspace_id,
+            &prompt,
+            |workspace_id, thread_id| {
+                self.event_sink.emit_app_server_event(AppServerEvent::new(
+                    workspace_id,
+                    AppEvent::background_thread_hidden(thread_id),
+                ));
+            },
+        )
+        .await
+    }
+
+    async fn background_turn_start(
+        &self,
+        workspace_id: String,
+        prompt: String,
+        cli_type: Option<String>,
+    ) -> AppResult<String> {
+        let event_sink = self.event_sink.clone();
+        background_turn_core::background_turn_start_core(
+            &self.sessions,
+            workspace_id,
+            prompt,
+            cli_type,
+            move |workspace_id, thread_id| {
+                event_sink.emit_app_server_event(AppServerEvent::new(
+                    workspace_id,
+                    AppEvent::background_thread_hidden(thread_id),
+                ));
+            },
+        )
+        .await
+    }
+
     async fn skills_list(&self, workspace_id: String) -> Result<Value, String> {
         codex_core::skills_list_core(&self.sessions, workspace_id).await
     }
@@ -791,19 +1397,113 @@ fn default_data_dir() -> PathBuf {
             return PathBuf::from(trimmed).join("codex-monitor-daemon");
         }
     }
-    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-    PathBuf::from(home)
-        .join(".local")
-        .join("share")
-        .join("codex-monitor-daemon")
+    let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
+    PathBuf::from(home)
+        .join(".local")
+        .join("share")
+        .join("codex-monitor-daemon")
+}
+
+fn usage() -> String {
+    format!(
+        "\
+USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> [--observer-token <token>] | --insecure-no-auth] [--tls-cert <pem> --tls-key <pem>]\n  codex-monitor-daemon --headless --workspace <path> --cli <type> --prompt <text> [--model <model>] [--read-only] [--timeout <secs>] [--data-dir <path>]\n\n\
+OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --observer-token <token>  Token for read-only observer clients\n  --insecure-no-auth      Disable auth (dev only)\n  --tls-cert <pem>       Serve TLS using this certificate chain\n  --tls-key <pem>        Private key for --tls-cert\n  -h, --help             Show this help\n\n\
+HEADLESS:\n  Runs one turn and prints its events as JSON lines. Exits 0 when the turn completes, 1 when it fails or is interrupted, 2 on errors.\n  --workspace <path>     Workspace directory; a registered workspace's settings apply\n  --cli <type>           codex, claude, gemini, cursor or a plugin id\n  --prompt <text>        The turn's prompt\n  --model <model>        Model for the turn\n  --read-only            Run the turn in a read-only sandbox\n  --timeout <secs>       Give up after this long (default: {DEFAULT_HEADLESS_TIMEOUT_SECS})\n"
+    )
+}
+
+struct HeadlessConfig {
+    data_dir: PathBuf,
+    turn: HeadlessTurn,
+}
+
+fn parse_headless_args(mut args: impl Iterator<Item = String>) -> Result<HeadlessConfig, String> {
+    let mut data_dir: Option<PathBuf> = None;
+    let mut workspace: Option<PathBuf> = None;
+    let mut cli_type: Option<String> = None;
+    let mut prompt: Option<String> = None;
+    let mut model: Option<String> = None;
+    let mut read_only = false;
+    let mut timeout_secs = DEFAULT_HEADLESS_TIMEOUT_SECS;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--data-dir" => {
+                let value = args.next().ok_or("--data-dir requires a value")?;
+                data_dir = Some(PathBuf::from(value.trim()));
+            }
+            "--workspace" => {
+                let value = args.next().ok_or("--workspace requires a value")?;
+                workspace = Some(PathBuf::from(value.trim()));
+            }
+            "--cli" => {
+                let value = args.next().ok_or("--cli requires a value")?;
+                cli_type = Some(value.trim().to_string());
+            }
+            "--prompt" => {
+                prompt = Some(args.next().ok_or("--prompt requires a value")?);
+            }
+            "--model" => {
+                let value = args.next().ok_or("--model requires a value")?;
+                model = Some(value.trim().to_string()).filter(|value| !value.is_empty());
+            }
+            "--read-only" => read_only = true,
+            "--timeout" => {
+                let value = args.next().ok_or("--timeout requires a value")?;
+                timeout_secs = value
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|secs| *secs > 0)
+                    .ok_or("--timeout requires a positive number of seconds")?;
+            }
+            _ => return Err(format!("Unknown argument: {arg}")),
+        }
+    }
+
+    let prompt = prompt
+        .filter(|prompt| !prompt.trim().is_empty())
+        .ok_or("Missing --prompt")?;
+    Ok(HeadlessConfig {
+        data_dir: data_dir.unwrap_or_else(default_data_dir),
+        turn: HeadlessTurn {
+            workspace_path: workspace.ok_or("Missing --workspace")?,
+            cli_type: cli_type.ok_or("Missing --cli")?,
+            prompt,
+            model,
+            read_only,
+            timeout: std::time::Duration::from_secs(timeout_secs),
+        },
+    })
 }
 
-fn usage() -> String {
-    format!(
-        "\
-USAGE:\n  codex-monitor-daemon [--listen <addr>] [--data-dir <path>] [--token <token> | --insecure-no-auth]\n\n\
-OPTIONS:\n  --listen <addr>        Bind address (default: {DEFAULT_LISTEN_ADDR})\n  --data-dir <path>      Data dir holding workspaces.json/settings.json\n  --token <token>        Shared token required by clients\n  --insecure-no-auth      Disable auth (dev only)\n  -h, --help             Show this help\n"
-    )
+/// Runs the turn of `--headless` and returns the process exit code.
+fn run_headless(config: HeadlessConfig) -> i32 {
+    let workspaces = read_workspaces(&config.data_dir.join("workspaces.json")).unwrap_or_default();
+    let app_settings = read_settings(&config.data_dir.join("settings.json")).unwrap_or_default();
+    apply_app_settings(&app_settings);
+    plugin_adapter::load_plugins(&config.data_dir);
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime");
+    let client_version = format!("headless-{}", env!("CARGO_PKG_VERSION"));
+    let result = runtime.block_on(headless_core::run_headless_turn(
+        config.turn,
+        &workspaces,
+        app_settings,
+        client_version,
+        &mut std::io::stdout(),
+    ));
+    match result {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(err) => {
+            eprintln!("{err}");
+            2
+        }
+    }
 }
 
 fn parse_args() -> Result<DaemonConfig, String> {
@@ -814,8 +1514,14 @@ fn parse_args() -> Result<DaemonConfig, String> {
         .ok()
         .map(|value| value.trim().to_string())
         .filter(|value| !value.is_empty());
+    let mut observer_token = env::var("CODEX_MONITOR_DAEMON_OBSERVER_TOKEN")
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
     let mut insecure_no_auth = false;
     let mut data_dir: Option<PathBuf> = None;
+    let mut tls_cert: Option<PathBuf> = None;
+    let mut tls_key: Option<PathBuf> = None;
 
     let mut args = env::args().skip(1);
     while let Some(arg) = args.next() {
@@ -836,6 +1542,14 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 }
                 token = Some(trimmed.to_string());
             }
+            "--observer-token" => {
+                let value = args.next().ok_or("--observer-token requires a value")?;
+                let trimmed = value.trim();
+                if trimmed.is_empty() {
+                    return Err("--observer-token requires a non-empty value".to_string());
+                }
+                observer_token = Some(trimmed.to_string());
+            }
             "--data-dir" => {
                 let value = args.next().ok_or("--data-dir requires a value")?;
                 let trimmed = value.trim();
@@ -848,6 +1562,14 @@ fn parse_args() -> Result<DaemonConfig, String> {
                 insecure_no_auth = true;
                 token = None;
             }
+            "--tls-cert" => {
+                let value = args.next().ok_or("--tls-cert requires a value")?;
+                tls_cert = Some(PathBuf::from(value.trim()));
+            }
+            "--tls-key" => {
+                let value = args.next().ok_or("--tls-key requires a value")?;
+                tls_key = Some(PathBuf::from(value.trim()));
+            }
             _ => return Err(format!("Unknown argument: {arg}")),
         }
     }
@@ -859,13 +1581,65 @@ fn parse_args() -> Result<DaemonConfig, String> {
         );
     }
 
+    if observer_token.is_some() && token.is_none() {
+        return Err("--observer-token requires --token".to_string());
+    }
+    if observer_token.is_some() && observer_token == token {
+        return Err("--observer-token must differ from --token".to_string());
+    }
+
+    if tls_cert.is_some() != tls_key.is_some() {
+        return Err("--tls-cert and --tls-key must be used together".to_string());
+    }
+
     Ok(DaemonConfig {
         listen,
         token,
+        observer_token,
         data_dir: data_dir.unwrap_or_else(default_data_dir),
+        tls_cert,
+        tls_key,
     })
 }
 
+fn build_tls_acceptor(cert_path: &Path, key_path: &Path) -> Result<TlsAcceptor, String> {
+    let certs = CertificateDer::pem_file_iter(cert_path)
+        .map_err(|err| format!("failed to read {}: {err}", cert_path.display()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("failed to parse {}: {err}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(format!("no certificates found in {}", cert_path.display()));
+    }
+    let key = PrivateKeyDer::from_pem_file(key_path)
+        .map_err(|err| format!("failed to read {}: {err}", key_path.display()))?;
+    let config = ServerConfig::builder_with_provider(Arc::new(
+        tokio_rustls::rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|err| err.to_string())?
+    .with_no_client_auth()
+    .with_single_cert(certs, key)
+    .map_err(|err| format!("invalid TLS certificate or key: {err}"))?;
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// The role `provided` authenticates as, or `None` when it matches neither
+/// token.
+fn auth_role(config: &DaemonConfig, provided: &str) -> Option<ConnectionRole> {
+    let matches = |token: &Option<String>| {
+        token
+            .as_deref()
+            .is_some_and(|expected| connection_role_core::tokens_match(expected, provided))
+    };
+    if matches(&config.token) {
+        Some(ConnectionRole::Operator)
+    } else if matches(&config.observer_token) {
+        Some(ConnectionRole::Observer)
+    } else {
+        None
+    }
+}
+
 fn build_error_response(id: Option<u64>, message: &str) -> Option<String> {
     let id = id?;
     Some(
@@ -947,6 +1721,13 @@ fn parse_optional_u32(value: &Value, key: &str) -> Option<u32> {
     }
 }
 
+fn parse_optional_u64(value: &Value, key: &str) -> Option<u64> {
+    match value {
+        Value::Object(map) => map.get(key).and_then(|value| value.as_u64()),
+        _ => None,
+    }
+}
+
 fn parse_optional_bool(value: &Value, key: &str) -> Option<bool> {
     match value {
         Value::Object(map) => map.get(key).and_then(|value| value.as_bool()),
@@ -994,6 +1775,13 @@ struct FileWriteRequest {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AgentProfileSyncRequest {
+    workspace_id: String,
+    action: agent_profiles_core::AgentProfileSyncAction,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AgentProfileApplyRequest {
@@ -1011,6 +1799,10 @@ fn parse_file_write_request(params: &Value) -> Result<FileWriteRequest, String>
     serde_json::from_value(params.clone()).map_err(|err| err.to_string())
 }
 
+fn parse_agent_profile_sync_request(params: &Value) -> Result<AgentProfileSyncRequest, String> {
+    serde_json::from_value(params.clone()).map_err(|err| err.to_string())
+}
+
 fn parse_agent_profile_apply_request(params: &Value) -> Result<AgentProfileApplyRequest, String> {
     serde_json::from_value(params.clone()).map_err(|err| err.to_string())
 }
@@ -1022,7 +1814,7 @@ async fn handle_rpc_request(
     client_version: String,
 ) -> Result<Value, String> {
     match method {
-        "ping" => Ok(json!({ "ok": true })),
+        "ping" => Ok(json!({ "ok": true, "version": env!("CARGO_PKG_VERSION") })),
         "list_workspaces" => {
             let workspaces = state.list_workspaces().await;
             serde_json::to_value(workspaces).map_err(|err| err.to_string())
@@ -1038,7 +1830,85 @@ async fn handle_rpc_request(
             let workspace = state.add_workspace(path, codex_bin, client_version).await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
-        "add_worktree" => {
+        "workspace_templates_list" => {
+            let templates =
+                workspace_templates_core::workspace_templates_list_core(&state.storage_path)?;
+            serde_json::to_value(templates).map_err(|err| err.to_string())
+        }
+        "workspace_template_save" => {
+            let template_value = match params {
+                Value::Object(map) => map.get("template").cloned().unwrap_or(Value::Null),
+                _ => Value::Null,
+            };
+            let template: WorkspaceTemplate =
+                serde_json::from_value(template_value).map_err(|err| err.to_string())?;
+            let templates = workspace_templates_core::workspace_template_save_core(
+                &state.storage_path,
+                template,
+            )?;
+            serde_json::to_value(templates).map_err(|err| err.to_string())
+        }
+        "workspace_template_delete" => {
+            let name = parse_string(&params, "name")?;
+            let templates = workspace_templates_core::workspace_template_delete_core(
+                &state.storage_path,
+                &name,
+            )?;
+            serde_json::to_value(templates).map_err(|err| err.to_string())
+        }
+        "workflows_list" => {
+            let workflows = workflows_core::workflows_list_core(&state.storage_path)?;
+            serde_json::to_value(workflows).map_err(|err| err.to_string())
+        }
+        "workflow_save" => {
+            let workflow_value = match params {
+                Value::Object(map) => map.get("workflow").cloned().unwrap_or(Value::Null),
+                _ => Value::Null,
+            };
+            let workflow: Workflow =
+                serde_json::from_value(workflow_value).map_err(|err| err.to_string())?;
+            let workflows = workflows_core::workflow_save_core(&state.storage_path, workflow)?;
+            serde_json::to_value(workflows).map_err(|err| err.to_string())
+        }
+        "workflow_delete" => {
+            let name = parse_string(&params, "name")?;
+            let workflows = workflows_core::workflow_delete_core(&state.storage_path, &name)?;
+            serde_json::to_value(workflows).map_err(|err| err.to_string())
+        }
+        "workflow_run" => {
+            let name = parse_string(&params, "name")?;
+            let run_id = workflows_core::workflow_run_core(
+                &name,
+                &state.storage_path,
+                &state.workspaces,
+                &state.sessions,
+                state.event_sink.clone(),
+            )
+            .await?;
+            Ok(json!(run_id))
+        }
+        "workspace_create_from_template" => {
+            let template = parse_string(&params, "template")?;
+            let path = parse_string(&params, "path")?;
+            let git_url = parse_optional_string(&params, "gitUrl");
+            let workspace = state
+                .workspace_create_from_template(template, path, git_url, client_version)
+                .await?;
+            serde_json::to_value(workspace).map_err(|err| err.to_string())
+        }
+        "workspaces_discover" => {
+            let root_paths = parse_optional_string_array(&params, "rootPaths").unwrap_or_default();
+            let max_depth = parse_optional_u32(&params, "maxDepth");
+            let candidates = workspace_discovery_core::workspaces_discover_core(
+                root_paths,
+                max_depth,
+                &state.workspaces,
+                &state.app_settings,
+            )
+            .await?;
+            serde_json::to_value(candidates).map_err(|err| err.to_string())
+        }
+        "add_worktree" | "worktree_create" => {
             let parent_id = parse_string(&params, "parentId")?;
             let branch = parse_string(&params, "branch")?;
             let name = parse_optional_string(&params, "name");
@@ -1048,6 +1918,11 @@ async fn handle_rpc_request(
                 .await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
+        "worktree_list" => {
+            let parent_id = parse_string(&params, "parentId")?;
+            let worktrees = state.worktree_list(parent_id).await?;
+            serde_json::to_value(worktrees).map_err(|err| err.to_string())
+        }
         "worktree_setup_status" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let status = state.worktree_setup_status(workspace_id).await?;
@@ -1068,7 +1943,16 @@ async fn handle_rpc_request(
             state.remove_workspace(id).await?;
             Ok(json!({ "ok": true }))
         }
-        "remove_worktree" => {
+        "workspace_trash_list" => {
+            let trash = state.workspace_trash_list()?;
+            serde_json::to_value(trash).map_err(|err| err.to_string())
+        }
+        "workspace_restore" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let workspace = state.workspace_restore(workspace_id).await?;
+            serde_json::to_value(workspace).map_err(|err| err.to_string())
+        }
+        "remove_worktree" | "worktree_remove" => {
             let id = parse_string(&params, "id")?;
             state.remove_worktree(id).await?;
             Ok(json!({ "ok": true }))
@@ -1113,6 +1997,14 @@ async fn handle_rpc_request(
             let workspace = state.update_workspace_codex_bin(id, codex_bin).await?;
             serde_json::to_value(workspace).map_err(|err| err.to_string())
         }
+        "workspace_relocate" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let new_path = parse_string(&params, "newPath")?;
+            let workspace = state
+                .workspace_relocate(workspace_id, new_path, client_version)
+                .await?;
+            serde_json::to_value(workspace).map_err(|err| err.to_string())
+        }
         "list_workspace_files" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let files = state.list_workspace_files(workspace_id).await?;
@@ -1143,6 +2035,84 @@ async fn handle_rpc_request(
                 .await?;
             serde_json::to_value(json!({ "ok": true })).map_err(|err| err.to_string())
         }
+        "workspace_file_read" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let response = state.workspace_file_read(workspace_id, path).await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "file_read_range" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let offset = parse_optional_u64(&params, "offset");
+            let length = parse_optional_u64(&params, "length").ok_or("missing `length`")?;
+            let response = state
+                .file_read_range(workspace_id, path, offset, length)
+                .await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "file_tail_subscribe" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let offset = parse_optional_u64(&params, "offset");
+            let response = state
+                .file_tail_subscribe(workspace_id, path, offset)
+                .await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "file_tail_unsubscribe" => {
+            let subscription_id = parse_string(&params, "subscriptionId")?;
+            let stopped = file_tail_core::file_tail_unsubscribe_core(&subscription_id);
+            Ok(Value::Bool(stopped))
+        }
+        "workspace_file_write" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_string(&params, "path")?;
+            let content = parse_string(&params, "content")?;
+            state
+                .workspace_file_write(workspace_id, path, content)
+                .await?;
+            serde_json::to_value(json!({ "ok": true })).map_err(|err| err.to_string())
+        }
+        "workspace_tree" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let path = parse_optional_string(&params, "path");
+            let depth = parse_optional_u32(&params, "depth").map(|depth| depth as usize);
+            let response = state.workspace_tree(workspace_id, path, depth).await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "agents_md_sections_list" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let response = state.agents_md_sections_list(workspace_id).await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "agents_md_section_update" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let heading = parse_string(&params, "heading")?;
+            let content = parse_string(&params, "content")?;
+            let response = state
+                .agents_md_section_update(workspace_id, heading, content)
+                .await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "agent_profile_status" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let response = state.agent_profile_status(workspace_id).await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "agent_profile_sync" => {
+            let request = parse_agent_profile_sync_request(&params)?;
+            let response = state
+                .agent_profile_sync(request.workspace_id, request.action)
+                .await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "agent_profile_save" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let profile = parse_string(&params, "profile")?;
+            let response = state.agent_profile_save(workspace_id, profile).await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
         "agent_profiles_list" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let response = state.agent_profiles_list(workspace_id).await?;
@@ -1179,6 +2149,27 @@ async fn handle_rpc_request(
             let path = settings_core::get_codex_config_path_core()?;
             Ok(Value::String(path))
         }
+        "codex_config_validate" => {
+            let diagnostics = settings_core::codex_config_validate_core()?;
+            serde_json::to_value(diagnostics).map_err(|err| err.to_string())
+        }
+        "codex_profiles_list" => {
+            let profiles = settings_core::codex_profiles_list_core()?;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
+        "codex_profile_apply" => {
+            let name = parse_optional_string(&params, "name");
+            let profiles = settings_core::codex_profile_apply_core(
+                name.as_deref(),
+                &state.services.audit_log,
+            )?;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
+        "codex_profile_save_current" => {
+            let name = parse_string(&params, "name")?;
+            let profiles = settings_core::codex_profile_save_current_core(&name)?;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
         "get_config_model" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.get_config_model(workspace_id).await
@@ -1202,7 +2193,31 @@ async fn handle_rpc_request(
             let cursor = parse_optional_string(&params, "cursor");
             let limit = parse_optional_u32(&params, "limit");
             let sort_key = parse_optional_string(&params, "sortKey");
-            state.list_threads(workspace_id, cursor, limit, sort_key).await
+            let archived = parse_optional_bool(&params, "archived");
+            state
+                .list_threads(workspace_id, cursor, limit, sort_key, archived)
+                .await
+        }
+        "codex_sessions_list" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let sessions = codex_sessions_core::codex_sessions_list_core(
+                &state.workspaces,
+                &state.services.redaction_patterns,
+                workspace_id,
+            )
+            .await?;
+            serde_json::to_value(sessions).map_err(|err| err.to_string())
+        }
+        "codex_session_read" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let session_id = parse_string(&params, "sessionId")?;
+            codex_sessions_core::codex_session_read_core(
+                &state.workspaces,
+                &state.services.redaction_patterns,
+                workspace_id,
+                session_id,
+            )
+            .await
         }
         "list_mcp_server_status" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
@@ -1215,6 +2230,16 @@ async fn handle_rpc_request(
             let thread_id = parse_string(&params, "threadId")?;
             state.archive_thread(workspace_id, thread_id).await
         }
+        "unarchive_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            state.unarchive_thread(workspace_id, thread_id).await
+        }
+        "delete_thread" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            state.delete_thread(workspace_id, thread_id).await
+        }
         "compact_thread" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -1254,6 +2279,175 @@ async fn handle_rpc_request(
             let turn_id = parse_string(&params, "turnId")?;
             state.turn_interrupt(workspace_id, thread_id, turn_id).await
         }
+        "request_cancel" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let request_id =
+                parse_optional_u64(&params, "requestId").ok_or("missing `requestId`")?;
+            let canceled = state.request_cancel(workspace_id, request_id).await?;
+            Ok(json!(canceled))
+        }
+        "pending_requests" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let requests = state.pending_requests(workspace_id).await?;
+            serde_json::to_value(requests).map_err(|err| err.to_string())
+        }
+        "background_callbacks" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let callbacks = state.background_callbacks(workspace_id).await?;
+            serde_json::to_value(callbacks).map_err(|err| err.to_string())
+        }
+        "turn_rollback" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_string(&params, "threadId")?;
+            let response = state.turn_rollback(workspace_id, thread_id).await?;
+            serde_json::to_value(response).map_err(|err| err.to_string())
+        }
+        "turn_metrics_list" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let thread_id = parse_optional_string(&params, "threadId");
+            let limit = parse_optional_u32(&params, "limit").map(|limit| limit as usize);
+            let turns = state.turn_metrics_list(&workspace_id, thread_id.as_deref(), limit);
+            serde_json::to_value(turns).map_err(|err| err.to_string())
+        }
+        "workspace_budget_status" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let status = state.workspace_budget_status(workspace_id).await?;
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
+        "workspace_budget_override" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let status = state.workspace_budget_override(workspace_id).await?;
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
+        "activity_timeline" => {
+            let days = parse_optional_u32(&params, "days");
+            let timeline = state.activity_timeline(days).await?;
+            serde_json::to_value(timeline).map_err(|err| err.to_string())
+        }
+        "patch_apply" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let item_id = parse_string(&params, "itemId")?;
+            let result = state.patch_apply(workspace_id, item_id).await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
+        "cli_updates_check" => {
+            serde_json::to_value(state.cli_updates_check().await).map_err(|err| err.to_string())
+        }
+        "cli_plugins_list" => {
+            serde_json::to_value(state.cli_plugins()).map_err(|err| err.to_string())
+        }
+        "cli_update" => {
+            let name = parse_string(&params, "name")?;
+            let status = state.cli_update(name).await?;
+            serde_json::to_value(status).map_err(|err| err.to_string())
+        }
+        "account_profiles_list" => {
+            let provider = parse_optional_string(&params, "provider");
+            let profiles = account_profiles_core::account_profiles_list_core(provider)?;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
+        "account_profile_create" => {
+            let provider = parse_string(&params, "provider")?;
+            let name = parse_string(&params, "name")?;
+            let profile = account_profiles_core::account_profile_create_core(provider, name)?;
+            serde_json::to_value(profile).map_err(|err| err.to_string())
+        }
+        "account_profile_delete" => {
+            let provider = parse_string(&params, "provider")?;
+            let name = parse_string(&params, "name")?;
+            state.account_profile_delete(provider, name).await?;
+            Ok(json!({ "ok": true }))
+        }
+        "app_state_export" => {
+            let path = parse_string(&params, "path")?;
+            let transfer = state.app_state_export(path).await?;
+            serde_json::to_value(transfer).map_err(|err| err.to_string())
+        }
+        "app_state_import" => {
+            let path = parse_string(&params, "path")?;
+            let transfer = state.app_state_import(path).await?;
+            serde_json::to_value(transfer).map_err(|err| err.to_string())
+        }
+        "audit_log_query" => {
+            let query: AuditLogQuery =
+                serde_json::from_value(params.clone()).map_err(|err| err.to_string())?;
+            let result = state.audit_log_query(query).await?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
+        "settings_profiles_list" => {
+            let profiles = state.settings_profiles_list().await;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
+        "settings_profile_save" => {
+            let name = parse_string(&params, "name")?;
+            let profiles = state.settings_profile_save(name).await?;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
+        "settings_profile_delete" => {
+            let name = parse_string(&params, "name")?;
+            let profiles = state.settings_profile_delete(name).await?;
+            serde_json::to_value(profiles).map_err(|err| err.to_string())
+        }
+        "settings_profile_switch" => {
+            let name = parse_string(&params, "name")?;
+            let settings = state.settings_profile_switch(name).await?;
+            serde_json::to_value(settings).map_err(|err| err.to_string())
+        }
+        "maintenance_status" => {
+            serde_json::to_value(state.maintenance_status()).map_err(|err| err.to_string())
+        }
+        "mcp_servers_list" => {
+            let servers = mcp_servers_core::mcp_servers_list_core()?;
+            serde_json::to_value(servers).map_err(|err| err.to_string())
+        }
+        "mcp_server_add" => {
+            let server_value = match params {
+                Value::Object(map) => map.get("server").cloned().unwrap_or(Value::Null),
+                _ => Value::Null,
+            };
+            let server: McpServer =
+                serde_json::from_value(server_value).map_err(|err| err.to_string())?;
+            let servers = mcp_servers_core::mcp_server_add_core(server, &state.services.audit_log)?;
+            serde_json::to_value(servers).map_err(|err| err.to_string())
+        }
+        "mcp_server_remove" => {
+            let name = parse_string(&params, "name")?;
+            let servers = mcp_servers_core::mcp_server_remove_core(&name, &state.services.audit_log)?;
+            serde_json::to_value(servers).map_err(|err| err.to_string())
+        }
+        "mcp_server_toggle" => {
+            let name = parse_string(&params, "name")?;
+            let enabled = parse_optional_bool(&params, "enabled")
+                .ok_or_else(|| "missing or invalid `enabled`".to_string())?;
+            let servers = mcp_servers_core::mcp_server_toggle_core(&name, enabled, &state.services.audit_log)?;
+            serde_json::to_value(servers).map_err(|err| err.to_string())
+        }
+        "get_claude_settings" => {
+            let settings = claude_settings::read_user_settings()?.unwrap_or_default();
+            serde_json::to_value(settings).map_err(|err| err.to_string())
+        }
+        "update_claude_settings" => {
+            let settings_value = match params {
+                Value::Object(map) => map.get("settings").cloned().unwrap_or(Value::Null),
+                _ => Value::Null,
+            };
+            let settings: claude_settings::ClaudeSettings =
+                serde_json::from_value(settings_value).map_err(|err| err.to_string())?;
+            claude_settings::write_user_settings(&settings, &state.services.audit_log)?;
+            serde_json::to_value(settings).map_err(|err| err.to_string())
+        }
+        "log_tail" => {
+            let workspace_id = parse_optional_string(&params, "workspaceId");
+            let lines = parse_optional_u32(&params, "lines").unwrap_or(DEFAULT_LOG_TAIL_LINES);
+            let lines = state.log_tail(workspace_id.as_deref(), lines as usize)?;
+            serde_json::to_value(lines).map_err(|err| err.to_string())
+        }
+        "events_replay" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let since_seq = parse_optional_u64(&params, "sinceSeq").unwrap_or(0);
+            serde_json::to_value(state.events_replay(&workspace_id, since_seq))
+                .map_err(|err| err.to_string())
+        }
         "start_review" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             let thread_id = parse_string(&params, "threadId")?;
@@ -1281,14 +2475,33 @@ async fn handle_rpc_request(
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.account_read(workspace_id).await
         }
-        "codex_login" => {
+        "codex_login" | "gemini_login" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.codex_login(workspace_id).await
         }
-        "codex_login_cancel" => {
+        "codex_login_cancel" | "gemini_login_cancel" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.codex_login_cancel(workspace_id).await
         }
+        "generate_run_metadata" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let prompt = parse_string(&params, "prompt")?;
+            state.generate_run_metadata(workspace_id, prompt).await
+        }
+        "background_turn_start" => {
+            let workspace_id = parse_string(&params, "workspaceId")?;
+            let prompt = parse_string(&params, "prompt")?;
+            let cli_type = parse_optional_string(&params, "cliType");
+            let turn_id = state
+                .background_turn_start(workspace_id, prompt, cli_type)
+                .await?;
+            Ok(json!(turn_id))
+        }
+        "background_turn_result" => {
+            let turn_id = parse_string(&params, "turnId")?;
+            let result = background_turn_core::background_turn_result_core(&turn_id)?;
+            serde_json::to_value(result).map_err(|err| err.to_string())
+        }
         "skills_list" => {
             let workspace_id = parse_string(&params, "workspaceId")?;
             state.skills_list(workspace_id).await
@@ -1342,13 +2555,15 @@ async fn forward_events(
     }
 }
 
-async fn handle_client(
-    socket: TcpStream,
+async fn handle_client<S>(
+    socket: S,
     config: Arc<DaemonConfig>,
     state: Arc<DaemonState>,
     events: broadcast::Sender<DaemonEvent>,
-) {
-    let (reader, mut writer) = socket.into_split();
+) where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (reader, mut writer) = tokio::io::split(socket);
     let mut lines = BufReader::new(reader).lines();
 
     let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
@@ -1360,10 +2575,14 @@ async fn handle_client(
             if writer.write_all(b"\n").await.is_err() {
                 break;
             }
+            if writer.flush().await.is_err() {
+                break;
+            }
         }
     });
 
     let mut authenticated = config.token.is_none();
+    let mut role = ConnectionRole::Operator;
     let mut events_task: Option<tokio::task::JoinHandle<()>> = None;
 
     if authenticated {
@@ -1399,17 +2618,22 @@ async fn handle_client(
                 continue;
             }
 
-            let expected = config.token.clone().unwrap_or_default();
             let provided = parse_auth_token(&params).unwrap_or_default();
-            if expected != provided {
+            let Some(token_role) = auth_role(&config, &provided) else {
                 if let Some(response) = build_error_response(id, "invalid token") {
                     let _ = out_tx.send(response);
                 }
                 continue;
-            }
+            };
 
             authenticated = true;
-            if let Some(response) = build_result_response(id, json!({ "ok": true })) {
+            role = token_role;
+            let auth_result = json!({
+                "ok": true,
+                "version": env!("CARGO_PKG_VERSION"),
+                "role": role,
+            });
+            if let Some(response) = build_result_response(id, auth_result) {
                 let _ = out_tx.send(response);
             }
 
@@ -1420,8 +2644,20 @@ async fn handle_client(
             continue;
         }
 
+        if let Err(message) = connection_role_core::check_method_allowed(role, &method) {
+            if let Some(response) = build_error_response(id, &message) {
+                let _ = out_tx.send(response);
+            }
+            continue;
+        }
+
         let client_version = format!("daemon-{}", env!("CARGO_PKG_VERSION"));
-        let result = handle_rpc_request(&state, &method, params, client_version).await;
+        let result = handle_rpc_request(&state, &method, params, client_version)
+            .await
+            .map(|mut result| {
+                connection_role_core::mask_result_for_role(role, &method, &mut result);
+                result
+            });
         let response = match result {
             Ok(result) => build_result_response(id, result),
             Err(message) => build_error_response(id, &message),
@@ -1439,6 +2675,16 @@ async fn handle_client(
 }
 
 fn main() {
+    if env::args().nth(1).as_deref() == Some("--headless") {
+        match parse_headless_args(env::args().skip(2)) {
+            Ok(config) => std::process::exit(run_headless(config)),
+            Err(err) => {
+                eprintln!("{err}\n\n{}", usage());
+                std::process::exit(2);
+            }
+        }
+    }
+
     let config = match parse_args() {
         Ok(config) => config,
         Err(err) => {
@@ -1447,6 +2693,10 @@ fn main() {
         }
     };
 
+    if let Err(err) = logging_core::init_logging(config.data_dir.join("logs")) {
+        eprintln!("failed to initialize logging: {err}");
+    }
+
     let runtime = tokio::runtime::Builder::new_current_thread()
         .enable_all()
         .build()
@@ -1456,9 +2706,22 @@ fn main() {
         let (events_tx, _events_rx) = broadcast::channel::<DaemonEvent>(2048);
         let event_sink = DaemonEventSink {
             tx: events_tx.clone(),
+            services: RuntimeServices::default(),
         };
-        let state = Arc::new(DaemonState::load(&config, event_sink));
+        let state = Arc::new(DaemonState::load(&config, event_sink.clone()));
+        tokio::spawn(Arc::clone(&state.maintenance).run(event_sink.clone()));
+        let monitored = Arc::clone(&state);
+        tokio::spawn(async move {
+            process_monitor::run_process_monitor(&monitored.sessions, event_sink).await;
+        });
         let config = Arc::new(config);
+        let tls_acceptor = match (&config.tls_cert, &config.tls_key) {
+            (Some(cert), Some(key)) => Some(
+                build_tls_acceptor(cert, key)
+                    .unwrap_or_else(|err| panic!("failed to configure TLS: {err}")),
+            ),
+            _ => None,
+        };
 
         let listener = TcpListener::bind(config.listen)
             .await
@@ -1473,18 +2736,60 @@ fn main() {
                 .display()
         );
 
-        loop {
-            match listener.accept().await {
-                Ok((socket, _addr)) => {
-                    let config = Arc::clone(&config);
-                    let state = Arc::clone(&state);
-                    let events = events_tx.clone();
-                    tokio::spawn(async move {
-                        handle_client(socket, config, state, events).await;
-                    });
+        let serve = async {
+            loop {
+                match listener.accept().await {
+                    Ok((socket, _addr)) => {
+                        let config = Arc::clone(&config);
+                        let state = Arc::clone(&state);
+                        let events = events_tx.clone();
+                        let tls_acceptor = tls_acceptor.clone();
+                        tokio::spawn(async move {
+                            match tls_acceptor {
+                                Some(acceptor) => match acceptor.accept(socket).await {
+                                    Ok(stream) => {
+                                        handle_client(stream, config, state, events).await
+                                    }
+                                    Err(err) => eprintln!("TLS handshake failed: {err}"),
+                                },
+                                None => handle_client(socket, config, state, events).await,
+                            }
+                        });
+                    }
+                    Err(_) => continue,
                 }
-                Err(_) => continue,
             }
+        };
+        tokio::select! {
+            _ = serve => {}
+            _ = shutdown_signal() => {}
         }
+
+        eprintln!("codex-monitor-daemon shutting down");
+        let settings = state.app_settings.lock().await.clone();
+        shutdown_core::shutdown_sessions(&state.sessions, &settings, &state.event_sink).await;
     });
 }
+
+/// Resolves on Ctrl-C, or SIGTERM on Unix.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
@@ -0,0 +1,48 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+pub(crate) mod models;
+pub(crate) mod sessions;
+pub(crate) mod settings;
+
+use crate::remote_backend;
+use crate::state::AppState;
+use settings::ClaudeSettings;
+
+/// Reads Claude Code's user settings (`~/.claude/settings.json`).
+#[tauri::command]
+pub(crate) async fn get_claude_settings(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ClaudeSettings, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "get_claude_settings", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(settings::read_user_settings()?.unwrap_or_default())
+}
+
+/// Replaces Claude Code's user settings. Unmodeled keys round-trip through
+/// `ClaudeSettings::extra`.
+#[tauri::command]
+pub(crate) async fn update_claude_settings(
+    settings: ClaudeSettings,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<ClaudeSettings, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "update_claude_settings",
+            json!({ "settings": settings }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    settings::write_user_settings(&settings, &state.services.audit_log)?;
+    Ok(settings)
+}
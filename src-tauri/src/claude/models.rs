@@ -0,0 +1,237 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use crate::backend::app_server::CliSpawnConfig;
+use crate::claude::settings::read_user_settings;
+use crate::shared::workspace_env_core::resolve_workspace_env;
+
+const DEFAULT_API_BASE: &str = "https://api.anthropic.com";
+const API_VERSION: &str = "2023-06-01";
+const MODELS_TTL: Duration = Duration::from_secs(60 * 60);
+/// Failed lookups are retried sooner than successful ones are refreshed.
+const FAILED_LOOKUP_TTL: Duration = Duration::from_secs(5 * 60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Used when the models API can't be reached (no API key, offline, ...).
+const FALLBACK_MODELS: &[(&str, &str)] = &[
+    ("claude-sonnet-4-20250514", "Claude Sonnet 4"),
+    ("claude-opus-4-20250514", "Claude Opus 4"),
+    ("claude-haiku-4-20250514", "Claude Haiku 4"),
+];
+const FALLBACK_DEFAULT_MODEL: &str = "claude-sonnet-4-20250514";
+
+struct CachedModels {
+    fetched_at: Instant,
+    models: Option<Vec<(String, String)>>,
+}
+
+/// Discovered models per API base URL.
+fn models_cache() -> &'static Mutex<HashMap<String, CachedModels>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, CachedModels>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn model_entry(id: &str, name: &str) -> Value {
+    let mut efforts = vec![
+        json!({ "reasoningEffort": "low", "description": "Fast, minimal thinking" }),
+        json!({ "reasoningEffort": "medium", "description": "Balanced speed and depth" }),
+        json!({ "reasoningEffort": "high", "description": "Deep thinking (default)" }),
+    ];
+    if id.contains("opus") {
+        efforts.push(
+            json!({ "reasoningEffort": "max", "description": "Maximum depth, no token limit" }),
+        );
+    }
+    json!({
+        "id": id,
+        "name": name,
+        "supportedReasoningEfforts": efforts,
+        "defaultReasoningEffort": "high"
+    })
+}
+
+/// Builds the `model/list` response. The model set in Claude's
+/// `settings.json` is the default and is listed even if discovery missed it.
+fn model_list_response(models: &[(String, String)], configured: Option<&str>) -> Value {
+    let mut entries: Vec<Value> = models
+        .iter()
+        .map(|(id, name)| model_entry(id, name))
+        .collect();
+    if let Some(configured) = configured {
+        if !models.iter().any(|(id, _)| id == configured) {
+            entries.insert(0, model_entry(configured, configured));
+        }
+    }
+    let default_model = configured
+        .map(str::to_string)
+        .or_else(|| {
+            models
+                .iter()
+                .find(|(id, _)| id == FALLBACK_DEFAULT_MODEL)
+                .or_else(|| models.iter().find(|(id, _)| id.contains("sonnet")))
+                .or_else(|| models.first())
+                .map(|(id, _)| id.clone())
+        })
+        .unwrap_or_else(|| FALLBACK_DEFAULT_MODEL.to_string());
+    json!({
+        "result": {
+            "models": entries,
+            "defaultModel": default_model
+        }
+    })
+}
+
+fn parse_models_page(body: &str) -> Result<Vec<(String, String)>, String> {
+    let value: Value =
+        serde_json::from_str(body).map_err(|err| format!("Invalid models response: {err}"))?;
+    let data = value
+        .get("data")
+        .and_then(Value::as_array)
+        .ok_or("Models response has no data")?;
+    Ok(data
+        .iter()
+        .filter_map(|model| {
+            let id = model.get("id").and_then(Value::as_str)?;
+            let name = model
+                .get("display_name")
+                .and_then(Value::as_str)
+                .unwrap_or(id);
+            Some((id.to_string(), name.to_string()))
+        })
+        .collect())
+}
+
+async fn fetch_models(api_base: &str, api_key: &str) -> Result<Vec<(String, String)>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    let url = format!("{}/v1/models?limit=100", api_base.trim_end_matches('/'));
+    let response = client
+        .get(url)
+        .header("x-api-key", api_key)
+        .header("anthropic-version", API_VERSION)
+        .send()
+        .await
+        .map_err(|err| err.to_string())?
+        .error_for_status()
+        .map_err(|err| err.to_string())?;
+    let body = response.text().await.map_err(|err| err.to_string())?;
+    let models = parse_models_page(&body)?;
+    if models.is_empty() {
+        return Err("Models response is empty".to_string());
+    }
+    Ok(models)
+}
+
+/// `ANTHROPIC_*` variables from the workspace env, Claude's settings, then
+/// the monitor's own environment.
+fn lookup_var(
+    name: &str,
+    workspace_env: &[(String, String)],
+    settings_env: Option<&BTreeMap<String, String>>,
+) -> Option<String> {
+    workspace_env
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.clone())
+        .or_else(|| settings_env.and_then(|env| env.get(name).cloned()))
+        .or_else(|| std::env::var(name).ok())
+        .filter(|value| !value.trim().is_empty())
+}
+
+async fn discovered_models(api_base: &str, api_key: Option<&str>) -> Option<Vec<(String, String)>> {
+    if let Ok(cache) = models_cache().lock() {
+        if let Some(cached) = cache.get(api_base) {
+            let ttl = if cached.models.is_some() {
+                MODELS_TTL
+            } else {
+                FAILED_LOOKUP_TTL
+            };
+            if cached.fetched_at.elapsed() < ttl {
+                return cached.models.clone();
+            }
+        }
+    }
+    let models = match api_key {
+        Some(api_key) => match fetch_models(api_base, api_key).await {
+            Ok(models) => Some(models),
+            Err(err) => {
+                tracing::warn!("Claude model discovery failed: {err}");
+                None
+            }
+        },
+        None => None,
+    };
+    if let Ok(mut cache) = models_cache().lock() {
+        cache.insert(
+            api_base.to_string(),
+            CachedModels {
+                fetched_at: Instant::now(),
+                models: models.clone(),
+            },
+        );
+    }
+    models
+}
+
+/// Models from the Anthropic models API (cached for an hour), falling back to
+/// a built-in list when there's no API key or the lookup fails.
+pub(crate) async fn model_list(config: &CliSpawnConfig) -> Value {
+    let settings = read_user_settings().ok().flatten();
+    let workspace_env = resolve_workspace_env(&config.env).unwrap_or_default();
+    let settings_env = settings.as_ref().and_then(|settings| settings.env.as_ref());
+    let api_key = lookup_var("ANTHROPIC_API_KEY", &workspace_env, settings_env);
+    let api_base = lookup_var("ANTHROPIC_BASE_URL", &workspace_env, settings_env)
+        .unwrap_or_else(|| DEFAULT_API_BASE.to_string());
+    let configured = settings
+        .as_ref()
+        .and_then(|settings| settings.model.clone());
+
+    let models = discovered_models(&api_base, api_key.as_deref())
+        .await
+        .unwrap_or_else(|| {
+            FALLBACK_MODELS
+                .iter()
+                .map(|(id, name)| (id.to_string(), name.to_string()))
+                .collect()
+        });
+    model_list_response(&models, configured.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{model_list_response, parse_models_page};
+
+    #[test]
+    fn builds_model_list_from_api_page() {
+        let page = r#"{"data":[
+            {"type":"model","id":"claude-opus-4-1-20250805","display_name":"Claude Opus 4.1"},
+            {"type":"model","id":"claude-sonnet-4-5-20250929","display_name":"Claude Sonnet 4.5"}
+        ],"has_more":false}"#;
+        let models = parse_models_page(page).expect("models");
+        assert_eq!(models.len(), 2);
+
+        let response = model_list_response(&models, None);
+        let entries = response["result"]["models"].as_array().expect("models");
+        assert_eq!(entries[0]["name"], "Claude Opus 4.1");
+        assert_eq!(
+            entries[0]["supportedReasoningEfforts"]
+                .as_array()
+                .map(Vec::len),
+            Some(4)
+        );
+        assert_eq!(
+            response["result"]["defaultModel"],
+            "claude-sonnet-4-5-20250929"
+        );
+
+        let response = model_list_response(&models, Some("opusplan"));
+        assert_eq!(response["result"]["models"][0]["id"], "opusplan");
+        assert_eq!(response["result"]["defaultModel"], "opusplan");
+        assert!(parse_models_page("{}").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use chrono::DateTime;
+use serde_json::{json, Value};
+
+use crate::backend::adapter_base::ImportedSession;
+use crate::claude::settings::resolve_default_claude_home;
+
+const SESSION_NAME_MAX_CHARS: usize = 80;
+
+/// Claude Code keeps one folder per project under `~/.claude/projects`, named
+/// after the project path with every non-alphanumeric character replaced by `-`.
+fn project_dir(workspace_path: &str) -> Option<PathBuf> {
+    let encoded: String = workspace_path
+        .trim_end_matches(['/', '\\'])
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch } else { '-' })
+        .collect();
+    resolve_default_claude_home().map(|home| home.join("projects").join(encoded))
+}
+
+/// Transcript Claude Code writes for `session_id` when run in
+/// `workspace_path`.
+pub(crate) fn session_path(workspace_path: &str, session_id: &str) -> Option<PathBuf> {
+    if session_id.is_empty() || session_id.contains(['/', '\\', '.']) {
+        return None;
+    }
+    project_dir(workspace_path).map(|dir| dir.join(format!("{session_id}.jsonl")))
+}
+
+fn parse_timestamp(record: &Value) -> Option<u64> {
+    let raw = record.get("timestamp")?.as_str()?;
+    let parsed = DateTime::parse_from_rfc3339(raw).ok()?;
+    u64::try_from(parsed.timestamp()).ok()
+}
+
+fn read_records(path: &Path) -> Result<Vec<Value>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .filter(|record| record.get("isSidechain").and_then(Value::as_bool) != Some(true))
+        .collect())
+}
+
+/// `kind` is `text` or `thinking`; a bare string counts as text.
+fn content_parts(content: Option<&Value>, kind: &str) -> Vec<String> {
+    match content {
+        Some(Value::String(text)) if kind == "text" => vec![text.clone()],
+        Some(Value::Array(blocks)) => blocks
+            .iter()
+            .filter(|block| block.get("type").and_then(Value::as_str) == Some(kind))
+            .filter_map(|block| block.get(kind).and_then(Value::as_str))
+            .map(str::to_string)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Text typed by the user; tool results and meta records are skipped.
+fn user_text(record: &Value) -> Option<String> {
+    if record.get("type").and_then(Value::as_str) != Some("user")
+        || record.get("isMeta").and_then(Value::as_bool) == Some(true)
+    {
+        return None;
+    }
+    let text = content_parts(record.pointer("/message/content"), "text").join("\n");
+    (!text.trim().is_empty()).then_some(text)
+}
+
+fn session_name(records: &[Value]) -> Option<String> {
+    let summary = records.iter().rev().find_map(|record| {
+        (record.get("type").and_then(Value::as_str) == Some("summary"))
+            .then(|| record.get("summary").and_then(Value::as_str))
+            .flatten()
+            .map(str::to_string)
+    });
+    summary
+        .or_else(|| records.iter().find_map(user_text))
+        .map(|name| {
+            let line = name.lines().next().unwrap_or_default().trim();
+            line.chars().take(SESSION_NAME_MAX_CHARS).collect()
+        })
+}
+
+fn summarize_session(path: &Path, session_id: String) -> Option<ImportedSession> {
+    let records = read_records(path).ok()?;
+    if !records.iter().any(|record| user_text(record).is_some()) {
+        return None;
+    }
+    let timestamps: Vec<u64> = records.iter().filter_map(parse_timestamp).collect();
+    Some(ImportedSession {
+        cli_session_id: session_id,
+        name: session_name(&records),
+        created_at: timestamps.iter().copied().min().unwrap_or(0),
+        updated_at: timestamps.iter().copied().max().unwrap_or(0),
+        source_path: path.to_path_buf(),
+    })
+}
+
+/// Claude Code sessions recorded for `workspace_path` whose ids aren't in
+/// `known`.
+pub(crate) fn list_workspace_sessions(
+    workspace_path: &str,
+    known: &HashSet<String>,
+) -> Vec<ImportedSession> {
+    let Some(dir) = project_dir(workspace_path) else {
+        return Vec::new();
+    };
+    let Ok(entries) = std::fs::read_dir(&dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("jsonl"))
+        .filter_map(|path| {
+            let session_id = path.file_stem()?.to_str()?.to_string();
+            if known.contains(&session_id) {
+                return None;
+            }
+            summarize_session(&path, session_id)
+        })
+        .collect()
+}
+
+/// Converts a session transcript into `thread/resume` turns. Each prompt
+/// typed by the user starts a new turn.
+pub(crate) fn read_session_turns(path: &Path) -> Result<Vec<Value>, String> {
+    let records = read_records(path)?;
+    let mut turns: Vec<Value> = Vec::new();
+    let mut items: Vec<Value> = Vec::new();
+    for (index, record) in records.iter().enumerate() {
+        let id = record
+            .get("uuid")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("item-{index}"));
+        if let Some(text) = user_text(record) {
+            if !items.is_empty() {
+                turns.push(json!({ "id": format!("turn-{}", turns.len()), "items": items }));
+                items = Vec::new();
+            }
+            items.push(json!({
+                "id": id,
+                "type": "userMessage",
+                "content": [{ "type": "text", "text": text }],
+            }));
+            continue;
+        }
+        if record.get("type").and_then(Value::as_str) != Some("assistant") {
+            continue;
+        }
+        let content = record.pointer("/message/content");
+        let thinking = content_parts(content, "thinking");
+        if !thinking.is_empty() {
+            items.push(json!({
+                "id": format!("{id}-reasoning"),
+                "type": "reasoning",
+                "summary": [],
+                "content": thinking,
+            }));
+        }
+        let text = content_parts(content, "text").join("\n");
+        if !text.trim().is_empty() {
+            items.push(json!({ "id": id, "type": "agentMessage", "text": text }));
+        }
+    }
+    if !items.is_empty() {
+        turns.push(json!({ "id": format!("turn-{}", turns.len()), "items": items }));
+    }
+    Ok(turns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_session_turns, summarize_session};
+    use uuid::Uuid;
+
+    #[test]
+    fn converts_session_transcript_into_turns() {
+        let dir = std::env::temp_dir().join(format!("claude-sessions-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        let path = dir.join("session-1.jsonl");
+        let lines = [
+            r#"{"type":"user","uuid":"u1","timestamp":"2025-01-01T10:00:00Z","message":{"role":"user","content":"Fix the login bug\nDetails follow"}}"#,
+            r#"{"type":"assistant","uuid":"a1","timestamp":"2025-01-01T10:00:05Z","message":{"role":"assistant","content":[{"type":"thinking","thinking":"Check auth.rs"},{"type":"text","text":"Looking now."},{"type":"tool_use","id":"t1","name":"Read","input":{}}]}}"#,
+            r#"{"type":"user","uuid":"u2","timestamp":"2025-01-01T10:00:06Z","message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"..."}]}}"#,
+            r#"{"type":"assistant","uuid":"a2","timestamp":"2025-01-01T10:00:09Z","message":{"role":"assistant","content":[{"type":"text","text":"Fixed."}]}}"#,
+            r#"{"type":"assistant","uuid":"s1","isSidechain":true,"timestamp":"2025-01-01T10:00:10Z","message":{"role":"assistant","content":[{"type":"text","text":"subagent"}]}}"#,
+            r#"{"type":"user","uuid":"u3","timestamp":"2025-01-01T10:05:00Z","message":{"role":"user","content":[{"type":"text","text":"Thanks"}]}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).expect("write session");
+
+        let session = summarize_session(&path, "session-1".to_string()).expect("session");
+        assert_eq!(session.name.as_deref(), Some("Fix the login bug"));
+        assert_eq!(session.created_at, 1_735_725_600);
+        assert_eq!(session.updated_at, 1_735_725_900);
+
+        let turns = read_session_turns(&path).expect("turns");
+        assert_eq!(turns.len(), 2);
+        let types: Vec<&str> = turns[0]["items"]
+            .as_array()
+            .expect("items")
+            .iter()
+            .filter_map(|item| item["type"].as_str())
+            .collect();
+        assert_eq!(
+            types,
+            vec!["userMessage", "reasoning", "agentMessage", "agentMessage"]
+        );
+        assert_eq!(turns[0]["items"][3]["text"], "Fixed.");
+        assert_eq!(turns[1]["items"][0]["content"][0]["text"], "Thanks");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::path::PathBuf;
+
+use crate::shared::atomic_write::write_atomic;
+use crate::shared::audit_log_core::AuditLog;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClaudePermissions {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) allow: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) deny: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) ask: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) default_mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) additional_directories: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+/// `~/.claude/settings.json`. Keys the monitor doesn't model are kept in
+/// `extra` so a read-modify-write round trip leaves them untouched.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct ClaudeSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) permissions: Option<ClaudePermissions>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) hooks: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) env: Option<BTreeMap<String, String>>,
+    #[serde(flatten)]
+    pub(crate) extra: HashMap<String, Value>,
+}
+
+/// `CLAUDE_CONFIG_DIR` when set, otherwise `~/.claude`.
+pub(crate) fn resolve_default_claude_home() -> Option<PathBuf> {
+    if let Ok(value) = env::var("CLAUDE_CONFIG_DIR") {
+        let trimmed = value.trim();
+        if !trimmed.is_empty() {
+            return Some(PathBuf::from(trimmed));
+        }
+    }
+    ["HOME", "USERPROFILE"].iter().find_map(|key| {
+        let value = env::var(key).ok()?;
+        let trimmed = value.trim();
+        (!trimmed.is_empty()).then(|| PathBuf::from(trimmed).join(".claude"))
+    })
+}
+
+pub(crate) fn settings_json_path() -> Option<PathBuf> {
+    resolve_default_claude_home().map(|home| home.join("settings.json"))
+}
+
+pub(crate) fn read_user_settings() -> Result<Option<ClaudeSettings>, String> {
+    let Some(path) = settings_json_path() else {
+        return Ok(None);
+    };
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    if contents.trim().is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str::<ClaudeSettings>(&contents)
+        .map(Some)
+        .map_err(|err| format!("Failed to parse {}: {err}", path.display()))
+}
+
+pub(crate) fn write_user_settings(
+    settings: &ClaudeSettings,
+    audit_log: &AuditLog,
+) -> Result<(), String> {
+    let Some(path) = settings_json_path() else {
+        return Err("Unable to resolve the Claude config directory".to_string());
+    };
+    let json = serde_json::to_string_pretty(settings)
+        .map_err(|err| format!("Failed to serialize settings: {err}"))?;
+    write_atomic(&path, format!("{json}\n").as_bytes())
+        .map_err(|err| format!("Failed to write {}: {err}", path.display()))?;
+    // Only the path: the settings' env holds API keys.
+    audit_log.record(
+        "claude_settings_update",
+        serde_json::json!({ "path": path.display().to_string() }),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ClaudeSettings;
+    use serde_json::json;
+
+    #[test]
+    fn settings_round_trip_keeps_unknown_keys() {
+        let raw = json!({
+            "model": "sonnet",
+            "permissions": {
+                "allow": ["Bash(npm run test:*)"],
+                "defaultMode": "acceptEdits",
+                "disableBypassPermissionsMode": "disable"
+            },
+            "env": { "DEBUG": "1" },
+            "hooks": { "PostToolUse": [] },
+            "includeCoAuthoredBy": false
+        });
+        let settings: ClaudeSettings = serde_json::from_value(raw.clone()).expect("parse");
+        let permissions = settings.permissions.as_ref().expect("permissions");
+        assert_eq!(permissions.default_mode.as_deref(), Some("acceptEdits"));
+        assert_eq!(
+            settings
+                .env
+                .as_ref()
+                .and_then(|env| env.get("DEBUG"))
+                .map(String::as_str),
+            Some("1")
+        );
+        assert_eq!(serde_json::to_value(&settings).expect("serialize"), raw);
+
+        let empty = serde_json::to_value(ClaudeSettings::default()).expect("serialize");
+        assert_eq!(empty, json!({}));
+    }
+}
@@ -1,4 +1,7 @@
-use crate::types::{AppSettings, WorkspaceEntry};
+use crate::types::{AppSettings, CodexConfigOverrides, WorkspaceEntry};
+
+const APPROVAL_POLICIES: [&str; 4] = ["untrusted", "on-failure", "on-request", "never"];
+const SANDBOX_MODES: [&str; 3] = ["read-only", "workspace-write", "danger-full-access"];
 
 pub(crate) fn parse_codex_args(value: Option<&str>) -> Result<Vec<String>, String> {
     let raw = match value {
@@ -37,6 +40,57 @@ pub(crate) fn resolve_workspace_codex_args(
     None
 }
 
+pub(crate) fn validate_codex_config_overrides(
+    overrides: Option<&CodexConfigOverrides>,
+) -> Result<(), String> {
+    let Some(overrides) = overrides else {
+        return Ok(());
+    };
+    if let Some(policy) = overrides.approval_policy.as_deref() {
+        if !APPROVAL_POLICIES.contains(&policy) {
+            return Err(format!("Unknown approval policy \"{policy}\""));
+        }
+    }
+    if let Some(mode) = overrides.sandbox_mode.as_deref() {
+        if !SANDBOX_MODES.contains(&mode) {
+            return Err(format!("Unknown sandbox mode \"{mode}\""));
+        }
+    }
+    Ok(())
+}
+
+/// The workspace's codex config overrides as `-c key=value` arguments. A
+/// worktree inherits each key its parent sets and it doesn't.
+pub(crate) fn workspace_codex_config_args(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) -> Vec<String> {
+    let own = entry.settings.codex_config.clone().unwrap_or_default();
+    let inherited = parent_entry
+        .filter(|_| entry.kind.is_worktree())
+        .and_then(|parent| parent.settings.codex_config.clone())
+        .unwrap_or_default();
+    [
+        ("model", own.model, inherited.model),
+        (
+            "approval_policy",
+            own.approval_policy,
+            inherited.approval_policy,
+        ),
+        ("sandbox_mode", own.sandbox_mode, inherited.sandbox_mode),
+    ]
+    .into_iter()
+    .filter_map(|(key, own, inherited)| {
+        let value = own
+            .as_deref()
+            .and_then(normalize_codex_args)
+            .or_else(|| inherited.as_deref().and_then(normalize_codex_args))?;
+        Some(format!("{key}={}", toml_edit::Value::from(value)))
+    })
+    .flat_map(|arg| ["-c".to_string(), arg])
+    .collect()
+}
+
 fn normalize_codex_args(value: &str) -> Option<String> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
@@ -1,12 +1,50 @@
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
 
 use toml::Value as TomlValue;
+use toml_edit::{value, DocumentMut, Item, Table, TableLike};
 
 use crate::files::io::read_text_file_within;
 use crate::files::ops::write_with_policy;
 use crate::files::policy::{policy_for, FileKind, FileScope};
-
-const FEATURES_TABLE: &str = "[features]";
+use crate::types::{CodexProfile, ConfigDiagnostic};
+
+const FEATURES_TABLE: &str = "features";
+
+/// Feature flags Codex understands; anything else under `[features]` is
+/// reported by `validate_config_contents`.
+const KNOWN_FEATURE_FLAGS: &[&str] = &[
+    "apply_patch_freeform",
+    "apps",
+    "collab",
+    "collaboration_modes",
+    "exec_policy",
+    "experimental_windows_sandbox",
+    "ghost_commit",
+    "remote_compaction",
+    "rmcp_client",
+    "shell_snapshot",
+    "shell_tool",
+    "skills",
+    "steer",
+    "undo",
+    "unified_exec",
+    "view_image_tool",
+    "web_search_request",
+];
+const PROFILES_TABLE: &str = "profiles";
+/// Top-level keys a profile overrides; `save_current_profile` snapshots these.
+const PROFILE_KEYS: &[&str] = &[
+    "model",
+    "model_provider",
+    "model_reasoning_effort",
+    "approval_policy",
+    "sandbox_mode",
+];
+const APPROVAL_POLICIES: &[&str] = &["untrusted", "on-failure", "on-request", "never"];
+const SANDBOX_MODES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
 
 pub(crate) fn read_steer_enabled() -> Result<Option<bool>, String> {
     read_feature_flag("steer")
@@ -32,10 +70,8 @@ pub(crate) fn read_personality() -> Result<Option<String>, String> {
     let Some(root) = resolve_default_codex_home() else {
         return Ok(None);
     };
-    let contents = read_config_contents_from_root(&root)?;
-    Ok(contents
-        .as_deref()
-        .and_then(parse_personality_from_toml)
+    Ok(load_config_snapshot(&root)?
+        .personality()
         .map(|value| value.to_string()))
 }
 
@@ -60,67 +96,229 @@ pub(crate) fn write_apps_enabled(enabled: bool) -> Result<(), String> {
 }
 
 pub(crate) fn write_personality(personality: &str) -> Result<(), String> {
-    let Some(root) = resolve_default_codex_home() else {
-        return Ok(());
+    let normalized = normalize_personality_value(personality);
+    update_config_document(|doc| set_config_item(doc, &["personality"], normalized.map(value)))
+}
+
+pub(crate) fn read_profiles() -> Result<Vec<CodexProfile>, String> {
+    let Some(contents) = read_config_toml()? else {
+        return Ok(Vec::new());
     };
-    let policy = config_policy()?;
-    let response = read_text_file_within(
-        &root,
-        policy.filename,
-        policy.root_may_be_missing,
-        policy.root_context,
-        policy.filename,
-        policy.allow_external_symlink_target,
-    )?;
-    let contents = if response.exists {
-        response.content
-    } else {
-        String::new()
+    Ok(profiles_from_document(&parse_config_document(&contents)?))
+}
+
+/// Points the top-level `profile` key at `name`, or clears it with `None`.
+pub(crate) fn write_active_profile(name: Option<&str>) -> Result<(), String> {
+    update_config_document(|doc| {
+        if let Some(name) = name {
+            if config_item(doc, &[PROFILES_TABLE, name]).is_none() {
+                return Err(format!("Profile `{name}` is not defined"));
+            }
+        }
+        set_config_item(doc, &["profile"], name.map(value))
+    })
+}
+
+/// The top-level profile keys and the active `profile`, as a settings
+/// profile captures them.
+pub(crate) fn read_settings_overlay() -> Result<BTreeMap<String, String>, String> {
+    let Some(contents) = read_config_toml()? else {
+        return Ok(BTreeMap::new());
     };
-    let normalized = normalize_personality_value(personality);
-    let updated = match normalized {
-        Some(value) => upsert_top_level_string_key(&contents, "personality", value),
-        None => remove_top_level_key(&contents, "personality"),
+    Ok(overlay_from_document(&parse_config_document(&contents)?))
+}
+
+/// Sets the overlay keys to `overlay`, removing those it doesn't have.
+pub(crate) fn write_settings_overlay(overlay: &BTreeMap<String, String>) -> Result<(), String> {
+    update_config_document(|doc| apply_overlay(doc, overlay))
+}
+
+fn overlay_keys() -> impl Iterator<Item = &'static str> {
+    PROFILE_KEYS.iter().copied().chain(["profile"])
+}
+
+fn overlay_from_document(doc: &DocumentMut) -> BTreeMap<String, String> {
+    overlay_keys()
+        .filter_map(|key| {
+            let current = doc.get(key).and_then(Item::as_str)?;
+            Some((key.to_string(), current.to_string()))
+        })
+        .collect()
+}
+
+fn apply_overlay(doc: &mut DocumentMut, overlay: &BTreeMap<String, String>) -> Result<(), String> {
+    if let Some(profile) = overlay.get("profile") {
+        if config_item(doc, &[PROFILES_TABLE, profile]).is_none() {
+            return Err(format!("Profile `{profile}` is not defined"));
+        }
+    }
+    for key in overlay_keys() {
+        set_config_item(
+            doc,
+            &[key],
+            overlay.get(key).map(|current| value(current.as_str())),
+        )?;
+    }
+    Ok(())
+}
+
+/// Saves the current top-level model/provider/approval settings as
+/// `[profiles.<name>]`, replacing those keys in an existing profile and
+/// leaving its other keys alone.
+pub(crate) fn save_current_profile(name: &str) -> Result<(), String> {
+    update_config_document(|doc| save_profile_from_top_level(doc, name))
+}
+
+fn profiles_from_document(doc: &DocumentMut) -> Vec<CodexProfile> {
+    let active = config_item(doc, &["profile"]).and_then(Item::as_str);
+    let Some(profiles) = config_item(doc, &[PROFILES_TABLE]).and_then(Item::as_table_like) else {
+        return Vec::new();
     };
-    write_with_policy(&root, policy, &updated)
+    profiles
+        .iter()
+        .filter(|(_, item)| item.is_table_like())
+        .map(|(name, item)| {
+            let string_key = |key: &str| item.get(key).and_then(Item::as_str).map(str::to_string);
+            CodexProfile {
+                name: name.to_string(),
+                model: string_key("model"),
+                model_provider: string_key("model_provider"),
+                model_reasoning_effort: string_key("model_reasoning_effort"),
+                approval_policy: string_key("approval_policy"),
+                sandbox_mode: string_key("sandbox_mode"),
+                active: active == Some(name),
+            }
+        })
+        .collect()
+}
+
+fn save_profile_from_top_level(doc: &mut DocumentMut, name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    for key in PROFILE_KEYS {
+        let current = doc
+            .get(key)
+            .and_then(Item::as_value)
+            .map(|current| Item::Value(current.clone().decorated(" ", "")));
+        set_config_item(doc, &[PROFILES_TABLE, name, key], current)?;
+    }
+    if config_item(doc, &[PROFILES_TABLE, name]).is_none() {
+        set_config_item(
+            doc,
+            &[PROFILES_TABLE, name],
+            Some(Item::Table(Table::new())),
+        )?;
+    }
+    Ok(())
 }
 
 fn read_feature_flag(key: &str) -> Result<Option<bool>, String> {
     let Some(root) = resolve_default_codex_home() else {
         return Ok(None);
     };
-    let contents = read_config_contents_from_root(&root)?;
-    Ok(contents
-        .as_deref()
-        .and_then(|value| find_feature_flag(value, key)))
+    Ok(load_config_snapshot(&root)?.feature_flag(key))
 }
 
 fn write_feature_flag(key: &str, enabled: bool) -> Result<(), String> {
+    update_config_document(|doc| set_config_item(doc, &[FEATURES_TABLE, key], Some(value(enabled))))
+}
+
+/// Reads the default `config.toml`, applies `edit` and writes it back.
+/// Everything the edit doesn't touch (comments, ordering, whitespace) is
+/// kept as written.
+fn update_config_document(
+    edit: impl FnOnce(&mut DocumentMut) -> Result<(), String>,
+) -> Result<(), String> {
     let Some(root) = resolve_default_codex_home() else {
         return Ok(());
     };
-    let policy = config_policy()?;
-    let response = read_text_file_within(
-        &root,
-        policy.filename,
-        policy.root_may_be_missing,
-        policy.root_context,
-        policy.filename,
-        policy.allow_external_symlink_target,
-    )?;
-    let contents = if response.exists {
-        response.content
-    } else {
-        String::new()
+    let contents = load_config_snapshot(&root)?
+        .contents
+        .clone()
+        .unwrap_or_default();
+    let mut doc = parse_config_document(&contents)?;
+    edit(&mut doc)?;
+    let written = write_with_policy(&root, config_policy()?, &doc.to_string());
+    invalidate_config_snapshot(&root);
+    written
+}
+
+/// Parses `config.toml` for in-place editing.
+pub(crate) fn parse_config_document(contents: &str) -> Result<DocumentMut, String> {
+    contents
+        .parse::<DocumentMut>()
+        .map_err(|err| format!("Failed to parse config.toml: {err}"))
+}
+
+/// Item at `path`, whether it is spelled as a `[table]`, a dotted key or an
+/// inline table.
+pub(crate) fn config_item<'a>(doc: &'a DocumentMut, path: &[&str]) -> Option<&'a Item> {
+    path.iter()
+        .try_fold(doc.as_item(), |item, segment| item.get(segment))
+}
+
+/// Sets the item at `path`, or removes it when `item` is `None`. Missing
+/// parent tables are created; a parent that exists but isn't a table is an
+/// error.
+pub(crate) fn set_config_item(
+    doc: &mut DocumentMut,
+    path: &[&str],
+    item: Option<Item>,
+) -> Result<(), String> {
+    let Some((key, parents)) = path.split_last() else {
+        return Ok(());
     };
-    let updated = upsert_feature_flag(&contents, key, enabled);
-    write_with_policy(&root, policy, &updated)
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for (depth, segment) in parents.iter().enumerate() {
+        if item.is_none() && !table.contains_key(segment) {
+            return Ok(());
+        }
+        let entry = table.entry(segment).or_insert_with(|| {
+            let mut parent = Table::new();
+            parent.set_implicit(true);
+            Item::Table(parent)
+        });
+        table = entry.as_table_like_mut().ok_or_else(|| {
+            format!(
+                "`{}` in config.toml is not a table",
+                parents[..=depth].join(".")
+            )
+        })?;
+    }
+    match item {
+        Some(item) => {
+            table.insert(key, item);
+        }
+        None => {
+            table.remove(key);
+        }
+    }
+    Ok(())
 }
 
 pub(crate) fn config_toml_path() -> Option<PathBuf> {
     resolve_default_codex_home().map(|home| home.join("config.toml"))
 }
 
+/// Raw contents of the default `config.toml`, `None` when it doesn't exist.
+pub(crate) fn read_config_toml() -> Result<Option<String>, String> {
+    let Some(root) = resolve_default_codex_home() else {
+        return Ok(None);
+    };
+    Ok(load_config_snapshot(&root)?.contents.clone())
+}
+
+pub(crate) fn write_config_toml(contents: &str) -> Result<(), String> {
+    let Some(root) = resolve_default_codex_home() else {
+        return Err("Unable to resolve CODEX_HOME".to_string());
+    };
+    let written = write_with_policy(&root, config_policy()?, contents);
+    invalidate_config_snapshot(&root);
+    written
+}
+
 pub(crate) fn read_config_model(codex_home: Option<PathBuf>) -> Result<Option<String>, String> {
     let root = codex_home.or_else(resolve_default_codex_home);
     let Some(root) = root else {
@@ -137,6 +335,91 @@ fn config_policy() -> Result<crate::files::policy::FilePolicy, String> {
     policy_for(FileScope::Global, FileKind::Config)
 }
 
+/// `config.toml` read and parsed once, shared by every `read_*` call until
+/// the file's mtime or size changes or the monitor writes it.
+struct ConfigSnapshot {
+    stamp: Option<(SystemTime, u64)>,
+    contents: Option<String>,
+    parsed: Option<TomlValue>,
+}
+
+impl ConfigSnapshot {
+    fn new(stamp: Option<(SystemTime, u64)>, contents: Option<String>) -> Self {
+        let parsed = contents
+            .as_deref()
+            .and_then(|contents| toml::from_str(contents).ok());
+        Self {
+            stamp,
+            contents,
+            parsed,
+        }
+    }
+
+    fn feature_flag(&self, key: &str) -> Option<bool> {
+        self.parsed
+            .as_ref()?
+            .get(FEATURES_TABLE)?
+            .get(key)?
+            .as_bool()
+    }
+
+    fn personality(&self) -> Option<&'static str> {
+        let value = self.parsed.as_ref()?.get("personality")?.as_str()?;
+        normalize_personality_value(value)
+    }
+
+    fn model(&self) -> Option<String> {
+        let model = self.parsed.as_ref()?.get("model")?.as_str()?;
+        let trimmed = model.trim();
+        if trimmed.is_empty() {
+            None
+        } else {
+            Some(trimmed.to_string())
+        }
+    }
+}
+
+fn config_snapshots() -> &'static Mutex<HashMap<PathBuf, Arc<ConfigSnapshot>>> {
+    static SNAPSHOTS: OnceLock<Mutex<HashMap<PathBuf, Arc<ConfigSnapshot>>>> = OnceLock::new();
+    SNAPSHOTS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn load_config_snapshot(root: &Path) -> Result<Arc<ConfigSnapshot>, String> {
+    let path = root.join(config_policy()?.filename);
+    let stamp = file_stamp(&path);
+    let cached = config_snapshots()
+        .lock()
+        .ok()
+        .and_then(|snapshots| snapshots.get(&path).cloned());
+    if let Some(cached) = cached {
+        if stamp.is_some() && cached.stamp == stamp {
+            return Ok(cached);
+        }
+    }
+    let snapshot = Arc::new(ConfigSnapshot::new(
+        stamp,
+        read_config_contents_from_root(root)?,
+    ));
+    if let Ok(mut snapshots) = config_snapshots().lock() {
+        snapshots.insert(path, Arc::clone(&snapshot));
+    }
+    Ok(snapshot)
+}
+
+fn invalidate_config_snapshot(root: &Path) {
+    let Ok(policy) = config_policy() else {
+        return;
+    };
+    if let Ok(mut snapshots) = config_snapshots().lock() {
+        snapshots.remove(&root.join(policy.filename));
+    }
+}
+
 fn read_config_contents_from_root(root: &Path) -> Result<Option<String>, String> {
     let policy = config_policy()?;
     let response = read_text_file_within(
@@ -155,25 +438,7 @@ fn read_config_contents_from_root(root: &Path) -> Result<Option<String>, String>
 }
 
 fn read_config_model_from_root(root: &Path) -> Result<Option<String>, String> {
-    let contents = read_config_contents_from_root(root)?;
-    Ok(contents.as_deref().and_then(parse_model_from_toml))
-}
-
-fn parse_model_from_toml(contents: &str) -> Option<String> {
-    let parsed: TomlValue = toml::from_str(contents).ok()?;
-    let model = parsed.get("model")?.as_str()?;
-    let trimmed = model.trim();
-    if trimmed.is_empty() {
-        None
-    } else {
-        Some(trimmed.to_string())
-    }
-}
-
-fn parse_personality_from_toml(contents: &str) -> Option<&'static str> {
-    let parsed: TomlValue = toml::from_str(contents).ok()?;
-    let value = parsed.get("personality")?.as_str()?;
-    normalize_personality_value(value)
+    Ok(load_config_snapshot(root)?.model())
 }
 
 fn normalize_personality_value(value: &str) -> Option<&'static str> {
@@ -184,186 +449,178 @@ fn normalize_personality_value(value: &str) -> Option<&'static str> {
     }
 }
 
-fn find_feature_flag(contents: &str, key: &str) -> Option<bool> {
-    let mut in_features = false;
-    for line in contents.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            in_features = trimmed == FEATURES_TABLE;
-            continue;
-        }
-        if !in_features || trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
+/// Syntax errors, unknown or malformed feature flags, invalid enum values
+/// and settings that contradict each other, in file order where possible.
+pub(crate) fn validate_config_contents(contents: &str) -> Vec<ConfigDiagnostic> {
+    let doc = match toml_edit::Document::parse(contents) {
+        Ok(doc) => doc,
+        Err(err) => {
+            let line = err.span().map(|span| line_at(contents, span.start));
+            return vec![diagnostic("error", err.message().trim(), line, None)];
         }
-        let (candidate_key, value) = trimmed.split_once('=')?;
-        if candidate_key.trim() != key {
-            continue;
-        }
-        let value = value.split('#').next().unwrap_or("").trim();
-        return match value {
-            "true" => Some(true),
-            "false" => Some(false),
-            _ => None,
-        };
-    }
-    None
-}
-
-fn upsert_feature_flag(contents: &str, key: &str, enabled: bool) -> String {
-    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
-    let mut in_features = false;
-    let mut features_start: Option<usize> = None;
-    let mut features_end: Option<usize> = None;
-    let mut key_index: Option<usize> = None;
-
-    for (idx, line) in lines.iter().enumerate() {
-        let trimmed = line.trim();
-        if trimmed.starts_with('[') && trimmed.ends_with(']') {
-            if in_features {
-                features_end = Some(idx);
-                break;
-            }
-            in_features = trimmed == FEATURES_TABLE;
-            if in_features {
-                features_start = Some(idx);
-            }
-            continue;
-        }
-        if !in_features || trimmed.is_empty() || trimmed.starts_with('#') {
-            continue;
-        }
-        if let Some((candidate_key, _)) = trimmed.split_once('=') {
-            if candidate_key.trim() == key {
-                key_index = Some(idx);
-                break;
+    };
+    let root = doc.as_table();
+    let key_line = |table: &dyn TableLike, key: &str| {
+        table
+            .key(key)
+            .and_then(|key| key.span())
+            .map(|span| line_at(contents, span.start))
+    };
+    let mut diagnostics = Vec::new();
+
+    if let Some(features) = root.get(FEATURES_TABLE) {
+        match features.as_table_like() {
+            Some(features) => {
+                for (key, item) in features.iter() {
+                    let path = format!("{FEATURES_TABLE}.{key}");
+                    let line = key_line(features, key);
+                    if !KNOWN_FEATURE_FLAGS.contains(&key) {
+                        let message = format!("Unknown feature flag `{path}`");
+                        diagnostics.push(diagnostic("warning", &message, line, Some(&path)));
+                    } else if item.as_bool().is_none() {
+                        let message = format!("`{path}` must be true or false");
+                        diagnostics.push(diagnostic("error", &message, line, Some(&path)));
+                    }
+                }
             }
+            None => diagnostics.push(diagnostic(
+                "error",
+                "`features` must be a table",
+                key_line(root, FEATURES_TABLE),
+                Some(FEATURES_TABLE),
+            )),
         }
     }
 
-    let flag_line = format!("{key} = {}", if enabled { "true" } else { "false" });
-
-    if let Some(start) = features_start {
-        let end = features_end.unwrap_or(lines.len());
-        if let Some(index) = key_index {
-            lines[index] = flag_line;
-        } else {
-            let insert_at = if end > start + 1 { end } else { start + 1 };
-            lines.insert(insert_at, flag_line);
-        }
-    } else {
-        if !lines.is_empty() && !lines.last().unwrap().trim().is_empty() {
-            lines.push(String::new());
+    let mut enum_setting = |key: &str, allowed: &[&str]| -> Option<String> {
+        let item = root.get(key)?;
+        match item.as_str() {
+            Some(value) if allowed.contains(&value) => Some(value.to_string()),
+            _ => {
+                let message = format!("`{key}` must be one of: {}", allowed.join(", "));
+                diagnostics.push(diagnostic(
+                    "error",
+                    &message,
+                    key_line(root, key),
+                    Some(key),
+                ));
+                None
+            }
         }
-        lines.push(FEATURES_TABLE.to_string());
-        lines.push(flag_line);
-    }
-
-    let mut updated = lines.join("\n");
-    if contents.ends_with('\n') || updated.is_empty() {
-        updated.push('\n');
+    };
+    let approval_policy = enum_setting("approval_policy", APPROVAL_POLICIES);
+    let sandbox_mode = enum_setting("sandbox_mode", SANDBOX_MODES);
+
+    let approval_line = key_line(root, "approval_policy");
+    match (sandbox_mode.as_deref(), approval_policy.as_deref()) {
+        (Some("danger-full-access"), Some("never")) => diagnostics.push(diagnostic(
+            "warning",
+            "Commands run outside the sandbox without asking for approval",
+            approval_line,
+            Some("approval_policy"),
+        )),
+        (Some("read-only"), Some("never")) => diagnostics.push(diagnostic(
+            "warning",
+            "With a read-only sandbox and `approval_policy = \"never\"`, commands that need to write fail instead of asking",
+            approval_line,
+            Some("approval_policy"),
+        )),
+        _ => {}
     }
-    updated
-}
-
-fn remove_top_level_key(contents: &str, key: &str) -> String {
-    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
-    let table_start = first_table_start_index(&lines).unwrap_or(lines.len());
-    lines.retain_with_index(|idx, line| {
-        if idx >= table_start {
-            return true;
-        }
-        !is_key_value_for(line, key)
-    });
-
-    let mut updated = lines.join("\n");
-    if contents.ends_with('\n') || updated.is_empty() {
-        updated.push('\n');
+    if root.contains_key("sandbox_workspace_write")
+        && sandbox_mode
+            .as_deref()
+            .is_some_and(|mode| mode != "workspace-write")
+    {
+        diagnostics.push(diagnostic(
+            "warning",
+            "`[sandbox_workspace_write]` is ignored unless `sandbox_mode` is `workspace-write`",
+            key_line(root, "sandbox_workspace_write"),
+            Some("sandbox_workspace_write"),
+        ));
     }
-    updated
-}
-
-fn upsert_top_level_string_key(contents: &str, key: &str, value: &str) -> String {
-    let mut lines: Vec<String> = contents.lines().map(|line| line.to_string()).collect();
-    let table_start = first_table_start_index(&lines).unwrap_or(lines.len());
-    let replacement = format!("{key} = \"{value}\"");
-    let mut replaced = false;
 
-    for line in lines.iter_mut().take(table_start) {
-        if is_key_value_for(line, key) {
-            *line = replacement.clone();
-            replaced = true;
-            break;
+    if let Some(profile) = root.get("profile").and_then(Item::as_str) {
+        let defined = root
+            .get("profiles")
+            .and_then(|profiles| profiles.get(profile))
+            .is_some();
+        if !defined {
+            let message = format!("Profile `{profile}` is not defined in `[profiles]`");
+            diagnostics.push(diagnostic(
+                "error",
+                &message,
+                key_line(root, "profile"),
+                Some("profile"),
+            ));
         }
     }
 
-    if !replaced {
-        lines.insert(table_start, replacement);
-    }
-
-    let mut updated = lines.join("\n");
-    if contents.ends_with('\n') || updated.is_empty() {
-        updated.push('\n');
+    diagnostics.sort_by_key(|entry| entry.line.unwrap_or(usize::MAX));
+    diagnostics
+}
+
+fn diagnostic(
+    severity: &str,
+    message: &str,
+    line: Option<usize>,
+    key: Option<&str>,
+) -> ConfigDiagnostic {
+    ConfigDiagnostic {
+        severity: severity.to_string(),
+        message: message.to_string(),
+        line,
+        key: key.map(|key| key.to_string()),
     }
-    updated
 }
 
-fn is_key_value_for(line: &str, key: &str) -> bool {
-    let trimmed = line.trim();
-    if trimmed.is_empty() || trimmed.starts_with('#') {
-        return false;
-    }
-    let Some((candidate_key, _)) = trimmed.split_once('=') else {
-        return false;
-    };
-    candidate_key.trim() == key
-}
-
-fn first_table_start_index(lines: &[String]) -> Option<usize> {
-    lines.iter().position(|line| {
-        let trimmed = line.trim();
-        trimmed.starts_with('[') && trimmed.ends_with(']')
-    })
+fn line_at(contents: &str, offset: usize) -> usize {
+    let offset = offset.min(contents.len());
+    contents.as_bytes()[..offset]
+        .iter()
+        .filter(|byte| **byte == b'\n')
+        .count()
+        + 1
 }
 
-trait RetainWithIndex<T> {
-    fn retain_with_index<F: FnMut(usize, &T) -> bool>(&mut self, f: F);
-}
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_overlay, config_item, load_config_snapshot, overlay_from_document,
+        parse_config_document, profiles_from_document, save_profile_from_top_level,
+        set_config_item, validate_config_contents, ConfigSnapshot,
+    };
+    use std::sync::Arc;
+    use toml_edit::value;
+    use uuid::Uuid;
 
-impl<T> RetainWithIndex<T> for Vec<T> {
-    fn retain_with_index<F: FnMut(usize, &T) -> bool>(&mut self, mut f: F) {
-        let mut index = 0usize;
-        self.retain(|item| {
-            let keep = f(index, item);
-            index += 1;
-            keep
-        });
+    fn snapshot(contents: &str) -> ConfigSnapshot {
+        ConfigSnapshot::new(None, Some(contents.to_string()))
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::{parse_personality_from_toml, remove_top_level_key, upsert_top_level_string_key};
+    fn edit(contents: &str, path: &[&str], item: Option<toml_edit::Item>) -> String {
+        let mut doc = parse_config_document(contents).expect("parse config");
+        set_config_item(&mut doc, path, item).expect("edit config");
+        doc.to_string()
+    }
 
     #[test]
     fn parse_personality_reads_supported_values() {
         assert_eq!(
-            parse_personality_from_toml("personality = \"friendly\"\n"),
+            snapshot("personality = \"friendly\"\n").personality(),
             Some("friendly")
         );
         assert_eq!(
-            parse_personality_from_toml("personality = \"pragmatic\"\n"),
+            snapshot("personality = \"pragmatic\"\n").personality(),
             Some("pragmatic")
         );
-        assert_eq!(
-            parse_personality_from_toml("personality = \"unknown\"\n"),
-            None
-        );
+        assert_eq!(snapshot("personality = \"unknown\"\n").personality(), None);
     }
 
     #[test]
     fn upsert_top_level_personality_before_tables() {
         let input = "[features]\nsteer = true\n";
-        let updated = upsert_top_level_string_key(input, "personality", "friendly");
+        let updated = edit(input, &["personality"], Some(value("friendly")));
         assert_eq!(
             updated,
             "personality = \"friendly\"\n[features]\nsteer = true\n"
@@ -373,7 +630,7 @@ mod tests {
     #[test]
     fn upsert_replaces_existing_top_level_personality() {
         let input = "personality = \"friendly\"\n[features]\nsteer = true\n";
-        let updated = upsert_top_level_string_key(input, "personality", "pragmatic");
+        let updated = edit(input, &["personality"], Some(value("pragmatic")));
         assert_eq!(
             updated,
             "personality = \"pragmatic\"\n[features]\nsteer = true\n"
@@ -383,7 +640,117 @@ mod tests {
     #[test]
     fn remove_top_level_personality_keeps_other_keys() {
         let input = "personality = \"friendly\"\nmodel = \"gpt-5\"\n[features]\nsteer = true\n";
-        let updated = remove_top_level_key(input, "personality");
+        let updated = edit(input, &["personality"], None);
         assert_eq!(updated, "model = \"gpt-5\"\n[features]\nsteer = true\n");
     }
+
+    #[test]
+    fn nested_edits_keep_comments_and_arrays() {
+        let input = "# pinned\nmodel = \"gpt-5\" # default\n\n[features]\n# flags\nsteer = true\n\n[profiles.fast]\nmodel = \"o4-mini\"\nargs = [\n  \"a\", # first\n  \"b\",\n]\n";
+        let updated = edit(input, &["features", "collab"], Some(value(false)));
+        let updated = edit(&updated, &["profiles", "fast", "model"], Some(value("o3")));
+        assert_eq!(
+            updated,
+            "# pinned\nmodel = \"gpt-5\" # default\n\n[features]\n# flags\nsteer = true\ncollab = false\n\n[profiles.fast]\nmodel = \"o3\"\nargs = [\n  \"a\", # first\n  \"b\",\n]\n"
+        );
+
+        let dotted = edit(
+            "features.steer = true\n",
+            &["features", "apps"],
+            Some(value(true)),
+        );
+        assert_eq!(dotted, "features.steer = true\nfeatures.apps = true\n");
+        assert_eq!(snapshot(&dotted).feature_flag("apps"), Some(true));
+
+        let doc = parse_config_document(&dotted).expect("parse config");
+        assert!(config_item(&doc, &["features", "steer", "nested"]).is_none());
+        let mut doc = doc;
+        assert!(
+            set_config_item(&mut doc, &["features", "steer", "nested"], Some(value(1))).is_err()
+        );
+    }
+
+    #[test]
+    fn validate_reports_syntax_unknown_flags_and_conflicts() {
+        let broken = validate_config_contents("model = \"gpt-5\"\n[features\nsteer = true\n");
+        assert_eq!(broken.len(), 1);
+        assert_eq!(
+            (broken[0].severity.as_str(), broken[0].line),
+            ("error", Some(2))
+        );
+
+        let input = "sandbox_mode = \"danger-full-access\"\napproval_policy = \"never\"\nprofile = \"fast\"\n\n[features]\nsteer = true\nsteeer = true\napps = \"yes\"\n";
+        let found = validate_config_contents(input)
+            .into_iter()
+            .map(|entry| (entry.severity, entry.line, entry.key))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("warning", 2, "approval_policy"),
+            ("error", 3, "profile"),
+            ("warning", 7, "features.steeer"),
+            ("error", 8, "features.apps"),
+        ]
+        .map(|(severity, line, key)| (severity.to_string(), Some(line), Some(key.to_string())));
+        assert_eq!(found, expected);
+        assert!(validate_config_contents("[features]\nsteer = true\n").is_empty());
+    }
+
+    #[test]
+    fn profiles_are_listed_and_saved_from_top_level_keys() {
+        let input = "model = \"o3\"\nmodel_reasoning_effort = \"high\" # deep\nprofile = \"fast\"\n\n[profiles.fast]\nmodel = \"o4-mini\"\nmodel_provider = \"openai\"\n";
+        let mut doc = parse_config_document(input).expect("parse config");
+        let profiles = profiles_from_document(&doc);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].model.as_deref(), Some("o4-mini"));
+        assert!(profiles[0].active);
+
+        save_profile_from_top_level(&mut doc, "deep").expect("save profile");
+        save_profile_from_top_level(&mut doc, "fast").expect("save profile");
+        assert_eq!(
+            doc.to_string(),
+            "model = \"o3\"\nmodel_reasoning_effort = \"high\" # deep\nprofile = \"fast\"\n\n[profiles.fast]\nmodel = \"o3\"\nmodel_reasoning_effort = \"high\"\n\n[profiles.deep]\nmodel = \"o3\"\nmodel_reasoning_effort = \"high\"\n"
+        );
+        assert!(save_profile_from_top_level(&mut doc, " ").is_err());
+    }
+
+    #[test]
+    fn settings_overlay_replaces_the_top_level_keys() {
+        let mut doc = parse_config_document(
+            "model = \"o3\"\nsandbox_mode = \"read-only\"\n\n[profiles.work]\nmodel = \"gpt-5\"\n",
+        )
+        .expect("parse config");
+        let overlay = overlay_from_document(&doc);
+        assert_eq!(overlay.len(), 2);
+        assert_eq!(overlay["model"], "o3");
+
+        let work = [("model", "gpt-5"), ("profile", "work")]
+            .into_iter()
+            .map(|(key, current)| (key.to_string(), current.to_string()))
+            .collect();
+        apply_overlay(&mut doc, &work).expect("apply overlay");
+        assert_eq!(overlay_from_document(&doc), work);
+
+        let missing = [("profile".to_string(), "home".to_string())].into();
+        assert!(apply_overlay(&mut doc, &missing).is_err());
+    }
+
+    #[test]
+    fn snapshot_is_reused_until_the_file_changes() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create temp dir");
+        let path = root.join("config.toml");
+        std::fs::write(&path, "model = \"o3\"\n").expect("write config");
+
+        let first = load_config_snapshot(&root).expect("load");
+        let second = load_config_snapshot(&root).expect("load");
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(first.model().as_deref(), Some("o3"));
+
+        std::fs::write(&path, "model = \"gpt-5\"\n[features]\nsteer = true\n").expect("write");
+        let changed = load_config_snapshot(&root).expect("load");
+        assert_eq!(changed.model().as_deref(), Some("gpt-5"));
+        assert_eq!(changed.feature_flag("steer"), Some(true));
+
+        std::fs::remove_dir_all(&root).expect("remove temp dir");
+    }
 }
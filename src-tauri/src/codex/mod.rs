@@ -1,35 +1,70 @@
 use serde_json::{json, Map, Value};
 use std::sync::Arc;
 
-use tauri::{AppHandle, Emitter, State};
+use tauri::{AppHandle, Emitter, Manager, State};
 
 pub(crate) mod args;
 pub(crate) mod config;
 pub(crate) mod home;
 
-use crate::backend::app_server::{spawn_workspace_session as spawn_workspace_session_inner, CliSpawnConfig};
+use crate::backend::app_server::{spawn_workspace_session as spawn_workspace_session_inner, CliSpawnConfig, PendingRequestInfo};
 pub(crate) use crate::backend::app_server::WorkspaceSession;
-use crate::backend::events::AppServerEvent;
+use crate::backend::adapter_base::turn_metrics_path;
+use crate::backend::background_queue::BackgroundCallbackInfo;
+use crate::backend::events::{AppEvent, AppServerEvent};
+use crate::backend::turn_metrics::{self, TurnMetrics};
 use crate::event_sink::TauriEventSink;
 use crate::remote_backend;
-use crate::shared::codex_core;
+use crate::shared::activity_core::{self, ActivityTimeline};
+use crate::shared::app_error::{AppError, AppResult};
+use crate::shared::background_turn_core::{self, BackgroundTurnResult};
+use crate::shared::budget_core::{self, BudgetStatus};
+use crate::shared::patch_core::{self, PatchApplyResult};
+use crate::shared::{codex_core, codex_sessions_core, session_watchdog_core};
+use crate::shared::turn_snapshot_core::{self, TurnRollbackResponse};
 use crate::state::AppState;
-use crate::types::WorkspaceEntry;
+use crate::tray;
+use crate::types::{CodexSessionSummary, WorkspaceEntry};
 
 pub(crate) async fn spawn_workspace_session(
     entry: WorkspaceEntry,
-    config: CliSpawnConfig,
+    mut config: CliSpawnConfig,
     app_handle: AppHandle,
 ) -> Result<Arc<WorkspaceSession>, String> {
+    let state = app_handle.state::<AppState>();
+    config.active_turns = state.maintenance.active_turns();
+    config.services = state.services.clone();
     let client_version = app_handle.package_info().version.to_string();
-    let event_sink = TauriEventSink::new(app_handle);
-    spawn_workspace_session_inner(
-        entry,
-        config,
-        client_version,
-        event_sink,
+    let event_sink = TauriEventSink::new(app_handle.clone());
+    let session = spawn_workspace_session_inner(
+        entry.clone(),
+        config.clone(),
+        client_version.clone(),
+        event_sink.clone(),
     )
-    .await
+    .await?;
+
+    let supervised = Arc::clone(&session);
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        session_watchdog_core::supervise_session(
+            entry.id.clone(),
+            supervised,
+            &state.sessions,
+            &state.app_settings,
+            event_sink.clone(),
+            || {
+                spawn_workspace_session_inner(
+                    entry.clone(),
+                    config.clone(),
+                    client_version.clone(),
+                    event_sink.clone(),
+                )
+            },
+        )
+        .await;
+    });
+    Ok(session)
 }
 
 #[tauri::command]
@@ -37,9 +72,15 @@ pub(crate) async fn codex_doctor(
     codex_bin: Option<String>,
     codex_args: Option<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
-    crate::shared::codex_aux_core::codex_doctor_core(&state.app_settings, codex_bin, codex_args)
-        .await
+) -> AppResult<Value> {
+    Ok(
+        crate::shared::codex_aux_core::codex_doctor_core(
+            &state.app_settings,
+            codex_bin,
+            codex_args,
+        )
+        .await?,
+    )
 }
 
 #[tauri::command]
@@ -47,7 +88,7 @@ pub(crate) async fn start_thread(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -55,10 +96,11 @@ pub(crate) async fn start_thread(
             "start_thread",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::start_thread_core(&state.sessions, workspace_id).await
+    Ok(codex_core::start_thread_core(&state.sessions, workspace_id).await?)
 }
 
 #[tauri::command]
@@ -67,7 +109,7 @@ pub(crate) async fn resume_thread(
     thread_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -75,10 +117,11 @@ pub(crate) async fn resume_thread(
             "resume_thread",
             json!({ "workspaceId": workspace_id, "threadId": thread_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::resume_thread_core(&state.sessions, workspace_id, thread_id).await
+    Ok(codex_core::resume_thread_core(&state.sessions, workspace_id, thread_id).await?)
 }
 
 #[tauri::command]
@@ -87,7 +130,7 @@ pub(crate) async fn fork_thread(
     thread_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -95,10 +138,11 @@ pub(crate) async fn fork_thread(
             "fork_thread",
             json!({ "workspaceId": workspace_id, "threadId": thread_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::fork_thread_core(&state.sessions, workspace_id, thread_id).await
+    Ok(codex_core::fork_thread_core(&state.sessions, workspace_id, thread_id).await?)
 }
 
 #[tauri::command]
@@ -107,9 +151,10 @@ pub(crate) async fn list_threads(
     cursor: Option<String>,
     limit: Option<u32>,
     sort_key: Option<String>,
+    archived: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -119,13 +164,78 @@ pub(crate) async fn list_threads(
                 "workspaceId": workspace_id,
                 "cursor": cursor,
                 "limit": limit,
-                "sortKey": sort_key
+                "sortKey": sort_key,
+                "archived": archived
             }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
+    }
+
+    Ok(codex_core::list_threads_core(
+        &state.sessions,
+        workspace_id,
+        cursor,
+        limit,
+        sort_key,
+        archived,
+    )
+    .await?)
+}
+
+/// Past Codex CLI sessions (e.g. run in a terminal) for the workspace. They
+/// can be opened with `codex_session_read` or continued with `resume_thread`.
+#[tauri::command]
+pub(crate) async fn codex_sessions_list(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<CodexSessionSummary>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_sessions_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_sessions_core::codex_sessions_list_core(
+        &state.workspaces,
+        &state.services.redaction_patterns,
+        workspace_id,
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub(crate) async fn codex_session_read(
+    workspace_id: String,
+    session_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Value> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_session_read",
+            json!({ "workspaceId": workspace_id, "sessionId": session_id }),
+        )
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::list_threads_core(&state.sessions, workspace_id, cursor, limit, sort_key).await
+    Ok(codex_sessions_core::codex_session_read_core(
+        &state.workspaces,
+        &state.services.redaction_patterns,
+        workspace_id,
+        session_id,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -135,7 +245,7 @@ pub(crate) async fn list_mcp_server_status(
     limit: Option<u32>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -143,10 +253,14 @@ pub(crate) async fn list_mcp_server_status(
             "list_mcp_server_status",
             json!({ "workspaceId": workspace_id, "cursor": cursor, "limit": limit }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::list_mcp_server_status_core(&state.sessions, workspace_id, cursor, limit).await
+    Ok(
+        codex_core::list_mcp_server_status_core(&state.sessions, workspace_id, cursor, limit)
+            .await?,
+    )
 }
 
 #[tauri::command]
@@ -155,7 +269,7 @@ pub(crate) async fn archive_thread(
     thread_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -163,10 +277,53 @@ pub(crate) async fn archive_thread(
             "archive_thread",
             json!({ "workspaceId": workspace_id, "threadId": thread_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::archive_thread_core(&state.sessions, workspace_id, thread_id).await
+    Ok(codex_core::archive_thread_core(&state.sessions, workspace_id, thread_id).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn unarchive_thread(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Value> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "unarchive_thread",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await
+        .map_err(AppError::Remote);
+    }
+
+    Ok(codex_core::unarchive_thread_core(&state.sessions, workspace_id, thread_id).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn delete_thread(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Value> {
+    if remote_backend::is_remote_mode(&*state).await {
+        return remote_backend::call_remote(
+            &*state,
+            app,
+            "delete_thread",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await
+        .map_err(AppError::Remote);
+    }
+
+    Ok(codex_core::delete_thread_core(&state.sessions, workspace_id, thread_id).await?)
 }
 
 #[tauri::command]
@@ -175,7 +332,7 @@ pub(crate) async fn compact_thread(
     thread_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -183,10 +340,11 @@ pub(crate) async fn compact_thread(
             "compact_thread",
             json!({ "workspaceId": workspace_id, "threadId": thread_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::compact_thread_core(&state.sessions, workspace_id, thread_id).await
+    Ok(codex_core::compact_thread_core(&state.sessions, workspace_id, thread_id).await?)
 }
 
 #[tauri::command]
@@ -196,7 +354,7 @@ pub(crate) async fn set_thread_name(
     name: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -204,10 +362,11 @@ pub(crate) async fn set_thread_name(
             "set_thread_name",
             json!({ "workspaceId": workspace_id, "threadId": thread_id, "name": name }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::set_thread_name_core(&state.sessions, workspace_id, thread_id, name).await
+    Ok(codex_core::set_thread_name_core(&state.sessions, workspace_id, thread_id, name).await?)
 }
 
 #[tauri::command]
@@ -222,7 +381,7 @@ pub(crate) async fn send_user_message(
     collaboration_mode: Option<Value>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         let images = images.map(|paths| {
             paths
@@ -249,10 +408,11 @@ pub(crate) async fn send_user_message(
             "send_user_message",
             Value::Object(payload),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::send_user_message_core(
+    Ok(codex_core::send_user_message_core(
         &state.sessions,
         workspace_id,
         thread_id,
@@ -263,7 +423,7 @@ pub(crate) async fn send_user_message(
         images,
         collaboration_mode,
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
@@ -271,7 +431,7 @@ pub(crate) async fn collaboration_mode_list(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -279,10 +439,11 @@ pub(crate) async fn collaboration_mode_list(
             "collaboration_mode_list",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::collaboration_mode_list_core(&state.sessions, workspace_id).await
+    Ok(codex_core::collaboration_mode_list_core(&state.sessions, workspace_id).await?)
 }
 
 #[tauri::command]
@@ -292,7 +453,7 @@ pub(crate) async fn turn_interrupt(
     turn_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -300,10 +461,224 @@ pub(crate) async fn turn_interrupt(
             "turn_interrupt",
             json!({ "workspaceId": workspace_id, "threadId": thread_id, "turnId": turn_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::turn_interrupt_core(&state.sessions, workspace_id, thread_id, turn_id).await
+    Ok(codex_core::turn_interrupt_core(&state.sessions, workspace_id, thread_id, turn_id).await?)
+}
+
+/// Abandons a stuck app-server request, e.g. one listed by `pending_requests`.
+#[tauri::command]
+pub(crate) async fn request_cancel(
+    workspace_id: String,
+    request_id: u64,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<bool> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "request_cancel",
+            json!({ "workspaceId": workspace_id, "requestId": request_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_core::request_cancel_core(&state.sessions, workspace_id, request_id).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn pending_requests(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<PendingRequestInfo>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "pending_requests",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_core::pending_requests_core(&state.sessions, workspace_id).await?)
+}
+
+/// Queue depth of each background thread callback, e.g. commit message
+/// generation, for diagnosing slow consumers.
+#[tauri::command]
+pub(crate) async fn background_callbacks(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<BackgroundCallbackInfo>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "background_callbacks",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_core::background_callbacks_core(&state.sessions, workspace_id).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn turn_rollback(
+    workspace_id: String,
+    thread_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<TurnRollbackResponse> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "turn_rollback",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(turn_snapshot_core::turn_rollback_core(
+        &state.workspaces,
+        &state.services.audit_log,
+        workspace_id,
+        thread_id,
+    )
+    .await?)
+}
+
+/// Stored per-turn metrics for a workspace, newest first.
+#[tauri::command]
+pub(crate) async fn turn_metrics_list(
+    workspace_id: String,
+    thread_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<TurnMetrics>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "turn_metrics_list",
+            json!({ "workspaceId": workspace_id, "threadId": thread_id, "limit": limit }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(turn_metrics::read_turn_metrics(
+        &turn_metrics_path(&workspace_id),
+        thread_id.as_deref(),
+        limit,
+    ))
+}
+
+/// This month's usage against the workspace's `monthlyBudget`.
+#[tauri::command]
+pub(crate) async fn workspace_budget_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<BudgetStatus> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_budget_status",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(budget_core::workspace_budget_status_core(&state.workspaces, workspace_id).await?)
+}
+
+/// Allows turns past the workspace's budget for the rest of the month.
+#[tauri::command]
+pub(crate) async fn workspace_budget_override(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<BudgetStatus> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_budget_override",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(budget_core::workspace_budget_override_core(&state.workspaces, workspace_id).await?)
+}
+
+/// Per-day turn counts, tokens, cost and success rates for every workspace.
+#[tauri::command]
+pub(crate) async fn activity_timeline(
+    days: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<ActivityTimeline> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "activity_timeline", json!({ "days": days }))
+                .await
+                .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(activity_core::activity_timeline_core(&state.workspaces, &state.app_settings, days).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn patch_apply(
+    workspace_id: String,
+    item_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<PatchApplyResult> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "patch_apply",
+            json!({ "workspaceId": workspace_id, "itemId": item_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(patch_core::patch_apply_core(
+        &state.sessions,
+        &state.services.audit_log,
+        workspace_id,
+        item_id,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -314,7 +689,7 @@ pub(crate) async fn start_review(
     delivery: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -327,10 +702,14 @@ pub(crate) async fn start_review(
                 "delivery": delivery,
             }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::start_review_core(&state.sessions, workspace_id, thread_id, target, delivery).await
+    Ok(
+        codex_core::start_review_core(&state.sessions, workspace_id, thread_id, target, delivery)
+            .await?,
+    )
 }
 
 #[tauri::command]
@@ -338,7 +717,7 @@ pub(crate) async fn model_list(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -346,10 +725,11 @@ pub(crate) async fn model_list(
             "model_list",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::model_list_core(&state.sessions, workspace_id).await
+    Ok(codex_core::model_list_core(&state.sessions, workspace_id).await?)
 }
 
 #[tauri::command]
@@ -357,7 +737,7 @@ pub(crate) async fn account_rate_limits(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -365,10 +745,11 @@ pub(crate) async fn account_rate_limits(
             "account_rate_limits",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::account_rate_limits_core(&state.sessions, workspace_id).await
+    Ok(codex_core::account_rate_limits_core(&state.sessions, workspace_id).await?)
 }
 
 #[tauri::command]
@@ -376,7 +757,7 @@ pub(crate) async fn account_read(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -384,10 +765,11 @@ pub(crate) async fn account_read(
             "account_read",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::account_read_core(&state.sessions, &state.workspaces, workspace_id).await
+    Ok(codex_core::account_read_core(&state.sessions, &state.workspaces, workspace_id).await?)
 }
 
 #[tauri::command]
@@ -395,7 +777,7 @@ pub(crate) async fn codex_login(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -403,10 +785,14 @@ pub(crate) async fn codex_login(
             "codex_login",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::codex_login_core(&state.sessions, &state.codex_login_cancels, workspace_id).await
+    Ok(
+        codex_core::codex_login_core(&state.sessions, &state.codex_login_cancels, workspace_id)
+            .await?,
+    )
 }
 
 #[tauri::command]
@@ -414,7 +800,7 @@ pub(crate) async fn codex_login_cancel(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -422,11 +808,16 @@ pub(crate) async fn codex_login_cancel(
             "codex_login_cancel",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::codex_login_cancel_core(&state.sessions, &state.codex_login_cancels, workspace_id)
-        .await
+    Ok(codex_core::codex_login_cancel_core(
+        &state.sessions,
+        &state.codex_login_cancels,
+        workspace_id,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -434,7 +825,7 @@ pub(crate) async fn skills_list(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -442,10 +833,11 @@ pub(crate) async fn skills_list(
             "skills_list",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::skills_list_core(&state.sessions, workspace_id).await
+    Ok(codex_core::skills_list_core(&state.sessions, workspace_id).await?)
 }
 
 #[tauri::command]
@@ -455,7 +847,7 @@ pub(crate) async fn apps_list(
     limit: Option<u32>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -463,10 +855,11 @@ pub(crate) async fn apps_list(
             "apps_list",
             json!({ "workspaceId": workspace_id, "cursor": cursor, "limit": limit }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::apps_list_core(&state.sessions, workspace_id, cursor, limit).await
+    Ok(codex_core::apps_list_core(&state.sessions, workspace_id, cursor, limit).await?)
 }
 
 #[tauri::command]
@@ -476,20 +869,29 @@ pub(crate) async fn respond_to_server_request(
     result: Value,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(&*state).await {
         remote_backend::call_remote(
             &*state,
-            app,
+            app.clone(),
             "respond_to_server_request",
             json!({ "workspaceId": workspace_id, "requestId": request_id, "result": result }),
         )
-        .await?;
+        .await
+        .map_err(AppError::Remote)?;
+        tray::approval_answered(&app, &workspace_id, &request_id);
         return Ok(());
     }
 
-    codex_core::respond_to_server_request_core(&state.sessions, workspace_id, request_id, result)
-        .await
+    codex_core::respond_to_server_request_core(
+        &state.sessions,
+        workspace_id.clone(),
+        request_id.clone(),
+        result,
+    )
+    .await?;
+    tray::approval_answered(&app, &workspace_id, &request_id);
+    Ok(())
 }
 
 /// Gets the diff content for commit message generation
@@ -497,12 +899,14 @@ pub(crate) async fn respond_to_server_request(
 pub(crate) async fn get_commit_message_prompt(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> AppResult<String> {
     // Get the diff from git
     let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
 
     if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
+        return Err(AppError::InvalidInput(
+            "No changes to generate commit message for".to_string(),
+        ));
     }
 
     Ok(crate::shared::codex_aux_core::build_commit_message_prompt(
@@ -515,8 +919,8 @@ pub(crate) async fn remember_approval_rule(
     workspace_id: String,
     command: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<Value, String> {
-    codex_core::remember_approval_rule_core(&state.workspaces, workspace_id, command).await
+) -> AppResult<Value> {
+    Ok(codex_core::remember_approval_rule_core(&state.workspaces, workspace_id, command).await?)
 }
 
 #[tauri::command]
@@ -524,7 +928,7 @@ pub(crate) async fn get_config_model(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -532,10 +936,11 @@ pub(crate) async fn get_config_model(
             "get_config_model",
             json!({ "workspaceId": workspace_id }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    codex_core::get_config_model_core(&state.workspaces, workspace_id).await
+    Ok(codex_core::get_config_model_core(&state.workspaces, workspace_id).await?)
 }
 
 /// Generates a commit message in the background without showing in the main chat
@@ -544,11 +949,13 @@ pub(crate) async fn generate_commit_message(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<String, String> {
+) -> AppResult<String> {
     let diff = crate::git::get_workspace_diff(&workspace_id, &state).await?;
 
     if diff.trim().is_empty() {
-        return Err("No changes to generate commit message for".to_string());
+        return Err(AppError::InvalidInput(
+            "No changes to generate commit message for".to_string(),
+        ));
     }
 
     let prompt = crate::shared::codex_aux_core::build_commit_message_prompt(&diff);
@@ -559,16 +966,7 @@ pub(crate) async fn generate_commit_message(
         |workspace_id, thread_id| {
             let _ = app.emit(
                 "app-server-event",
-                AppServerEvent {
-                    workspace_id: workspace_id.to_string(),
-                    message: json!({
-                        "method": "codex/backgroundThread",
-                        "params": {
-                            "threadId": thread_id,
-                            "action": "hide"
-                        }
-                    }),
-                },
+                AppServerEvent::new(workspace_id, AppEvent::background_thread_hidden(thread_id)),
             );
         },
         "Timeout waiting for commit message generation",
@@ -578,7 +976,7 @@ pub(crate) async fn generate_commit_message(
 
     let trimmed = response.trim().to_string();
     if trimmed.is_empty() {
-        return Err("No commit message was generated".to_string());
+        return Err("No commit message was generated".to_string().into());
     }
 
     Ok(trimmed)
@@ -590,7 +988,7 @@ pub(crate) async fn generate_run_metadata(
     prompt: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Value, String> {
+) -> AppResult<Value> {
     if remote_backend::is_remote_mode(&*state).await {
         return remote_backend::call_remote(
             &*state,
@@ -598,62 +996,78 @@ pub(crate) async fn generate_run_metadata(
             "generate_run_metadata",
             json!({ "workspaceId": workspace_id, "prompt": prompt }),
         )
-        .await;
+        .await
+        .map_err(AppError::Remote);
     }
 
-    let cleaned_prompt = prompt.trim();
-    if cleaned_prompt.is_empty() {
-        return Err("Prompt is required.".to_string());
+    Ok(crate::shared::codex_aux_core::generate_run_metadata_core(
+        &state.sessions,
+        workspace_id,
+        &prompt,
+        |workspace_id, thread_id| {
+            let _ = app.emit(
+                "app-server-event",
+                AppServerEvent::new(workspace_id, AppEvent::background_thread_hidden(thread_id)),
+            );
+        },
+    )
+    .await?)
+}
+
+/// Runs a prompt as a hidden turn for automation ("agent calls agent");
+/// poll `background_turn_result` with the returned id.
+#[tauri::command]
+pub(crate) async fn background_turn_start(
+    workspace_id: String,
+    prompt: String,
+    cli_type: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "background_turn_start",
+            json!({ "workspaceId": workspace_id, "prompt": prompt, "cliType": cli_type }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    let title_prompt = crate::shared::codex_aux_core::build_run_metadata_prompt(cleaned_prompt);
-    let response_text = crate::shared::codex_aux_core::run_background_prompt_core(
+    background_turn_core::background_turn_start_core(
         &state.sessions,
         workspace_id,
-        title_prompt,
-        |workspace_id, thread_id| {
+        prompt,
+        cli_type,
+        move |workspace_id, thread_id| {
             let _ = app.emit(
                 "app-server-event",
-                AppServerEvent {
-                    workspace_id: workspace_id.to_string(),
-                    message: json!({
-                        "method": "codex/backgroundThread",
-                        "params": {
-                            "threadId": thread_id,
-                            "action": "hide"
-                        }
-                    }),
-                },
+                AppServerEvent::new(workspace_id, AppEvent::background_thread_hidden(thread_id)),
             );
         },
-        "Timeout waiting for metadata generation",
-        "Unknown error during metadata generation",
     )
-    .await?;
+    .await
+}
 
-    let trimmed = response_text.trim();
-    if trimmed.is_empty() {
-        return Err("No metadata was generated".to_string());
-    }
-
-    let json_value = crate::shared::codex_aux_core::extract_json_value(trimmed)
-        .ok_or_else(|| "Failed to parse metadata JSON".to_string())?;
-    let title = json_value
-        .get("title")
-        .and_then(|v| v.as_str())
-        .map(|v| v.trim().to_string())
-        .filter(|v| !v.is_empty())
-        .ok_or_else(|| "Missing title in metadata".to_string())?;
-    let worktree_name = json_value
-        .get("worktreeName")
-        .or_else(|| json_value.get("worktree_name"))
-        .and_then(|v| v.as_str())
-        .map(crate::shared::codex_aux_core::sanitize_run_worktree_name)
-        .filter(|v| !v.is_empty())
-        .ok_or_else(|| "Missing worktree name in metadata".to_string())?;
-
-    Ok(json!({
-        "title": title,
-        "worktreeName": worktree_name
-    }))
+#[tauri::command]
+pub(crate) async fn background_turn_result(
+    turn_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<BackgroundTurnResult> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "background_turn_result",
+            json!({ "turnId": turn_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    background_turn_core::background_turn_result_core(&turn_id)
 }
@@ -0,0 +1,32 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::shared::event_replay_core::EventReplay;
+use crate::state::AppState;
+
+/// Returns buffered `app-server-event`s for a workspace with `seq` greater
+/// than `since_seq`, so a reloaded window can catch up.
+#[tauri::command]
+pub(crate) async fn events_replay(
+    workspace_id: String,
+    since_seq: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<EventReplay, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "events_replay",
+            json!({ "workspaceId": workspace_id, "sinceSeq": since_seq }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(state
+        .services
+        .event_replay
+        .since(&workspace_id, since_seq.unwrap_or(0)))
+}
@@ -1,6 +1,11 @@
-use tauri::{AppHandle, Emitter};
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager};
 
 use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use crate::shared::event_schema_core;
+use crate::shared::runtime_services::RuntimeServices;
+use crate::state::AppState;
 
 #[derive(Clone)]
 pub(crate) struct TauriEventSink {
@@ -13,9 +18,28 @@ impl TauriEventSink {
     }
 }
 
+fn deliver_app_server_event(app: &AppHandle, services: &RuntimeServices, event: AppServerEvent) {
+    crate::tray::observe_event(app, &event);
+    let event = services.event_replay.record(event);
+    services.event_hooks.dispatch(&event);
+    crate::http_api::publish(&event);
+    let _ = app.emit("app-server-event", event);
+}
+
 impl EventSink for TauriEventSink {
-    fn emit_app_server_event(&self, event: AppServerEvent) {
-        let _ = self.app.emit("app-server-event", event);
+    fn emit_app_server_event(&self, mut event: AppServerEvent) {
+        event_schema_core::normalize_event(&mut event.message);
+        let Some(state) = self.app.try_state::<AppState>() else {
+            let _ = self.app.emit("app-server-event", event);
+            return;
+        };
+        let services = state.services.clone();
+        let app = self.app.clone();
+        let batcher = Arc::clone(&services.delta_batcher);
+        batcher.push(
+            event,
+            Arc::new(move |event| deliver_app_server_event(&app, &services, event)),
+        );
     }
 
     fn emit_terminal_output(&self, event: TerminalOutput) {
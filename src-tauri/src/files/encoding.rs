@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16_LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16_BE_BOM: &[u8] = &[0xFE, 0xFF];
+/// Bytes searched for a NUL when deciding whether a file is binary, as git
+/// does.
+const BINARY_SNIFF_BYTES: usize = 8_000;
+
+/// How a text file was stored on disk. Writes keep the encoding of the file
+/// they replace.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum TextEncoding {
+    #[default]
+    #[serde(rename = "utf-8")]
+    Utf8,
+    #[serde(rename = "utf-8-bom")]
+    Utf8Bom,
+    #[serde(rename = "utf-16le")]
+    Utf16Le,
+    #[serde(rename = "utf-16be")]
+    Utf16Be,
+    /// Bytes that aren't valid UTF-8, read as ISO-8859-1.
+    #[serde(rename = "latin-1")]
+    Latin1,
+}
+
+impl TextEncoding {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Utf8 | Self::Utf8Bom => "UTF-8",
+            Self::Utf16Le | Self::Utf16Be => "UTF-16",
+            Self::Latin1 => "Latin-1",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TextDecodeError {
+    Binary,
+    Invalid(TextEncoding),
+}
+
+impl TextDecodeError {
+    /// The error returned to the UI. A binary file always reads
+    /// `<file> is a binary file`.
+    pub(crate) fn message(self, file_context: &str) -> String {
+        match self {
+            Self::Binary => format!("{file_context} is a binary file"),
+            Self::Invalid(encoding) => {
+                format!("{file_context} is not valid {}", encoding.label())
+            }
+        }
+    }
+}
+
+fn decode_utf16(
+    bytes: &[u8],
+    encoding: TextEncoding,
+    truncated: bool,
+) -> Result<String, TextDecodeError> {
+    let units = bytes.chunks_exact(2).map(|pair| match encoding {
+        TextEncoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+        _ => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut decoded = char::decode_utf16(units).peekable();
+    while let Some(unit) = decoded.next() {
+        match unit {
+            Ok(ch) => text.push(ch),
+            // A surrogate pair split by a truncated read.
+            Err(_) if truncated && decoded.peek().is_none() => break,
+            Err(_) => return Err(TextDecodeError::Invalid(encoding)),
+        }
+    }
+    if bytes.len() % 2 == 1 && !truncated {
+        return Err(TextDecodeError::Invalid(encoding));
+    }
+    Ok(text)
+}
+
+/// Decodes file bytes, picking the encoding from a BOM, then UTF-8, then
+/// Latin-1. Files with a NUL byte near the start and no UTF-16 BOM are
+/// binary. `truncated` allows a character cut off at the end.
+pub(crate) fn decode_text(
+    bytes: &[u8],
+    truncated: bool,
+) -> Result<(String, TextEncoding), TextDecodeError> {
+    if let Some(rest) = bytes.strip_prefix(UTF16_LE_BOM) {
+        let encoding = TextEncoding::Utf16Le;
+        return decode_utf16(rest, encoding, truncated).map(|text| (text, encoding));
+    }
+    if let Some(rest) = bytes.strip_prefix(UTF16_BE_BOM) {
+        let encoding = TextEncoding::Utf16Be;
+        return decode_utf16(rest, encoding, truncated).map(|text| (text, encoding));
+    }
+    let (rest, utf8_encoding) = match bytes.strip_prefix(UTF8_BOM) {
+        Some(rest) => (rest, TextEncoding::Utf8Bom),
+        None => (bytes, TextEncoding::Utf8),
+    };
+    if rest[..rest.len().min(BINARY_SNIFF_BYTES)].contains(&0) {
+        return Err(TextDecodeError::Binary);
+    }
+    match std::str::from_utf8(rest) {
+        Ok(text) => Ok((text.to_string(), utf8_encoding)),
+        // A character split by a truncated read.
+        Err(err) if truncated && err.error_len().is_none() => Ok((
+            String::from_utf8_lossy(&rest[..err.valid_up_to()]).into_owned(),
+            utf8_encoding,
+        )),
+        Err(_) if utf8_encoding == TextEncoding::Utf8Bom => {
+            Err(TextDecodeError::Invalid(utf8_encoding))
+        }
+        Err(_) => Ok((
+            rest.iter().map(|byte| char::from(*byte)).collect(),
+            TextEncoding::Latin1,
+        )),
+    }
+}
+
+/// Encodes `content` for a file stored as `encoding`. Content that Latin-1
+/// can't hold is written as UTF-8 instead.
+pub(crate) fn encode_text(content: &str, encoding: TextEncoding) -> Vec<u8> {
+    match encoding {
+        TextEncoding::Utf8 => content.as_bytes().to_vec(),
+        TextEncoding::Utf8Bom => [UTF8_BOM, content.as_bytes()].concat(),
+        TextEncoding::Utf16Le => UTF16_LE_BOM
+            .iter()
+            .copied()
+            .chain(content.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+        TextEncoding::Utf16Be => UTF16_BE_BOM
+            .iter()
+            .copied()
+            .chain(content.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect(),
+        TextEncoding::Latin1 => content
+            .chars()
+            .map(|ch| u8::try_from(u32::from(ch)).ok())
+            .collect::<Option<Vec<_>>>()
+            .unwrap_or_else(|| content.as_bytes().to_vec()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_text, encode_text, TextDecodeError, TextEncoding};
+
+    #[test]
+    fn detects_bom_utf16_latin1_and_binary_files() {
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16Le,
+            TextEncoding::Utf16Be,
+        ] {
+            let bytes = encode_text("model = \"ü\"\r\n", encoding);
+            assert_eq!(
+                decode_text(&bytes, false),
+                Ok(("model = \"ü\"\r\n".to_string(), encoding))
+            );
+        }
+
+        let latin1 = encode_text("café", TextEncoding::Latin1);
+        assert_eq!(latin1, b"caf\xE9");
+        assert_eq!(
+            decode_text(&latin1, false),
+            Ok(("café".to_string(), TextEncoding::Latin1))
+        );
+        assert_eq!(encode_text("€", TextEncoding::Latin1), "€".as_bytes());
+
+        assert_eq!(
+            decode_text(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR", false),
+            Err(TextDecodeError::Binary)
+        );
+        assert_eq!(
+            TextDecodeError::Binary.message("logo.png"),
+            "logo.png is a binary file"
+        );
+
+        let cut = &"é".as_bytes()[..1];
+        assert_eq!(
+            decode_text(cut, true),
+            Ok((String::new(), TextEncoding::Utf8))
+        );
+        let utf16 = encode_text("a😀", TextEncoding::Utf16Le);
+        assert_eq!(
+            decode_text(&utf16[..utf16.len() - 1], true),
+            Ok(("a".to_string(), TextEncoding::Utf16Le))
+        );
+    }
+}
@@ -1,14 +1,33 @@
 use std::fs::File;
-use std::io::Read;
-use std::path::{Path, PathBuf};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
+use crate::files::encoding::{decode_text, encode_text, TextEncoding};
+use crate::shared::atomic_write::write_atomic;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub(crate) struct TextFileResponse {
     pub exists: bool,
     pub content: String,
     pub truncated: bool,
+    #[serde(default)]
+    pub encoding: TextEncoding,
+}
+
+/// A slice of a text file, read by byte offset.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TextRangeResponse {
+    pub exists: bool,
+    pub content: String,
+    /// Byte offset `content` starts at.
+    pub offset: u64,
+    /// Byte offset just past `content`, where the next page starts.
+    pub next_offset: u64,
+    pub file_size: u64,
+    pub encoding: TextEncoding,
 }
 
 fn missing_response() -> TextFileResponse {
@@ -16,6 +35,7 @@ fn missing_response() -> TextFileResponse {
         exists: false,
         content: String::new(),
         truncated: false,
+        encoding: TextEncoding::default(),
     }
 }
 
@@ -56,13 +76,81 @@ pub(crate) fn read_text_file_within(
     file_context: &str,
     allow_external_symlink_target: bool,
 ) -> Result<TextFileResponse, String> {
-    let Some(canonical_root) = resolve_root(root, root_context, root_may_be_missing)? else {
+    read_text_file_within_limit(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+        None,
+    )
+}
+
+/// Like [`read_text_file_within`], but reads at most `max_bytes` and reports
+/// `truncated` when the file is longer. The content is cut at a character
+/// boundary.
+pub(crate) fn read_text_file_within_limit(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+    max_bytes: Option<u64>,
+) -> Result<TextFileResponse, String> {
+    let Some(canonical_path) = resolve_existing_file_within(
+        root,
+        filename,
+        root_may_be_missing,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
         return Ok(missing_response());
     };
 
+    let file =
+        File::open(&canonical_path).map_err(|err| format!("Failed to open {file_context}: {err}"))?;
+    let mut buffer = Vec::new();
+    match max_bytes {
+        Some(max_bytes) => file.take(max_bytes + 1).read_to_end(&mut buffer),
+        None => (&file).read_to_end(&mut buffer),
+    }
+    .map_err(|err| format!("Failed to read {file_context}: {err}"))?;
+    let truncated = max_bytes.is_some_and(|max_bytes| buffer.len() as u64 > max_bytes);
+    if let Some(max_bytes) = max_bytes.filter(|_| truncated) {
+        buffer.truncate(max_bytes as usize);
+    }
+    let (content, encoding) =
+        decode_text(&buffer, truncated).map_err(|err| err.message(file_context))?;
+
+    Ok(TextFileResponse {
+        exists: true,
+        content,
+        truncated,
+        encoding,
+    })
+}
+
+/// The canonical path of `filename` under `root`, or `None` when the root
+/// (if allowed to be missing) or the file doesn't exist.
+fn resolve_existing_file_within(
+    root: &Path,
+    filename: &str,
+    root_may_be_missing: bool,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+) -> Result<Option<PathBuf>, String> {
+    let Some(canonical_root) = resolve_root(root, root_context, root_may_be_missing)? else {
+        return Ok(None);
+    };
+
     let candidate = canonical_root.join(filename);
     if !candidate.exists() {
-        return Ok(missing_response());
+        return Ok(None);
     }
 
     let candidate_is_symlink = std::fs::symlink_metadata(&candidate)
@@ -77,22 +165,158 @@ pub(crate) fn read_text_file_within(
     {
         return Err(format!("Invalid {file_context} path"));
     }
+    Ok(Some(canonical_path))
+}
 
-    let mut file =
-        File::open(&canonical_path).map_err(|err| format!("Failed to open {file_context}: {err}"))?;
+/// Bytes at the start of `rest` that finish a UTF-8 character begun in
+/// `before`, the bytes just ahead of a range.
+fn split_char_tail(before: &[u8], rest: &[u8]) -> usize {
+    let Some((index, lead)) = before
+        .iter()
+        .enumerate()
+        .rev()
+        .find(|(_, byte)| *byte & 0xC0 != 0x80)
+    else {
+        return 0;
+    };
+    let char_len = match lead {
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return 0,
+    };
+    let missing = char_len - (before.len() - index).min(char_len);
+    if rest.len() >= missing && rest[..missing].iter().all(|byte| byte & 0xC0 == 0x80) {
+        missing
+    } else {
+        0
+    }
+}
+
+/// Reads up to `length` bytes of `filename` under `root`, which must exist,
+/// starting at `offset`, or ending at the end of the file when `offset` is
+/// `None`. The range is moved forward to whole characters, so `offset` and
+/// `next_offset` in the response are where the content really starts and
+/// ends.
+pub(crate) fn read_text_range_within(
+    root: &Path,
+    filename: &str,
+    root_context: &str,
+    file_context: &str,
+    allow_external_symlink_target: bool,
+    offset: Option<u64>,
+    length: u64,
+) -> Result<TextRangeResponse, String> {
+    let Some(canonical_path) = resolve_existing_file_within(
+        root,
+        filename,
+        false,
+        root_context,
+        file_context,
+        allow_external_symlink_target,
+    )?
+    else {
+        return Ok(TextRangeResponse::default());
+    };
+
+    let mut file = File::open(&canonical_path)
+        .map_err(|err| format!("Failed to open {file_context}: {err}"))?;
+    let read_error = |err: std::io::Error| format!("Failed to read {file_context}: {err}");
+    let file_size = file.metadata().map_err(read_error)?.len();
+    let mut bom = Vec::with_capacity(2);
+    (&mut file)
+        .take(2)
+        .read_to_end(&mut bom)
+        .map_err(read_error)?;
+    let utf16_bom = Some(bom).filter(|bom| *bom == [0xFF, 0xFE] || *bom == [0xFE, 0xFF]);
+
+    let mut start = offset
+        .unwrap_or_else(|| file_size.saturating_sub(length))
+        .min(file_size);
+    if let Some(bom) = &utf16_bom {
+        // Whole code units after the mark; the mark itself is never content.
+        start = start.max(bom.len() as u64);
+        start += start % 2;
+    }
+    let lookbehind = if utf16_bom.is_none() { start.min(3) } else { 0 };
+    file.seek(SeekFrom::Start(start - lookbehind))
+        .map_err(read_error)?;
     let mut buffer = Vec::new();
-    file.read_to_end(&mut buffer)
-        .map_err(|err| format!("Failed to read {file_context}: {err}"))?;
-    let content = String::from_utf8(buffer)
-        .map_err(|_| format!("{file_context} is not valid UTF-8"))?;
+    file.take(lookbehind + length)
+        .read_to_end(&mut buffer)
+        .map_err(read_error)?;
+    let (before, rest) = buffer.split_at(lookbehind as usize);
+    let skip = split_char_tail(before, rest);
+    start += skip as u64;
+    let rest = &rest[skip..];
+    let truncated = start + (rest.len() as u64) < file_size;
+
+    let (content, encoding, read) = match &utf16_bom {
+        Some(bom) => {
+            let with_bom = [bom.as_slice(), rest].concat();
+            let (content, encoding) =
+                decode_text(&with_bom, truncated).map_err(|err| err.message(file_context))?;
+            let read = encode_text(&content, encoding).len() - bom.len();
+            (content, encoding, read)
+        }
+        None => {
+            let (content, encoding) =
+                decode_text(rest, truncated).map_err(|err| err.message(file_context))?;
+            let read = encode_text(&content, encoding).len();
+            (content, encoding, read)
+        }
+    };
 
-    Ok(TextFileResponse {
+    Ok(TextRangeResponse {
         exists: true,
         content,
-        truncated: false,
+        offset: start,
+        next_offset: start + read as u64,
+        file_size,
+        encoding,
     })
 }
 
+/// Creates the missing directories above `candidate`. The deepest existing
+/// one must resolve inside `canonical_root` and the missing ones must be
+/// plain names, so a `..` or symlinked path can't create directories
+/// outside the root.
+pub(crate) fn ensure_parent_within_root(
+    candidate: &Path,
+    canonical_root: &Path,
+    file_context: &str,
+) -> Result<(), String> {
+    let Some(parent) = candidate.parent() else {
+        return Ok(());
+    };
+    if parent == canonical_root {
+        return Ok(());
+    }
+    let mut existing = parent;
+    while !existing.exists() {
+        if !matches!(
+            existing.components().next_back(),
+            Some(Component::Normal(_))
+        ) {
+            return Err(format!("Invalid {file_context} path"));
+        }
+        existing = existing
+            .parent()
+            .ok_or_else(|| format!("Invalid {file_context} path"))?;
+    }
+    let canonical_existing = existing
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve {file_context} directory: {err}"))?;
+    if !canonical_existing.starts_with(canonical_root) {
+        return Err(format!("Invalid {file_context} path"));
+    }
+    if existing != parent {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("Failed to create {file_context} directory: {err}"))?;
+    }
+    Ok(())
+}
+
 pub(crate) fn write_text_file_within(
     root: &Path,
     filename: &str,
@@ -129,10 +353,17 @@ pub(crate) fn write_text_file_within(
         }
         canonical_path
     } else {
+        ensure_parent_within_root(&candidate, &canonical_root, file_context)?;
         candidate
     };
 
-    std::fs::write(&target_path, content)
+    // Keep the encoding of the file being replaced, e.g. UTF-16 from Windows
+    // tools.
+    let encoding = std::fs::read(&target_path)
+        .ok()
+        .and_then(|existing| decode_text(&existing, false).ok())
+        .map_or(TextEncoding::Utf8, |(_, encoding)| encoding);
+    write_atomic(&target_path, &encode_text(content, encoding))
         .map_err(|err| format!("Failed to write {file_context}: {err}"))
 }
 
@@ -174,6 +405,104 @@ mod tests {
         assert_eq!(response.content, "hello");
     }
 
+    #[test]
+    fn write_creates_nested_parent_directories() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        write_text_file_within(
+            &root,
+            ".codex/instructions.md",
+            "nested",
+            false,
+            "workspace root",
+            ".codex/instructions.md",
+            false,
+        )
+        .expect("write should succeed");
+        let response = read_text_file_within(
+            &root,
+            ".codex/instructions.md",
+            false,
+            "workspace root",
+            ".codex/instructions.md",
+            false,
+        )
+        .expect("read should succeed");
+        assert!(response.exists);
+        assert_eq!(response.content, "nested");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_rejects_symlinked_parent_escape() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir();
+        let outside = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside");
+        symlink(&outside, root.join(".codex")).expect("create dir symlink");
+
+        let error = write_text_file_within(
+            &root,
+            ".codex/instructions.md",
+            "escaped",
+            false,
+            "workspace root",
+            ".codex/instructions.md",
+            false,
+        )
+        .expect_err("should reject symlinked parent");
+        assert!(error.contains("Invalid .codex/instructions.md path"));
+        assert!(!outside.join("instructions.md").exists());
+    }
+
+    #[test]
+    fn write_rejects_parent_escape_without_creating_directories() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        let escaped = format!("escaped-{}", Uuid::new_v4());
+
+        let error = write_text_file_within(
+            &root,
+            &format!("new/../../{escaped}/notes.md"),
+            "escaped",
+            false,
+            "workspace root",
+            "notes.md",
+            false,
+        )
+        .expect_err("should reject parent escape");
+        assert!(error.contains("Invalid notes.md path"));
+        assert!(!root.join("new").exists());
+        assert!(!root.parent().expect("root parent").join(&escaped).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn write_rejects_symlinked_ancestor_without_creating_directories() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir();
+        let outside = temp_dir();
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::create_dir_all(&outside).expect("create outside");
+        symlink(&outside, root.join(".codex")).expect("create dir symlink");
+
+        let error = write_text_file_within(
+            &root,
+            ".codex/prompts/review.md",
+            "escaped",
+            false,
+            "workspace root",
+            "review.md",
+            false,
+        )
+        .expect_err("should reject symlinked ancestor");
+        assert!(error.contains("Invalid review.md path"));
+        assert!(!outside.join("prompts").exists());
+    }
+
     #[cfg(unix)]
     #[test]
     fn write_rejects_symlink_escape() {
@@ -2,18 +2,33 @@ use serde_json::json;
 use tauri::{AppHandle, State};
 
 use crate::remote_backend;
+use crate::shared::app_error::{AppError, AppResult};
 use crate::shared::agent_profiles_core::{
-    apply_agent_profile_core, list_agent_profiles_core, AgentProfileApplyMode,
-    AgentProfileApplyResponse, AgentProfileListResponse,
+    agent_profile_save_core, agent_profile_status_core, agent_profile_sync_core, apply_agent_profile_core,
+    list_agent_profiles_core, AgentProfileApplyMode, AgentProfileApplyResponse,
+    AgentProfileListResponse, AgentProfileStatus, AgentProfileSyncAction,
+};
+use crate::shared::agents_md_core::{
+    agents_md_section_update_core, agents_md_sections_list_core, AgentsMdSectionsResponse,
+};
+use crate::event_sink::TauriEventSink;
+use crate::shared::file_tail_core::{
+    file_tail_subscribe_core, file_tail_unsubscribe_core, FileTailSubscription,
+};
+use crate::shared::files_core::{
+    file_read_core, file_read_range_core, file_write_core, workspace_file_read_core, workspace_file_write_core,
+    workspace_tree_core,
 };
-use crate::shared::files_core::{file_read_core, file_write_core};
 use crate::state::AppState;
-use self::io::TextFileResponse;
+use self::io::{TextFileResponse, TextRangeResponse};
 use self::policy::{FileKind, FileScope};
+use self::tree::WorkspaceTreeResponse;
 
+pub(crate) mod encoding;
 pub(crate) mod io;
 pub(crate) mod ops;
 pub(crate) mod policy;
+pub(crate) mod tree;
 
 async fn file_read_impl(
     scope: FileScope,
@@ -21,7 +36,7 @@ async fn file_read_impl(
     workspace_id: Option<String>,
     state: &AppState,
     app: &AppHandle,
-) -> Result<TextFileResponse, String> {
+) -> AppResult<TextFileResponse> {
     if remote_backend::is_remote_mode(state).await {
         let response = remote_backend::call_remote(
             state,
@@ -29,11 +44,12 @@ async fn file_read_impl(
             "file_read",
             json!({ "scope": scope, "kind": kind, "workspaceId": workspace_id }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    file_read_core(&state.workspaces, scope, kind, workspace_id).await
+    Ok(file_read_core(&state.workspaces, scope, kind, workspace_id).await?)
 }
 
 async fn file_write_impl(
@@ -43,9 +59,9 @@ async fn file_write_impl(
     content: String,
     state: &AppState,
     app: &AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(state).await {
-        remote_backend::call_remote(
+        remote_backend::call_remote_or_queue(
             state,
             app.clone(),
             "file_write",
@@ -56,18 +72,225 @@ async fn file_write_impl(
                 "content": content,
             }),
         )
-        .await?;
+        .await
+        .map_err(AppError::Remote)?;
+        return Ok(());
+    }
+
+    Ok(file_write_core(
+        &state.workspaces,
+        &state.services.audit_log,
+        scope,
+        kind,
+        workspace_id,
+        content,
+    )
+    .await?)
+}
+
+async fn workspace_file_read_impl(
+    workspace_id: String,
+    path: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<TextFileResponse> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "workspace_file_read",
+            json!({ "workspaceId": workspace_id, "path": path }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_file_read_core(&state.workspaces, workspace_id, path).await?)
+}
+
+async fn file_read_range_impl(
+    workspace_id: String,
+    path: String,
+    offset: Option<u64>,
+    length: u64,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<TextRangeResponse> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_read_range",
+            json!({
+                "workspaceId": workspace_id,
+                "path": path,
+                "offset": offset,
+                "length": length,
+            }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(file_read_range_core(&state.workspaces, workspace_id, path, offset, length).await?)
+}
+
+async fn file_tail_subscribe_impl(
+    workspace_id: String,
+    path: String,
+    offset: Option<u64>,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<FileTailSubscription> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_tail_subscribe",
+            json!({ "workspaceId": workspace_id, "path": path, "offset": offset }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(file_tail_subscribe_core(
+        &state.workspaces,
+        TauriEventSink::new(app.clone()),
+        workspace_id,
+        path,
+        offset,
+    )
+    .await?)
+}
+
+async fn file_tail_unsubscribe_impl(
+    subscription_id: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<bool> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "file_tail_unsubscribe",
+            json!({ "subscriptionId": subscription_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(file_tail_unsubscribe_core(&subscription_id))
+}
+
+async fn workspace_file_write_impl(
+    workspace_id: String,
+    path: String,
+    content: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<()> {
+    if remote_backend::is_remote_mode(state).await {
+        remote_backend::call_remote_or_queue(
+            state,
+            app.clone(),
+            "workspace_file_write",
+            json!({ "workspaceId": workspace_id, "path": path, "content": content }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
         return Ok(());
     }
 
-    file_write_core(&state.workspaces, scope, kind, workspace_id, content).await
+    Ok(workspace_file_write_core(
+        &state.workspaces,
+        &state.services.audit_log,
+        workspace_id,
+        path,
+        content,
+    )
+    .await?)
+}
+
+async fn workspace_tree_impl(
+    workspace_id: String,
+    path: Option<String>,
+    depth: Option<usize>,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<WorkspaceTreeResponse> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "workspace_tree",
+            json!({ "workspaceId": workspace_id, "path": path, "depth": depth }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_tree_core(&state.workspaces, workspace_id, path, depth).await?)
+}
+
+async fn agents_md_sections_list_impl(
+    workspace_id: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<AgentsMdSectionsResponse> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "agents_md_sections_list",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(agents_md_sections_list_core(&state.workspaces, workspace_id).await?)
+}
+
+async fn agents_md_section_update_impl(
+    workspace_id: String,
+    heading: String,
+    content: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<AgentsMdSectionsResponse> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "agents_md_section_update",
+            json!({ "workspaceId": workspace_id, "heading": heading, "content": content }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(agents_md_section_update_core(
+        &state.workspaces,
+        &state.services.audit_log,
+        workspace_id,
+        heading,
+        content,
+    )
+    .await?)
 }
 
 async fn agent_profiles_list_impl(
     workspace_id: String,
     state: &AppState,
     app: &AppHandle,
-) -> Result<AgentProfileListResponse, String> {
+) -> AppResult<AgentProfileListResponse> {
     if remote_backend::is_remote_mode(state).await {
         let response = remote_backend::call_remote(
             state,
@@ -75,15 +298,16 @@ async fn agent_profiles_list_impl(
             "agent_profiles_list",
             json!({ "workspaceId": workspace_id }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
     let cli_type = {
         let settings = state.app_settings.lock().await;
         settings.cli_type.clone()
     };
-    list_agent_profiles_core(&state.workspaces, workspace_id, &cli_type).await
+    Ok(list_agent_profiles_core(&state.workspaces, workspace_id, &cli_type).await?)
 }
 
 async fn agent_profile_apply_impl(
@@ -92,7 +316,7 @@ async fn agent_profile_apply_impl(
     mode: Option<AgentProfileApplyMode>,
     state: &AppState,
     app: &AppHandle,
-) -> Result<AgentProfileApplyResponse, String> {
+) -> AppResult<AgentProfileApplyResponse> {
     if remote_backend::is_remote_mode(state).await {
         let response = remote_backend::call_remote(
             state,
@@ -104,22 +328,112 @@ async fn agent_profile_apply_impl(
                 "mode": mode.unwrap_or(AgentProfileApplyMode::Auto),
             }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
     let cli_type = {
         let settings = state.app_settings.lock().await;
         settings.cli_type.clone()
     };
-    apply_agent_profile_core(
+    Ok(apply_agent_profile_core(
         &state.workspaces,
+        &state.services.audit_log,
         workspace_id,
         profile,
         &cli_type,
         mode.unwrap_or(AgentProfileApplyMode::Auto),
     )
-    .await
+    .await?)
+}
+
+async fn agent_profile_status_impl(
+    workspace_id: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<AgentProfileStatus> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "agent_profile_status",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let cli_type = {
+        let settings = state.app_settings.lock().await;
+        settings.cli_type.clone()
+    };
+    Ok(agent_profile_status_core(&state.workspaces, workspace_id, &cli_type).await?)
+}
+
+async fn agent_profile_sync_impl(
+    workspace_id: String,
+    action: AgentProfileSyncAction,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<AgentProfileStatus> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "agent_profile_sync",
+            json!({ "workspaceId": workspace_id, "action": action }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let cli_type = {
+        let settings = state.app_settings.lock().await;
+        settings.cli_type.clone()
+    };
+    Ok(agent_profile_sync_core(
+        &state.workspaces,
+        &state.services.audit_log,
+        workspace_id,
+        &cli_type,
+        action,
+    )
+    .await?)
+}
+
+async fn agent_profile_save_impl(
+    workspace_id: String,
+    profile: String,
+    state: &AppState,
+    app: &AppHandle,
+) -> AppResult<AgentProfileStatus> {
+    if remote_backend::is_remote_mode(state).await {
+        let response = remote_backend::call_remote(
+            state,
+            app.clone(),
+            "agent_profile_save",
+            json!({ "workspaceId": workspace_id, "profile": profile }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let cli_type = {
+        let settings = state.app_settings.lock().await;
+        settings.cli_type.clone()
+    };
+    Ok(agent_profile_save_core(
+        &state.workspaces,
+        &state.services.audit_log,
+        workspace_id,
+        profile,
+        &cli_type,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -129,7 +443,7 @@ pub(crate) async fn file_read(
     workspace_id: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<TextFileResponse, String> {
+) -> AppResult<TextFileResponse> {
     file_read_impl(scope, kind, workspace_id, &*state, &app).await
 }
 
@@ -141,16 +455,100 @@ pub(crate) async fn file_write(
     content: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     file_write_impl(scope, kind, workspace_id, content, &*state, &app).await
 }
 
+#[tauri::command]
+pub(crate) async fn workspace_file_read(
+    workspace_id: String,
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<TextFileResponse> {
+    workspace_file_read_impl(workspace_id, path, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn file_read_range(
+    workspace_id: String,
+    path: String,
+    offset: Option<u64>,
+    length: u64,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<TextRangeResponse> {
+    file_read_range_impl(workspace_id, path, offset, length, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn file_tail_subscribe(
+    workspace_id: String,
+    path: String,
+    offset: Option<u64>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<FileTailSubscription> {
+    file_tail_subscribe_impl(workspace_id, path, offset, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn file_tail_unsubscribe(
+    subscription_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<bool> {
+    file_tail_unsubscribe_impl(subscription_id, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_file_write(
+    workspace_id: String,
+    path: String,
+    content: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<()> {
+    workspace_file_write_impl(workspace_id, path, content, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_tree(
+    workspace_id: String,
+    path: Option<String>,
+    depth: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<WorkspaceTreeResponse> {
+    workspace_tree_impl(workspace_id, path, depth, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn agents_md_sections_list(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AgentsMdSectionsResponse> {
+    agents_md_sections_list_impl(workspace_id, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn agents_md_section_update(
+    workspace_id: String,
+    heading: String,
+    content: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AgentsMdSectionsResponse> {
+    agents_md_section_update_impl(workspace_id, heading, content, &*state, &app).await
+}
+
 #[tauri::command]
 pub(crate) async fn agent_profiles_list(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<AgentProfileListResponse, String> {
+) -> AppResult<AgentProfileListResponse> {
     agent_profiles_list_impl(workspace_id, &*state, &app).await
 }
 
@@ -161,6 +559,35 @@ pub(crate) async fn agent_profile_apply(
     mode: Option<AgentProfileApplyMode>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<AgentProfileApplyResponse, String> {
+) -> AppResult<AgentProfileApplyResponse> {
     agent_profile_apply_impl(workspace_id, profile, mode, &*state, &app).await
 }
+
+#[tauri::command]
+pub(crate) async fn agent_profile_status(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AgentProfileStatus> {
+    agent_profile_status_impl(workspace_id, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn agent_profile_sync(
+    workspace_id: String,
+    action: AgentProfileSyncAction,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AgentProfileStatus> {
+    agent_profile_sync_impl(workspace_id, action, &*state, &app).await
+}
+
+#[tauri::command]
+pub(crate) async fn agent_profile_save(
+    workspace_id: String,
+    profile: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AgentProfileStatus> {
+    agent_profile_save_impl(workspace_id, profile, &*state, &app).await
+}
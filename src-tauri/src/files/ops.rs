@@ -1,7 +1,12 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-use crate::files::io::{read_text_file_within, write_text_file_within, TextFileResponse};
-use crate::files::policy::FilePolicy;
+use crate::files::io::{
+    read_text_file_within, read_text_file_within_limit, read_text_range_within,
+    write_text_file_within, TextFileResponse, TextRangeResponse,
+};
+use crate::files::policy::{
+    check_workspace_relative_path, workspace_read_policy, FilePolicy, MAX_WORKSPACE_FILE_BYTES,
+};
 
 pub(crate) fn read_with_policy(root: &PathBuf, policy: FilePolicy) -> Result<TextFileResponse, String> {
     read_text_file_within(
@@ -30,15 +35,81 @@ pub(crate) fn write_with_policy(
     )
 }
 
+/// Reads a file by its path relative to the workspace root, truncated to
+/// [`MAX_WORKSPACE_FILE_BYTES`].
+pub(crate) fn read_workspace_relative(root: &Path, path: &str) -> Result<TextFileResponse, String> {
+    check_workspace_relative_path(path)?;
+    let policy = workspace_read_policy();
+    read_text_file_within_limit(
+        root,
+        path,
+        policy.root_may_be_missing,
+        policy.root_context,
+        path,
+        policy.allow_external_symlink_target,
+        Some(MAX_WORKSPACE_FILE_BYTES),
+    )
+}
+
+/// Reads a byte range of a workspace file, at most
+/// [`MAX_WORKSPACE_FILE_BYTES`] long. Without `offset` the range ends at
+/// the end of the file.
+pub(crate) fn read_workspace_range(
+    root: &Path,
+    path: &str,
+    offset: Option<u64>,
+    length: u64,
+) -> Result<TextRangeResponse, String> {
+    check_workspace_relative_path(path)?;
+    let policy = workspace_read_policy();
+    read_text_range_within(
+        root,
+        path,
+        policy.root_context,
+        path,
+        policy.allow_external_symlink_target,
+        offset,
+        length.min(MAX_WORKSPACE_FILE_BYTES),
+    )
+}
+
+/// Writes a file by its path relative to the workspace root, creating
+/// missing parent directories inside the root.
+pub(crate) fn write_workspace_relative(
+    root: &Path,
+    path: &str,
+    content: &str,
+) -> Result<(), String> {
+    check_workspace_relative_path(path)?;
+    if content.len() as u64 > MAX_WORKSPACE_FILE_BYTES {
+        return Err(format!(
+            "{path} is larger than the {MAX_WORKSPACE_FILE_BYTES} byte limit"
+        ));
+    }
+    let policy = workspace_read_policy();
+    write_text_file_within(
+        root,
+        path,
+        content,
+        policy.create_root,
+        policy.root_context,
+        path,
+        policy.allow_external_symlink_target,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
 
     use uuid::Uuid;
 
-    use crate::files::policy::{policy_for, FileKind, FileScope};
+    use crate::files::policy::{policy_for, FileKind, FileScope, MAX_WORKSPACE_FILE_BYTES};
 
-    use super::{read_with_policy, write_with_policy};
+    use super::{
+        read_with_policy, read_workspace_range, read_workspace_relative, write_with_policy,
+        write_workspace_relative,
+    };
 
     fn temp_dir(prefix: &str) -> std::path::PathBuf {
         let dir = std::env::temp_dir().join(format!("codex-monitor-{prefix}-{}", Uuid::new_v4()));
@@ -134,4 +205,72 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
         let _ = fs::remove_dir_all(&outside);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn workspace_relative_files_stay_inside_the_root() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir("workspace-relative");
+        let outside = temp_dir("workspace-relative-outside");
+        fs::create_dir_all(&root).expect("create root");
+        fs::create_dir_all(&outside).expect("create outside");
+        fs::write(outside.join("notes.md"), "outside").expect("seed outside");
+        symlink(outside.join("notes.md"), root.join("linked.md")).expect("file symlink");
+        symlink(&outside, root.join("docs")).expect("dir symlink");
+
+        write_workspace_relative(&root, "src/lib.rs", "pub fn a() {}\n").expect("write nested");
+        let response = read_workspace_relative(&root, "src/lib.rs").expect("read nested");
+        assert!(response.exists);
+        assert_eq!(response.content, "pub fn a() {}\n");
+        let missing = read_workspace_relative(&root, "src/missing.rs").expect("read missing");
+        assert!(!missing.exists);
+
+        assert!(read_workspace_relative(&root, "linked.md").is_err());
+        assert!(write_workspace_relative(&root, "linked.md", "x").is_err());
+        assert!(write_workspace_relative(&root, "docs/new.md", "x").is_err());
+        assert!(!outside.join("new.md").exists());
+        assert_eq!(
+            fs::read_to_string(outside.join("notes.md")).expect("outside intact"),
+            "outside"
+        );
+
+        // The limit falls inside a two-byte character.
+        let large = format!("a{}", "é".repeat(MAX_WORKSPACE_FILE_BYTES as usize));
+        assert!(write_workspace_relative(&root, "large.txt", &large).is_err());
+        fs::write(root.join("large.txt"), &large).expect("seed large");
+        let response = read_workspace_relative(&root, "large.txt").expect("read large");
+        assert!(response.truncated);
+        assert_eq!(response.content.len() as u64, MAX_WORKSPACE_FILE_BYTES - 1);
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn workspace_ranges_page_through_whole_characters() {
+        let root = temp_dir("workspace-range");
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("agent.log"), "één\ntwee\n").expect("write log");
+
+        // Offset 1 falls inside the first "é".
+        let page = read_workspace_range(&root, "agent.log", Some(1), 4).expect("first page");
+        assert_eq!((page.offset, page.content.as_str()), (2, "én"));
+        assert_eq!(page.next_offset, 5);
+        let page = read_workspace_range(&root, "agent.log", Some(page.next_offset), 100)
+            .expect("second page");
+        assert_eq!(page.content, "\ntwee\n");
+        assert_eq!(page.next_offset, page.file_size);
+
+        let tail = read_workspace_range(&root, "agent.log", None, 5).expect("tail");
+        assert_eq!((tail.offset, tail.content.as_str()), (6, "twee\n"));
+
+        fs::write(root.join("win.log"), b"\xFF\xFEa\0b\0").expect("write utf-16");
+        let page = read_workspace_range(&root, "win.log", Some(3), 2).expect("utf-16 page");
+        assert_eq!((page.offset, page.content.as_str()), (4, "b"));
+        let missing = read_workspace_range(&root, "missing.log", None, 10).expect("missing");
+        assert!(!missing.exists);
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -1,3 +1,5 @@
+use std::path::{Component, Path};
+
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -12,6 +14,11 @@ pub(crate) enum FileScope {
 pub(crate) enum FileKind {
     Agents,
     Config,
+    Claude,
+    Gemini,
+    CursorRules,
+    CodexInstructions,
+    Mcp,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -23,41 +30,206 @@ pub(crate) struct FilePolicy {
     pub(crate) allow_external_symlink_target: bool,
 }
 
-const AGENTS_FILENAME: &str = "AGENTS.md";
-const CONFIG_FILENAME: &str = "config.toml";
+/// Managed file kinds and the filename each resolves to per scope. Workspace
+/// files live under the workspace root; global files live under CODEX_HOME.
+/// `None` means the kind is not available in that scope.
+struct FileKindEntry {
+    kind: FileKind,
+    workspace_filename: Option<&'static str>,
+    global_filename: Option<&'static str>,
+}
+
+const FILE_KINDS: &[FileKindEntry] = &[
+    FileKindEntry {
+        kind: FileKind::Agents,
+        workspace_filename: Some("AGENTS.md"),
+        global_filename: Some("AGENTS.md"),
+    },
+    FileKindEntry {
+        kind: FileKind::Config,
+        workspace_filename: None,
+        global_filename: Some("config.toml"),
+    },
+    FileKindEntry {
+        kind: FileKind::Claude,
+        workspace_filename: Some("CLAUDE.md"),
+        global_filename: None,
+    },
+    FileKindEntry {
+        kind: FileKind::Gemini,
+        workspace_filename: Some("GEMINI.md"),
+        global_filename: None,
+    },
+    FileKindEntry {
+        kind: FileKind::CursorRules,
+        workspace_filename: Some(".cursorrules"),
+        global_filename: None,
+    },
+    FileKindEntry {
+        kind: FileKind::CodexInstructions,
+        workspace_filename: Some(".codex/instructions.md"),
+        global_filename: Some("instructions.md"),
+    },
+    FileKindEntry {
+        kind: FileKind::Mcp,
+        workspace_filename: Some(".mcp.json"),
+        global_filename: None,
+    },
+];
+
+fn file_kind_entry(kind: FileKind) -> &'static FileKindEntry {
+    FILE_KINDS
+        .iter()
+        .find(|entry| entry.kind == kind)
+        .expect("every FileKind has a registry entry")
+}
+
+/// The workspace-root instructions file a CLI reads: `CLAUDE.md` for Claude,
+/// `GEMINI.md` for Gemini, `.cursorrules` for Cursor and `AGENTS.md` for
+/// Codex.
+pub(crate) fn instructions_kind_for_cli(cli_type: &str) -> FileKind {
+    match cli_type {
+        "claude" => FileKind::Claude,
+        "gemini" => FileKind::Gemini,
+        "cursor" => FileKind::CursorRules,
+        _ => FileKind::Agents,
+    }
+}
+
+/// The filename `kind` resolves to under a workspace root, if it has one.
+pub(crate) fn workspace_filename(kind: FileKind) -> Option<&'static str> {
+    file_kind_entry(kind).workspace_filename
+}
 
 pub(crate) fn policy_for(scope: FileScope, kind: FileKind) -> Result<FilePolicy, String> {
-    match (scope, kind) {
-        (FileScope::Workspace, FileKind::Agents) => Ok(FilePolicy {
-            filename: AGENTS_FILENAME,
-            root_context: "workspace root",
-            root_may_be_missing: false,
-            create_root: false,
-            allow_external_symlink_target: false,
-        }),
-        (FileScope::Global, FileKind::Agents) => Ok(FilePolicy {
-            filename: AGENTS_FILENAME,
-            root_context: "CODEX_HOME",
-            root_may_be_missing: true,
-            create_root: true,
-            allow_external_symlink_target: true,
-        }),
-        (FileScope::Global, FileKind::Config) => Ok(FilePolicy {
-            filename: CONFIG_FILENAME,
-            root_context: "CODEX_HOME",
-            root_may_be_missing: true,
-            create_root: true,
-            allow_external_symlink_target: true,
-        }),
-        (FileScope::Workspace, FileKind::Config) => {
-            Err("config.toml is only supported for global scope".to_string())
+    let entry = file_kind_entry(kind);
+    match scope {
+        FileScope::Workspace => {
+            let filename = entry.workspace_filename.ok_or_else(|| {
+                format!(
+                    "{} is only supported for global scope",
+                    entry.global_filename.unwrap_or("file")
+                )
+            })?;
+            Ok(FilePolicy {
+                filename,
+                root_context: "workspace root",
+                root_may_be_missing: false,
+                create_root: false,
+                allow_external_symlink_target: false,
+            })
+        }
+        FileScope::Global => {
+            let filename = entry.global_filename.ok_or_else(|| {
+                format!(
+                    "{} is only supported for workspace scope",
+                    entry.workspace_filename.unwrap_or("file")
+                )
+            })?;
+            Ok(FilePolicy {
+                filename,
+                root_context: "CODEX_HOME",
+                root_may_be_missing: true,
+                create_root: true,
+                allow_external_symlink_target: true,
+            })
         }
     }
 }
 
+/// Containment rules for reading other files under a workspace root (e.g.
+/// files attached to a prompt). Same as the workspace scope; the filename is
+/// supplied by the caller.
+pub(crate) fn workspace_read_policy() -> FilePolicy {
+    FilePolicy {
+        filename: "",
+        root_context: "workspace root",
+        root_may_be_missing: false,
+        create_root: false,
+        allow_external_symlink_target: false,
+    }
+}
+
+/// Largest workspace file `workspace_file_read` returns in full and
+/// `workspace_file_write` accepts.
+pub(crate) const MAX_WORKSPACE_FILE_BYTES: u64 = 1_000_000;
+
+/// Extensions of workspace files that can be opened by relative path.
+const EDITABLE_EXTENSIONS: &[&str] = &[
+    "c", "cc", "cfg", "cjs", "conf", "cpp", "cs", "css", "csv", "go", "h", "hpp", "html", "ini",
+    "java", "js", "json", "jsonc", "jsx", "kt", "less", "lock", "log", "lua", "md", "mdx", "mjs",
+    "php", "py", "rb", "rs", "scss", "sh", "sql", "svelte", "swift", "toml", "ts", "tsx", "txt",
+    "vue", "xml", "yaml", "yml", "zsh",
+];
+
+/// Extensionless or dot-files that can be opened by relative path.
+const EDITABLE_FILENAMES: &[&str] = &[
+    ".cursorrules",
+    ".editorconfig",
+    ".gitattributes",
+    ".gitignore",
+    ".npmrc",
+    ".prettierrc",
+    "Dockerfile",
+    "Gemfile",
+    "LICENSE",
+    "Makefile",
+    "Procfile",
+];
+
+/// Checks that `path` is relative and stays below the workspace root
+/// without `..` or entering `.git`. Symlinks are checked when the path is
+/// opened.
+pub(crate) fn check_workspace_relative_dir(path: &str) -> Result<(), String> {
+    for component in Path::new(path).components() {
+        match component {
+            Component::Normal(name) if name == ".git" => {
+                return Err(format!("{path} is inside .git"));
+            }
+            Component::Normal(_) | Component::CurDir => {}
+            Component::ParentDir => {
+                return Err(format!("{path} leaves the workspace"));
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(format!("{path} is not relative to the workspace"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Checks a path the UI asks to read or write under a workspace root: it
+/// must pass [`check_workspace_relative_dir`] and name a text file on the
+/// allowlist. Symlinks are checked when the file is opened, by
+/// `workspace_read_policy`.
+pub(crate) fn check_workspace_relative_path(path: &str) -> Result<(), String> {
+    if path.trim().is_empty() {
+        return Err("path is required".to_string());
+    }
+    check_workspace_relative_dir(path)?;
+    let relative = Path::new(path);
+    let Some(file_name) = relative.file_name().and_then(|name| name.to_str()) else {
+        return Err(format!("{path} is not a file path"));
+    };
+    let allowed_extension = relative
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            EDITABLE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+        });
+    if allowed_extension || EDITABLE_FILENAMES.contains(&file_name) {
+        Ok(())
+    } else {
+        Err(format!("{file_name} is not a supported text file"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{policy_for, FileKind, FileScope};
+    use super::{
+        check_workspace_relative_path, instructions_kind_for_cli, policy_for, workspace_filename,
+        FileKind, FileScope,
+    };
 
     #[test]
     fn workspace_agents_policy_is_strict() {
@@ -94,4 +266,75 @@ mod tests {
         let result = policy_for(FileScope::Workspace, FileKind::Config);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn instruction_files_are_workspace_scoped() {
+        for (kind, filename) in [
+            (FileKind::Claude, "CLAUDE.md"),
+            (FileKind::Gemini, "GEMINI.md"),
+            (FileKind::CursorRules, ".cursorrules"),
+            (FileKind::Mcp, ".mcp.json"),
+        ] {
+            let policy = policy_for(FileScope::Workspace, kind).expect("policy");
+            assert_eq!(policy.filename, filename);
+            assert!(!policy.allow_external_symlink_target);
+            let error = policy_for(FileScope::Global, kind).expect_err("global rejected");
+            assert_eq!(
+                error,
+                format!("{filename} is only supported for workspace scope")
+            );
+        }
+    }
+
+    #[test]
+    fn codex_instructions_resolve_per_scope() {
+        let workspace = policy_for(FileScope::Workspace, FileKind::CodexInstructions)
+            .expect("workspace policy");
+        assert_eq!(workspace.filename, ".codex/instructions.md");
+        let global =
+            policy_for(FileScope::Global, FileKind::CodexInstructions).expect("global policy");
+        assert_eq!(global.filename, "instructions.md");
+    }
+
+    #[test]
+    fn each_cli_reads_its_own_instructions_file() {
+        for (cli_type, filename) in [
+            ("codex", "AGENTS.md"),
+            ("claude", "CLAUDE.md"),
+            ("gemini", "GEMINI.md"),
+            ("cursor", ".cursorrules"),
+        ] {
+            let kind = instructions_kind_for_cli(cli_type);
+            assert_eq!(workspace_filename(kind), Some(filename));
+        }
+    }
+
+    #[test]
+    fn file_kinds_deserialize_from_snake_case() {
+        let kind: FileKind = serde_json::from_str("\"cursor_rules\"").expect("kind");
+        assert_eq!(kind, FileKind::CursorRules);
+        let kind: FileKind = serde_json::from_str("\"codex_instructions\"").expect("kind");
+        assert_eq!(kind, FileKind::CodexInstructions);
+    }
+
+    #[test]
+    fn workspace_relative_paths_stay_inside_and_on_the_allowlist() {
+        for path in ["src/main.rs", "./docs/Guide.MD", "Makefile", ".gitignore"] {
+            assert_eq!(check_workspace_relative_path(path), Ok(()), "{path}");
+        }
+        for (path, error) in [
+            ("../secrets.md", "../secrets.md leaves the workspace"),
+            ("src/../../x.rs", "src/../../x.rs leaves the workspace"),
+            (
+                "/etc/hosts.txt",
+                "/etc/hosts.txt is not relative to the workspace",
+            ),
+            (".git/config.toml", ".git/config.toml is inside .git"),
+            ("bin/app.exe", "app.exe is not a supported text file"),
+            (".env", ".env is not a supported text file"),
+            ("", "path is required"),
+        ] {
+            assert_eq!(check_workspace_relative_path(path), Err(error.to_string()));
+        }
+    }
 }
@@ -0,0 +1,233 @@
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+
+use crate::files::policy::check_workspace_relative_dir;
+use crate::utils::normalize_git_path;
+
+/// Levels listed when the caller doesn't pass a depth.
+const DEFAULT_TREE_DEPTH: usize = 1;
+const MAX_TREE_DEPTH: usize = 8;
+/// Entries listed before the tree is cut off and marked `truncated`.
+const MAX_TREE_ENTRIES: usize = 5_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum WorkspaceTreeEntryKind {
+    File,
+    Dir,
+    Symlink,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceTreeEntry {
+    pub(crate) name: String,
+    /// Relative to the workspace root, with `/` separators.
+    pub(crate) path: String,
+    pub(crate) kind: WorkspaceTreeEntryKind,
+    /// File size in bytes; `None` for directories and symlinks.
+    pub(crate) size: Option<u64>,
+    /// Last modification, in milliseconds since the Unix epoch.
+    pub(crate) modified_ms: Option<u64>,
+    /// Listed children of a directory. `None` below the requested depth.
+    pub(crate) children: Option<Vec<WorkspaceTreeEntry>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceTreeResponse {
+    pub(crate) path: String,
+    pub(crate) depth: usize,
+    pub(crate) entries: Vec<WorkspaceTreeEntry>,
+    /// More than [`MAX_TREE_ENTRIES`] entries were found.
+    pub(crate) truncated: bool,
+}
+
+fn tree_entry(
+    entry: &ignore::DirEntry,
+    root: &Path,
+    listed_depth: usize,
+) -> Option<WorkspaceTreeEntry> {
+    let file_type = entry.file_type()?;
+    let relative = entry.path().strip_prefix(root).ok()?;
+    let metadata = entry.metadata().ok();
+    let kind = if file_type.is_symlink() {
+        WorkspaceTreeEntryKind::Symlink
+    } else if file_type.is_dir() {
+        WorkspaceTreeEntryKind::Dir
+    } else {
+        WorkspaceTreeEntryKind::File
+    };
+    let modified_ms = metadata
+        .as_ref()
+        .and_then(|metadata| metadata.modified().ok())
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|since| since.as_millis() as u64);
+    Some(WorkspaceTreeEntry {
+        name: entry.file_name().to_string_lossy().to_string(),
+        path: normalize_git_path(&relative.to_string_lossy()),
+        kind,
+        size: metadata
+            .filter(|_| kind == WorkspaceTreeEntryKind::File)
+            .map(|metadata| metadata.len()),
+        modified_ms,
+        children: (kind == WorkspaceTreeEntryKind::Dir && entry.depth() < listed_depth)
+            .then(Vec::new),
+    })
+}
+
+/// Places `entry` under the directory named by `parents`, relative to
+/// `entries`.
+fn insert_entry(
+    entries: &mut Vec<WorkspaceTreeEntry>,
+    parents: &[&str],
+    entry: WorkspaceTreeEntry,
+) {
+    let Some((first, rest)) = parents.split_first() else {
+        entries.push(entry);
+        return;
+    };
+    if let Some(children) = entries
+        .iter_mut()
+        .find(|candidate| candidate.name == *first)
+        .and_then(|parent| parent.children.as_mut())
+    {
+        insert_entry(children, rest, entry);
+    }
+}
+
+fn sort_entries(entries: &mut [WorkspaceTreeEntry]) {
+    entries.sort_by(|a, b| {
+        (a.kind != WorkspaceTreeEntryKind::Dir, &a.name)
+            .cmp(&(b.kind != WorkspaceTreeEntryKind::Dir, &b.name))
+    });
+    for entry in entries {
+        if let Some(children) = entry.children.as_mut() {
+            sort_entries(children);
+        }
+    }
+}
+
+/// Lists the workspace below `path` down to `depth` levels, skipping what
+/// `.gitignore` (and `.ignore`) exclude and the `.git` directory.
+/// Directories come before files, each sorted by name. Symlinks are listed
+/// but not followed.
+pub(crate) fn workspace_tree(
+    root: &Path,
+    path: &str,
+    depth: Option<usize>,
+) -> Result<WorkspaceTreeResponse, String> {
+    check_workspace_relative_dir(path)?;
+    let depth = depth.unwrap_or(DEFAULT_TREE_DEPTH).clamp(1, MAX_TREE_DEPTH);
+    let canonical_root = root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let start = canonical_root
+        .join(path)
+        .canonicalize()
+        .map_err(|err| format!("Failed to open {path}: {err}"))?;
+    if !start.starts_with(&canonical_root) {
+        return Err(format!("Invalid {path} path"));
+    }
+    if !start.is_dir() {
+        return Err(format!("{path} is not a directory"));
+    }
+
+    let walker = WalkBuilder::new(&start)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .max_depth(Some(depth))
+        .filter_entry(|entry| entry.depth() == 0 || entry.file_name() != ".git")
+        .build();
+    let mut entries = Vec::new();
+    let mut count = 0;
+    let mut truncated = false;
+    for entry in walker.flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        if count == MAX_TREE_ENTRIES {
+            truncated = true;
+            break;
+        }
+        let Some(tree_entry) = tree_entry(&entry, &canonical_root, depth) else {
+            continue;
+        };
+        let parents = entry
+            .path()
+            .strip_prefix(&start)
+            .ok()
+            .and_then(Path::parent)
+            .map(|parent| {
+                parent
+                    .iter()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let parents = parents.iter().map(String::as_str).collect::<Vec<_>>();
+        insert_entry(&mut entries, &parents, tree_entry);
+        count += 1;
+    }
+    sort_entries(&mut entries);
+
+    let listed = start
+        .strip_prefix(&canonical_root)
+        .map(|relative| normalize_git_path(&relative.to_string_lossy()))
+        .unwrap_or_default();
+    Ok(WorkspaceTreeResponse {
+        path: listed,
+        depth,
+        entries,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use uuid::Uuid;
+
+    use super::{workspace_tree, WorkspaceTreeEntry, WorkspaceTreeEntryKind};
+
+    fn names(entries: &[WorkspaceTreeEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn lists_the_tree_without_ignored_files() {
+        let root = std::env::temp_dir().join(format!("workspace-tree-{}", Uuid::new_v4()));
+        fs::create_dir_all(root.join("src/nested")).expect("create src");
+        fs::create_dir_all(root.join("target/debug")).expect("create target");
+        fs::create_dir_all(root.join(".git")).expect("create .git");
+        fs::write(root.join(".gitignore"), "target/\n*.log\n").expect("write gitignore");
+        fs::write(root.join("README.md"), "hello").expect("write readme");
+        fs::write(root.join("debug.log"), "").expect("write log");
+        fs::write(root.join("src/main.rs"), "fn main() {}").expect("write main");
+        fs::write(root.join("src/nested/deep.rs"), "").expect("write deep");
+
+        let tree = workspace_tree(&root, "", Some(2)).expect("tree");
+        assert_eq!(names(&tree.entries), ["src", ".gitignore", "README.md"]);
+        let readme = &tree.entries[2];
+        assert_eq!(readme.kind, WorkspaceTreeEntryKind::File);
+        assert_eq!(readme.size, Some(5));
+        assert!(readme.modified_ms.is_some());
+        let src = tree.entries[0].children.as_ref().expect("src listed");
+        assert_eq!(names(src), ["nested", "main.rs"]);
+        assert_eq!(src[1].path, "src/main.rs");
+        assert_eq!(src[0].children, None);
+
+        let nested = workspace_tree(&root, "src/nested", None).expect("subtree");
+        assert_eq!(nested.path, "src/nested");
+        assert_eq!(nested.entries[0].path, "src/nested/deep.rs");
+        assert!(workspace_tree(&root, "../", None).is_err());
+        assert!(workspace_tree(&root, "README.md", None).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
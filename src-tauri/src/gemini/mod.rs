@@ -5,7 +5,6 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use tauri::{AppHandle, Emitter, State};
-use tokio::sync::mpsc;
 use tokio::time::timeout;
 
 pub(crate) mod args;
@@ -14,7 +13,8 @@ pub(crate) mod home;
 pub(crate) mod settings;
 
 pub(crate) use crate::backend::app_server::WorkspaceSession;
-use crate::backend::events::AppServerEvent;
+use crate::backend::background_queue::{background_channel, BACKGROUND_QUEUE_CAPACITY};
+use crate::backend::events::{AppEvent, AppServerEvent};
 use crate::backend::app_server::{
     build_gemini_command_with_bin, build_gemini_path_env, check_gemini_installation,
     spawn_workspace_session as spawn_workspace_session_inner, CliSpawnConfig, CursorCliSettings,
@@ -733,20 +733,14 @@ pub(crate) async fn generate_commit_message(
     // Hide background helper threads from the sidebar, even if a thread/started event leaked.
     let _ = app.emit(
         "app-server-event",
-        AppServerEvent {
-            workspace_id: workspace_id.clone(),
-            message: json!({
-                "method": "gemini/backgroundThread",
-                "params": {
-                    "threadId": thread_id,
-                    "action": "hide"
-                }
-            }),
-        },
+        AppServerEvent::new(
+            workspace_id.clone(),
+            AppEvent::background_thread_hidden(thread_id.clone()),
+        ),
     );
 
     // Create channel for receiving events
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let (tx, mut rx) = background_channel(BACKGROUND_QUEUE_CAPACITY);
 
     // Register callback for this thread
     {
@@ -931,19 +925,13 @@ Task:\n{cleaned_prompt}"
     // Hide background helper threads from the sidebar, even if a thread/started event leaked.
     let _ = app.emit(
         "app-server-event",
-        AppServerEvent {
-            workspace_id: workspace_id.clone(),
-            message: json!({
-                "method": "gemini/backgroundThread",
-                "params": {
-                    "threadId": thread_id,
-                    "action": "hide"
-                }
-            }),
-        },
+        AppServerEvent::new(
+            workspace_id.clone(),
+            AppEvent::background_thread_hidden(thread_id.clone()),
+        ),
     );
 
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let (tx, mut rx) = background_channel(BACKGROUND_QUEUE_CAPACITY);
     {
         let mut callbacks = session.background_thread_callbacks.lock().await;
         callbacks.insert(thread_id.clone(), tx);
@@ -3,6 +3,8 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::shared::atomic_write::write_atomic;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub(crate) struct GeminiSettings {
     #[serde(default)]
@@ -38,13 +40,9 @@ pub(crate) fn write_user_settings(settings: &GeminiSettings) -> Result<(), Strin
     let Some(path) = settings_json_path() else {
         return Err("Unable to resolve GEMINI_HOME".to_string());
     };
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
-    }
     let json = serde_json::to_string_pretty(settings)
         .map_err(|err| format!("Failed to serialize settings: {err}"))?;
-    std::fs::write(&path, format!("{json}\n"))
+    write_atomic(&path, format!("{json}\n").as_bytes())
         .map_err(|err| format!("Failed to write {}: {err}", path.display()))
 }
 
@@ -4,17 +4,19 @@ use std::path::{Path, PathBuf};
 use base64::{engine::general_purpose::STANDARD, Engine as _};
 use git2::{BranchType, DiffOptions, Repository, Sort, Status, StatusOptions};
 use serde_json::json;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
-use crate::shared::process_core::tokio_command;
+use crate::backend::events::{AppEvent, AppServerEvent};
 use crate::git_utils::{
     checkout_branch, commit_to_entry, diff_patch_to_string, diff_stats_for_path, image_mime_type,
     list_git_roots as scan_git_roots, parse_github_repo, resolve_git_root,
 };
+use crate::shared::app_error::{AppError, AppResult};
+use crate::shared::process_core::tokio_command;
 use crate::state::AppState;
 use crate::types::{
     BranchInfo, GitCommitDiff, GitFileDiff, GitFileStatus, GitHubIssue, GitHubIssuesResponse,
-    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestDiff,
+    GitHubPullRequest, GitHubPullRequestComment, GitHubPullRequestCreated, GitHubPullRequestDiff,
     GitHubPullRequestsResponse, GitLogResponse,
 };
 use crate::utils::{git_env_path, normalize_git_path, resolve_git_binary};
@@ -411,6 +413,229 @@ fn github_repo_from_path(path: &Path) -> Result<String, String> {
     parse_github_repo(remote_url).ok_or("Remote is not a GitHub repository.".to_string())
 }
 
+fn default_remote_name(path: &Path) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| e.to_string())?;
+    let remotes = repo.remotes().map_err(|e| e.to_string())?;
+    if remotes.iter().any(|remote| remote == Some("origin")) {
+        return Ok("origin".to_string());
+    }
+    remotes
+        .iter()
+        .flatten()
+        .next()
+        .map(|name| name.to_string())
+        .ok_or_else(|| "No git remote configured.".to_string())
+}
+
+fn current_branch_name(path: &Path) -> Result<String, String> {
+    let repo = Repository::open(path).map_err(|e| e.to_string())?;
+    let head = repo.head().map_err(|e| e.to_string())?;
+    if !head.is_branch() {
+        return Err("Cannot open a pull request from a detached HEAD.".to_string());
+    }
+    head.shorthand()
+        .map(|name| name.to_string())
+        .ok_or_else(|| "Unable to resolve current branch.".to_string())
+}
+
+async fn push_branch_for_pr(repo_root: &Path, branch: &str) -> Result<(), String> {
+    if upstream_remote_and_branch(repo_root)?.is_some() {
+        return push_with_upstream(repo_root).await;
+    }
+    let remote = default_remote_name(repo_root)?;
+    let refspec = format!("HEAD:refs/heads/{branch}");
+    run_git_command(
+        repo_root,
+        &["push", "--set-upstream", remote.as_str(), refspec.as_str()],
+    )
+    .await
+}
+
+fn pr_create_args(
+    repo_name: &str,
+    branch: &str,
+    title: Option<&str>,
+    body: Option<&str>,
+    base: Option<&str>,
+    draft: bool,
+) -> Vec<String> {
+    let mut args = vec![
+        "pr".to_string(),
+        "create".to_string(),
+        "--repo".to_string(),
+        repo_name.to_string(),
+        "--head".to_string(),
+        branch.to_string(),
+    ];
+    if let Some(base) = base {
+        args.push("--base".to_string());
+        args.push(base.to_string());
+    }
+    match title {
+        Some(title) => {
+            args.push("--title".to_string());
+            args.push(title.to_string());
+            args.push("--body".to_string());
+            args.push(body.unwrap_or_default().to_string());
+        }
+        // Without a title gh derives both fields from the branch commits.
+        None => args.push("--fill".to_string()),
+    }
+    if draft {
+        args.push("--draft".to_string());
+    }
+    args
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+fn emit_pr_progress(app: &AppHandle, workspace_id: &str, stage: &str, message: &str) {
+    let _ = app.emit(
+        "app-server-event",
+        AppServerEvent::new(
+            workspace_id,
+            AppEvent::GitPrProgress {
+                workspace_id: workspace_id.to_string(),
+                stage: stage.to_string(),
+                message: message.to_string(),
+            },
+        ),
+    );
+}
+
+async fn generate_pr_text(
+    workspace_id: &str,
+    transcript: &str,
+    state: &State<'_, AppState>,
+    app: &AppHandle,
+) -> Result<(String, String), String> {
+    let prompt = crate::shared::codex_aux_core::build_pr_description_prompt(transcript);
+    let response = crate::shared::codex_aux_core::run_background_prompt_core(
+        &state.sessions,
+        workspace_id.to_string(),
+        prompt,
+        |workspace_id, thread_id| {
+            let _ = app.emit(
+                "app-server-event",
+                AppServerEvent::new(workspace_id, AppEvent::background_thread_hidden(thread_id)),
+            );
+        },
+        "Timeout waiting for pull request description",
+        "Unknown error during pull request description generation",
+    )
+    .await?;
+    let value = crate::shared::codex_aux_core::extract_json_value(response.trim())
+        .ok_or_else(|| "Failed to parse pull request description".to_string())?;
+    let title = non_empty(
+        value
+            .get("title")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+    )
+    .ok_or_else(|| "Missing title in pull request description".to_string())?;
+    let body = value
+        .get("body")
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    Ok((title, body))
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_pull_request(
+    workspace_id: &str,
+    title: Option<String>,
+    body: Option<String>,
+    transcript: Option<String>,
+    base: Option<String>,
+    draft: bool,
+    state: &State<'_, AppState>,
+    app: &AppHandle,
+) -> Result<GitHubPullRequestCreated, String> {
+    let entry = {
+        let workspaces = state.workspaces.lock().await;
+        workspaces
+            .get(workspace_id)
+            .ok_or(AppError::WorkspaceNotFound)?
+            .clone()
+    };
+    let repo_root = resolve_git_root(&entry)?;
+    let repo_name = github_repo_from_path(&repo_root)?;
+    let branch = current_branch_name(&repo_root)?;
+
+    let mut title = non_empty(title);
+    let mut body = non_empty(body);
+    if title.is_none() {
+        if let Some(transcript) = non_empty(transcript) {
+            emit_pr_progress(
+                app,
+                workspace_id,
+                "generating",
+                "Writing pull request description",
+            );
+            match generate_pr_text(workspace_id, &transcript, state, app).await {
+                Ok((generated_title, generated_body)) => {
+                    title = Some(generated_title);
+                    body = body.or(Some(generated_body));
+                }
+                Err(error) => {
+                    emit_pr_progress(
+                        app,
+                        workspace_id,
+                        "generating",
+                        &format!("Using commit messages instead: {error}"),
+                    );
+                }
+            }
+        }
+    }
+
+    emit_pr_progress(app, workspace_id, "pushing", &format!("Pushing {branch}"));
+    push_branch_for_pr(&repo_root, &branch).await?;
+
+    emit_pr_progress(app, workspace_id, "creating", "Creating pull request");
+    let args = pr_create_args(
+        &repo_name,
+        &branch,
+        title.as_deref(),
+        body.as_deref(),
+        non_empty(base).as_deref(),
+        draft,
+    );
+    let output = tokio_command("gh")
+        .args(&args)
+        .current_dir(&repo_root)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run gh: {e}"))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let detail = if stderr.trim().is_empty() {
+            stdout.trim()
+        } else {
+            stderr.trim()
+        };
+        if detail.is_empty() {
+            return Err("GitHub CLI command failed.".to_string());
+        }
+        return Err(detail.to_string());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let url = stdout
+        .lines()
+        .map(str::trim)
+        .rfind(|line| !line.is_empty())
+        .unwrap_or_default()
+        .to_string();
+    Ok(GitHubPullRequestCreated { url, branch, title })
+}
+
 fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
     let mut entries = Vec::new();
     let mut current_lines: Vec<&str> = Vec::new();
@@ -506,11 +731,11 @@ fn parse_pr_diff(diff: &str) -> Vec<GitHubPullRequestDiff> {
 pub(crate) async fn get_git_status(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> AppResult<serde_json::Value> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     drop(workspaces);
 
@@ -638,13 +863,13 @@ pub(crate) async fn stage_git_file(
     workspace_id: String,
     path: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let entry = {
         let workspaces = state.workspaces.lock().await;
         workspaces
             .get(&workspace_id)
             .cloned()
-            .ok_or("workspace not found")?
+            .ok_or(AppError::WorkspaceNotFound)?
     };
 
     let repo_root = resolve_git_root(&entry)?;
@@ -660,17 +885,17 @@ pub(crate) async fn stage_git_file(
 pub(crate) async fn stage_git_all(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let entry = {
         let workspaces = state.workspaces.lock().await;
         workspaces
             .get(&workspace_id)
             .cloned()
-            .ok_or("workspace not found")?
+            .ok_or(AppError::WorkspaceNotFound)?
     };
 
     let repo_root = resolve_git_root(&entry)?;
-    run_git_command(&repo_root, &["add", "-A"]).await
+    Ok(run_git_command(&repo_root, &["add", "-A"]).await?)
 }
 
 #[tauri::command]
@@ -678,13 +903,13 @@ pub(crate) async fn unstage_git_file(
     workspace_id: String,
     path: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let entry = {
         let workspaces = state.workspaces.lock().await;
         workspaces
             .get(&workspace_id)
             .cloned()
-            .ok_or("workspace not found")?
+            .ok_or(AppError::WorkspaceNotFound)?
     };
 
     let repo_root = resolve_git_root(&entry)?;
@@ -699,13 +924,13 @@ pub(crate) async fn revert_git_file(
     workspace_id: String,
     path: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let entry = {
         let workspaces = state.workspaces.lock().await;
         workspaces
             .get(&workspace_id)
             .cloned()
-            .ok_or("workspace not found")?
+            .ok_or(AppError::WorkspaceNotFound)?
     };
 
     let repo_root = resolve_git_root(&entry)?;
@@ -728,14 +953,18 @@ pub(crate) async fn revert_git_file(
 pub(crate) async fn revert_git_all(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?;
+        .ok_or(AppError::WorkspaceNotFound)?;
     let repo_root = resolve_git_root(entry)?;
-    run_git_command(&repo_root, &["restore", "--staged", "--worktree", "--", "."]).await?;
-    run_git_command(&repo_root, &["clean", "-f", "-d"]).await
+    run_git_command(
+        &repo_root,
+        &["restore", "--staged", "--worktree", "--", "."],
+    )
+    .await?;
+    Ok(run_git_command(&repo_root, &["clean", "-f", "-d"]).await?)
 }
 
 #[tauri::command]
@@ -743,77 +972,95 @@ pub(crate) async fn commit_git(
     workspace_id: String,
     message: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
-    run_git_command(&repo_root, &["commit", "-m", &message]).await
+    Ok(run_git_command(&repo_root, &["commit", "-m", &message]).await?)
 }
 
 #[tauri::command]
-pub(crate) async fn push_git(
-    workspace_id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
+pub(crate) async fn push_git(workspace_id: String, state: State<'_, AppState>) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
-    push_with_upstream(&repo_root).await
+    Ok(push_with_upstream(&repo_root).await?)
 }
 
 #[tauri::command]
-pub(crate) async fn pull_git(
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn git_pr_create(
     workspace_id: String,
+    title: Option<String>,
+    body: Option<String>,
+    transcript: Option<String>,
+    base: Option<String>,
+    draft: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+    app: AppHandle,
+) -> AppResult<GitHubPullRequestCreated> {
+    let result = create_pull_request(
+        &workspace_id,
+        title,
+        body,
+        transcript,
+        base,
+        draft.unwrap_or(false),
+        &state,
+        &app,
+    )
+    .await;
+    match &result {
+        Ok(created) => emit_pr_progress(&app, &workspace_id, "done", &created.url),
+        Err(error) => emit_pr_progress(&app, &workspace_id, "failed", error),
+    }
+    Ok(result?)
+}
+
+#[tauri::command]
+pub(crate) async fn pull_git(workspace_id: String, state: State<'_, AppState>) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
-    pull_with_default_strategy(&repo_root).await
+    Ok(pull_with_default_strategy(&repo_root).await?)
 }
 
 #[tauri::command]
-pub(crate) async fn fetch_git(
-    workspace_id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
+pub(crate) async fn fetch_git(workspace_id: String, state: State<'_, AppState>) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
-    fetch_with_default_remote(&repo_root).await
+    Ok(fetch_with_default_remote(&repo_root).await?)
 }
 
 #[tauri::command]
-pub(crate) async fn sync_git(
-    workspace_id: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
+pub(crate) async fn sync_git(workspace_id: String, state: State<'_, AppState>) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
     // Pull first, then push (like VSCode sync)
     pull_with_default_strategy(&repo_root).await?;
-    push_with_upstream(&repo_root).await
+    Ok(push_with_upstream(&repo_root).await?)
 }
 
 #[tauri::command]
@@ -821,11 +1068,11 @@ pub(crate) async fn list_git_roots(
     workspace_id: String,
     depth: Option<usize>,
     state: State<'_, AppState>,
-) -> Result<Vec<String>, String> {
+) -> AppResult<Vec<String>> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let root = PathBuf::from(&entry.path);
@@ -841,7 +1088,7 @@ pub(crate) async fn get_workspace_diff(
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     drop(workspaces);
 
@@ -853,11 +1100,11 @@ pub(crate) async fn get_workspace_diff(
 pub(crate) async fn get_git_diffs(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<GitFileDiff>, String> {
+) -> AppResult<Vec<GitFileDiff>> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     drop(workspaces);
 
@@ -866,7 +1113,7 @@ pub(crate) async fn get_git_diffs(
         let settings = state.app_settings.lock().await;
         settings.git_diff_ignore_whitespace_changes
     };
-    tokio::task::spawn_blocking(move || {
+    tokio::task::spawn_blocking(move || -> Result<Vec<GitFileDiff>, String> {
         let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
         let head_tree = repo
             .head()
@@ -999,6 +1246,7 @@ pub(crate) async fn get_git_diffs(
     })
     .await
     .map_err(|e| e.to_string())?
+    .map_err(AppError::from)
 }
 
 #[tauri::command]
@@ -1006,11 +1254,11 @@ pub(crate) async fn get_git_log(
     workspace_id: String,
     limit: Option<usize>,
     state: State<'_, AppState>,
-) -> Result<GitLogResponse, String> {
+) -> AppResult<GitLogResponse> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     drop(workspaces);
 
@@ -1115,11 +1363,11 @@ pub(crate) async fn get_git_commit_diff(
     workspace_id: String,
     sha: String,
     state: State<'_, AppState>,
-) -> Result<Vec<GitCommitDiff>, String> {
+) -> AppResult<Vec<GitCommitDiff>> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     drop(workspaces);
 
@@ -1253,11 +1501,11 @@ pub(crate) async fn get_git_commit_diff(
 pub(crate) async fn get_git_remote(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<Option<String>, String> {
+) -> AppResult<Option<String>> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
@@ -1284,11 +1532,11 @@ pub(crate) async fn get_git_remote(
 pub(crate) async fn get_github_issues(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<GitHubIssuesResponse, String> {
+) -> AppResult<GitHubIssuesResponse> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
@@ -1319,9 +1567,9 @@ pub(crate) async fn get_github_issues(
             stderr.trim()
         };
         if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
+            return Err(AppError::Remote("GitHub CLI command failed.".to_string()));
         }
-        return Err(detail.to_string());
+        return Err(AppError::Remote(detail.to_string()));
     }
 
     let issues: Vec<GitHubIssue> =
@@ -1354,11 +1602,11 @@ pub(crate) async fn get_github_issues(
 pub(crate) async fn get_github_pull_requests(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<GitHubPullRequestsResponse, String> {
+) -> AppResult<GitHubPullRequestsResponse> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
@@ -1391,9 +1639,9 @@ pub(crate) async fn get_github_pull_requests(
             stderr.trim()
         };
         if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
+            return Err(AppError::Remote("GitHub CLI command failed.".to_string()));
         }
-        return Err(detail.to_string());
+        return Err(AppError::Remote(detail.to_string()));
     }
 
     let pull_requests: Vec<GitHubPullRequest> =
@@ -1430,11 +1678,11 @@ pub(crate) async fn get_github_pull_request_diff(
     workspace_id: String,
     pr_number: u64,
     state: State<'_, AppState>,
-) -> Result<Vec<GitHubPullRequestDiff>, String> {
+) -> AppResult<Vec<GitHubPullRequestDiff>> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
@@ -1464,9 +1712,9 @@ pub(crate) async fn get_github_pull_request_diff(
             stderr.trim()
         };
         if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
+            return Err(AppError::Remote("GitHub CLI command failed.".to_string()));
         }
-        return Err(detail.to_string());
+        return Err(AppError::Remote(detail.to_string()));
     }
 
     let diff_text = String::from_utf8_lossy(&output.stdout);
@@ -1478,11 +1726,11 @@ pub(crate) async fn get_github_pull_request_comments(
     workspace_id: String,
     pr_number: u64,
     state: State<'_, AppState>,
-) -> Result<Vec<GitHubPullRequestComment>, String> {
+) -> AppResult<Vec<GitHubPullRequestComment>> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
 
     let repo_root = resolve_git_root(&entry)?;
@@ -1508,9 +1756,9 @@ pub(crate) async fn get_github_pull_request_comments(
             stderr.trim()
         };
         if detail.is_empty() {
-            return Err("GitHub CLI command failed.".to_string());
+            return Err(AppError::Remote("GitHub CLI command failed.".to_string()));
         }
-        return Err(detail.to_string());
+        return Err(AppError::Remote(detail.to_string()));
     }
 
     let comments: Vec<GitHubPullRequestComment> =
@@ -1523,11 +1771,11 @@ pub(crate) async fn get_github_pull_request_comments(
 pub(crate) async fn list_git_branches(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> AppResult<serde_json::Value> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     let repo_root = resolve_git_root(&entry)?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
@@ -1558,15 +1806,15 @@ pub(crate) async fn checkout_git_branch(
     workspace_id: String,
     name: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     let repo_root = resolve_git_root(&entry)?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
-    checkout_branch(&repo, &name).map_err(|e| e.to_string())
+    checkout_branch(&repo, &name).map_err(|e| AppError::Internal(e.to_string()))
 }
 
 #[tauri::command]
@@ -1574,11 +1822,11 @@ pub(crate) async fn create_git_branch(
     workspace_id: String,
     name: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let workspaces = state.workspaces.lock().await;
     let entry = workspaces
         .get(&workspace_id)
-        .ok_or("workspace not found")?
+        .ok_or(AppError::WorkspaceNotFound)?
         .clone();
     let repo_root = resolve_git_root(&entry)?;
     let repo = Repository::open(&repo_root).map_err(|e| e.to_string())?;
@@ -1586,7 +1834,7 @@ pub(crate) async fn create_git_branch(
     let target = head.peel_to_commit().map_err(|e| e.to_string())?;
     repo.branch(&name, &target, false)
         .map_err(|e| e.to_string())?;
-    checkout_branch(&repo, &name).map_err(|e| e.to_string())
+    checkout_branch(&repo, &name).map_err(|e| AppError::Internal(e.to_string()))
 }
 
 #[cfg(test)]
@@ -1661,4 +1909,48 @@ mod tests {
         let paths = action_paths_for_file(&root, "b.txt");
         assert_eq!(paths, vec!["a.txt".to_string(), "b.txt".to_string()]);
     }
+
+    #[test]
+    fn pr_create_args_use_fill_without_title() {
+        let args = pr_create_args("owner/repo", "feat/login", None, None, Some("main"), true);
+        assert_eq!(
+            args,
+            vec![
+                "pr",
+                "create",
+                "--repo",
+                "owner/repo",
+                "--head",
+                "feat/login",
+                "--base",
+                "main",
+                "--fill",
+                "--draft",
+            ]
+        );
+
+        let args = pr_create_args(
+            "owner/repo",
+            "feat/login",
+            Some("Fix login redirect"),
+            None,
+            None,
+            false,
+        );
+        assert_eq!(
+            args,
+            vec![
+                "pr",
+                "create",
+                "--repo",
+                "owner/repo",
+                "--head",
+                "feat/login",
+                "--title",
+                "Fix login redirect",
+                "--body",
+                "",
+            ]
+        );
+    }
 }
@@ -0,0 +1,571 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::JoinSet;
+use tokio::time::timeout;
+
+use crate::local_usage::local_usage_snapshot_core;
+use crate::shared::connection_role_core::tokens_match;
+use crate::shared::event_replay_core::SequencedEvent;
+use crate::shared::{codex_core, workspaces_core};
+use crate::state::AppState;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REQUEST_HEAD_BYTES: u64 = 16 * 1024;
+const EVENT_KEEPALIVE: Duration = Duration::from_secs(15);
+const EVENT_BUFFER: usize = 512;
+/// Pause after a failed `accept()`, so running out of file descriptors
+/// doesn't turn the accept loop into a busy loop.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+fn event_stream() -> &'static broadcast::Sender<SequencedEvent> {
+    static EVENTS: OnceLock<broadcast::Sender<SequencedEvent>> = OnceLock::new();
+    EVENTS.get_or_init(|| broadcast::channel(EVENT_BUFFER).0)
+}
+
+/// Forwards `event` to open `/api/events` streams.
+pub(crate) fn publish(event: &SequencedEvent) {
+    let events = event_stream();
+    if events.receiver_count() > 0 {
+        let _ = events.send(event.clone());
+    }
+}
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    /// Decoded path segments, so an escaped `/` stays inside its segment.
+    segments: Vec<String>,
+    query: HashMap<String, String>,
+    bearer: Option<String>,
+}
+
+impl Request {
+    fn token(&self) -> Option<&str> {
+        self.bearer
+            .as_deref()
+            .or_else(|| self.query.get("token").map(String::as_str))
+    }
+}
+
+fn hex_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'0'..=b'9' => Some(byte - b'0'),
+        b'a'..=b'f' => Some(byte - b'a' + 10),
+        b'A'..=b'F' => Some(byte - b'A' + 10),
+        _ => None,
+    }
+}
+
+/// Decodes `%XX` escapes, where both `X` must be hex digits, keeping
+/// malformed ones as they are. `+` means a space only in query strings, so
+/// only those pass `plus_as_space`.
+fn percent_decode(value: &str, plus_as_space: bool) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' if plus_as_space => decoded.push(b' '),
+            b'%' if index + 2 < bytes.len() => {
+                match (hex_value(bytes[index + 1]), hex_value(bytes[index + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push(high << 4 | low);
+                        index += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Parses the request line and headers; the body, if any, is ignored.
+///
+/// The API is a handful of token-protected GET routes on loopback, so the
+/// request head is parsed here instead of bringing an HTTP framework into
+/// the app: only the request line and the `Authorization` header are used,
+/// the head is capped at `MAX_REQUEST_HEAD_BYTES` and must arrive within
+/// `REQUEST_TIMEOUT`, and every response closes the connection. The tests
+/// below feed it mutated and random input.
+fn parse_request(head: &str) -> Option<Request> {
+    let mut lines = head.lines();
+    let mut request_line = lines.next()?.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let target = request_line.next()?;
+    let version = request_line.next()?;
+    if !target.starts_with('/') || !version.starts_with("HTTP/") || request_line.next().is_some() {
+        return None;
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .map(|segment| percent_decode(segment, false))
+        .collect();
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (percent_decode(key, true), percent_decode(value, true))
+        })
+        .collect();
+    let bearer = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .map(|token| token.trim().to_string());
+    Some(Request {
+        method,
+        segments,
+        query,
+        bearer,
+    })
+}
+
+async fn read_request<R: AsyncRead + Unpin>(stream: R) -> Result<Request, String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD_BYTES));
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        let read = reader
+            .read_line(&mut line)
+            .await
+            .map_err(|err| err.to_string())?;
+        if read == 0 {
+            if reader.get_ref().limit() == 0 {
+                return Err("Request head too large".to_string());
+            }
+            return Err("Request head incomplete".to_string());
+        }
+        if line.trim_end().is_empty() {
+            break;
+        }
+        head.push_str(&line);
+    }
+    parse_request(&head).ok_or_else(|| "Malformed request line".to_string())
+}
+
+async fn write_json(stream: &mut TcpStream, status: u16, body: &Value) -> std::io::Result<()> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn to_response<T: serde::Serialize>(result: Result<T, String>) -> (u16, Value) {
+    match result.and_then(|value| serde_json::to_value(value).map_err(|err| err.to_string())) {
+        Ok(value) => (200, value),
+        Err(error) => (500, json!({ "error": error })),
+    }
+}
+
+async fn running_turns(state: &AppState) -> Vec<Value> {
+    let sessions = state.sessions.lock().await;
+    let mut turns = Vec::new();
+    for (workspace_id, session) in sessions.iter() {
+        for (thread_id, turn_id) in session.turn_hooks.running_turns() {
+            turns.push(json!({
+                "workspaceId": workspace_id,
+                "threadId": thread_id,
+                "turnId": turn_id,
+            }));
+        }
+    }
+    turns
+}
+
+async fn route(app: &AppHandle, request: &Request) -> (u16, Value) {
+    let state = app.state::<AppState>();
+    let segments: Vec<&str> = request.segments.iter().map(String::as_str).collect();
+    match segments.as_slice() {
+        ["api", "workspaces"] => (
+            200,
+            json!(workspaces_core::list_workspaces_core(&state.workspaces, &state.sessions).await),
+        ),
+        ["api", "workspaces", workspace_id, "threads"] => {
+            let limit = request
+                .query
+                .get("limit")
+                .and_then(|value| value.parse().ok());
+            let result = codex_core::list_threads_core(
+                &state.sessions,
+                workspace_id.to_string(),
+                request.query.get("cursor").cloned(),
+                limit,
+                request.query.get("sortKey").cloned(),
+                request.query.get("archived").map(|value| value == "true"),
+            )
+            .await;
+            to_response(result)
+        }
+        ["api", "turns"] => (200, json!(running_turns(&state).await)),
+        ["api", "usage"] => {
+            let days = request
+                .query
+                .get("days")
+                .and_then(|value| value.parse().ok());
+            let result = local_usage_snapshot_core(
+                &state.workspaces,
+                days,
+                request.query.get("workspacePath").cloned(),
+                request.query.get("cliType").cloned(),
+            )
+            .await;
+            to_response(result)
+        }
+        _ => (404, json!({ "error": "Not found" })),
+    }
+}
+
+/// Streams every app-server event as `id: <seq>` / `data: <json>` until the
+/// client disconnects, with a comment line whenever the stream is idle.
+async fn stream_events(stream: &mut TcpStream) -> std::io::Result<()> {
+    let mut events = event_stream().subscribe();
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\nConnection: keep-alive\r\n\r\n",
+        )
+        .await?;
+    loop {
+        let chunk = match timeout(EVENT_KEEPALIVE, events.recv()).await {
+            Err(_) => ": keepalive\n\n".to_string(),
+            Ok(Ok(event)) => match serde_json::to_string(&event) {
+                Ok(data) => format!("id: {}\ndata: {data}\n\n", event.seq),
+                Err(_) => continue,
+            },
+            Ok(Err(RecvError::Lagged(skipped))) => format!("event: lagged\ndata: {skipped}\n\n"),
+            Ok(Err(RecvError::Closed)) => return Ok(()),
+        };
+        stream.write_all(chunk.as_bytes()).await?;
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, app: &AppHandle, token: &str) {
+    let request = match timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await {
+        Ok(Ok(request)) => request,
+        Ok(Err(error)) => {
+            let _ = write_json(&mut stream, 400, &json!({ "error": error })).await;
+            return;
+        }
+        Err(_) => return,
+    };
+    if !request
+        .token()
+        .is_some_and(|provided| tokens_match(token, provided))
+    {
+        let _ = write_json(&mut stream, 401, &json!({ "error": "Unauthorized" })).await;
+        return;
+    }
+    if request.method != "GET" {
+        let _ = write_json(
+            &mut stream,
+            405,
+            &json!({ "error": "Only GET is supported" }),
+        )
+        .await;
+        return;
+    }
+    if request.segments == ["api", "events"] {
+        let _ = stream_events(&mut stream).await;
+        return;
+    }
+    let (status, body) = route(app, &request).await;
+    let _ = write_json(&mut stream, status, &body).await;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ListenConfig {
+    port: u16,
+    token: String,
+}
+
+/// Where and with which token to serve, or `None` while the API is off.
+async fn listen_config(state: &AppState) -> Option<ListenConfig> {
+    let settings = state.app_settings.lock().await;
+    if !settings.http_api_enabled {
+        return None;
+    }
+    let token = settings
+        .http_api_token
+        .clone()
+        .filter(|token| !token.trim().is_empty());
+    match token {
+        Some(token) => Some(ListenConfig {
+            port: settings.http_api_port,
+            token,
+        }),
+        None => {
+            tracing::warn!("HTTP API is enabled without a token; not starting it");
+            None
+        }
+    }
+}
+
+/// Waits for a settings change that affects the API and returns the new
+/// config.
+async fn config_change(state: &AppState, current: &Option<ListenConfig>) -> Option<ListenConfig> {
+    loop {
+        state.http_api_settings_changed.notified().await;
+        let next = listen_config(state).await;
+        if next != *current {
+            return next;
+        }
+    }
+}
+
+/// Accepts connections until the listener can't be opened. Dropping the
+/// future closes the listener and every open connection with it.
+async fn listen(app: &AppHandle, config: &ListenConfig) {
+    let port = config.port;
+    let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::warn!("HTTP API could not listen on 127.0.0.1:{port}: {err}");
+            return;
+        }
+    };
+    tracing::info!("HTTP API listening on 127.0.0.1:{port}");
+    let mut connections = JoinSet::new();
+    loop {
+        while connections.try_join_next().is_some() {}
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                tracing::warn!("HTTP API failed to accept a connection: {err}");
+                tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                continue;
+            }
+        };
+        let app = app.clone();
+        let token = config.token.clone();
+        connections.spawn(async move {
+            handle_connection(stream, &app, &token).await;
+        });
+    }
+}
+
+/// Tells `serve` to pick up changed HTTP API settings.
+pub(crate) fn settings_changed(state: &AppState) {
+    state.http_api_settings_changed.notify_one();
+}
+
+/// Serves the read-only HTTP API on `127.0.0.1:<httpApiPort>` while
+/// `httpApiEnabled` is set, for the lifetime of the app. Changing the
+/// enabled flag, port or token restarts the listener and drops open
+/// connections, including event streams authenticated with the old token.
+pub(crate) async fn serve(app: AppHandle) {
+    let state = app.state::<AppState>();
+    let mut config = listen_config(&state).await;
+    loop {
+        config = match &config {
+            Some(current) => {
+                tokio::select! {
+                    next = config_change(&state, &config) => next,
+                    () = listen(&app, current) => config_change(&state, &config).await,
+                }
+            }
+            None => config_change(&state, &config).await,
+        };
+        if config.is_none() {
+            tracing::info!("HTTP API stopped");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_request, percent_decode, read_request};
+
+    #[test]
+    fn parses_path_query_and_bearer_token() {
+        let request = parse_request(
+            "GET /api/usage?days=7&workspacePath=%2Ftmp%2Fmy+repo HTTP/1.1\r\nHost: 127.0.0.1\r\nauthorization: Bearer secret\r\n",
+        )
+        .expect("request");
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.segments, ["api", "usage"]);
+        assert_eq!(request.query.get("days").map(String::as_str), Some("7"));
+        assert_eq!(
+            request.query.get("workspacePath").map(String::as_str),
+            Some("/tmp/my repo")
+        );
+        assert_eq!(request.token(), Some("secret"));
+
+        let request = parse_request("GET /api/events?token=abc HTTP/1.1\r\n").expect("request");
+        assert_eq!(request.token(), Some("abc"));
+        assert!(parse_request("").is_none());
+    }
+
+    #[test]
+    fn decodes_plus_as_space_only_in_the_query() {
+        let request =
+            parse_request("GET /api/workspaces/ws+1%2Fa/threads?cursor=a+b%2B HTTP/1.1\r\n")
+                .expect("request");
+        assert_eq!(request.segments, ["api", "workspaces", "ws+1/a", "threads"]);
+        assert_eq!(
+            request.query.get("cursor").map(String::as_str),
+            Some("a b+")
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_request_lines() {
+        for line in [
+            "GET\r\n",
+            "GET /api/turns\r\n",
+            "GET api/turns HTTP/1.1\r\n",
+            "GET /api/turns FTP/1.0\r\n",
+            "GET /api/turns HTTP/1.1 extra\r\n",
+        ] {
+            assert!(parse_request(line).is_none(), "{line:?}");
+        }
+    }
+
+    #[test]
+    fn keeps_malformed_percent_escapes() {
+        assert_eq!(percent_decode("100%", true), "100%");
+        assert_eq!(percent_decode("%zz%41", true), "%zzA");
+        assert_eq!(percent_decode("%+1%-1%4", false), "%+1%-1%4");
+        assert_eq!(percent_decode("%+1", true), "% 1");
+    }
+
+    /// xorshift64, so the fuzz tests are repeatable without a dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, bound: usize) -> usize {
+            (self.next() % bound as u64) as usize
+        }
+    }
+
+    /// Request heads with bytes inserted, deleted, replaced and duplicated,
+    /// biased towards the characters the parser cares about.
+    fn mutated_heads(rng: &mut Rng, count: usize) -> Vec<Vec<u8>> {
+        const SEEDS: &[&str] = &[
+            "GET /api/usage?days=7&workspacePath=%2Ftmp%2Fmy+repo HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+            "GET /api/workspaces/ws%2F1/threads?cursor=a+b&limit=5 HTTP/1.1\r\nHost: x\r\n\r\n",
+            "GET /api/events?token=abc HTTP/1.1\r\n\r\n",
+        ];
+        const INTERESTING: &[u8] = b"%+?&=/:\r\n \t0fF\x00\xc3\xff";
+        (0..count)
+            .map(|_| {
+                let mut head = SEEDS[rng.below(SEEDS.len())].as_bytes().to_vec();
+                for _ in 0..=rng.below(8) {
+                    let at = rng.below(head.len() + 1);
+                    let byte = if rng.below(2) == 0 {
+                        INTERESTING[rng.below(INTERESTING.len())]
+                    } else {
+                        rng.next() as u8
+                    };
+                    match rng.below(4) {
+                        0 => head.insert(at, byte),
+                        1 if at < head.len() => {
+                            head.remove(at);
+                        }
+                        2 if at < head.len() => head[at] = byte,
+                        _ => {
+                            let end = (at + rng.below(16)).min(head.len());
+                            let copy = head[at..end].to_vec();
+                            head.splice(at..at, copy);
+                        }
+                    }
+                }
+                head
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_mutated_and_random_heads_without_panicking() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let mut inputs = mutated_heads(&mut rng, 20_000);
+        inputs.extend((0..5_000).map(|_| {
+            let len = rng.below(256);
+            (0..len).map(|_| rng.next() as u8).collect::<Vec<_>>()
+        }));
+        for input in inputs {
+            let head = String::from_utf8_lossy(&input);
+            if let Some(request) = parse_request(&head) {
+                assert!(!request.method.is_empty(), "{head:?}");
+                assert!(!request.segments.is_empty(), "{head:?}");
+            }
+            percent_decode(&head, true);
+            percent_decode(&head, false);
+        }
+    }
+
+    #[test]
+    fn percent_decoding_round_trips_encoded_text() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        for _ in 0..5_000 {
+            let text = (0..rng.below(32))
+                .filter_map(|_| char::from_u32(rng.below(0x1_0000) as u32))
+                .collect::<String>();
+            let encoded = text
+                .bytes()
+                .map(|byte| format!("%{byte:02X}"))
+                .collect::<String>();
+            assert_eq!(percent_decode(&encoded, true), text);
+            assert_eq!(percent_decode(&encoded.to_lowercase(), false), text);
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_mutated_heads_without_panicking() {
+        let mut rng = Rng(0xd1b5_4a32_d192_ed03);
+        for input in mutated_heads(&mut rng, 2_000) {
+            let _ = read_request(input.as_slice()).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn reads_request_heads_up_to_the_size_limit() {
+        let request = read_request("GET /api/turns HTTP/1.1\r\nHost: x\r\n\r\nbody".as_bytes())
+            .await
+            .expect("request");
+        assert_eq!(request.segments, ["api", "turns"]);
+
+        let incomplete = read_request("GET /api/turns HTTP/1.1\r\nHost: x\r\n".as_bytes()).await;
+        assert_eq!(incomplete.unwrap_err(), "Request head incomplete");
+
+        let oversized = format!(
+            "GET /api/turns HTTP/1.1\r\nX-Padding: {}\r\n\r\n",
+            "a".repeat(20 * 1024)
+        );
+        let oversized = read_request(oversized.as_bytes()).await;
+        assert_eq!(oversized.unwrap_err(), "Request head too large");
+
+        let malformed = read_request("NONSENSE\r\n\r\n".as_bytes()).await;
+        assert_eq!(malformed.unwrap_err(), "Malformed request line");
+    }
+}
@@ -1,15 +1,19 @@
-use tauri::Manager;
-#[cfg(target_os = "macos")]
-use tauri::{RunEvent, WindowEvent};
+use tauri::{Manager, RunEvent, WindowEvent};
 
 mod backend;
+mod claude;
 mod codex;
 mod files;
 mod dictation;
+mod event_replay;
 mod event_sink;
 mod git;
 mod git_utils;
+mod http_api;
 mod local_usage;
+mod logging;
+mod maintenance;
+mod mcp_servers;
 mod menu;
 mod notifications;
 mod prompts;
@@ -19,10 +23,13 @@ mod settings;
 mod state;
 mod storage;
 mod shared;
+mod shutdown;
 mod terminal;
+mod tray;
 mod types;
 mod utils;
 mod window;
+mod workflows;
 mod workspaces;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -38,25 +45,49 @@ pub fn run() {
     let builder = tauri::Builder::default()
         .enable_macos_default_menu(false)
         .manage(menu::MenuItemRegistry::<tauri::Wry>::default())
+        .manage(std::sync::Mutex::new(tray::TrayStatus::default()))
         .menu(menu::build_menu)
         .on_menu_event(menu::handle_menu_event)
-        .on_window_event(|window, _event| {
+        .on_window_event(|window, event| {
             if window.label() != "main" {
                 return;
             }
-            #[cfg(target_os = "macos")]
-            if let WindowEvent::CloseRequested { api, .. } = _event {
-                api.prevent_close();
-                let _ = window.hide();
+            if let WindowEvent::CloseRequested { api, .. } = event {
+                if tray::hides_on_close(window.app_handle()) {
+                    api.prevent_close();
+                    let _ = window.hide();
+                }
             }
         })
         .setup(|app| {
+            if let Ok(data_dir) = app.path().app_data_dir() {
+                if let Err(err) = shared::logging_core::init_logging(data_dir.join("logs")) {
+                    eprintln!("failed to initialize logging: {err}");
+                }
+            }
             let state = state::AppState::load(&app.handle());
+            let maintenance = std::sync::Arc::clone(&state.maintenance);
             app.manage(state);
+            tauri::async_runtime::spawn(
+                maintenance.run(event_sink::TauriEventSink::new(app.handle().clone())),
+            );
+            let handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = handle.state::<state::AppState>();
+                backend::process_monitor::run_process_monitor(
+                    &state.sessions,
+                    event_sink::TauriEventSink::new(handle.clone()),
+                )
+                .await;
+            });
+            tauri::async_runtime::spawn(http_api::serve(app.handle().clone()));
             #[cfg(desktop)]
             {
                 app.handle()
                     .plugin(tauri_plugin_updater::Builder::new().build())?;
+                if let Err(err) = tray::init_tray(app.handle()) {
+                    tracing::warn!("failed to create the tray icon: {err}");
+                }
             }
             Ok(())
         });
@@ -74,22 +105,75 @@ pub fn run() {
             settings::get_app_settings,
             settings::update_app_settings,
             settings::get_codex_config_path,
+            settings::codex_config_validate,
+            settings::codex_profiles_list,
+            settings::codex_profile_apply,
+            settings::codex_profile_save_current,
             settings::detect_installed_clis,
+            settings::cli_plugins_list,
+            settings::cli_updates_check,
+            settings::cli_update,
+            settings::account_profiles_list,
+            settings::account_profile_create,
+            settings::account_profile_delete,
+            settings::app_state_export,
+            settings::app_state_import,
+            settings::audit_log_query,
+            settings::settings_profiles_list,
+            settings::settings_profile_save,
+            settings::settings_profile_delete,
+            settings::settings_profile_switch,
+            settings::remote_backend_test_connection,
             files::file_read,
             files::file_write,
+            files::workspace_file_read,
+            files::file_read_range,
+            files::file_tail_subscribe,
+            files::file_tail_unsubscribe,
+            files::workspace_file_write,
+            files::workspace_tree,
+            files::agents_md_sections_list,
+            files::agents_md_section_update,
             files::agent_profiles_list,
             files::agent_profile_apply,
+            files::agent_profile_status,
+            files::agent_profile_sync,
+            files::agent_profile_save,
             codex::get_config_model,
             menu::menu_set_accelerators,
             codex::codex_doctor,
+            maintenance::maintenance_status,
+            event_replay::events_replay,
+            logging::open_log_dir,
+            logging::log_tail,
+            mcp_servers::mcp_servers_list,
+            mcp_servers::mcp_server_add,
+            mcp_servers::mcp_server_remove,
+            mcp_servers::mcp_server_toggle,
+            claude::get_claude_settings,
+            claude::update_claude_settings,
             workspaces::list_workspaces,
             workspaces::is_workspace_path_dir,
             workspaces::add_workspace,
             workspaces::add_clone,
+            workspaces::workspace_templates_list,
+            workspaces::workspace_template_save,
+            workspaces::workspace_template_delete,
+            workflows::workflows_list,
+            workflows::workflow_save,
+            workflows::workflow_delete,
+            workflows::workflow_run,
+            workspaces::workspace_create_from_template,
+            workspaces::workspaces_discover,
             workspaces::add_worktree,
+            workspaces::worktree_create,
+            workspaces::worktree_list,
+            workspaces::worktree_remove,
             workspaces::worktree_setup_status,
             workspaces::worktree_setup_mark_ran,
             workspaces::remove_workspace,
+            workspaces::workspace_trash_list,
+            workspaces::workspace_restore,
             workspaces::remove_worktree,
             workspaces::rename_worktree,
             workspaces::rename_worktree_upstream,
@@ -97,20 +181,36 @@ pub fn run() {
             workspaces::update_workspace_settings,
             workspaces::update_workspace_cli_bin,
             workspaces::update_workspace_codex_bin,
+            workspaces::workspace_relocate,
             codex::start_thread,
             codex::send_user_message,
             codex::turn_interrupt,
+            codex::request_cancel,
+            codex::pending_requests,
+            codex::background_callbacks,
+            codex::turn_rollback,
+            codex::turn_metrics_list,
+            codex::workspace_budget_status,
+            codex::workspace_budget_override,
+            codex::activity_timeline,
+            codex::patch_apply,
             codex::start_review,
             codex::respond_to_server_request,
             codex::remember_approval_rule,
             codex::get_commit_message_prompt,
             codex::generate_commit_message,
             codex::generate_run_metadata,
+            codex::background_turn_start,
+            codex::background_turn_result,
             codex::resume_thread,
             codex::fork_thread,
             codex::list_threads,
+            codex::codex_sessions_list,
+            codex::codex_session_read,
             codex::list_mcp_server_status,
             codex::archive_thread,
+            codex::unarchive_thread,
+            codex::delete_thread,
             codex::compact_thread,
             codex::set_thread_name,
             codex::collaboration_mode_list,
@@ -128,6 +228,7 @@ pub fn run() {
             git::revert_git_all,
             git::commit_git,
             git::push_git,
+            git::git_pr_create,
             git::pull_git,
             git::fetch_git,
             git::sync_git,
@@ -175,10 +276,15 @@ pub fn run() {
         .build(tauri::generate_context!())
         .expect("error while running tauri application");
 
-    app.run(|_app_handle, _event| {
+    app.run(|app_handle, event| {
+        if let RunEvent::ExitRequested { api, .. } = &event {
+            if shutdown::hold_exit_for_shutdown(app_handle) {
+                api.prevent_exit();
+            }
+        }
         #[cfg(target_os = "macos")]
-        if let RunEvent::Reopen { .. } = _event {
-            if let Some(window) = _app_handle.get_webview_window("main") {
+        if let RunEvent::Reopen { .. } = event {
+            if let Some(window) = app_handle.get_webview_window("main") {
                 let _ = window.show();
                 let _ = window.set_focus();
             }
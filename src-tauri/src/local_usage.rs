@@ -6,6 +6,7 @@ use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::State;
+use tokio::sync::Mutex;
 
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::state::AppState;
@@ -59,6 +60,15 @@ pub(crate) async fn local_usage_snapshot(
     workspace_path: Option<String>,
     cli_type: Option<String>,
     state: State<'_, AppState>,
+) -> Result<LocalUsageSnapshot, String> {
+    local_usage_snapshot_core(&state.workspaces, days, workspace_path, cli_type).await
+}
+
+pub(crate) async fn local_usage_snapshot_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    days: Option<u32>,
+    workspace_path: Option<String>,
+    cli_type: Option<String>,
 ) -> Result<LocalUsageSnapshot, String> {
     let days = days.unwrap_or(30).clamp(1, 90);
     let workspace_path = workspace_path.and_then(|value| {
@@ -71,7 +81,7 @@ pub(crate) async fn local_usage_snapshot(
     });
     let cli_filter = CliFilter::parse(cli_type);
     let sessions_roots = {
-        let workspaces = state.workspaces.lock().await;
+        let workspaces = workspaces.lock().await;
         resolve_sessions_roots(&workspaces, workspace_path.as_deref())
     };
     let snapshot = tokio::task::spawn_blocking(move || {
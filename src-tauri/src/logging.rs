@@ -0,0 +1,42 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+use tauri_plugin_opener::OpenerExt;
+
+use crate::remote_backend;
+use crate::shared::logging_core;
+use crate::state::AppState;
+
+const DEFAULT_LOG_TAIL_LINES: usize = 200;
+
+/// Opens the local log directory in the system file browser.
+#[tauri::command]
+pub(crate) async fn open_log_dir(app: AppHandle) -> Result<(), String> {
+    let dir = logging_core::log_dir()?;
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|err| err.to_string())
+}
+
+/// Returns the last `n` lines of a workspace session log, or of the app log
+/// when `workspace_id` is omitted.
+#[tauri::command]
+pub(crate) async fn log_tail(
+    workspace_id: Option<String>,
+    n: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<String>, String> {
+    let lines = n.unwrap_or(DEFAULT_LOG_TAIL_LINES);
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "log_tail",
+            json!({ "workspaceId": workspace_id, "lines": lines }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    logging_core::log_tail_core(&logging_core::log_dir()?, workspace_id.as_deref(), lines)
+}
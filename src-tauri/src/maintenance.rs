@@ -0,0 +1,20 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::shared::maintenance_core::MaintenanceStatus;
+use crate::state::AppState;
+
+#[tauri::command]
+pub(crate) async fn maintenance_status(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<MaintenanceStatus, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "maintenance_status", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    Ok(state.maintenance.status())
+}
@@ -0,0 +1,80 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::remote_backend;
+use crate::shared::mcp_servers_core;
+use crate::state::AppState;
+use crate::types::McpServer;
+
+/// Lists MCP servers from the Codex `config.toml` and Gemini `settings.json`.
+#[tauri::command]
+pub(crate) async fn mcp_servers_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<McpServer>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "mcp_servers_list", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    mcp_servers_core::mcp_servers_list_core()
+}
+
+/// Adds (or replaces) a server in both configs.
+#[tauri::command]
+pub(crate) async fn mcp_server_add(
+    server: McpServer,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<McpServer>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "mcp_server_add",
+            json!({ "server": server }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    mcp_servers_core::mcp_server_add_core(server, &state.services.audit_log)
+}
+
+#[tauri::command]
+pub(crate) async fn mcp_server_remove(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<McpServer>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "mcp_server_remove", json!({ "name": name }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    mcp_servers_core::mcp_server_remove_core(&name, &state.services.audit_log)
+}
+
+#[tauri::command]
+pub(crate) async fn mcp_server_toggle(
+    name: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<McpServer>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "mcp_server_toggle",
+            json!({ "name": name, "enabled": enabled }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    mcp_servers_core::mcp_server_toggle_core(&name, enabled, &state.services.audit_log)
+}
@@ -1,18 +1,37 @@
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
 
-use tauri::{AppHandle, Emitter};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::net::TcpStream;
 use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
 
+use crate::backend::events::{AppEvent, AppServerEvent};
+use crate::shared::connection_role_core::{self, ConnectionRole};
 use crate::state::AppState;
-use crate::types::BackendMode;
+use crate::types::{AppSettings, BackendMode};
 
 const DEFAULT_REMOTE_HOST: &str = "127.0.0.1:4732";
 const DISCONNECTED_MESSAGE: &str = "remote backend disconnected";
+const CALL_CONNECT_ATTEMPTS: u32 = 3;
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(500);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+const MAX_QUEUED_CALLS: usize = 200;
+/// Writes that can be replayed after a reconnect without the caller waiting
+/// on the remote result.
+const QUEUEABLE_METHODS: &[&str] = &[
+    "file_write",
+    "workspace_file_write",
+    "update_workspace_settings",
+];
 
 type PendingMap = HashMap<u64, oneshot::Sender<Result<Value, String>>>;
 
@@ -60,13 +79,24 @@ struct RemoteBackendInner {
     pending: Arc<Mutex<PendingMap>>,
     next_id: AtomicU64,
     connected: Arc<AtomicBool>,
+    /// The role the daemon granted at auth; operator until then.
+    role: OnceLock<ConnectionRole>,
 }
 
 impl RemoteBackend {
+    fn is_connected(&self) -> bool {
+        self.inner.connected.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn role(&self) -> ConnectionRole {
+        self.inner.role.get().copied().unwrap_or_default()
+    }
+
     pub(crate) async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
         if !self.inner.connected.load(Ordering::SeqCst) {
             return Err(DISCONNECTED_MESSAGE.to_string());
         }
+        connection_role_core::check_method_allowed(self.role(), method)?;
 
         let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
         let (tx, rx) = oneshot::channel();
@@ -99,14 +129,341 @@ pub(crate) async fn call_remote(
     method: &str,
     params: Value,
 ) -> Result<Value, String> {
-    let client = ensure_remote_backend(state, app).await?;
-    match client.call(method, params).await {
-        Ok(value) => Ok(value),
-        Err(err) => {
-            *state.remote_backend.lock().await = None;
-            Err(err)
+    match call_with_reconnect(state, app, method, &params).await {
+        Ok(value) => {
+            state
+                .remote_session
+                .lock()
+                .await
+                .record_success(method, &params, &value);
+            Ok(value)
+        }
+        Err(RemoteCallError::Offline(err) | RemoteCallError::Failed(err)) => Err(err),
+    }
+}
+
+/// Like `call_remote`, but queues queueable writes while the backend is
+/// unreachable and returns `Ok(None)`. The queue is flushed, in order, as soon
+/// as a connection is re-established.
+pub(crate) async fn call_remote_or_queue(
+    state: &AppState,
+    app: AppHandle,
+    method: &str,
+    params: Value,
+) -> Result<Option<Value>, String> {
+    match call_with_reconnect(state, app.clone(), method, &params).await {
+        Ok(value) => {
+            state
+                .remote_session
+                .lock()
+                .await
+                .record_success(method, &params, &value);
+            Ok(Some(value))
+        }
+        Err(RemoteCallError::Offline(err)) if QUEUEABLE_METHODS.contains(&method) => {
+            let queued = state.remote_session.lock().await.enqueue(method, params);
+            emit_connection_status(&app, "offline", queued, Some(err.as_str()));
+            schedule_reconnect(app);
+            Ok(None)
+        }
+        Err(RemoteCallError::Offline(err) | RemoteCallError::Failed(err)) => Err(err),
+    }
+}
+
+/// Last known `WorkspaceInfo` for a workspace with `settings` applied, used
+/// to answer a settings write that was queued while offline.
+pub(crate) async fn cached_workspace_with_settings(
+    state: &AppState,
+    workspace_id: &str,
+    settings: Value,
+) -> Option<Value> {
+    let session = state.remote_session.lock().await;
+    let mut workspace = session.workspaces.get(workspace_id)?.clone();
+    workspace["settings"] = settings;
+    Some(workspace)
+}
+
+enum RemoteCallError {
+    /// The request never reached the backend or the connection dropped
+    /// before a response arrived.
+    Offline(String),
+    /// The backend answered with an error.
+    Failed(String),
+}
+
+/// Retries the connection with backoff before giving up. A request is only
+/// sent once: if the connection drops mid-call it is reported as offline
+/// rather than re-sent, since the backend may already have applied it.
+async fn call_with_reconnect(
+    state: &AppState,
+    app: AppHandle,
+    method: &str,
+    params: &Value,
+) -> Result<Value, RemoteCallError> {
+    let mut delay = RECONNECT_INITIAL_DELAY;
+    let mut last_error = DISCONNECTED_MESSAGE.to_string();
+    for attempt in 0..CALL_CONNECT_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(delay).await;
+            delay = next_reconnect_delay(delay);
+        }
+        let client = match ensure_remote_backend(state, app.clone()).await {
+            Ok(client) => client,
+            Err(err) => {
+                last_error = err;
+                continue;
+            }
+        };
+        return match client.call(method, params.clone()).await {
+            Ok(value) => Ok(value),
+            Err(err) if !client.is_connected() => {
+                clear_disconnected_backend(state).await;
+                Err(RemoteCallError::Offline(err))
+            }
+            Err(err) => Err(RemoteCallError::Failed(err)),
+        };
+    }
+    Err(RemoteCallError::Offline(last_error))
+}
+
+fn next_reconnect_delay(delay: Duration) -> Duration {
+    (delay * 2).min(RECONNECT_MAX_DELAY)
+}
+
+struct QueuedCall {
+    key: String,
+    method: String,
+    params: Value,
+}
+
+/// Client-side view of the remote session that has to survive reconnects:
+/// workspaces whose event streams should be resumed, the last workspace list,
+/// and writes made while offline.
+#[derive(Default)]
+pub(crate) struct RemoteSessionState {
+    connected_workspaces: HashSet<String>,
+    workspaces: HashMap<String, Value>,
+    queue: VecDeque<QueuedCall>,
+    reconnecting: bool,
+}
+
+impl RemoteSessionState {
+    fn record_success(&mut self, method: &str, params: &Value, result: &Value) {
+        let workspace_id = params.get("id").and_then(|value| value.as_str());
+        match (method, workspace_id) {
+            ("connect_workspace", Some(id)) => {
+                self.connected_workspaces.insert(id.to_string());
+            }
+            ("remove_workspace", Some(id)) => {
+                self.connected_workspaces.remove(id);
+                self.workspaces.remove(id);
+            }
+            ("update_workspace_settings", Some(id)) => {
+                self.workspaces.insert(id.to_string(), result.clone());
+            }
+            ("list_workspaces", _) => {
+                if let Some(items) = result.as_array() {
+                    self.workspaces = items
+                        .iter()
+                        .filter_map(|item| {
+                            let id = item.get("id")?.as_str()?;
+                            Some((id.to_string(), item.clone()))
+                        })
+                        .collect();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Queues a write, replacing an earlier queued write to the same target
+    /// so only the latest content is replayed. Returns the queue length.
+    fn enqueue(&mut self, method: &str, params: Value) -> usize {
+        let key = queue_key(method, &params);
+        self.queue.retain(|call| call.key != key);
+        if self.queue.len() >= MAX_QUEUED_CALLS {
+            self.queue.pop_front();
+        }
+        self.queue.push_back(QueuedCall {
+            key,
+            method: method.to_string(),
+            params,
+        });
+        self.queue.len()
+    }
+}
+
+fn queue_key(method: &str, params: &Value) -> String {
+    let field = |name: &str| {
+        params
+            .get(name)
+            .and_then(|value| value.as_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    match method {
+        "file_write" => format!(
+            "{method}:{}:{}:{}",
+            field("scope"),
+            field("kind"),
+            field("workspaceId")
+        ),
+        "workspace_file_write" => {
+            format!("{method}:{}:{}", field("workspaceId"), field("path"))
+        }
+        _ => format!("{method}:{}", field("id")),
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct RemoteConnectionOptions {
+    pub(crate) host: String,
+    pub(crate) token: Option<String>,
+    pub(crate) tls: bool,
+    pub(crate) tls_ca_path: Option<String>,
+}
+
+impl RemoteConnectionOptions {
+    pub(crate) fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            host: settings.remote_backend_host.clone(),
+            token: settings.remote_backend_token.clone(),
+            tls: settings.remote_backend_tls,
+            tls_ca_path: settings.remote_backend_tls_ca_path.clone(),
+        }
+    }
+
+    fn resolved_host(&self) -> String {
+        if self.host.trim().is_empty() {
+            DEFAULT_REMOTE_HOST.to_string()
+        } else {
+            self.host.trim().to_string()
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct RemoteConnectionInfo {
+    pub(crate) host: String,
+    pub(crate) tls: bool,
+    pub(crate) authenticated: bool,
+    pub(crate) role: ConnectionRole,
+    pub(crate) server_version: Option<String>,
+    pub(crate) latency_ms: u64,
+}
+
+trait RemoteStream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> RemoteStream for T {}
+
+/// Drops any cached connection so the next call reconnects with the current
+/// host, token and TLS settings.
+pub(crate) async fn reset_remote_backend(state: &AppState) {
+    *state.remote_backend.lock().await = None;
+}
+
+/// Drops the cached connection only if it is the one that went away, so a
+/// connection opened concurrently by another call is kept.
+async fn clear_disconnected_backend(state: &AppState) {
+    let mut guard = state.remote_backend.lock().await;
+    if guard.as_ref().is_some_and(|client| !client.is_connected()) {
+        *guard = None;
+    }
+}
+
+fn emit_connection_status(app: &AppHandle, status: &str, queued: usize, error: Option<&str>) {
+    let _ = app.emit(
+        "app-server-event",
+        AppServerEvent::new(
+            String::new(),
+            AppEvent::RemoteConnection {
+                status: status.to_string(),
+                queued,
+                error: error.map(str::to_string),
+            },
+        ),
+    );
+}
+
+/// Reconnects in the background after the event stream drops, backing off
+/// up to `RECONNECT_MAX_DELAY`. Only one loop runs at a time, and it stops
+/// once the app leaves remote mode.
+fn schedule_reconnect(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        {
+            let mut session = state.remote_session.lock().await;
+            if session.reconnecting {
+                return;
+            }
+            session.reconnecting = true;
+        }
+        clear_disconnected_backend(&state).await;
+        let mut delay = RECONNECT_INITIAL_DELAY;
+        let mut attempts = 0u32;
+        while is_remote_mode(&state).await {
+            match ensure_remote_backend(&state, app.clone()).await {
+                Ok(_) => {
+                    if attempts > 0 {
+                        let queued = state.remote_session.lock().await.queue.len();
+                        emit_connection_status(&app, "connected", queued, None);
+                    }
+                    break;
+                }
+                Err(err) => {
+                    attempts += 1;
+                    if attempts == 1 {
+                        let queued = state.remote_session.lock().await.queue.len();
+                        emit_connection_status(&app, "reconnecting", queued, Some(err.as_str()));
+                    }
+                    tokio::time::sleep(delay).await;
+                    delay = next_reconnect_delay(delay);
+                }
+            }
+        }
+        state.remote_session.lock().await.reconnecting = false;
+    });
+}
+
+/// Resumes event streams for previously connected workspaces, then replays
+/// queued writes in order. Stops early if the new connection drops, keeping
+/// the unsent writes for the next attempt.
+async fn restore_remote_session(state: &AppState, client: &RemoteBackend) -> usize {
+    let (workspace_ids, mut queued) = {
+        let mut session = state.remote_session.lock().await;
+        let ids = session
+            .connected_workspaces
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>();
+        (ids, std::mem::take(&mut session.queue))
+    };
+    for id in workspace_ids {
+        if let Err(err) = client.call("connect_workspace", json!({ "id": id })).await {
+            tracing::warn!(workspace_id = %id, "remote backend: failed to resume workspace: {err}");
+        }
+    }
+    let mut flushed = 0;
+    while let Some(call) = queued.pop_front() {
+        match client.call(&call.method, call.params.clone()).await {
+            Ok(_) => flushed += 1,
+            Err(_) if !client.is_connected() => {
+                queued.push_front(call);
+                break;
+            }
+            Err(err) => {
+                tracing::warn!("remote backend: queued {} failed: {err}", call.method);
+            }
+        }
+    }
+    if !queued.is_empty() {
+        let mut session = state.remote_session.lock().await;
+        while let Some(call) = queued.pop_back() {
+            session.queue.push_front(call);
         }
     }
+    flushed
 }
 
 async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<RemoteBackend, String> {
@@ -117,24 +474,110 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
         }
     }
 
-    let (host, token) = {
+    let options = {
         let settings = state.app_settings.lock().await;
-        (
-            settings.remote_backend_host.clone(),
-            settings.remote_backend_token.clone(),
-        )
+        RemoteConnectionOptions::from_settings(&settings)
     };
+    let (client, _) = open_remote_backend(&options, Some(app.clone())).await?;
 
-    let resolved_host = if host.trim().is_empty() {
-        DEFAULT_REMOTE_HOST.to_string()
-    } else {
-        host
+    {
+        let mut guard = state.remote_backend.lock().await;
+        *guard = Some(client.clone());
+    }
+
+    let flushed = restore_remote_session(state, &client).await;
+    let queued = state.remote_session.lock().await.queue.len();
+    if flushed > 0 || queued > 0 {
+        emit_connection_status(&app, "connected", queued, None);
+    }
+
+    Ok(client)
+}
+
+/// Connects, authenticates and checks the server version without replacing
+/// the active connection.
+pub(crate) async fn test_remote_connection(
+    options: &RemoteConnectionOptions,
+) -> Result<RemoteConnectionInfo, String> {
+    let started = Instant::now();
+    let (client, auth_result) = open_remote_backend(options, None).await?;
+    let ping = client.call("ping", Value::Null).await?;
+    let server_version = ping
+        .get("version")
+        .or_else(|| auth_result.as_ref().and_then(|value| value.get("version")))
+        .and_then(|value| value.as_str())
+        .map(|value| value.to_string());
+    Ok(RemoteConnectionInfo {
+        host: options.resolved_host(),
+        tls: options.tls,
+        authenticated: auth_result.is_some(),
+        role: client.role(),
+        server_version,
+        latency_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+fn tls_server_name(host: &str) -> Result<ServerName<'static>, String> {
+    let name = match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|ch| ch.is_ascii_digit()) => name,
+        _ => host,
     };
+    let name = name.trim_start_matches('[').trim_end_matches(']');
+    ServerName::try_from(name.to_string())
+        .map_err(|err| format!("Invalid remote backend host {host}: {err}"))
+}
+
+fn tls_connector(ca_path: Option<&str>) -> Result<TlsConnector, String> {
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    if let Some(ca_path) = ca_path.map(str::trim).filter(|path| !path.is_empty()) {
+        let certs = CertificateDer::pem_file_iter(ca_path)
+            .map_err(|err| format!("Failed to read CA certificate {ca_path}: {err}"))?;
+        for cert in certs {
+            let cert =
+                cert.map_err(|err| format!("Failed to parse CA certificate {ca_path}: {err}"))?;
+            roots
+                .add(cert)
+                .map_err(|err| format!("Invalid CA certificate {ca_path}: {err}"))?;
+        }
+    }
+    let config = ClientConfig::builder_with_provider(Arc::new(
+        tokio_rustls::rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .map_err(|err| err.to_string())?
+    .with_root_certificates(roots)
+    .with_no_client_auth();
+    Ok(TlsConnector::from(Arc::new(config)))
+}
 
-    let stream = TcpStream::connect(resolved_host.clone())
+async fn connect_stream(
+    options: &RemoteConnectionOptions,
+) -> Result<Box<dyn RemoteStream>, String> {
+    let host = options.resolved_host();
+    let stream = TcpStream::connect(host.clone())
         .await
-        .map_err(|err| format!("Failed to connect to remote backend at {resolved_host}: {err}"))?;
-    let (reader, mut writer) = stream.into_split();
+        .map_err(|err| format!("Failed to connect to remote backend at {host}: {err}"))?;
+    if !options.tls {
+        return Ok(Box::new(stream));
+    }
+    let connector = tls_connector(options.tls_ca_path.as_deref())?;
+    let stream = connector
+        .connect(tls_server_name(&host)?, stream)
+        .await
+        .map_err(|err| format!("TLS handshake with {host} failed: {err}"))?;
+    Ok(Box::new(stream))
+}
+
+/// Opens a connection and runs the auth handshake. Events are forwarded to
+/// the UI only when an app handle is given. Returns the auth result when a
+/// token was sent.
+async fn open_remote_backend(
+    options: &RemoteConnectionOptions,
+    app: Option<AppHandle>,
+) -> Result<(RemoteBackend, Option<Value>), String> {
+    let stream = connect_stream(options).await?;
+    let (reader, mut writer) = tokio::io::split(stream);
 
     let (out_tx, mut out_rx) = mpsc::unbounded_channel::<String>();
     let pending = Arc::new(Mutex::new(PendingMap::new()));
@@ -144,11 +587,14 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
     let connected = Arc::new(AtomicBool::new(true));
     let connected_for_writer = Arc::clone(&connected);
     let connected_for_reader = Arc::clone(&connected);
+    let established = Arc::new(AtomicBool::new(false));
+    let established_for_reader = Arc::clone(&established);
 
     let write_task = tokio::spawn(async move {
         while let Some(message) = out_rx.recv().await {
             if writer.write_all(message.as_bytes()).await.is_err()
                 || writer.write_all(b"\n").await.is_err()
+                || writer.flush().await.is_err()
             {
                 connected_for_writer.store(false, Ordering::SeqCst);
                 let mut pending = pending_for_writer.lock().await;
@@ -158,17 +604,20 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
                 break;
             }
         }
+        let _ = writer.shutdown().await;
     });
 
-    let app_for_reader = app.clone();
     let read_task = tokio::spawn(async move {
         read_loop(
-            app_for_reader,
+            app.clone(),
             reader,
             pending_for_reader,
             connected_for_reader,
         )
         .await;
+        if let Some(app) = app.filter(|_| established_for_reader.load(Ordering::SeqCst)) {
+            schedule_reconnect(app);
+        }
     });
 
     let client = RemoteBackend {
@@ -177,29 +626,35 @@ async fn ensure_remote_backend(state: &AppState, app: AppHandle) -> Result<Remot
             pending,
             next_id: AtomicU64::new(1),
             connected,
+            role: OnceLock::new(),
         }),
     };
 
-    if let Some(token) = token {
-        client
-            .call("auth", json!({ "token": token }))
-            .await
-            .map(|_| ())?;
-    }
-
+    let auth_result = match options
+        .token
+        .as_deref()
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
     {
-        let mut guard = state.remote_backend.lock().await;
-        *guard = Some(client.clone());
-    }
+        Some(token) => Some(client.call("auth", json!({ "token": token })).await?),
+        None => None,
+    };
+    let role = auth_result
+        .as_ref()
+        .and_then(|result| result.get("role").cloned())
+        .and_then(|role| serde_json::from_value(role).ok())
+        .unwrap_or_default();
+    let _ = client.inner.role.set(role);
+    established.store(true, Ordering::SeqCst);
 
     drop((write_task, read_task));
 
-    Ok(client)
+    Ok((client, auth_result))
 }
 
-async fn read_loop(
-    app: AppHandle,
-    reader: tokio::net::tcp::OwnedReadHalf,
+async fn read_loop<R: AsyncRead + Unpin>(
+    app: Option<AppHandle>,
+    reader: R,
     pending: Arc<Mutex<PendingMap>>,
     connected: Arc<AtomicBool>,
 ) {
@@ -243,9 +698,13 @@ async fn read_loop(
         if method.is_empty() {
             continue;
         }
+        let Some(app) = app.as_ref() else {
+            continue;
+        };
         let params = message.get("params").cloned().unwrap_or(Value::Null);
         match method {
             "app-server-event" => {
+                crate::tray::observe_remote_event(app, &params);
                 let _ = app.emit("app-server-event", params);
             }
             "terminal-output" => {
@@ -264,3 +723,89 @@ async fn read_loop(
         let _ = sender.send(Err(DISCONNECTED_MESSAGE.to_string()));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{next_reconnect_delay, tls_server_name, RemoteSessionState, RECONNECT_MAX_DELAY};
+    use serde_json::json;
+    use std::time::Duration;
+
+    #[test]
+    fn tls_server_name_strips_port_and_brackets() {
+        assert_eq!(
+            tls_server_name("daemon.example.com:4732")
+                .expect("dns name")
+                .to_str(),
+            "daemon.example.com"
+        );
+        assert_eq!(
+            tls_server_name("127.0.0.1:4732").expect("ipv4").to_str(),
+            "127.0.0.1"
+        );
+        assert_eq!(tls_server_name("[::1]:4732").expect("ipv6").to_str(), "::1");
+        assert!(tls_server_name("bad host:4732").is_err());
+    }
+
+    #[test]
+    fn queued_writes_keep_only_latest_per_target() {
+        let mut session = RemoteSessionState::default();
+        let write = |content: &str| {
+            json!({
+                "scope": "workspace",
+                "kind": "agents",
+                "workspaceId": "ws-1",
+                "content": content,
+            })
+        };
+        assert_eq!(session.enqueue("file_write", write("first")), 1);
+        assert_eq!(
+            session.enqueue(
+                "update_workspace_settings",
+                json!({ "id": "ws-1", "settings": {} })
+            ),
+            2
+        );
+        assert_eq!(session.enqueue("file_write", write("second")), 2);
+        let methods = session
+            .queue
+            .iter()
+            .map(|call| (call.method.as_str(), call.params["content"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            methods,
+            vec![
+                ("update_workspace_settings", json!(null)),
+                ("file_write", json!("second")),
+            ]
+        );
+    }
+
+    #[test]
+    fn session_tracks_connected_and_listed_workspaces() {
+        let mut session = RemoteSessionState::default();
+        session.record_success("connect_workspace", &json!({ "id": "ws-1" }), &json!(null));
+        session.record_success(
+            "list_workspaces",
+            &json!({}),
+            &json!([{ "id": "ws-1", "name": "one" }, { "id": "ws-2", "name": "two" }]),
+        );
+        assert!(session.connected_workspaces.contains("ws-1"));
+        assert_eq!(session.workspaces.len(), 2);
+
+        session.record_success("remove_workspace", &json!({ "id": "ws-1" }), &json!(null));
+        assert!(session.connected_workspaces.is_empty());
+        assert!(!session.workspaces.contains_key("ws-1"));
+    }
+
+    #[test]
+    fn reconnect_delay_doubles_up_to_cap() {
+        assert_eq!(
+            next_reconnect_delay(Duration::from_millis(500)),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            next_reconnect_delay(RECONNECT_MAX_DELAY),
+            RECONNECT_MAX_DELAY
+        );
+    }
+}
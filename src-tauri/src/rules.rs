@@ -4,6 +4,8 @@ use std::path::{Path, PathBuf};
 use std::thread;
 use std::time::{Duration, Instant, SystemTime};
 
+use crate::shared::atomic_write::write_atomic;
+
 const RULES_DIR: &str = "rules";
 const DEFAULT_RULES_FILE: &str = "default.rules";
 
@@ -41,7 +43,7 @@ pub(crate) fn append_prefix_rule(path: &Path, pattern: &[String]) -> Result<(),
         updated.push('\n');
     }
 
-    fs::write(path, updated).map_err(|err| err.to_string())
+    write_atomic(path, updated.as_bytes()).map_err(|err| err.to_string())
 }
 
 struct RulesFileLock {
@@ -1,18 +1,31 @@
-use tauri::{State, Window};
+use serde_json::json;
+use tauri::{AppHandle, State, Window};
 
+use crate::backend::plugin_adapter::{self, CliPlugins};
+use crate::event_sink::TauriEventSink;
+use crate::http_api;
+use crate::remote_backend::{self, RemoteConnectionInfo, RemoteConnectionOptions};
 use crate::state::AppState;
+use crate::shared::account_profiles_core::{self, AccountProfile};
+use crate::shared::app_error::{AppError, AppResult};
+use crate::shared::app_state_core::{self, AppStateTransfer};
+use crate::shared::audit_log_core::{self, AuditLogQuery, AuditLogQueryResult};
 use crate::shared::cli_detect_core::{self, DetectedClis};
+use crate::shared::cli_updates_core::{cli_update_core, cli_updates_check_core, CliUpdateStatus};
 use crate::shared::settings_core::{
-    get_app_settings_core, get_codex_config_path_core, update_app_settings_core,
+    codex_config_validate_core, codex_profile_apply_core, codex_profile_save_current_core,
+    codex_profiles_list_core, get_app_settings_core, get_codex_config_path_core,
+    update_app_settings_core,
 };
-use crate::types::AppSettings;
+use crate::shared::settings_profiles_core::{self, SettingsProfileInfo};
+use crate::types::{AppSettings, CodexProfile, ConfigDiagnostic};
 use crate::window;
 
 #[tauri::command]
 pub(crate) async fn get_app_settings(
     state: State<'_, AppState>,
     window: Window,
-) -> Result<AppSettings, String> {
+) -> AppResult<AppSettings> {
     let settings = get_app_settings_core(&state.app_settings).await;
     let _ = window::apply_window_appearance(&window, settings.theme.as_str());
     Ok(settings)
@@ -23,19 +36,399 @@ pub(crate) async fn update_app_settings(
     settings: AppSettings,
     state: State<'_, AppState>,
     window: Window,
-) -> Result<AppSettings, String> {
-    let updated =
-        update_app_settings_core(settings, &state.app_settings, &state.settings_path).await?;
+) -> AppResult<AppSettings> {
+    let updated = update_app_settings_core(
+        settings,
+        &state.app_settings,
+        &state.settings_path,
+        &state.services,
+    )
+    .await?;
+    remote_backend::reset_remote_backend(&state).await;
+    http_api::settings_changed(&state);
     let _ = window::apply_window_appearance(&window, updated.theme.as_str());
     Ok(updated)
 }
 
 #[tauri::command]
-pub(crate) async fn get_codex_config_path() -> Result<String, String> {
-    get_codex_config_path_core()
+pub(crate) async fn get_codex_config_path() -> AppResult<String> {
+    Ok(get_codex_config_path_core()?)
 }
 
+/// Checks `config.toml` for syntax errors, unknown feature flags and
+/// conflicting settings.
 #[tauri::command]
-pub(crate) async fn detect_installed_clis() -> Result<DetectedClis, String> {
-    Ok(cli_detect_core::detect_installed_clis().await)
+pub(crate) async fn codex_config_validate(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<ConfigDiagnostic>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "codex_config_validate", json!({}))
+                .await
+                .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_config_validate_core()?)
+}
+
+/// Lists `[profiles.<name>]` presets from `config.toml`.
+#[tauri::command]
+pub(crate) async fn codex_profiles_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<CodexProfile>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "codex_profiles_list", json!({}))
+            .await
+            .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_profiles_list_core()?)
+}
+
+/// Sets the top-level `profile` key; `None` clears it.
+#[tauri::command]
+pub(crate) async fn codex_profile_apply(
+    name: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<CodexProfile>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_profile_apply",
+            json!({ "name": name }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_profile_apply_core(
+        name.as_deref(),
+        &state.services.audit_log,
+    )?)
+}
+
+/// Saves the current top-level model/provider settings as a named profile.
+#[tauri::command]
+pub(crate) async fn codex_profile_save_current(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<CodexProfile>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "codex_profile_save_current",
+            json!({ "name": name }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(codex_profile_save_current_core(&name)?)
+}
+
+#[tauri::command]
+pub(crate) async fn detect_installed_clis(force_refresh: Option<bool>) -> AppResult<DetectedClis> {
+    Ok(cli_detect_core::detect_installed_clis(force_refresh.unwrap_or(false)).await)
+}
+
+#[tauri::command]
+pub(crate) async fn cli_plugins_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<CliPlugins> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "cli_plugins_list", json!({}))
+            .await
+            .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(plugin_adapter::cli_plugins())
+}
+
+#[tauri::command]
+pub(crate) async fn cli_updates_check(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<CliUpdateStatus>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "cli_updates_check", json!({}))
+            .await
+            .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let settings = state.app_settings.lock().await.clone();
+    Ok(cli_updates_check_core(&settings).await)
+}
+
+#[tauri::command]
+pub(crate) async fn cli_update(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<CliUpdateStatus> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "cli_update", json!({ "name": name }))
+                .await
+                .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let settings = state.app_settings.lock().await.clone();
+    Ok(cli_update_core(&settings, TauriEventSink::new(app), &name).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn account_profiles_list(
+    provider: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<AccountProfile>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "account_profiles_list",
+            json!({ "provider": provider }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(account_profiles_core::account_profiles_list_core(provider)?)
+}
+
+#[tauri::command]
+pub(crate) async fn account_profile_create(
+    provider: String,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AccountProfile> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "account_profile_create",
+            json!({ "provider": provider, "name": name }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let profile = account_profiles_core::account_profile_create_core(provider, name)?;
+    Ok(profile)
+}
+
+#[tauri::command]
+pub(crate) async fn account_profile_delete(
+    provider: String,
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<()> {
+    if remote_backend::is_remote_mode(&*state).await {
+        remote_backend::call_remote(
+            &*state,
+            app,
+            "account_profile_delete",
+            json!({ "provider": provider, "name": name }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return Ok(());
+    }
+
+    account_profiles_core::account_profile_delete_core(&state.workspaces, provider, name).await?;
+    Ok(())
+}
+
+/// Writes settings, workspaces, adapter threads, usage and agent profile
+/// state to `path`, leaving secrets out.
+#[tauri::command]
+pub(crate) async fn app_state_export(
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AppStateTransfer> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "app_state_export", json!({ "path": path }))
+                .await
+                .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let transfer =
+        app_state_core::app_state_export_core(&path, &state.app_settings, &state.workspaces)
+            .await?;
+    Ok(transfer)
+}
+
+/// Restores an archive written by `app_state_export`.
+#[tauri::command]
+pub(crate) async fn app_state_import(
+    path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AppStateTransfer> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "app_state_import", json!({ "path": path }))
+                .await
+                .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    let transfer = app_state_core::app_state_import_core(
+        &path,
+        &state.app_settings,
+        &state.workspaces,
+        &state.settings_path,
+        &state.storage_path,
+        &state.services,
+    )
+    .await?;
+    remote_backend::reset_remote_backend(&state).await;
+    http_api::settings_changed(&state);
+    Ok(transfer)
+}
+
+/// Entries of the audit log matching `query`, with the result of checking
+/// the log's hash chain.
+#[tauri::command]
+pub(crate) async fn audit_log_query(
+    action: Option<String>,
+    since: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<AuditLogQueryResult> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "audit_log_query",
+            json!({ "action": action, "since": since, "limit": limit }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(audit_log_core::audit_log_query_core(
+        &state.services.audit_log,
+        AuditLogQuery {
+            action,
+            since,
+            limit,
+        },
+    )
+    .await?)
+}
+
+#[tauri::command]
+pub(crate) async fn settings_profiles_list(
+    state: State<'_, AppState>,
+) -> AppResult<Vec<SettingsProfileInfo>> {
+    Ok(settings_profiles_core::settings_profiles_list_core(
+        &state.app_settings,
+        &state.settings_path,
+    )
+    .await)
+}
+
+/// Saves the current settings and codex model keys as profile `name`.
+#[tauri::command]
+pub(crate) async fn settings_profile_save(
+    name: String,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<SettingsProfileInfo>> {
+    settings_profiles_core::settings_profile_save_core(
+        &name,
+        &state.app_settings,
+        &state.settings_path,
+    )
+    .await
+}
+
+#[tauri::command]
+pub(crate) async fn settings_profile_delete(
+    name: String,
+    state: State<'_, AppState>,
+) -> AppResult<Vec<SettingsProfileInfo>> {
+    settings_profiles_core::settings_profile_delete_core(
+        &name,
+        &state.app_settings,
+        &state.settings_path,
+    )
+    .await
+}
+
+/// Replaces the settings and codex model keys with profile `name`'s and
+/// emits `settings/changed`.
+#[tauri::command]
+pub(crate) async fn settings_profile_switch(
+    name: String,
+    state: State<'_, AppState>,
+    window: Window,
+    app: AppHandle,
+) -> AppResult<AppSettings> {
+    let updated = settings_profiles_core::settings_profile_switch_core(
+        &name,
+        &state.app_settings,
+        &state.settings_path,
+        &state.services,
+        &TauriEventSink::new(app),
+    )
+    .await?;
+    remote_backend::reset_remote_backend(&state).await;
+    http_api::settings_changed(&state);
+    let _ = window::apply_window_appearance(&window, updated.theme.as_str());
+    Ok(updated)
+}
+
+/// Checks a remote backend without switching to it. Omitted fields fall back
+/// to the saved settings.
+#[tauri::command]
+pub(crate) async fn remote_backend_test_connection(
+    host: Option<String>,
+    token: Option<String>,
+    tls: Option<bool>,
+    tls_ca_path: Option<String>,
+    state: State<'_, AppState>,
+) -> AppResult<RemoteConnectionInfo> {
+    let mut options = {
+        let settings = state.app_settings.lock().await;
+        RemoteConnectionOptions::from_settings(&settings)
+    };
+    if let Some(host) = host {
+        options.host = host;
+    }
+    if token.is_some() {
+        options.token = token;
+    }
+    if let Some(tls) = tls {
+        options.tls = tls;
+    }
+    if tls_ca_path.is_some() {
+        options.tls_ca_path = tls_ca_path;
+    }
+    remote_backend::test_remote_connection(&options)
+        .await
+        .map_err(AppError::Remote)
 }
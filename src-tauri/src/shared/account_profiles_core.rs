@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::types::WorkspaceEntry;
+
+const ACCOUNTS_DIR: &str = "accounts";
+const PROVIDERS: [&str; 4] = ["codex", "claude", "gemini", "cursor"];
+
+/// A named CLI home a workspace can run a provider's CLI against, so one
+/// machine can stay signed in to several accounts of the same provider.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AccountProfile {
+    pub(crate) provider: String,
+    pub(crate) name: String,
+    /// Passed to the CLI as its home (`CODEX_HOME`, `CLAUDE_CONFIG_DIR`, ...).
+    pub(crate) home: String,
+}
+
+fn accounts_root() -> Result<PathBuf, String> {
+    let base = dirs_next::data_dir().ok_or_else(|| "Unable to resolve data dir".to_string())?;
+    Ok(base.join("agent-monitor").join(ACCOUNTS_DIR))
+}
+
+fn check_provider(provider: &str) -> Result<(), String> {
+    if PROVIDERS.contains(&provider) {
+        Ok(())
+    } else {
+        Err(format!("Unknown provider: {provider}"))
+    }
+}
+
+fn check_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Profile name is required.".to_string());
+    }
+    let valid = name
+        .chars()
+        .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid profile name \"{name}\"; use letters, digits, - and _"
+        ));
+    }
+    Ok(())
+}
+
+/// The provider an account profile is picked for when running `cli_type`.
+pub(crate) fn account_profile_provider(cli_type: &str) -> &'static str {
+    PROVIDERS
+        .into_iter()
+        .find(|provider| *provider == cli_type)
+        .unwrap_or("codex")
+}
+
+fn profile_home_in(root: &Path, provider: &str, name: &str) -> Result<PathBuf, String> {
+    check_provider(provider)?;
+    check_profile_name(name)?;
+    Ok(root.join(provider).join(name))
+}
+
+/// Home directory of the `provider` profile called `name`.
+pub(crate) fn account_profile_home(provider: &str, name: &str) -> Result<PathBuf, String> {
+    profile_home_in(&accounts_root()?, provider, name)
+}
+
+fn list_profiles_in(root: &Path, provider: Option<&str>) -> Vec<AccountProfile> {
+    let mut profiles = Vec::new();
+    for candidate in PROVIDERS {
+        if provider.is_some_and(|provider| provider != candidate) {
+            continue;
+        }
+        let Ok(entries) = std::fs::read_dir(root.join(candidate)) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !entry.path().is_dir() || check_profile_name(&name).is_err() {
+                continue;
+            }
+            profiles.push(AccountProfile {
+                provider: candidate.to_string(),
+                name,
+                home: entry.path().to_string_lossy().to_string(),
+            });
+        }
+    }
+    profiles.sort_by(|a, b| (&a.provider, &a.name).cmp(&(&b.provider, &b.name)));
+    profiles
+}
+
+pub(crate) fn account_profiles_list_core(
+    provider: Option<String>,
+) -> Result<Vec<AccountProfile>, String> {
+    if let Some(provider) = provider.as_deref() {
+        check_provider(provider)?;
+    }
+    Ok(list_profiles_in(&accounts_root()?, provider.as_deref()))
+}
+
+/// Creates an empty home for a new profile. Sign in by running the
+/// provider's CLI against it from a workspace that selects the profile.
+pub(crate) fn account_profile_create_core(
+    provider: String,
+    name: String,
+) -> Result<AccountProfile, String> {
+    let name = name.trim();
+    let home = account_profile_home(&provider, name)?;
+    if home.exists() {
+        return Err(format!(
+            "A {provider} profile named \"{name}\" already exists"
+        ));
+    }
+    std::fs::create_dir_all(&home)
+        .map_err(|err| format!("Failed to create profile {name}: {err}"))?;
+    Ok(AccountProfile {
+        provider,
+        name: name.to_string(),
+        home: home.to_string_lossy().to_string(),
+    })
+}
+
+/// Deletes a profile and the credentials stored in it. Profiles a workspace
+/// still selects are kept.
+pub(crate) async fn account_profile_delete_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    provider: String,
+    name: String,
+) -> Result<(), String> {
+    let home = account_profile_home(&provider, &name)?;
+    let in_use = workspaces
+        .lock()
+        .await
+        .values()
+        .find(|entry| entry.settings.account_profiles.get(&provider) == Some(&name))
+        .map(|entry| entry.name.clone());
+    if let Some(workspace) = in_use {
+        return Err(format!(
+            "The {provider} profile \"{name}\" is used by {workspace}"
+        ));
+    }
+    if !home.is_dir() {
+        return Err(format!("No {provider} profile named \"{name}\""));
+    }
+    std::fs::remove_dir_all(&home).map_err(|err| format!("Failed to delete profile {name}: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{account_profile_provider, list_profiles_in, profile_home_in};
+    use uuid::Uuid;
+
+    #[test]
+    fn lists_profiles_and_rejects_bad_names() {
+        let root = std::env::temp_dir().join(format!("account-profiles-{}", Uuid::new_v4()));
+        let work = profile_home_in(&root, "claude", "work").expect("work home");
+        assert_eq!(work, root.join("claude").join("work"));
+        std::fs::create_dir_all(&work).expect("create work");
+        std::fs::create_dir_all(root.join("claude/personal")).expect("create personal");
+        std::fs::create_dir_all(root.join("codex/work")).expect("create codex");
+        std::fs::write(root.join("claude/notes.txt"), "").expect("write stray file");
+
+        let claude = list_profiles_in(&root, Some("claude"));
+        let names = claude
+            .iter()
+            .map(|profile| profile.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["personal", "work"]);
+        assert_eq!(list_profiles_in(&root, None).len(), 3);
+
+        assert!(profile_home_in(&root, "claude", "../escape").is_err());
+        assert!(profile_home_in(&root, "claude", "").is_err());
+        assert!(profile_home_in(&root, "copilot", "work").is_err());
+        assert_eq!(account_profile_provider("claude"), "claude");
+        assert_eq!(account_profile_provider("app-server"), "codex");
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
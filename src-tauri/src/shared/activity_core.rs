@@ -0,0 +1,264 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Duration, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::backend::adapter_base::turn_metrics_path;
+use crate::backend::turn_metrics::{read_turn_metrics, TurnMetrics};
+use crate::types::{AppSettings, WorkspaceEntry};
+
+const DEFAULT_DAYS: u32 = 30;
+const MAX_DAYS: u32 = 365;
+
+/// Turn counts and usage over a set of turns. A turn failed when it timed
+/// out or its CLI exited non-zero; the Codex app-server reports no exit
+/// code, so its turns only fail by timing out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActivityStats {
+    pub(crate) turns: u32,
+    pub(crate) succeeded: u32,
+    pub(crate) failed: u32,
+    /// `succeeded / turns`, or `None` without turns.
+    pub(crate) success_rate: Option<f64>,
+    pub(crate) input_tokens: u64,
+    pub(crate) output_tokens: u64,
+    /// Only Claude turns report a cost.
+    pub(crate) cost_usd: f64,
+    pub(crate) duration_ms: u64,
+}
+
+impl ActivityStats {
+    fn add(&mut self, turn: &TurnMetrics) {
+        self.turns += 1;
+        if turn.timed_out || turn.exit_code.is_some_and(|code| code != 0) {
+            self.failed += 1;
+        } else {
+            self.succeeded += 1;
+        }
+        self.success_rate = Some(f64::from(self.succeeded) / f64::from(self.turns));
+        self.input_tokens += turn.input_tokens.unwrap_or(0);
+        self.output_tokens += turn.output_tokens.unwrap_or(0);
+        self.cost_usd += turn.cost_usd.unwrap_or(0.0);
+        self.duration_ms += turn.duration_ms;
+    }
+}
+
+/// One workspace's activity on one local day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActivityDay {
+    /// `YYYY-MM-DD`, local time.
+    pub(crate) day: String,
+    pub(crate) workspace_id: String,
+    pub(crate) provider: String,
+    #[serde(flatten)]
+    pub(crate) stats: ActivityStats,
+}
+
+/// One workspace's activity over the whole range.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActivityTotals {
+    pub(crate) workspace_id: String,
+    pub(crate) provider: String,
+    #[serde(flatten)]
+    pub(crate) stats: ActivityStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ActivityTimeline {
+    /// Every day in the range, oldest first, including days without turns.
+    pub(crate) days: Vec<String>,
+    /// Days without turns are left out.
+    pub(crate) activity: Vec<ActivityDay>,
+    pub(crate) workspaces: Vec<ActivityTotals>,
+}
+
+/// The last `days` local days, oldest first, and the first second of the
+/// oldest as Unix time.
+fn day_range(days: u32) -> (Vec<String>, u64) {
+    let today = Local::now().date_naive();
+    let first = today - Duration::days(i64::from(days) - 1);
+    let since = first
+        .and_hms_opt(0, 0, 0)
+        .and_then(|start| Local.from_local_datetime(&start).earliest())
+        .map(|start| u64::try_from(start.timestamp()).unwrap_or(0))
+        .unwrap_or(0);
+    let keys = (0..days)
+        .map(|offset| {
+            (first + Duration::days(i64::from(offset)))
+                .format("%Y-%m-%d")
+                .to_string()
+        })
+        .collect();
+    (keys, since)
+}
+
+fn day_key(started_at: u64) -> Option<String> {
+    Local
+        .timestamp_opt(i64::try_from(started_at).ok()?, 0)
+        .single()
+        .map(|time| time.format("%Y-%m-%d").to_string())
+}
+
+/// Groups each workspace's turns since `since` by local day. `workspaces`
+/// pairs a workspace id and provider with its stored turns.
+fn build_timeline(
+    days: Vec<String>,
+    since: u64,
+    workspaces: Vec<(String, String, Vec<TurnMetrics>)>,
+) -> ActivityTimeline {
+    let mut activity = Vec::new();
+    let mut totals = Vec::new();
+    for (workspace_id, provider, turns) in workspaces {
+        let mut by_day: BTreeMap<String, ActivityStats> = BTreeMap::new();
+        let mut total = ActivityStats::default();
+        for turn in turns.iter().filter(|turn| turn.started_at >= since) {
+            let Some(day) = day_key(turn.started_at) else {
+                continue;
+            };
+            by_day.entry(day).or_default().add(turn);
+            total.add(turn);
+        }
+        if total.turns == 0 {
+            continue;
+        }
+        activity.extend(by_day.into_iter().map(|(day, stats)| ActivityDay {
+            day,
+            workspace_id: workspace_id.clone(),
+            provider: provider.clone(),
+            stats,
+        }));
+        totals.push(ActivityTotals {
+            workspace_id,
+            provider,
+            stats: total,
+        });
+    }
+    activity.sort_by(|a, b| a.day.cmp(&b.day).then(a.workspace_id.cmp(&b.workspace_id)));
+    totals.sort_by(|a, b| a.workspace_id.cmp(&b.workspace_id));
+    ActivityTimeline {
+        days,
+        activity,
+        workspaces: totals,
+    }
+}
+
+/// Per-day turn counts and usage for every workspace over the last `days`
+/// days (default 30, at most 365), read from the stored turn metrics. The
+/// CLI is chosen app-wide, so every workspace counts under the current one.
+pub(crate) async fn activity_timeline_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+    days: Option<u32>,
+) -> Result<ActivityTimeline, String> {
+    let days = days.unwrap_or(DEFAULT_DAYS).clamp(1, MAX_DAYS);
+    let provider = app_settings.lock().await.cli_type.clone();
+    let targets: Vec<(String, String)> = workspaces
+        .lock()
+        .await
+        .keys()
+        .map(|workspace_id| (workspace_id.clone(), provider.clone()))
+        .collect();
+    tokio::task::spawn_blocking(move || {
+        let (keys, since) = day_range(days);
+        let workspaces = targets
+            .into_iter()
+            .map(|(workspace_id, provider)| {
+                let turns = read_turn_metrics(&turn_metrics_path(&workspace_id), None, None);
+                (workspace_id, provider, turns)
+            })
+            .collect();
+        build_timeline(keys, since, workspaces)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use super::build_timeline;
+    use crate::backend::turn_metrics::TurnMetrics;
+
+    fn turn(started_at: u64, exit_code: Option<i32>, timed_out: bool) -> TurnMetrics {
+        TurnMetrics {
+            thread_id: "t".to_string(),
+            turn_id: None,
+            started_at,
+            duration_ms: 1_000,
+            tool_calls: 0,
+            output_bytes: 0,
+            input_tokens: Some(100),
+            output_tokens: Some(10),
+            cost_usd: Some(0.5),
+            exit_code,
+            timed_out,
+        }
+    }
+
+    #[test]
+    fn groups_turns_by_day_and_counts_failures() {
+        let at = |day: u32, hour: u32| {
+            Local
+                .with_ymd_and_hms(2026, 10, day, hour, 0, 0)
+                .unwrap()
+                .timestamp() as u64
+        };
+        let days = vec!["2026-10-14".to_string(), "2026-10-15".to_string()];
+        let timeline = build_timeline(
+            days,
+            at(14, 0),
+            vec![
+                (
+                    "ws-b".to_string(),
+                    "claude".to_string(),
+                    vec![
+                        turn(at(13, 12), Some(0), false),
+                        turn(at(14, 9), Some(0), false),
+                        turn(at(14, 10), Some(1), false),
+                        turn(at(15, 9), None, true),
+                    ],
+                ),
+                (
+                    "ws-a".to_string(),
+                    "codex".to_string(),
+                    vec![turn(at(15, 8), None, false)],
+                ),
+                ("ws-idle".to_string(), "gemini".to_string(), Vec::new()),
+            ],
+        );
+
+        let rows: Vec<(&str, &str, u32, u32)> = timeline
+            .activity
+            .iter()
+            .map(|day| {
+                (
+                    day.day.as_str(),
+                    day.workspace_id.as_str(),
+                    day.stats.turns,
+                    day.stats.failed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("2026-10-14", "ws-b", 2, 1),
+                ("2026-10-15", "ws-a", 1, 0),
+                ("2026-10-15", "ws-b", 1, 1),
+            ]
+        );
+        let totals = &timeline.workspaces[1];
+        assert_eq!(totals.workspace_id, "ws-b");
+        assert_eq!(totals.stats.turns, 3);
+        assert_eq!(totals.stats.success_rate, Some(1.0 / 3.0));
+        assert_eq!(totals.stats.input_tokens, 300);
+        assert_eq!(totals.stats.cost_usd, 1.5);
+        assert_eq!(timeline.workspaces.len(), 2);
+    }
+}
@@ -1,15 +1,21 @@
-use std::collections::HashMap;
-use std::path::{Path, PathBuf};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tokio::sync::Mutex;
 
+use crate::files::policy::{instructions_kind_for_cli, workspace_filename, FileKind};
+use crate::shared::atomic_write::write_atomic;
+use crate::shared::audit_log_core::AuditLog;
 use crate::types::WorkspaceEntry;
 
 const PROFILES_DIR: &str = "profiles";
-const AGENTS_MD: &str = "AGENTS.md";
-const CLAUDE_MD: &str = "CLAUDE.md";
-const PROFILE_STATE_FILE: &str = ".agent-profile-state.json";
+pub(crate) const PROFILE_STATE_FILE: &str = ".agent-profile-state.json";
+const PROFILE_MANIFEST: &str = "profile.toml";
+/// Above this many line pairs the drift diff is skipped.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+const DIFF_CONTEXT: usize = 3;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -33,6 +39,13 @@ pub(crate) struct AgentProfile {
     pub(crate) label: String,
     pub(crate) has_agents: bool,
     pub(crate) has_claude: bool,
+    #[serde(default)]
+    pub(crate) has_gemini: bool,
+    #[serde(default)]
+    pub(crate) has_cursor_rules: bool,
+    /// Profiles this one builds on, from its `profile.toml`.
+    #[serde(default)]
+    pub(crate) extends: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -53,6 +66,42 @@ pub(crate) struct AgentProfileApplyResponse {
     pub(crate) fallback_used: bool,
 }
 
+/// How the workspace's instructions file compares to the applied profile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum AgentProfileDrift {
+    /// No profile has been applied to this target file.
+    Unmanaged,
+    InSync,
+    /// The file was edited after the profile was applied.
+    Modified,
+    /// The file was deleted.
+    Missing,
+    /// The profile no longer provides the file.
+    SourceMissing,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum AgentProfileSyncAction {
+    /// Applies the profile again, discarding edits to the target file.
+    Reapply,
+    /// Copies the edited target file into the profile.
+    SaveBackToProfile,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentProfileStatus {
+    pub(crate) profile: Option<String>,
+    pub(crate) target_file: String,
+    pub(crate) active_mode: Option<AgentProfileWriteMode>,
+    pub(crate) drift: AgentProfileDrift,
+    /// Unified diff from the profile to the target file when modified.
+    pub(crate) diff: Option<String>,
+    pub(crate) actions: Vec<AgentProfileSyncAction>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct AgentProfileState {
@@ -61,12 +110,12 @@ struct AgentProfileState {
     active_mode: AgentProfileWriteMode,
 }
 
+fn instructions_file(kind: FileKind) -> &'static str {
+    workspace_filename(kind).expect("instruction file kinds have a workspace filename")
+}
+
 fn selected_target_file(cli_type: &str) -> &'static str {
-    if cli_type == "claude" {
-        CLAUDE_MD
-    } else {
-        AGENTS_MD
-    }
+    instructions_file(instructions_kind_for_cli(cli_type))
 }
 
 fn profile_label(name: &str) -> String {
@@ -106,6 +155,130 @@ fn profile_source(workspace_root: &Path, profile: &str, target_file: &str) -> Pa
         .join(target_file)
 }
 
+/// `profile.toml`: the profiles a profile builds on and the fragment files,
+/// relative to its directory, that follow its own instructions file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ProfileManifest {
+    extends: Vec<String>,
+    fragments: Vec<String>,
+}
+
+fn read_manifest(profile_dir: &Path) -> Result<Option<ProfileManifest>, String> {
+    let path = profile_dir.join(PROFILE_MANIFEST);
+    let data = match std::fs::read_to_string(&path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("Failed to read {}: {err}", path.display())),
+    };
+    toml::from_str(&data)
+        .map(Some)
+        .map_err(|err| format!("Failed to parse {}: {err}", path.display()))
+}
+
+fn is_plain_relative(path: &str) -> bool {
+    !path.is_empty()
+        && Path::new(path)
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Appends the instructions `profile` contributes to `target_file` to
+/// `parts`: its bases first, depth-first in `extends` order, then its own
+/// file, then its fragments. A base shared by several parents is included
+/// once.
+fn collect_profile_parts(
+    profiles_root: &Path,
+    profile: &str,
+    target_file: &str,
+    chain: &mut Vec<String>,
+    included: &mut HashSet<String>,
+    parts: &mut Vec<String>,
+) -> Result<(), String> {
+    if chain.iter().any(|name| name == profile) {
+        chain.push(profile.to_string());
+        return Err(format!("Profile inheritance cycle: {}", chain.join(" -> ")));
+    }
+    if included.contains(profile) {
+        return Ok(());
+    }
+    let dir = profiles_root.join(profile);
+    if !is_plain_relative(profile) || profile.contains(['/', '\\']) || !dir.is_dir() {
+        return Err(match chain.last() {
+            Some(parent) => format!("Profile `{parent}` extends missing profile `{profile}`"),
+            None => format!("Profile `{profile}` not found"),
+        });
+    }
+    let manifest = read_manifest(&dir)?.unwrap_or_default();
+    chain.push(profile.to_string());
+    for base in &manifest.extends {
+        collect_profile_parts(profiles_root, base, target_file, chain, included, parts)?;
+    }
+    chain.pop();
+    included.insert(profile.to_string());
+
+    let own = dir.join(target_file);
+    if own.is_file() {
+        let content = std::fs::read_to_string(&own)
+            .map_err(|err| format!("Failed to read {}: {err}", own.display()))?;
+        parts.push(content);
+    }
+    for fragment in &manifest.fragments {
+        if !is_plain_relative(fragment) {
+            return Err(format!(
+                "Fragment `{fragment}` of profile `{profile}` must be a path inside the profile"
+            ));
+        }
+        let path = dir.join(fragment);
+        let content = std::fs::read_to_string(&path).map_err(|err| {
+            format!("Failed to read fragment `{fragment}` of profile `{profile}`: {err}")
+        })?;
+        parts.push(content);
+    }
+    Ok(())
+}
+
+/// The composed instructions of a profile with a `profile.toml`, or `None`
+/// for a plain profile whose file is applied as is. `Some("")` when the
+/// composition has nothing for `target_file`.
+fn composed_profile(
+    workspace_root: &Path,
+    profile: &str,
+    target_file: &str,
+) -> Result<Option<String>, String> {
+    let profiles_root = workspace_root.join(PROFILES_DIR);
+    if !profiles_root.join(profile).join(PROFILE_MANIFEST).is_file() {
+        return Ok(None);
+    }
+    let mut parts = Vec::new();
+    collect_profile_parts(
+        &profiles_root,
+        profile,
+        target_file,
+        &mut Vec::new(),
+        &mut HashSet::new(),
+        &mut parts,
+    )?;
+    let parts = parts
+        .iter()
+        .map(|part| part.trim_matches('\n'))
+        .filter(|part| !part.trim().is_empty())
+        .collect::<Vec<_>>();
+    if parts.is_empty() {
+        return Ok(Some(String::new()));
+    }
+    Ok(Some(format!("{}\n", parts.join("\n\n"))))
+}
+
+/// Whether applying `profile` for `target_file` would write anything.
+fn provides_target(workspace_root: &Path, profile: &str, target_file: &str) -> bool {
+    match composed_profile(workspace_root, profile, target_file) {
+        Ok(Some(content)) => !content.is_empty(),
+        Ok(None) => profile_source(workspace_root, profile, target_file).is_file(),
+        Err(_) => false,
+    }
+}
+
 fn list_profiles(workspace_root: &Path) -> Result<Vec<AgentProfile>, String> {
     let profiles_root = workspace_root.join(PROFILES_DIR);
     if !profiles_root.exists() {
@@ -124,17 +297,27 @@ fn list_profiles(workspace_root: &Path) -> Result<Vec<AgentProfile>, String> {
             continue;
         }
         let name = entry.file_name().to_string_lossy().to_string();
-        let dir = entry.path();
-        let has_agents = dir.join(AGENTS_MD).is_file();
-        let has_claude = dir.join(CLAUDE_MD).is_file();
-        if !has_agents && !has_claude {
+        let provides = |kind| provides_target(workspace_root, &name, instructions_file(kind));
+        let has_agents = provides(FileKind::Agents);
+        let has_claude = provides(FileKind::Claude);
+        let has_gemini = provides(FileKind::Gemini);
+        let has_cursor_rules = provides(FileKind::CursorRules);
+        if !(has_agents || has_claude || has_gemini || has_cursor_rules) {
             continue;
         }
+        let extends = read_manifest(&entry.path())
+            .ok()
+            .flatten()
+            .map(|manifest| manifest.extends)
+            .unwrap_or_default();
         profiles.push(AgentProfile {
             label: profile_label(&name),
             name,
             has_agents,
             has_claude,
+            has_gemini,
+            has_cursor_rules,
+            extends,
         });
     }
     profiles.sort_by(|a, b| a.name.cmp(&b.name));
@@ -161,7 +344,8 @@ fn write_profile_state(
     let data = serde_json::to_string_pretty(&state)
         .map_err(|err| format!("Failed to serialize profile state: {err}"))?;
     let state_path = workspace_root.join(PROFILE_STATE_FILE);
-    std::fs::write(state_path, data).map_err(|err| format!("Failed to persist profile state: {err}"))
+    write_atomic(&state_path, data.as_bytes())
+        .map_err(|err| format!("Failed to persist profile state: {err}"))
 }
 
 fn remove_existing_target(target_path: &Path) -> Result<(), String> {
@@ -241,7 +425,13 @@ fn detect_active_copy_profile(
         return None;
     }
     let target_content = std::fs::read(workspace_root.join(target_file)).ok()?;
-    let source_content = std::fs::read(profile_source(workspace_root, &state.profile, target_file)).ok()?;
+    let source_content = match composed_profile(workspace_root, &state.profile, target_file) {
+        Ok(Some(content)) => content.into_bytes(),
+        Ok(None) => {
+            std::fs::read(profile_source(workspace_root, &state.profile, target_file)).ok()?
+        }
+        Err(_) => return None,
+    };
     if target_content == source_content {
         Some(state.profile.clone())
     } else {
@@ -249,6 +439,24 @@ fn detect_active_copy_profile(
     }
 }
 
+fn detect_active_profile(
+    workspace_root: &Path,
+    target_file: &str,
+    profiles: &[AgentProfile],
+) -> Option<String> {
+    detect_active_symlink_profile(workspace_root, target_file, profiles).or_else(|| {
+        let state = read_profile_state(workspace_root);
+        detect_active_copy_profile(workspace_root, target_file, state.as_ref())
+    })
+}
+
+/// Profile whose instructions file is currently in place in a folder that
+/// may not be registered as a workspace yet.
+pub(crate) fn detect_agent_profile_at(workspace_root: &Path, cli_type: &str) -> Option<String> {
+    let profiles = list_profiles(workspace_root).ok()?;
+    detect_active_profile(workspace_root, selected_target_file(cli_type), &profiles)
+}
+
 pub(crate) async fn list_agent_profiles_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: String,
@@ -257,11 +465,7 @@ pub(crate) async fn list_agent_profiles_core(
     let workspace_root = resolve_workspace_root(workspaces, &workspace_id).await?;
     let target_file = selected_target_file(cli_type).to_string();
     let profiles = list_profiles(&workspace_root)?;
-    let active_profile = detect_active_symlink_profile(&workspace_root, &target_file, &profiles)
-        .or_else(|| {
-            let state = read_profile_state(&workspace_root);
-            detect_active_copy_profile(&workspace_root, &target_file, state.as_ref())
-        });
+    let active_profile = detect_active_profile(&workspace_root, &target_file, &profiles);
     let active_mode = active_profile.as_ref().and_then(|profile_name| {
         let state = read_profile_state(&workspace_root)?;
         if state.profile == *profile_name && state.target_file == target_file {
@@ -281,14 +485,75 @@ pub(crate) async fn list_agent_profiles_core(
 
 pub(crate) async fn apply_agent_profile_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
     workspace_id: String,
     profile: String,
     cli_type: &str,
     mode: AgentProfileApplyMode,
 ) -> Result<AgentProfileApplyResponse, String> {
     let workspace_root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    let response = apply_agent_profile_at(&workspace_root, profile, cli_type, mode)?;
+    audit_log.record(
+        "agent_profile_apply",
+        json!({
+            "workspaceId": workspace_id,
+            "profile": response.active_profile,
+            "targetFile": response.target_file,
+            "mode": response.active_mode,
+        }),
+    );
+    Ok(response)
+}
+
+/// Writes a composed profile's instructions as a plain file. They exist only
+/// once merged, so there is nothing to symlink.
+fn apply_composed_profile(
+    workspace_root: &Path,
+    profile: String,
+    target_file: String,
+    content: String,
+    mode: AgentProfileApplyMode,
+) -> Result<AgentProfileApplyResponse, String> {
+    if content.is_empty() {
+        return Err(format!(
+            "Profile `{profile}` and the profiles it extends provide no {target_file}."
+        ));
+    }
+    if mode == AgentProfileApplyMode::Symlink {
+        return Err(format!(
+            "Profile `{profile}` is composed from {PROFILE_MANIFEST} and can only be applied as a copy."
+        ));
+    }
+    let target = workspace_root.join(&target_file);
+    remove_existing_target(&target)?;
+    write_atomic(&target, content.as_bytes())
+        .map_err(|err| format!("Failed to write {target_file}: {err}"))?;
+    write_profile_state(
+        workspace_root,
+        &profile,
+        &target_file,
+        AgentProfileWriteMode::Copy,
+    )?;
+    Ok(AgentProfileApplyResponse {
+        active_profile: profile,
+        target_file,
+        active_mode: AgentProfileWriteMode::Copy,
+        fallback_used: false,
+    })
+}
+
+/// Applies `profile` in a workspace folder that may not be registered yet.
+pub(crate) fn apply_agent_profile_at(
+    workspace_root: &Path,
+    profile: String,
+    cli_type: &str,
+    mode: AgentProfileApplyMode,
+) -> Result<AgentProfileApplyResponse, String> {
     let target_file = selected_target_file(cli_type).to_string();
-    let source = profile_source(&workspace_root, &profile, &target_file);
+    if let Some(content) = composed_profile(workspace_root, &profile, &target_file)? {
+        return apply_composed_profile(workspace_root, profile, target_file, content, mode);
+    }
+    let source = profile_source(workspace_root, &profile, &target_file);
     if !source.is_file() {
         return Err(format!(
             "Profile `{profile}` does not provide {target_file}. Add `{}/{target_file}` \
@@ -305,10 +570,10 @@ in that profile or switch CLI mode.",
             AgentProfileWriteMode::Copy
         }
         AgentProfileApplyMode::Symlink => {
-            apply_symlink_mode(&workspace_root, &source, &target)?;
+            apply_symlink_mode(workspace_root, &source, &target)?;
             AgentProfileWriteMode::Symlink
         }
-        AgentProfileApplyMode::Auto => match apply_symlink_mode(&workspace_root, &source, &target) {
+        AgentProfileApplyMode::Auto => match apply_symlink_mode(workspace_root, &source, &target) {
             Ok(()) => AgentProfileWriteMode::Symlink,
             Err(_) => {
                 apply_copy_mode(&source, &target)?;
@@ -318,7 +583,7 @@ in that profile or switch CLI mode.",
         },
     };
 
-    write_profile_state(&workspace_root, &profile, &target_file, active_mode)?;
+    write_profile_state(workspace_root, &profile, &target_file, active_mode)?;
     Ok(AgentProfileApplyResponse {
         active_profile: profile,
         target_file,
@@ -326,3 +591,425 @@ in that profile or switch CLI mode.",
         fallback_used,
     })
 }
+
+/// Unified diff of two texts with [`DIFF_CONTEXT`] lines of context.
+fn unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) -> String {
+    let old_lines = old.lines().collect::<Vec<_>>();
+    let new_lines = new.lines().collect::<Vec<_>>();
+    let (n, m) = (old_lines.len(), new_lines.len());
+    if n.saturating_mul(m) > MAX_DIFF_CELLS {
+        return format!("--- {old_name}\n+++ {new_name}\n(files differ; too large to diff)\n");
+    }
+    // lcs[i][j]: longest common subsequence of old_lines[i..] and new_lines[j..].
+    let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    // (tag, line, old index, new index) before each op.
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old_lines[i] == new_lines[j] {
+            ops.push((' ', old_lines[i], i, j));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            ops.push(('-', old_lines[i], i, j));
+            i += 1;
+        } else {
+            ops.push(('+', new_lines[j], i, j));
+            j += 1;
+        }
+    }
+
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, op) in ops.iter().enumerate() {
+        if op.0 == ' ' {
+            continue;
+        }
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    let mut out = format!("--- {old_name}\n+++ {new_name}\n");
+    for (start, end) in hunks {
+        let ops = &ops[start..end];
+        let old_len = ops.iter().filter(|op| op.0 != '+').count();
+        let new_len = ops.iter().filter(|op| op.0 != '-').count();
+        let old_start = ops[0].2 + usize::from(old_len > 0);
+        let new_start = ops[0].3 + usize::from(new_len > 0);
+        out.push_str(&format!(
+            "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+        ));
+        for (tag, line, _, _) in ops {
+            out.push(*tag);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// The instructions `profile` applies to `target_file`, or `None` when it
+/// doesn't provide that file.
+fn expected_profile_content(
+    workspace_root: &Path,
+    profile: &str,
+    target_file: &str,
+) -> Option<String> {
+    match composed_profile(workspace_root, profile, target_file) {
+        Ok(Some(content)) => Some(content).filter(|content| !content.is_empty()),
+        Ok(None) => {
+            std::fs::read_to_string(profile_source(workspace_root, profile, target_file)).ok()
+        }
+        Err(_) => None,
+    }
+}
+
+fn profile_status_at(workspace_root: &Path, cli_type: &str) -> AgentProfileStatus {
+    let target_file = selected_target_file(cli_type).to_string();
+    let state = read_profile_state(workspace_root).filter(|state| state.target_file == target_file);
+    let mut status = AgentProfileStatus {
+        profile: state.as_ref().map(|state| state.profile.clone()),
+        target_file: target_file.clone(),
+        active_mode: state.as_ref().map(|state| state.active_mode),
+        drift: AgentProfileDrift::Unmanaged,
+        diff: None,
+        actions: Vec::new(),
+    };
+    let Some(state) = state else {
+        return status;
+    };
+    let composed = workspace_root
+        .join(PROFILES_DIR)
+        .join(&state.profile)
+        .join(PROFILE_MANIFEST)
+        .is_file();
+    let target = std::fs::read_to_string(workspace_root.join(&target_file)).ok();
+    let Some(expected) = expected_profile_content(workspace_root, &state.profile, &target_file)
+    else {
+        status.drift = AgentProfileDrift::SourceMissing;
+        if target.is_some() && !composed {
+            status.actions = vec![AgentProfileSyncAction::SaveBackToProfile];
+        }
+        return status;
+    };
+    let Some(target) = target else {
+        status.drift = AgentProfileDrift::Missing;
+        status.actions = vec![AgentProfileSyncAction::Reapply];
+        return status;
+    };
+    if target == expected {
+        status.drift = AgentProfileDrift::InSync;
+        return status;
+    }
+    status.drift = AgentProfileDrift::Modified;
+    status.diff = Some(unified_diff(
+        &expected,
+        &target,
+        &format!("{PROFILES_DIR}/{}/{target_file}", state.profile),
+        &target_file,
+    ));
+    status.actions = vec![AgentProfileSyncAction::Reapply];
+    if !composed {
+        status
+            .actions
+            .push(AgentProfileSyncAction::SaveBackToProfile);
+    }
+    status
+}
+
+fn sync_profile_at(
+    workspace_root: &Path,
+    cli_type: &str,
+    action: AgentProfileSyncAction,
+) -> Result<AgentProfileStatus, String> {
+    let status = profile_status_at(workspace_root, cli_type);
+    let (Some(profile), Some(active_mode)) = (status.profile.clone(), status.active_mode) else {
+        return Err(format!(
+            "No agent profile is applied to {}",
+            status.target_file
+        ));
+    };
+    if !status.actions.contains(&action) {
+        return Err(match status.drift {
+            AgentProfileDrift::InSync => {
+                format!("{} already matches `{profile}`", status.target_file)
+            }
+            _ => format!("That action isn't available for profile `{profile}`"),
+        });
+    }
+    match action {
+        AgentProfileSyncAction::Reapply => {
+            let mode = match active_mode {
+                AgentProfileWriteMode::Symlink => AgentProfileApplyMode::Symlink,
+                AgentProfileWriteMode::Copy => AgentProfileApplyMode::Copy,
+            };
+            apply_agent_profile_at(workspace_root, profile, cli_type, mode)?;
+        }
+        AgentProfileSyncAction::SaveBackToProfile => {
+            let target = workspace_root.join(&status.target_file);
+            let content = std::fs::read(&target)
+                .map_err(|err| format!("Failed to read {}: {err}", status.target_file))?;
+            let source = profile_source(workspace_root, &profile, &status.target_file);
+            write_atomic(&source, &content)
+                .map_err(|err| format!("Failed to update profile `{profile}`: {err}"))?;
+        }
+    }
+    Ok(profile_status_at(workspace_root, cli_type))
+}
+
+/// Copies the workspace's instruction files into `profiles/<profile>/`,
+/// creating the profile if needed. The CLI's file then counts as applied
+/// from that profile, in copy mode.
+fn save_profile_at(
+    workspace_root: &Path,
+    profile: &str,
+    cli_type: &str,
+) -> Result<AgentProfileStatus, String> {
+    let profile = profile.trim();
+    if profile.is_empty()
+        || profile.starts_with('.')
+        || profile.contains(['/', '\\'])
+        || !is_plain_relative(profile)
+    {
+        return Err(format!("Invalid profile name `{profile}`"));
+    }
+    let profile_dir = workspace_root.join(PROFILES_DIR).join(profile);
+    if profile_dir.join(PROFILE_MANIFEST).is_file() {
+        return Err(format!(
+            "Profile `{profile}` is composed from {PROFILE_MANIFEST}; save to another profile instead."
+        ));
+    }
+    let target_file = selected_target_file(cli_type);
+    if std::fs::metadata(workspace_root.join(target_file)).is_err() {
+        return Err(format!("The workspace has no {target_file} to save"));
+    }
+    for kind in [
+        FileKind::Agents,
+        FileKind::Claude,
+        FileKind::Gemini,
+        FileKind::CursorRules,
+    ] {
+        let file = instructions_file(kind);
+        let content = match std::fs::read(workspace_root.join(file)) {
+            Ok(content) => content,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(format!("Failed to read {file}: {err}")),
+        };
+        let source = profile_dir.join(file);
+        // A symlink to this profile's own file is already saved.
+        let same_file = source
+            .canonicalize()
+            .ok()
+            .zip(workspace_root.join(file).canonicalize().ok())
+            .is_some_and(|(source, target)| source == target);
+        if !same_file {
+            write_atomic(&source, &content)
+                .map_err(|err| format!("Failed to save {file} to profile `{profile}`: {err}"))?;
+        }
+    }
+    let target_is_file = std::fs::symlink_metadata(workspace_root.join(target_file))
+        .is_ok_and(|metadata| metadata.file_type().is_file());
+    if target_is_file {
+        write_profile_state(
+            workspace_root,
+            profile,
+            target_file,
+            AgentProfileWriteMode::Copy,
+        )?;
+    }
+    Ok(profile_status_at(workspace_root, cli_type))
+}
+
+/// Compares the workspace's instructions file with the profile last applied
+/// to it.
+pub(crate) async fn agent_profile_status_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    cli_type: &str,
+) -> Result<AgentProfileStatus, String> {
+    let workspace_root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    Ok(profile_status_at(&workspace_root, cli_type))
+}
+
+/// Resolves drift by reapplying the profile or saving the edited file back
+/// into it.
+pub(crate) async fn agent_profile_sync_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
+    workspace_id: String,
+    cli_type: &str,
+    action: AgentProfileSyncAction,
+) -> Result<AgentProfileStatus, String> {
+    let workspace_root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    let status = sync_profile_at(&workspace_root, cli_type, action)?;
+    audit_log.record(
+        "agent_profile_sync",
+        json!({ "workspaceId": workspace_id, "action": action }),
+    );
+    Ok(status)
+}
+
+pub(crate) async fn agent_profile_save_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
+    workspace_id: String,
+    profile: String,
+    cli_type: &str,
+) -> Result<AgentProfileStatus, String> {
+    let workspace_root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    let status = save_profile_at(&workspace_root, &profile, cli_type)?;
+    audit_log.record(
+        "agent_profile_save",
+        json!({ "workspaceId": workspace_id, "profile": profile }),
+    );
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        apply_agent_profile_at, detect_agent_profile_at, list_profiles, profile_status_at,
+        save_profile_at, sync_profile_at, AgentProfileApplyMode, AgentProfileDrift,
+        AgentProfileSyncAction, AgentProfileWriteMode,
+    };
+    use std::fs;
+    use uuid::Uuid;
+
+    #[test]
+    fn composes_extended_profiles_and_fragments_in_order() {
+        let root = std::env::temp_dir().join(format!("agent-profiles-{}", Uuid::new_v4()));
+        let profiles = root.join("profiles");
+        for (path, content) in [
+            ("base/AGENTS.md", "# Base\n"),
+            ("rust/AGENTS.md", "## Rust\n"),
+            ("rust/profile.toml", "extends = [\"base\"]\n"),
+            (
+                "strict/profile.toml",
+                "extends = [\"base\", \"rust\"]\nfragments = [\"review.md\"]\n",
+            ),
+            ("strict/review.md", "## Review\n\nBe picky.\n"),
+            ("loop/profile.toml", "extends = [\"loop\"]\n"),
+        ] {
+            let path = profiles.join(path);
+            fs::create_dir_all(path.parent().unwrap()).expect("create profile dir");
+            fs::write(path, content).expect("write profile file");
+        }
+
+        let listed = list_profiles(&root).expect("list");
+        let strict = listed
+            .iter()
+            .find(|profile| profile.name == "strict")
+            .unwrap();
+        assert!(strict.has_agents && strict.has_claude && strict.has_gemini);
+        assert_eq!(strict.extends, ["base", "rust"]);
+        assert!(!listed.iter().any(|profile| profile.name == "loop"));
+
+        let applied = apply_agent_profile_at(
+            &root,
+            "strict".to_string(),
+            "codex",
+            AgentProfileApplyMode::Auto,
+        )
+        .expect("apply");
+        assert_eq!(applied.active_mode, AgentProfileWriteMode::Copy);
+        assert_eq!(
+            fs::read_to_string(root.join("AGENTS.md")).unwrap(),
+            "# Base\n\n## Rust\n\n## Review\n\nBe picky.\n"
+        );
+        assert_eq!(
+            detect_agent_profile_at(&root, "codex").as_deref(),
+            Some("strict")
+        );
+
+        let error = apply_agent_profile_at(
+            &root,
+            "loop".to_string(),
+            "codex",
+            AgentProfileApplyMode::Copy,
+        )
+        .unwrap_err();
+        assert_eq!(error, "Profile inheritance cycle: loop -> loop");
+        let _ = fs::remove_dir_all(&root);
+    }
+    #[test]
+    fn reports_and_resolves_drift_of_a_copied_profile() {
+        let root = std::env::temp_dir().join(format!("agent-profile-drift-{}", Uuid::new_v4()));
+        let source = root.join("profiles/team/AGENTS.md");
+        fs::create_dir_all(source.parent().unwrap()).expect("create profile dir");
+        fs::write(&source, "# Team\n\nRun tests.\n").expect("write profile");
+        assert_eq!(
+            profile_status_at(&root, "codex").drift,
+            AgentProfileDrift::Unmanaged
+        );
+        apply_agent_profile_at(
+            &root,
+            "team".to_string(),
+            "codex",
+            AgentProfileApplyMode::Copy,
+        )
+        .expect("apply");
+        assert_eq!(
+            profile_status_at(&root, "codex").drift,
+            AgentProfileDrift::InSync
+        );
+
+        fs::write(root.join("AGENTS.md"), "# Team\n\nRun all tests.\n").expect("edit");
+        let status = profile_status_at(&root, "codex");
+        assert_eq!(status.drift, AgentProfileDrift::Modified);
+        assert_eq!(
+            status.diff.as_deref(),
+            Some(
+                "--- profiles/team/AGENTS.md\n+++ AGENTS.md\n@@ -1,3 +1,3 @@\n # Team\n \n-Run tests.\n+Run all tests.\n"
+            )
+        );
+        assert_eq!(
+            status.actions,
+            [
+                AgentProfileSyncAction::Reapply,
+                AgentProfileSyncAction::SaveBackToProfile
+            ]
+        );
+
+        let status = sync_profile_at(&root, "codex", AgentProfileSyncAction::SaveBackToProfile)
+            .expect("save back");
+        assert_eq!(status.drift, AgentProfileDrift::InSync);
+        assert_eq!(
+            fs::read_to_string(&source).unwrap(),
+            "# Team\n\nRun all tests.\n"
+        );
+
+        fs::remove_file(root.join("AGENTS.md")).expect("delete");
+        assert_eq!(
+            profile_status_at(&root, "codex").drift,
+            AgentProfileDrift::Missing
+        );
+        let status =
+            sync_profile_at(&root, "codex", AgentProfileSyncAction::Reapply).expect("reapply");
+        assert_eq!(status.drift, AgentProfileDrift::InSync);
+        assert_eq!(status.active_mode, Some(AgentProfileWriteMode::Copy));
+
+        fs::write(root.join("AGENTS.md"), "# Tuned\n").expect("edit");
+        fs::write(root.join("GEMINI.md"), "# Gemini\n").expect("write gemini");
+        let status = save_profile_at(&root, "tuned", "codex").expect("save");
+        assert_eq!(status.profile.as_deref(), Some("tuned"));
+        assert_eq!(status.drift, AgentProfileDrift::InSync);
+        let tuned = list_profiles(&root)
+            .unwrap()
+            .into_iter()
+            .find(|p| p.name == "tuned");
+        assert!(tuned.is_some_and(|tuned| tuned.has_agents && tuned.has_gemini));
+        assert!(save_profile_at(&root, "../escape", "codex").is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
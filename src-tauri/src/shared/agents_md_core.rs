@@ -0,0 +1,301 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::files::ops::{read_with_policy, write_with_policy};
+use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::files_core::resolve_root_core;
+use crate::types::WorkspaceEntry;
+
+/// Level of a section `agents_md_section_update` appends when the heading is
+/// given without `#` marks.
+const DEFAULT_SECTION_LEVEL: usize = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentsMdSection {
+    pub(crate) heading: String,
+    pub(crate) level: usize,
+    /// 1-based line of the heading.
+    pub(crate) line: usize,
+    /// Text below the heading up to the next heading of the same or a
+    /// higher level, so subsections are included.
+    pub(crate) content: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AgentsMdSectionsResponse {
+    pub(crate) exists: bool,
+    /// Text ahead of the first heading.
+    pub(crate) preamble: String,
+    pub(crate) sections: Vec<AgentsMdSection>,
+}
+
+struct Heading {
+    index: usize,
+    level: usize,
+    text: String,
+}
+
+/// Level and text of an ATX heading line (`## Testing`, `## Testing ##`).
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.len() - rest.trim_start_matches('#').len();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &rest[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    let text = rest.trim();
+    let without_closing = text.trim_end_matches('#');
+    let text = if without_closing.is_empty() || without_closing.ends_with([' ', '\t']) {
+        without_closing.trim_end()
+    } else {
+        text
+    };
+    Some((level, text.to_string()))
+}
+
+/// Headings outside fenced code blocks.
+fn find_headings(lines: &[&str]) -> Vec<Heading> {
+    let mut headings = Vec::new();
+    let mut fence: Option<&str> = None;
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"]
+            .into_iter()
+            .find(|marker| trimmed.starts_with(marker));
+        match (fence, marker) {
+            (Some(open), Some(marker)) if open == marker => fence = None,
+            (None, Some(marker)) => fence = Some(marker),
+            (None, None) => {
+                if let Some((level, text)) = parse_heading(line) {
+                    headings.push(Heading { index, level, text });
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// Index of the line that ends the section opened by `headings[position]`.
+fn section_end(headings: &[Heading], position: usize, line_count: usize) -> usize {
+    let level = headings[position].level;
+    headings[position + 1..]
+        .iter()
+        .find(|heading| heading.level <= level)
+        .map_or(line_count, |heading| heading.index)
+}
+
+fn join_lines(lines: &[&str]) -> String {
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+fn parse_sections(markdown: &str) -> (String, Vec<AgentsMdSection>) {
+    let lines = markdown.lines().collect::<Vec<_>>();
+    let headings = find_headings(&lines);
+    let preamble_end = headings
+        .first()
+        .map_or(lines.len(), |heading| heading.index);
+    let sections = headings
+        .iter()
+        .enumerate()
+        .map(|(position, heading)| AgentsMdSection {
+            heading: heading.text.clone(),
+            level: heading.level,
+            line: heading.index + 1,
+            content: join_lines(
+                &lines[heading.index + 1..section_end(&headings, position, lines.len())],
+            ),
+        })
+        .collect();
+    (join_lines(&lines[..preamble_end]), sections)
+}
+
+/// Replaces the body of the section titled `heading`, leaving the rest of
+/// the file as it was. `heading` may carry `#` marks to pick a level, which
+/// is also the level of the section appended when none matches.
+fn update_section(markdown: &str, heading: &str, content: &str) -> Result<String, String> {
+    let (wanted_level, wanted_text) = match parse_heading(heading) {
+        Some((level, text)) => (Some(level), text),
+        None => (None, heading.trim().to_string()),
+    };
+    if wanted_text.is_empty() {
+        return Err("heading is required".to_string());
+    }
+    let line_ending = if markdown.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let lines = markdown.lines().collect::<Vec<_>>();
+    let headings = find_headings(&lines);
+    let matches = headings
+        .iter()
+        .enumerate()
+        .filter(|(_, found)| {
+            found.text == wanted_text && wanted_level.is_none_or(|level| level == found.level)
+        })
+        .map(|(position, _)| position)
+        .collect::<Vec<_>>();
+    if matches.len() > 1 {
+        return Err(format!(
+            "\"{wanted_text}\" matches {} sections; prefix it with # marks to pick one",
+            matches.len()
+        ));
+    }
+    let level = match matches.first() {
+        Some(position) => headings[*position].level,
+        None => wanted_level.unwrap_or(DEFAULT_SECTION_LEVEL),
+    };
+
+    let body = content.replace("\r\n", "\n");
+    let body = body.trim_matches('\n');
+    let body_lines = body.lines().collect::<Vec<_>>();
+    if let Some(nested) = find_headings(&body_lines)
+        .iter()
+        .find(|nested| nested.level <= level)
+    {
+        return Err(format!(
+            "content can't contain the heading \"{}\" at level {}; it would split the section",
+            nested.text, nested.level
+        ));
+    }
+
+    let mut out: Vec<&str> = Vec::with_capacity(lines.len() + body_lines.len() + 3);
+    let heading_line = format!("{} {wanted_text}", "#".repeat(level));
+    match matches.first() {
+        Some(position) => {
+            let start = headings[*position].index;
+            let end = section_end(&headings, *position, lines.len());
+            out.extend(&lines[..=start]);
+            if !body.is_empty() {
+                out.push("");
+                out.extend(&body_lines);
+            }
+            if end < lines.len() {
+                out.push("");
+                out.extend(&lines[end..]);
+            }
+        }
+        None => {
+            let existing = markdown.trim_end_matches(['\r', '\n']);
+            if !existing.is_empty() {
+                out.extend(existing.lines());
+                out.push("");
+            }
+            out.push(&heading_line);
+            if !body.is_empty() {
+                out.push("");
+                out.extend(&body_lines);
+            }
+        }
+    }
+    let mut updated = out.join(line_ending);
+    updated.push_str(line_ending);
+    Ok(updated)
+}
+
+async fn read_agents_md(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<(std::path::PathBuf, bool, String), String> {
+    let policy = policy_for(FileScope::Workspace, FileKind::Agents)?;
+    let root = resolve_root_core(workspaces, FileScope::Workspace, Some(workspace_id)).await?;
+    let response = read_with_policy(&root, policy)?;
+    if response.truncated {
+        return Err("AGENTS.md is too large to edit by section".to_string());
+    }
+    Ok((root, response.exists, response.content))
+}
+
+fn sections_response(exists: bool, markdown: &str) -> AgentsMdSectionsResponse {
+    let (preamble, sections) = parse_sections(markdown);
+    AgentsMdSectionsResponse {
+        exists,
+        preamble,
+        sections,
+    }
+}
+
+pub(crate) async fn agents_md_sections_list_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<AgentsMdSectionsResponse, String> {
+    let (_, exists, markdown) = read_agents_md(workspaces, &workspace_id).await?;
+    Ok(sections_response(exists, &markdown))
+}
+
+/// Rewrites one section of the workspace's `AGENTS.md`, creating the file
+/// or appending the section when missing.
+pub(crate) async fn agents_md_section_update_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
+    workspace_id: String,
+    heading: String,
+    content: String,
+) -> Result<AgentsMdSectionsResponse, String> {
+    let (root, _, markdown) = read_agents_md(workspaces, &workspace_id).await?;
+    let updated = update_section(&markdown, &heading, &content)?;
+    let policy = policy_for(FileScope::Workspace, FileKind::Agents)?;
+    write_with_policy(&root, policy, &updated)?;
+    audit_log.record(
+        "agents_md_section_update",
+        json!({ "workspaceId": workspace_id, "heading": heading, "bytes": content.len() }),
+    );
+    Ok(sections_response(true, &updated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_sections, update_section};
+
+    const AGENTS_MD: &str = "# Project\n\nIntro.\n\n## Testing\n\nRun `cargo test`.\n\n### Slow tests\n\nSkip them.\n\n```md\n## Not a heading\n```\n\n## Style\n\nUse rustfmt.\n";
+
+    #[test]
+    fn updates_one_section_and_keeps_the_rest() {
+        let (preamble, sections) = parse_sections(AGENTS_MD);
+        assert_eq!(preamble, "");
+        let headings = sections
+            .iter()
+            .map(|section| (section.heading.as_str(), section.level))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headings,
+            [
+                ("Project", 1),
+                ("Testing", 2),
+                ("Slow tests", 3),
+                ("Style", 2)
+            ]
+        );
+        assert!(sections[1].content.contains("## Not a heading"));
+
+        let updated = update_section(AGENTS_MD, "Testing", "Run `cargo test --workspace`.\n")
+            .expect("update");
+        assert_eq!(
+            updated,
+            "# Project\n\nIntro.\n\n## Testing\n\nRun `cargo test --workspace`.\n\n## Style\n\nUse rustfmt.\n"
+        );
+
+        let appended = update_section(AGENTS_MD, "### Commits", "Sign them.").expect("append");
+        assert!(appended.starts_with(AGENTS_MD));
+        assert!(appended.ends_with("Use rustfmt.\n\n### Commits\n\nSign them.\n"));
+
+        let error = update_section(AGENTS_MD, "Testing", "## Style\nnope").unwrap_err();
+        assert!(error.contains("would split the section"));
+        let crlf = update_section("## A\r\n\r\nold\r\n", "A", "new").expect("crlf");
+        assert_eq!(crlf, "## A\r\n\r\nnew\r\n");
+    }
+}
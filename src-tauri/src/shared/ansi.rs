@@ -0,0 +1,92 @@
+use std::borrow::Cow;
+
+use serde_json::Value;
+
+const ESC: char = '\u{1b}';
+const BEL: char = '\u{7}';
+
+/// Removes terminal escape sequences (colors, cursor movement, window
+/// titles) from CLI output. A carriage return that isn't part of a line break
+/// overwrites the current line, as it would on a terminal, so progress-bar
+/// redraws keep only their last state.
+pub(crate) fn strip_ansi(text: &str) -> Cow<'_, str> {
+    if !text.contains([ESC, '\r']) {
+        return Cow::Borrowed(text);
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            ESC => match chars.next() {
+                // CSI: parameters and intermediates up to a final byte in `@`..`~`.
+                Some('[') => {
+                    for next in chars.by_ref() {
+                        if ('@'..='~').contains(&next) {
+                            break;
+                        }
+                    }
+                }
+                // OSC, DCS, APC, PM: terminated by BEL or ESC `\`.
+                Some(']' | 'P' | '_' | '^') => {
+                    while let Some(next) = chars.next() {
+                        if next == BEL {
+                            break;
+                        }
+                        if next == ESC && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                // Character set selection takes one more byte.
+                Some('(' | ')' | '*' | '+') => {
+                    chars.next();
+                }
+                _ => {}
+            },
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\r' => output.truncate(output.rfind('\n').map_or(0, |index| index + 1)),
+            _ => output.push(ch),
+        }
+    }
+    Cow::Owned(output)
+}
+
+/// Strips escape sequences from every string in `value` in place.
+pub(crate) fn strip_ansi_value(value: &mut Value) {
+    match value {
+        Value::String(text) => {
+            if let Cow::Owned(stripped) = strip_ansi(text) {
+                *text = stripped;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_ansi_value),
+        Value::Object(map) => map.values_mut().for_each(strip_ansi_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_ansi, strip_ansi_value};
+    use serde_json::json;
+    use std::borrow::Cow;
+
+    #[test]
+    fn strips_color_cursor_and_title_sequences() {
+        assert!(matches!(strip_ansi("plain text"), Cow::Borrowed(_)));
+        assert_eq!(strip_ansi("\u{1b}[1;32mok\u{1b}[0m done\r\n"), "ok done\n");
+        assert_eq!(
+            strip_ansi("\u{1b}]0;claude\u{7}\u{1b}[2K\u{1b}[1Gprogress 50%\rprogress 100%"),
+            "progress 100%"
+        );
+        assert_eq!(
+            strip_ansi("\u{1b}]8;;https://x\u{1b}\\link\u{1b}(B"),
+            "link"
+        );
+
+        let mut event = json!({ "params": { "delta": "\u{1b}[31merror\u{1b}[39m", "n": 1 } });
+        strip_ansi_value(&mut event);
+        assert_eq!(event, json!({ "params": { "delta": "error", "n": 1 } }));
+    }
+}
@@ -0,0 +1,123 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+
+/// Errors returned to the frontend, with a stable `code` to branch on and
+/// whether trying again may help. Serialized as
+/// `{ "code", "message", "retriable" }`.
+///
+/// The settings, workspace, git, file and thread commands return it. Core
+/// functions still return `Result<_, String>` and convert with `?` through
+/// the `From` impls below: the shared "workspace not found" and "workspace
+/// not connected" messages keep their codes, other strings become
+/// `internal`.
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum AppError {
+    #[error("{name} CLI not found. Install {name} and ensure `{bin}` is on your PATH.")]
+    CliNotFound { name: String, bin: String },
+    #[error("workspace not found")]
+    WorkspaceNotFound,
+    #[error("workspace not connected")]
+    WorkspaceNotConnected,
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Timeout(String),
+    /// A remote service, such as the remote backend or the npm registry,
+    /// couldn't be reached or answered with an error.
+    #[error("{0}")]
+    Remote(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+pub(crate) type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            AppError::CliNotFound { .. } => "cli_not_found",
+            AppError::WorkspaceNotFound => "workspace_not_found",
+            AppError::WorkspaceNotConnected => "workspace_not_connected",
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::Io(_) => "io",
+            AppError::Timeout(_) => "timeout",
+            AppError::Remote(_) => "remote",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    pub(crate) fn retriable(&self) -> bool {
+        match self {
+            AppError::WorkspaceNotConnected | AppError::Timeout(_) | AppError::Remote(_) => true,
+            AppError::Io(err) => matches!(
+                err.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::WouldBlock
+            ),
+            _ => false,
+        }
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        match message.as_str() {
+            "workspace not found" => AppError::WorkspaceNotFound,
+            "workspace not connected" => AppError::WorkspaceNotConnected,
+            _ => AppError::Internal(message),
+        }
+    }
+}
+
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.serialize_field("retriable", &self.retriable())?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::AppError;
+    use serde_json::json;
+
+    #[test]
+    fn serializes_code_message_and_retriable() {
+        let error = AppError::CliNotFound {
+            name: "Codex".to_string(),
+            bin: "codex".to_string(),
+        };
+        assert_eq!(
+            serde_json::to_value(&error).expect("serialize"),
+            json!({
+                "code": "cli_not_found",
+                "message": "Codex CLI not found. Install Codex and ensure `codex` is on your PATH.",
+                "retriable": false,
+            })
+        );
+
+        let timed_out = std::io::Error::from(std::io::ErrorKind::TimedOut);
+        assert!(AppError::from(timed_out).retriable());
+        let legacy = AppError::from("disk full".to_string());
+        assert_eq!(legacy.code(), "internal");
+        let missing = AppError::from("workspace not found".to_string());
+        assert_eq!(missing.code(), "workspace_not_found");
+        assert!(AppError::from("workspace not connected".to_string()).retriable());
+        assert_eq!(
+            String::from(AppError::WorkspaceNotFound),
+            "workspace not found"
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::sync::Mutex;
+
+use crate::backend::adapter_base::adapter_threads_dir;
+use crate::shared::agent_profiles_core::PROFILE_STATE_FILE;
+use crate::shared::atomic_write::{write_atomic, write_with_backup};
+use crate::shared::redaction_core::looks_like_secret_name;
+use crate::shared::runtime_services::RuntimeServices;
+use crate::shared::settings_core::update_app_settings_core;
+use crate::storage::write_workspaces;
+use crate::types::{AppSettings, WorkspaceEntry};
+
+const ARCHIVE_VERSION: u32 = 1;
+const KEYCHAIN_PREFIX: &str = "keychain:";
+
+/// Everything needed to set the monitor up again on another machine.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AppStateArchive {
+    version: u32,
+    exported_at: u64,
+    settings: AppSettings,
+    workspaces: Vec<WorkspaceEntry>,
+    /// Adapter thread stores and turn metrics, by file name.
+    #[serde(default)]
+    thread_files: BTreeMap<String, Value>,
+    /// The agent profile state of each workspace, by workspace id.
+    #[serde(default)]
+    agent_profile_states: BTreeMap<String, Value>,
+}
+
+/// What an export wrote or an import restored.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AppStateTransfer {
+    pub(crate) workspaces: usize,
+    pub(crate) thread_files: usize,
+    pub(crate) agent_profile_states: usize,
+    /// Secrets left out of the archive, to be re-entered after importing.
+    pub(crate) excluded_secrets: Vec<String>,
+}
+
+/// Removes credential-like variables from `env`, keeping keychain
+/// references, and returns their names sorted.
+fn strip_secret_env(env: &mut HashMap<String, String>) -> Vec<String> {
+    let mut names = env
+        .iter()
+        .filter(|(name, value)| {
+            looks_like_secret_name(name) && !value.trim_start().starts_with(KEYCHAIN_PREFIX)
+        })
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    names.sort();
+    for name in &names {
+        env.remove(name);
+    }
+    names
+}
+
+/// Removes the remote backend and HTTP API tokens and workspace variables that hold a
+/// credential in plain text. Keychain references are kept; the keychain
+/// entries themselves stay on this machine.
+fn strip_secrets(settings: &mut AppSettings, workspaces: &mut [WorkspaceEntry]) -> Vec<String> {
+    let mut excluded = Vec::new();
+    if settings.remote_backend_token.take().is_some() {
+        excluded.push("remoteBackendToken".to_string());
+    }
+    if settings.http_api_token.take().is_some() {
+        excluded.push("httpApiToken".to_string());
+    }
+    for entry in workspaces.iter_mut() {
+        for name in strip_secret_env(&mut entry.settings.env) {
+            excluded.push(format!("{}: {name}", entry.name));
+        }
+        if let Some(docker) = entry.settings.docker.as_mut() {
+            for name in strip_secret_env(&mut docker.env) {
+                excluded.push(format!("{}: docker.env.{name}", entry.name));
+            }
+        }
+    }
+    excluded
+}
+
+/// Archived files are written back into the adapter threads dir, so only
+/// plain `.json` file names are accepted.
+fn is_thread_file_name(name: &str) -> bool {
+    name.ends_with(".json")
+        && !name.starts_with('.')
+        && !name.contains(['/', '\\'])
+        && Path::new(name).file_name().is_some_and(|file| file == name)
+}
+
+fn read_thread_files(dir: &Path, workspaces: &[WorkspaceEntry]) -> BTreeMap<String, Value> {
+    let mut files = BTreeMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let owned = workspaces
+            .iter()
+            .any(|workspace| name.starts_with(&format!("{}.", workspace.id)));
+        if !owned || !is_thread_file_name(&name) {
+            continue;
+        }
+        let contents = std::fs::read_to_string(entry.path()).ok();
+        if let Some(value) = contents.and_then(|data| serde_json::from_str(&data).ok()) {
+            files.insert(name, value);
+        }
+    }
+    files
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Writes settings, the workspace registry, adapter thread stores, turn
+/// metrics and agent profile state to `path` as one JSON archive.
+///
+/// Usage data is left out: the usage snapshot is scanned from the CLIs' own
+/// session logs, which aren't copied, and budget overrides only apply to the
+/// month they were made in. Monthly budgets carry over through the turn
+/// metrics.
+pub(crate) async fn app_state_export_core(
+    path: &str,
+    app_settings: &Mutex<AppSettings>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+) -> Result<AppStateTransfer, String> {
+    let mut settings = app_settings.lock().await.clone();
+    let mut entries = workspaces
+        .lock()
+        .await
+        .values()
+        .cloned()
+        .collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.id.cmp(&b.id));
+    let excluded_secrets = strip_secrets(&mut settings, &mut entries);
+    let thread_files = read_thread_files(&adapter_threads_dir(), &entries);
+    let agent_profile_states = entries
+        .iter()
+        .filter_map(|entry| {
+            let data = std::fs::read_to_string(Path::new(&entry.path).join(PROFILE_STATE_FILE));
+            let state = serde_json::from_str::<Value>(&data.ok()?).ok()?;
+            Some((entry.id.clone(), state))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let transfer = AppStateTransfer {
+        workspaces: entries.len(),
+        thread_files: thread_files.len(),
+        agent_profile_states: agent_profile_states.len(),
+        excluded_secrets,
+    };
+    let archive = AppStateArchive {
+        version: ARCHIVE_VERSION,
+        exported_at: now_secs(),
+        settings,
+        workspaces: entries,
+        thread_files,
+        agent_profile_states,
+    };
+    let data = serde_json::to_string_pretty(&archive).map_err(|err| err.to_string())?;
+    write_atomic(Path::new(path), data.as_bytes())
+        .map_err(|err| format!("Failed to write {path}: {err}"))?;
+    Ok(transfer)
+}
+
+/// Restores an archive written by `app_state_export_core`. Archived
+/// workspaces replace registered ones with the same id, keeping variables
+/// the archive left out; agent profile state is only restored into
+/// workspace folders that exist and have none. Secrets excluded from the
+/// archive are not restored.
+pub(crate) async fn app_state_import_core(
+    path: &str,
+    app_settings: &Mutex<AppSettings>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    settings_path: &PathBuf,
+    storage_path: &PathBuf,
+    services: &RuntimeServices,
+) -> Result<AppStateTransfer, String> {
+    let data =
+        std::fs::read_to_string(path).map_err(|err| format!("Failed to read {path}: {err}"))?;
+    let archive: AppStateArchive =
+        serde_json::from_str(&data).map_err(|err| format!("Invalid app state archive: {err}"))?;
+    if archive.version > ARCHIVE_VERSION {
+        return Err(format!(
+            "The archive is from a newer version (format {})",
+            archive.version
+        ));
+    }
+
+    let mut transfer = AppStateTransfer::default();
+    let mut settings = archive.settings;
+    {
+        let current = app_settings.lock().await;
+        settings.remote_backend_token = current.remote_backend_token.clone();
+        settings.http_api_token = current.http_api_token.clone();
+    }
+    update_app_settings_core(settings, app_settings, settings_path, services).await?;
+
+    {
+        let mut registry = workspaces.lock().await;
+        for mut entry in archive.workspaces {
+            if let Some(existing) = registry.get(&entry.id) {
+                for (name, value) in &existing.settings.env {
+                    entry
+                        .settings
+                        .env
+                        .entry(name.clone())
+                        .or_insert_with(|| value.clone());
+                }
+                if let (Some(docker), Some(existing_docker)) = (
+                    entry.settings.docker.as_mut(),
+                    existing.settings.docker.as_ref(),
+                ) {
+                    for (name, value) in &existing_docker.env {
+                        docker
+                            .env
+                            .entry(name.clone())
+                            .or_insert_with(|| value.clone());
+                    }
+                }
+            }
+            registry.insert(entry.id.clone(), entry);
+            transfer.workspaces += 1;
+        }
+        let list: Vec<_> = registry.values().cloned().collect();
+        write_workspaces(storage_path, &list)?;
+
+        for (workspace_id, state) in &archive.agent_profile_states {
+            let Some(entry) = registry.get(workspace_id) else {
+                continue;
+            };
+            let root = Path::new(&entry.path);
+            let state_path = root.join(PROFILE_STATE_FILE);
+            if !root.is_dir() || state_path.exists() {
+                continue;
+            }
+            let data = serde_json::to_string_pretty(state).map_err(|err| err.to_string())?;
+            std::fs::write(&state_path, data)
+                .map_err(|err| format!("Failed to write {}: {err}", state_path.display()))?;
+            transfer.agent_profile_states += 1;
+        }
+    }
+
+    let dir = adapter_threads_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+    for (name, contents) in &archive.thread_files {
+        if !is_thread_file_name(name) {
+            continue;
+        }
+        let data = serde_json::to_string_pretty(contents).map_err(|err| err.to_string())?;
+        write_with_backup(&dir.join(name), data.as_bytes())
+            .map_err(|err| format!("Failed to restore {name}: {err}"))?;
+        transfer.thread_files += 1;
+    }
+    Ok(transfer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_thread_file_name, strip_secrets};
+    use crate::types::{
+        AppSettings, DockerTarget, WorkspaceEntry, WorkspaceKind, WorkspaceSettings,
+    };
+
+    #[test]
+    fn leaves_secrets_and_unsafe_file_names_out() {
+        let mut settings = AppSettings {
+            remote_backend_token: Some("token".to_string()),
+            ..AppSettings::default()
+        };
+        let mut workspace_settings = WorkspaceSettings::default();
+        for (name, value) in [
+            ("OPENAI_API_KEY", "sk-live"),
+            ("GITHUB_TOKEN", "keychain:github"),
+            ("RUST_LOG", "debug"),
+        ] {
+            workspace_settings
+                .env
+                .insert(name.to_string(), value.to_string());
+        }
+        let mut docker = DockerTarget::default();
+        for (name, value) in [("GITHUB_TOKEN", "ghp_live"), ("TZ", "UTC")] {
+            docker.env.insert(name.to_string(), value.to_string());
+        }
+        workspace_settings.docker = Some(docker);
+        let mut workspaces = vec![WorkspaceEntry {
+            id: "ws-1".to_string(),
+            name: "app".to_string(),
+            path: "/tmp/app".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::default(),
+            parent_id: None,
+            worktree: None,
+            settings: workspace_settings,
+        }];
+
+        let excluded = strip_secrets(&mut settings, &mut workspaces);
+        assert_eq!(
+            excluded,
+            [
+                "remoteBackendToken",
+                "app: OPENAI_API_KEY",
+                "app: docker.env.GITHUB_TOKEN"
+            ]
+        );
+        assert!(settings.remote_backend_token.is_none());
+        let env = &workspaces[0].settings.env;
+        assert_eq!(env.len(), 2);
+        assert_eq!(env["GITHUB_TOKEN"], "keychain:github");
+        let docker_env = &workspaces[0].settings.docker.as_ref().expect("docker").env;
+        assert_eq!(docker_env.len(), 1);
+        assert_eq!(docker_env["TZ"], "UTC");
+
+        assert!(is_thread_file_name("ws-1.metrics.json"));
+        assert!(!is_thread_file_name("../settings.json"));
+        assert!(!is_thread_file_name("ws-1.txt"));
+    }
+}
@@ -0,0 +1,133 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// `<path>.bak`: the contents `path` had before its last write.
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    path.with_file_name(name)
+}
+
+/// Writes `data` to a temp file next to `path`, syncs it to disk and renames
+/// it over `path`, so a crash leaves either the old or the new contents and
+/// never a partial file. An existing file's permissions are kept.
+pub(crate) fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    std::fs::create_dir_all(parent)?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(path.file_name().unwrap_or_default());
+    temp_name.push(format!(".{}.tmp", uuid::Uuid::new_v4()));
+    let temp_path = parent.join(temp_name);
+
+    let result = (|| {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(data)?;
+        file.sync_all()?;
+        drop(file);
+        if let Ok(metadata) = std::fs::metadata(path) {
+            std::fs::set_permissions(&temp_path, metadata.permissions())?;
+        }
+        std::fs::rename(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp_path);
+        return result;
+    }
+    // Persist the rename itself. Directories can't be opened this way on
+    // Windows, where the rename is already durable.
+    #[cfg(unix)]
+    if let Ok(dir) = File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// [`write_atomic`], after keeping the current contents in `<path>.bak` for
+/// [`read_with_backup`] to fall back on.
+pub(crate) fn write_with_backup(path: &Path, data: &[u8]) -> io::Result<()> {
+    match std::fs::read(path) {
+        Ok(current) if !current.is_empty() && current != data => {
+            write_atomic(&backup_path(path), &current)?;
+        }
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => return Err(err),
+    }
+    write_atomic(path, data)
+}
+
+/// Reads and parses `path`. When the file can't be read or parsed, the
+/// `.bak` copy is used instead and restored over it. `None` when `path`
+/// doesn't exist.
+pub(crate) fn read_with_backup<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> Result<T, String>,
+) -> Result<Option<T>, String> {
+    let error = match std::fs::read_to_string(path) {
+        Ok(data) => match parse(&data) {
+            Ok(value) => return Ok(Some(value)),
+            Err(err) => err,
+        },
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => err.to_string(),
+    };
+    let backup = backup_path(path);
+    let Some((data, value)) = std::fs::read_to_string(&backup)
+        .ok()
+        .and_then(|data| parse(&data).ok().map(|value| (data, value)))
+    else {
+        return Err(error);
+    };
+    tracing::warn!(
+        "{} is unreadable ({error}); restoring it from {}",
+        path.display(),
+        backup.display()
+    );
+    if let Err(err) = write_atomic(path, data.as_bytes()) {
+        tracing::warn!("failed to restore {}: {err}", path.display());
+    }
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backup_path, read_with_backup, write_with_backup};
+    use uuid::Uuid;
+
+    fn parse(data: &str) -> Result<Vec<u32>, String> {
+        serde_json::from_str(data).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn recovers_a_truncated_file_from_its_backup() {
+        let dir = std::env::temp_dir().join(format!("atomic-write-{}", Uuid::new_v4()));
+        let path = dir.join("threads.json");
+        assert_eq!(read_with_backup(&path, parse), Ok(None));
+
+        write_with_backup(&path, b"[1]").expect("first write");
+        write_with_backup(&path, b"[1,2]").expect("second write");
+        assert_eq!(std::fs::read_to_string(backup_path(&path)).unwrap(), "[1]");
+        assert_eq!(read_with_backup(&path, parse), Ok(Some(vec![1, 2])));
+
+        std::fs::write(&path, "").expect("truncate");
+        assert_eq!(read_with_backup(&path, parse), Ok(Some(vec![1])));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[1]");
+
+        std::fs::write(backup_path(&path), "[").expect("corrupt backup");
+        std::fs::write(&path, "").expect("truncate");
+        assert!(read_with_backup(&path, parse).is_err());
+        let leftovers = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter(|entry| {
+                let name = entry.as_ref().unwrap().file_name();
+                name.to_string_lossy().ends_with(".tmp")
+            })
+            .count();
+        assert_eq!(leftovers, 0);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
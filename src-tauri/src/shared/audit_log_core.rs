@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+const AUDIT_LOG_FILE: &str = "audit.jsonl";
+/// `prevHash` of the first entry.
+const GENESIS_HASH: &str = "0000000000000000000000000000000000000000";
+const DEFAULT_QUERY_LIMIT: usize = 500;
+
+/// One line of the audit log. `hash` covers every other field, including
+/// the previous entry's hash, so editing or dropping a line breaks the
+/// chain from there on.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditEntry {
+    pub(crate) seq: u64,
+    pub(crate) timestamp: String,
+    pub(crate) action: String,
+    /// `local` for the app, `remote` for the daemon serving a remote client.
+    pub(crate) origin: String,
+    pub(crate) details: Value,
+    pub(crate) prev_hash: String,
+    #[serde(default)]
+    pub(crate) hash: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditLogQuery {
+    #[serde(default)]
+    pub(crate) action: Option<String>,
+    /// RFC 3339 timestamp; older entries are left out.
+    #[serde(default)]
+    pub(crate) since: Option<String>,
+    #[serde(default)]
+    pub(crate) limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct AuditLogQueryResult {
+    /// Matching entries, newest last.
+    pub(crate) entries: Vec<AuditEntry>,
+    /// Whether the whole chain verified.
+    pub(crate) verified: bool,
+    /// The line of the first entry that doesn't verify.
+    pub(crate) broken_at_line: Option<usize>,
+}
+
+struct AuditLogFile {
+    path: PathBuf,
+    origin: &'static str,
+    /// `(seq, hash)` of the last entry, read from the file on first append.
+    last: Option<(u64, String)>,
+}
+
+/// The audit log of the app or daemon. Clones append to the same file.
+#[derive(Clone, Default)]
+pub(crate) struct AuditLog(Arc<Mutex<Option<AuditLogFile>>>);
+
+impl AuditLog {
+    /// Starts recording to `audit.jsonl` in `data_dir`. Until this is called
+    /// nothing is recorded.
+    pub(crate) fn init(&self, data_dir: &Path, origin: &'static str) {
+        if let Ok(mut log) = self.0.lock() {
+            *log = Some(AuditLogFile {
+                path: data_dir.join(AUDIT_LOG_FILE),
+                origin,
+                last: None,
+            });
+        }
+    }
+
+    /// Appends `action` to the audit log. Failures are reported but don't
+    /// fail the action, which has already happened.
+    pub(crate) fn record(&self, action: &str, details: Value) {
+        let Ok(mut log) = self.0.lock() else {
+            return;
+        };
+        let Some(log) = log.as_mut() else {
+            return;
+        };
+        if let Err(error) = append_entry(log, action, details) {
+            tracing::warn!("audit log: failed to record {action}: {error}");
+        }
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        self.0
+            .lock()
+            .ok()
+            .and_then(|log| log.as_ref().map(|log| log.path.clone()))
+    }
+}
+
+fn entry_hash(entry: &AuditEntry) -> String {
+    let unhashed = AuditEntry {
+        hash: String::new(),
+        ..entry.clone()
+    };
+    let data = serde_json::to_vec(&unhashed).unwrap_or_default();
+    git2::Oid::hash_object(git2::ObjectType::Blob, &data)
+        .map(|oid| oid.to_string())
+        .unwrap_or_default()
+}
+
+/// Where the chain continues in an existing log.
+#[derive(Default)]
+struct LogTail {
+    /// `(seq, hash)` of the last entry that parses.
+    last: Option<(u64, String)>,
+    /// The first unreadable line after it, left by a crash mid-write or an
+    /// edit.
+    broken_at_line: Option<usize>,
+    /// Whether the file ends without a newline, so the next entry has to
+    /// start on a new line.
+    needs_newline: bool,
+}
+
+fn read_tail(path: &Path) -> LogTail {
+    let Ok(bytes) = std::fs::read(path) else {
+        return LogTail::default();
+    };
+    let data = String::from_utf8_lossy(&bytes);
+    let mut tail = LogTail {
+        needs_newline: !data.is_empty() && !data.ends_with('\n'),
+        ..LogTail::default()
+    };
+    let lines = data.lines().collect::<Vec<_>>();
+    for (index, line) in lines.iter().enumerate().rev() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<AuditEntry>(line) {
+            Ok(entry) => {
+                tail.last = Some((entry.seq, entry.hash));
+                break;
+            }
+            Err(_) => tail.broken_at_line = Some(index + 1),
+        }
+    }
+    tail
+}
+
+/// Appends an entry. An unreadable last line doesn't restart the chain at
+/// genesis: the chain continues from the last readable entry, behind an
+/// `audit_chain_break` entry naming the line.
+fn append_entry(log: &mut AuditLogFile, action: &str, details: Value) -> Result<(), String> {
+    if log.last.is_none() {
+        let tail = read_tail(&log.path);
+        log.last = tail.last;
+        if let Some(line) = tail.broken_at_line {
+            let prefix = if tail.needs_newline { "\n" } else { "" };
+            write_entry(
+                log,
+                prefix,
+                "audit_chain_break",
+                serde_json::json!({ "line": line }),
+            )?;
+        }
+    }
+    write_entry(log, "", action, details)
+}
+
+fn write_entry(
+    log: &mut AuditLogFile,
+    prefix: &str,
+    action: &str,
+    details: Value,
+) -> Result<(), String> {
+    let (seq, prev_hash) = match &log.last {
+        Some((seq, hash)) => (seq + 1, hash.clone()),
+        None => (0, GENESIS_HASH.to_string()),
+    };
+    let mut entry = AuditEntry {
+        seq,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        action: action.to_string(),
+        origin: log.origin.to_string(),
+        details,
+        prev_hash,
+        hash: String::new(),
+    };
+    entry.hash = entry_hash(&entry);
+    let line = serde_json::to_string(&entry).map_err(|err| err.to_string())?;
+    if let Some(parent) = log.path.parent() {
+        std::fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log.path)
+        .map_err(|err| err.to_string())?;
+    writeln!(file, "{prefix}{line}").map_err(|err| err.to_string())?;
+    file.sync_data().map_err(|err| err.to_string())?;
+    log.last = Some((entry.seq, entry.hash));
+    Ok(())
+}
+
+/// Reads the log, checking each entry's hash and link to the one before,
+/// and returns the entries matching `query`.
+fn query_log(path: &Path, query: &AuditLogQuery) -> Result<AuditLogQueryResult, String> {
+    let data = match std::fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(format!("Failed to read the audit log: {err}")),
+    };
+    let since = query
+        .since
+        .as_deref()
+        .map(chrono::DateTime::parse_from_rfc3339)
+        .transpose()
+        .map_err(|err| format!("Invalid `since` timestamp: {err}"))?;
+    let mut entries = Vec::new();
+    let mut broken_at_line = None;
+    let mut prev_hash = GENESIS_HASH.to_string();
+    for (index, line) in data.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(entry) = serde_json::from_str::<AuditEntry>(line) else {
+            broken_at_line.get_or_insert(index + 1);
+            continue;
+        };
+        if entry.prev_hash != prev_hash || entry.hash != entry_hash(&entry) {
+            broken_at_line.get_or_insert(index + 1);
+        }
+        prev_hash = entry.hash.clone();
+        let recent = since.is_none_or(|since| {
+            chrono::DateTime::parse_from_rfc3339(&entry.timestamp).is_ok_and(|at| at >= since)
+        });
+        let wanted = query
+            .action
+            .as_deref()
+            .is_none_or(|action| action == entry.action);
+        if recent && wanted {
+            entries.push(entry);
+        }
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_QUERY_LIMIT);
+    let skip = entries.len().saturating_sub(limit);
+    entries.drain(..skip);
+    Ok(AuditLogQueryResult {
+        entries,
+        verified: broken_at_line.is_none(),
+        broken_at_line,
+    })
+}
+
+pub(crate) async fn audit_log_query_core(
+    audit_log: &AuditLog,
+    query: AuditLogQuery,
+) -> Result<AuditLogQueryResult, String> {
+    let path = audit_log
+        .path()
+        .ok_or_else(|| "The audit log is not available".to_string())?;
+    tokio::task::spawn_blocking(move || query_log(&path, &query))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{append_entry, query_log, AuditLogFile, AuditLogQuery};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn detects_edited_entries() {
+        let dir = std::env::temp_dir().join(format!("audit-log-{}", Uuid::new_v4()));
+        let mut log = AuditLogFile {
+            path: dir.join("audit.jsonl"),
+            origin: "local",
+            last: None,
+        };
+        append_entry(&mut log, "file_write", json!({ "path": "AGENTS.md" })).expect("append");
+        append_entry(&mut log, "update_app_settings", json!({})).expect("append");
+        append_entry(&mut log, "file_write", json!({ "path": "a.md" })).expect("append");
+
+        let writes = AuditLogQuery {
+            action: Some("file_write".to_string()),
+            ..AuditLogQuery::default()
+        };
+        let result = query_log(&log.path, &writes).expect("query");
+        assert!(result.verified);
+        assert_eq!(result.entries.len(), 2);
+        assert_eq!(result.entries[1].seq, 2);
+
+        let data = std::fs::read_to_string(&log.path).expect("read log");
+        std::fs::write(&log.path, data.replace("AGENTS.md", "README.md")).expect("edit log");
+        let result = query_log(&log.path, &AuditLogQuery::default()).expect("query");
+        assert!(!result.verified);
+        assert_eq!(result.broken_at_line, Some(1));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn records_a_chain_break_after_an_unreadable_last_line() {
+        let dir = std::env::temp_dir().join(format!("audit-log-{}", Uuid::new_v4()));
+        let mut log = AuditLogFile {
+            path: dir.join("audit.jsonl"),
+            origin: "local",
+            last: None,
+        };
+        append_entry(&mut log, "file_write", json!({})).expect("append");
+        append_entry(&mut log, "file_write", json!({})).expect("append");
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&log.path)
+            .expect("open log");
+        std::io::Write::write_all(&mut file, b"{\"seq\":2,\"time").expect("truncate entry");
+
+        let mut reopened = AuditLogFile {
+            path: log.path.clone(),
+            origin: "local",
+            last: None,
+        };
+        append_entry(&mut reopened, "update_app_settings", json!({})).expect("append");
+
+        let result = query_log(&log.path, &AuditLogQuery::default()).expect("query");
+        assert!(!result.verified);
+        assert_eq!(result.broken_at_line, Some(3));
+        let entries = result
+            .entries
+            .iter()
+            .map(|entry| (entry.seq, entry.action.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (0, "file_write"),
+                (1, "file_write"),
+                (2, "audit_chain_break"),
+                (3, "update_app_settings")
+            ]
+        );
+        assert_eq!(result.entries[2].details, json!({ "line": 3 }));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,198 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex as StdMutex, OnceLock};
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::shared::app_error::{AppError, AppResult};
+use crate::shared::codex_aux_core::{run_background_turn, BackgroundTurnOutput};
+
+/// How long a background turn may run before it is given up on.
+const BACKGROUND_TURN_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Finished turns whose results are kept; the oldest are forgotten first.
+const FINISHED_TURNS_KEPT: usize = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum BackgroundTurnStatus {
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BackgroundTurnResult {
+    pub(crate) turn_id: String,
+    pub(crate) workspace_id: String,
+    pub(crate) status: BackgroundTurnStatus,
+    /// The assistant's final message, once completed.
+    pub(crate) text: Option<String>,
+    /// Items the turn completed (commands, file changes, messages).
+    pub(crate) items: Vec<Value>,
+    /// Set when events were dropped, so `text` and `items` may be incomplete.
+    pub(crate) truncated: bool,
+    pub(crate) error: Option<String>,
+    pub(crate) started_at: String,
+    pub(crate) finished_at: Option<String>,
+}
+
+#[derive(Default)]
+struct Registry {
+    turns: HashMap<String, BackgroundTurnResult>,
+    finished: VecDeque<String>,
+}
+
+impl Registry {
+    fn start(&mut self, turn_id: &str, workspace_id: &str) {
+        self.turns.insert(
+            turn_id.to_string(),
+            BackgroundTurnResult {
+                turn_id: turn_id.to_string(),
+                workspace_id: workspace_id.to_string(),
+                status: BackgroundTurnStatus::Running,
+                text: None,
+                items: Vec::new(),
+                truncated: false,
+                error: None,
+                started_at: chrono::Utc::now().to_rfc3339(),
+                finished_at: None,
+            },
+        );
+    }
+
+    fn finish(&mut self, turn_id: &str, outcome: Result<BackgroundTurnOutput, String>) {
+        let Some(turn) = self.turns.get_mut(turn_id) else {
+            return;
+        };
+        match outcome {
+            Ok(output) => {
+                turn.status = BackgroundTurnStatus::Completed;
+                turn.text = Some(output.text);
+                turn.items = output.items;
+                turn.truncated = output.truncated;
+            }
+            Err(error) => {
+                turn.status = BackgroundTurnStatus::Failed;
+                turn.error = Some(error);
+            }
+        }
+        turn.finished_at = Some(chrono::Utc::now().to_rfc3339());
+        self.finished.push_back(turn_id.to_string());
+        while self.finished.len() > FINISHED_TURNS_KEPT {
+            if let Some(oldest) = self.finished.pop_front() {
+                self.turns.remove(&oldest);
+            }
+        }
+    }
+}
+
+fn registry() -> &'static StdMutex<Registry> {
+    static REGISTRY: OnceLock<StdMutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| StdMutex::new(Registry::default()))
+}
+
+/// Starts `prompt` as a hidden, read-only turn on the workspace's session
+/// and returns an id to poll with `background_turn_result_core`. When
+/// `cli_type` is given it must match the CLI the session runs.
+pub(crate) async fn background_turn_start_core<F>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    prompt: String,
+    cli_type: Option<String>,
+    on_hide_thread: F,
+) -> AppResult<String>
+where
+    F: Fn(&str, &str) + Send + Sync + 'static,
+{
+    let prompt = prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err(AppError::InvalidInput("Prompt is required.".to_string()));
+    }
+    let session = sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or(AppError::WorkspaceNotConnected)?;
+    if let Some(cli_type) = cli_type.filter(|value| !value.trim().is_empty()) {
+        if cli_type != session.cli_type() {
+            return Err(AppError::InvalidInput(format!(
+                "Workspace runs {}, not {cli_type}",
+                session.cli_type()
+            )));
+        }
+    }
+
+    let turn_id = Uuid::new_v4().to_string();
+    if let Ok(mut registry) = registry().lock() {
+        registry.start(&turn_id, &workspace_id);
+    }
+    let id = turn_id.clone();
+    tokio::spawn(async move {
+        let outcome = run_background_turn(
+            &session,
+            prompt,
+            on_hide_thread,
+            BACKGROUND_TURN_TIMEOUT,
+            "Timeout waiting for background turn",
+            "Unknown error during background turn",
+        )
+        .await;
+        if let Ok(mut registry) = registry().lock() {
+            registry.finish(&id, outcome);
+        }
+    });
+    Ok(turn_id)
+}
+
+pub(crate) fn background_turn_result_core(turn_id: &str) -> AppResult<BackgroundTurnResult> {
+    registry()
+        .lock()
+        .ok()
+        .and_then(|registry| registry.turns.get(turn_id).cloned())
+        .ok_or_else(|| AppError::InvalidInput(format!("Unknown background turn `{turn_id}`")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BackgroundTurnStatus, Registry, FINISHED_TURNS_KEPT};
+    use crate::shared::codex_aux_core::BackgroundTurnOutput;
+    use serde_json::json;
+
+    #[test]
+    fn records_results_and_forgets_the_oldest_finished_turns() {
+        let mut registry = Registry::default();
+        registry.start("first", "ws-1");
+        assert_eq!(
+            registry.turns["first"].status,
+            BackgroundTurnStatus::Running
+        );
+        registry.finish(
+            "first",
+            Ok(BackgroundTurnOutput {
+                text: "done".to_string(),
+                items: vec![json!({ "type": "agentMessage" })],
+                truncated: false,
+            }),
+        );
+        let first = &registry.turns["first"];
+        assert_eq!(first.status, BackgroundTurnStatus::Completed);
+        assert_eq!(first.text.as_deref(), Some("done"));
+        assert_eq!(first.items.len(), 1);
+
+        registry.start("running", "ws-1");
+        for n in 0..FINISHED_TURNS_KEPT {
+            let turn_id = format!("turn-{n}");
+            registry.start(&turn_id, "ws-1");
+            registry.finish(&turn_id, Err("boom".to_string()));
+        }
+        assert!(!registry.turns.contains_key("first"));
+        assert!(registry.turns.contains_key("running"));
+        assert_eq!(registry.turns["turn-0"].error.as_deref(), Some("boom"));
+    }
+}
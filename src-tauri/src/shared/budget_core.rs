@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+
+use crate::backend::adapter_base::turn_metrics_path;
+use crate::backend::turn_metrics::{read_turn_metrics, TurnMetrics};
+use crate::shared::atomic_write::{read_with_backup, write_with_backup};
+use crate::types::{MonthlyBudget, WorkspaceEntry};
+
+/// Share of the budget at which `budget/warning` is emitted.
+pub(crate) const WARN_FRACTION: f64 = 0.8;
+
+/// A workspace's usage for the current month against its budget.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BudgetStatus {
+    /// `YYYY-MM`, local time.
+    pub(crate) month: String,
+    pub(crate) spent_usd: f64,
+    pub(crate) tokens: u64,
+    pub(crate) budget: Option<MonthlyBudget>,
+    /// Share used of whichever limit is closest, or `None` without a budget.
+    pub(crate) used_fraction: Option<f64>,
+    /// The user chose to keep running turns past the budget this month.
+    pub(crate) overridden: bool,
+}
+
+impl BudgetStatus {
+    pub(crate) fn is_exceeded(&self) -> bool {
+        self.used_fraction.is_some_and(|fraction| fraction >= 1.0)
+    }
+
+    /// Turns are refused once the budget is used up, unless overridden.
+    pub(crate) fn blocks_turns(&self) -> bool {
+        self.is_exceeded() && !self.overridden
+    }
+}
+
+/// The current month's key and its first second as Unix time.
+fn current_month() -> (String, u64) {
+    let now = Local::now();
+    let start = Local
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .earliest()
+        .map(|start| start.timestamp())
+        .unwrap_or_default();
+    (
+        format!("{:04}-{:02}", now.year(), now.month()),
+        u64::try_from(start).unwrap_or(0),
+    )
+}
+
+fn month_usage(turns: &[TurnMetrics], since: u64) -> (f64, u64) {
+    turns
+        .iter()
+        .filter(|turn| turn.started_at >= since)
+        .fold((0.0, 0), |(usd, tokens), turn| {
+            (
+                usd + turn.cost_usd.unwrap_or(0.0),
+                tokens + turn.input_tokens.unwrap_or(0) + turn.output_tokens.unwrap_or(0),
+            )
+        })
+}
+
+fn used_fraction(budget: &MonthlyBudget, spent_usd: f64, tokens: u64) -> Option<f64> {
+    let usd = budget
+        .max_usd
+        .filter(|max| *max > 0.0)
+        .map(|max| spent_usd / max);
+    let tokens = budget
+        .max_tokens
+        .filter(|max| *max > 0)
+        .map(|max| tokens as f64 / max as f64);
+    match (usd, tokens) {
+        (Some(usd), Some(tokens)) => Some(usd.max(tokens)),
+        (usd, tokens) => usd.or(tokens),
+    }
+}
+
+fn overrides_path() -> PathBuf {
+    dirs_next::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("agent-monitor")
+        .join("budget-overrides.json")
+}
+
+/// Workspace id to the month its budget was overridden for.
+fn read_overrides(path: &Path) -> HashMap<String, String> {
+    read_with_backup(path, |content| {
+        serde_json::from_str(content).map_err(|err| err.to_string())
+    })
+    .ok()
+    .flatten()
+    .unwrap_or_default()
+}
+
+fn build_status(
+    turns: &[TurnMetrics],
+    budget: Option<&MonthlyBudget>,
+    month: String,
+    since: u64,
+    overridden: bool,
+) -> BudgetStatus {
+    let (spent_usd, tokens) = month_usage(turns, since);
+    BudgetStatus {
+        used_fraction: budget.and_then(|budget| used_fraction(budget, spent_usd, tokens)),
+        budget: budget.cloned(),
+        month,
+        spent_usd,
+        tokens,
+        overridden,
+    }
+}
+
+/// Reads the workspace's turn metrics and budget override. Blocking.
+pub(crate) fn budget_status(workspace_id: &str, budget: Option<&MonthlyBudget>) -> BudgetStatus {
+    let (month, since) = current_month();
+    let turns = read_turn_metrics(&turn_metrics_path(workspace_id), None, None);
+    let overridden = read_overrides(&overrides_path()).get(workspace_id) == Some(&month);
+    build_status(&turns, budget, month, since, overridden)
+}
+
+async fn workspace_budget(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+) -> Result<Option<MonthlyBudget>, String> {
+    let workspaces = workspaces.lock().await;
+    let entry = workspaces
+        .get(workspace_id)
+        .ok_or_else(|| "workspace not found".to_string())?;
+    Ok(entry.settings.monthly_budget.clone())
+}
+
+pub(crate) async fn workspace_budget_status_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<BudgetStatus, String> {
+    let budget = workspace_budget(workspaces, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || budget_status(&workspace_id, budget.as_ref()))
+        .await
+        .map_err(|err| err.to_string())
+}
+
+/// Lets the workspace run turns past its budget until the month ends.
+pub(crate) async fn workspace_budget_override_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+) -> Result<BudgetStatus, String> {
+    let budget = workspace_budget(workspaces, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        let path = overrides_path();
+        let mut overrides = read_overrides(&path);
+        overrides.insert(workspace_id.clone(), current_month().0);
+        let json = serde_json::to_string(&overrides).map_err(|err| err.to_string())?;
+        write_with_backup(&path, json.as_bytes())
+            .map_err(|err| format!("Failed to write budget overrides: {err}"))?;
+        Ok(budget_status(&workspace_id, budget.as_ref()))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::build_status;
+    use crate::backend::turn_metrics::TurnMetrics;
+    use crate::types::MonthlyBudget;
+
+    fn turn(started_at: u64, tokens: u64, cost_usd: Option<f64>) -> TurnMetrics {
+        TurnMetrics {
+            thread_id: "t".to_string(),
+            turn_id: None,
+            started_at,
+            duration_ms: 0,
+            tool_calls: 0,
+            output_bytes: 0,
+            input_tokens: Some(tokens),
+            output_tokens: None,
+            cost_usd,
+            exit_code: Some(0),
+            timed_out: false,
+        }
+    }
+
+    #[test]
+    fn counts_this_months_turns_against_the_closest_limit() {
+        let turns = [
+            turn(50, 9_000, Some(4.0)),
+            turn(150, 1_000, Some(2.5)),
+            turn(200, 2_000, None),
+        ];
+        let budget = MonthlyBudget {
+            max_usd: Some(10.0),
+            max_tokens: Some(4_000),
+        };
+
+        let status = build_status(&turns, Some(&budget), "2026-10".into(), 100, false);
+        assert_eq!(status.spent_usd, 2.5);
+        assert_eq!(status.tokens, 3_000);
+        assert_eq!(status.used_fraction, Some(0.75));
+        assert!(!status.is_exceeded());
+
+        let status = build_status(&turns, Some(&budget), "2026-10".into(), 0, true);
+        assert!(status.is_exceeded());
+        assert!(!status.blocks_turns());
+        assert_eq!(
+            build_status(&turns, None, "2026-10".into(), 0, false).used_fraction,
+            None
+        );
+    }
+}
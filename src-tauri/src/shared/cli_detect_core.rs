@@ -1,31 +1,308 @@
+#![allow(dead_code)]
+
 use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime};
 
 use crate::backend::app_server::check_cli_installation;
+use crate::shared::path_env::build_cli_path_env;
+
+/// How long a probe result is reused while the binary stays unchanged.
+const DETECTION_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Bin and display name of each CLI that is probed.
+const KNOWN_CLIS: &[(&str, &str)] = &[
+    ("codex", "Codex"),
+    ("claude", "Claude"),
+    ("gemini", "Gemini"),
+    ("cursor", "Cursor"),
+    ("aider", "Aider"),
+    ("opencode", "OpenCode"),
+    ("qwen", "Qwen"),
+    ("copilot", "Copilot"),
+    ("goose", "Goose"),
+];
 
 #[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
 pub(crate) struct DetectedClis {
     pub(crate) codex: Option<String>,
     pub(crate) claude: Option<String>,
     pub(crate) gemini: Option<String>,
     pub(crate) cursor: Option<String>,
+    pub(crate) aider: Option<String>,
+    pub(crate) opencode: Option<String>,
+    pub(crate) qwen: Option<String>,
+    pub(crate) copilot: Option<String>,
+    pub(crate) goose: Option<String>,
+    /// Resolved binary of each CLI found on PATH, by bin name.
+    pub(crate) paths: BTreeMap<String, String>,
+    /// When each CLI was last probed (RFC 3339), by bin name.
+    pub(crate) detected_at: BTreeMap<String, String>,
+    /// How to install each CLI that wasn't found, by bin name.
+    pub(crate) install_hints: BTreeMap<String, InstallHint>,
+}
+
+/// How to install a missing CLI on this OS.
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct InstallHint {
+    /// `npm`, `brew`, `pip`, `script` or `download`.
+    pub(crate) package_manager: &'static str,
+    /// Shell command that installs it; `None` when it has to be downloaded.
+    pub(crate) command: Option<&'static str>,
+    pub(crate) docs_url: &'static str,
+}
+
+const GOOSE_INSTALL_SCRIPT: &str =
+    "curl -fsSL https://github.com/block/goose/releases/download/stable/download_cli.sh | bash";
+
+fn npm_hint(command: &'static str, docs_url: &'static str) -> InstallHint {
+    InstallHint {
+        package_manager: "npm",
+        command: Some(command),
+        docs_url,
+    }
+}
+
+fn brew_hint(command: &'static str, docs_url: &'static str) -> InstallHint {
+    InstallHint {
+        package_manager: "brew",
+        command: Some(command),
+        docs_url,
+    }
+}
+
+/// Install hint for `bin` on `os` (as in `std::env::consts::OS`). Homebrew
+/// is suggested on macOS when the CLI has a formula.
+fn install_hint(bin: &str, os: &str) -> Option<InstallHint> {
+    let macos = os == "macos";
+    let hint = match bin {
+        "codex" if macos => brew_hint("brew install codex", "https://github.com/openai/codex"),
+        "codex" => npm_hint(
+            "npm install -g @openai/codex",
+            "https://github.com/openai/codex",
+        ),
+        "claude" => npm_hint(
+            "npm install -g @anthropic-ai/claude-code",
+            "https://docs.anthropic.com/en/docs/claude-code/setup",
+        ),
+        "gemini" if macos => brew_hint(
+            "brew install gemini-cli",
+            "https://github.com/google-gemini/gemini-cli",
+        ),
+        "gemini" => npm_hint(
+            "npm install -g @google/gemini-cli",
+            "https://github.com/google-gemini/gemini-cli",
+        ),
+        "cursor" if macos => {
+            brew_hint("brew install --cask cursor", "https://cursor.com/downloads")
+        }
+        "cursor" => InstallHint {
+            package_manager: "download",
+            command: None,
+            docs_url: "https://cursor.com/downloads",
+        },
+        "aider" if macos => brew_hint("brew install aider", "https://aider.chat/docs/install.html"),
+        "aider" => InstallHint {
+            package_manager: "pip",
+            command: Some("python -m pip install aider-install && aider-install"),
+            docs_url: "https://aider.chat/docs/install.html",
+        },
+        "opencode" if macos => {
+            brew_hint("brew install sst/tap/opencode", "https://opencode.ai/docs")
+        }
+        "opencode" => npm_hint("npm install -g opencode-ai", "https://opencode.ai/docs"),
+        "qwen" => npm_hint(
+            "npm install -g @qwen-code/qwen-code",
+            "https://github.com/QwenLM/qwen-code",
+        ),
+        "copilot" => npm_hint(
+            "npm install -g @github/copilot",
+            "https://github.com/github/copilot-cli",
+        ),
+        "goose" if macos => brew_hint(
+            "brew install block-goose-cli",
+            "https://block.github.io/goose/docs/getting-started/installation",
+        ),
+        "goose" if os == "windows" => InstallHint {
+            package_manager: "download",
+            command: None,
+            docs_url: "https://block.github.io/goose/docs/getting-started/installation",
+        },
+        "goose" => InstallHint {
+            package_manager: "script",
+            command: Some(GOOSE_INSTALL_SCRIPT),
+            docs_url: "https://block.github.io/goose/docs/getting-started/installation",
+        },
+        _ => return None,
+    };
+    Some(hint)
+}
+
+#[derive(Debug, Clone)]
+struct CachedProbe {
+    version: Option<String>,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    probed: Instant,
+    detected_at: String,
+}
+
+impl CachedProbe {
+    /// Still good while it is younger than the TTL and the bin resolves to
+    /// the same, unmodified file, so installs and upgrades are picked up.
+    fn is_fresh(&self, path: Option<&PathBuf>, modified: Option<SystemTime>, now: Instant) -> bool {
+        now.duration_since(self.probed) < DETECTION_TTL
+            && self.path.as_ref() == path
+            && self.modified == modified
+    }
+}
+
+fn probe_cache() -> &'static Mutex<HashMap<&'static str, CachedProbe>> {
+    static CACHE: OnceLock<Mutex<HashMap<&'static str, CachedProbe>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Finds `bin` on the PATH the probe would run it with.
+fn resolve_cli_path(bin: &str) -> Option<PathBuf> {
+    let path_env = build_cli_path_env(Some(bin));
+    #[cfg(target_os = "windows")]
+    {
+        crate::shared::process_core::resolve_windows_executable(bin, path_env.as_deref())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        std::env::split_paths(&path_env?)
+            .map(|dir| dir.join(bin))
+            .find(|candidate| candidate.is_file())
+    }
 }
 
 /// Probes default bin names on PATH; ignores user-configured custom bin overrides.
-pub(crate) async fn detect_installed_clis() -> DetectedClis {
-    let (codex, claude, gemini, cursor) = tokio::join!(
-        probe_cli(Some("codex".to_string()), "Codex"),
-        probe_cli(Some("claude".to_string()), "Claude"),
-        probe_cli(Some("gemini".to_string()), "Gemini"),
-        probe_cli(Some("cursor".to_string()), "Cursor"),
-    );
+/// Results are cached per CLI unless `force_refresh` is set.
+pub(crate) async fn detect_installed_clis(force_refresh: bool) -> DetectedClis {
+    let probes = KNOWN_CLIS
+        .iter()
+        .map(|&(bin, name)| tokio::spawn(probe_cli(bin, name, force_refresh)))
+        .collect::<Vec<_>>();
+
+    let mut versions = HashMap::new();
+    let mut paths = BTreeMap::new();
+    let mut detected_at = BTreeMap::new();
+    let mut install_hints = BTreeMap::new();
+    for (&(bin, _), probe) in KNOWN_CLIS.iter().zip(probes) {
+        let Ok(probe) = probe.await else {
+            continue;
+        };
+        if let Some(path) = &probe.path {
+            paths.insert(bin.to_string(), path.to_string_lossy().to_string());
+        }
+        detected_at.insert(bin.to_string(), probe.detected_at);
+        match probe.version {
+            Some(version) => {
+                versions.insert(bin, version);
+            }
+            None => {
+                if let Some(hint) = install_hint(bin, std::env::consts::OS) {
+                    install_hints.insert(bin.to_string(), hint);
+                }
+            }
+        }
+    }
     DetectedClis {
-        codex,
-        claude,
-        gemini,
-        cursor,
+        codex: versions.remove("codex"),
+        claude: versions.remove("claude"),
+        gemini: versions.remove("gemini"),
+        cursor: versions.remove("cursor"),
+        aider: versions.remove("aider"),
+        opencode: versions.remove("opencode"),
+        qwen: versions.remove("qwen"),
+        copilot: versions.remove("copilot"),
+        goose: versions.remove("goose"),
+        paths,
+        detected_at,
+        install_hints,
+    }
+}
+
+async fn probe_cli(bin: &'static str, name: &'static str, force_refresh: bool) -> CachedProbe {
+    let path = resolve_cli_path(bin);
+    let modified = path
+        .as_ref()
+        .and_then(|path| std::fs::metadata(path).ok())
+        .and_then(|metadata| metadata.modified().ok());
+    if !force_refresh {
+        let cached = probe_cache()
+            .lock()
+            .ok()
+            .and_then(|cache| cache.get(bin).cloned());
+        if let Some(cached) = cached {
+            if cached.is_fresh(path.as_ref(), modified, Instant::now()) {
+                return cached;
+            }
+        }
     }
+
+    let version = check_cli_installation(Some(bin.to_string()), name)
+        .await
+        .ok()
+        .flatten();
+    let probe = CachedProbe {
+        version,
+        path,
+        modified,
+        probed: Instant::now(),
+        detected_at: chrono::Utc::now().to_rfc3339(),
+    };
+    if let Ok(mut cache) = probe_cache().lock() {
+        cache.insert(bin, probe.clone());
+    }
+    probe
 }
 
-async fn probe_cli(bin: Option<String>, name: &str) -> Option<String> {
-    check_cli_installation(bin, name).await.ok().flatten()
+#[cfg(test)]
+mod tests {
+    use super::{install_hint, CachedProbe, DETECTION_TTL, KNOWN_CLIS};
+    use std::path::PathBuf;
+    use std::time::{Duration, Instant, SystemTime};
+
+    #[test]
+    fn cached_probe_expires_on_ttl_or_binary_change() {
+        let path = PathBuf::from("/usr/local/bin/codex");
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let probed = Instant::now();
+        let probe = CachedProbe {
+            version: Some("codex 1.0.0".to_string()),
+            path: Some(path.clone()),
+            modified: Some(modified),
+            probed,
+            detected_at: String::new(),
+        };
+
+        assert!(probe.is_fresh(Some(&path), Some(modified), probed));
+        assert!(!probe.is_fresh(Some(&path), Some(modified), probed + DETECTION_TTL));
+        let upgraded = modified + Duration::from_secs(1);
+        assert!(!probe.is_fresh(Some(&path), Some(upgraded), probed));
+        let moved = PathBuf::from("/opt/homebrew/bin/codex");
+        assert!(!probe.is_fresh(Some(&moved), Some(modified), probed));
+        assert!(!probe.is_fresh(None, None, probed));
+    }
+
+    #[test]
+    fn every_known_cli_has_an_install_hint_on_each_os() {
+        for os in ["macos", "linux", "windows"] {
+            for (bin, _) in KNOWN_CLIS {
+                let hint = install_hint(bin, os).expect("install hint");
+                assert!(hint.command.is_some() || hint.package_manager == "download");
+            }
+        }
+        let goose = install_hint("goose", "linux").expect("goose hint");
+        assert_eq!(goose.package_manager, "script");
+        let aider = install_hint("aider", "macos").expect("aider hint");
+        assert_eq!(aider.command, Some("brew install aider"));
+        assert_eq!(install_hint("unknown", "linux"), None);
+    }
 }
@@ -0,0 +1,296 @@
+use std::process::Stdio;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+
+use crate::backend::app_server::{build_codex_command_with_bin, check_cli_installation};
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::shared::app_error::{AppError, AppResult};
+use crate::types::AppSettings;
+
+const REGISTRY_URL: &str = "https://registry.npmjs.org";
+const REGISTRY_TIMEOUT: Duration = Duration::from_secs(10);
+/// Longest an installer may run before the update is abandoned.
+const UPDATE_TIMEOUT: Duration = Duration::from_secs(600);
+
+struct CliTarget {
+    /// The id passed to `cli_update`.
+    name: &'static str,
+    label: &'static str,
+    /// The npm package the CLI ships as; `None` for CLIs that update
+    /// themselves with `<bin> update`.
+    npm_package: Option<&'static str>,
+}
+
+const CLI_TARGETS: [CliTarget; 4] = [
+    CliTarget {
+        name: "codex",
+        label: "Codex",
+        npm_package: Some("@openai/codex"),
+    },
+    CliTarget {
+        name: "claude",
+        label: "Claude",
+        npm_package: Some("@anthropic-ai/claude-code"),
+    },
+    CliTarget {
+        name: "gemini",
+        label: "Gemini",
+        npm_package: Some("@google/gemini-cli"),
+    },
+    CliTarget {
+        name: "cursor",
+        label: "Cursor",
+        npm_package: None,
+    },
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliUpdateStatus {
+    pub(crate) name: String,
+    /// `--version` output of the configured bin; `None` when not installed.
+    pub(crate) installed: Option<String>,
+    /// Latest published version; `None` when it couldn't be looked up.
+    pub(crate) latest: Option<String>,
+    pub(crate) update_available: bool,
+    pub(crate) error: Option<String>,
+}
+
+fn configured_bin(settings: &AppSettings, name: &str) -> String {
+    let configured = match name {
+        "codex" => settings.codex_bin.as_deref(),
+        "claude" => settings.claude_bin.as_deref(),
+        "gemini" => settings.gemini_bin.as_deref(),
+        "cursor" => settings.cursor_bin.as_deref(),
+        _ => None,
+    };
+    configured
+        .map(str::trim)
+        .filter(|bin| !bin.is_empty())
+        .unwrap_or(name)
+        .to_string()
+}
+
+/// The first dotted number in a `--version` line (`codex-cli 0.20.0`,
+/// `1.0.3 (Claude Code)`, `v0.1.13`), ignoring any pre-release suffix.
+fn parse_version(text: &str) -> Option<Vec<u64>> {
+    text.split_whitespace().find_map(|token| {
+        let token = token.trim_start_matches('v');
+        let release = token.split(['-', '+']).next()?;
+        let parts = release
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        (parts.len() >= 2).then_some(parts)
+    })
+}
+
+fn is_newer(latest: &str, installed: &str) -> bool {
+    match (parse_version(latest), parse_version(installed)) {
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => false,
+    }
+}
+
+async fn latest_npm_version(client: &reqwest::Client, package: &str) -> AppResult<String> {
+    let remote_error = |err: reqwest::Error| AppError::Remote(err.to_string());
+    let response = client
+        .get(format!("{REGISTRY_URL}/{package}/latest"))
+        .send()
+        .await
+        .map_err(remote_error)?
+        .error_for_status()
+        .map_err(remote_error)?;
+    let body = response.text().await.map_err(remote_error)?;
+    let value: Value =
+        serde_json::from_str(&body).map_err(|err| AppError::Remote(err.to_string()))?;
+    value
+        .get("version")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Remote(format!("npm has no version for {package}")))
+}
+
+async fn check_target(
+    client: &reqwest::Client,
+    settings: &AppSettings,
+    target: &CliTarget,
+) -> CliUpdateStatus {
+    let bin = configured_bin(settings, target.name);
+    let installed = check_cli_installation(Some(bin), target.label)
+        .await
+        .ok()
+        .flatten();
+    let latest = match target.npm_package {
+        Some(package) => Some(latest_npm_version(client, package).await),
+        None => None,
+    };
+    let (latest, error) = match latest {
+        Some(Ok(version)) => (Some(version), None),
+        Some(Err(error)) => (None, Some(error.to_string())),
+        None => (None, None),
+    };
+    let update_available = match (&latest, &installed) {
+        (Some(latest), Some(installed)) => is_newer(latest, installed),
+        _ => false,
+    };
+    CliUpdateStatus {
+        name: target.name.to_string(),
+        installed,
+        latest,
+        update_available,
+        error,
+    }
+}
+
+/// Compares each CLI's installed version with the latest one on npm. Cursor
+/// has no public version feed, so only its installed version is reported.
+pub(crate) async fn cli_updates_check_core(settings: &AppSettings) -> Vec<CliUpdateStatus> {
+    let client = match reqwest::Client::builder().timeout(REGISTRY_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(err) => {
+            return CLI_TARGETS
+                .iter()
+                .map(|target| CliUpdateStatus {
+                    name: target.name.to_string(),
+                    installed: None,
+                    latest: None,
+                    update_available: false,
+                    error: Some(err.to_string()),
+                })
+                .collect();
+        }
+    };
+    let (codex, claude, gemini, cursor) = tokio::join!(
+        check_target(&client, settings, &CLI_TARGETS[0]),
+        check_target(&client, settings, &CLI_TARGETS[1]),
+        check_target(&client, settings, &CLI_TARGETS[2]),
+        check_target(&client, settings, &CLI_TARGETS[3]),
+    );
+    vec![codex, claude, gemini, cursor]
+}
+
+fn emit_progress<E: EventSink>(sink: &E, name: &str, message: String, done: bool) {
+    sink.emit_app_server_event(AppServerEvent::new(
+        String::new(),
+        AppEvent::CliUpdateProgress {
+            name: name.to_string(),
+            message,
+            done,
+        },
+    ));
+}
+
+async fn stream_output<R, E>(reader: R, sink: E, name: &'static str)
+where
+    R: AsyncRead + Unpin,
+    E: EventSink,
+{
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if !line.trim().is_empty() {
+            emit_progress(&sink, name, line, false);
+        }
+    }
+}
+
+/// Updates one CLI with `npm install -g <package>@latest` (or `<bin> update`
+/// for Cursor), streaming the installer's output as `cli/updateProgress`
+/// events, then returns the CLI's new status.
+pub(crate) async fn cli_update_core<E: EventSink>(
+    settings: &AppSettings,
+    sink: E,
+    name: &str,
+) -> Result<CliUpdateStatus, String> {
+    let target = CLI_TARGETS
+        .iter()
+        .find(|target| target.name == name)
+        .ok_or_else(|| format!("Unknown CLI: {name}"))?;
+    let bin = configured_bin(settings, target.name);
+    let (program, args) = match target.npm_package {
+        Some(package) => (
+            "npm".to_string(),
+            vec![
+                "install".to_string(),
+                "-g".to_string(),
+                format!("{package}@latest"),
+            ],
+        ),
+        None => (bin, vec!["update".to_string()]),
+    };
+    emit_progress(
+        &sink,
+        target.name,
+        format!("Running {program} {}", args.join(" ")),
+        false,
+    );
+    let mut command = build_codex_command_with_bin(Some(program.clone()), None, args)?;
+    command.stdout(Stdio::piped());
+    command.stderr(Stdio::piped());
+    command.kill_on_drop(true);
+    let mut child = command
+        .spawn()
+        .map_err(|err| format!("Failed to run {program}: {err}"))?;
+    let stdout = child
+        .stdout
+        .take()
+        .map(|stdout| tokio::spawn(stream_output(stdout, sink.clone(), target.name)));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|stderr| tokio::spawn(stream_output(stderr, sink.clone(), target.name)));
+    let status = match tokio::time::timeout(UPDATE_TIMEOUT, child.wait()).await {
+        Ok(status) => status.map_err(|err| err.to_string()),
+        Err(_) => {
+            let _ = child.kill().await;
+            Err(format!(
+                "{} update timed out after {}s",
+                target.label,
+                UPDATE_TIMEOUT.as_secs()
+            ))
+        }
+    };
+    for task in [stdout, stderr].into_iter().flatten() {
+        let _ = task.await;
+    }
+    let result = match status {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!(
+            "{} update failed ({status}); see the update output",
+            target.label
+        )),
+        Err(error) => Err(error),
+    };
+    let message = match &result {
+        Ok(()) => format!("{} is up to date", target.label),
+        Err(error) => error.clone(),
+    };
+    emit_progress(&sink, target.name, message, true);
+    result?;
+
+    let client = reqwest::Client::builder()
+        .timeout(REGISTRY_TIMEOUT)
+        .build()
+        .map_err(|err| err.to_string())?;
+    Ok(check_target(&client, settings, target).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_newer, parse_version};
+
+    #[test]
+    fn compares_versions_from_cli_output() {
+        assert_eq!(parse_version("codex-cli 0.20.0"), Some(vec![0, 20, 0]));
+        assert_eq!(parse_version("1.0.3 (Claude Code)"), Some(vec![1, 0, 3]));
+        assert_eq!(parse_version("v0.1.13-nightly.2"), Some(vec![0, 1, 13]));
+        assert_eq!(parse_version("unknown"), None);
+        assert!(is_newer("0.21.0", "codex-cli 0.20.9"));
+        assert!(is_newer("1.0.10", "1.0.9 (Claude Code)"));
+        assert!(!is_newer("1.0.3", "1.0.3 (Claude Code)"));
+        assert!(!is_newer("1.0.3", "dev build"));
+    }
+}
@@ -1,18 +1,26 @@
+#![allow(dead_code)]
+
 use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::Mutex;
 use tokio::time::timeout;
 
 use crate::backend::app_server::{
-    build_codex_command_with_bin, build_codex_path_env, check_cli_installation, WorkspaceSession,
+    build_codex_command_with_bin, check_cli_installation, WorkspaceSession,
+};
+use crate::backend::background_queue::{
+    background_channel, BACKGROUND_QUEUE_CAPACITY, EVENTS_DROPPED_METHOD,
 };
+use crate::shared::path_env::build_cli_path_env;
 use crate::shared::process_core::tokio_command;
 use crate::shared::workspaces_core::resolve_default_cli_bin;
 use crate::types::AppSettings;
 
+const PR_TRANSCRIPT_MAX_CHARS: usize = 12_000;
+
 pub(crate) fn build_commit_message_prompt(diff: &str) -> String {
     format!(
         "Generate a concise git commit message for the following changes. \
@@ -42,6 +50,26 @@ Task:\n{cleaned_prompt}"
     )
 }
 
+pub(crate) fn build_pr_description_prompt(transcript: &str) -> String {
+    let char_count = transcript.chars().count();
+    let transcript: String = if char_count > PR_TRANSCRIPT_MAX_CHARS {
+        transcript
+            .chars()
+            .skip(char_count - PR_TRANSCRIPT_MAX_CHARS)
+            .collect()
+    } else {
+        transcript.to_string()
+    };
+    format!(
+        "You write GitHub pull request descriptions for work done by a coding agent.\n\
+Return ONLY a JSON object with keys:\n\
+- title: imperative summary under 72 characters\n\
+- body: markdown with a short summary paragraph and a bullet list of the notable changes\n\n\
+Describe what changed and why; do not mention the agent or the conversation.\n\n\
+Transcript:\n{transcript}"
+    )
+}
+
 pub(crate) fn extract_json_value(raw: &str) -> Option<Value> {
     let start = raw.find('{')?;
     let end = raw.rfind('}')?;
@@ -134,7 +162,7 @@ pub(crate) async fn codex_doctor_core(
         .clone()
         .filter(|value| !value.trim().is_empty())
         .or(default_args);
-    let path_env = build_codex_path_env(resolved.as_deref());
+    let path_env = build_cli_path_env(resolved.as_deref());
     let version = check_cli_installation(resolved.clone(), cli_name).await?;
 
     let (app_server_ok, app_server_details) = if is_codex {
@@ -234,6 +262,17 @@ pub(crate) async fn codex_doctor_core(
     }))
 }
 
+/// What a background turn produced.
+#[derive(Debug, Default)]
+pub(crate) struct BackgroundTurnOutput {
+    /// The assistant's message text, trimmed.
+    pub(crate) text: String,
+    /// `params.item` of each `item/completed` event, in order.
+    pub(crate) items: Vec<Value>,
+    /// Set when the callback queue overflowed and events were dropped.
+    pub(crate) truncated: bool,
+}
+
 pub(crate) async fn run_background_prompt_core<F>(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -253,6 +292,36 @@ where
             .clone()
     };
 
+    let output = run_background_turn(
+        &session,
+        prompt,
+        on_hide_thread,
+        Duration::from_secs(60),
+        timeout_error,
+        turn_error_fallback,
+    )
+    .await?;
+    if output.text.is_empty() {
+        return Err("No response was generated".to_string());
+    }
+
+    Ok(output.text)
+}
+
+/// Runs `prompt` on a new read-only thread whose events go to a background
+/// callback instead of the UI, then archives the thread. `on_hide_thread`
+/// is called once the thread exists so the UI can hide it.
+pub(crate) async fn run_background_turn<F>(
+    session: &WorkspaceSession,
+    prompt: String,
+    on_hide_thread: F,
+    limit: Duration,
+    timeout_error: &str,
+    turn_error_fallback: &str,
+) -> Result<BackgroundTurnOutput, String>
+where
+    F: Fn(&str, &str),
+{
     let thread_params = json!({
         "cwd": session.entry.path,
         "approvalPolicy": "never"
@@ -287,9 +356,9 @@ where
         })?
         .to_string();
 
-    on_hide_thread(&workspace_id, &thread_id);
+    on_hide_thread(&session.entry.id, &thread_id);
 
-    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    let (tx, mut rx) = background_channel(BACKGROUND_QUEUE_CAPACITY);
     {
         let mut callbacks = session.background_thread_callbacks.lock().await;
         callbacks.insert(thread_id.clone(), tx);
@@ -330,18 +399,24 @@ where
         return Err(error_msg.to_string());
     }
 
-    let mut response_text = String::new();
-    let collect_result = timeout(Duration::from_secs(60), async {
+    let mut output = BackgroundTurnOutput::default();
+    let collect_result = timeout(limit, async {
         while let Some(event) = rx.recv().await {
             let method = event.get("method").and_then(|m| m.as_str()).unwrap_or("");
             match method {
                 "item/agentMessage/delta" => {
                     if let Some(params) = event.get("params") {
                         if let Some(delta) = params.get("delta").and_then(|d| d.as_str()) {
-                            response_text.push_str(delta);
+                            output.text.push_str(delta);
                         }
                     }
                 }
+                "item/completed" => {
+                    if let Some(item) = event.get("params").and_then(|p| p.get("item")) {
+                        output.items.push(item.clone());
+                    }
+                }
+                EVENTS_DROPPED_METHOD => output.truncated = true,
                 "turn/completed" => break,
                 "turn/error" => {
                     let error_msg = event
@@ -372,10 +447,58 @@ where
         Err(_) => return Err(timeout_error.to_string()),
     }
 
-    let trimmed = response_text.trim().to_string();
+    output.text = output.text.trim().to_string();
+    Ok(output)
+}
+
+pub(crate) async fn generate_run_metadata_core<F>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    prompt: &str,
+    on_hide_thread: F,
+) -> Result<Value, String>
+where
+    F: Fn(&str, &str),
+{
+    let cleaned_prompt = prompt.trim();
+    if cleaned_prompt.is_empty() {
+        return Err("Prompt is required.".to_string());
+    }
+
+    let title_prompt = build_run_metadata_prompt(cleaned_prompt);
+    let response_text = run_background_prompt_core(
+        sessions,
+        workspace_id,
+        title_prompt,
+        on_hide_thread,
+        "Timeout waiting for metadata generation",
+        "Unknown error during metadata generation",
+    )
+    .await?;
+
+    let trimmed = response_text.trim();
     if trimmed.is_empty() {
-        return Err("No response was generated".to_string());
+        return Err("No metadata was generated".to_string());
     }
 
-    Ok(trimmed)
+    let json_value =
+        extract_json_value(trimmed).ok_or_else(|| "Failed to parse metadata JSON".to_string())?;
+    let title = json_value
+        .get("title")
+        .and_then(|v| v.as_str())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| "Missing title in metadata".to_string())?;
+    let worktree_name = json_value
+        .get("worktreeName")
+        .or_else(|| json_value.get("worktree_name"))
+        .and_then(|v| v.as_str())
+        .map(sanitize_run_worktree_name)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| "Missing worktree name in metadata".to_string())?;
+
+    Ok(json!({
+        "title": title,
+        "worktreeName": worktree_name
+    }))
 }
@@ -10,11 +10,13 @@ use tokio::sync::{oneshot, Mutex};
 use tokio::time::timeout;
 use tokio::time::Instant;
 
-use crate::backend::app_server::WorkspaceSession;
+use crate::backend::app_server::{PendingRequestInfo, WorkspaceSession};
+use crate::backend::background_queue::BackgroundCallbackInfo;
 use crate::codex::config as codex_config;
 use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
 use crate::rules;
 use crate::shared::account::{build_account_response, read_auth_account};
+use crate::shared::app_error::{AppError, AppResult};
 use crate::types::WorkspaceEntry;
 
 const LOGIN_START_TIMEOUT: Duration = Duration::from_secs(30);
@@ -27,23 +29,23 @@ pub(crate) enum CodexLoginCancelState {
 async fn get_session_clone(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: &str,
-) -> Result<Arc<WorkspaceSession>, String> {
+) -> AppResult<Arc<WorkspaceSession>> {
     let sessions = sessions.lock().await;
     sessions
         .get(workspace_id)
         .cloned()
-        .ok_or_else(|| "workspace not connected".to_string())
+        .ok_or(AppError::WorkspaceNotConnected)
 }
 
 async fn resolve_workspace_and_parent(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
-) -> Result<(WorkspaceEntry, Option<WorkspaceEntry>), String> {
+) -> AppResult<(WorkspaceEntry, Option<WorkspaceEntry>)> {
     let workspaces = workspaces.lock().await;
     let entry = workspaces
         .get(workspace_id)
         .cloned()
-        .ok_or_else(|| "workspace not found".to_string())?;
+        .ok_or(AppError::WorkspaceNotFound)?;
     let parent_entry = entry
         .parent_id
         .as_ref()
@@ -100,9 +102,15 @@ pub(crate) async fn list_threads_core(
     cursor: Option<String>,
     limit: Option<u32>,
     sort_key: Option<String>,
+    archived: Option<bool>,
 ) -> Result<Value, String> {
     let session = get_session_clone(sessions, &workspace_id).await?;
-    let params = json!({ "cursor": cursor, "limit": limit, "sortKey": sort_key });
+    let params = json!({
+        "cursor": cursor,
+        "limit": limit,
+        "sortKey": sort_key,
+        "archived": archived
+    });
     session.send_request("thread/list", params).await
 }
 
@@ -127,6 +135,28 @@ pub(crate) async fn archive_thread_core(
     session.send_request("thread/archive", params).await
 }
 
+pub(crate) async fn unarchive_thread_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let params = json!({ "threadId": thread_id });
+    session.send_request("thread/unarchive", params).await
+}
+
+/// Removes the thread and its transcript. Only CLI adapter workspaces
+/// support this; the Codex app-server rejects it.
+pub(crate) async fn delete_thread_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<Value, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    let params = json!({ "threadId": thread_id });
+    session.send_request("thread/delete", params).await
+}
+
 pub(crate) async fn compact_thread_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -241,6 +271,32 @@ pub(crate) async fn turn_interrupt_core(
     session.send_request("turn/interrupt", params).await
 }
 
+/// Cancels app-server request `request_id`; see `WorkspaceSession::request_cancel`.
+pub(crate) async fn request_cancel_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+    request_id: u64,
+) -> Result<bool, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    session.request_cancel(request_id).await
+}
+
+pub(crate) async fn pending_requests_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+) -> Result<Vec<PendingRequestInfo>, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    Ok(session.pending_requests().await)
+}
+
+pub(crate) async fn background_callbacks_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: String,
+) -> Result<Vec<BackgroundCallbackInfo>, String> {
+    let session = get_session_clone(sessions, &workspace_id).await?;
+    Ok(session.background_callbacks().await)
+}
+
 pub(crate) async fn start_review_core(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     workspace_id: String,
@@ -0,0 +1,288 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use chrono::DateTime;
+use serde_json::{json, Value};
+use tokio::sync::Mutex;
+
+use crate::codex::home::{resolve_default_codex_home, resolve_workspace_codex_home};
+use crate::shared::redaction_core::{RedactionPatterns, SecretRedactor};
+use crate::shared::workspace_env_core::{
+    merged_workspace_env, resolve_workspace_env, workspace_secret_values,
+};
+use crate::types::{CodexSessionSummary, WorkspaceEntry};
+
+/// `sessions/YYYY/MM/DD/rollout-*.jsonl`
+const ROLLOUT_DIR_DEPTH: usize = 3;
+/// Lines read past the session header while looking for the first prompt.
+const PREVIEW_SCAN_LINES: usize = 400;
+const PREVIEW_MAX_CHARS: usize = 120;
+
+struct SessionScope {
+    sessions_root: PathBuf,
+    workspace_path: PathBuf,
+    redactor: SecretRedactor,
+}
+
+async fn session_scope(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: &str,
+    redaction_patterns: &RedactionPatterns,
+) -> Result<SessionScope, String> {
+    let (codex_home, workspace_path, env) = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        let parent = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id));
+        (
+            resolve_workspace_codex_home(entry, parent).or_else(resolve_default_codex_home),
+            PathBuf::from(&entry.path),
+            merged_workspace_env(entry, parent),
+        )
+    };
+    let codex_home = codex_home.ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())?;
+    let secrets = resolve_workspace_env(&env)
+        .map(|resolved| workspace_secret_values(&env, &resolved))
+        .unwrap_or_default();
+    Ok(SessionScope {
+        sessions_root: codex_home.join("sessions"),
+        workspace_path,
+        redactor: SecretRedactor::new(secrets, redaction_patterns.clone()),
+    })
+}
+
+fn collect_rollouts(dir: &Path, depth: usize, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < ROLLOUT_DIR_DEPTH {
+                collect_rollouts(&path, depth + 1, files);
+            }
+        } else if path.extension().and_then(|ext| ext.to_str()) == Some("jsonl") {
+            files.push(path);
+        }
+    }
+}
+
+fn parse_timestamp(value: Option<&Value>) -> Option<u64> {
+    let parsed = DateTime::parse_from_rfc3339(value?.as_str()?).ok()?;
+    u64::try_from(parsed.timestamp()).ok()
+}
+
+fn event_payload<'a>(record: &'a Value, event_type: &str) -> Option<&'a Value> {
+    if record.get("type").and_then(Value::as_str) != Some("event_msg") {
+        return None;
+    }
+    let payload = record.get("payload")?;
+    (payload.get("type").and_then(Value::as_str) == Some(event_type)).then_some(payload)
+}
+
+fn user_message(record: &Value) -> Option<String> {
+    let message = event_payload(record, "user_message")?
+        .get("message")
+        .and_then(Value::as_str)?;
+    (!message.trim().is_empty()).then(|| message.to_string())
+}
+
+fn path_matches_workspace(cwd: &str, workspace_path: &Path) -> bool {
+    let cwd_path = Path::new(cwd);
+    cwd_path == workspace_path || cwd_path.starts_with(workspace_path)
+}
+
+fn summarize_rollout(path: &Path, workspace_path: &Path) -> Option<CodexSessionSummary> {
+    let file = std::fs::File::open(path).ok()?;
+    let mut lines = BufReader::new(file).lines();
+    let header: Value = serde_json::from_str(&lines.next()?.ok()?).ok()?;
+    if header.get("type").and_then(Value::as_str) != Some("session_meta") {
+        return None;
+    }
+    let meta = header.get("payload")?;
+    let cwd = meta.get("cwd").and_then(Value::as_str)?;
+    if !path_matches_workspace(cwd, workspace_path) {
+        return None;
+    }
+    let preview = lines
+        .take(PREVIEW_SCAN_LINES)
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+        .find_map(|record| user_message(&record))
+        .map(|message| {
+            let first_line = message.lines().next().unwrap_or_default().trim();
+            first_line.chars().take(PREVIEW_MAX_CHARS).collect()
+        });
+    let created_at = parse_timestamp(meta.get("timestamp"))
+        .or_else(|| parse_timestamp(header.get("timestamp")))
+        .unwrap_or(0);
+    let updated_at = std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_secs())
+        .unwrap_or(created_at);
+    Some(CodexSessionSummary {
+        id: meta.get("id").and_then(Value::as_str)?.to_string(),
+        path: path.to_string_lossy().to_string(),
+        cwd: cwd.to_string(),
+        preview,
+        source: meta
+            .get("source")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        created_at,
+        updated_at,
+    })
+}
+
+fn list_sessions(sessions_root: &Path, workspace_path: &Path) -> Vec<CodexSessionSummary> {
+    let mut files = Vec::new();
+    collect_rollouts(sessions_root, 0, &mut files);
+    let mut sessions: Vec<CodexSessionSummary> = files
+        .iter()
+        .filter_map(|path| summarize_rollout(path, workspace_path))
+        .collect();
+    sessions.sort_by_key(|session| Reverse(session.updated_at));
+    sessions
+}
+
+/// Turns in the `thread/resume` shape. Each user prompt starts a new turn.
+fn rollout_turns(path: &Path) -> Result<Vec<Value>, String> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    let mut turns: Vec<Value> = Vec::new();
+    let mut items: Vec<Value> = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let Ok(record) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        let id = format!("item-{index}");
+        if let Some(message) = user_message(&record) {
+            if !items.is_empty() {
+                turns.push(json!({ "id": format!("turn-{}", turns.len()), "items": items }));
+                items = Vec::new();
+            }
+            items.push(json!({
+                "id": id,
+                "type": "userMessage",
+                "content": [{ "type": "text", "text": message }],
+            }));
+        } else if let Some(payload) = event_payload(&record, "agent_message") {
+            let text = payload.get("message").and_then(Value::as_str).unwrap_or("");
+            items.push(json!({ "id": id, "type": "agentMessage", "text": text }));
+        } else if let Some(payload) = event_payload(&record, "agent_reasoning") {
+            let text = payload.get("text").and_then(Value::as_str).unwrap_or("");
+            items.push(json!({
+                "id": id,
+                "type": "reasoning",
+                "summary": [text],
+                "content": [],
+            }));
+        }
+    }
+    if !items.is_empty() {
+        turns.push(json!({ "id": format!("turn-{}", turns.len()), "items": items }));
+    }
+    Ok(turns)
+}
+
+/// Sessions from the Codex CLI's history whose working directory is inside
+/// the workspace, newest first.
+pub(crate) async fn codex_sessions_list_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    redaction_patterns: &RedactionPatterns,
+    workspace_id: String,
+) -> Result<Vec<CodexSessionSummary>, String> {
+    let scope = session_scope(workspaces, &workspace_id, redaction_patterns).await?;
+    tokio::task::spawn_blocking(move || {
+        let mut sessions = list_sessions(&scope.sessions_root, &scope.workspace_path);
+        for session in &mut sessions {
+            if let Some(preview) = session.preview.as_mut() {
+                *preview = scope.redactor.redact(preview).into_owned();
+            }
+        }
+        sessions
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Reads a past session as `{ thread: { id, cwd, preview, turns } }`. The
+/// session can be continued with `thread/resume` on the same id.
+pub(crate) async fn codex_session_read_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    redaction_patterns: &RedactionPatterns,
+    workspace_id: String,
+    session_id: String,
+) -> Result<Value, String> {
+    let scope = session_scope(workspaces, &workspace_id, redaction_patterns).await?;
+    tokio::task::spawn_blocking(move || {
+        let session = list_sessions(&scope.sessions_root, &scope.workspace_path)
+            .into_iter()
+            .find(|session| session.id == session_id)
+            .ok_or_else(|| format!("Codex session `{session_id}` not found"))?;
+        let turns = rollout_turns(Path::new(&session.path))?;
+        let mut response = json!({
+            "thread": {
+                "id": session.id,
+                "cwd": session.cwd,
+                "preview": session.preview,
+                "createdAt": session.created_at,
+                "updatedAt": session.updated_at,
+                "turns": turns,
+            }
+        });
+        scope.redactor.redact_value(&mut response);
+        Ok(response)
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_sessions, rollout_turns};
+    use std::path::Path;
+    use uuid::Uuid;
+
+    #[test]
+    fn lists_and_reads_workspace_rollouts() {
+        let root = std::env::temp_dir().join(format!("codex-sessions-{}", Uuid::new_v4()));
+        let day = root.join("2025/01/02");
+        std::fs::create_dir_all(&day).expect("create day dir");
+        let session = [
+            r#"{"timestamp":"2025-01-02T09:00:00Z","type":"session_meta","payload":{"id":"s-1","timestamp":"2025-01-02T09:00:00Z","cwd":"/work/app/src","source":"cli"}}"#,
+            r#"{"timestamp":"2025-01-02T09:00:01Z","type":"response_item","payload":{"type":"message","role":"user","content":[]}}"#,
+            r#"{"timestamp":"2025-01-02T09:00:01Z","type":"event_msg","payload":{"type":"user_message","message":"Add tests\nfor the parser"}}"#,
+            r#"{"timestamp":"2025-01-02T09:00:03Z","type":"event_msg","payload":{"type":"agent_reasoning","text":"Reading parser.rs"}}"#,
+            r#"{"timestamp":"2025-01-02T09:00:05Z","type":"event_msg","payload":{"type":"agent_message","message":"Done."}}"#,
+            r#"{"timestamp":"2025-01-02T09:01:00Z","type":"event_msg","payload":{"type":"user_message","message":"Thanks"}}"#,
+        ];
+        let path = day.join("rollout-2025-01-02T09-00-00-s-1.jsonl");
+        std::fs::write(&path, session.join("\n")).expect("write rollout");
+        let other = r#"{"timestamp":"2025-01-02T09:00:00Z","type":"session_meta","payload":{"id":"s-2","cwd":"/elsewhere"}}"#;
+        std::fs::write(day.join("rollout-other.jsonl"), other).expect("write rollout");
+
+        let sessions = list_sessions(&root, Path::new("/work/app"));
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, "s-1");
+        assert_eq!(sessions[0].preview.as_deref(), Some("Add tests"));
+        assert_eq!(sessions[0].source.as_deref(), Some("cli"));
+        assert_eq!(sessions[0].created_at, 1_735_808_400);
+
+        let turns = rollout_turns(&path).expect("turns");
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0]["items"][1]["type"], "reasoning");
+        assert_eq!(turns[0]["items"][2]["text"], "Done.");
+        assert_eq!(turns[1]["items"][0]["content"][0]["text"], "Thanks");
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,203 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::shared::redaction_core::looks_like_secret_name;
+
+const MASKED: &str = "[REDACTED]";
+const KEYCHAIN_PREFIX: &str = "keychain:";
+
+/// What a remote backend connection may do, decided by the token it
+/// authenticated with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ConnectionRole {
+    /// Full access; daemons that don't report a role only have this one.
+    #[default]
+    Operator,
+    /// Can watch workspaces and threads but not start turns, answer
+    /// approvals or change files and settings.
+    Observer,
+}
+
+/// Methods an observer may call. They only read state, or (for
+/// `connect_workspace` and the file tail subscriptions) start streaming
+/// events to the connection.
+const OBSERVER_METHODS: &[&str] = &[
+    "ping",
+    "list_workspaces",
+    "is_workspace_path_dir",
+    "workspace_trash_list",
+    "workspace_templates_list",
+    "workflows_list",
+    "worktree_list",
+    "worktree_setup_status",
+    "connect_workspace",
+    "list_workspace_files",
+    "read_workspace_file",
+    "file_read",
+    "workspace_file_read",
+    "file_read_range",
+    "file_tail_subscribe",
+    "file_tail_unsubscribe",
+    "workspace_tree",
+    "agents_md_sections_list",
+    "agent_profile_status",
+    "agent_profiles_list",
+    "get_app_settings",
+    "get_codex_config_path",
+    "codex_config_validate",
+    "codex_profiles_list",
+    "get_config_model",
+    "resume_thread",
+    "list_threads",
+    "codex_sessions_list",
+    "codex_session_read",
+    "list_mcp_server_status",
+    "turn_metrics_list",
+    "pending_requests",
+    "background_callbacks",
+    "background_turn_result",
+    "workspace_budget_status",
+    "activity_timeline",
+    "cli_plugins_list",
+    "cli_updates_check",
+    "account_profiles_list",
+    "audit_log_query",
+    "settings_profiles_list",
+    "maintenance_status",
+    "mcp_servers_list",
+    "get_claude_settings",
+    "log_tail",
+    "events_replay",
+    "model_list",
+    "collaboration_mode_list",
+    "account_rate_limits",
+    "account_read",
+    "skills_list",
+    "apps_list",
+];
+
+/// Observer methods whose results carry credentials: the settings tokens,
+/// workspace, Claude and MCP server `env` maps, and MCP `headers`.
+const MASKED_RESULT_METHODS: &[&str] = &[
+    "list_workspaces",
+    "get_app_settings",
+    "get_claude_settings",
+    "mcp_servers_list",
+];
+
+/// Rejects `method` when `role` may not call it. Methods the observer list
+/// doesn't name are treated as writes, so new commands start out denied.
+pub(crate) fn check_method_allowed(role: ConnectionRole, method: &str) -> Result<(), String> {
+    if role == ConnectionRole::Observer && !OBSERVER_METHODS.contains(&method) {
+        return Err(format!(
+            "read-only observer connection: `{method}` is not allowed"
+        ));
+    }
+    Ok(())
+}
+
+/// Masks credentials in `result` before it goes to an observer. Strings under
+/// a credential-like key are replaced; keychain references are kept since
+/// they don't hold the secret. Only the daemon serves observers.
+#[allow(dead_code)]
+pub(crate) fn mask_result_for_role(role: ConnectionRole, method: &str, result: &mut Value) {
+    if role == ConnectionRole::Observer && MASKED_RESULT_METHODS.contains(&method) {
+        mask_secrets(result);
+    }
+}
+
+fn mask_secrets(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (name, item) in map.iter_mut() {
+                match item {
+                    Value::String(text)
+                        if is_secret_field(name) && !text.starts_with(KEYCHAIN_PREFIX) =>
+                    {
+                        *text = MASKED.to_string();
+                    }
+                    _ => mask_secrets(item),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+fn is_secret_field(name: &str) -> bool {
+    looks_like_secret_name(name) || name.eq_ignore_ascii_case("authorization")
+}
+
+/// Compares tokens without short-circuiting on the first mismatched byte.
+pub(crate) fn tokens_match(expected: &str, provided: &str) -> bool {
+    let expected = expected.as_bytes();
+    let provided = provided.as_bytes();
+    if expected.len() != provided.len() {
+        return false;
+    }
+    expected
+        .iter()
+        .zip(provided)
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_method_allowed, mask_result_for_role, ConnectionRole};
+    use serde_json::json;
+
+    #[test]
+    fn observers_may_only_read() {
+        for method in ["list_workspaces", "file_read", "events_replay"] {
+            assert!(check_method_allowed(ConnectionRole::Observer, method).is_ok());
+        }
+        let denied = check_method_allowed(ConnectionRole::Observer, "send_user_message")
+            .expect_err("observer turn");
+        assert_eq!(
+            denied,
+            "read-only observer connection: `send_user_message` is not allowed"
+        );
+        assert!(check_method_allowed(ConnectionRole::Observer, "file_write").is_err());
+        assert!(check_method_allowed(ConnectionRole::Observer, "some_new_command").is_err());
+        assert!(check_method_allowed(ConnectionRole::Operator, "file_write").is_ok());
+    }
+
+    #[test]
+    fn observers_get_settings_without_credentials() {
+        let settings = json!({
+            "remoteBackendToken": "daemon-token-1234",
+            "httpApiToken": "http-token-1234",
+            "theme": "dark",
+            "maxTokens": 4096
+        });
+        let mut result = settings.clone();
+        mask_result_for_role(ConnectionRole::Operator, "get_app_settings", &mut result);
+        assert_eq!(result, settings);
+        mask_result_for_role(ConnectionRole::Observer, "get_app_settings", &mut result);
+        assert_eq!(result["remoteBackendToken"], "[REDACTED]");
+        assert_eq!(result["httpApiToken"], "[REDACTED]");
+        assert_eq!(result["theme"], "dark");
+        assert_eq!(result["maxTokens"], 4096);
+
+        let mut servers = json!([{
+            "name": "github",
+            "env": { "GITHUB_TOKEN": "ghp_secret", "LOG_LEVEL": "debug" },
+            "headers": { "Authorization": "Bearer abc" }
+        }]);
+        mask_result_for_role(ConnectionRole::Observer, "mcp_servers_list", &mut servers);
+        assert_eq!(servers[0]["env"]["GITHUB_TOKEN"], "[REDACTED]");
+        assert_eq!(servers[0]["env"]["LOG_LEVEL"], "debug");
+        assert_eq!(servers[0]["headers"]["Authorization"], "[REDACTED]");
+
+        let mut claude = json!({ "env": {
+            "ANTHROPIC_API_KEY": "sk-ant-secret",
+            "OPENAI_API_KEY": "keychain:openai"
+        } });
+        mask_result_for_role(ConnectionRole::Observer, "get_claude_settings", &mut claude);
+        assert_eq!(claude["env"]["ANTHROPIC_API_KEY"], "[REDACTED]");
+        assert_eq!(claude["env"]["OPENAI_API_KEY"], "keychain:openai");
+    }
+}
@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::backend::events::AppServerEvent;
+
+const DEFAULT_WINDOW_MS: u64 = 16;
+
+/// Streaming notifications whose `params.delta` text can be joined.
+const DELTA_METHODS: &[&str] = &[
+    "item/agentMessage/delta",
+    "item/commandExecution/outputDelta",
+    "item/fileChange/outputDelta",
+    "item/plan/delta",
+    "item/reasoning/summaryTextDelta",
+    "item/reasoning/textDelta",
+];
+
+fn is_delta(message: &Value) -> bool {
+    let method = message.get("method").and_then(Value::as_str);
+    method.is_some_and(|method| DELTA_METHODS.contains(&method))
+        && message
+            .get("params")
+            .and_then(|params| params.get("delta"))
+            .is_some_and(Value::is_string)
+}
+
+/// Whether `next` continues `last`: the same method with the same params
+/// apart from the delta text, so the same item and summary part.
+fn continues(last: &Value, next: &Value) -> bool {
+    let without_delta = |message: &Value| {
+        let mut params = message.get("params").cloned().unwrap_or(Value::Null);
+        if let Some(params) = params.as_object_mut() {
+            params.remove("delta");
+        }
+        params
+    };
+    last.get("method") == next.get("method") && without_delta(last) == without_delta(next)
+}
+
+/// Appends `event`'s delta to the last queued event when it continues it,
+/// otherwise queues it.
+fn push_delta(queue: &mut Vec<AppServerEvent>, event: AppServerEvent) {
+    if let Some(last) = queue.last_mut() {
+        if continues(&last.message, &event.message) {
+            let delta = event.message["params"]["delta"]
+                .as_str()
+                .unwrap_or_default();
+            if let Some(Value::String(text)) = last.message["params"].get_mut("delta") {
+                text.push_str(delta);
+                return;
+            }
+        }
+    }
+    queue.push(event);
+}
+
+type Emit = Arc<dyn Fn(AppServerEvent) + Send + Sync>;
+
+/// Joins runs of delta notifications for the same item that arrive within
+/// the batch window into one event, so fast streams don't flood the IPC
+/// channel. Other events flush the workspace's queued deltas first, so
+/// ordering is kept. A window of 0 turns batching off.
+///
+/// Only events headed for the sinks go through here; background thread
+/// callbacks get every delta as it arrives.
+pub(crate) struct DeltaBatcher {
+    window_ms: AtomicU64,
+    queues: Mutex<HashMap<String, Vec<AppServerEvent>>>,
+    /// Held while draining and emitting so a timer flush and a pass-through
+    /// event can't overtake each other. Queuing a delta doesn't take it, and
+    /// `queues` is never held while emitting.
+    emitting: Mutex<()>,
+}
+
+impl DeltaBatcher {
+    fn new(window_ms: u64) -> Self {
+        Self {
+            window_ms: AtomicU64::new(window_ms),
+            queues: Mutex::new(HashMap::new()),
+            emitting: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn set_window_ms(&self, window_ms: u64) {
+        self.window_ms.store(window_ms, Ordering::Relaxed);
+    }
+
+    fn queues(&self) -> MutexGuard<'_, HashMap<String, Vec<AppServerEvent>>> {
+        self.queues
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Emits `event` through `emit`, now or when its batch window closes.
+    /// Batching needs a tokio runtime for the timer; without one events go
+    /// straight through.
+    pub(crate) fn push(self: &Arc<Self>, event: AppServerEvent, emit: Emit) {
+        let window_ms = self.window_ms.load(Ordering::Relaxed);
+        let runtime = tokio::runtime::Handle::try_current()
+            .ok()
+            .filter(|_| window_ms > 0 && is_delta(&event.message));
+        let workspace_id = event.workspace_id.clone();
+        let Some(runtime) = runtime else {
+            let _emitting = self
+                .emitting
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let queued = self.queues().remove(&workspace_id).unwrap_or_default();
+            for queued in queued {
+                emit(queued);
+            }
+            emit(event);
+            return;
+        };
+        let starts_window = {
+            let mut queues = self.queues();
+            let queue = queues.entry(workspace_id.clone()).or_default();
+            let starts_window = queue.is_empty();
+            push_delta(queue, event);
+            starts_window
+        };
+        if starts_window {
+            let batcher = Arc::clone(self);
+            runtime.spawn(async move {
+                tokio::time::sleep(Duration::from_millis(window_ms)).await;
+                batcher.flush(&workspace_id, &emit);
+            });
+        }
+    }
+
+    fn flush(&self, workspace_id: &str, emit: &Emit) {
+        let _emitting = self
+            .emitting
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let queued = self.queues().remove(workspace_id).unwrap_or_default();
+        for queued in queued {
+            emit(queued);
+        }
+    }
+}
+
+impl Default for DeltaBatcher {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW_MS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_delta, push_delta, DeltaBatcher, Emit};
+    use crate::backend::events::AppServerEvent;
+    use serde_json::json;
+    use std::sync::{Arc, Mutex};
+
+    fn delta(item_id: &str, text: &str) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message: json!({
+                "method": "item/agentMessage/delta",
+                "params": {
+                    "threadId": "t1",
+                    "turnId": "turn-1",
+                    "itemId": item_id,
+                    "delta": text,
+                },
+            }),
+        }
+    }
+
+    #[test]
+    fn joins_consecutive_deltas_for_the_same_item() {
+        let mut queue = Vec::new();
+        for (item_id, text) in [("a", "Hel"), ("a", "lo"), ("b", "Hi"), ("a", "!")] {
+            push_delta(&mut queue, delta(item_id, text));
+        }
+        let texts = queue
+            .iter()
+            .map(|event| {
+                event.message["params"]["delta"]
+                    .as_str()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(texts, vec!["Hello", "Hi", "!"]);
+
+        assert!(is_delta(&delta("a", "x").message));
+        assert!(!is_delta(
+            &json!({ "method": "item/completed", "params": {} })
+        ));
+    }
+
+    #[test]
+    fn flushes_queued_deltas_first_even_after_a_poisoned_lock() {
+        let batcher = Arc::new(DeltaBatcher::new(60_000));
+        let emitted = Arc::new(Mutex::new(Vec::new()));
+        let emit: Emit = {
+            let emitted = Arc::clone(&emitted);
+            Arc::new(move |event: AppServerEvent| {
+                emitted.lock().unwrap().push(event.message);
+            })
+        };
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_time()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            batcher.push(delta("a", "Hel"), Arc::clone(&emit));
+            batcher.push(delta("a", "lo"), Arc::clone(&emit));
+        });
+        assert!(emitted.lock().unwrap().is_empty());
+
+        let poisoner = Arc::clone(&batcher);
+        let _ = std::thread::spawn(move || {
+            let _queues = poisoner.queues.lock();
+            panic!("poison the queue lock");
+        })
+        .join();
+        let completed = AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message: json!({ "method": "item/completed", "params": {} }),
+        };
+        batcher.push(completed, emit);
+
+        let emitted = emitted.lock().unwrap();
+        assert_eq!(emitted.len(), 2);
+        assert_eq!(emitted[0]["params"]["delta"], "Hello");
+        assert_eq!(emitted[1]["method"], "item/completed");
+    }
+}
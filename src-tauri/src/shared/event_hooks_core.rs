@@ -0,0 +1,194 @@
+use std::process::Stdio;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Semaphore;
+use tokio::time::timeout;
+
+use crate::shared::event_replay_core::SequencedEvent;
+use crate::shared::path_env::build_cli_path_env;
+use crate::shared::process_core::{kill_child_process_tree, shell_command};
+use crate::types::EventHook;
+
+const DEFAULT_HOOK_TIMEOUT: Duration = Duration::from_secs(30);
+/// Hook commands running at once; events that would start more are skipped.
+const MAX_RUNNING_HOOKS: usize = 8;
+
+/// The `eventHooks` setting and the cap on hook commands running at once.
+/// Clones share both, so a settings change reaches every event sink.
+#[derive(Clone)]
+pub(crate) struct EventHooks {
+    hooks: Arc<RwLock<Vec<EventHook>>>,
+    running: Arc<Semaphore>,
+}
+
+impl Default for EventHooks {
+    fn default() -> Self {
+        Self {
+            hooks: Arc::new(RwLock::new(Vec::new())),
+            running: Arc::new(Semaphore::new(MAX_RUNNING_HOOKS)),
+        }
+    }
+}
+
+/// Rejects the first hook without a method pattern or command.
+pub(crate) fn validate_event_hooks(hooks: &[EventHook]) -> Result<(), String> {
+    for hook in hooks {
+        if hook.method.trim().is_empty() {
+            return Err(format!("Event hook `{}` needs a method", hook.command));
+        }
+        if hook.command.trim().is_empty() {
+            return Err(format!("Event hook for `{}` needs a command", hook.method));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `method` matches `pattern`, where `*` matches any run of
+/// characters.
+fn method_matches(pattern: &str, method: &str) -> bool {
+    let mut parts = pattern.trim().split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = method.strip_prefix(first) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        return rest.is_empty();
+    };
+    for part in middle {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(last)
+}
+
+fn hook_matches(hook: &EventHook, workspace_id: &str, method: &str) -> bool {
+    let workspace_matches = hook
+        .workspace_id
+        .as_deref()
+        .filter(|id| !id.trim().is_empty())
+        .is_none_or(|id| id == workspace_id);
+    workspace_matches && method_matches(&hook.method, method)
+}
+
+impl EventHooks {
+    /// Replaces the `eventHooks` setting.
+    pub(crate) fn set(&self, hooks: &[EventHook]) {
+        if let Ok(mut current) = self.hooks.write() {
+            *current = hooks
+                .iter()
+                .filter(|hook| !hook.method.trim().is_empty() && !hook.command.trim().is_empty())
+                .cloned()
+                .collect();
+        }
+    }
+
+    /// Starts the command of every event hook matching `event`. Commands run in
+    /// the background on the tokio runtime, so events emitted outside one
+    /// don't run hooks.
+    pub(crate) fn dispatch(&self, event: &SequencedEvent) {
+        let Some(method) = event.message.get("method").and_then(|value| value.as_str()) else {
+            return;
+        };
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        let commands: Vec<(String, Duration)> = match self.hooks.read() {
+            Ok(hooks) => hooks
+                .iter()
+                .filter(|hook| hook_matches(hook, &event.workspace_id, method))
+                .map(|hook| {
+                    let limit = hook
+                        .timeout_secs
+                        .filter(|secs| *secs > 0)
+                        .map_or(DEFAULT_HOOK_TIMEOUT, Duration::from_secs);
+                    (hook.command.clone(), limit)
+                })
+                .collect(),
+            Err(_) => return,
+        };
+        if commands.is_empty() {
+            return;
+        }
+        let Ok(payload) = serde_json::to_vec(event) else {
+            return;
+        };
+        let payload = Arc::new(payload);
+        for (command, limit) in commands {
+            let Ok(permit) = Arc::clone(&self.running).try_acquire_owned() else {
+                tracing::warn!(
+                    "event hook `{command}` skipped for {method}: too many hooks running"
+                );
+                continue;
+            };
+            let payload = Arc::clone(&payload);
+            runtime.spawn(async move {
+                if let Err(error) = run_hook(&command, &payload, limit).await {
+                    tracing::warn!("event hook `{command}` failed: {error}");
+                }
+                drop(permit);
+            });
+        }
+    }
+}
+
+async fn run_hook(command: &str, payload: &[u8], limit: Duration) -> Result<(), String> {
+    let mut shell = shell_command(command);
+    shell
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if let Some(path_env) = build_cli_path_env(None) {
+        shell.env("PATH", path_env);
+    }
+    let mut child = shell.spawn().map_err(|err| err.to_string())?;
+    let stdin = child.stdin.take();
+    let waited = timeout(limit, async {
+        if let Some(mut stdin) = stdin {
+            // A hook that ignores its input may exit before reading it.
+            let _ = stdin.write_all(payload).await;
+        }
+        child.wait().await
+    })
+    .await;
+    match waited {
+        Ok(Ok(status)) if status.success() => Ok(()),
+        Ok(Ok(status)) => Err(format!("exited with {status}")),
+        Ok(Err(err)) => Err(err.to_string()),
+        Err(_) => {
+            kill_child_process_tree(&mut child).await;
+            Err(format!("timed out after {}s", limit.as_secs()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hook_matches, method_matches};
+    use crate::types::EventHook;
+
+    #[test]
+    fn matches_methods_with_wildcards_and_workspace_filter() {
+        assert!(method_matches("turn/completed", "turn/completed"));
+        assert!(!method_matches("turn/completed", "turn/completedX"));
+        assert!(method_matches("turn/*", "turn/started"));
+        assert!(!method_matches("turn/*", "item/started"));
+        assert!(method_matches("*", "anything"));
+        assert!(method_matches("item/*/delta", "item/agentMessage/delta"));
+        assert!(!method_matches("item/*/delta", "item/plan/deltas"));
+
+        let hook = EventHook {
+            method: "turn/*".to_string(),
+            workspace_id: Some("ws-1".to_string()),
+            command: "cat >> journal.jsonl".to_string(),
+            timeout_secs: None,
+        };
+        assert!(hook_matches(&hook, "ws-1", "turn/completed"));
+        assert!(!hook_matches(&hook, "ws-2", "turn/completed"));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::backend::events::AppServerEvent;
+
+const DEFAULT_CAPACITY: usize = 500;
+
+/// An `AppServerEvent` tagged with a sequence number. Serializes like
+/// `AppServerEvent` plus `seq`, so it can be emitted in its place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct SequencedEvent {
+    pub(crate) seq: u64,
+    pub(crate) workspace_id: String,
+    pub(crate) message: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EventReplay {
+    pub(crate) events: Vec<SequencedEvent>,
+    pub(crate) latest_seq: u64,
+    /// True when events after `since_seq` were already evicted, so the UI
+    /// should fall back to a full refresh.
+    pub(crate) truncated: bool,
+}
+
+#[derive(Default)]
+struct WorkspaceEvents {
+    events: VecDeque<SequencedEvent>,
+    evicted_through: u64,
+}
+
+impl WorkspaceEvents {
+    fn trim(&mut self, capacity: usize) {
+        while self.events.len() > capacity {
+            if let Some(event) = self.events.pop_front() {
+                self.evicted_through = event.seq;
+            }
+        }
+    }
+}
+
+/// Keeps the most recent events per workspace so a reloaded window can catch
+/// up on an in-flight turn. Sequence numbers are global and start at 1.
+pub(crate) struct EventReplayBuffer {
+    next_seq: AtomicU64,
+    capacity: AtomicUsize,
+    workspaces: Mutex<HashMap<String, WorkspaceEvents>>,
+}
+
+impl EventReplayBuffer {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            next_seq: AtomicU64::new(1),
+            capacity: AtomicUsize::new(capacity),
+            workspaces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::SeqCst);
+        if let Ok(mut workspaces) = self.workspaces.lock() {
+            for events in workspaces.values_mut() {
+                events.trim(capacity);
+            }
+        }
+    }
+
+    pub(crate) fn record(&self, event: AppServerEvent) -> SequencedEvent {
+        let sequenced = SequencedEvent {
+            seq: self.next_seq.fetch_add(1, Ordering::SeqCst),
+            workspace_id: event.workspace_id,
+            message: event.message,
+        };
+        let capacity = self.capacity.load(Ordering::SeqCst);
+        if !is_replayable(&sequenced.message) {
+            return sequenced;
+        }
+        if let Ok(mut workspaces) = self.workspaces.lock() {
+            let events = workspaces
+                .entry(sequenced.workspace_id.clone())
+                .or_default();
+            events.events.push_back(sequenced.clone());
+            events.trim(capacity);
+        }
+        sequenced
+    }
+
+    pub(crate) fn since(&self, workspace_id: &str, since_seq: u64) -> EventReplay {
+        let latest_seq = self.next_seq.load(Ordering::SeqCst).saturating_sub(1);
+        let workspaces = match self.workspaces.lock() {
+            Ok(workspaces) => workspaces,
+            Err(_) => {
+                return EventReplay {
+                    events: Vec::new(),
+                    latest_seq,
+                    truncated: true,
+                }
+            }
+        };
+        let Some(events) = workspaces.get(workspace_id) else {
+            return EventReplay {
+                events: Vec::new(),
+                latest_seq,
+                truncated: false,
+            };
+        };
+        EventReplay {
+            events: events
+                .events
+                .iter()
+                .filter(|event| event.seq > since_seq)
+                .cloned()
+                .collect(),
+            latest_seq,
+            truncated: events.evicted_through > since_seq,
+        }
+    }
+}
+
+impl Default for EventReplayBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+/// Debug mirrors (`debug/rpc`) and periodic `process/stats` samples are high
+/// volume and only useful live, so they are numbered but not buffered.
+fn is_replayable(message: &Value) -> bool {
+    !message
+        .get("method")
+        .and_then(|value| value.as_str())
+        .is_some_and(|method| method.starts_with("debug/") || method == "process/stats")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EventReplayBuffer;
+    use crate::backend::events::AppServerEvent;
+    use serde_json::json;
+
+    fn event(workspace_id: &str, index: u64) -> AppServerEvent {
+        AppServerEvent {
+            workspace_id: workspace_id.to_string(),
+            message: json!({ "method": "item/updated", "params": { "index": index } }),
+        }
+    }
+
+    #[test]
+    fn replays_events_after_sequence_per_workspace() {
+        let buffer = EventReplayBuffer::new(3);
+        for index in 0..5 {
+            buffer.record(event("ws-1", index));
+        }
+        buffer.record(event("ws-2", 0));
+
+        let replay = buffer.since("ws-1", 3);
+        assert_eq!(
+            replay
+                .events
+                .iter()
+                .map(|event| event.seq)
+                .collect::<Vec<_>>(),
+            vec![4, 5]
+        );
+        assert_eq!(replay.latest_seq, 6);
+        assert!(!replay.truncated);
+
+        let replay = buffer.since("ws-1", 0);
+        assert_eq!(replay.events.len(), 3);
+        assert!(replay.truncated);
+
+        assert!(buffer.since("ws-3", 0).events.is_empty());
+    }
+
+    #[test]
+    fn shrinking_capacity_evicts_oldest_events() {
+        let buffer = EventReplayBuffer::new(10);
+        for index in 0..4 {
+            buffer.record(event("ws-1", index));
+        }
+        buffer.set_capacity(1);
+        let replay = buffer.since("ws-1", 0);
+        assert_eq!(
+            replay
+                .events
+                .iter()
+                .map(|event| event.seq)
+                .collect::<Vec<_>>(),
+            vec![4]
+        );
+        assert!(replay.truncated);
+
+        buffer.record(AppServerEvent {
+            workspace_id: "ws-1".to_string(),
+            message: json!({ "method": "debug/rpc", "params": {} }),
+        });
+        assert_eq!(buffer.since("ws-1", 0).events.len(), 1);
+
+        buffer.set_capacity(0);
+        buffer.record(event("ws-1", 4));
+        assert!(buffer.since("ws-1", 0).events.is_empty());
+    }
+}
@@ -0,0 +1,187 @@
+use serde_json::{Map, Value};
+
+/// Legacy snake_case param names and their canonical camelCase form.
+const ID_KEYS: [(&str, &str); 3] = [
+    ("thread_id", "threadId"),
+    ("turn_id", "turnId"),
+    ("item_id", "itemId"),
+];
+
+fn id_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(id) if !id.is_empty() => Some(id.clone()),
+        Value::Number(id) => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Status for a `turn` object built from an adapter's flat turn params.
+fn adapter_turn_status(method: &str, params: &Map<String, Value>) -> &'static str {
+    if method == "turn/started" {
+        return "inProgress";
+    }
+    let failed = params.get("error").is_some_and(|error| !error.is_null());
+    match params.get("exitCode").and_then(Value::as_i64) {
+        _ if failed => "failed",
+        Some(0) => "completed",
+        Some(_) => "failed",
+        // The adapters report no exit code when the turn was interrupted.
+        None if params.contains_key("exitCode") => "interrupted",
+        None => "completed",
+    }
+}
+
+/// Rewrites turn and item notifications into the one schema the frontend
+/// reads, whichever backend produced them:
+///
+/// - `threadId`, `turnId` and `itemId` are camelCase strings at the top of
+///   `params`, copied from the nested `turn`/`item` objects when only those
+///   carry them.
+/// - `turn/started` and `turn/completed` always carry a `turn` object with
+///   `id` and `status`, as the Codex app-server sends them.
+/// - `item/started` and `item/completed` items have a string `id`, and a
+///   `status` when the item doesn't report one itself.
+///
+/// Item types are left as the backend reported them.
+pub(crate) fn normalize_event(message: &mut Value) {
+    let Some(method) = message
+        .get("method")
+        .and_then(Value::as_str)
+        .map(str::to_string)
+    else {
+        return;
+    };
+    if !method.starts_with("turn/") && !method.starts_with("item/") && method != "error" {
+        return;
+    }
+    let Some(params) = message.get_mut("params").and_then(Value::as_object_mut) else {
+        return;
+    };
+
+    for (legacy, canonical) in ID_KEYS {
+        if let Some(value) = params.remove(legacy) {
+            params.entry(canonical).or_insert(value);
+        }
+    }
+    for (_, key) in ID_KEYS {
+        if let Some(id) = params.get(key).and_then(id_string) {
+            params.insert(key.to_string(), Value::String(id));
+        }
+    }
+
+    let nested_turn = params.get("turn").and_then(Value::as_object).map(|turn| {
+        let thread_id = turn.get("threadId").or_else(|| turn.get("thread_id"));
+        (
+            turn.get("id").and_then(id_string),
+            thread_id.and_then(id_string),
+        )
+    });
+    if let Some((turn_id, thread_id)) = nested_turn {
+        if let Some(turn_id) = turn_id {
+            params
+                .entry("turnId")
+                .or_insert_with(|| Value::String(turn_id));
+        }
+        if let Some(thread_id) = thread_id {
+            params
+                .entry("threadId")
+                .or_insert_with(|| Value::String(thread_id));
+        }
+    } else if method == "turn/started" || method == "turn/completed" {
+        if let Some(turn_id) = params.get("turnId").cloned() {
+            let status = adapter_turn_status(&method, params);
+            let mut turn = Map::new();
+            turn.insert("id".to_string(), turn_id);
+            turn.insert("status".to_string(), Value::String(status.to_string()));
+            if let Some(error) = params.get("error").filter(|error| !error.is_null()) {
+                turn.insert("error".to_string(), error.clone());
+            }
+            params.insert("turn".to_string(), Value::Object(turn));
+        }
+    }
+
+    if method == "item/started" || method == "item/completed" {
+        let default_status = if method == "item/started" {
+            "inProgress"
+        } else {
+            "completed"
+        };
+        let item_id = params
+            .get_mut("item")
+            .and_then(Value::as_object_mut)
+            .and_then(|item| {
+                let id = item.get("id").and_then(id_string)?;
+                item.insert("id".to_string(), Value::String(id.clone()));
+                item.entry("status")
+                    .or_insert_with(|| Value::String(default_status.to_string()));
+                Some(id)
+            });
+        if let Some(item_id) = item_id {
+            params
+                .entry("itemId")
+                .or_insert_with(|| Value::String(item_id));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::normalize_event;
+    use serde_json::json;
+
+    #[test]
+    fn codex_and_adapter_events_share_one_schema() {
+        let mut codex = json!({
+            "method": "turn/completed",
+            "params": {
+                "threadId": "thread-1",
+                "turn": { "id": "turn-1", "status": "completed", "items": [] }
+            }
+        });
+        let mut adapter = json!({
+            "method": "turn/completed",
+            "params": {
+                "thread_id": "thread-1",
+                "turnId": "turn-1",
+                "costUsd": null,
+                "durationMs": 40,
+                "exitCode": 0,
+                "error": null
+            }
+        });
+        normalize_event(&mut codex);
+        normalize_event(&mut adapter);
+        for event in [&codex, &adapter] {
+            assert_eq!(event["params"]["threadId"], "thread-1");
+            assert_eq!(event["params"]["turnId"], "turn-1");
+            assert_eq!(event["params"]["turn"]["id"], "turn-1");
+            assert_eq!(event["params"]["turn"]["status"], "completed");
+        }
+
+        let mut interrupted = json!({
+            "method": "turn/completed",
+            "params": { "threadId": "thread-1", "turnId": "turn-2", "exitCode": null, "error": null }
+        });
+        normalize_event(&mut interrupted);
+        assert_eq!(interrupted["params"]["turn"]["status"], "interrupted");
+
+        let mut item = json!({
+            "method": "item/started",
+            "params": {
+                "threadId": "thread-1",
+                "turnId": "turn-1",
+                "item": { "id": 7, "type": "tool_use", "name": "Read" }
+            }
+        });
+        normalize_event(&mut item);
+        assert_eq!(item["params"]["itemId"], "7");
+        assert_eq!(item["params"]["item"]["id"], "7");
+        assert_eq!(item["params"]["item"]["status"], "inProgress");
+        assert_eq!(item["params"]["item"]["type"], "tool_use");
+
+        let mut other = json!({ "method": "codex/connected", "params": { "workspace_id": "w" } });
+        let before = other.clone();
+        normalize_event(&mut other);
+        assert_eq!(other, before);
+    }
+}
@@ -0,0 +1,330 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::Serialize;
+
+use crate::files::encoding::decode_text;
+use crate::shared::turn_snapshot_core::walk_workspace_files;
+
+/// Largest file a [`FileWatch`] keeps in memory to count changed lines.
+const WATCH_CACHE_FILE_BYTES: u64 = 256 * 1024;
+/// Total text a [`FileWatch`] keeps; files past it are reported without
+/// line counts.
+const WATCH_CACHE_TOTAL_BYTES: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FileStamp {
+    len: u64,
+    modified: Option<SystemTime>,
+}
+
+pub(crate) type WorkspaceStamps = HashMap<String, FileStamp>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChangedFiles {
+    pub(crate) added: Vec<String>,
+    pub(crate) modified: Vec<String>,
+    pub(crate) deleted: Vec<String>,
+}
+
+impl ChangedFiles {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.modified.is_empty() && self.deleted.is_empty()
+    }
+
+    pub(crate) fn paths(&self) -> Vec<String> {
+        let mut paths = self
+            .added
+            .iter()
+            .chain(self.modified.iter())
+            .chain(self.deleted.iter())
+            .cloned()
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+}
+
+/// Records size and mtime for every workspace file.
+pub(crate) fn scan_workspace_stamps(root: &Path) -> WorkspaceStamps {
+    let (paths, _) = walk_workspace_files(root);
+    paths
+        .into_iter()
+        .filter_map(|rel_path| {
+            let metadata = std::fs::metadata(root.join(&rel_path)).ok()?;
+            let stamp = FileStamp {
+                len: metadata.len(),
+                modified: metadata.modified().ok(),
+            };
+            Some((rel_path, stamp))
+        })
+        .collect()
+}
+
+pub(crate) fn diff_stamps(before: &WorkspaceStamps, after: &WorkspaceStamps) -> ChangedFiles {
+    let mut changes = ChangedFiles::default();
+    for (path, stamp) in after {
+        match before.get(path) {
+            None => changes.added.push(path.clone()),
+            Some(previous) if previous != stamp => changes.modified.push(path.clone()),
+            Some(_) => {}
+        }
+    }
+    for path in before.keys() {
+        if !after.contains_key(path) {
+            changes.deleted.push(path.clone());
+        }
+    }
+    changes.added.sort();
+    changes.modified.sort();
+    changes.deleted.sort();
+    changes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileChangeSummary {
+    pub(crate) path: String,
+    pub(crate) kind: FileChangeKind,
+    /// Lines added since the previous poll. `None` when the file is binary,
+    /// too large, or wasn't cached before it changed.
+    pub(crate) additions: Option<usize>,
+    pub(crate) deletions: Option<usize>,
+}
+
+/// Lines added and removed between two versions of a file. Lines are
+/// counted as a multiset, so a moved line counts as neither.
+fn line_changes(old: &str, new: &str) -> (usize, usize) {
+    let mut counts: HashMap<&str, i64> = HashMap::new();
+    for line in old.lines() {
+        *counts.entry(line).or_default() -= 1;
+    }
+    for line in new.lines() {
+        *counts.entry(line).or_default() += 1;
+    }
+    counts
+        .values()
+        .fold((0, 0), |(additions, deletions), count| {
+            if *count > 0 {
+                (additions + count.unsigned_abs() as usize, deletions)
+            } else {
+                (additions, deletions + count.unsigned_abs() as usize)
+            }
+        })
+}
+
+/// Follows a workspace while a turn runs. Each [`FileWatch::poll`] reports
+/// the files changed since the previous one, with line counts for text
+/// files whose earlier content is cached.
+pub(crate) struct FileWatch {
+    root: PathBuf,
+    /// The workspace as it was when the watch started.
+    baseline: WorkspaceStamps,
+    stamps: WorkspaceStamps,
+    contents: HashMap<String, String>,
+    cached_bytes: usize,
+}
+
+impl FileWatch {
+    pub(crate) fn start(root: PathBuf) -> Self {
+        let stamps = scan_workspace_stamps(&root);
+        let mut watch = Self {
+            root,
+            baseline: WorkspaceStamps::new(),
+            stamps: WorkspaceStamps::new(),
+            contents: HashMap::new(),
+            cached_bytes: 0,
+        };
+        let mut paths = stamps.keys().cloned().collect::<Vec<_>>();
+        paths.sort();
+        for path in paths {
+            if let Some(content) = watch.read_text(&path) {
+                watch.cache(path, content);
+            }
+        }
+        watch.baseline = stamps.clone();
+        watch.stamps = stamps;
+        watch
+    }
+
+    fn read_text(&self, path: &str) -> Option<String> {
+        let path = self.root.join(path);
+        if std::fs::metadata(&path).ok()?.len() > WATCH_CACHE_FILE_BYTES {
+            return None;
+        }
+        let bytes = std::fs::read(path).ok()?;
+        decode_text(&bytes, false).ok().map(|(text, _)| text)
+    }
+
+    fn cache(&mut self, path: String, content: String) {
+        if self.cached_bytes + content.len() > WATCH_CACHE_TOTAL_BYTES {
+            return;
+        }
+        self.cached_bytes += content.len();
+        self.contents.insert(path, content);
+    }
+
+    fn uncache(&mut self, path: &str) -> Option<String> {
+        let content = self.contents.remove(path)?;
+        self.cached_bytes -= content.len();
+        Some(content)
+    }
+
+    fn summarize(&mut self, path: String, kind: FileChangeKind) -> FileChangeSummary {
+        let previous = self.uncache(&path);
+        let current = match kind {
+            FileChangeKind::Deleted => None,
+            _ => self.read_text(&path),
+        };
+        let counts = match (kind, previous.as_deref(), current.as_deref()) {
+            (FileChangeKind::Added, _, Some(current)) => Some(line_changes("", current)),
+            (FileChangeKind::Modified, Some(previous), Some(current)) => {
+                Some(line_changes(previous, current))
+            }
+            (FileChangeKind::Deleted, Some(previous), _) => Some(line_changes(previous, "")),
+            _ => None,
+        };
+        if let Some(current) = current {
+            self.cache(path.clone(), current);
+        }
+        FileChangeSummary {
+            path,
+            kind,
+            additions: counts.map(|(additions, _)| additions),
+            deletions: counts.map(|(_, deletions)| deletions),
+        }
+    }
+
+    pub(crate) fn poll(&mut self) -> Vec<FileChangeSummary> {
+        let stamps = scan_workspace_stamps(&self.root);
+        let changes = diff_stamps(&self.stamps, &stamps);
+        self.stamps = stamps;
+        let mut summaries = Vec::new();
+        for (paths, kind) in [
+            (changes.added, FileChangeKind::Added),
+            (changes.modified, FileChangeKind::Modified),
+            (changes.deleted, FileChangeKind::Deleted),
+        ] {
+            for path in paths {
+                summaries.push(self.summarize(path, kind));
+            }
+        }
+        summaries.sort_by(|a, b| a.path.cmp(&b.path));
+        summaries
+    }
+
+    /// What changed between [`FileWatch::start`] and the latest poll.
+    pub(crate) fn changes_since_start(&self) -> ChangedFiles {
+        diff_stamps(&self.baseline, &self.stamps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff_stamps, scan_workspace_stamps, FileChangeKind, FileWatch};
+    use std::fs;
+    use uuid::Uuid;
+
+    #[test]
+    fn diff_reports_added_modified_and_deleted_files() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-stamps-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("keep.txt"), "keep").expect("seed keep");
+        fs::write(root.join("edit.txt"), "before").expect("seed edit");
+        fs::write(root.join("gone.txt"), "gone").expect("seed gone");
+
+        let before = scan_workspace_stamps(&root);
+        fs::write(root.join("edit.txt"), "after the turn").expect("modify");
+        fs::remove_file(root.join("gone.txt")).expect("delete");
+        fs::write(root.join("new.txt"), "new").expect("create");
+        let after = scan_workspace_stamps(&root);
+
+        let changes = diff_stamps(&before, &after);
+        assert_eq!(changes.added, vec!["new.txt"]);
+        assert_eq!(changes.modified, vec!["edit.txt"]);
+        assert_eq!(changes.deleted, vec!["gone.txt"]);
+        assert_eq!(changes.paths(), vec!["edit.txt", "gone.txt", "new.txt"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn diff_is_empty_when_nothing_changed() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-stamps-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("same.txt"), "same").expect("seed");
+        let before = scan_workspace_stamps(&root);
+        let after = scan_workspace_stamps(&root);
+        assert!(diff_stamps(&before, &after).is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn file_watch_reports_changes_since_the_previous_poll() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-watch-{}", Uuid::new_v4()));
+        fs::create_dir_all(&root).expect("create root");
+        fs::write(root.join("edit.txt"), "one\ntwo\nthree\n").expect("seed edit");
+        fs::write(root.join("gone.txt"), "a\nb\n").expect("seed gone");
+
+        let mut watch = FileWatch::start(root.clone());
+        assert!(watch.poll().is_empty());
+
+        fs::write(root.join("edit.txt"), "one\n2\nthree\nfour\n").expect("modify");
+        fs::remove_file(root.join("gone.txt")).expect("delete");
+        fs::write(root.join("new.txt"), "new\n").expect("create");
+        let summary = watch
+            .poll()
+            .into_iter()
+            .map(|change| (change.path, change.kind, change.additions, change.deletions))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (
+                    "edit.txt".to_string(),
+                    FileChangeKind::Modified,
+                    Some(2),
+                    Some(1)
+                ),
+                (
+                    "gone.txt".to_string(),
+                    FileChangeKind::Deleted,
+                    Some(0),
+                    Some(2)
+                ),
+                (
+                    "new.txt".to_string(),
+                    FileChangeKind::Added,
+                    Some(1),
+                    Some(0)
+                ),
+            ]
+        );
+
+        fs::write(root.join("new.txt"), "new\nmore lines\n").expect("modify again");
+        let changes = watch.poll();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(
+            (changes[0].additions, changes[0].deletions),
+            (Some(1), Some(0))
+        );
+        assert!(watch.poll().is_empty());
+
+        let turn = watch.changes_since_start();
+        assert_eq!(turn.added, ["new.txt"]);
+        assert_eq!(turn.modified, ["edit.txt"]);
+        assert_eq!(turn.deleted, ["gone.txt"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
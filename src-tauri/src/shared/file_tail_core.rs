@@ -0,0 +1,234 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex as StdMutex, OnceLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::files::ops::read_workspace_range;
+use crate::files::policy::{check_workspace_relative_path, FileScope};
+use crate::shared::files_core::resolve_root_core;
+use crate::types::WorkspaceEntry;
+
+const TAIL_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Bytes sent per `file/tail` event; a file that grew more catches up over
+/// the following polls.
+const TAIL_CHUNK_BYTES: u64 = 256 * 1024;
+const MAX_TAIL_SUBSCRIPTIONS: usize = 32;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FileTailSubscription {
+    pub(crate) subscription_id: String,
+    /// Where following starts; earlier content can be paged with
+    /// `file_read_range`.
+    pub(crate) offset: u64,
+}
+
+fn tails() -> &'static StdMutex<HashMap<String, AbortHandle>> {
+    static TAILS: OnceLock<StdMutex<HashMap<String, AbortHandle>>> = OnceLock::new();
+    TAILS.get_or_init(Default::default)
+}
+
+struct Tail {
+    workspace_id: String,
+    subscription_id: String,
+    root: PathBuf,
+    path: String,
+    offset: u64,
+}
+
+impl Tail {
+    /// Reads what was appended since the last poll. A file that shrank was
+    /// truncated or rotated and is followed again from the start.
+    fn poll(&mut self) -> Result<Option<AppEvent>, String> {
+        let size = match std::fs::metadata(self.root.join(&self.path)) {
+            Ok(metadata) => metadata.len(),
+            Err(_) => return Ok(None),
+        };
+        let reset = size < self.offset;
+        if reset {
+            self.offset = 0;
+        }
+        if size == self.offset && !reset {
+            return Ok(None);
+        }
+        let range =
+            read_workspace_range(&self.root, &self.path, Some(self.offset), TAIL_CHUNK_BYTES)?;
+        if !range.exists {
+            return Ok(None);
+        }
+        let offset = range.offset;
+        self.offset = range.next_offset;
+        Ok(Some(AppEvent::FileTail {
+            workspace_id: self.workspace_id.clone(),
+            subscription_id: self.subscription_id.clone(),
+            path: self.path.clone(),
+            offset,
+            content: range.content,
+            reset,
+            error: None,
+        }))
+    }
+
+    fn error_event(&self, error: String) -> AppEvent {
+        AppEvent::FileTail {
+            workspace_id: self.workspace_id.clone(),
+            subscription_id: self.subscription_id.clone(),
+            path: self.path.clone(),
+            offset: self.offset,
+            content: String::new(),
+            reset: false,
+            error: Some(error),
+        }
+    }
+}
+
+/// Polls until unsubscribed, or until the file can't be read as text.
+async fn follow<E: EventSink>(mut tail: Tail, sink: E) {
+    let workspace_id = tail.workspace_id.clone();
+    let subscription_id = tail.subscription_id.clone();
+    loop {
+        tokio::time::sleep(TAIL_POLL_INTERVAL).await;
+        let polled = tokio::task::spawn_blocking(move || {
+            let result = tail.poll();
+            (result, tail)
+        })
+        .await;
+        let Ok((result, returned)) = polled else {
+            break;
+        };
+        tail = returned;
+        match result {
+            Ok(Some(event)) => {
+                sink.emit_app_server_event(AppServerEvent::new(&workspace_id, event));
+            }
+            Ok(None) => {}
+            Err(error) => {
+                let event = tail.error_event(error);
+                sink.emit_app_server_event(AppServerEvent::new(&workspace_id, event));
+                break;
+            }
+        }
+    }
+    if let Ok(mut tails) = tails().lock() {
+        tails.remove(&subscription_id);
+    }
+}
+
+/// Follows a workspace file, emitting `file/tail` with what was appended
+/// every half second until unsubscribed. Starts at `offset`, or at the end
+/// of the file.
+pub(crate) async fn file_tail_subscribe_core<E: EventSink>(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sink: E,
+    workspace_id: String,
+    path: String,
+    offset: Option<u64>,
+) -> Result<FileTailSubscription, String> {
+    check_workspace_relative_path(&path)?;
+    let root = resolve_root_core(workspaces, FileScope::Workspace, Some(&workspace_id)).await?;
+    let start = {
+        let root = root.clone();
+        let path = path.clone();
+        tokio::task::spawn_blocking(move || read_workspace_range(&root, &path, offset, 0))
+            .await
+            .map_err(|err| err.to_string())??
+    };
+    if !start.exists {
+        return Err(format!("{path} does not exist"));
+    }
+
+    let subscription_id = Uuid::new_v4().to_string();
+    let tail = Tail {
+        workspace_id,
+        subscription_id: subscription_id.clone(),
+        root,
+        path,
+        offset: offset.map_or(start.file_size, |_| start.offset),
+    };
+    let start_offset = tail.offset;
+    let mut tails = tails()
+        .lock()
+        .map_err(|_| "file tails lock poisoned".to_string())?;
+    if tails.len() >= MAX_TAIL_SUBSCRIPTIONS {
+        return Err(format!(
+            "At most {MAX_TAIL_SUBSCRIPTIONS} files can be followed at once"
+        ));
+    }
+    let task = tokio::spawn(follow(tail, sink));
+    tails.insert(subscription_id.clone(), task.abort_handle());
+    Ok(FileTailSubscription {
+        subscription_id,
+        offset: start_offset,
+    })
+}
+
+/// Stops a `file_tail_subscribe` subscription. Returns whether it was
+/// still running.
+pub(crate) fn file_tail_unsubscribe_core(subscription_id: &str) -> bool {
+    let handle = tails()
+        .lock()
+        .ok()
+        .and_then(|mut tails| tails.remove(subscription_id));
+    match handle {
+        Some(handle) => {
+            handle.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tail;
+    use crate::backend::events::AppEvent;
+    use uuid::Uuid;
+
+    #[test]
+    fn polls_appended_content_and_restarts_after_truncation() {
+        let root = std::env::temp_dir().join(format!("file-tail-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&root).expect("create root");
+        std::fs::write(root.join("run.log"), "one\n").expect("write log");
+        let mut tail = Tail {
+            workspace_id: "ws".to_string(),
+            subscription_id: "sub".to_string(),
+            root: root.clone(),
+            path: "run.log".to_string(),
+            offset: 4,
+        };
+        assert!(tail.poll().expect("idle poll").is_none());
+
+        std::fs::write(root.join("run.log"), "one\ntwo\n").expect("append");
+        let Some(AppEvent::FileTail {
+            offset,
+            content,
+            reset,
+            ..
+        }) = tail.poll().expect("poll")
+        else {
+            panic!("expected file/tail");
+        };
+        assert_eq!((offset, content.as_str(), reset), (4, "two\n", false));
+
+        std::fs::write(root.join("run.log"), "new\n").expect("truncate");
+        let Some(AppEvent::FileTail {
+            offset,
+            content,
+            reset,
+            ..
+        }) = tail.poll().expect("poll after truncation")
+        else {
+            panic!("expected file/tail");
+        };
+        assert_eq!((offset, content.as_str(), reset), (0, "new\n", true));
+        assert_eq!(tail.offset, 4);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,12 +1,18 @@
+use serde_json::json;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use tokio::sync::Mutex;
 
 use crate::codex::home as codex_home;
-use crate::files::io::TextFileResponse;
-use crate::files::ops::{read_with_policy, write_with_policy};
+use crate::files::io::{TextFileResponse, TextRangeResponse};
+use crate::files::ops::{
+    read_with_policy, read_workspace_range, read_workspace_relative, write_with_policy,
+    write_workspace_relative,
+};
 use crate::files::policy::{policy_for, FileKind, FileScope};
+use crate::files::tree::{workspace_tree, WorkspaceTreeResponse};
+use crate::shared::audit_log_core::AuditLog;
 use crate::types::WorkspaceEntry;
 
 fn resolve_default_codex_home() -> Result<PathBuf, String> {
@@ -53,6 +59,7 @@ pub(crate) async fn file_read_core(
 
 pub(crate) async fn file_write_core(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
     scope: FileScope,
     kind: FileKind,
     workspace_id: Option<String>,
@@ -60,5 +67,67 @@ pub(crate) async fn file_write_core(
 ) -> Result<(), String> {
     let policy = policy_for(scope, kind)?;
     let root = resolve_root_core(workspaces, scope, workspace_id.as_deref()).await?;
-    write_with_policy(&root, policy, &content)
+    write_with_policy(&root, policy, &content)?;
+    audit_log.record(
+        "file_write",
+        json!({
+            "scope": scope,
+            "kind": kind,
+            "workspaceId": workspace_id,
+            "bytes": content.len(),
+        }),
+    );
+    Ok(())
+}
+
+pub(crate) async fn workspace_file_read_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+) -> Result<TextFileResponse, String> {
+    let root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    read_workspace_relative(&root, &path)
+}
+
+pub(crate) async fn file_read_range_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: String,
+    offset: Option<u64>,
+    length: u64,
+) -> Result<TextRangeResponse, String> {
+    let root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || read_workspace_range(&root, &path, offset, length))
+        .await
+        .map_err(|err| err.to_string())?
+}
+
+pub(crate) async fn workspace_file_write_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
+    workspace_id: String,
+    path: String,
+    content: String,
+) -> Result<(), String> {
+    let root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    write_workspace_relative(&root, &path, &content)?;
+    audit_log.record(
+        "file_write",
+        json!({ "workspaceId": workspace_id, "path": path, "bytes": content.len() }),
+    );
+    Ok(())
+}
+
+pub(crate) async fn workspace_tree_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    workspace_id: String,
+    path: Option<String>,
+    depth: Option<usize>,
+) -> Result<WorkspaceTreeResponse, String> {
+    let root = resolve_workspace_root(workspaces, &workspace_id).await?;
+    tokio::task::spawn_blocking(move || {
+        workspace_tree(&root, path.as_deref().unwrap_or_default(), depth)
+    })
+    .await
+    .map_err(|err| err.to_string())?
 }
@@ -35,6 +35,21 @@ pub(crate) async fn run_git_command(repo_path: &PathBuf, args: &[&str]) -> Resul
     Err(format_git_error(&output.stdout, &output.stderr))
 }
 
+/// Stages everything and commits it. Returns the new HEAD SHA, or `None` when
+/// there was nothing to commit.
+pub(crate) async fn commit_all_changes(
+    repo_path: &PathBuf,
+    message: &str,
+) -> Result<Option<String>, String> {
+    run_git_command(repo_path, &["add", "-A"]).await?;
+    let staged = run_git_command(repo_path, &["diff", "--cached", "--name-only"]).await?;
+    if staged.is_empty() {
+        return Ok(None);
+    }
+    run_git_command(repo_path, &["commit", "-m", message]).await?;
+    run_git_command(repo_path, &["rev-parse", "HEAD"]).await.map(Some)
+}
+
 pub(crate) async fn run_git_command_owned(
     repo_path: PathBuf,
     args_owned: Vec<String>,
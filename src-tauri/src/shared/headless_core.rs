@@ -0,0 +1,225 @@
+#![allow(dead_code)]
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::time::Instant;
+
+use crate::backend::app_server::spawn_workspace_session;
+use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+use crate::backend::plugin_adapter;
+use crate::shared::event_schema_core;
+use crate::shared::workspaces_core::build_cli_spawn_config;
+use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+/// One turn run from the command line, outside the app.
+pub(crate) struct HeadlessTurn {
+    pub(crate) workspace_path: PathBuf,
+    pub(crate) cli_type: String,
+    pub(crate) prompt: String,
+    pub(crate) model: Option<String>,
+    /// Run the turn in a read-only sandbox instead of one that may write to
+    /// the workspace.
+    pub(crate) read_only: bool,
+    pub(crate) timeout: Duration,
+}
+
+/// Forwards the session's notifications, normalized like the app sees
+/// them, to the turn runner.
+#[derive(Clone)]
+struct HeadlessEventSink {
+    tx: mpsc::UnboundedSender<Value>,
+}
+
+impl EventSink for HeadlessEventSink {
+    fn emit_app_server_event(&self, mut event: AppServerEvent) {
+        event_schema_core::normalize_event(&mut event.message);
+        let _ = self.tx.send(event.message);
+    }
+
+    fn emit_terminal_output(&self, _event: TerminalOutput) {}
+
+    fn emit_terminal_exit(&self, _event: TerminalExit) {}
+}
+
+/// The registered workspace at `path`, so its settings (env, account
+/// profile, execution target, ...) apply, or a bare entry for it.
+fn headless_workspace_entry(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    path: &Path,
+) -> Result<WorkspaceEntry, String> {
+    let path = path
+        .canonicalize()
+        .map_err(|err| format!("Invalid workspace path {}: {err}", path.display()))?;
+    let registered = workspaces.values().find(|entry| {
+        Path::new(&entry.path)
+            .canonicalize()
+            .is_ok_and(|candidate| candidate == path)
+    });
+    if let Some(entry) = registered {
+        return Ok(entry.clone());
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "workspace".to_string());
+    Ok(WorkspaceEntry {
+        id: format!("headless-{}", uuid::Uuid::new_v4()),
+        name,
+        path: path.to_string_lossy().to_string(),
+        codex_bin: None,
+        kind: WorkspaceKind::default(),
+        parent_id: None,
+        worktree: None,
+        settings: WorkspaceSettings::default(),
+    })
+}
+
+fn thread_id_of(event: &Value) -> Option<&str> {
+    event["params"]["threadId"].as_str()
+}
+
+/// The status `turn/completed` reports for `thread_id`'s turn, if `event`
+/// is that notification.
+fn completed_turn_status<'a>(event: &'a Value, thread_id: &str) -> Option<&'a str> {
+    if event["method"] != "turn/completed" || thread_id_of(event) != Some(thread_id) {
+        return None;
+    }
+    Some(
+        event["params"]["turn"]["status"]
+            .as_str()
+            .unwrap_or("completed"),
+    )
+}
+
+fn response_error(response: &Value) -> Option<String> {
+    let error = response.get("error")?;
+    Some(
+        error
+            .get("message")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| error.to_string()),
+    )
+}
+
+/// Runs `turn` in a fresh session of its CLI and writes the thread's
+/// notifications to `out` as JSON lines. Returns whether the turn
+/// completed; errors starting it are returned as `Err`. The turn runs
+/// without approval prompts, since nobody is there to answer them.
+pub(crate) async fn run_headless_turn(
+    turn: HeadlessTurn,
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    mut app_settings: AppSettings,
+    client_version: String,
+    out: &mut impl Write,
+) -> Result<bool, String> {
+    if !plugin_adapter::is_known_cli_type(&turn.cli_type) {
+        return Err(format!("Unknown CLI type: {}", turn.cli_type));
+    }
+    let entry = headless_workspace_entry(workspaces, &turn.workspace_path)?;
+    let parent = entry
+        .parent_id
+        .as_ref()
+        .and_then(|parent_id| workspaces.get(parent_id));
+    app_settings.cli_type = turn.cli_type;
+    let config = build_cli_spawn_config(&entry, parent, &app_settings);
+    config.services.apply_settings(&app_settings);
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let session = spawn_workspace_session(
+        entry.clone(),
+        config,
+        client_version,
+        HeadlessEventSink { tx },
+    )
+    .await?;
+
+    let result: Result<bool, String> = async {
+        let thread = session
+            .send_request(
+                "thread/start",
+                json!({ "cwd": entry.path, "approvalPolicy": "never" }),
+            )
+            .await?;
+        if let Some(error) = response_error(&thread) {
+            return Err(format!("Failed to start thread: {error}"));
+        }
+        let result = thread.get("result").unwrap_or(&thread);
+        let thread_id = result["threadId"]
+            .as_str()
+            .or_else(|| result["thread"]["id"].as_str())
+            .ok_or_else(|| format!("thread/start returned no thread id: {thread}"))?
+            .to_string();
+
+        let sandbox_policy = if turn.read_only {
+            json!({ "type": "readOnly" })
+        } else {
+            json!({
+                "type": "workspaceWrite",
+                "writableRoots": [entry.path],
+                "networkAccess": true
+            })
+        };
+        let started = session
+            .send_request(
+                "turn/start",
+                json!({
+                    "threadId": thread_id,
+                    "input": [{ "type": "text", "text": turn.prompt }],
+                    "cwd": entry.path,
+                    "approvalPolicy": "never",
+                    "sandboxPolicy": sandbox_policy,
+                    "model": turn.model,
+                }),
+            )
+            .await?;
+        if let Some(error) = response_error(&started) {
+            return Err(format!("Failed to start turn: {error}"));
+        }
+
+        let deadline = Instant::now() + turn.timeout;
+        loop {
+            let event = tokio::time::timeout_at(deadline, rx.recv())
+                .await
+                .map_err(|_| format!("Turn timed out after {}s", turn.timeout.as_secs()))?
+                .ok_or("The CLI session ended before the turn completed")?;
+            if thread_id_of(&event) != Some(thread_id.as_str()) {
+                continue;
+            }
+            writeln!(out, "{event}").map_err(|err| err.to_string())?;
+            if let Some(status) = completed_turn_status(&event, &thread_id) {
+                return Ok(status == "completed");
+            }
+        }
+    }
+    .await;
+    session.kill().await;
+    let _ = out.flush();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::completed_turn_status;
+    use serde_json::json;
+
+    #[test]
+    fn reads_the_status_of_the_thread_turn() {
+        let completed = json!({
+            "method": "turn/completed",
+            "params": { "threadId": "t-1", "turn": { "id": "turn-1", "status": "failed" } }
+        });
+        assert_eq!(completed_turn_status(&completed, "t-1"), Some("failed"));
+        assert_eq!(completed_turn_status(&completed, "t-2"), None);
+
+        let delta = json!({
+            "method": "item/agentMessage/delta",
+            "params": { "threadId": "t-1", "delta": "hi" }
+        });
+        assert_eq!(completed_turn_status(&delta, "t-1"), None);
+    }
+}
@@ -0,0 +1,313 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{SecondsFormat, Utc};
+use tracing::field::{Field, Visit};
+use tracing::subscriber::Interest;
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+pub(crate) const APP_LOG_NAME: &str = "app";
+const MAX_LOG_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_ROTATED_FILES: usize = 3;
+pub(crate) const RPC_LOG_MAX_CHARS: usize = 4_000;
+
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+static RPC_INSPECTOR: AtomicBool = AtomicBool::new(false);
+
+/// Installs the global subscriber. Events carrying a `workspace_id` field go
+/// to `<workspace_id>.log`, everything else at info or above to `app.log`.
+pub(crate) fn init_logging(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|err| format!("Failed to create log dir: {err}"))?;
+    let _ = LOG_DIR.set(dir.clone());
+    tracing_subscriber::registry()
+        .with(SessionLogLayer::new(dir, MAX_LOG_FILE_BYTES))
+        .try_init()
+        .map_err(|err| err.to_string())
+}
+
+pub(crate) fn log_dir() -> Result<PathBuf, String> {
+    LOG_DIR
+        .get()
+        .cloned()
+        .ok_or_else(|| "Logging is not initialized".to_string())
+}
+
+/// Toggles mirroring of raw JSON-RPC traffic as `debug/rpc` events
+/// (`debugRpcInspector` in app settings).
+pub(crate) fn set_rpc_inspector_enabled(enabled: bool) {
+    RPC_INSPECTOR.store(enabled, Ordering::SeqCst);
+}
+
+pub(crate) fn rpc_inspector_enabled() -> bool {
+    RPC_INSPECTOR.load(Ordering::SeqCst)
+}
+
+/// Shortens large payloads (JSON-RPC messages, prompts passed as arguments)
+/// before they are written to a session log.
+pub(crate) fn truncate_for_log(text: &str, max_chars: usize) -> String {
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+    format!("{}... ({} bytes truncated)", &text[..cut], text.len() - cut)
+}
+
+pub(crate) fn command_line_for_log(command: &std::process::Command) -> String {
+    let line = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|part| part.to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    truncate_for_log(&line, RPC_LOG_MAX_CHARS)
+}
+
+/// Returns the last `lines` lines of a workspace log (or the app log when no
+/// workspace is given), reading into the most recent rotated file if needed.
+pub(crate) fn log_tail_core(
+    dir: &Path,
+    workspace_id: Option<&str>,
+    lines: usize,
+) -> Result<Vec<String>, String> {
+    let path = log_path(dir, workspace_id.map(log_file_stem).as_deref());
+    let mut tail = VecDeque::with_capacity(lines);
+    for file in [rotated_path(&path, 1), path] {
+        let handle = match File::open(&file) {
+            Ok(handle) => handle,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(format!("Failed to read {}: {err}", file.display())),
+        };
+        for line in BufReader::new(handle).lines() {
+            let line = line.map_err(|err| err.to_string())?;
+            if tail.len() == lines {
+                tail.pop_front();
+            }
+            if lines > 0 {
+                tail.push_back(line);
+            }
+        }
+    }
+    Ok(tail.into())
+}
+
+pub(crate) fn log_file_stem(workspace_id: &str) -> String {
+    workspace_id
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn log_path(dir: &Path, stem: Option<&str>) -> PathBuf {
+    dir.join(format!("{}.log", stem.unwrap_or(APP_LOG_NAME)))
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or(APP_LOG_NAME);
+    path.with_file_name(format!("{stem}.{index}.log"))
+}
+
+struct RotatingLogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+}
+
+impl RotatingLogFile {
+    fn open(path: PathBuf, max_bytes: u64) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_bytes,
+        })
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let len = line.len() as u64 + 1;
+        if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += len;
+        Ok(())
+    }
+
+    /// Shifts `name.log` to `name.1.log`, `name.1.log` to `name.2.log` and so
+    /// on, dropping the oldest file.
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (1..MAX_ROTATED_FILES).rev() {
+            let from = rotated_path(&self.path, index);
+            if from.exists() {
+                fs::rename(&from, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct FieldCollector {
+    workspace_id: Option<String>,
+    message: String,
+    fields: String,
+}
+
+impl Visit for FieldCollector {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "workspace_id" => self.workspace_id = Some(value.to_string()),
+            "message" => self.message = value.to_string(),
+            name => {
+                let _ = write!(self.fields, " {name}={value:?}");
+            }
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "workspace_id" => self.workspace_id = Some(format!("{value:?}")),
+            "message" => self.message = format!("{value:?}"),
+            name => {
+                let _ = write!(self.fields, " {name}={value:?}");
+            }
+        }
+    }
+}
+
+struct SessionLogLayer {
+    dir: PathBuf,
+    max_bytes: u64,
+    files: Mutex<HashMap<String, RotatingLogFile>>,
+}
+
+impl SessionLogLayer {
+    fn new(dir: PathBuf, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            max_bytes,
+            files: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Session events are kept at every level; other crates only at info or
+    /// above so dependency debug output doesn't flood the app log.
+    fn wants(metadata: &Metadata<'_>) -> bool {
+        metadata.fields().field("workspace_id").is_some() || *metadata.level() <= Level::INFO
+    }
+
+    fn write(&self, stem: String, line: &str) {
+        let Ok(mut files) = self.files.lock() else {
+            return;
+        };
+        if !files.contains_key(&stem) {
+            let path = log_path(&self.dir, Some(&stem));
+            match RotatingLogFile::open(path, self.max_bytes) {
+                Ok(file) => {
+                    files.insert(stem.clone(), file);
+                }
+                Err(err) => {
+                    eprintln!("logging: failed to open {stem}.log: {err}");
+                    return;
+                }
+            }
+        }
+        if let Some(file) = files.get_mut(&stem) {
+            if let Err(err) = file.write_line(line) {
+                eprintln!("logging: failed to write {stem}.log: {err}");
+            }
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for SessionLogLayer {
+    fn register_callsite(&self, metadata: &'static Metadata<'static>) -> Interest {
+        if Self::wants(metadata) {
+            Interest::always()
+        } else {
+            Interest::never()
+        }
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        Self::wants(metadata)
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut collector = FieldCollector::default();
+        event.record(&mut collector);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:>5} {}: {}{}",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            metadata.level(),
+            metadata.target(),
+            collector.message,
+            collector.fields
+        );
+        let stem = collector
+            .workspace_id
+            .as_deref()
+            .map(log_file_stem)
+            .unwrap_or_else(|| APP_LOG_NAME.to_string());
+        self.write(stem, &line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{log_file_stem, log_tail_core, truncate_for_log, RotatingLogFile};
+    use std::fs;
+    use uuid::Uuid;
+
+    #[test]
+    fn rotates_and_tails_across_files() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-logs-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create dir");
+        let mut file = RotatingLogFile::open(dir.join("ws-1.log"), 16).expect("open log");
+        for line in ["line-1", "line-2", "line-3", "line-4"] {
+            file.write_line(line).expect("write line");
+        }
+        assert!(dir.join("ws-1.1.log").exists());
+        assert_eq!(
+            log_tail_core(&dir, Some("ws-1"), 3).expect("tail"),
+            vec!["line-2", "line-3", "line-4"]
+        );
+        assert!(log_tail_core(&dir, Some("missing"), 3)
+            .expect("missing tail")
+            .is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn truncates_long_payloads_and_sanitizes_names() {
+        assert_eq!(truncate_for_log("short", 10), "short");
+        assert_eq!(
+            truncate_for_log("abcdefghij", 4),
+            "abcd... (6 bytes truncated)"
+        );
+        assert_eq!(log_file_stem("../ws 1"), "___ws_1");
+    }
+}
@@ -0,0 +1,442 @@
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::shared::thread_retention_core::RetentionSettings;
+use crate::shared::{
+    orphan_cleanup_core, thread_retention_core, turn_snapshot_core, workspace_trash_core,
+};
+use crate::storage::read_workspaces;
+
+const SCHEDULER_TICK: Duration = Duration::from_secs(60);
+const SNAPSHOT_CLEANUP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const THREAD_RETENTION_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const ORPHAN_CLEANUP_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+const TRASH_PURGE_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Agent turns in flight, shared by a [`MaintenanceCoordinator`] and the
+/// sessions it waits for.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActiveTurns(Arc<AtomicUsize>);
+
+impl ActiveTurns {
+    /// Called when an agent turn starts. Maintenance is held off until every
+    /// started turn has finished.
+    pub(crate) fn started(&self) {
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn finished(&self) {
+        let _ = self
+            .0
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                count.checked_sub(1)
+            });
+    }
+
+    fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+fn now_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+type MaintenanceFuture = Pin<Box<dyn Future<Output = Result<String, String>> + Send>>;
+type MaintenanceJob = Arc<dyn Fn(MaintenanceProgress) -> MaintenanceFuture + Send + Sync>;
+
+/// Handle passed to a running job so it can report intermediate progress.
+#[derive(Clone)]
+pub(crate) struct MaintenanceProgress {
+    task: String,
+    emit: Arc<dyn Fn(Value) + Send + Sync>,
+}
+
+impl MaintenanceProgress {
+    pub(crate) fn report(&self, message: &str) {
+        (self.emit)(json!({
+            "task": self.task,
+            "state": "running",
+            "message": message,
+        }));
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum MaintenanceTaskState {
+    Idle,
+    Running,
+    Deferred,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MaintenanceTaskStatus {
+    pub(crate) name: String,
+    pub(crate) interval_seconds: u64,
+    pub(crate) state: MaintenanceTaskState,
+    pub(crate) runs: u64,
+    pub(crate) next_run_at: u64,
+    pub(crate) last_started_at: Option<u64>,
+    pub(crate) last_finished_at: Option<u64>,
+    pub(crate) last_result: Option<String>,
+    pub(crate) last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MaintenanceStatus {
+    pub(crate) active_turns: usize,
+    pub(crate) running: Option<String>,
+    pub(crate) tasks: Vec<MaintenanceTaskStatus>,
+}
+
+struct MaintenanceTask {
+    job: MaintenanceJob,
+    status: MaintenanceTaskStatus,
+}
+
+/// Runs registered background jobs (pruning, cleanup, indexing, ...) one at a
+/// time on a shared schedule, and never while an agent turn is in flight.
+pub(crate) struct MaintenanceCoordinator {
+    tasks: Mutex<Vec<MaintenanceTask>>,
+    active_turns: ActiveTurns,
+}
+
+impl MaintenanceCoordinator {
+    pub(crate) fn new() -> Self {
+        Self {
+            tasks: Mutex::new(Vec::new()),
+            active_turns: ActiveTurns::default(),
+        }
+    }
+
+    /// The counter sessions report their turns to; pass it to every
+    /// session spawned while this coordinator runs.
+    pub(crate) fn active_turns(&self) -> ActiveTurns {
+        self.active_turns.clone()
+    }
+
+    /// Registers a job that runs every `interval`, starting with the next
+    /// scheduler tick. Registering an existing name replaces that job.
+    pub(crate) fn register<F, Fut>(&self, name: &str, interval: Duration, job: F)
+    where
+        F: Fn(MaintenanceProgress) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, String>> + Send + 'static,
+    {
+        let job: MaintenanceJob = Arc::new(move |progress| Box::pin(job(progress)));
+        let task = MaintenanceTask {
+            job,
+            status: MaintenanceTaskStatus {
+                name: name.to_string(),
+                interval_seconds: interval.as_secs(),
+                state: MaintenanceTaskState::Idle,
+                runs: 0,
+                next_run_at: now_epoch(),
+                last_started_at: None,
+                last_finished_at: None,
+                last_result: None,
+                last_error: None,
+            },
+        };
+        let Ok(mut tasks) = self.tasks.lock() else {
+            return;
+        };
+        tasks.retain(|existing| existing.status.name != name);
+        tasks.push(task);
+    }
+
+    pub(crate) fn status(&self) -> MaintenanceStatus {
+        let tasks = self
+            .tasks
+            .lock()
+            .map(|tasks| {
+                tasks
+                    .iter()
+                    .map(|task| task.status.clone())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let running = tasks
+            .iter()
+            .find(|task| task.state == MaintenanceTaskState::Running)
+            .map(|task| task.name.clone());
+        MaintenanceStatus {
+            active_turns: self.active_turns.count(),
+            running,
+            tasks,
+        }
+    }
+
+    /// Scheduler loop. Adapters spawn this once on their own runtime.
+    pub(crate) async fn run<E: EventSink>(self: Arc<Self>, event_sink: E) {
+        loop {
+            self.run_due_tasks(&event_sink).await;
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    }
+
+    async fn run_due_tasks<E: EventSink>(&self, event_sink: &E) {
+        loop {
+            let now = now_epoch();
+            if self.active_turns.count() > 0 {
+                for name in self.defer_due_tasks(now) {
+                    emit_maintenance_event(
+                        event_sink,
+                        json!({ "task": name, "state": "deferred" }),
+                    );
+                }
+                return;
+            }
+            let Some((name, job)) = self.start_next_due(now) else {
+                return;
+            };
+            emit_maintenance_event(event_sink, json!({ "task": name, "state": "started" }));
+            let sink = event_sink.clone();
+            let progress = MaintenanceProgress {
+                task: name.clone(),
+                emit: Arc::new(move |params: Value| emit_maintenance_event(&sink, params)),
+            };
+            let result = job(progress).await;
+            self.finish(&name, &result);
+            let params = match result {
+                Ok(message) => json!({ "task": name, "state": "completed", "message": message }),
+                Err(error) => json!({ "task": name, "state": "failed", "error": error }),
+            };
+            emit_maintenance_event(event_sink, params);
+        }
+    }
+
+    /// Marks due tasks as deferred and returns the ones that just changed
+    /// state, so the deferral is announced once rather than every tick.
+    fn defer_due_tasks(&self, now: u64) -> Vec<String> {
+        let Ok(mut tasks) = self.tasks.lock() else {
+            return Vec::new();
+        };
+        tasks
+            .iter_mut()
+            .filter(|task| {
+                task.status.next_run_at <= now && task.status.state == MaintenanceTaskState::Idle
+            })
+            .map(|task| {
+                task.status.state = MaintenanceTaskState::Deferred;
+                task.status.name.clone()
+            })
+            .collect()
+    }
+
+    fn start_next_due(&self, now: u64) -> Option<(String, MaintenanceJob)> {
+        let mut tasks = self.tasks.lock().ok()?;
+        let task = tasks.iter_mut().find(|task| {
+            task.status.next_run_at <= now && task.status.state != MaintenanceTaskState::Running
+        })?;
+        task.status.state = MaintenanceTaskState::Running;
+        task.status.last_started_at = Some(now);
+        Some((task.status.name.clone(), Arc::clone(&task.job)))
+    }
+
+    fn finish(&self, name: &str, result: &Result<String, String>) {
+        let Ok(mut tasks) = self.tasks.lock() else {
+            return;
+        };
+        let Some(task) = tasks.iter_mut().find(|task| task.status.name == name) else {
+            return;
+        };
+        let now = now_epoch();
+        task.status.state = MaintenanceTaskState::Idle;
+        task.status.runs += 1;
+        task.status.last_finished_at = Some(now);
+        task.status.next_run_at = now + task.status.interval_seconds.max(1);
+        match result {
+            Ok(message) => {
+                task.status.last_result = Some(message.clone());
+                task.status.last_error = None;
+            }
+            Err(error) => {
+                task.status.last_error = Some(error.clone());
+            }
+        }
+    }
+}
+
+fn emit_maintenance_event<E: EventSink>(event_sink: &E, params: Value) {
+    event_sink.emit_app_server_event(AppServerEvent::new(
+        String::new(),
+        AppEvent::MaintenanceProgress(params),
+    ));
+}
+
+/// Registers the built-in jobs shared by the app and the daemon.
+pub(crate) fn register_default_tasks(
+    coordinator: &MaintenanceCoordinator,
+    storage_path: PathBuf,
+    retention: RetentionSettings,
+) {
+    let retention_storage_path = storage_path.clone();
+    let orphan_storage_path = storage_path.clone();
+    let trash_storage_path = storage_path.clone();
+    coordinator.register(
+        "snapshotCleanup",
+        SNAPSHOT_CLEANUP_INTERVAL,
+        move |progress| {
+            let storage_path = storage_path.clone();
+            async move {
+                progress.report("Removing snapshots for deleted workspaces");
+                tokio::task::spawn_blocking(move || -> Result<String, String> {
+                    let mut workspace_ids = read_workspaces(&storage_path)?
+                        .into_keys()
+                        .collect::<Vec<_>>();
+                    // Trashed workspaces keep their snapshots until purged.
+                    workspace_ids
+                        .extend(workspace_trash_core::trashed_workspace_ids(&storage_path)?);
+                    let removed =
+                        turn_snapshot_core::prune_orphaned_snapshot_stores(&workspace_ids)?;
+                    Ok(format!("Removed {removed} orphaned snapshot store(s)"))
+                })
+                .await
+                .map_err(|err| err.to_string())?
+            }
+        },
+    );
+    coordinator.register(
+        "threadRetention",
+        THREAD_RETENTION_INTERVAL,
+        move |progress| {
+            let storage_path = retention_storage_path.clone();
+            let policy = retention.policy();
+            async move {
+                progress.report("Archiving and deleting old threads");
+                let workspace_ids = read_workspaces(&storage_path)?
+                    .into_keys()
+                    .collect::<Vec<_>>();
+                thread_retention_core::run_thread_retention(workspace_ids, policy).await
+            }
+        },
+    );
+    coordinator.register("orphanCleanup", ORPHAN_CLEANUP_INTERVAL, move |progress| {
+        let storage_path = orphan_storage_path.clone();
+        async move {
+            progress.report("Archiving data of removed workspaces");
+            tokio::task::spawn_blocking(move || -> Result<String, String> {
+                let workspace_ids = read_workspaces(&storage_path)?
+                    .into_keys()
+                    .collect::<Vec<_>>();
+                Ok(orphan_cleanup_core::clean_up_orphaned_data(&workspace_ids))
+            })
+            .await
+            .map_err(|err| err.to_string())?
+        }
+    });
+    coordinator.register("trashPurge", TRASH_PURGE_INTERVAL, move |progress| {
+        let storage_path = trash_storage_path.clone();
+        async move {
+            progress.report("Deleting expired removed workspaces");
+            tokio::task::spawn_blocking(move || {
+                workspace_trash_core::purge_expired_trash(&storage_path)
+            })
+            .await
+            .map_err(|err| err.to_string())?
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MaintenanceCoordinator, MaintenanceTaskState};
+    use crate::backend::events::{AppServerEvent, EventSink, TerminalExit, TerminalOutput};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    #[derive(Clone, Default)]
+    struct RecordingSink {
+        states: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl EventSink for RecordingSink {
+        fn emit_app_server_event(&self, event: AppServerEvent) {
+            let state = event.message["params"]["state"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string();
+            self.states.lock().unwrap().push(state);
+        }
+
+        fn emit_terminal_output(&self, _event: TerminalOutput) {}
+
+        fn emit_terminal_exit(&self, _event: TerminalExit) {}
+    }
+
+    #[tokio::test]
+    async fn runs_due_tasks_once_turns_finish() {
+        let coordinator = MaintenanceCoordinator::new();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let job_calls = Arc::clone(&calls);
+        coordinator.register("cleanup", Duration::from_secs(3600), move |progress| {
+            let calls = Arc::clone(&job_calls);
+            async move {
+                progress.report("working");
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok("done".to_string())
+            }
+        });
+        coordinator.register("broken", Duration::from_secs(60), |_progress| async {
+            Err("disk unavailable".to_string())
+        });
+        let sink = RecordingSink::default();
+
+        let active_turns = coordinator.active_turns();
+        active_turns.started();
+        coordinator.run_due_tasks(&sink).await;
+        coordinator.run_due_tasks(&sink).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        let status = coordinator.status();
+        assert_eq!(status.active_turns, 1);
+        assert!(status
+            .tasks
+            .iter()
+            .all(|task| task.state == MaintenanceTaskState::Deferred));
+
+        active_turns.finished();
+        coordinator.run_due_tasks(&sink).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let status = coordinator.status();
+        assert_eq!(status.tasks[0].state, MaintenanceTaskState::Idle);
+        assert_eq!(status.tasks[0].runs, 1);
+        assert_eq!(status.tasks[0].last_result.as_deref(), Some("done"));
+        assert!(status.tasks[0].next_run_at > status.tasks[0].last_started_at.unwrap());
+        assert_eq!(status.tasks[1].runs, 1);
+        assert_eq!(
+            status.tasks[1].last_error.as_deref(),
+            Some("disk unavailable")
+        );
+        assert!(status.running.is_none());
+
+        coordinator.run_due_tasks(&sink).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *sink.states.lock().unwrap(),
+            vec![
+                "deferred",
+                "deferred",
+                "started",
+                "running",
+                "completed",
+                "started",
+                "failed"
+            ]
+        );
+    }
+}
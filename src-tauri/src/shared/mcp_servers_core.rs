@@ -0,0 +1,467 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use toml::Value as TomlValue;
+use toml_edit::{value, Array, DocumentMut, Item, Table};
+
+use crate::codex::config as codex_config;
+use crate::shared::atomic_write::write_atomic;
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::sandbox_setup_core::resolve_default_gemini_home_fallback;
+use crate::types::McpServer;
+
+const CODEX_SOURCE: &str = "codex";
+const GEMINI_SOURCE: &str = "gemini";
+const CODEX_TABLE: &str = "mcp_servers";
+
+/// Servers from both configs, merged by name. A server defined in both is
+/// reported as enabled only if neither config disables it.
+pub(crate) fn mcp_servers_list_core() -> Result<Vec<McpServer>, String> {
+    let codex = codex_config::read_config_toml()?
+        .map(|contents| parse_codex_servers(&contents))
+        .transpose()?
+        .unwrap_or_default();
+    let gemini = read_gemini_settings()?
+        .map(|(_, root)| parse_gemini_servers(&root))
+        .unwrap_or_default();
+    Ok(merge_servers(codex, gemini))
+}
+
+pub(crate) fn mcp_server_add_core(
+    server: McpServer,
+    audit_log: &AuditLog,
+) -> Result<Vec<McpServer>, String> {
+    validate_server(&server)?;
+    let contents = codex_config::read_config_toml()?.unwrap_or_default();
+    let mut doc = codex_config::parse_config_document(&contents)?;
+    upsert_codex_server(&mut doc, &server)?;
+    codex_config::write_config_toml(&doc.to_string())?;
+
+    let (path, mut root) = read_gemini_settings()?.unwrap_or_else(|| (gemini_path(), json!({})));
+    upsert_gemini_server(&mut root, &server);
+    write_gemini_settings(path, &root)?;
+    audit_log.record("mcp_server_add", json!({ "name": server.name }));
+    mcp_servers_list_core()
+}
+
+pub(crate) fn mcp_server_remove_core(
+    name: &str,
+    audit_log: &AuditLog,
+) -> Result<Vec<McpServer>, String> {
+    let mut found = false;
+    if let Some(contents) = codex_config::read_config_toml()? {
+        let mut doc = codex_config::parse_config_document(&contents)?;
+        if remove_codex_server(&mut doc, name)? {
+            codex_config::write_config_toml(&doc.to_string())?;
+            found = true;
+        }
+    }
+    if let Some((path, mut root)) = read_gemini_settings()? {
+        if remove_gemini_server(&mut root, name) {
+            write_gemini_settings(path, &root)?;
+            found = true;
+        }
+    }
+    if !found {
+        return Err(format!("MCP server `{name}` not found"));
+    }
+    audit_log.record("mcp_server_remove", json!({ "name": name }));
+    mcp_servers_list_core()
+}
+
+pub(crate) fn mcp_server_toggle_core(
+    name: &str,
+    enabled: bool,
+    audit_log: &AuditLog,
+) -> Result<Vec<McpServer>, String> {
+    let mut found = false;
+    if let Some(contents) = codex_config::read_config_toml()? {
+        let mut doc = codex_config::parse_config_document(&contents)?;
+        if set_codex_server_enabled(&mut doc, name, enabled)? {
+            codex_config::write_config_toml(&doc.to_string())?;
+            found = true;
+        }
+    }
+    if let Some((path, mut root)) = read_gemini_settings()? {
+        if set_gemini_server_enabled(&mut root, name, enabled) {
+            write_gemini_settings(path, &root)?;
+            found = true;
+        }
+    }
+    if !found {
+        return Err(format!("MCP server `{name}` not found"));
+    }
+    audit_log.record(
+        "mcp_server_toggle",
+        json!({ "name": name, "enabled": enabled }),
+    );
+    mcp_servers_list_core()
+}
+
+fn validate_server(server: &McpServer) -> Result<(), String> {
+    let valid_name = !server.name.is_empty()
+        && server
+            .name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if !valid_name {
+        return Err("MCP server name may only contain letters, digits, `-` and `_`".to_string());
+    }
+    let has_command = server
+        .command
+        .as_deref()
+        .is_some_and(|command| !command.trim().is_empty());
+    let has_url = server
+        .url
+        .as_deref()
+        .is_some_and(|url| !url.trim().is_empty());
+    if has_command == has_url {
+        return Err("MCP server needs either a command or a url".to_string());
+    }
+    Ok(())
+}
+
+fn merge_servers(codex: Vec<McpServer>, gemini: Vec<McpServer>) -> Vec<McpServer> {
+    let mut merged: BTreeMap<String, McpServer> = BTreeMap::new();
+    for server in codex.into_iter().chain(gemini) {
+        match merged.get_mut(&server.name) {
+            Some(existing) => {
+                existing.enabled &= server.enabled;
+                existing.sources.extend(server.sources);
+            }
+            None => {
+                merged.insert(server.name.clone(), server);
+            }
+        }
+    }
+    merged.into_values().collect()
+}
+
+fn string_map(value: Option<&Value>) -> BTreeMap<String, String> {
+    value
+        .and_then(|value| value.as_object())
+        .map(|map| {
+            map.iter()
+                .filter_map(|(key, value)| Some((key.clone(), value.as_str()?.to_string())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn string_list(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(|value| value.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|item| item.as_str().map(|value| value.to_string()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_codex_servers(contents: &str) -> Result<Vec<McpServer>, String> {
+    let parsed: TomlValue =
+        toml::from_str(contents).map_err(|err| format!("Failed to parse config.toml: {err}"))?;
+    let Some(servers) = parsed.get(CODEX_TABLE).and_then(|value| value.as_table()) else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::new();
+    for (name, entry) in servers {
+        let entry = serde_json::to_value(entry).map_err(|err| err.to_string())?;
+        result.push(McpServer {
+            name: name.clone(),
+            command: entry
+                .get("command")
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string()),
+            args: string_list(entry.get("args")),
+            env: string_map(entry.get("env")),
+            url: entry
+                .get("url")
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string()),
+            enabled: entry
+                .get("enabled")
+                .and_then(|value| value.as_bool())
+                .unwrap_or(true),
+            sources: vec![CODEX_SOURCE.to_string()],
+        });
+    }
+    Ok(result)
+}
+
+fn codex_server_item(server: &McpServer) -> Item {
+    let mut table = Table::new();
+    if let Some(command) = &server.command {
+        table.insert("command", value(command.as_str()));
+    }
+    if !server.args.is_empty() {
+        let args = server.args.iter().map(String::as_str).collect::<Array>();
+        table.insert("args", value(args));
+    }
+    if let Some(url) = &server.url {
+        table.insert("url", value(url.as_str()));
+    }
+    if !server.enabled {
+        table.insert("enabled", value(false));
+    }
+    if !server.env.is_empty() {
+        let mut env = Table::new();
+        for (key, entry) in &server.env {
+            env.insert(key, value(entry.as_str()));
+        }
+        table.insert("env", Item::Table(env));
+    }
+    Item::Table(table)
+}
+
+/// Replaces the server's table, leaving the rest of the file as written.
+fn upsert_codex_server(doc: &mut DocumentMut, server: &McpServer) -> Result<(), String> {
+    codex_config::set_config_item(
+        doc,
+        &[CODEX_TABLE, &server.name],
+        Some(codex_server_item(server)),
+    )
+}
+
+fn remove_codex_server(doc: &mut DocumentMut, name: &str) -> Result<bool, String> {
+    if codex_config::config_item(doc, &[CODEX_TABLE, name]).is_none() {
+        return Ok(false);
+    }
+    codex_config::set_config_item(doc, &[CODEX_TABLE, name], None)?;
+    Ok(true)
+}
+
+/// Sets `enabled` on the server; `false` if the server isn't defined in the
+/// file.
+fn set_codex_server_enabled(
+    doc: &mut DocumentMut,
+    name: &str,
+    enabled: bool,
+) -> Result<bool, String> {
+    if codex_config::config_item(doc, &[CODEX_TABLE, name]).is_none() {
+        return Ok(false);
+    }
+    codex_config::set_config_item(doc, &[CODEX_TABLE, name, "enabled"], Some(value(enabled)))?;
+    Ok(true)
+}
+
+fn gemini_path() -> PathBuf {
+    resolve_default_gemini_home_fallback()
+        .unwrap_or_else(|| PathBuf::from(".gemini"))
+        .join("settings.json")
+}
+
+fn read_gemini_settings() -> Result<Option<(PathBuf, Value)>, String> {
+    let path = gemini_path();
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|err| format!("Failed to read {}: {err}", path.display()))?;
+    if contents.trim().is_empty() {
+        return Ok(Some((path, json!({}))));
+    }
+    let root = serde_json::from_str::<Value>(&contents)
+        .map_err(|err| format!("Failed to parse {}: {err}", path.display()))?;
+    Ok(Some((path, root)))
+}
+
+fn write_gemini_settings(path: PathBuf, root: &Value) -> Result<(), String> {
+    let serialized = serde_json::to_string_pretty(root)
+        .map_err(|err| format!("Failed to serialize Gemini settings: {err}"))?;
+    write_atomic(&path, format!("{serialized}\n").as_bytes())
+        .map_err(|err| format!("Failed to write {}: {err}", path.display()))
+}
+
+fn ensure_object(value: &mut Value) -> &mut Map<String, Value> {
+    if !value.is_object() {
+        *value = json!({});
+    }
+    value
+        .as_object_mut()
+        .expect("value was initialized to an object")
+}
+
+/// Gemini configs vary across versions (`mcpServers` vs `mcp.servers`);
+/// `mcpServers` wins when a server is defined in both.
+fn gemini_server_entries(root: &Value) -> Map<String, Value> {
+    let mut entries = root
+        .pointer("/mcp/servers")
+        .and_then(|value| value.as_object())
+        .cloned()
+        .unwrap_or_default();
+    if let Some(servers) = root.get("mcpServers").and_then(|value| value.as_object()) {
+        entries.extend(servers.clone());
+    }
+    entries
+}
+
+fn gemini_excluded(root: &Value) -> Vec<String> {
+    string_list(root.pointer("/mcp/excluded"))
+}
+
+fn parse_gemini_servers(root: &Value) -> Vec<McpServer> {
+    let excluded = gemini_excluded(root);
+    gemini_server_entries(root)
+        .into_iter()
+        .map(|(name, entry)| McpServer {
+            enabled: !excluded.contains(&name),
+            command: entry
+                .get("command")
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string()),
+            args: string_list(entry.get("args")),
+            env: string_map(entry.get("env")),
+            url: entry
+                .get("httpUrl")
+                .or_else(|| entry.get("url"))
+                .and_then(|value| value.as_str())
+                .map(|value| value.to_string()),
+            sources: vec![GEMINI_SOURCE.to_string()],
+            name,
+        })
+        .collect()
+}
+
+fn upsert_gemini_server(root: &mut Value, server: &McpServer) {
+    let mut payload = Map::new();
+    if let Some(command) = &server.command {
+        payload.insert("command".to_string(), json!(command));
+        payload.insert("args".to_string(), json!(server.args));
+    }
+    if let Some(url) = &server.url {
+        payload.insert("httpUrl".to_string(), json!(url));
+    }
+    if !server.env.is_empty() {
+        payload.insert("env".to_string(), json!(server.env));
+    }
+    let payload = Value::Object(payload);
+
+    let root_object = ensure_object(root);
+    let servers = root_object
+        .entry("mcpServers".to_string())
+        .or_insert_with(|| json!({}));
+    ensure_object(servers).insert(server.name.clone(), payload.clone());
+    if let Some(servers) = root_object
+        .get_mut("mcp")
+        .and_then(|mcp| mcp.get_mut("servers"))
+        .and_then(|servers| servers.as_object_mut())
+    {
+        servers.insert(server.name.clone(), payload);
+    }
+    set_gemini_server_enabled(root, &server.name, server.enabled);
+}
+
+fn remove_gemini_server(root: &mut Value, name: &str) -> bool {
+    let mut removed = false;
+    for pointer in ["/mcpServers", "/mcp/servers"] {
+        if let Some(servers) = root
+            .pointer_mut(pointer)
+            .and_then(|servers| servers.as_object_mut())
+        {
+            removed |= servers.remove(name).is_some();
+        }
+    }
+    if let Some(excluded) = root
+        .pointer_mut("/mcp/excluded")
+        .and_then(|excluded| excluded.as_array_mut())
+    {
+        excluded.retain(|item| item.as_str() != Some(name));
+    }
+    removed
+}
+
+/// Toggles the server through the `mcp.excluded` list; `false` if the
+/// server isn't defined.
+fn set_gemini_server_enabled(root: &mut Value, name: &str, enabled: bool) -> bool {
+    if !gemini_server_entries(root).contains_key(name) {
+        return false;
+    }
+    let mcp = ensure_object(root)
+        .entry("mcp".to_string())
+        .or_insert_with(|| json!({}));
+    let excluded = ensure_object(mcp)
+        .entry("excluded".to_string())
+        .or_insert_with(|| json!([]));
+    if !excluded.is_array() {
+        *excluded = json!([]);
+    }
+    if let Some(excluded) = excluded.as_array_mut() {
+        excluded.retain(|item| item.as_str() != Some(name));
+        if !enabled {
+            excluded.push(json!(name));
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_codex_servers, parse_gemini_servers, remove_codex_server, remove_gemini_server,
+        set_codex_server_enabled, set_gemini_server_enabled, upsert_codex_server,
+        upsert_gemini_server,
+    };
+    use crate::codex::config::parse_config_document;
+    use crate::types::McpServer;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    fn server(name: &str) -> McpServer {
+        McpServer {
+            name: name.to_string(),
+            command: Some("npx".to_string()),
+            args: vec!["-y".to_string(), "docs-mcp".to_string()],
+            env: BTreeMap::from([("API_KEY".to_string(), "secret".to_string())]),
+            url: None,
+            enabled: true,
+            sources: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn codex_servers_are_added_toggled_and_removed_in_place() {
+        let original = "model = \"gpt-5\" # default\n\n[features]\nsteer = true\n";
+        let mut doc = parse_config_document(original).expect("parse config");
+        upsert_codex_server(&mut doc, &server("docs")).expect("add");
+        let added = doc.to_string();
+        let parsed = parse_codex_servers(&added).expect("parse servers");
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].args, vec!["-y", "docs-mcp"]);
+        assert_eq!(
+            parsed[0].env.get("API_KEY").map(String::as_str),
+            Some("secret")
+        );
+        assert!(parsed[0].enabled);
+
+        assert!(set_codex_server_enabled(&mut doc, "docs", false).expect("toggle"));
+        assert!(!parse_codex_servers(&doc.to_string()).expect("parse")[0].enabled);
+        assert!(set_codex_server_enabled(&mut doc, "docs", true).expect("toggle"));
+        assert_eq!(doc.to_string().matches("enabled =").count(), 1);
+        assert!(!set_codex_server_enabled(&mut doc, "missing", false).expect("toggle"));
+
+        assert!(remove_codex_server(&mut doc, "docs").expect("remove"));
+        assert_eq!(doc.to_string(), original);
+    }
+
+    #[test]
+    fn gemini_servers_use_both_tables_and_excluded_list() {
+        let mut root = json!({ "mcp": { "servers": {} }, "theme": "dark" });
+        upsert_gemini_server(&mut root, &server("docs"));
+        assert_eq!(root["mcpServers"]["docs"]["command"], "npx");
+        assert_eq!(root["mcp"]["servers"]["docs"]["env"]["API_KEY"], "secret");
+
+        assert!(set_gemini_server_enabled(&mut root, "docs", false));
+        assert_eq!(root["mcp"]["excluded"], json!(["docs"]));
+        assert!(!parse_gemini_servers(&root)[0].enabled);
+        assert!(!set_gemini_server_enabled(&mut root, "missing", false));
+
+        assert!(remove_gemini_server(&mut root, "docs"));
+        assert!(parse_gemini_servers(&root).is_empty());
+        assert_eq!(root["mcp"]["excluded"], json!([]));
+        assert_eq!(root["theme"], "dark");
+    }
+}
@@ -1,12 +1,51 @@
 pub(crate) mod account;
+pub(crate) mod account_profiles_core;
+pub(crate) mod activity_core;
 pub(crate) mod agent_profiles_core;
+pub(crate) mod agents_md_core;
+pub(crate) mod ansi;
+pub(crate) mod app_error;
+pub(crate) mod app_state_core;
+pub(crate) mod atomic_write;
+pub(crate) mod audit_log_core;
+pub(crate) mod background_turn_core;
+pub(crate) mod budget_core;
 pub(crate) mod cli_detect_core;
+pub(crate) mod cli_updates_core;
 pub(crate) mod codex_aux_core;
 pub(crate) mod codex_core;
+pub(crate) mod codex_sessions_core;
+pub(crate) mod connection_role_core;
+pub(crate) mod delta_batch_core;
+pub(crate) mod event_hooks_core;
+pub(crate) mod event_replay_core;
+pub(crate) mod event_schema_core;
+pub(crate) mod file_changes_core;
+pub(crate) mod file_tail_core;
 pub(crate) mod files_core;
 pub(crate) mod git_core;
+pub(crate) mod headless_core;
+pub(crate) mod logging_core;
+pub(crate) mod maintenance_core;
+pub(crate) mod mcp_servers_core;
+pub(crate) mod orphan_cleanup_core;
+pub(crate) mod patch_core;
+pub(crate) mod path_env;
 pub(crate) mod process_core;
+pub(crate) mod redaction_core;
+pub(crate) mod runtime_services;
 pub(crate) mod sandbox_setup_core;
+pub(crate) mod session_watchdog_core;
 pub(crate) mod settings_core;
+pub(crate) mod settings_profiles_core;
+pub(crate) mod shutdown_core;
+pub(crate) mod thread_retention_core;
+pub(crate) mod turn_limit_core;
+pub(crate) mod turn_snapshot_core;
+pub(crate) mod workflows_core;
+pub(crate) mod workspace_discovery_core;
+pub(crate) mod workspace_env_core;
+pub(crate) mod workspace_templates_core;
+pub(crate) mod workspace_trash_core;
 pub(crate) mod workspaces_core;
 pub(crate) mod worktree_core;
@@ -0,0 +1,370 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::backend::adapter_base::{thread_store_path, ThreadStore};
+use crate::shared::{logging_core, turn_snapshot_core};
+
+/// How long data archived from removed workspaces is kept.
+const ARCHIVE_RETENTION: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+/// Where per-workspace data lives, where it waits while the workspace is
+/// in the trash, and where it is archived once the workspace is gone.
+struct DataDirs {
+    adapter_threads: PathBuf,
+    logs: Option<PathBuf>,
+    trash: PathBuf,
+    archive: PathBuf,
+}
+
+impl DataDirs {
+    fn resolve() -> Self {
+        let adapter_threads = thread_store_path("_")
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let archive = adapter_threads.with_file_name("orphaned");
+        let trash = adapter_threads.with_file_name("trash");
+        Self {
+            adapter_threads,
+            logs: logging_core::log_dir().ok(),
+            trash,
+            archive,
+        }
+    }
+}
+
+/// The workspace an adapter data file belongs to: `<id>.json`,
+/// `<id>.metrics.json` and their `.bak` copies.
+fn store_workspace_id(file_name: &str) -> Option<&str> {
+    if file_name.starts_with('.') {
+        return None;
+    }
+    let name = file_name.strip_suffix(".bak").unwrap_or(file_name);
+    let name = name.strip_suffix(".json")?;
+    Some(name.strip_suffix(".metrics").unwrap_or(name))
+}
+
+/// The log stem of `<stem>.log` or a rotated `<stem>.<n>.log`.
+fn log_stem(file_name: &str) -> Option<&str> {
+    let name = file_name.strip_suffix(".log")?;
+    Some(name.split('.').next().unwrap_or(name))
+}
+
+fn files_in(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .map(|entry| {
+            (
+                entry.file_name().to_string_lossy().to_string(),
+                entry.path(),
+            )
+        })
+        .collect()
+}
+
+/// Moves `files` into `<archive>/<key>/`. Returns how many were moved.
+fn archive_files(archive: &Path, key: &str, files: &[PathBuf]) -> usize {
+    if files.is_empty() {
+        return 0;
+    }
+    let dir = archive.join(logging_core::log_file_stem(key));
+    if let Err(err) = std::fs::create_dir_all(&dir) {
+        tracing::warn!("failed to create {}: {err}", dir.display());
+        return 0;
+    }
+    let mut moved = 0;
+    for file in files {
+        let Some(name) = file.file_name() else {
+            continue;
+        };
+        match std::fs::rename(file, dir.join(name)) {
+            Ok(()) => moved += 1,
+            Err(err) => tracing::warn!("failed to archive {}: {err}", file.display()),
+        }
+    }
+    moved
+}
+
+/// Removes transcripts the CLI recorded for the monitor's own turns. Sessions
+/// imported from the CLI's history are left alone.
+fn remove_turn_transcripts(store_path: &Path) {
+    let store = ThreadStore::load(&store_path.to_path_buf());
+    for meta in store.threads.values() {
+        let Some(path) = &meta.transcript_path else {
+            continue;
+        };
+        if let Err(err) = std::fs::remove_file(path) {
+            if err.kind() != std::io::ErrorKind::NotFound {
+                tracing::warn!("failed to remove {}: {err}", path.display());
+            }
+        }
+    }
+}
+
+/// A workspace's adapter thread store, turn metrics and logs.
+fn workspace_files(dirs: &DataDirs, workspace_id: &str) -> Vec<PathBuf> {
+    let mut files = files_in(&dirs.adapter_threads)
+        .into_iter()
+        .filter(|(name, _)| store_workspace_id(name) == Some(workspace_id))
+        .map(|(_, path)| path)
+        .collect::<Vec<_>>();
+    if let Some(logs) = &dirs.logs {
+        let stem = logging_core::log_file_stem(workspace_id);
+        files.extend(
+            files_in(logs)
+                .into_iter()
+                .filter(|(name, _)| log_stem(name) == Some(stem.as_str()))
+                .map(|(_, path)| path),
+        );
+    }
+    files
+}
+
+fn clean_up_workspace_in(dirs: &DataDirs, workspace_id: &str) -> usize {
+    remove_turn_transcripts(&dirs.adapter_threads.join(format!("{workspace_id}.json")));
+    archive_files(
+        &dirs.archive,
+        workspace_id,
+        &workspace_files(dirs, workspace_id),
+    )
+}
+
+fn trash_workspace_in(dirs: &DataDirs, workspace_id: &str) -> usize {
+    archive_files(
+        &dirs.trash,
+        workspace_id,
+        &workspace_files(dirs, workspace_id),
+    )
+}
+
+/// Moves trashed files back; files that were recreated meanwhile win.
+fn restore_workspace_in(dirs: &DataDirs, workspace_id: &str) -> usize {
+    let dir = dirs.trash.join(logging_core::log_file_stem(workspace_id));
+    let mut restored = 0;
+    for (name, path) in files_in(&dir) {
+        let target = match (log_stem(&name), &dirs.logs) {
+            (Some(_), Some(logs)) => logs.join(&name),
+            (Some(_), None) => continue,
+            (None, _) => dirs.adapter_threads.join(&name),
+        };
+        if target.exists() {
+            continue;
+        }
+        match std::fs::rename(&path, &target) {
+            Ok(()) => restored += 1,
+            Err(err) => tracing::warn!("failed to restore {}: {err}", path.display()),
+        }
+    }
+    if let Err(err) = std::fs::remove_dir_all(&dir) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("failed to remove {}: {err}", dir.display());
+        }
+    }
+    restored
+}
+
+fn remove_turn_snapshots(workspace_id: &str) {
+    if let Ok(store_dir) = turn_snapshot_core::snapshot_store_dir(workspace_id) {
+        if store_dir.exists() {
+            if let Err(err) = std::fs::remove_dir_all(&store_dir) {
+                tracing::warn!(workspace_id, "failed to remove snapshots: {err}");
+            }
+        }
+    }
+}
+
+/// Deletes archives older than [`ARCHIVE_RETENTION`].
+fn prune_archive(archive: &Path, now: SystemTime) -> usize {
+    let Ok(entries) = std::fs::read_dir(archive) else {
+        return 0;
+    };
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > ARCHIVE_RETENTION);
+        if expired && std::fs::remove_dir_all(entry.path()).is_ok() {
+            removed += 1;
+        }
+    }
+    removed
+}
+
+/// Archives a removed workspace's adapter thread store, turn metrics and
+/// logs, and deletes its turn snapshots and the CLI transcripts of its turns.
+/// Blocking.
+pub(crate) fn clean_up_workspace_data(workspace_id: &str) {
+    let archived = clean_up_workspace_in(&DataDirs::resolve(), workspace_id);
+    remove_turn_snapshots(workspace_id);
+    if archived > 0 {
+        tracing::info!("archived {archived} file(s) of removed workspace {workspace_id}");
+    }
+}
+
+/// Moves a trashed workspace's adapter thread store, turn metrics and logs
+/// into the trash. Its turn snapshots and CLI transcripts stay in place so
+/// a restored workspace can still resume and roll back. Blocking.
+pub(crate) fn trash_workspace_data(workspace_id: &str) {
+    trash_workspace_in(&DataDirs::resolve(), workspace_id);
+}
+
+/// Moves a restored workspace's data back out of the trash. Blocking.
+pub(crate) fn restore_workspace_data(workspace_id: &str) {
+    restore_workspace_in(&DataDirs::resolve(), workspace_id);
+}
+
+/// Deletes a trashed workspace's data, turn snapshots and the CLI
+/// transcripts of its turns. Blocking.
+pub(crate) fn purge_workspace_data(workspace_id: &str) {
+    let dir = DataDirs::resolve()
+        .trash
+        .join(logging_core::log_file_stem(workspace_id));
+    remove_turn_transcripts(&dir.join(format!("{workspace_id}.json")));
+    if let Err(err) = std::fs::remove_dir_all(&dir) {
+        if err.kind() != std::io::ErrorKind::NotFound {
+            tracing::warn!("failed to remove {}: {err}", dir.display());
+        }
+    }
+    remove_turn_snapshots(workspace_id);
+}
+
+fn clean_up_orphans_in(dirs: &DataDirs, known_workspace_ids: &[String], now: SystemTime) -> String {
+    let known = known_workspace_ids
+        .iter()
+        .map(String::as_str)
+        .collect::<HashSet<_>>();
+    let orphaned = files_in(&dirs.adapter_threads)
+        .iter()
+        .filter_map(|(name, _)| store_workspace_id(name))
+        .filter(|id| !known.contains(id))
+        .map(str::to_string)
+        .collect::<HashSet<_>>();
+    let mut archived = 0;
+    for workspace_id in &orphaned {
+        archived += clean_up_workspace_in(dirs, workspace_id);
+    }
+
+    // Logs of workspaces that never had an adapter thread store.
+    if let Some(logs) = &dirs.logs {
+        let known_stems = known_workspace_ids
+            .iter()
+            .map(|id| logging_core::log_file_stem(id))
+            .collect::<HashSet<_>>();
+        let mut orphaned_logs = Vec::new();
+        for (name, path) in files_in(logs) {
+            let Some(stem) = log_stem(&name) else {
+                continue;
+            };
+            if stem != logging_core::APP_LOG_NAME && !known_stems.contains(stem) {
+                orphaned_logs.push((stem.to_string(), path));
+            }
+        }
+        for (stem, path) in orphaned_logs {
+            archived += archive_files(&dirs.archive, &stem, &[path]);
+        }
+    }
+
+    let pruned = prune_archive(&dirs.archive, now);
+    format!(
+        "Archived {archived} file(s) of {} removed workspace(s), deleted {pruned} expired archive(s)",
+        orphaned.len()
+    )
+}
+
+/// Archives adapter data and logs left behind by workspaces that are no
+/// longer registered, and drops archives past their retention. Blocking.
+pub(crate) fn clean_up_orphaned_data(known_workspace_ids: &[String]) -> String {
+    clean_up_orphans_in(&DataDirs::resolve(), known_workspace_ids, SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clean_up_orphans_in, restore_workspace_in, store_workspace_id, trash_workspace_in,
+        DataDirs, ARCHIVE_RETENTION,
+    };
+    use std::time::SystemTime;
+    use uuid::Uuid;
+
+    #[test]
+    fn archives_data_of_unknown_workspaces() {
+        let root = std::env::temp_dir().join(format!("orphan-cleanup-{}", Uuid::new_v4()));
+        let dirs = DataDirs {
+            adapter_threads: root.join("adapter-threads"),
+            logs: Some(root.join("logs")),
+            trash: root.join("trash"),
+            archive: root.join("orphaned"),
+        };
+        std::fs::create_dir_all(&dirs.adapter_threads).expect("create stores");
+        std::fs::create_dir_all(root.join("logs")).expect("create logs");
+        for name in [
+            "keep.json",
+            "gone.json",
+            "gone.json.bak",
+            "gone.metrics.json",
+        ] {
+            std::fs::write(dirs.adapter_threads.join(name), "{}").expect("write store");
+        }
+        for name in ["app.log", "keep.log", "gone.log", "gone.1.log", "stale.log"] {
+            std::fs::write(root.join("logs").join(name), "").expect("write log");
+        }
+
+        let summary = clean_up_orphans_in(&dirs, &["keep".to_string()], SystemTime::now());
+        assert_eq!(
+            summary,
+            "Archived 6 file(s) of 1 removed workspace(s), deleted 0 expired archive(s)"
+        );
+        assert!(dirs.adapter_threads.join("keep.json").exists());
+        assert!(!dirs.adapter_threads.join("gone.metrics.json").exists());
+        assert!(dirs.archive.join("gone/gone.json.bak").exists());
+        assert!(dirs.archive.join("gone/gone.1.log").exists());
+        assert!(dirs.archive.join("stale/stale.log").exists());
+        assert!(root.join("logs/app.log").exists());
+
+        let later = SystemTime::now() + ARCHIVE_RETENTION * 2;
+        let summary = clean_up_orphans_in(&dirs, &["keep".to_string()], later);
+        assert!(summary.ends_with("deleted 2 expired archive(s)"));
+        assert_eq!(store_workspace_id(".gone.json.1234.tmp"), None);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn trashed_workspace_data_is_restored_without_overwriting() {
+        let root = std::env::temp_dir().join(format!("workspace-trash-{}", Uuid::new_v4()));
+        let dirs = DataDirs {
+            adapter_threads: root.join("adapter-threads"),
+            logs: Some(root.join("logs")),
+            trash: root.join("trash"),
+            archive: root.join("orphaned"),
+        };
+        std::fs::create_dir_all(&dirs.adapter_threads).expect("create stores");
+        std::fs::create_dir_all(root.join("logs")).expect("create logs");
+        for name in ["ws.json", "ws.metrics.json", "other.json"] {
+            std::fs::write(dirs.adapter_threads.join(name), "{}").expect("write store");
+        }
+        std::fs::write(root.join("logs/ws.log"), "").expect("write log");
+
+        assert_eq!(trash_workspace_in(&dirs, "ws"), 3);
+        assert!(!dirs.adapter_threads.join("ws.json").exists());
+        assert!(dirs.trash.join("ws/ws.log").exists());
+        assert!(dirs.adapter_threads.join("other.json").exists());
+
+        std::fs::write(dirs.adapter_threads.join("ws.metrics.json"), "new").expect("write");
+        assert_eq!(restore_workspace_in(&dirs, "ws"), 2);
+        assert!(dirs.adapter_threads.join("ws.json").exists());
+        assert!(root.join("logs/ws.log").exists());
+        let metrics = std::fs::read_to_string(dirs.adapter_threads.join("ws.metrics.json"))
+            .expect("read metrics");
+        assert_eq!(metrics, "new");
+        assert!(!dirs.trash.join("ws").exists());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
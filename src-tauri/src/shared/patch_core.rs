@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::git_core;
+
+/// Git extended header lines between `diff --git` and `---`.
+const EXTENDED_HEADERS: &[&str] = &[
+    "index ",
+    "new file mode ",
+    "deleted file mode ",
+    "old mode ",
+    "new mode ",
+    "similarity index ",
+    "dissimilarity index ",
+    "rename from ",
+    "rename to ",
+    "copy from ",
+    "copy to ",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PatchHunk {
+    pub(crate) old_start: u32,
+    pub(crate) old_lines: u32,
+    pub(crate) new_start: u32,
+    pub(crate) new_lines: u32,
+    /// Each line starts with ` `, `+` or `-`.
+    pub(crate) lines: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PatchFile {
+    /// `None` for a file the patch creates.
+    pub(crate) old_path: Option<String>,
+    /// `None` for a file the patch deletes.
+    pub(crate) new_path: Option<String>,
+    pub(crate) additions: u32,
+    pub(crate) deletions: u32,
+    pub(crate) hunks: Vec<PatchHunk>,
+}
+
+impl PatchFile {
+    fn path(&self) -> &str {
+        self.new_path
+            .as_deref()
+            .or(self.old_path.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+/// A unified diff found in agent output.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Patch {
+    pub(crate) files: Vec<PatchFile>,
+    /// The diff as `git apply` reads it, with blank context lines restored.
+    pub(crate) diff: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PatchApplyResult {
+    pub(crate) applied: bool,
+    /// Files the patch touches.
+    pub(crate) files: Vec<String>,
+    /// Files whose hunks don't match the workspace as it is now.
+    pub(crate) conflicts: Vec<String>,
+    /// Why `git apply` refused the patch.
+    pub(crate) error: Option<String>,
+}
+
+fn strip_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or(raw).trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    Some(path.to_string())
+}
+
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    let (start, count) = range.split_once(',').unwrap_or((range, "1"));
+    Some((start.parse().ok()?, count.parse().ok()?))
+}
+
+/// The old and new ranges of a `@@ -1,3 +1,4 @@` line.
+fn parse_hunk_header(line: &str) -> Option<((u32, u32), (u32, u32))> {
+    let (ranges, _) = line.strip_prefix("@@ -")?.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    Some((parse_range(old)?, parse_range(new)?))
+}
+
+/// Parses the diff of one file at `lines[start]`. Returns the file, its
+/// lines as `git apply` reads them and the index after it. Hunks are read
+/// by their line counts, so prose right after a diff isn't taken for it.
+fn parse_file(lines: &[&str], start: usize) -> Option<(PatchFile, Vec<String>, usize)> {
+    let mut index = start;
+    let mut text = Vec::new();
+    if lines[index].starts_with("diff --git ") {
+        text.push(lines[index].to_string());
+        index += 1;
+        while lines.get(index).is_some_and(|line| {
+            EXTENDED_HEADERS
+                .iter()
+                .any(|header| line.starts_with(header))
+        }) {
+            text.push(lines[index].to_string());
+            index += 1;
+        }
+    }
+    let old_path = lines.get(index)?.strip_prefix("--- ")?;
+    let new_path = lines.get(index + 1)?.strip_prefix("+++ ")?;
+    text.push(lines[index].to_string());
+    text.push(lines[index + 1].to_string());
+    index += 2;
+
+    let mut file = PatchFile {
+        old_path: strip_path(old_path),
+        new_path: strip_path(new_path),
+        additions: 0,
+        deletions: 0,
+        hunks: Vec::new(),
+    };
+    while let Some(((old_start, old_lines), (new_start, new_lines))) =
+        lines.get(index).and_then(|line| parse_hunk_header(line))
+    {
+        text.push(lines[index].to_string());
+        index += 1;
+        let (mut old_left, mut new_left) = (old_lines, new_lines);
+        let mut hunk_lines = Vec::new();
+        while old_left > 0 || new_left > 0 {
+            // Blank context lines often lose their leading space in chat
+            // output.
+            let line = match *lines.get(index)? {
+                "" => " ",
+                line => line,
+            };
+            match line.as_bytes()[0] {
+                b' ' if old_left > 0 && new_left > 0 => {
+                    old_left -= 1;
+                    new_left -= 1;
+                }
+                b'-' if old_left > 0 => {
+                    old_left -= 1;
+                    file.deletions += 1;
+                }
+                b'+' if new_left > 0 => {
+                    new_left -= 1;
+                    file.additions += 1;
+                }
+                b'\\' => {
+                    text.push(line.to_string());
+                    index += 1;
+                    continue;
+                }
+                _ => return None,
+            }
+            text.push(line.to_string());
+            hunk_lines.push(line.to_string());
+            index += 1;
+        }
+        if lines.get(index).is_some_and(|line| line.starts_with('\\')) {
+            text.push(lines[index].to_string());
+            index += 1;
+        }
+        file.hunks.push(PatchHunk {
+            old_start,
+            old_lines,
+            new_start,
+            new_lines,
+            lines: hunk_lines,
+        });
+    }
+    if file.hunks.is_empty() {
+        return None;
+    }
+    Some((file, text, index))
+}
+
+/// The unified diffs in `text`, fenced or not. Consecutive file diffs form
+/// one patch.
+pub(crate) fn extract_patches(text: &str) -> Vec<Patch> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut patches = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let mut files = Vec::new();
+        let mut diff = String::new();
+        while let Some((file, file_text, next)) =
+            lines.get(index).and_then(|_| parse_file(&lines, index))
+        {
+            files.push(file);
+            for line in file_text {
+                diff.push_str(&line);
+                diff.push('\n');
+            }
+            index = next;
+        }
+        if files.is_empty() {
+            index += 1;
+        } else {
+            patches.push(Patch { files, diff });
+        }
+    }
+    patches
+}
+
+/// The files named in `git apply` errors such as `error: patch failed:
+/// src/lib.rs:12` or `error: src/lib.rs: patch does not apply`.
+fn conflicting_paths(output: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for line in output.lines() {
+        let Some(rest) = line.trim().strip_prefix("error: ") else {
+            continue;
+        };
+        let path = match rest.strip_prefix("patch failed: ") {
+            Some(location) => location.rsplit_once(':').map_or(location, |(path, _)| path),
+            None => match rest.split_once(": ") {
+                Some((path, _)) => path,
+                None => continue,
+            },
+        };
+        if !paths.iter().any(|known| known == path) {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+/// Applies `patch` to the workspace with `git apply`, after checking that
+/// every hunk still matches. A patch with conflicts changes nothing.
+async fn apply_patch(workspace_path: &PathBuf, patch: &Patch) -> Result<PatchApplyResult, String> {
+    let patch_path =
+        std::env::temp_dir().join(format!("codex-monitor-patch-{}.diff", Uuid::new_v4()));
+    tokio::fs::write(&patch_path, &patch.diff)
+        .await
+        .map_err(|err| format!("Failed to write patch: {err}"))?;
+    let patch_arg = patch_path.to_string_lossy().to_string();
+    let mut outcome = git_core::run_git_command(
+        workspace_path,
+        &["apply", "--check", "--whitespace=nowarn", &patch_arg],
+    )
+    .await;
+    if outcome.is_ok() {
+        outcome = git_core::run_git_command(
+            workspace_path,
+            &["apply", "--whitespace=nowarn", &patch_arg],
+        )
+        .await;
+    }
+    let _ = tokio::fs::remove_file(&patch_path).await;
+
+    let files = patch
+        .files
+        .iter()
+        .map(|file| file.path().to_string())
+        .collect();
+    Ok(match outcome {
+        Ok(_) => PatchApplyResult {
+            applied: true,
+            files,
+            conflicts: Vec::new(),
+            error: None,
+        },
+        Err(error) => PatchApplyResult {
+            applied: false,
+            files,
+            conflicts: conflicting_paths(&error),
+            error: Some(error),
+        },
+    })
+}
+
+/// Applies the patch emitted as `item/patch` item `item_id`. Patches are
+/// kept while the workspace's session runs.
+pub(crate) async fn patch_apply_core(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    audit_log: &AuditLog,
+    workspace_id: String,
+    item_id: String,
+) -> Result<PatchApplyResult, String> {
+    let session = sessions
+        .lock()
+        .await
+        .get(&workspace_id)
+        .cloned()
+        .ok_or("workspace not connected")?;
+    let patch = session
+        .turn_hooks
+        .patch(&item_id)
+        .ok_or_else(|| format!("Patch `{item_id}` not found"))?;
+    let result = apply_patch(&PathBuf::from(&session.entry.path), &patch).await?;
+    if result.applied {
+        audit_log.record(
+            "patch_apply",
+            json!({ "workspaceId": workspace_id, "itemId": item_id, "files": result.files }),
+        );
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{conflicting_paths, extract_patches};
+
+    #[test]
+    fn extracts_fenced_and_bare_diffs_with_their_hunks() {
+        let text = "Here is the fix:\n\n```diff\ndiff --git a/src/lib.rs b/src/lib.rs\nindex 1111111..2222222 100644\n--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1,4 +1,4 @@ fn main()\n fn main() {\n-    println!(\"hi\");\n+    println!(\"hello\");\n\n }\n```\n\nAnd a new file:\n--- /dev/null\n+++ b/NOTES.md\n@@ -0,0 +1,2 @@\n+# Notes\n+done\nThat's all.\n";
+        let patches = extract_patches(text);
+        assert_eq!(patches.len(), 2);
+
+        let edit = &patches[0].files[0];
+        assert_eq!(edit.old_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!(edit.new_path.as_deref(), Some("src/lib.rs"));
+        assert_eq!((edit.additions, edit.deletions), (1, 1));
+        assert_eq!(edit.hunks[0].old_lines, 4);
+        assert_eq!(edit.hunks[0].lines[3], " ");
+        assert!(patches[0]
+            .diff
+            .ends_with("+    println!(\"hello\");\n \n }\n"));
+
+        let created = &patches[1].files[0];
+        assert_eq!(created.old_path, None);
+        assert_eq!(created.new_path.as_deref(), Some("NOTES.md"));
+        assert_eq!(created.additions, 2);
+        assert!(!patches[1].diff.contains("That's all"));
+
+        assert!(extract_patches("--- a/x\n+++ b/x\n@@ -1,2 +1,2 @@\n-a\n").is_empty());
+    }
+
+    #[test]
+    fn reads_conflicting_paths_from_git_apply_errors() {
+        let output = "error: patch failed: src/lib.rs:12\nerror: src/lib.rs: patch does not apply\nerror: NOTES.md: already exists in working directory";
+        assert_eq!(conflicting_paths(output), ["src/lib.rs", "NOTES.md"]);
+    }
+}
@@ -0,0 +1,257 @@
+use std::env;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// Longest the login shell may take to print its PATH.
+const LOGIN_SHELL_TIMEOUT: Duration = Duration::from_secs(5);
+const LOGIN_SHELL_MARKER: &str = "__AGENT_MONITOR_PATH__";
+
+static USE_LOGIN_SHELL_PATH: AtomicBool = AtomicBool::new(false);
+static LOGIN_SHELL_PATH: OnceLock<Vec<PathBuf>> = OnceLock::new();
+
+/// Adds the PATH of the user's login shell to spawned CLIs, for CLIs that
+/// only land on PATH once `.zshrc` (asdf, mise, nvm, ...) has run. The
+/// shell is asked once, in the background, and the answer is cached.
+pub(crate) fn set_login_shell_path_enabled(enabled: bool) {
+    USE_LOGIN_SHELL_PATH.store(enabled, Ordering::Relaxed);
+    if enabled && LOGIN_SHELL_PATH.get().is_none() {
+        std::thread::spawn(|| {
+            login_shell_path();
+        });
+    }
+}
+
+fn login_shell_path() -> &'static [PathBuf] {
+    LOGIN_SHELL_PATH.get_or_init(|| match read_login_shell_path() {
+        Ok(path) => env::split_paths(&path).collect(),
+        Err(error) => {
+            tracing::warn!("failed to read PATH from the login shell: {error}");
+            Vec::new()
+        }
+    })
+}
+
+/// The text between the markers, so whatever the rc files print around it
+/// is ignored.
+fn parse_marked_path(output: &str) -> Option<&str> {
+    let start = output.find(LOGIN_SHELL_MARKER)? + LOGIN_SHELL_MARKER.len();
+    let end = start + output[start..].find(LOGIN_SHELL_MARKER)?;
+    Some(output[start..end].trim()).filter(|path| !path.is_empty())
+}
+
+/// Runs `$SHELL -ilc` so both the login profile and the interactive rc
+/// file are sourced, and returns the PATH it ends up with.
+fn read_login_shell_path() -> Result<String, String> {
+    if cfg!(target_os = "windows") {
+        return Err("not supported on Windows".to_string());
+    }
+    let shell = env::var("SHELL").map_err(|_| "SHELL is not set".to_string())?;
+    let mut child = Command::new(&shell)
+        .arg("-ilc")
+        .arg(format!(
+            "printf '%s' \"{LOGIN_SHELL_MARKER}$PATH{LOGIN_SHELL_MARKER}\""
+        ))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|err| format!("Failed to run {shell}: {err}"))?;
+    let mut stdout = child.stdout.take().ok_or("no stdout")?;
+    let reader = std::thread::spawn(move || {
+        let mut output = String::new();
+        let _ = stdout.read_to_string(&mut output);
+        output
+    });
+    let deadline = Instant::now() + LOGIN_SHELL_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(_)) => break,
+            Ok(None) if Instant::now() < deadline => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!(
+                    "{shell} took longer than {}s",
+                    LOGIN_SHELL_TIMEOUT.as_secs()
+                ));
+            }
+            Err(err) => return Err(err.to_string()),
+        }
+    }
+    let output = reader.join().unwrap_or_default();
+    parse_marked_path(&output)
+        .map(str::to_string)
+        .ok_or_else(|| format!("{shell} printed no PATH"))
+}
+
+/// Directories CLIs are commonly installed into that the PATH of an app
+/// started from the desktop often lacks.
+fn cli_install_dirs() -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        dirs.extend(
+            [
+                "/opt/homebrew/bin",
+                "/usr/local/bin",
+                "/usr/bin",
+                "/bin",
+                "/usr/sbin",
+                "/sbin",
+            ]
+            .into_iter()
+            .map(PathBuf::from),
+        );
+
+        if let Ok(home) = env::var("HOME") {
+            let home_path = Path::new(&home);
+            dirs.push(home_path.join(".local/bin"));
+            dirs.push(home_path.join(".local/share/mise/shims"));
+            dirs.push(home_path.join(".cargo/bin"));
+            dirs.push(home_path.join(".bun/bin"));
+            let nvm_root = home_path.join(".nvm/versions/node");
+            if let Ok(entries) = std::fs::read_dir(nvm_root) {
+                for entry in entries.flatten() {
+                    let bin_path = entry.path().join("bin");
+                    if bin_path.is_dir() {
+                        dirs.push(bin_path);
+                    }
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        if let Ok(appdata) = env::var("APPDATA") {
+            dirs.push(Path::new(&appdata).join("npm"));
+        }
+        if let Ok(local_app_data) = env::var("LOCALAPPDATA") {
+            dirs.push(
+                Path::new(&local_app_data)
+                    .join("Microsoft")
+                    .join("WindowsApps"),
+            );
+        }
+        if let Ok(home) = env::var("USERPROFILE").or_else(|_| env::var("HOME")) {
+            let home_path = Path::new(&home);
+            dirs.push(home_path.join(".cargo").join("bin"));
+            dirs.push(home_path.join("scoop").join("shims"));
+        }
+        if let Ok(program_data) = env::var("PROGRAMDATA") {
+            dirs.push(Path::new(&program_data).join("chocolatey").join("bin"));
+        }
+    }
+
+    dirs
+}
+
+/// The directory of a configured bin given as a path, so the tools next to
+/// it (a bundled `node`, say) are found too. Bare command names have none.
+fn bin_dir(bin: Option<&str>) -> Option<PathBuf> {
+    let bin = bin.map(str::trim).filter(|value| !value.is_empty())?;
+    Path::new(bin)
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(Path::to_path_buf)
+}
+
+/// The entries of the current PATH.
+pub(crate) fn current_path_entries() -> Vec<PathBuf> {
+    env::var_os("PATH")
+        .map(|value| env::split_paths(&value).collect())
+        .unwrap_or_default()
+}
+
+/// Appends `extras` to `paths`, keeping the first occurrence of each entry
+/// and dropping empty ones.
+pub(crate) fn merge_path_entries(
+    paths: Vec<PathBuf>,
+    extras: impl IntoIterator<Item = PathBuf>,
+) -> Vec<PathBuf> {
+    let mut merged: Vec<PathBuf> = Vec::new();
+    for path in paths.into_iter().chain(extras) {
+        if !path.as_os_str().is_empty() && !merged.contains(&path) {
+            merged.push(path);
+        }
+    }
+    merged
+}
+
+fn join_path_entries(paths: Vec<PathBuf>) -> Option<String> {
+    if paths.is_empty() {
+        return None;
+    }
+    env::join_paths(paths)
+        .ok()
+        .map(|joined| joined.to_string_lossy().to_string())
+}
+
+/// PATH for a spawned CLI: the current PATH, then the login shell's when
+/// enabled, then the usual install directories for this platform, then the
+/// directory of `bin` when it is a path. Joined with the platform
+/// separator; `None` when there is nothing to set.
+pub(crate) fn build_cli_path_env(bin: Option<&str>) -> Option<String> {
+    let login_shell = if USE_LOGIN_SHELL_PATH.load(Ordering::Relaxed) {
+        login_shell_path().to_vec()
+    } else {
+        Vec::new()
+    };
+    let extras = login_shell
+        .into_iter()
+        .chain(cli_install_dirs())
+        .chain(bin_dir(bin));
+    join_path_entries(merge_path_entries(current_path_entries(), extras))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+
+    use super::{bin_dir, join_path_entries, merge_path_entries, parse_marked_path};
+
+    #[test]
+    fn merges_extras_once_and_adds_the_bin_dir() {
+        let usr_bin = PathBuf::from("/usr/bin");
+        let local_bin = PathBuf::from("/usr/local/bin");
+        let tools = PathBuf::from("/opt/tools/bin");
+        let merged = merge_path_entries(
+            vec![usr_bin.clone(), PathBuf::new(), usr_bin.clone()],
+            [local_bin.clone(), usr_bin.clone()]
+                .into_iter()
+                .chain(bin_dir(Some("/opt/tools/bin/codex"))),
+        );
+        assert_eq!(merged, [usr_bin, local_bin, tools]);
+
+        assert_eq!(bin_dir(Some("codex")), None);
+        assert_eq!(bin_dir(Some("  ")), None);
+        assert_eq!(join_path_entries(Vec::new()), None);
+
+        let joined = join_path_entries(merged.clone()).expect("joined");
+        let split = env::split_paths(&joined).collect::<Vec<_>>();
+        assert_eq!(split, merged);
+    }
+
+    #[test]
+    fn reads_the_path_between_markers() {
+        let output =
+            "Welcome!\n__AGENT_MONITOR_PATH__/home/me/.asdf/shims:/usr/bin__AGENT_MONITOR_PATH__";
+        assert_eq!(
+            parse_marked_path(output),
+            Some("/home/me/.asdf/shims:/usr/bin")
+        );
+        assert_eq!(parse_marked_path("no markers"), None);
+        assert_eq!(
+            parse_marked_path("__AGENT_MONITOR_PATH____AGENT_MONITOR_PATH__"),
+            None
+        );
+    }
+}
@@ -25,6 +25,23 @@ pub(crate) fn tokio_command(program: impl AsRef<OsStr>) -> Command {
     command
 }
 
+/// Runs `command` through the platform shell (`sh -c`, or `cmd /C` on
+/// Windows).
+pub(crate) fn shell_command(command: &str) -> Command {
+    #[cfg(target_os = "windows")]
+    {
+        let mut shell = tokio_command("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        let mut shell = tokio_command("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
 pub(crate) async fn kill_child_process_tree(child: &mut Child) {
     #[cfg(windows)]
     {
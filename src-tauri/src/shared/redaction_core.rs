@@ -0,0 +1,363 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Arc, RwLock};
+
+use regex::Regex;
+use serde_json::Value;
+
+const REDACTED: &str = "[REDACTED]";
+/// Shorter values (`1`, `dev`, ...) would mangle ordinary output.
+const MIN_SECRET_LEN: usize = 8;
+const SECRET_NAME_HINTS: &[&str] = &["KEY", "TOKEN", "SECRET", "PASSWORD", "PASSWD", "CREDENTIAL"];
+/// How much of a delta stream is held back while `redactionPatterns` are
+/// set. A pattern match has no length bound, so this is a cap rather than a
+/// guarantee, sized for tokens and keys.
+const PATTERN_HOLD_BYTES: usize = 256;
+/// Streaming notifications whose `params.delta` text is redacted as one
+/// stream per item.
+const DELTA_METHODS: &[&str] = &[
+    "item/agentMessage/delta",
+    "item/commandExecution/outputDelta",
+    "item/fileChange/outputDelta",
+    "item/plan/delta",
+    "item/reasoning/summaryTextDelta",
+    "item/reasoning/textDelta",
+];
+
+/// The compiled `redactionPatterns` setting. Clones share the list, so a
+/// settings change reaches redactors that are already running.
+#[derive(Clone, Default)]
+pub(crate) struct RedactionPatterns(Arc<RwLock<Vec<Regex>>>);
+
+impl RedactionPatterns {
+    /// Replaces the patterns. Patterns that don't compile are skipped so one
+    /// bad entry doesn't disable the rest.
+    pub(crate) fn set(&self, patterns: &[String]) {
+        let compiled = patterns
+            .iter()
+            .filter(|pattern| !pattern.trim().is_empty())
+            .filter_map(|pattern| Regex::new(pattern).ok())
+            .collect();
+        if let Ok(mut current) = self.0.write() {
+            *current = compiled;
+        }
+    }
+}
+
+/// Rejects the first pattern that doesn't compile.
+pub(crate) fn validate_redaction_patterns(patterns: &[String]) -> Result<(), String> {
+    for pattern in patterns {
+        Regex::new(pattern)
+            .map_err(|err| format!("Invalid redaction pattern `{pattern}`: {err}"))?;
+    }
+    Ok(())
+}
+
+pub(crate) fn looks_like_secret_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    SECRET_NAME_HINTS.iter().any(|hint| upper.contains(hint))
+}
+
+/// Masks secrets in agent output: values of credential-like variables from
+/// the monitor's environment, the workspace secrets passed in, and the
+/// configured `redactionPatterns`.
+pub(crate) struct SecretRedactor {
+    values: Vec<String>,
+    patterns: RedactionPatterns,
+}
+
+impl SecretRedactor {
+    pub(crate) fn new(
+        workspace_secrets: impl IntoIterator<Item = String>,
+        patterns: RedactionPatterns,
+    ) -> Self {
+        let process_secrets = std::env::vars()
+            .filter(|(name, _)| looks_like_secret_name(name))
+            .map(|(_, value)| value);
+        let mut values: Vec<String> = workspace_secrets
+            .into_iter()
+            .chain(process_secrets)
+            .filter(|value| value.len() >= MIN_SECRET_LEN)
+            .collect();
+        // Longest first so a secret containing another is masked whole.
+        values.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        values.dedup();
+        Self { values, patterns }
+    }
+
+    pub(crate) fn redact<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        let mut output = Cow::Borrowed(text);
+        for value in &self.values {
+            if output.contains(value.as_str()) {
+                output = Cow::Owned(output.replace(value.as_str(), REDACTED));
+            }
+        }
+        let Ok(patterns) = self.patterns.0.read() else {
+            return output;
+        };
+        for pattern in patterns.iter() {
+            let replaced = match pattern.replace_all(&output, REDACTED) {
+                Cow::Owned(replaced) => Some(replaced),
+                Cow::Borrowed(_) => None,
+            };
+            if let Some(replaced) = replaced {
+                output = Cow::Owned(replaced);
+            }
+        }
+        output
+    }
+
+    /// Redacts every string in `value` in place.
+    pub(crate) fn redact_value(&self, value: &mut Value) {
+        match value {
+            Value::String(text) => {
+                if let Cow::Owned(redacted) = self.redact(text) {
+                    *text = redacted;
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.redact_value(item)),
+            Value::Object(map) => map.values_mut().for_each(|item| self.redact_value(item)),
+            _ => {}
+        }
+    }
+
+    /// How many trailing bytes of a stream could still be the start of a
+    /// secret that the next chunk completes.
+    fn hold_len(&self) -> usize {
+        let longest_value = self.values.first().map_or(0, |value| value.len() - 1);
+        let has_patterns = self
+            .patterns
+            .0
+            .read()
+            .is_ok_and(|patterns| !patterns.is_empty());
+        if has_patterns {
+            longest_value.max(PATTERN_HOLD_BYTES)
+        } else {
+            longest_value
+        }
+    }
+
+    fn match_ranges(&self, text: &str) -> Vec<Range<usize>> {
+        let mut ranges = Vec::new();
+        for value in &self.values {
+            ranges.extend(
+                text.match_indices(value.as_str())
+                    .map(|(start, value)| start..start + value.len()),
+            );
+        }
+        if let Ok(patterns) = self.patterns.0.read() {
+            for pattern in patterns.iter() {
+                ranges.extend(pattern.find_iter(text).map(|found| found.range()));
+            }
+        }
+        ranges
+    }
+
+    /// Splits stream text into the part that's safe to send, redacted, and
+    /// the tail to hold until more text arrives. The cut never falls inside
+    /// a match.
+    fn split_stream(&self, text: &str) -> (String, String) {
+        let mut cut = text.len().saturating_sub(self.hold_len());
+        while !text.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        let ranges = self.match_ranges(text);
+        while let Some(range) = ranges
+            .iter()
+            .find(|range| range.start < cut && cut < range.end)
+        {
+            cut = range.start;
+        }
+        (
+            self.redact(&text[..cut]).into_owned(),
+            text[cut..].to_string(),
+        )
+    }
+}
+
+struct HeldDelta {
+    event: Value,
+    text: String,
+}
+
+/// Redacts a session's event stream. A secret can arrive split across two
+/// deltas, so each stream keeps back the tail that could still be the start
+/// of one and sends it ahead of the stream's next delta, or on its own before
+/// the item or turn completes.
+pub(crate) struct DeltaRedactor {
+    redactor: Arc<SecretRedactor>,
+    held: HashMap<String, HeldDelta>,
+}
+
+impl DeltaRedactor {
+    pub(crate) fn new(redactor: Arc<SecretRedactor>) -> Self {
+        Self {
+            redactor,
+            held: HashMap::new(),
+        }
+    }
+
+    /// Redacts `event` in place. Returns held-back deltas that have to be
+    /// emitted before it.
+    pub(crate) fn redact_event(&mut self, event: &mut Value) -> Vec<Value> {
+        let method = event
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        if DELTA_METHODS.contains(&method.as_str()) {
+            if let Some(Value::String(delta)) = event
+                .get_mut("params")
+                .and_then(|params| params.as_object_mut())
+                .and_then(|params| params.remove("delta"))
+            {
+                self.redactor.redact_value(event);
+                let key = format!("{method}\n{}", event["params"]);
+                let held = self.held.remove(&key).map(|held| held.text);
+                let text = held.unwrap_or_default() + &delta;
+                let (ready, rest) = self.redactor.split_stream(&text);
+                event["params"]["delta"] = Value::String(ready);
+                if !rest.is_empty() {
+                    let held = HeldDelta {
+                        event: event.clone(),
+                        text: rest,
+                    };
+                    self.held.insert(key, held);
+                }
+                return Vec::new();
+            }
+        }
+        self.redactor.redact_value(event);
+        match method.as_str() {
+            "item/completed" => {
+                let item_id = event["params"]["itemId"]
+                    .as_str()
+                    .or_else(|| event["params"]["item"]["id"].as_str());
+                match item_id {
+                    Some(item_id) => self.release(|held| held["params"]["itemId"] == item_id),
+                    None => Vec::new(),
+                }
+            }
+            "turn/completed" | "error" => self.flush(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Releases every held-back delta, redacted, for when the stream ends.
+    pub(crate) fn flush(&mut self) -> Vec<Value> {
+        self.release(|_| true)
+    }
+
+    fn release(&mut self, matches: impl Fn(&Value) -> bool) -> Vec<Value> {
+        let keys = self
+            .held
+            .iter()
+            .filter(|(_, held)| matches(&held.event))
+            .map(|(key, _)| key.clone())
+            .collect::<Vec<_>>();
+        keys.into_iter()
+            .filter_map(|key| self.held.remove(&key))
+            .map(|held| {
+                let mut event = held.event;
+                event["params"]["delta"] =
+                    Value::String(self.redactor.redact(&held.text).into_owned());
+                event
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{validate_redaction_patterns, DeltaRedactor, RedactionPatterns, SecretRedactor};
+    use serde_json::{json, Value};
+    use std::sync::Arc;
+
+    #[test]
+    fn masks_secret_values_and_patterns() {
+        let patterns = RedactionPatterns::default();
+        patterns.set(&[r"ghp_[A-Za-z0-9]{36}".to_string(), "(".to_string()]);
+        assert!(validate_redaction_patterns(&["(".to_string()]).is_err());
+
+        let redactor = SecretRedactor::new(
+            ["hunter2-db-password".to_string(), "dev".to_string()],
+            patterns,
+        );
+        let token = format!("ghp_{}", "a".repeat(36));
+        let mut event = json!({
+            "method": "item/agentMessage/delta",
+            "params": {
+                "delta": format!("DB=hunter2-db-password GH={token} profile=dev"),
+                "ids": [1, "hunter2-db-password"]
+            }
+        });
+        redactor.redact_value(&mut event);
+        assert_eq!(
+            event["params"]["delta"],
+            "DB=[REDACTED] GH=[REDACTED] profile=dev"
+        );
+        assert_eq!(event["params"]["ids"], json!([1, "[REDACTED]"]));
+        assert_eq!(event["method"], "item/agentMessage/delta");
+    }
+
+    fn delta(item_id: &str, text: &str) -> Value {
+        json!({
+            "method": "item/agentMessage/delta",
+            "params": { "threadId": "thread-1", "itemId": item_id, "delta": text }
+        })
+    }
+
+    /// Feeds `events` through `redactor` and joins the delta text that
+    /// would reach the client.
+    fn streamed_text(redactor: &mut DeltaRedactor, events: Vec<Value>) -> String {
+        let mut text = String::new();
+        for mut event in events {
+            for held in redactor.redact_event(&mut event) {
+                text.push_str(held["params"]["delta"].as_str().unwrap_or_default());
+            }
+            text.push_str(event["params"]["delta"].as_str().unwrap_or_default());
+        }
+        text
+    }
+
+    #[test]
+    fn masks_secrets_split_across_deltas() {
+        let patterns = RedactionPatterns::default();
+        patterns.set(&[r"ghp_[A-Za-z0-9]{36}".to_string()]);
+        let secret = SecretRedactor::new(["hunter2-db-password".to_string()], patterns);
+        let mut redactor = DeltaRedactor::new(Arc::new(secret));
+        let token = format!("ghp_{}", "b".repeat(36));
+
+        let text = streamed_text(
+            &mut redactor,
+            vec![
+                delta("item-1", "password is hunter2-"),
+                delta("item-2", "other item "),
+                delta("item-1", "db-password and token "),
+                delta("item-1", &token[..10]),
+                delta("item-1", &token[10..]),
+                delta("item-1", " done"),
+                json!({
+                    "method": "item/completed",
+                    "params": { "threadId": "thread-1", "item": { "id": "item-1" } }
+                }),
+            ],
+        );
+        assert_eq!(
+            text, "password is [REDACTED] and token [REDACTED] done",
+            "item-2's stream is still held"
+        );
+        let rest = redactor
+            .flush()
+            .into_iter()
+            .map(|event| {
+                event["params"]["delta"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string()
+            })
+            .collect::<String>();
+        assert_eq!(rest, "other item ");
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::backend::adapter_base::AdapterSettings;
+use crate::backend::rate_limits::RateLimits;
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::delta_batch_core::DeltaBatcher;
+use crate::shared::event_hooks_core::EventHooks;
+use crate::shared::event_replay_core::EventReplayBuffer;
+use crate::shared::redaction_core::RedactionPatterns;
+use crate::shared::thread_retention_core::RetentionSettings;
+use crate::shared::turn_limit_core::TurnLimiter;
+use crate::types::AppSettings;
+
+/// State shared by the sessions, event sinks and commands of the app or the
+/// daemon. `AppState` and `DaemonState` each own one and sessions get a
+/// clone through `CliSpawnConfig`; clones share everything, so a settings
+/// change reaches running sessions.
+#[derive(Clone, Default)]
+pub(crate) struct RuntimeServices {
+    pub(crate) event_replay: Arc<EventReplayBuffer>,
+    pub(crate) delta_batcher: Arc<DeltaBatcher>,
+    pub(crate) event_hooks: EventHooks,
+    pub(crate) turn_limiter: Arc<TurnLimiter>,
+    pub(crate) redaction_patterns: RedactionPatterns,
+    pub(crate) retention: RetentionSettings,
+    pub(crate) rate_limits: RateLimits,
+    pub(crate) adapter_settings: AdapterSettings,
+    pub(crate) audit_log: AuditLog,
+}
+
+impl RuntimeServices {
+    /// Applies the settings these services read, at startup and after every
+    /// settings change.
+    pub(crate) fn apply_settings(&self, settings: &AppSettings) {
+        self.event_replay
+            .set_capacity(settings.event_replay_buffer_size);
+        self.delta_batcher
+            .set_window_ms(settings.delta_batch_window_ms);
+        self.event_hooks.set(&settings.event_hooks);
+        self.turn_limiter.set_limit(settings.max_concurrent_turns);
+        self.redaction_patterns.set(&settings.redaction_patterns);
+        self.retention.set(
+            settings.thread_auto_archive_days,
+            settings.thread_purge_archived_days,
+        );
+        self.rate_limits
+            .set_max_retries(settings.adapter_rate_limit_retries);
+        self.adapter_settings
+            .set_turn_timeout_secs(settings.adapter_turn_timeout_secs);
+        self.adapter_settings
+            .set_show_thinking(settings.claude_show_thinking);
+    }
+}
+
+impl fmt::Debug for RuntimeServices {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RuntimeServices").finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RuntimeServices;
+    use crate::types::AppSettings;
+
+    #[test]
+    fn settings_reach_existing_clones_only_of_the_same_services() {
+        let services = RuntimeServices::default();
+        let session = services.clone();
+        let other = RuntimeServices::default();
+        let settings = AppSettings {
+            adapter_rate_limit_retries: 3,
+            claude_show_thinking: true,
+            ..AppSettings::default()
+        };
+
+        services.apply_settings(&settings);
+
+        assert_eq!(session.rate_limits.max_retries(), 3);
+        assert!(session.adapter_settings.show_thinking());
+        assert_eq!(other.rate_limits.max_retries(), 0);
+        assert!(!other.adapter_settings.show_thinking());
+    }
+}
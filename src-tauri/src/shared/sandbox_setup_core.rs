@@ -2,6 +2,8 @@ use serde_json::{json, Map, Value};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+use crate::shared::atomic_write::write_atomic;
+
 const GONDOLIN_MCP_SERVER: &str = "gondolin";
 
 fn gondolin_command_spec() -> (String, Vec<String>) {
@@ -123,11 +125,11 @@ fn ensure_gemini_mcp_server(gemini_home: Option<PathBuf>) -> Result<(), String>
     upsert_gemini_mcp_config(&mut value);
     let serialized = serde_json::to_string_pretty(&value)
         .map_err(|err| format!("Failed to serialize Gemini settings: {err}"))?;
-    std::fs::write(&settings_path, format!("{serialized}\n"))
+    write_atomic(&settings_path, format!("{serialized}\n").as_bytes())
         .map_err(|err| format!("Failed to write {}: {err}", settings_path.display()))
 }
 
-fn resolve_default_gemini_home_fallback() -> Option<PathBuf> {
+pub(crate) fn resolve_default_gemini_home_fallback() -> Option<PathBuf> {
     if let Ok(value) = std::env::var("GEMINI_HOME") {
         let trimmed = value.trim();
         if !trimmed.is_empty() {
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::types::AppSettings;
+
+const RESTART_INITIAL_DELAY: Duration = Duration::from_secs(1);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(60);
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+
+fn restart_delay(attempt: u32) -> Duration {
+    RESTART_INITIAL_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(RESTART_MAX_DELAY)
+}
+
+async fn is_current_session(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    workspace_id: &str,
+    session: &Arc<WorkspaceSession>,
+) -> bool {
+    sessions
+        .lock()
+        .await
+        .get(workspace_id)
+        .is_some_and(|current| Arc::ptr_eq(current, session))
+}
+
+/// Watches a workspace's app-server and, when it exits without being
+/// stopped, respawns it with exponential backoff (`autoRestartSessions`).
+/// `respawn` must return an unsupervised session; this loop keeps watching
+/// the replacement. Gives up if the workspace was reconnected or removed in
+/// the meantime.
+pub(crate) async fn supervise_session<E, F, Fut>(
+    workspace_id: String,
+    mut session: Arc<WorkspaceSession>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    event_sink: E,
+    respawn: F,
+) where
+    E: EventSink,
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    loop {
+        let Some(exit) = session.wait_for_exit().await else {
+            return;
+        };
+        if exit.expected || !app_settings.lock().await.auto_restart_sessions {
+            return;
+        }
+
+        let mut replacement = None;
+        for attempt in 1..=MAX_RESTART_ATTEMPTS {
+            let delay = restart_delay(attempt);
+            event_sink.emit_app_server_event(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::Restarting {
+                    workspace_id: workspace_id.clone(),
+                    attempt,
+                    delay_ms: delay.as_millis() as u64,
+                },
+            ));
+            tokio::time::sleep(delay).await;
+            if !is_current_session(sessions, &workspace_id, &session).await {
+                return;
+            }
+            match respawn().await {
+                Ok(next) => {
+                    replacement = Some(next);
+                    break;
+                }
+                Err(error) => {
+                    tracing::warn!(
+                        workspace_id = %workspace_id,
+                        "restart attempt {attempt} failed: {error}"
+                    );
+                }
+            }
+        }
+
+        let Some(next) = replacement else {
+            event_sink.emit_app_server_event(AppServerEvent::new(
+                workspace_id.clone(),
+                AppEvent::RestartFailed {
+                    workspace_id: workspace_id.clone(),
+                    attempts: MAX_RESTART_ATTEMPTS,
+                },
+            ));
+            return;
+        };
+
+        {
+            let mut sessions = sessions.lock().await;
+            let still_current = sessions
+                .get(&workspace_id)
+                .is_some_and(|current| Arc::ptr_eq(current, &session));
+            if !still_current {
+                drop(sessions);
+                next.kill().await;
+                return;
+            }
+            sessions.insert(workspace_id.clone(), Arc::clone(&next));
+        }
+        session = next;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{restart_delay, RESTART_MAX_DELAY};
+    use std::time::Duration;
+
+    #[test]
+    fn restart_delay_backs_off_exponentially() {
+        assert_eq!(restart_delay(1), Duration::from_secs(1));
+        assert_eq!(restart_delay(2), Duration::from_secs(2));
+        assert_eq!(restart_delay(4), Duration::from_secs(8));
+        assert_eq!(restart_delay(20), RESTART_MAX_DELAY);
+    }
+}
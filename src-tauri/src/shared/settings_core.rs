@@ -1,10 +1,14 @@
+use serde_json::{json, Value};
 use std::path::PathBuf;
 
 use tokio::sync::Mutex;
 
 use crate::codex::config as codex_config;
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::runtime_services::RuntimeServices;
+use crate::shared::{event_hooks_core, logging_core, path_env, redaction_core};
 use crate::storage::write_settings;
-use crate::types::AppSettings;
+use crate::types::{AppSettings, CodexProfile, ConfigDiagnostic};
 
 fn normalize_personality(value: &str) -> Option<&'static str> {
     match value.trim() {
@@ -46,7 +50,18 @@ pub(crate) async fn update_app_settings_core(
     settings: AppSettings,
     app_settings: &Mutex<AppSettings>,
     settings_path: &PathBuf,
+    services: &RuntimeServices,
 ) -> Result<AppSettings, String> {
+    redaction_core::validate_redaction_patterns(&settings.redaction_patterns)?;
+    event_hooks_core::validate_event_hooks(&settings.event_hooks)?;
+    if settings.http_api_enabled
+        && settings
+            .http_api_token
+            .as_deref()
+            .is_none_or(|token| token.trim().is_empty())
+    {
+        return Err("The HTTP API needs a token (`httpApiToken`).".to_string());
+    }
     let _ = codex_config::write_collab_enabled(settings.experimental_collab_enabled);
     let _ = codex_config::write_collaboration_modes_enabled(
         settings.collaboration_modes_enabled,
@@ -56,11 +71,32 @@ pub(crate) async fn update_app_settings_core(
     let _ = codex_config::write_apps_enabled(settings.experimental_apps_enabled);
     let _ = codex_config::write_personality(settings.personality.as_str());
     write_settings(settings_path, &settings)?;
+    services.apply_settings(&settings);
+    logging_core::set_rpc_inspector_enabled(settings.debug_rpc_inspector);
+    path_env::set_login_shell_path_enabled(settings.resolve_login_shell_path);
     let mut current = app_settings.lock().await;
-    *current = settings.clone();
+    let previous = std::mem::replace(&mut *current, settings.clone());
+    services.audit_log.record(
+        "update_app_settings",
+        json!({ "changedKeys": changed_settings_keys(&previous, &settings) }),
+    );
     Ok(settings)
 }
 
+/// The settings keys whose values differ; values are left out of the audit
+/// log since some are credentials.
+fn changed_settings_keys(previous: &AppSettings, next: &AppSettings) -> Vec<String> {
+    let (Ok(Value::Object(previous)), Ok(Value::Object(next))) =
+        (serde_json::to_value(previous), serde_json::to_value(next))
+    else {
+        return Vec::new();
+    };
+    next.iter()
+        .filter(|(key, value)| previous.get(*key) != Some(*value))
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
 pub(crate) fn get_codex_config_path_core() -> Result<String, String> {
     codex_config::config_toml_path()
         .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
@@ -70,3 +106,29 @@ pub(crate) fn get_codex_config_path_core() -> Result<String, String> {
                 .ok_or_else(|| "Unable to resolve CODEX_HOME".to_string())
         })
 }
+
+/// Diagnostics for the default `config.toml`; empty when the file is missing.
+pub(crate) fn codex_config_validate_core() -> Result<Vec<ConfigDiagnostic>, String> {
+    Ok(codex_config::read_config_toml()?
+        .map(|contents| codex_config::validate_config_contents(&contents))
+        .unwrap_or_default())
+}
+
+pub(crate) fn codex_profiles_list_core() -> Result<Vec<CodexProfile>, String> {
+    codex_config::read_profiles()
+}
+
+/// Makes `name` the active profile (`profile = "name"`); `None` clears it.
+pub(crate) fn codex_profile_apply_core(
+    name: Option<&str>,
+    audit_log: &AuditLog,
+) -> Result<Vec<CodexProfile>, String> {
+    codex_config::write_active_profile(name)?;
+    audit_log.record("codex_profile_apply", json!({ "profile": name }));
+    codex_config::read_profiles()
+}
+
+pub(crate) fn codex_profile_save_current_core(name: &str) -> Result<Vec<CodexProfile>, String> {
+    codex_config::save_current_profile(name)?;
+    codex_config::read_profiles()
+}
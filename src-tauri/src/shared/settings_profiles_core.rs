@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use tokio::sync::Mutex;
+
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::codex::config as codex_config;
+use crate::shared::app_error::{AppError, AppResult};
+use crate::shared::atomic_write::write_atomic;
+use crate::shared::redaction_core;
+use crate::shared::runtime_services::RuntimeServices;
+use crate::shared::settings_core::update_app_settings_core;
+use crate::types::AppSettings;
+
+const PROFILES_DIR: &str = "settings-profiles";
+
+/// A named snapshot of the app settings and the codex `config.toml` keys
+/// that pick a model and provider, to switch between as a whole.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SettingsProfile {
+    settings: AppSettings,
+    /// Top-level `model`, `model_provider`, `model_reasoning_effort`,
+    /// `approval_policy`, `sandbox_mode` and `profile` values.
+    #[serde(default)]
+    codex_overlay: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SettingsProfileInfo {
+    pub(crate) name: String,
+    pub(crate) codex_overlay: BTreeMap<String, String>,
+    pub(crate) active: bool,
+}
+
+/// Profiles are kept next to `settings.json`.
+fn profiles_dir(settings_path: &Path) -> PathBuf {
+    settings_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(PROFILES_DIR)
+}
+
+fn profile_path(dir: &Path, name: &str) -> AppResult<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+    if !valid {
+        return Err(AppError::InvalidInput(format!(
+            "Invalid profile name \"{name}\"; use letters, digits, - and _"
+        )));
+    }
+    Ok(dir.join(format!("{name}.json")))
+}
+
+fn missing_profile(name: &str) -> AppError {
+    AppError::InvalidInput(format!("No settings profile named \"{name}\""))
+}
+
+fn read_profile(dir: &Path, name: &str) -> AppResult<SettingsProfile> {
+    let path = profile_path(dir, name)?;
+    let data = std::fs::read_to_string(&path).map_err(|_| missing_profile(name))?;
+    serde_json::from_str(&data)
+        .map_err(|err| AppError::Internal(format!("Invalid settings profile {name}: {err}")))
+}
+
+fn list_profiles_in(dir: &Path, active: Option<&str>) -> Vec<SettingsProfileInfo> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut profiles = entries
+        .flatten()
+        .filter_map(|entry| {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = file_name.strip_suffix(".json")?;
+            let profile = read_profile(dir, name).ok()?;
+            Some(SettingsProfileInfo {
+                name: name.to_string(),
+                codex_overlay: profile.codex_overlay,
+                active: active == Some(name),
+            })
+        })
+        .collect::<Vec<_>>();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    profiles
+}
+
+pub(crate) async fn settings_profiles_list_core(
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &Path,
+) -> Vec<SettingsProfileInfo> {
+    let active = app_settings.lock().await.settings_profile.clone();
+    list_profiles_in(&profiles_dir(settings_path), active.as_deref())
+}
+
+/// Saves the current settings and codex overlay as `name`, replacing a
+/// profile of that name.
+pub(crate) async fn settings_profile_save_core(
+    name: &str,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &Path,
+) -> AppResult<Vec<SettingsProfileInfo>> {
+    let dir = profiles_dir(settings_path);
+    let path = profile_path(&dir, name.trim())?;
+    let mut settings = app_settings.lock().await.clone();
+    settings.settings_profile = None;
+    let profile = SettingsProfile {
+        settings,
+        codex_overlay: codex_config::read_settings_overlay()?,
+    };
+    let data = serde_json::to_string_pretty(&profile).map_err(|err| err.to_string())?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|err| format!("Failed to create {}: {err}", dir.display()))?;
+    write_atomic(&path, data.as_bytes())
+        .map_err(|err| format!("Failed to save settings profile: {err}"))?;
+    Ok(settings_profiles_list_core(app_settings, settings_path).await)
+}
+
+pub(crate) async fn settings_profile_delete_core(
+    name: &str,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &Path,
+) -> AppResult<Vec<SettingsProfileInfo>> {
+    let path = profile_path(&profiles_dir(settings_path), name)?;
+    std::fs::remove_file(&path).map_err(|_| missing_profile(name))?;
+    Ok(settings_profiles_list_core(app_settings, settings_path).await)
+}
+
+/// Applies profile `name`: its codex overlay, then its settings. When the
+/// settings can't be saved the previous overlay is written back, so a
+/// failed switch leaves both as they were. Emits `settings/changed`.
+pub(crate) async fn settings_profile_switch_core<E: EventSink>(
+    name: &str,
+    app_settings: &Mutex<AppSettings>,
+    settings_path: &PathBuf,
+    services: &RuntimeServices,
+    event_sink: &E,
+) -> AppResult<AppSettings> {
+    let profile = read_profile(&profiles_dir(settings_path), name)?;
+    let mut settings = profile.settings;
+    redaction_core::validate_redaction_patterns(&settings.redaction_patterns)
+        .map_err(AppError::InvalidInput)?;
+    settings.settings_profile = Some(name.to_string());
+    // The token is a credential of this machine, not of the profile.
+    settings.remote_backend_token = app_settings.lock().await.remote_backend_token.clone();
+
+    let previous_overlay = codex_config::read_settings_overlay()?;
+    codex_config::write_settings_overlay(&profile.codex_overlay)?;
+    let updated =
+        match update_app_settings_core(settings, app_settings, settings_path, services).await {
+            Ok(updated) => updated,
+            Err(err) => {
+                let _ = codex_config::write_settings_overlay(&previous_overlay);
+                return Err(err.into());
+            }
+        };
+    services
+        .audit_log
+        .record("settings_profile_switch", json!({ "profile": name }));
+    event_sink.emit_app_server_event(AppServerEvent::new(
+        String::new(),
+        AppEvent::SettingsChanged {
+            profile: Some(name.to_string()),
+            settings: serde_json::to_value(&updated).unwrap_or_default(),
+        },
+    ));
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{list_profiles_in, profile_path, SettingsProfile};
+    use crate::types::AppSettings;
+    use uuid::Uuid;
+
+    #[test]
+    fn lists_saved_profiles_and_marks_the_active_one() {
+        let dir = std::env::temp_dir().join(format!("settings-profiles-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        for name in ["work", "personal"] {
+            let profile = SettingsProfile {
+                settings: AppSettings::default(),
+                codex_overlay: [("model".to_string(), format!("{name}-model"))].into(),
+            };
+            let data = serde_json::to_string(&profile).expect("serialize");
+            std::fs::write(profile_path(&dir, name).expect("path"), data).expect("write");
+        }
+        std::fs::write(dir.join("notes.txt"), "").expect("write stray file");
+
+        let profiles = list_profiles_in(&dir, Some("work"));
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].name, "personal");
+        assert!(!profiles[0].active);
+        assert!(profiles[1].active);
+        assert_eq!(profiles[1].codex_overlay["model"], "work-model");
+
+        assert!(profile_path(&dir, "../settings").is_err());
+        assert!(profile_path(&dir, "").is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
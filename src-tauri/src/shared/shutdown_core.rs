@@ -0,0 +1,194 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::types::{AppSettings, ShutdownTurnPolicy};
+
+/// How long interrupted turns get to report `turn/completed`.
+const INTERRUPT_GRACE: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+const INTERRUPT_REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DrainPhase {
+    Draining,
+    Interrupting,
+}
+
+impl DrainPhase {
+    fn as_str(self) -> &'static str {
+        match self {
+            DrainPhase::Draining => "draining",
+            DrainPhase::Interrupting => "interrupting",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DrainStep {
+    /// No turns left, or the interrupted ones ran out of time.
+    Done,
+    /// Waiting ran out; interrupt what is left.
+    Interrupt,
+    Wait {
+        remaining_secs: u64,
+    },
+}
+
+/// Decides, from the running turn count at each poll, when to stop waiting
+/// and when to interrupt.
+struct Drain {
+    phase: DrainPhase,
+    deadline: Instant,
+}
+
+impl Drain {
+    fn new(policy: ShutdownTurnPolicy, timeout: Duration, now: Instant) -> Self {
+        match policy {
+            ShutdownTurnPolicy::Wait => Self {
+                phase: DrainPhase::Draining,
+                deadline: now + timeout,
+            },
+            ShutdownTurnPolicy::Interrupt => Self {
+                phase: DrainPhase::Interrupting,
+                deadline: now + INTERRUPT_GRACE,
+            },
+        }
+    }
+
+    fn step(&mut self, running_turns: usize, now: Instant) -> DrainStep {
+        if running_turns == 0 {
+            return DrainStep::Done;
+        }
+        if now < self.deadline {
+            return DrainStep::Wait {
+                remaining_secs: (self.deadline - now).as_secs(),
+            };
+        }
+        if self.phase == DrainPhase::Interrupting {
+            return DrainStep::Done;
+        }
+        self.phase = DrainPhase::Interrupting;
+        self.deadline = now + INTERRUPT_GRACE;
+        DrainStep::Interrupt
+    }
+}
+
+fn running_turn_count(sessions: &[Arc<WorkspaceSession>]) -> usize {
+    sessions
+        .iter()
+        .map(|session| session.turn_hooks.running_turns().len())
+        .sum()
+}
+
+async fn interrupt_turns(sessions: &[Arc<WorkspaceSession>]) {
+    let mut requests = Vec::new();
+    for session in sessions {
+        for (thread_id, turn_id) in session.turn_hooks.running_turns() {
+            let session = Arc::clone(session);
+            let params = json!({ "threadId": thread_id, "turnId": turn_id });
+            requests.push(tokio::spawn(async move {
+                let _ = session
+                    .send_request_with_timeout("turn/interrupt", params, INTERRUPT_REQUEST_TIMEOUT)
+                    .await;
+            }));
+        }
+    }
+    for request in requests {
+        let _ = request.await;
+    }
+}
+
+fn emit_progress<E: EventSink>(
+    event_sink: &E,
+    phase: &str,
+    running_turns: usize,
+    remaining_secs: u64,
+) {
+    event_sink.emit_app_server_event(AppServerEvent::new(
+        String::new(),
+        AppEvent::ShutdownProgress {
+            phase: phase.to_string(),
+            running_turns,
+            remaining_secs,
+        },
+    ));
+}
+
+/// Stops every session before the process exits. Running turns are waited
+/// for or interrupted according to `shutdownTurnPolicy`, emitting
+/// `shutdown/progress` as the count changes; then each session's thread
+/// store is written and its CLI processes are killed.
+pub(crate) async fn shutdown_sessions<E: EventSink>(
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &AppSettings,
+    event_sink: &E,
+) {
+    let sessions = sessions.lock().await.values().cloned().collect::<Vec<_>>();
+    let timeout = Duration::from_secs(app_settings.shutdown_timeout_secs);
+    let mut drain = Drain::new(app_settings.shutdown_turn_policy, timeout, Instant::now());
+    if drain.phase == DrainPhase::Interrupting {
+        interrupt_turns(&sessions).await;
+    }
+    let mut reported = None;
+    loop {
+        let running = running_turn_count(&sessions);
+        match drain.step(running, Instant::now()) {
+            DrainStep::Done => break,
+            DrainStep::Interrupt => interrupt_turns(&sessions).await,
+            DrainStep::Wait { remaining_secs } => {
+                let progress = (drain.phase, running, remaining_secs);
+                if reported != Some(progress) {
+                    emit_progress(event_sink, drain.phase.as_str(), running, remaining_secs);
+                    reported = Some(progress);
+                }
+                tokio::time::sleep(POLL_INTERVAL).await;
+            }
+        }
+    }
+
+    emit_progress(event_sink, "stopping", running_turn_count(&sessions), 0);
+    for session in &sessions {
+        if let Err(err) = session.flush().await {
+            tracing::warn!(workspace_id = %session.entry.id, "shutdown: {err}");
+        }
+        session.kill().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Drain, DrainPhase, DrainStep, INTERRUPT_GRACE};
+    use crate::types::ShutdownTurnPolicy;
+    use std::time::Duration;
+    use tokio::time::Instant;
+
+    #[test]
+    fn waits_then_interrupts_then_gives_up() {
+        let start = Instant::now();
+        let mut drain = Drain::new(ShutdownTurnPolicy::Wait, Duration::from_secs(30), start);
+        assert_eq!(
+            drain.step(2, start + Duration::from_secs(10)),
+            DrainStep::Wait { remaining_secs: 20 }
+        );
+        let timed_out = start + Duration::from_secs(30);
+        assert_eq!(drain.step(1, timed_out), DrainStep::Interrupt);
+        assert_eq!(drain.phase, DrainPhase::Interrupting);
+        assert!(matches!(drain.step(1, timed_out), DrainStep::Wait { .. }));
+        assert_eq!(drain.step(1, timed_out + INTERRUPT_GRACE), DrainStep::Done);
+
+        let mut drain = Drain::new(
+            ShutdownTurnPolicy::Interrupt,
+            Duration::from_secs(30),
+            start,
+        );
+        assert_eq!(drain.phase, DrainPhase::Interrupting);
+        assert_eq!(drain.step(0, start), DrainStep::Done);
+    }
+}
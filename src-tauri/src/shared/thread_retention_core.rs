@@ -0,0 +1,177 @@
+use std::sync::{Arc, Mutex};
+
+use crate::backend::adapter_base::{
+    now_epoch, open_thread_store, purge_thread_files, thread_store_path, ThreadMetadata,
+    ThreadStore,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// `threadAutoArchiveDays` / `threadPurgeArchivedDays`; 0 turns a step off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct RetentionPolicy {
+    pub(crate) auto_archive_days: u32,
+    pub(crate) purge_archived_days: u32,
+}
+
+impl RetentionPolicy {
+    fn is_enabled(&self) -> bool {
+        self.auto_archive_days > 0 || self.purge_archived_days > 0
+    }
+}
+
+/// The retention settings. Clones share them, so a settings change reaches
+/// the maintenance job.
+#[derive(Clone, Default)]
+pub(crate) struct RetentionSettings(Arc<Mutex<RetentionPolicy>>);
+
+impl RetentionSettings {
+    pub(crate) fn set(&self, auto_archive_days: u32, purge_archived_days: u32) {
+        if let Ok(mut policy) = self.0.lock() {
+            *policy = RetentionPolicy {
+                auto_archive_days,
+                purge_archived_days,
+            };
+        }
+    }
+
+    pub(crate) fn policy(&self) -> RetentionPolicy {
+        self.0.lock().map(|policy| *policy).unwrap_or_default()
+    }
+}
+
+fn cutoff(now: u64, days: u32) -> Option<u64> {
+    (days > 0).then(|| now.saturating_sub(u64::from(days) * SECONDS_PER_DAY))
+}
+
+/// Archives threads idle for longer than the archive window and removes
+/// threads archived for longer than the purge window. Archiving bumps
+/// `updated_at`, so the purge window counts from the archive time. Returns
+/// how many threads were archived and the removed threads.
+fn apply_retention(
+    store: &mut ThreadStore,
+    policy: RetentionPolicy,
+    now: u64,
+) -> (usize, Vec<(String, ThreadMetadata)>) {
+    let purge_before = cutoff(now, policy.purge_archived_days);
+    let purged_ids: Vec<String> = store
+        .threads
+        .iter()
+        .filter(|(_, meta)| {
+            meta.archived && purge_before.is_some_and(|before| meta.updated_at < before)
+        })
+        .map(|(id, _)| id.clone())
+        .collect();
+    let purged = purged_ids
+        .into_iter()
+        .filter_map(|id| store.threads.remove(&id).map(|meta| (id, meta)))
+        .collect();
+
+    let mut archived = 0;
+    if let Some(before) = cutoff(now, policy.auto_archive_days) {
+        for meta in store.threads.values_mut() {
+            if !meta.archived && meta.updated_at < before {
+                meta.archived = true;
+                meta.updated_at = now;
+                archived += 1;
+            }
+        }
+    }
+    (archived, purged)
+}
+
+/// Applies the retention policy to the thread stores of CLI adapter
+/// workspaces. Codex threads live in the Codex app-server and are left alone.
+pub(crate) async fn run_thread_retention(
+    workspace_ids: Vec<String>,
+    policy: RetentionPolicy,
+) -> Result<String, String> {
+    if !policy.is_enabled() {
+        return Ok("Thread retention is off".to_string());
+    }
+    let now = now_epoch();
+    let (mut archived_total, mut purged_total) = (0, 0);
+    for workspace_id in workspace_ids {
+        let path = thread_store_path(&workspace_id);
+        if !path.is_file() {
+            continue;
+        }
+        let store = open_thread_store(&path);
+        let purged = {
+            let mut store = store.lock().await;
+            let (archived, purged) = apply_retention(&mut store, policy, now);
+            if archived == 0 && purged.is_empty() {
+                continue;
+            }
+            store.save(&path)?;
+            archived_total += archived;
+            purged
+        };
+        purged_total += purged.len();
+        tokio::task::spawn_blocking(move || {
+            for (thread_id, meta) in &purged {
+                purge_thread_files(&workspace_id, thread_id, meta);
+            }
+        })
+        .await
+        .map_err(|err| err.to_string())?;
+    }
+    Ok(format!(
+        "Archived {archived_total} and deleted {purged_total} thread(s)"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_retention, RetentionPolicy, SECONDS_PER_DAY};
+    use crate::backend::adapter_base::{ThreadMetadata, ThreadStore};
+
+    fn thread(updated_at: u64, archived: bool) -> ThreadMetadata {
+        ThreadMetadata {
+            cli_session_id: None,
+            name: None,
+            created_at: 0,
+            updated_at,
+            archived,
+            source_path: None,
+            transcript_path: None,
+            compacted_summary: None,
+            last_turn_failure: None,
+        }
+    }
+
+    #[test]
+    fn archives_idle_threads_and_purges_old_archives() {
+        let now = 100 * SECONDS_PER_DAY;
+        let mut store = ThreadStore::default();
+        store
+            .threads
+            .insert("recent".into(), thread(now - 2 * SECONDS_PER_DAY, false));
+        store
+            .threads
+            .insert("idle".into(), thread(now - 40 * SECONDS_PER_DAY, false));
+        store
+            .threads
+            .insert("archived".into(), thread(now - 5 * SECONDS_PER_DAY, true));
+        store
+            .threads
+            .insert("expired".into(), thread(now - 90 * SECONDS_PER_DAY, true));
+        let policy = RetentionPolicy {
+            auto_archive_days: 30,
+            purge_archived_days: 60,
+        };
+
+        let (archived, purged) = apply_retention(&mut store, policy, now);
+
+        assert_eq!(archived, 1);
+        assert_eq!(purged.len(), 1);
+        assert_eq!(purged[0].0, "expired");
+        assert!(store.threads["idle"].archived);
+        assert_eq!(store.threads["idle"].updated_at, now);
+        assert!(!store.threads["recent"].archived);
+        assert!(store.threads.contains_key("archived"));
+
+        let (archived, purged) = apply_retention(&mut store, RetentionPolicy::default(), now);
+        assert_eq!((archived, purged.len()), (0, 0));
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::Notify;
+
+#[derive(Default)]
+struct LimiterState {
+    limit: usize,
+    active: usize,
+    next_ticket: u64,
+    queue: VecDeque<u64>,
+}
+
+impl LimiterState {
+    fn has_capacity(&self) -> bool {
+        self.limit == 0 || self.active < self.limit
+    }
+}
+
+/// Caps how many foreground turns run at once across all workspaces
+/// (`maxConcurrentTurns`, 0 means unlimited). Queued turns start in the
+/// order they were requested.
+pub(crate) struct TurnLimiter {
+    state: Mutex<LimiterState>,
+    notify: Notify,
+}
+
+/// A running turn's slot; released when dropped.
+pub(crate) struct TurnPermit {
+    limiter: Arc<TurnLimiter>,
+}
+
+impl Drop for TurnPermit {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.limiter.state.lock() {
+            state.active = state.active.saturating_sub(1);
+        }
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+/// A turn waiting for a slot. Dropping it leaves the queue.
+pub(crate) struct QueuedTurn {
+    limiter: Arc<TurnLimiter>,
+    ticket: Option<u64>,
+    pub(crate) position: usize,
+}
+
+impl QueuedTurn {
+    pub(crate) async fn wait(mut self) -> TurnPermit {
+        let limiter = Arc::clone(&self.limiter);
+        loop {
+            let notified = limiter.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(ticket) = self.ticket {
+                if limiter.admit(ticket) {
+                    self.ticket = None;
+                    return TurnPermit {
+                        limiter: Arc::clone(&limiter),
+                    };
+                }
+            }
+            notified.await;
+        }
+    }
+}
+
+impl Drop for QueuedTurn {
+    fn drop(&mut self) {
+        let Some(ticket) = self.ticket else {
+            return;
+        };
+        if let Ok(mut state) = self.limiter.state.lock() {
+            state.queue.retain(|queued| *queued != ticket);
+        }
+        self.limiter.notify.notify_waiters();
+    }
+}
+
+impl TurnLimiter {
+    pub(crate) fn new(limit: usize) -> Self {
+        Self {
+            state: Mutex::new(LimiterState {
+                limit,
+                ..LimiterState::default()
+            }),
+            notify: Notify::new(),
+        }
+    }
+
+    pub(crate) fn set_limit(&self, limit: usize) {
+        if let Ok(mut state) = self.state.lock() {
+            state.limit = limit;
+        }
+        self.notify.notify_waiters();
+    }
+
+    /// Takes a slot right away when one is free and no turn is queued ahead;
+    /// otherwise joins the back of the queue.
+    pub(crate) fn try_acquire(self: &Arc<Self>) -> Result<TurnPermit, QueuedTurn> {
+        let mut state = match self.state.lock() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if state.queue.is_empty() && state.has_capacity() {
+            state.active += 1;
+            return Ok(TurnPermit {
+                limiter: Arc::clone(self),
+            });
+        }
+        let ticket = state.next_ticket;
+        state.next_ticket += 1;
+        state.queue.push_back(ticket);
+        Err(QueuedTurn {
+            limiter: Arc::clone(self),
+            ticket: Some(ticket),
+            position: state.queue.len(),
+        })
+    }
+
+    fn admit(&self, ticket: u64) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return false;
+        };
+        if state.queue.front() != Some(&ticket) || !state.has_capacity() {
+            return false;
+        }
+        state.queue.pop_front();
+        state.active += 1;
+        drop(state);
+        // The next queued turn may fit as well (e.g. after the limit grew).
+        self.notify.notify_waiters();
+        true
+    }
+}
+
+impl Default for TurnLimiter {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TurnLimiter;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn queued_turns_start_in_order_as_slots_free() {
+        let limiter = Arc::new(TurnLimiter::new(1));
+        let Ok(first) = limiter.try_acquire() else {
+            panic!("first turn should run");
+        };
+        let Err(second) = limiter.try_acquire() else {
+            panic!("second turn should be queued");
+        };
+        let Err(third) = limiter.try_acquire() else {
+            panic!("third turn should be queued");
+        };
+        assert_eq!((second.position, third.position), (1, 2));
+
+        // Giving up on a queued turn frees its place in line.
+        let third_wait = tokio::time::timeout(Duration::from_millis(20), third.wait());
+        assert!(third_wait.await.is_err(), "third turn is still blocked");
+        drop(first);
+        let second = tokio::time::timeout(Duration::from_secs(1), second.wait())
+            .await
+            .expect("second turn admitted");
+
+        limiter.set_limit(0);
+        let unlimited = limiter.try_acquire();
+        assert!(unlimited.is_ok());
+        drop(second);
+    }
+}
@@ -0,0 +1,572 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::{File, Metadata, OpenOptions};
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ignore::WalkBuilder;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::files::io::ensure_parent_within_root;
+use crate::shared::atomic_write::write_atomic;
+use crate::shared::audit_log_core::AuditLog;
+use crate::types::WorkspaceEntry;
+use crate::utils::normalize_git_path;
+
+const SNAPSHOTS_DIR: &str = "snapshots";
+const BLOBS_DIR: &str = "blobs";
+const MANIFESTS_DIR: &str = "manifests";
+const LOCK_FILE: &str = ".lock";
+const MAX_SNAPSHOT_FILE_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_SNAPSHOT_FILES: usize = 20_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotManifest {
+    thread_id: String,
+    created_at: u64,
+    files: BTreeMap<String, String>,
+    #[serde(default)]
+    skipped: Vec<String>,
+    /// False when the walk stopped at `MAX_SNAPSHOT_FILES`; rollback then
+    /// leaves files it has no record of untouched.
+    complete: bool,
+    /// Size and mtime of each file when it was hashed, so the next capture
+    /// can reuse the hash of a file that hasn't changed instead of reading it.
+    #[serde(default)]
+    stamps: BTreeMap<String, FileStamp>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FileStamp {
+    len: u64,
+    modified_ns: u64,
+}
+
+impl FileStamp {
+    fn of(metadata: &Metadata) -> Option<Self> {
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            len: metadata.len(),
+            modified_ns: u64::try_from(modified.as_nanos()).ok()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TurnRollbackResponse {
+    pub(crate) thread_id: String,
+    pub(crate) snapshot_created_at: u64,
+    pub(crate) restored: Vec<String>,
+    pub(crate) removed: Vec<String>,
+    pub(crate) skipped: Vec<String>,
+}
+
+fn now_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+fn should_skip_dir(name: &str) -> bool {
+    matches!(name, ".git" | "node_modules" | "target")
+}
+
+fn sanitize_key(value: &str) -> String {
+    value
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+fn snapshots_root() -> Result<PathBuf, String> {
+    let base = dirs_next::data_dir().ok_or_else(|| "Unable to resolve data dir".to_string())?;
+    Ok(base.join("agent-monitor").join(SNAPSHOTS_DIR))
+}
+
+pub(crate) fn snapshot_store_dir(workspace_id: &str) -> Result<PathBuf, String> {
+    Ok(snapshots_root()?.join(sanitize_key(workspace_id)))
+}
+
+/// Deletes snapshot stores that belong to workspaces which are no longer
+/// registered. Returns the number of stores removed.
+pub(crate) fn prune_orphaned_snapshot_stores(
+    known_workspace_ids: &[String],
+) -> Result<usize, String> {
+    let root = snapshots_root()?;
+    prune_orphaned_stores_in(&root, known_workspace_ids)
+}
+
+fn prune_orphaned_stores_in(root: &Path, known_workspace_ids: &[String]) -> Result<usize, String> {
+    let entries = match std::fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(err) => return Err(format!("Failed to read snapshot stores: {err}")),
+    };
+    let known = known_workspace_ids
+        .iter()
+        .map(|id| sanitize_key(id))
+        .collect::<HashSet<_>>();
+    let mut removed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if known.contains(&name) || !entry.path().is_dir() {
+            continue;
+        }
+        std::fs::remove_dir_all(entry.path())
+            .map_err(|err| format!("Failed to remove snapshot store {name}: {err}"))?;
+        removed += 1;
+    }
+    Ok(removed)
+}
+
+fn manifest_path(store_dir: &Path, thread_id: &str) -> PathBuf {
+    store_dir
+        .join(MANIFESTS_DIR)
+        .join(format!("{}.json", sanitize_key(thread_id)))
+}
+
+/// Takes the store's lock file. Capture, rollback and blob pruning hold it
+/// for their whole run, so a prune can't delete a blob another turn in the
+/// same workspace has written but not yet listed in its manifest. It's a
+/// file lock, so the app and the daemon exclude each other too.
+fn lock_store(store_dir: &Path) -> Result<File, String> {
+    std::fs::create_dir_all(store_dir)
+        .map_err(|err| format!("Failed to create snapshot directory: {err}"))?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(store_dir.join(LOCK_FILE))
+        .map_err(|err| format!("Failed to open snapshot lock: {err}"))?;
+    file.lock()
+        .map_err(|err| format!("Failed to lock snapshot store: {err}"))?;
+    Ok(file)
+}
+
+/// Drops the snapshot taken for a deleted thread along with blobs no other
+/// thread references.
+pub(crate) fn remove_thread_snapshot(store_dir: &Path, thread_id: &str) -> Result<(), String> {
+    if !store_dir.exists() {
+        return Ok(());
+    }
+    let _lock = lock_store(store_dir)?;
+    match std::fs::remove_file(manifest_path(store_dir, thread_id)) {
+        Ok(()) => prune_unreferenced_blobs(store_dir),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(format!("Failed to remove snapshot: {err}")),
+    }
+}
+
+fn hash_blob(content: &[u8]) -> Result<String, String> {
+    git2::Oid::hash_object(git2::ObjectType::Blob, content)
+        .map(|oid| oid.to_string())
+        .map_err(|err| format!("Failed to hash file: {err}"))
+}
+
+/// Walks the workspace the same way the file list does (gitignore-aware,
+/// hidden files included). Returns relative paths and whether the walk
+/// finished before hitting the file cap.
+pub(crate) fn walk_workspace_files(root: &Path) -> (Vec<String>, bool) {
+    let mut results = Vec::new();
+    let walker = WalkBuilder::new(root)
+        .hidden(false)
+        .follow_links(false)
+        .require_git(false)
+        .filter_entry(|entry| {
+            if entry.depth() == 0 {
+                return true;
+            }
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                let name = entry.file_name().to_string_lossy();
+                return !should_skip_dir(&name);
+            }
+            true
+        })
+        .build();
+
+    for entry in walker {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+            continue;
+        }
+        if results.len() >= MAX_SNAPSHOT_FILES {
+            return (results, false);
+        }
+        if let Ok(rel_path) = entry.path().strip_prefix(root) {
+            let normalized = normalize_git_path(&rel_path.to_string_lossy());
+            if !normalized.is_empty() {
+                results.push(normalized);
+            }
+        }
+    }
+    (results, true)
+}
+
+fn is_safe_relative_path(value: &str) -> bool {
+    let path = Path::new(value);
+    !value.is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Hashes recorded by earlier captures, keyed by path and stamp. A stamp
+/// that isn't older than its manifest is left out: the file may have been
+/// rewritten within the same second, with the same size, after it was read.
+fn known_hashes(store_dir: &Path) -> HashMap<(String, FileStamp), String> {
+    let mut known = HashMap::new();
+    for manifest in read_manifests(store_dir) {
+        let created_ns = manifest.created_at.saturating_mul(1_000_000_000);
+        for (rel_path, stamp) in manifest.stamps {
+            if stamp.modified_ns >= created_ns {
+                continue;
+            }
+            if let Some(hash) = manifest.files.get(&rel_path) {
+                known.insert((rel_path, stamp), hash.clone());
+            }
+        }
+    }
+    known
+}
+
+/// Records the current content of every workspace file. Blobs are stored by
+/// content hash so unchanged files cost nothing on later turns, and files
+/// whose size and mtime match an earlier capture aren't read again.
+pub(crate) fn capture_snapshot(
+    root: &Path,
+    store_dir: &Path,
+    thread_id: &str,
+) -> Result<(), String> {
+    let blobs_dir = store_dir.join(BLOBS_DIR);
+    std::fs::create_dir_all(&blobs_dir)
+        .map_err(|err| format!("Failed to create snapshot directory: {err}"))?;
+    std::fs::create_dir_all(store_dir.join(MANIFESTS_DIR))
+        .map_err(|err| format!("Failed to create snapshot directory: {err}"))?;
+
+    let _lock = lock_store(store_dir)?;
+
+    let known = known_hashes(store_dir);
+    let (paths, complete) = walk_workspace_files(root);
+    let mut files = BTreeMap::new();
+    let mut stamps = BTreeMap::new();
+    let mut skipped = Vec::new();
+    for rel_path in paths {
+        let full_path = root.join(&rel_path);
+        let Ok(metadata) = std::fs::metadata(&full_path) else {
+            skipped.push(rel_path);
+            continue;
+        };
+        if metadata.len() > MAX_SNAPSHOT_FILE_BYTES {
+            skipped.push(rel_path);
+            continue;
+        }
+        let stamp = FileStamp::of(&metadata);
+        let known_hash = stamp
+            .and_then(|stamp| known.get(&(rel_path.clone(), stamp)))
+            .filter(|hash| blobs_dir.join(hash).exists());
+        if let (Some(stamp), Some(hash)) = (stamp, known_hash) {
+            files.insert(rel_path.clone(), hash.clone());
+            stamps.insert(rel_path, stamp);
+            continue;
+        }
+        let Ok(content) = std::fs::read(&full_path) else {
+            skipped.push(rel_path);
+            continue;
+        };
+        let hash = hash_blob(&content)?;
+        let blob_path = blobs_dir.join(&hash);
+        if !blob_path.exists() {
+            std::fs::write(&blob_path, &content)
+                .map_err(|err| format!("Failed to store snapshot blob: {err}"))?;
+        }
+        if let Some(stamp) = stamp {
+            stamps.insert(rel_path.clone(), stamp);
+        }
+        files.insert(rel_path, hash);
+    }
+
+    let manifest = SnapshotManifest {
+        thread_id: thread_id.to_string(),
+        created_at: now_epoch(),
+        files,
+        skipped,
+        complete,
+        stamps,
+    };
+    let data = serde_json::to_string(&manifest)
+        .map_err(|err| format!("Failed to serialize snapshot: {err}"))?;
+    write_atomic(&manifest_path(store_dir, thread_id), data.as_bytes())
+        .map_err(|err| format!("Failed to write snapshot: {err}"))?;
+    prune_unreferenced_blobs(store_dir)
+}
+
+fn read_manifests(store_dir: &Path) -> Vec<SnapshotManifest> {
+    let Ok(entries) = std::fs::read_dir(store_dir.join(MANIFESTS_DIR)) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| std::fs::read_to_string(entry.path()).ok())
+        .filter_map(|data| serde_json::from_str::<SnapshotManifest>(&data).ok())
+        .collect()
+}
+
+fn prune_unreferenced_blobs(store_dir: &Path) -> Result<(), String> {
+    let referenced = read_manifests(store_dir)
+        .into_iter()
+        .flat_map(|manifest| manifest.files.into_values())
+        .collect::<HashSet<_>>();
+    let Ok(entries) = std::fs::read_dir(store_dir.join(BLOBS_DIR)) else {
+        return Ok(());
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !referenced.contains(&name) {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Restores the workspace to the snapshot taken before the thread's last
+/// turn: modified and deleted files are rewritten, files created since are
+/// removed. Whatever the agent left at a restored path is removed rather
+/// than written through, and the parent must resolve inside the root, so a
+/// planted symlink can't redirect the write outside the workspace.
+pub(crate) fn rollback_snapshot(
+    root: &Path,
+    store_dir: &Path,
+    thread_id: &str,
+) -> Result<TurnRollbackResponse, String> {
+    let _lock = lock_store(store_dir)?;
+    let root = &root
+        .canonicalize()
+        .map_err(|err| format!("Failed to resolve workspace root: {err}"))?;
+    let data = std::fs::read_to_string(manifest_path(store_dir, thread_id))
+        .map_err(|_| "No snapshot recorded for this thread.".to_string())?;
+    let manifest: SnapshotManifest =
+        serde_json::from_str(&data).map_err(|err| format!("Invalid snapshot: {err}"))?;
+
+    let blobs_dir = store_dir.join(BLOBS_DIR);
+    let mut restored = Vec::new();
+    for (rel_path, hash) in &manifest.files {
+        if !is_safe_relative_path(rel_path) {
+            return Err(format!("Invalid snapshot path: {rel_path}"));
+        }
+        let target = root.join(rel_path);
+        let current = std::fs::symlink_metadata(&target).ok();
+        let unchanged = current.as_ref().is_some_and(|metadata| metadata.is_file())
+            && std::fs::read(&target)
+                .ok()
+                .and_then(|content| hash_blob(&content).ok())
+                .is_some_and(|current| current == *hash);
+        if unchanged {
+            continue;
+        }
+        let content = std::fs::read(blobs_dir.join(hash))
+            .map_err(|err| format!("Snapshot blob missing for {rel_path}: {err}"))?;
+        ensure_parent_within_root(&target, root, "snapshot")
+            .map_err(|err| format!("Failed to restore {rel_path}: {err}"))?;
+        let removal = match current {
+            Some(metadata) if metadata.is_dir() => std::fs::remove_dir_all(&target),
+            Some(_) => std::fs::remove_file(&target),
+            None => Ok(()),
+        };
+        removal
+            .and_then(|()| write_atomic(&target, &content))
+            .map_err(|err| format!("Failed to restore {rel_path}: {err}"))?;
+        restored.push(rel_path.clone());
+    }
+
+    let mut removed = Vec::new();
+    if manifest.complete {
+        let skipped = manifest.skipped.iter().collect::<HashSet<_>>();
+        let (current_paths, _) = walk_workspace_files(root);
+        for rel_path in current_paths {
+            if manifest.files.contains_key(&rel_path) || skipped.contains(&rel_path) {
+                continue;
+            }
+            std::fs::remove_file(root.join(&rel_path))
+                .map_err(|err| format!("Failed to remove {rel_path}: {err}"))?;
+            removed.push(rel_path);
+        }
+    }
+
+    Ok(TurnRollbackResponse {
+        thread_id: manifest.thread_id,
+        snapshot_created_at: manifest.created_at,
+        restored,
+        removed,
+        skipped: manifest.skipped,
+    })
+}
+
+pub(crate) async fn turn_rollback_core(
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    audit_log: &AuditLog,
+    workspace_id: String,
+    thread_id: String,
+) -> Result<TurnRollbackResponse, String> {
+    let root = {
+        let workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get(&workspace_id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        PathBuf::from(&entry.path)
+    };
+    let store_dir = snapshot_store_dir(&workspace_id)?;
+    let response =
+        tokio::task::spawn_blocking(move || rollback_snapshot(&root, &store_dir, &thread_id))
+            .await
+            .map_err(|err| format!("Rollback task failed: {err}"))??;
+    audit_log.record(
+        "turn_rollback",
+        json!({
+            "workspaceId": workspace_id,
+            "threadId": response.thread_id,
+            "restored": response.restored,
+            "removed": response.removed,
+        }),
+    );
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capture_snapshot, prune_orphaned_stores_in, rollback_snapshot};
+    use std::fs;
+    use uuid::Uuid;
+
+    fn temp_dir(prefix: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{prefix}-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn rollback_restores_pre_turn_state() {
+        let root = temp_dir("snapshot-root");
+        let store = temp_dir("snapshot-store");
+        fs::create_dir_all(root.join("src")).expect("create src");
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").expect("seed main");
+        fs::write(root.join("README.md"), "readme\n").expect("seed readme");
+
+        capture_snapshot(&root, &store, "thread-1").expect("capture");
+
+        fs::write(root.join("src/main.rs"), "fn main() { panic!() }\n").expect("modify");
+        fs::remove_file(root.join("README.md")).expect("delete");
+        fs::write(root.join("src/new.rs"), "// new\n").expect("create");
+
+        let response = rollback_snapshot(&root, &store, "thread-1").expect("rollback");
+        assert_eq!(response.thread_id, "thread-1");
+        assert_eq!(response.restored, vec!["README.md", "src/main.rs"]);
+        assert_eq!(response.removed, vec!["src/new.rs"]);
+        assert_eq!(
+            fs::read_to_string(root.join("src/main.rs")).expect("read main"),
+            "fn main() {}\n"
+        );
+        assert!(root.join("README.md").exists());
+        assert!(!root.join("src/new.rs").exists());
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&store);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rollback_replaces_symlinks_instead_of_writing_through_them() {
+        use std::os::unix::fs::symlink;
+
+        let root = temp_dir("snapshot-link-root");
+        let store = temp_dir("snapshot-link-store");
+        let outside = temp_dir("snapshot-link-outside");
+        fs::create_dir_all(root.join("src")).expect("create src");
+        fs::write(root.join("config.toml"), "safe\n").expect("seed config");
+        fs::write(root.join("src/lib.rs"), "// lib\n").expect("seed lib");
+        capture_snapshot(&root, &store, "thread-1").expect("capture");
+
+        fs::write(outside.join("target.txt"), "outside\n").expect("seed outside");
+        fs::remove_file(root.join("config.toml")).expect("remove config");
+        symlink(outside.join("target.txt"), root.join("config.toml")).expect("link file");
+        fs::remove_dir_all(root.join("src")).expect("remove src");
+        symlink(&outside, root.join("src")).expect("link dir");
+
+        let error = rollback_snapshot(&root, &store, "thread-1").expect_err("escaped parent");
+        assert!(error.contains("src/lib.rs"));
+        assert!(!outside.join("lib.rs").exists());
+        assert_eq!(
+            fs::read_to_string(outside.join("target.txt")).expect("read outside"),
+            "outside\n"
+        );
+        assert!(!fs::symlink_metadata(root.join("config.toml"))
+            .expect("config metadata")
+            .file_type()
+            .is_symlink());
+        assert_eq!(
+            fs::read_to_string(root.join("config.toml")).expect("read config"),
+            "safe\n"
+        );
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&store);
+        let _ = fs::remove_dir_all(&outside);
+    }
+
+    #[test]
+    fn recapture_records_same_size_rewrites() {
+        let root = temp_dir("snapshot-recapture-root");
+        let store = temp_dir("snapshot-recapture-store");
+        fs::write(root.join("notes.txt"), "one\n").expect("seed notes");
+        capture_snapshot(&root, &store, "thread-1").expect("first capture");
+
+        fs::write(root.join("notes.txt"), "two\n").expect("rewrite notes");
+        capture_snapshot(&root, &store, "thread-1").expect("second capture");
+        fs::write(root.join("notes.txt"), "three\n").expect("modify notes");
+
+        rollback_snapshot(&root, &store, "thread-1").expect("rollback");
+        assert_eq!(
+            fs::read_to_string(root.join("notes.txt")).expect("read notes"),
+            "two\n"
+        );
+
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&store);
+    }
+
+    #[test]
+    fn rollback_without_snapshot_fails() {
+        let root = temp_dir("snapshot-missing-root");
+        let store = temp_dir("snapshot-missing-store");
+        assert!(rollback_snapshot(&root, &store, "thread-x").is_err());
+        let _ = fs::remove_dir_all(&root);
+        let _ = fs::remove_dir_all(&store);
+    }
+
+    #[test]
+    fn prune_removes_stores_for_unknown_workspaces() {
+        let root = temp_dir("snapshot-stores");
+        fs::create_dir_all(root.join("ws-keep")).expect("create kept store");
+        fs::create_dir_all(root.join("ws-gone")).expect("create orphan store");
+
+        let removed =
+            prune_orphaned_stores_in(&root, &["ws-keep".to_string()]).expect("prune stores");
+        assert_eq!(removed, 1);
+        assert!(root.join("ws-keep").exists());
+        assert!(!root.join("ws-gone").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,404 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+use crate::backend::app_server::WorkspaceSession;
+use crate::backend::events::{AppEvent, AppServerEvent, EventSink};
+use crate::shared::codex_aux_core::run_background_turn;
+use crate::shared::path_env::build_cli_path_env;
+use crate::shared::process_core::shell_command;
+use crate::storage::{read_workflows, write_workflows};
+use crate::types::{Workflow, WorkflowAction, WorkflowFailurePolicy, WorkflowStep, WorkspaceEntry};
+
+const WORKFLOWS_FILE: &str = "workflows.json";
+const PROMPT_STEP_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const SHELL_STEP_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+fn workflows_path(storage_path: &Path) -> PathBuf {
+    storage_path.with_file_name(WORKFLOWS_FILE)
+}
+
+pub(crate) fn workflows_list_core(storage_path: &Path) -> Result<Vec<Workflow>, String> {
+    read_workflows(&workflows_path(storage_path))
+}
+
+fn validate_workflow(workflow: &Workflow) -> Result<(), String> {
+    if workflow.name.is_empty() {
+        return Err("Workflow name is required.".to_string());
+    }
+    if workflow.steps.is_empty() {
+        return Err("A workflow needs at least one step.".to_string());
+    }
+    for (index, step) in workflow.steps.iter().enumerate() {
+        let number = index + 1;
+        if step.workspace_id.trim().is_empty() {
+            return Err(format!("Step {number} needs a workspace."));
+        }
+        let empty = match &step.action {
+            WorkflowAction::Prompt { prompt } => prompt.trim().is_empty(),
+            WorkflowAction::Shell { command, .. } => command.trim().is_empty(),
+        };
+        if empty {
+            return Err(format!("Step {number} has nothing to run."));
+        }
+    }
+    Ok(())
+}
+
+/// Adds `workflow`, replacing any workflow with the same name.
+pub(crate) fn workflow_save_core(
+    storage_path: &Path,
+    mut workflow: Workflow,
+) -> Result<Vec<Workflow>, String> {
+    workflow.name = workflow.name.trim().to_string();
+    validate_workflow(&workflow)?;
+    let path = workflows_path(storage_path);
+    let mut workflows = read_workflows(&path)?;
+    match workflows
+        .iter_mut()
+        .find(|entry| entry.name == workflow.name)
+    {
+        Some(existing) => *existing = workflow,
+        None => workflows.push(workflow),
+    }
+    write_workflows(&path, &workflows)?;
+    Ok(workflows)
+}
+
+pub(crate) fn workflow_delete_core(
+    storage_path: &Path,
+    name: &str,
+) -> Result<Vec<Workflow>, String> {
+    let path = workflows_path(storage_path);
+    let mut workflows = read_workflows(&path)?;
+    let before = workflows.len();
+    workflows.retain(|entry| entry.name != name);
+    if workflows.len() == before {
+        return Err(format!("Workflow `{name}` not found"));
+    }
+    write_workflows(&path, &workflows)?;
+    Ok(workflows)
+}
+
+/// Value of placeholder `name`: `previous`, or `stepN` counting from 1.
+fn placeholder_value<'a>(name: &str, outputs: &'a [String]) -> Option<&'a str> {
+    if name == "previous" {
+        return Some(outputs.last().map(String::as_str).unwrap_or_default());
+    }
+    let number: usize = name.strip_prefix("step")?.parse().ok()?;
+    outputs.get(number.checked_sub(1)?).map(String::as_str)
+}
+
+/// Fills `{{previous}}` and `{{stepN}}` in a prompt from earlier step
+/// outputs. Unknown placeholders are left as written, and placeholders
+/// inside substituted outputs aren't expanded.
+fn substitute(template: &str, outputs: &[String]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        match placeholder_value(after[..end].trim(), outputs) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+enum StepTarget {
+    Session(Arc<WorkspaceSession>),
+    Folder(PathBuf),
+}
+
+/// Earlier step outputs as `PREVIOUS` and `STEP_N` variables for a shell
+/// step. They're never pasted into the command, so agent output can't
+/// inject shell syntax.
+fn step_output_env(outputs: &[String]) -> Vec<(String, &str)> {
+    let previous = outputs.last().map(String::as_str).unwrap_or_default();
+    std::iter::once(("PREVIOUS".to_string(), previous))
+        .chain(
+            outputs
+                .iter()
+                .enumerate()
+                .map(|(index, output)| (format!("STEP_{}", index + 1), output.as_str())),
+        )
+        .collect()
+}
+
+async fn run_shell(
+    command: &str,
+    cwd: &Path,
+    limit: Duration,
+    outputs: &[String],
+) -> Result<String, String> {
+    let mut child = shell_command(command);
+    child
+        .current_dir(cwd)
+        .kill_on_drop(true)
+        .envs(step_output_env(outputs));
+    if let Some(path_env) = build_cli_path_env(None) {
+        child.env("PATH", path_env);
+    }
+    let output = timeout(limit, child.output())
+        .await
+        .map_err(|_| format!("`{command}` timed out after {}s", limit.as_secs()))?
+        .map_err(|err| format!("Failed to run `{command}`: {err}"))?;
+    let stdout = String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_string();
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let detail = if stderr.trim().is_empty() {
+            stdout.as_str()
+        } else {
+            stderr.trim()
+        };
+        return Err(format!("`{command}` failed ({}): {detail}", output.status));
+    }
+    Ok(stdout)
+}
+
+async fn run_step<E: EventSink>(
+    step: &WorkflowStep,
+    target: &StepTarget,
+    outputs: &[String],
+    event_sink: &E,
+) -> Result<String, String> {
+    match (&step.action, target) {
+        (WorkflowAction::Prompt { prompt }, StepTarget::Session(session)) => {
+            let output = run_background_turn(
+                session,
+                substitute(prompt, outputs),
+                |workspace_id, thread_id| {
+                    event_sink.emit_app_server_event(AppServerEvent::new(
+                        workspace_id,
+                        AppEvent::background_thread_hidden(thread_id),
+                    ));
+                },
+                PROMPT_STEP_TIMEOUT,
+                "Timeout waiting for workflow step",
+                "Unknown error during workflow step",
+            )
+            .await?;
+            Ok(output.text)
+        }
+        (
+            WorkflowAction::Shell {
+                command,
+                timeout_secs,
+            },
+            StepTarget::Folder(cwd),
+        ) => {
+            let limit = timeout_secs
+                .filter(|secs| *secs > 0)
+                .map_or(SHELL_STEP_TIMEOUT, Duration::from_secs);
+            run_shell(command, cwd, limit, outputs).await
+        }
+        _ => Err("Step target doesn't match its action".to_string()),
+    }
+}
+
+struct WorkflowRun<E: EventSink> {
+    run_id: String,
+    workflow: String,
+    event_sink: E,
+}
+
+impl<E: EventSink> WorkflowRun<E> {
+    fn emit_step(
+        &self,
+        step: &WorkflowStep,
+        step_index: usize,
+        attempt: u32,
+        status: &str,
+        result: Option<&Result<String, String>>,
+    ) {
+        self.event_sink.emit_app_server_event(AppServerEvent::new(
+            step.workspace_id.clone(),
+            AppEvent::WorkflowStep {
+                run_id: self.run_id.clone(),
+                workflow: self.workflow.clone(),
+                step_index,
+                attempt,
+                status: status.to_string(),
+                output: result.and_then(|result| result.as_ref().ok().cloned()),
+                error: result.and_then(|result| result.as_ref().err().cloned()),
+            },
+        ));
+    }
+
+    fn emit_completed(&self, error: Option<String>) {
+        let status = if error.is_some() {
+            "failed"
+        } else {
+            "completed"
+        };
+        self.event_sink.emit_app_server_event(AppServerEvent::new(
+            String::new(),
+            AppEvent::WorkflowCompleted {
+                run_id: self.run_id.clone(),
+                workflow: self.workflow.clone(),
+                status: status.to_string(),
+                error,
+            },
+        ));
+    }
+
+    async fn run(self, steps: Vec<(WorkflowStep, StepTarget)>) {
+        let mut outputs = Vec::with_capacity(steps.len());
+        for (step_index, (step, target)) in steps.iter().enumerate() {
+            let mut attempt = 1;
+            let result = loop {
+                self.emit_step(step, step_index, attempt, "started", None);
+                let result = run_step(step, target, &outputs, &self.event_sink).await;
+                if result.is_err() && attempt <= step.retries {
+                    self.emit_step(step, step_index, attempt, "retrying", Some(&result));
+                    attempt += 1;
+                    continue;
+                }
+                break result;
+            };
+            match &result {
+                Ok(_) => self.emit_step(step, step_index, attempt, "completed", Some(&result)),
+                Err(_) => self.emit_step(step, step_index, attempt, "failed", Some(&result)),
+            }
+            match result {
+                Ok(output) => outputs.push(output),
+                Err(error) => {
+                    if step.on_failure == WorkflowFailurePolicy::Stop {
+                        self.emit_completed(Some(format!("Step {}: {error}", step_index + 1)));
+                        return;
+                    }
+                    outputs.push(String::new());
+                }
+            }
+        }
+        self.emit_completed(None);
+    }
+}
+
+/// Starts workflow `name` and returns its run id. Every step's workspace is
+/// checked first (prompt steps need a connected session), then the steps
+/// run in the background, reporting `workflow/step` and
+/// `workflow/completed` events.
+pub(crate) async fn workflow_run_core<E: EventSink>(
+    name: &str,
+    storage_path: &Path,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    event_sink: E,
+) -> Result<String, String> {
+    let workflow = workflows_list_core(storage_path)?
+        .into_iter()
+        .find(|entry| entry.name == name)
+        .ok_or_else(|| format!("Workflow `{name}` not found"))?;
+    validate_workflow(&workflow)?;
+
+    let mut steps = Vec::with_capacity(workflow.steps.len());
+    for (index, step) in workflow.steps.into_iter().enumerate() {
+        let number = index + 1;
+        let target = match step.action {
+            WorkflowAction::Prompt { .. } => sessions
+                .lock()
+                .await
+                .get(&step.workspace_id)
+                .cloned()
+                .map(StepTarget::Session)
+                .ok_or_else(|| format!("Step {number}: workspace not connected"))?,
+            WorkflowAction::Shell { .. } => workspaces
+                .lock()
+                .await
+                .get(&step.workspace_id)
+                .map(|entry| StepTarget::Folder(PathBuf::from(&entry.path)))
+                .ok_or_else(|| format!("Step {number}: workspace not found"))?,
+        };
+        steps.push((step, target));
+    }
+
+    let run_id = Uuid::new_v4().to_string();
+    let run = WorkflowRun {
+        run_id: run_id.clone(),
+        workflow: workflow.name,
+        event_sink,
+    };
+    tokio::spawn(run.run(steps));
+    Ok(run_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_shell, substitute, validate_workflow};
+    use crate::types::{Workflow, WorkflowAction, WorkflowFailurePolicy, WorkflowStep};
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    #[test]
+    fn substitutes_previous_and_numbered_step_outputs() {
+        let outputs = vec!["plan".to_string(), "diff {{step1}}".to_string()];
+        assert_eq!(
+            substitute("Review {{previous}} against {{ step1 }}", &outputs),
+            "Review diff {{step1}} against plan"
+        );
+        assert_eq!(
+            substitute("{{step3}} and {{unknown}} {{", &outputs),
+            "{{step3}} and {{unknown}} {{"
+        );
+        assert_eq!(substitute("first: {{previous}}", &[]), "first: ");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn passes_step_outputs_to_shell_steps_as_variables() {
+        let cwd = std::env::temp_dir().join(format!("codex-monitor-workflow-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&cwd).expect("create cwd");
+        let outputs = vec!["plan".to_string(), "done; touch pwned".to_string()];
+
+        let output = run_shell(
+            "printf '%s|%s|{{previous}}' \"$STEP_1\" \"$PREVIOUS\"",
+            &cwd,
+            Duration::from_secs(10),
+            &outputs,
+        )
+        .await
+        .expect("run shell step");
+        assert_eq!(output, "plan|done; touch pwned|{{previous}}");
+        assert!(!cwd.join("pwned").exists());
+
+        let _ = std::fs::remove_dir_all(&cwd);
+    }
+
+    #[test]
+    fn rejects_workflows_with_empty_steps() {
+        let step = |command: &str| WorkflowStep {
+            workspace_id: "ws-1".to_string(),
+            action: WorkflowAction::Shell {
+                command: command.to_string(),
+                timeout_secs: None,
+            },
+            on_failure: WorkflowFailurePolicy::Stop,
+            retries: 0,
+        };
+        let mut workflow = Workflow {
+            name: "ship".to_string(),
+            steps: vec![step("cargo test")],
+        };
+        assert!(validate_workflow(&workflow).is_ok());
+        workflow.steps.push(step("  "));
+        assert_eq!(
+            validate_workflow(&workflow),
+            Err("Step 2 has nothing to run.".to_string())
+        );
+    }
+}
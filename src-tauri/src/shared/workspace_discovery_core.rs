@@ -0,0 +1,164 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use tokio::sync::Mutex;
+
+use crate::shared::agent_profiles_core::detect_agent_profile_at;
+use crate::types::{AppSettings, WorkspaceCandidate, WorkspaceEntry};
+
+const DEFAULT_MAX_DEPTH: u32 = 3;
+const MAX_DEPTH_LIMIT: u32 = 6;
+const MAX_CANDIDATES: usize = 200;
+
+fn should_skip_dir(name: &str) -> bool {
+    name.starts_with('.')
+        || matches!(
+            name,
+            "node_modules" | "dist" | "target" | "release-artifacts"
+        )
+}
+
+fn canonical_key(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+fn candidate_for(repo: &Path, cli_type: &str) -> WorkspaceCandidate {
+    WorkspaceCandidate {
+        path: repo.to_string_lossy().to_string(),
+        name: repo
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("Workspace")
+            .to_string(),
+        has_agents_md: repo.join("AGENTS.md").is_file(),
+        has_claude_md: repo.join("CLAUDE.md").is_file(),
+        agent_profile: detect_agent_profile_at(repo, cli_type),
+    }
+}
+
+/// Breadth-first walk of `root` for git repositories. Repositories are not
+/// descended into, so nested checkouts and submodules are not reported.
+async fn find_repos(root: &Path, max_depth: u32, found: &mut Vec<PathBuf>) {
+    let mut queue = VecDeque::from([(root.to_path_buf(), 0u32)]);
+    while let Some((dir, depth)) = queue.pop_front() {
+        if found.len() >= MAX_CANDIDATES {
+            return;
+        }
+        if tokio::fs::metadata(dir.join(".git")).await.is_ok() {
+            found.push(dir);
+            continue;
+        }
+        if depth >= max_depth {
+            continue;
+        }
+        let Ok(mut entries) = tokio::fs::read_dir(&dir).await else {
+            continue;
+        };
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            let is_dir = entry
+                .file_type()
+                .await
+                .map(|file_type| file_type.is_dir())
+                .unwrap_or(false);
+            let name = entry.file_name();
+            if is_dir && !should_skip_dir(&name.to_string_lossy()) {
+                queue.push_back((entry.path(), depth + 1));
+            }
+        }
+    }
+}
+
+/// Scans `root_paths` for git repositories that aren't registered as
+/// workspaces yet. Candidates note existing `AGENTS.md`/`CLAUDE.md` files and
+/// the agent profile they match so the UI can pre-select it.
+pub(crate) async fn workspaces_discover_core(
+    root_paths: Vec<String>,
+    max_depth: Option<u32>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    app_settings: &Mutex<AppSettings>,
+) -> Result<Vec<WorkspaceCandidate>, String> {
+    let roots: Vec<PathBuf> = root_paths
+        .iter()
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .collect();
+    if roots.is_empty() {
+        return Err("At least one folder to scan is required.".to_string());
+    }
+    let max_depth = max_depth.unwrap_or(DEFAULT_MAX_DEPTH).min(MAX_DEPTH_LIMIT);
+
+    let mut seen: HashSet<PathBuf> = {
+        let workspaces = workspaces.lock().await;
+        workspaces
+            .values()
+            .map(|entry| canonical_key(Path::new(&entry.path)))
+            .collect()
+    };
+    let cli_type = app_settings.lock().await.cli_type.clone();
+
+    let mut repos = Vec::new();
+    for root in &roots {
+        if !root.is_dir() {
+            return Err(format!("{} is not a folder.", root.display()));
+        }
+        find_repos(root, max_depth, &mut repos).await;
+    }
+
+    Ok(repos
+        .into_iter()
+        .filter(|repo| seen.insert(canonical_key(repo)))
+        .map(|repo| candidate_for(&repo, &cli_type))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::workspaces_discover_core;
+    use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use std::collections::HashMap;
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn discovers_unregistered_repos_within_depth() {
+        let root = std::env::temp_dir().join(format!("codex-monitor-{}", Uuid::new_v4()));
+        for repo in ["alpha", "group/beta", "group/beta/nested", "a/b/c/deep"] {
+            std::fs::create_dir_all(root.join(repo).join(".git")).expect("create repo");
+        }
+        std::fs::create_dir_all(root.join("node_modules/pkg/.git")).expect("create");
+        std::fs::write(root.join("group/beta/CLAUDE.md"), "# Beta").expect("write");
+
+        let registered = root.join("alpha").to_string_lossy().to_string();
+        let workspaces = Mutex::new(HashMap::from([(
+            "ws-1".to_string(),
+            WorkspaceEntry {
+                id: "ws-1".to_string(),
+                name: "alpha".to_string(),
+                path: registered,
+                codex_bin: None,
+                kind: WorkspaceKind::Main,
+                parent_id: None,
+                worktree: None,
+                settings: WorkspaceSettings::default(),
+            },
+        )]));
+        let app_settings = Mutex::new(AppSettings::default());
+
+        let candidates = workspaces_discover_core(
+            vec![root.to_string_lossy().to_string()],
+            Some(3),
+            &workspaces,
+            &app_settings,
+        )
+        .await
+        .expect("discover");
+
+        let names: Vec<&str> = candidates.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, vec!["beta"]);
+        assert!(candidates[0].has_claude_md);
+        assert!(!candidates[0].has_agents_md);
+        assert!(candidates[0].agent_profile.is_none());
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
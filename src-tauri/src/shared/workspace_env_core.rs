@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use crate::shared::redaction_core::looks_like_secret_name;
+use crate::types::WorkspaceEntry;
+
+const KEYCHAIN_PREFIX: &str = "keychain:";
+const DEFAULT_KEYCHAIN_SERVICE: &str = "codex-monitor";
+
+/// Worktrees inherit their parent's variables; the worktree's own values win.
+pub(crate) fn merged_workspace_env(
+    entry: &WorkspaceEntry,
+    parent_entry: Option<&WorkspaceEntry>,
+) -> HashMap<String, String> {
+    let mut env = parent_entry
+        .map(|parent| parent.settings.env.clone())
+        .unwrap_or_default();
+    env.extend(
+        entry
+            .settings
+            .env
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone())),
+    );
+    env
+}
+
+/// Parses `keychain:<service>/<account>` or `keychain:<account>` (which uses
+/// the app's own keychain service).
+fn parse_keychain_reference(value: &str) -> Option<(&str, &str)> {
+    let reference = value.strip_prefix(KEYCHAIN_PREFIX)?.trim();
+    match reference.split_once('/') {
+        Some((service, account)) if !service.is_empty() && !account.is_empty() => {
+            Some((service, account))
+        }
+        None if !reference.is_empty() => Some((DEFAULT_KEYCHAIN_SERVICE, reference)),
+        _ => None,
+    }
+}
+
+fn read_keychain_secret(service: &str, account: &str) -> Result<String, String> {
+    keyring::Entry::new(service, account)
+        .and_then(|entry| entry.get_password())
+        .map_err(|err| err.to_string())
+}
+
+fn resolve_env_with<F>(
+    env: &HashMap<String, String>,
+    read_secret: F,
+) -> Result<Vec<(String, String)>, String>
+where
+    F: Fn(&str, &str) -> Result<String, String>,
+{
+    let mut resolved = Vec::with_capacity(env.len());
+    for (key, value) in env {
+        let key = key.trim();
+        if key.is_empty() {
+            continue;
+        }
+        let value = match parse_keychain_reference(value) {
+            Some((service, account)) => read_secret(service, account).map_err(|err| {
+                format!("Failed to read `{key}` from the keychain ({service}/{account}): {err}")
+            })?,
+            None => value.clone(),
+        };
+        resolved.push((key.to_string(), value));
+    }
+    Ok(resolved)
+}
+
+/// Values to mask in agent output: everything read from the keychain plus
+/// variables whose names look like credentials.
+pub(crate) fn workspace_secret_values(
+    env: &HashMap<String, String>,
+    resolved: &[(String, String)],
+) -> Vec<String> {
+    resolved
+        .iter()
+        .filter(|(key, _)| {
+            looks_like_secret_name(key)
+                || env.iter().any(|(raw_key, raw_value)| {
+                    raw_key.trim() == key && parse_keychain_reference(raw_value).is_some()
+                })
+        })
+        .map(|(_, value)| value.clone())
+        .collect()
+}
+
+/// Resolves workspace variables for a child process, replacing keychain
+/// references with the stored secret.
+pub(crate) fn resolve_workspace_env(
+    env: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>, String> {
+    resolve_env_with(env, read_keychain_secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse_keychain_reference, resolve_env_with, workspace_secret_values,
+        DEFAULT_KEYCHAIN_SERVICE,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn resolves_plain_values_and_keychain_references() {
+        assert_eq!(
+            parse_keychain_reference("keychain:aws/dev"),
+            Some(("aws", "dev"))
+        );
+        assert_eq!(
+            parse_keychain_reference("keychain:db-password"),
+            Some((DEFAULT_KEYCHAIN_SERVICE, "db-password"))
+        );
+        assert_eq!(parse_keychain_reference("keychain:/x"), None);
+        assert_eq!(parse_keychain_reference("postgres://localhost"), None);
+
+        let env = HashMap::from([
+            ("AWS_PROFILE".to_string(), "dev".to_string()),
+            (
+                "DATABASE_URL".to_string(),
+                "keychain:db/primary".to_string(),
+            ),
+        ]);
+        let mut resolved = resolve_env_with(&env, |service, account| {
+            Ok(format!("secret-{service}-{account}"))
+        })
+        .expect("resolve");
+        resolved.sort();
+        assert_eq!(
+            resolved,
+            vec![
+                ("AWS_PROFILE".to_string(), "dev".to_string()),
+                ("DATABASE_URL".to_string(), "secret-db-primary".to_string()),
+            ]
+        );
+        assert_eq!(
+            workspace_secret_values(&env, &resolved),
+            vec!["secret-db-primary".to_string()]
+        );
+
+        let error = resolve_env_with(&env, |_, _| Err("not found".to_string()))
+            .expect_err("missing secret");
+        assert!(error.contains("DATABASE_URL"));
+    }
+}
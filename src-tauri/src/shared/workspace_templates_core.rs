@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::backend::app_server::{CliSpawnConfig, WorkspaceSession};
+use crate::shared::agent_profiles_core::{apply_agent_profile_at, AgentProfileApplyMode};
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::workspaces_core::register_workspace_core;
+use crate::storage::{read_templates, write_templates};
+use crate::types::{
+    AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorkspaceTemplate,
+};
+
+const TEMPLATES_FILE: &str = "templates.json";
+const AGENTS_MD: &str = "AGENTS.md";
+
+fn templates_path(storage_path: &Path) -> PathBuf {
+    storage_path.with_file_name(TEMPLATES_FILE)
+}
+
+pub(crate) fn workspace_templates_list_core(
+    storage_path: &Path,
+) -> Result<Vec<WorkspaceTemplate>, String> {
+    read_templates(&templates_path(storage_path))
+}
+
+/// Adds `template`, replacing any template with the same name.
+pub(crate) fn workspace_template_save_core(
+    storage_path: &Path,
+    mut template: WorkspaceTemplate,
+) -> Result<Vec<WorkspaceTemplate>, String> {
+    template.name = template.name.trim().to_string();
+    if template.name.is_empty() {
+        return Err("Template name is required.".to_string());
+    }
+    let path = templates_path(storage_path);
+    let mut templates = read_templates(&path)?;
+    match templates
+        .iter_mut()
+        .find(|entry| entry.name == template.name)
+    {
+        Some(existing) => *existing = template,
+        None => templates.push(template),
+    }
+    write_templates(&path, &templates)?;
+    Ok(templates)
+}
+
+pub(crate) fn workspace_template_delete_core(
+    storage_path: &Path,
+    name: &str,
+) -> Result<Vec<WorkspaceTemplate>, String> {
+    let path = templates_path(storage_path);
+    let mut templates = read_templates(&path)?;
+    let before = templates.len();
+    templates.retain(|entry| entry.name != name);
+    if templates.len() == before {
+        return Err(format!("Template `{name}` not found"));
+    }
+    write_templates(&path, &templates)?;
+    Ok(templates)
+}
+
+fn template_settings(template: &WorkspaceTemplate, cli_type: &str) -> WorkspaceSettings {
+    let cli_args = template
+        .cli_args
+        .as_ref()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+    let mut settings = WorkspaceSettings {
+        env: template.env.clone(),
+        ..WorkspaceSettings::default()
+    };
+    match cli_type {
+        "gemini" => settings.gemini_args = cli_args,
+        "cursor" => settings.cursor_args = cli_args,
+        "claude" => settings.claude_args = cli_args,
+        _ => settings.codex_args = cli_args,
+    }
+    settings
+}
+
+/// Creates the folder (or clones `git_url` into it), seeds `AGENTS.md`,
+/// applies the template's agent profile and registers the workspace. A
+/// folder created here is removed again if any later step fails.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn workspace_create_from_template_core<F, Fut, FRunGit, FutRunGit>(
+    template_name: &str,
+    path: String,
+    git_url: Option<String>,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    audit_log: &AuditLog,
+    run_git_command: FRunGit,
+    spawn_session: F,
+) -> Result<WorkspaceInfo, String>
+where
+    F: Fn(WorkspaceEntry, CliSpawnConfig) -> Fut,
+    Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+    FRunGit: Fn(&PathBuf, &[&str]) -> FutRunGit,
+    FutRunGit: Future<Output = Result<(), String>>,
+{
+    let template = workspace_templates_list_core(storage_path)?
+        .into_iter()
+        .find(|entry| entry.name == template_name)
+        .ok_or_else(|| format!("Template `{template_name}` not found"))?;
+    let path = path.trim().to_string();
+    if path.is_empty() {
+        return Err("Workspace path is required.".to_string());
+    }
+    let root = PathBuf::from(&path);
+    let git_url = git_url
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let created = if let Some(git_url) = git_url.as_deref() {
+        let occupied = std::fs::read_dir(&root)
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false);
+        if occupied {
+            return Err(format!("{path} already exists and is not empty."));
+        }
+        let parent = root
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .ok_or_else(|| "Workspace path needs a parent folder.".to_string())?
+            .to_path_buf();
+        std::fs::create_dir_all(&parent)
+            .map_err(|err| format!("Failed to create {}: {err}", parent.display()))?;
+        if let Err(error) = run_git_command(&parent, &["clone", git_url, &path]).await {
+            let _ = std::fs::remove_dir_all(&root);
+            return Err(error);
+        }
+        true
+    } else if root.exists() {
+        if !root.is_dir() {
+            return Err("Workspace path must be a folder.".to_string());
+        }
+        false
+    } else {
+        std::fs::create_dir_all(&root).map_err(|err| format!("Failed to create {path}: {err}"))?;
+        true
+    };
+
+    let app_cli_type = app_settings.lock().await.cli_type.clone();
+    let cli_type = template
+        .cli_type
+        .clone()
+        .unwrap_or_else(|| app_cli_type.clone());
+    let entry = WorkspaceEntry {
+        id: Uuid::new_v4().to_string(),
+        name: root
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("Workspace")
+            .to_string(),
+        path: path.clone(),
+        codex_bin: None,
+        kind: WorkspaceKind::Main,
+        parent_id: None,
+        worktree: None,
+        settings: template_settings(&template, &cli_type),
+    };
+
+    let result = async {
+        if let Some(contents) = template.agents_md.as_deref() {
+            let agents_md = root.join(AGENTS_MD);
+            if !agents_md.exists() {
+                std::fs::write(&agents_md, contents)
+                    .map_err(|err| format!("Failed to write {AGENTS_MD}: {err}"))?;
+            }
+        }
+        if let Some(profile) = template.agent_profile.clone() {
+            apply_agent_profile_at(&root, profile, &app_cli_type, AgentProfileApplyMode::Auto)?;
+        }
+        register_workspace_core(
+            entry,
+            workspaces,
+            sessions,
+            app_settings,
+            storage_path,
+            audit_log,
+            spawn_session,
+        )
+        .await
+    }
+    .await;
+
+    if result.is_err() && created {
+        let _ = std::fs::remove_dir_all(&root);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        template_settings, workspace_template_delete_core, workspace_template_save_core,
+        workspace_templates_list_core,
+    };
+    use crate::types::WorkspaceTemplate;
+    use std::collections::HashMap;
+    use uuid::Uuid;
+
+    #[test]
+    fn templates_are_saved_replaced_and_applied_to_settings() {
+        let dir = std::env::temp_dir().join(format!("codex-monitor-{}", Uuid::new_v4()));
+        let storage_path = dir.join("workspaces.json");
+        let template = WorkspaceTemplate {
+            name: " service ".to_string(),
+            cli_args: Some("--profile fast".to_string()),
+            env: HashMap::from([("AWS_PROFILE".to_string(), "dev".to_string())]),
+            ..WorkspaceTemplate::default()
+        };
+        workspace_template_save_core(&storage_path, template.clone()).expect("save");
+        let updated = WorkspaceTemplate {
+            cli_type: Some("claude".to_string()),
+            ..template
+        };
+        let saved = workspace_template_save_core(&storage_path, updated).expect("save");
+        assert_eq!(saved.len(), 1);
+        assert_eq!(saved[0].name, "service");
+
+        let listed = workspace_templates_list_core(&storage_path).expect("list");
+        let settings = template_settings(&listed[0], "claude");
+        assert_eq!(settings.claude_args.as_deref(), Some("--profile fast"));
+        assert!(settings.codex_args.is_none());
+        assert_eq!(
+            settings.env.get("AWS_PROFILE").map(String::as_str),
+            Some("dev")
+        );
+
+        assert!(workspace_template_delete_core(&storage_path, "service")
+            .expect("delete")
+            .is_empty());
+        assert!(workspace_template_delete_core(&storage_path, "service").is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
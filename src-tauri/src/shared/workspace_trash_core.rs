@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::json;
+use tokio::sync::Mutex;
+
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::orphan_cleanup_core;
+use crate::storage::{read_workspace_trash, write_workspace_trash, write_workspaces};
+use crate::types::{TrashedWorkspace, WorkspaceEntry, WorkspaceInfo};
+
+const TRASH_FILE: &str = "workspace-trash.json";
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+fn trash_path(storage_path: &Path) -> PathBuf {
+    storage_path.with_file_name(TRASH_FILE)
+}
+
+fn now_epoch() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Records `entry` as removed, restorable for `retention_days`. Blocking.
+pub(crate) fn add_to_trash(
+    storage_path: &Path,
+    entry: WorkspaceEntry,
+    retention_days: u32,
+) -> Result<(), String> {
+    let path = trash_path(storage_path);
+    let mut trash = read_workspace_trash(&path)?;
+    trash.retain(|trashed| trashed.entry.id != entry.id);
+    let removed_at = now_epoch();
+    trash.push(TrashedWorkspace {
+        entry,
+        removed_at,
+        purge_at: removed_at + u64::from(retention_days) * DAY_SECS,
+    });
+    write_workspace_trash(&path, &trash)
+}
+
+/// Trashed workspaces, most recently removed first.
+pub(crate) fn workspace_trash_list_core(
+    storage_path: &Path,
+) -> Result<Vec<TrashedWorkspace>, String> {
+    let mut trash = read_workspace_trash(&trash_path(storage_path))?;
+    trash.sort_by_key(|entry| std::cmp::Reverse(entry.removed_at));
+    Ok(trash)
+}
+
+pub(crate) fn trashed_workspace_ids(storage_path: &Path) -> Result<Vec<String>, String> {
+    Ok(read_workspace_trash(&trash_path(storage_path))?
+        .into_iter()
+        .map(|trashed| trashed.entry.id)
+        .collect())
+}
+
+/// Re-registers a trashed workspace and moves its thread store, metrics and
+/// logs back. The workspace comes back disconnected.
+pub(crate) async fn workspace_restore_core(
+    workspace_id: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    storage_path: &PathBuf,
+    audit_log: &AuditLog,
+) -> Result<WorkspaceInfo, String> {
+    let path = trash_path(storage_path);
+    let mut trash = read_workspace_trash(&path)?;
+    let index = trash
+        .iter()
+        .position(|trashed| trashed.entry.id == workspace_id)
+        .ok_or_else(|| format!("Workspace `{workspace_id}` is not in the trash"))?;
+    let entry = trash[index].entry.clone();
+    {
+        let mut workspaces = workspaces.lock().await;
+        if workspaces.contains_key(&entry.id) {
+            return Err(format!("Workspace `{}` is already registered.", entry.name));
+        }
+        if let Some(other) = workspaces.values().find(|other| other.path == entry.path) {
+            return Err(format!(
+                "`{}` is already the workspace `{}`.",
+                entry.path, other.name
+            ));
+        }
+        workspaces.insert(entry.id.clone(), entry.clone());
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        write_workspaces(storage_path, &list)?;
+    }
+    trash.remove(index);
+    write_workspace_trash(&path, &trash)?;
+    let restored_id = entry.id.clone();
+    tokio::task::spawn_blocking(move || orphan_cleanup_core::restore_workspace_data(&restored_id))
+        .await
+        .map_err(|err| err.to_string())?;
+    audit_log.record(
+        "workspace_restore",
+        json!({ "workspaceId": entry.id, "path": entry.path }),
+    );
+
+    Ok(WorkspaceInfo {
+        id: entry.id,
+        name: entry.name,
+        path: entry.path,
+        codex_bin: entry.codex_bin,
+        connected: false,
+        kind: entry.kind,
+        parent_id: entry.parent_id,
+        worktree: entry.worktree,
+        settings: entry.settings,
+    })
+}
+
+fn split_expired(
+    trash: Vec<TrashedWorkspace>,
+    now: u64,
+) -> (Vec<TrashedWorkspace>, Vec<TrashedWorkspace>) {
+    trash
+        .into_iter()
+        .partition(|trashed| trashed.purge_at > now)
+}
+
+/// Permanently deletes trashed workspaces past their `purgeAt`, with their
+/// data. Blocking.
+pub(crate) fn purge_expired_trash(storage_path: &Path) -> Result<String, String> {
+    let path = trash_path(storage_path);
+    let (kept, expired) = split_expired(read_workspace_trash(&path)?, now_epoch());
+    if expired.is_empty() {
+        return Ok("No trashed workspaces to purge".to_string());
+    }
+    write_workspace_trash(&path, &kept)?;
+    for trashed in &expired {
+        orphan_cleanup_core::purge_workspace_data(&trashed.entry.id);
+    }
+    Ok(format!("Purged {} trashed workspace(s)", expired.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_expired;
+    use crate::types::{TrashedWorkspace, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+
+    #[test]
+    fn keeps_trashed_workspaces_until_their_purge_time() {
+        let trashed = |id: &str, purge_at: u64| TrashedWorkspace {
+            entry: WorkspaceEntry {
+                id: id.to_string(),
+                name: id.to_string(),
+                path: format!("/repos/{id}"),
+                codex_bin: None,
+                kind: WorkspaceKind::Main,
+                parent_id: None,
+                worktree: None,
+                settings: WorkspaceSettings::default(),
+            },
+            removed_at: 0,
+            purge_at,
+        };
+        let (kept, expired) = split_expired(vec![trashed("old", 100), trashed("new", 300)], 200);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].entry.id, "new");
+        assert_eq!(expired[0].entry.id, "old");
+    }
+}
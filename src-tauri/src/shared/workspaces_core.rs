@@ -1,13 +1,27 @@
+use serde_json::json;
 use std::collections::HashMap;
 use std::future::Future;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use tokio::sync::Mutex;
 
 use crate::backend::app_server::{CliSpawnConfig, WorkspaceSession};
-use crate::codex::args::resolve_workspace_codex_args;
+use crate::backend::exec_target::ExecutionTarget;
+use crate::backend::guardrails::validate_guardrails;
+use crate::backend::turn_hooks::validate_auto_compact;
+use crate::codex::args::{
+    resolve_workspace_codex_args, validate_codex_config_overrides, workspace_codex_config_args,
+};
 use crate::codex::home::resolve_workspace_codex_home;
+use crate::shared::account_profiles_core::{account_profile_home, account_profile_provider};
+use crate::shared::agent_profiles_core::PROFILE_STATE_FILE;
+use crate::shared::audit_log_core::AuditLog;
+use crate::shared::maintenance_core::ActiveTurns;
+use crate::shared::orphan_cleanup_core;
+use crate::shared::runtime_services::RuntimeServices;
+use crate::shared::workspace_env_core::merged_workspace_env;
+use crate::shared::workspace_trash_core;
 use crate::storage::write_workspaces;
 use crate::types::{
     AppSettings, WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeInfo,
@@ -18,6 +32,7 @@ use uuid::Uuid;
 pub(crate) const WORKTREE_SETUP_MARKERS_DIR: &str = "worktree-setup";
 pub(crate) const WORKTREE_SETUP_MARKER_EXT: &str = "ran";
 const AGENTS_MD_FILE_NAME: &str = "AGENTS.md";
+const WORKTREES_DIR: &str = "worktrees";
 
 pub(crate) fn resolve_default_cli_bin(settings: &AppSettings) -> Option<String> {
     match settings.cli_type.as_str() {
@@ -95,16 +110,25 @@ fn workspace_cli_args_override(settings: &WorkspaceSettings, cli_type: &str) ->
     }
 }
 
+/// The home of the account profile the workspace picked for `cli_type`.
+fn workspace_account_profile_home(settings: &WorkspaceSettings, cli_type: &str) -> Option<String> {
+    let provider = account_profile_provider(cli_type);
+    let name = settings.account_profiles.get(provider)?;
+    account_profile_home(provider, name)
+        .ok()
+        .map(|home| home.to_string_lossy().to_string())
+}
+
 fn workspace_cli_home_override(settings: &WorkspaceSettings, cli_type: &str) -> Option<String> {
-    match cli_type {
-        "gemini" => normalize_workspace_cli_value(settings.gemini_home.clone())
-            .or_else(|| normalize_workspace_cli_value(settings.codex_home.clone())),
-        "cursor" => normalize_workspace_cli_value(settings.cursor_home.clone())
-            .or_else(|| normalize_workspace_cli_value(settings.codex_home.clone())),
-        "claude" => normalize_workspace_cli_value(settings.claude_home.clone())
-            .or_else(|| normalize_workspace_cli_value(settings.codex_home.clone())),
-        _ => normalize_workspace_cli_value(settings.codex_home.clone()),
-    }
+    let provider_home = match cli_type {
+        "gemini" => settings.gemini_home.clone(),
+        "cursor" => settings.cursor_home.clone(),
+        "claude" => settings.claude_home.clone(),
+        _ => settings.codex_home.clone(),
+    };
+    normalize_workspace_cli_value(provider_home)
+        .or_else(|| workspace_account_profile_home(settings, cli_type))
+        .or_else(|| normalize_workspace_cli_value(settings.codex_home.clone()))
 }
 
 fn set_workspace_cli_override(entry: &mut WorkspaceEntry, cli_type: &str, cli_bin: Option<String>) {
@@ -143,7 +167,16 @@ pub(crate) fn resolve_workspace_cli_args(
 ) -> Option<String> {
     let cli_type = app_settings.map(|settings| settings.cli_type.as_str()).unwrap_or("codex");
     if cli_type == "codex" {
-        return resolve_workspace_codex_args(entry, parent_entry, app_settings);
+        let args = resolve_workspace_codex_args(entry, parent_entry, app_settings);
+        let overrides = workspace_codex_config_args(entry, parent_entry);
+        if overrides.is_empty() {
+            return args;
+        }
+        let overrides = shell_words::join(overrides);
+        return Some(match args {
+            Some(args) => format!("{args} {overrides}"),
+            None => overrides,
+        });
     }
     workspace_cli_args_override(&entry.settings, cli_type)
         .or_else(|| {
@@ -184,6 +217,10 @@ pub(crate) fn build_cli_spawn_config(
         cli_bin: resolve_workspace_cli_bin(entry, app_settings),
         cli_args: resolve_workspace_cli_args(entry, parent_entry, Some(app_settings)),
         cli_home: resolve_workspace_cli_home(entry, parent_entry, Some(app_settings)),
+        env: merged_workspace_env(entry, parent_entry),
+        target: ExecutionTarget::for_workspace(&entry.settings),
+        active_turns: ActiveTurns::default(),
+        services: RuntimeServices::default(),
     }
 }
 
@@ -238,6 +275,25 @@ pub(crate) fn worktree_setup_marker_path(data_dir: &PathBuf, workspace_id: &str)
         .join(format!("{workspace_id}.{WORKTREE_SETUP_MARKER_EXT}"))
 }
 
+/// Resolves the directory that holds managed worktrees. A configured
+/// `worktreesRoot` wins; relative values are anchored at the app data dir.
+pub(crate) fn resolve_worktrees_root(data_dir: &PathBuf, settings: &AppSettings) -> PathBuf {
+    settings
+        .worktrees_root
+        .as_deref()
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            let path = PathBuf::from(value);
+            if path.is_absolute() {
+                path
+            } else {
+                data_dir.join(path)
+            }
+        })
+        .unwrap_or_else(|| data_dir.join(WORKTREES_DIR))
+}
+
 pub(crate) fn is_workspace_path_dir_core(path: &str) -> bool {
     PathBuf::from(path).is_dir()
 }
@@ -266,6 +322,30 @@ pub(crate) async fn list_workspaces_core(
     result
 }
 
+pub(crate) async fn list_worktrees_core(
+    parent_id: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+) -> Result<Vec<WorkspaceInfo>, String> {
+    {
+        let workspaces = workspaces.lock().await;
+        let parent = workspaces
+            .get(&parent_id)
+            .ok_or_else(|| "parent workspace not found".to_string())?;
+        if parent.kind.is_worktree() {
+            return Err("Worktrees cannot have child worktrees.".to_string());
+        }
+    }
+    let worktrees = list_workspaces_core(workspaces, sessions)
+        .await
+        .into_iter()
+        .filter(|workspace| {
+            workspace.kind.is_worktree() && workspace.parent_id.as_deref() == Some(&parent_id)
+        })
+        .collect();
+    Ok(worktrees)
+}
+
 async fn resolve_entry_and_parent(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
@@ -348,6 +428,7 @@ pub(crate) async fn worktree_setup_mark_ran_core(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn add_workspace_core<F, Fut>(
     path: String,
     codex_bin: Option<String>,
@@ -355,6 +436,7 @@ pub(crate) async fn add_workspace_core<F, Fut>(
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
     app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
+    audit_log: &AuditLog,
     spawn_session: F,
 ) -> Result<WorkspaceInfo, String>
 where
@@ -383,6 +465,34 @@ where
     };
     set_workspace_cli_override(&mut entry, settings_snapshot.cli_type.as_str(), codex_bin);
 
+    register_workspace_core(
+        entry,
+        workspaces,
+        sessions,
+        app_settings,
+        storage_path,
+        audit_log,
+        spawn_session,
+    )
+    .await
+}
+
+/// Spawns a session for a new main workspace and persists it; the entry is
+/// dropped again if persisting fails.
+pub(crate) async fn register_workspace_core<F, Fut>(
+    entry: WorkspaceEntry,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    audit_log: &AuditLog,
+    spawn_session: F,
+) -> Result<WorkspaceInfo, String>
+where
+    F: Fn(WorkspaceEntry, CliSpawnConfig) -> Fut,
+    Fut: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let settings_snapshot = app_settings.lock().await.clone();
     let config = build_cli_spawn_config(&entry, None, &settings_snapshot);
     let session = spawn_session(entry.clone(), config).await?;
 
@@ -401,6 +511,10 @@ where
     }
 
     sessions.lock().await.insert(entry.id.clone(), session);
+    audit_log.record(
+        "workspace_add",
+        json!({ "workspaceId": entry.id, "name": entry.name, "path": entry.path }),
+    );
 
     Ok(WorkspaceInfo {
         id: entry.id,
@@ -493,7 +607,10 @@ where
         return Err("Cannot create a worktree from another worktree.".to_string());
     }
 
-    let worktree_root = data_dir.join("worktrees").join(&parent_entry.id);
+    let worktree_root = {
+        let settings = app_settings.lock().await;
+        resolve_worktrees_root(data_dir, &settings).join(&parent_entry.id)
+    };
     std::fs::create_dir_all(&worktree_root)
         .map_err(|err| format!("Failed to create worktree directory: {err}"))?;
 
@@ -623,6 +740,24 @@ async fn kill_session_by_id(
     }
 }
 
+/// Archives the adapter data and logs of workspaces that were just removed.
+async fn clean_up_removed_workspace_data(workspace_ids: Vec<String>) {
+    let _ = tokio::task::spawn_blocking(move || {
+        for workspace_id in &workspace_ids {
+            orphan_cleanup_core::clean_up_workspace_data(workspace_id);
+        }
+    })
+    .await;
+}
+
+/// Moves the adapter data and logs of a trashed workspace into the trash.
+async fn trash_removed_workspace_data(workspace_id: String) {
+    let _ = tokio::task::spawn_blocking(move || {
+        orphan_cleanup_core::trash_workspace_data(&workspace_id);
+    })
+    .await;
+}
+
 pub(crate) async fn remove_workspace_core<
     FRunGit,
     FutRunGit,
@@ -632,7 +767,9 @@ pub(crate) async fn remove_workspace_core<
     id: String,
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
     storage_path: &PathBuf,
+    audit_log: &AuditLog,
     run_git_command: FRunGit,
     is_missing_worktree_error: FIsMissing,
     remove_dir_all: FRemoveDirAll,
@@ -706,15 +843,30 @@ where
         kill_session_by_id(sessions, &id).await;
         ids_to_remove.push(id.clone());
     }
+    // Worktree checkouts are gone, so only the workspace itself is trashed.
+    let trash_days = app_settings.lock().await.workspace_trash_retention_days;
+    let trashed = trash_days > 0 && ids_to_remove.contains(&id);
+    if trashed {
+        workspace_trash_core::add_to_trash(storage_path, entry.clone(), trash_days)?;
+    }
 
     {
         let mut workspaces = workspaces.lock().await;
-        for workspace_id in ids_to_remove {
-            workspaces.remove(&workspace_id);
+        for workspace_id in &ids_to_remove {
+            workspaces.remove(workspace_id);
         }
         let list: Vec<_> = workspaces.values().cloned().collect();
         write_workspaces(storage_path, &list)?;
     }
+    audit_log.record(
+        "workspace_remove",
+        json!({ "workspaceIds": ids_to_remove, "path": entry.path, "trashed": trashed }),
+    );
+    if trashed {
+        ids_to_remove.retain(|workspace_id| workspace_id != &id);
+        trash_removed_workspace_data(id.clone()).await;
+    }
+    clean_up_removed_workspace_data(ids_to_remove).await;
 
     if failures.is_empty() {
         return Ok(());
@@ -795,6 +947,7 @@ where
         let list: Vec<_> = workspaces.values().cloned().collect();
         write_workspaces(storage_path, &list)?;
     }
+    clean_up_removed_workspace_data(vec![entry.id]).await;
 
     Ok(())
 }
@@ -877,7 +1030,10 @@ where
 
     run_git_command(&parent_root, &["branch", "-m", &old_branch, &final_branch]).await?;
 
-    let worktree_root = data_dir.join("worktrees").join(&parent.id);
+    let worktree_root = {
+        let settings = app_settings.lock().await;
+        resolve_worktrees_root(data_dir, &settings).join(&parent.id)
+    };
     std::fs::create_dir_all(&worktree_root)
         .map_err(|err| format!("Failed to create worktree directory: {err}"))?;
 
@@ -1092,6 +1248,9 @@ where
     FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
 {
     settings.worktree_setup_script = normalize_setup_script(settings.worktree_setup_script);
+    validate_codex_config_overrides(settings.codex_config.as_ref())?;
+    validate_guardrails(&settings.guardrails)?;
+    validate_auto_compact(settings.auto_compact.as_ref())?;
     let app_settings_snapshot = app_settings.lock().await.clone();
 
     let (
@@ -1148,7 +1307,9 @@ where
         app_settings_snapshot.cli_type.as_str(),
     );
     let codex_home_changed = previous_cli_home != next_cli_home;
-    let codex_args_changed = previous_cli_args != next_cli_args;
+    let codex_config_changed = app_settings_snapshot.cli_type == "codex"
+        && previous_entry.settings.codex_config != entry_snapshot.settings.codex_config;
+    let codex_args_changed = previous_cli_args != next_cli_args || codex_config_changed;
     let worktree_setup_script_changed =
         previous_worktree_setup_script != entry_snapshot.settings.worktree_setup_script;
     let connected = sessions.lock().await.contains_key(&id);
@@ -1225,6 +1386,14 @@ where
             }
         }
     }
+    let live_session = sessions.lock().await.get(&id).cloned();
+    if let Some(session) = live_session {
+        session
+            .approvals
+            .set_policy(entry_snapshot.settings.approval_timeout.clone())
+            .await;
+        session.turn_hooks.apply_settings(&entry_snapshot.settings);
+    }
     if worktree_setup_script_changed && !entry_snapshot.kind.is_worktree() {
         let child_ids = child_entries
             .iter()
@@ -1295,6 +1464,148 @@ pub(crate) async fn update_workspace_codex_bin_core(
     })
 }
 
+/// Checks that `new_path` is a folder no other workspace uses and differs
+/// from the workspace's current path.
+fn validate_relocation(
+    workspaces: &HashMap<String, WorkspaceEntry>,
+    id: &str,
+    new_path: &str,
+) -> Result<WorkspaceEntry, String> {
+    let entry = workspaces
+        .get(id)
+        .cloned()
+        .ok_or_else(|| "workspace not found".to_string())?;
+    if new_path.is_empty() {
+        return Err("A new path is required.".to_string());
+    }
+    if entry.path == new_path {
+        return Err("The workspace is already at that path.".to_string());
+    }
+    if !Path::new(new_path).is_dir() {
+        return Err(format!("`{new_path}` is not a folder."));
+    }
+    if let Some(other) = workspaces
+        .values()
+        .find(|other| other.id != id && other.path == new_path)
+    {
+        return Err(format!(
+            "`{new_path}` is already the workspace `{}`.",
+            other.name
+        ));
+    }
+    Ok(entry)
+}
+
+/// Copies the agent profile state from the old folder when it still exists,
+/// e.g. after re-cloning the repository instead of moving it.
+fn carry_over_profile_state(old_root: &Path, new_root: &Path) -> Result<(), String> {
+    let source = old_root.join(PROFILE_STATE_FILE);
+    let target = new_root.join(PROFILE_STATE_FILE);
+    if !source.is_file() || target.exists() {
+        return Ok(());
+    }
+    std::fs::copy(&source, &target)
+        .map(|_| ())
+        .map_err(|err| format!("Failed to copy {}: {err}", source.display()))
+}
+
+/// Points a workspace at the folder its repository moved to. Git worktree
+/// links are repaired from the new location (for a worktree, from its
+/// parent; for a main workspace, for each of its worktrees), the agent
+/// profile state is carried over, and a connected session is restarted
+/// in the new folder so spawn problems show up right away.
+pub(crate) async fn workspace_relocate_core<FRunGit, FutRunGit, FSpawn, FutSpawn>(
+    id: String,
+    new_path: String,
+    workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
+    sessions: &Mutex<HashMap<String, Arc<WorkspaceSession>>>,
+    app_settings: &Mutex<AppSettings>,
+    storage_path: &PathBuf,
+    run_git_command: FRunGit,
+    spawn_session: FSpawn,
+) -> Result<WorkspaceInfo, String>
+where
+    FRunGit: Fn(&PathBuf, &[&str]) -> FutRunGit,
+    FutRunGit: Future<Output = Result<(), String>>,
+    FSpawn: Fn(WorkspaceEntry, CliSpawnConfig) -> FutSpawn,
+    FutSpawn: Future<Output = Result<Arc<WorkspaceSession>, String>>,
+{
+    let new_path = new_path.trim().trim_end_matches(['/', '\\']).to_string();
+    let (previous, parent, children) = {
+        let workspaces = workspaces.lock().await;
+        let previous = validate_relocation(&workspaces, &id, &new_path)?;
+        let parent = previous
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id))
+            .cloned();
+        let children = workspaces
+            .values()
+            .filter(|entry| entry.parent_id.as_deref() == Some(id.as_str()))
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>();
+        (previous, parent, children)
+    };
+
+    let new_root = PathBuf::from(&new_path);
+    carry_over_profile_state(Path::new(&previous.path), &new_root)?;
+    let repair = match (&parent, previous.kind.is_worktree()) {
+        (Some(parent), true) => {
+            run_git_command(
+                &PathBuf::from(&parent.path),
+                &["worktree", "repair", &new_path],
+            )
+            .await
+        }
+        (_, false) if !children.is_empty() => {
+            let mut args = vec!["worktree", "repair"];
+            args.extend(children.iter().map(String::as_str));
+            run_git_command(&new_root, &args).await
+        }
+        _ => Ok(()),
+    };
+    if let Err(error) = repair {
+        tracing::warn!(workspace_id = %id, "git worktree repair after relocating failed: {error}");
+    }
+
+    let (entry_snapshot, list) = {
+        let mut workspaces = workspaces.lock().await;
+        let entry = workspaces
+            .get_mut(&id)
+            .ok_or_else(|| "workspace not found".to_string())?;
+        entry.path = new_path.clone();
+        let snapshot = entry.clone();
+        let list: Vec<_> = workspaces.values().cloned().collect();
+        (snapshot, list)
+    };
+    write_workspaces(storage_path, &list)?;
+
+    let was_connected = sessions.lock().await.contains_key(&id);
+    if was_connected {
+        kill_session_by_id(sessions, &id).await;
+        let settings_snapshot = app_settings.lock().await.clone();
+        let config = build_cli_spawn_config(&entry_snapshot, parent.as_ref(), &settings_snapshot);
+        let session = spawn_session(entry_snapshot.clone(), config)
+            .await
+            .map_err(|error| {
+                format!("Workspace moved to `{new_path}`, but its session failed to start: {error}")
+            })?;
+        sessions.lock().await.insert(id.clone(), session);
+    }
+
+    Ok(WorkspaceInfo {
+        id: entry_snapshot.id,
+        name: entry_snapshot.name,
+        path: entry_snapshot.path,
+        codex_bin: entry_snapshot.codex_bin,
+        connected: was_connected,
+        kind: entry_snapshot.kind,
+        parent_id: entry_snapshot.parent_id,
+        worktree: entry_snapshot.worktree,
+        settings: entry_snapshot.settings,
+    })
+}
+
 pub(crate) async fn list_workspace_files_core<F>(
     workspaces: &Mutex<HashMap<String, WorkspaceEntry>>,
     workspace_id: &str,
@@ -1335,6 +1646,7 @@ fn sort_workspaces(workspaces: &mut [WorkspaceInfo]) {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use std::path::PathBuf;
 
     use super::copy_agents_md_from_parent_to_worktree;
@@ -1342,8 +1654,14 @@ mod tests {
     use super::resolve_workspace_cli_bin;
     use super::resolve_workspace_cli_home;
     use super::resolve_default_cli_bin;
+    use super::resolve_worktrees_root;
+    use super::validate_relocation;
     use super::AGENTS_MD_FILE_NAME;
-    use crate::types::{AppSettings, WorkspaceEntry, WorkspaceKind, WorkspaceSettings};
+    use crate::codex::args::parse_codex_args;
+    use crate::shared::account_profiles_core::account_profile_home;
+    use crate::types::{
+        AppSettings, CodexConfigOverrides, WorkspaceEntry, WorkspaceKind, WorkspaceSettings,
+    };
     use uuid::Uuid;
 
     fn make_temp_dir() -> std::path::PathBuf {
@@ -1392,6 +1710,32 @@ mod tests {
         let _ = std::fs::remove_dir_all(worktree);
     }
 
+    #[test]
+    fn resolves_worktrees_root_from_settings() {
+        let data_dir = PathBuf::from("/data");
+        let mut settings = AppSettings::default();
+        assert_eq!(
+            resolve_worktrees_root(&data_dir, &settings),
+            PathBuf::from("/data/worktrees")
+        );
+
+        settings.worktrees_root = Some("  ".to_string());
+        assert_eq!(
+            resolve_worktrees_root(&data_dir, &settings),
+            PathBuf::from("/data/worktrees")
+        );
+
+        settings.worktrees_root = Some("agent-trees".to_string());
+        assert_eq!(
+            resolve_worktrees_root(&data_dir, &settings),
+            PathBuf::from("/data/agent-trees")
+        );
+
+        let absolute = std::env::temp_dir().join("codex-monitor-worktrees");
+        settings.worktrees_root = Some(absolute.to_string_lossy().to_string());
+        assert_eq!(resolve_worktrees_root(&data_dir, &settings), absolute);
+    }
+
     #[test]
     fn resolves_default_cli_bin_from_active_cli_type() {
         let mut settings = AppSettings::default();
@@ -1572,6 +1916,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn appends_codex_config_overrides_to_codex_args() {
+        let mut settings = AppSettings::default();
+        settings.codex_args = Some("--profile app".to_string());
+
+        let parent = WorkspaceEntry {
+            id: "parent".to_string(),
+            name: "Parent".to_string(),
+            path: "/tmp/parent".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings {
+                codex_config: Some(CodexConfigOverrides {
+                    approval_policy: Some("untrusted".to_string()),
+                    sandbox_mode: Some("read-only".to_string()),
+                    ..CodexConfigOverrides::default()
+                }),
+                ..WorkspaceSettings::default()
+            },
+        };
+        let child = WorkspaceEntry {
+            id: "child".to_string(),
+            name: "Child".to_string(),
+            path: "/tmp/child".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Worktree,
+            parent_id: Some(parent.id.clone()),
+            worktree: None,
+            settings: WorkspaceSettings {
+                codex_config: Some(CodexConfigOverrides {
+                    model: Some("gpt-5".to_string()),
+                    approval_policy: Some("never".to_string()),
+                    ..CodexConfigOverrides::default()
+                }),
+                ..WorkspaceSettings::default()
+            },
+        };
+
+        let args = resolve_workspace_cli_args(&child, Some(&parent), Some(&settings));
+        assert_eq!(
+            parse_codex_args(args.as_deref()).expect("parse args"),
+            [
+                "--profile",
+                "app",
+                "-c",
+                "model=\"gpt-5\"",
+                "-c",
+                "approval_policy=\"never\"",
+                "-c",
+                "sandbox_mode=\"read-only\"",
+            ]
+        );
+
+        settings.cli_type = "claude".to_string();
+        settings.claude_args = None;
+        assert_eq!(
+            resolve_workspace_cli_args(&child, Some(&parent), Some(&settings)),
+            None
+        );
+    }
+
     #[test]
     fn resolves_workspace_cli_home_from_active_workspace_override() {
         let mut settings = AppSettings::default();
@@ -1606,4 +2013,68 @@ mod tests {
             Some(PathBuf::from("/tmp/parent/.claude-home"))
         );
     }
+
+    #[test]
+    fn resolves_workspace_cli_home_from_account_profile() {
+        let mut settings = AppSettings::default();
+        settings.cli_type = "claude".to_string();
+        let mut entry = WorkspaceEntry {
+            id: "ws".to_string(),
+            name: "Workspace".to_string(),
+            path: "/tmp/ws".to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        entry
+            .settings
+            .account_profiles
+            .insert("claude".to_string(), "work".to_string());
+
+        assert_eq!(
+            resolve_workspace_cli_home(&entry, None, Some(&settings)),
+            account_profile_home("claude", "work").ok()
+        );
+        entry.settings.claude_home = Some(".claude-home".to_string());
+        assert_eq!(
+            resolve_workspace_cli_home(&entry, None, Some(&settings)),
+            Some(PathBuf::from("/tmp/ws/.claude-home"))
+        );
+    }
+
+    #[test]
+    fn relocation_needs_an_unused_existing_folder() {
+        let current = make_temp_dir();
+        let target = make_temp_dir();
+        let entry = |id: &str, path: &std::path::Path| WorkspaceEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            path: path.to_string_lossy().to_string(),
+            codex_bin: None,
+            kind: WorkspaceKind::Main,
+            parent_id: None,
+            worktree: None,
+            settings: WorkspaceSettings::default(),
+        };
+        let mut workspaces = HashMap::new();
+        workspaces.insert("w1".to_string(), entry("w1", &current));
+        let target_path = target.to_string_lossy().to_string();
+
+        assert!(validate_relocation(&workspaces, "w1", &target_path).is_ok());
+        assert!(validate_relocation(&workspaces, "w1", &current.to_string_lossy()).is_err());
+        let missing = target.join("missing").to_string_lossy().to_string();
+        assert!(validate_relocation(&workspaces, "w1", &missing).is_err());
+        assert!(validate_relocation(&workspaces, "w9", &target_path).is_err());
+
+        workspaces.insert("w2".to_string(), entry("w2", &target));
+        assert_eq!(
+            validate_relocation(&workspaces, "w1", &target_path).err(),
+            Some(format!("`{target_path}` is already the workspace `w2`."))
+        );
+
+        let _ = std::fs::remove_dir_all(current);
+        let _ = std::fs::remove_dir_all(target);
+    }
 }
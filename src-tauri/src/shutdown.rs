@@ -0,0 +1,27 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tauri::{AppHandle, Manager};
+
+use crate::event_sink::TauriEventSink;
+use crate::shared::shutdown_core;
+use crate::state::AppState;
+
+static SHUTDOWN_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Called on every exit request. The first one starts stopping the sessions
+/// and returns `true` to hold the exit; once they are stopped the app exits
+/// again, and that request goes through.
+pub(crate) fn hold_exit_for_shutdown(app: &AppHandle) -> bool {
+    if app.try_state::<AppState>().is_none() || SHUTDOWN_STARTED.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app.state::<AppState>();
+        let settings = state.app_settings.lock().await.clone();
+        let event_sink = TauriEventSink::new(app.clone());
+        shutdown_core::shutdown_sessions(&state.sessions, &settings, &event_sink).await;
+        app.exit(0);
+    });
+    true
+}
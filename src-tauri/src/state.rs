@@ -2,10 +2,14 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, Notify};
 
+use crate::backend::plugin_adapter;
 use crate::dictation::DictationState;
 use crate::shared::codex_core::CodexLoginCancelState;
+use crate::shared::maintenance_core::{self, MaintenanceCoordinator};
+use crate::shared::runtime_services::RuntimeServices;
+use crate::shared::{logging_core, path_env};
 use crate::storage::{read_settings, read_workspaces};
 use crate::types::{AppSettings, WorkspaceEntry};
 
@@ -15,11 +19,16 @@ pub(crate) struct AppState {
     pub(crate) terminal_sessions:
         Mutex<HashMap<String, Arc<crate::terminal::TerminalSession>>>,
     pub(crate) remote_backend: Mutex<Option<crate::remote_backend::RemoteBackend>>,
+    pub(crate) remote_session: Mutex<crate::remote_backend::RemoteSessionState>,
     pub(crate) storage_path: PathBuf,
     pub(crate) settings_path: PathBuf,
     pub(crate) app_settings: Mutex<AppSettings>,
     pub(crate) dictation: Mutex<DictationState>,
     pub(crate) codex_login_cancels: Mutex<HashMap<String, CodexLoginCancelState>>,
+    pub(crate) maintenance: Arc<MaintenanceCoordinator>,
+    pub(crate) services: RuntimeServices,
+    /// Signalled after settings are saved, so the HTTP API can restart.
+    pub(crate) http_api_settings_changed: Notify,
 }
 
 impl AppState {
@@ -30,18 +39,34 @@ impl AppState {
             .unwrap_or_else(|_| std::env::current_dir().unwrap_or_else(|_| ".".into()));
         let storage_path = data_dir.join("workspaces.json");
         let settings_path = data_dir.join("settings.json");
+        let services = RuntimeServices::default();
+        services.audit_log.init(&data_dir, "local");
+        plugin_adapter::load_plugins(&data_dir);
         let workspaces = read_workspaces(&storage_path).unwrap_or_default();
         let app_settings = read_settings(&settings_path).unwrap_or_default();
+        services.apply_settings(&app_settings);
+        logging_core::set_rpc_inspector_enabled(app_settings.debug_rpc_inspector);
+        path_env::set_login_shell_path_enabled(app_settings.resolve_login_shell_path);
+        let maintenance = Arc::new(MaintenanceCoordinator::new());
+        maintenance_core::register_default_tasks(
+            &maintenance,
+            storage_path.clone(),
+            services.retention.clone(),
+        );
         Self {
             workspaces: Mutex::new(workspaces),
             sessions: Mutex::new(HashMap::new()),
             terminal_sessions: Mutex::new(HashMap::new()),
             remote_backend: Mutex::new(None),
+            remote_session: Mutex::new(Default::default()),
             storage_path,
             settings_path,
             app_settings: Mutex::new(app_settings),
             dictation: Mutex::new(DictationState::default()),
             codex_login_cancels: Mutex::new(HashMap::new()),
+            maintenance,
+            services,
+            http_api_settings_changed: Notify::new(),
         }
     }
 }
@@ -1,14 +1,15 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-use crate::types::{AppSettings, WorkspaceEntry};
+use crate::shared::atomic_write::{read_with_backup, write_with_backup};
+use crate::types::{AppSettings, TrashedWorkspace, Workflow, WorkspaceEntry, WorkspaceTemplate};
+
+fn parse_json<T: serde::de::DeserializeOwned>(data: &str) -> Result<T, String> {
+    serde_json::from_str(data).map_err(|e| e.to_string())
+}
 
 pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, WorkspaceEntry>, String> {
-    if !path.exists() {
-        return Ok(HashMap::new());
-    }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    let list: Vec<WorkspaceEntry> = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    let list: Vec<WorkspaceEntry> = read_with_backup(path, parse_json)?.unwrap_or_default();
     Ok(list
         .into_iter()
         .map(|entry| (entry.id.clone(), entry))
@@ -16,27 +17,47 @@ pub(crate) fn read_workspaces(path: &PathBuf) -> Result<HashMap<String, Workspac
 }
 
 pub(crate) fn write_workspaces(path: &PathBuf, entries: &[WorkspaceEntry]) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
     let data = serde_json::to_string_pretty(entries).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+    write_with_backup(path, data.as_bytes()).map_err(|e| e.to_string())
 }
 
 pub(crate) fn read_settings(path: &PathBuf) -> Result<AppSettings, String> {
-    if !path.exists() {
-        return Ok(AppSettings::default());
-    }
-    let data = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
-    serde_json::from_str(&data).map_err(|e| e.to_string())
+    Ok(read_with_backup(path, parse_json)?.unwrap_or_default())
 }
 
 pub(crate) fn write_settings(path: &PathBuf, settings: &AppSettings) -> Result<(), String> {
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-    }
     let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
-    std::fs::write(path, data).map_err(|e| e.to_string())
+    write_with_backup(path, data.as_bytes()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn read_templates(path: &PathBuf) -> Result<Vec<WorkspaceTemplate>, String> {
+    Ok(read_with_backup(path, parse_json)?.unwrap_or_default())
+}
+
+pub(crate) fn write_templates(path: &PathBuf, templates: &[WorkspaceTemplate]) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(templates).map_err(|e| e.to_string())?;
+    write_with_backup(path, data.as_bytes()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn read_workflows(path: &PathBuf) -> Result<Vec<Workflow>, String> {
+    Ok(read_with_backup(path, parse_json)?.unwrap_or_default())
+}
+
+pub(crate) fn write_workflows(path: &PathBuf, workflows: &[Workflow]) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(workflows).map_err(|e| e.to_string())?;
+    write_with_backup(path, data.as_bytes()).map_err(|e| e.to_string())
+}
+
+pub(crate) fn read_workspace_trash(path: &PathBuf) -> Result<Vec<TrashedWorkspace>, String> {
+    Ok(read_with_backup(path, parse_json)?.unwrap_or_default())
+}
+
+pub(crate) fn write_workspace_trash(
+    path: &PathBuf,
+    trash: &[TrashedWorkspace],
+) -> Result<(), String> {
+    let data = serde_json::to_string_pretty(trash).map_err(|e| e.to_string())?;
+    write_with_backup(path, data.as_bytes()).map_err(|e| e.to_string())
 }
 
 #[cfg(test)]
@@ -9,6 +9,7 @@ use tokio::sync::Mutex;
 
 use crate::backend::events::{EventSink, TerminalExit, TerminalOutput};
 use crate::event_sink::TauriEventSink;
+use crate::shared::workspace_env_core::{merged_workspace_env, resolve_workspace_env};
 use crate::state::AppState;
 
 pub(crate) struct TerminalSession {
@@ -142,6 +143,24 @@ async fn get_workspace_path(
     Ok(PathBuf::from(&entry.path))
 }
 
+async fn get_workspace_env(
+    workspace_id: &str,
+    state: &State<'_, AppState>,
+) -> Result<Vec<(String, String)>, String> {
+    let env = {
+        let workspaces = state.workspaces.lock().await;
+        let entry = workspaces
+            .get(workspace_id)
+            .ok_or_else(|| "Unknown workspace".to_string())?;
+        let parent = entry
+            .parent_id
+            .as_ref()
+            .and_then(|parent_id| workspaces.get(parent_id));
+        merged_workspace_env(entry, parent)
+    };
+    resolve_workspace_env(&env)
+}
+
 #[tauri::command]
 pub(crate) async fn terminal_open(
     workspace_id: String,
@@ -165,6 +184,7 @@ pub(crate) async fn terminal_open(
     }
 
     let cwd = get_workspace_path(&workspace_id, &state).await?;
+    let workspace_env = get_workspace_env(&workspace_id, &state).await?;
     let pty_system = native_pty_system();
     let size = PtySize {
         rows: rows.max(2),
@@ -184,6 +204,9 @@ pub(crate) async fn terminal_open(
     cmd.env("LANG", &locale);
     cmd.env("LC_ALL", &locale);
     cmd.env("LC_CTYPE", &locale);
+    for (key, value) in workspace_env {
+        cmd.env(key, value);
+    }
 
     let child = pair
         .slave
@@ -0,0 +1,405 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+
+use serde_json::Value;
+use tauri::menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::backend::approvals::is_approval_request_method;
+use crate::backend::events::AppServerEvent;
+use crate::state::AppState;
+use crate::types::WorkspaceInfo;
+
+const TRAY_ID: &str = "main";
+const WORKSPACE_ITEM_PREFIX: &str = "tray_workspace:";
+const NOTIFICATION_BODY_MAX_CHARS: usize = 200;
+
+#[derive(Debug, Default, PartialEq)]
+struct WorkspaceActivity {
+    /// Threads with a turn running.
+    running: Vec<String>,
+    /// Unanswered approval requests, by request id, with their thread.
+    approvals: BTreeMap<String, Option<String>>,
+}
+
+/// A turn the tray reports when it ends.
+#[derive(Debug, PartialEq)]
+struct FinishedTurn {
+    workspace_id: String,
+    error: Option<String>,
+}
+
+/// Running turns and pending approvals per workspace, kept from the event
+/// pipeline so the tray is current while the main window is closed.
+#[derive(Debug, Default)]
+pub(crate) struct TrayStatus {
+    activity: HashMap<String, WorkspaceActivity>,
+    /// Names of the listed workspaces, in list order.
+    workspaces: Vec<(String, String)>,
+}
+
+fn request_key(id: &Value) -> String {
+    id.as_str()
+        .map(str::to_string)
+        .unwrap_or_else(|| id.to_string())
+}
+
+impl TrayStatus {
+    /// Applies one normalized event. Returns whether the counts changed and
+    /// the turn it ended, if any.
+    fn observe(&mut self, workspace_id: &str, message: &Value) -> (bool, Option<FinishedTurn>) {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        let thread_id = params["threadId"].as_str().map(str::to_string);
+        let activity = self.activity.entry(workspace_id.to_string()).or_default();
+        let before = (activity.running.len(), activity.approvals.len());
+        let mut finished = None;
+        match method {
+            "turn/started" => {
+                if let Some(thread_id) = thread_id {
+                    if !activity.running.contains(&thread_id) {
+                        activity.running.push(thread_id);
+                    }
+                }
+            }
+            "turn/completed" => {
+                activity.running.retain(|id| Some(id) != thread_id.as_ref());
+                activity
+                    .approvals
+                    .retain(|_, thread| thread.is_some() && *thread != thread_id);
+                let error = params["turn"]["error"]
+                    .as_str()
+                    .or_else(|| params["turn"]["error"]["message"].as_str())
+                    .map(str::to_string)
+                    .or_else(|| {
+                        (params["turn"]["status"] == "failed").then(|| "Turn failed".to_string())
+                    });
+                finished = Some(FinishedTurn {
+                    workspace_id: workspace_id.to_string(),
+                    error,
+                });
+            }
+            "approval/autoResolved" | "approval/timeout" => {
+                activity
+                    .approvals
+                    .remove(&request_key(&params["requestId"]));
+            }
+            "codex/disconnected" => {
+                activity.running.clear();
+                activity.approvals.clear();
+            }
+            _ if is_approval_request_method(method) => {
+                if let Some(id) = message.get("id") {
+                    activity.approvals.insert(request_key(id), thread_id);
+                }
+            }
+            _ => {}
+        }
+        let changed = before != (activity.running.len(), activity.approvals.len());
+        (changed, finished)
+    }
+
+    fn answer_approval(&mut self, workspace_id: &str, request_id: &Value) -> bool {
+        self.activity.get_mut(workspace_id).is_some_and(|activity| {
+            activity
+                .approvals
+                .remove(&request_key(request_id))
+                .is_some()
+        })
+    }
+
+    fn counts(&self, workspace_id: &str) -> (usize, usize) {
+        self.activity
+            .get(workspace_id)
+            .map(|activity| (activity.running.len(), activity.approvals.len()))
+            .unwrap_or_default()
+    }
+
+    fn totals(&self) -> (usize, usize) {
+        self.activity
+            .values()
+            .fold((0, 0), |(running, approvals), activity| {
+                (
+                    running + activity.running.len(),
+                    approvals + activity.approvals.len(),
+                )
+            })
+    }
+
+    fn workspace_name(&self, workspace_id: &str) -> Option<&str> {
+        self.workspaces
+            .iter()
+            .find(|(id, _)| id == workspace_id)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// "2 turns running, 1 awaiting approval", or `None` when both are zero.
+fn describe_counts(running: usize, approvals: usize) -> Option<String> {
+    let mut parts = Vec::new();
+    if running > 0 {
+        let noun = if running == 1 { "turn" } else { "turns" };
+        parts.push(format!("{running} {noun} running"));
+    }
+    if approvals > 0 {
+        parts.push(format!("{approvals} awaiting approval"));
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+fn build_tray_menu(app: &AppHandle, status: &TrayStatus) -> tauri::Result<Menu<tauri::Wry>> {
+    let (running, approvals) = status.totals();
+    let summary = describe_counts(running, approvals).unwrap_or_else(|| "Idle".to_string());
+    let menu = Menu::new(app)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "tray_status",
+        summary,
+        false,
+        None::<&str>,
+    )?)?;
+    menu.append(&PredefinedMenuItem::separator(app)?)?;
+    for (id, name) in &status.workspaces {
+        let (running, approvals) = status.counts(id);
+        let label = match describe_counts(running, approvals) {
+            Some(counts) => format!("{name} — {counts}"),
+            None => name.clone(),
+        };
+        let item_id = format!("{WORKSPACE_ITEM_PREFIX}{id}");
+        menu.append(&MenuItem::with_id(app, item_id, label, true, None::<&str>)?)?;
+    }
+    if !status.workspaces.is_empty() {
+        menu.append(&PredefinedMenuItem::separator(app)?)?;
+    }
+    menu.append(&MenuItem::with_id(
+        app,
+        "tray_show",
+        "Show Agent Monitor",
+        true,
+        None::<&str>,
+    )?)?;
+    menu.append(&MenuItem::with_id(
+        app,
+        "tray_quit",
+        "Quit",
+        true,
+        None::<&str>,
+    )?)?;
+    Ok(menu)
+}
+
+/// Rebuilds the tray menu and tooltip from the current status.
+fn refresh(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let Some(status) = app.try_state::<Mutex<TrayStatus>>() else {
+        return;
+    };
+    let Ok(status) = status.lock() else {
+        return;
+    };
+    let (running, approvals) = status.totals();
+    let tooltip = match describe_counts(running, approvals) {
+        Some(counts) => format!("Agent Monitor: {counts}"),
+        None => "Agent Monitor".to_string(),
+    };
+    if let Ok(menu) = build_tray_menu(app, &status) {
+        let _ = tray.set_menu(Some(menu));
+    }
+    let _ = tray.set_tooltip(Some(tooltip));
+}
+
+fn show_main_window(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn handle_tray_menu_event(app: &AppHandle, event: MenuEvent) {
+    match event.id().as_ref() {
+        "tray_show" => show_main_window(app),
+        "tray_quit" => app.exit(0),
+        id => {
+            if let Some(workspace_id) = id.strip_prefix(WORKSPACE_ITEM_PREFIX) {
+                show_main_window(app);
+                let _ = app.emit("tray-open-workspace", workspace_id);
+            }
+        }
+    }
+}
+
+/// Whether closing the main window should hide it, keeping sessions and
+/// turns running. Always the case on macOS, where apps stay open without
+/// windows; elsewhere only with `backgroundMode` and a tray icon to bring
+/// the window back.
+pub(crate) fn hides_on_close(app: &AppHandle) -> bool {
+    if cfg!(target_os = "macos") {
+        return true;
+    }
+    app.tray_by_id(TRAY_ID).is_some()
+        && app
+            .try_state::<AppState>()
+            .is_some_and(|state| state.app_settings.blocking_lock().background_mode)
+}
+
+/// Adds the tray icon. The status itself is managed from `run`.
+pub(crate) fn init_tray(app: &AppHandle) -> tauri::Result<()> {
+    let status = app.state::<Mutex<TrayStatus>>();
+    let menu = match status.lock() {
+        Ok(status) => build_tray_menu(app, &status)?,
+        Err(_) => Menu::new(app)?,
+    };
+    let mut builder = TrayIconBuilder::with_id(TRAY_ID)
+        .tooltip("Agent Monitor")
+        .menu(&menu)
+        .on_menu_event(handle_tray_menu_event);
+    if let Some(icon) = app.default_window_icon() {
+        builder = builder.icon(icon.clone().to_owned());
+    }
+    builder.build(app)?;
+    Ok(())
+}
+
+/// Only notifies while the main window is hidden or minimized; otherwise
+/// the frontend's own notifications apply.
+async fn notify_turn_finished(app: AppHandle, turn: FinishedTurn, name: Option<String>) {
+    let visible = app.get_webview_window("main").is_some_and(|window| {
+        window.is_visible().unwrap_or(false) && !window.is_minimized().unwrap_or(false)
+    });
+    let Some(state) = app.try_state::<AppState>() else {
+        return;
+    };
+    let enabled = state.app_settings.lock().await.system_notifications_enabled;
+    if visible || !enabled {
+        return;
+    }
+    let title = name.unwrap_or_else(|| turn.workspace_id.clone());
+    let body = match turn.error {
+        Some(error) => format!("Turn failed: {error}"),
+        None => "Turn completed".to_string(),
+    };
+    let _ = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(
+            body.chars()
+                .take(NOTIFICATION_BODY_MAX_CHARS)
+                .collect::<String>(),
+        )
+        .show();
+}
+
+/// Feeds one app-server event to the tray, from the local event sink and
+/// from events forwarded by a remote backend.
+pub(crate) fn observe_event(app: &AppHandle, event: &AppServerEvent) {
+    let Some(status) = app.try_state::<Mutex<TrayStatus>>() else {
+        return;
+    };
+    let (changed, finished, name) = {
+        let Ok(mut status) = status.lock() else {
+            return;
+        };
+        let (changed, finished) = status.observe(&event.workspace_id, &event.message);
+        let name = status
+            .workspace_name(&event.workspace_id)
+            .map(str::to_string);
+        (changed, finished, name)
+    };
+    if changed {
+        refresh(app);
+    }
+    if let Some(turn) = finished {
+        tauri::async_runtime::spawn(notify_turn_finished(app.clone(), turn, name));
+    }
+}
+
+/// Like `observe_event`, for the `{ workspace_id, message }` params of an
+/// event forwarded by a remote backend.
+pub(crate) fn observe_remote_event(app: &AppHandle, params: &Value) {
+    let Some(workspace_id) = params["workspace_id"].as_str() else {
+        return;
+    };
+    observe_event(
+        app,
+        &AppServerEvent {
+            workspace_id: workspace_id.to_string(),
+            message: params["message"].clone(),
+        },
+    );
+}
+
+/// Clears an approval answered from the app.
+pub(crate) fn approval_answered(app: &AppHandle, workspace_id: &str, request_id: &Value) {
+    let Some(status) = app.try_state::<Mutex<TrayStatus>>() else {
+        return;
+    };
+    let changed = status
+        .lock()
+        .is_ok_and(|mut status| status.answer_approval(workspace_id, request_id));
+    if changed {
+        refresh(app);
+    }
+}
+
+/// Updates the workspaces the tray menu can jump to.
+pub(crate) fn set_workspaces(app: &AppHandle, workspaces: &[WorkspaceInfo]) {
+    let Some(status) = app.try_state::<Mutex<TrayStatus>>() else {
+        return;
+    };
+    if let Ok(mut status) = status.lock() {
+        status.workspaces = workspaces
+            .iter()
+            .map(|workspace| (workspace.id.clone(), workspace.name.clone()))
+            .collect();
+    }
+    refresh(app);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{describe_counts, TrayStatus};
+    use serde_json::json;
+
+    #[test]
+    fn counts_running_turns_and_pending_approvals() {
+        let mut status = TrayStatus::default();
+        let started = json!({ "method": "turn/started", "params": { "threadId": "t-1" } });
+        assert!(status.observe("ws-1", &started).0);
+        assert!(!status.observe("ws-1", &started).0);
+        let approval = json!({
+            "id": 7,
+            "method": "item/commandExecution/requestApproval",
+            "params": { "threadId": "t-1" }
+        });
+        status.observe("ws-1", &approval);
+        status.observe(
+            "ws-2",
+            &json!({ "method": "turn/started", "params": { "threadId": "t-2" } }),
+        );
+        assert_eq!(status.totals(), (2, 1));
+        assert_eq!(
+            describe_counts(2, 1).as_deref(),
+            Some("2 turns running, 1 awaiting approval")
+        );
+
+        let completed = json!({
+            "method": "turn/completed",
+            "params": { "threadId": "t-1", "turn": { "id": "turn-1", "status": "failed", "error": "boom" } }
+        });
+        let (changed, finished) = status.observe("ws-1", &completed);
+        assert!(changed);
+        assert_eq!(
+            finished.expect("finished turn").error.as_deref(),
+            Some("boom")
+        );
+        assert_eq!(status.counts("ws-1"), (0, 0));
+        assert_eq!(status.totals(), (1, 0));
+        assert_eq!(describe_counts(0, 0), None);
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitFileStatus {
@@ -124,6 +125,13 @@ pub(crate) struct GitHubPullRequestsResponse {
     pub(crate) pull_requests: Vec<GitHubPullRequest>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct GitHubPullRequestCreated {
+    pub(crate) url: String,
+    pub(crate) branch: String,
+    pub(crate) title: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct GitHubPullRequestDiff {
     pub(crate) path: String,
@@ -250,6 +258,16 @@ pub(crate) struct WorktreeInfo {
     pub(crate) branch: String,
 }
 
+/// A removed workspace that `workspace_restore` can bring back until
+/// `purge_at`. Kept in `workspace-trash.json` next to `workspaces.json`.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TrashedWorkspace {
+    pub(crate) entry: WorkspaceEntry,
+    pub(crate) removed_at: u64,
+    pub(crate) purge_at: u64,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct WorkspaceGroup {
     pub(crate) id: String,
@@ -300,6 +318,376 @@ pub(crate) struct WorkspaceSettings {
     pub(crate) launch_scripts: Option<Vec<LaunchScriptEntry>>,
     #[serde(default, rename = "worktreeSetupScript")]
     pub(crate) worktree_setup_script: Option<String>,
+    #[serde(default, rename = "approvalTimeout")]
+    pub(crate) approval_timeout: Option<ApprovalTimeoutPolicy>,
+    #[serde(default, rename = "autoCommit")]
+    pub(crate) auto_commit: bool,
+    #[serde(default, rename = "turnSnapshots")]
+    pub(crate) turn_snapshots: bool,
+    #[serde(default, rename = "processLimits")]
+    pub(crate) process_limits: Option<ProcessLimits>,
+    /// Reasoning effort for Claude/Gemini turns, overriding the composer's.
+    #[serde(default, rename = "reasoningEffort")]
+    pub(crate) reasoning_effort: Option<String>,
+    #[serde(default, rename = "monthlyBudget")]
+    pub(crate) monthly_budget: Option<MonthlyBudget>,
+    /// How often, in milliseconds, a running turn's file changes are
+    /// reported as `files/changed`. Unset leaves the watcher off.
+    #[serde(default, rename = "fileWatchDebounceMs")]
+    pub(crate) file_watch_debounce_ms: Option<u64>,
+    #[serde(default, rename = "autoCompact")]
+    pub(crate) auto_compact: Option<AutoCompactPolicy>,
+    /// Seconds a Claude/Gemini/Cursor turn may run before its CLI is
+    /// killed, overriding `adapterTurnTimeoutSecs` (0 = no timeout).
+    #[serde(default, rename = "turnTimeoutSecs")]
+    pub(crate) turn_timeout_secs: Option<u64>,
+    /// Account profile picked per provider (`claude` -> `work`); its home
+    /// is used unless the provider's home is set explicitly.
+    #[serde(default, rename = "accountProfiles")]
+    pub(crate) account_profiles: HashMap<String, String>,
+    /// Spawn the workspace's CLI over SSH on another machine.
+    #[serde(default)]
+    pub(crate) ssh: Option<SshTarget>,
+    /// Spawn the workspace's CLI in a Docker container. Takes precedence
+    /// over `ssh`.
+    #[serde(default)]
+    pub(crate) docker: Option<DockerTarget>,
+    /// Extra environment variables for the workspace's CLI processes.
+    #[serde(default)]
+    pub(crate) env: HashMap<String, String>,
+    /// Codex config keys for this workspace only.
+    #[serde(default, rename = "codexConfig")]
+    pub(crate) codex_config: Option<CodexConfigOverrides>,
+    #[serde(default)]
+    pub(crate) guardrails: Vec<GuardrailRule>,
+    /// Checks run in the workspace folder after each turn completes.
+    #[serde(default, rename = "postTurnHooks")]
+    pub(crate) post_turn_hooks: Vec<PostTurnHook>,
+    /// Commands run in the workspace folder before each turn is started.
+    #[serde(default, rename = "preTurnHooks")]
+    pub(crate) pre_turn_hooks: Vec<PreTurnHook>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ApprovalTimeoutAction {
+    Wait,
+    Deny,
+    ApproveLowRisk,
+}
+
+impl Default for ApprovalTimeoutAction {
+    fn default() -> Self {
+        ApprovalTimeoutAction::Wait
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct ApprovalTimeoutPolicy {
+    #[serde(rename = "timeoutSeconds")]
+    pub(crate) timeout_seconds: u64,
+    #[serde(default)]
+    pub(crate) action: ApprovalTimeoutAction,
+}
+
+/// Resource limits for a workspace's CLI process tree. A turn that exceeds
+/// either limit is interrupted.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ProcessLimits {
+    #[serde(default, rename = "maxMemoryMb")]
+    pub(crate) max_memory_mb: Option<u64>,
+    #[serde(default, rename = "maxCpuSeconds")]
+    pub(crate) max_cpu_seconds: Option<u64>,
+}
+
+/// Compacts a thread between turns once its context reaches
+/// `thresholdPercent` of the model's context window.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct AutoCompactPolicy {
+    #[serde(rename = "thresholdPercent")]
+    pub(crate) threshold_percent: u8,
+    /// Context window in tokens, for CLIs that don't report one (Claude,
+    /// Gemini, Cursor).
+    #[serde(default, rename = "contextWindow")]
+    pub(crate) context_window: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum GuardrailAction {
+    /// Emit `guardrail/triggered` and let the turn continue.
+    #[default]
+    Warn,
+    /// Emit `guardrail/triggered` and interrupt the turn.
+    Interrupt,
+}
+
+/// A regex matched against the inputs of the agent's tool calls (commands,
+/// tool arguments, file changes).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct GuardrailRule {
+    pub(crate) pattern: String,
+    #[serde(default)]
+    pub(crate) action: GuardrailAction,
+}
+
+/// A shell command, such as `cargo test` or `npm run lint`, that verifies an
+/// agent turn; a non-zero exit fails the verification.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct PostTurnHook {
+    pub(crate) command: String,
+    /// Seconds before the command is killed; defaults to 10 minutes.
+    #[serde(default, rename = "timeoutSecs")]
+    pub(crate) timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum PreTurnHookFailure {
+    /// Refuse the turn.
+    #[default]
+    Abort,
+    /// Start the turn anyway.
+    Continue,
+}
+
+/// A shell command, such as `git pull` or `docker compose up -d`, that
+/// prepares the workspace before an agent turn starts.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct PreTurnHook {
+    pub(crate) command: String,
+    /// Seconds before the command is killed; defaults to 10 minutes.
+    #[serde(default, rename = "timeoutSecs")]
+    pub(crate) timeout_secs: Option<u64>,
+    #[serde(default, rename = "onFailure")]
+    pub(crate) on_failure: PreTurnHookFailure,
+}
+
+/// A shell command run for each event whose method matches `method`, where
+/// `*` matches any run of characters (`turn/*`). The event JSON is written
+/// to the command's stdin.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct EventHook {
+    pub(crate) method: String,
+    /// Only events from this workspace; all workspaces when unset.
+    #[serde(default, rename = "workspaceId")]
+    pub(crate) workspace_id: Option<String>,
+    pub(crate) command: String,
+    /// Seconds before the command is killed; defaults to 30.
+    #[serde(default, rename = "timeoutSecs")]
+    pub(crate) timeout_secs: Option<u64>,
+}
+
+/// Codex `config.toml` keys set for one workspace. They are passed to its
+/// app-server as `-c key=value` overrides, leaving the global file alone.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub(crate) struct CodexConfigOverrides {
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    /// `untrusted`, `on-failure`, `on-request` or `never`.
+    #[serde(default, rename = "approvalPolicy")]
+    pub(crate) approval_policy: Option<String>,
+    /// `read-only`, `workspace-write` or `danger-full-access`.
+    #[serde(default, rename = "sandboxMode")]
+    pub(crate) sandbox_mode: Option<String>,
+}
+
+/// Runs a workspace's CLI on another machine over SSH instead of locally.
+/// Authentication is key-based only; password prompts are disabled.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub(crate) struct SshTarget {
+    /// `user@host:/path`; the CLI runs in `/path` on `host`.
+    pub(crate) destination: String,
+    #[serde(default)]
+    pub(crate) port: Option<u16>,
+    /// Private key passed to `ssh -i`; the SSH agent and config are used
+    /// otherwise.
+    #[serde(default, rename = "identityFile")]
+    pub(crate) identity_file: Option<String>,
+}
+
+/// Runs a workspace's CLI inside a Docker container: a fresh `docker run`
+/// of `image` per CLI process, or `docker exec` into a running `container`.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub(crate) struct DockerTarget {
+    #[serde(default)]
+    pub(crate) image: Option<String>,
+    /// A running container (a dev container, say); takes precedence over
+    /// `image`.
+    #[serde(default)]
+    pub(crate) container: Option<String>,
+    /// Extra `docker run` volumes (`/host/path:/container/path[:ro]`). The
+    /// workspace itself is always mounted at `workdir`.
+    #[serde(default)]
+    pub(crate) mounts: Vec<String>,
+    /// Variables set in the container on top of the workspace env.
+    #[serde(default)]
+    pub(crate) env: HashMap<String, String>,
+    /// Working directory in the container (default `/workspace`).
+    #[serde(default)]
+    pub(crate) workdir: Option<String>,
+}
+
+/// Spending cap for a workspace's turns in a calendar month. Either limit
+/// may be set; usage is counted from the stored turn metrics.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub(crate) struct MonthlyBudget {
+    #[serde(default, rename = "maxUsd")]
+    pub(crate) max_usd: Option<f64>,
+    #[serde(default, rename = "maxTokens")]
+    pub(crate) max_tokens: Option<u64>,
+}
+
+/// An MCP server as defined in the Codex `config.toml` and/or Gemini
+/// `settings.json`, normalized to one shape.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct McpServer {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) command: Option<String>,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    #[serde(default)]
+    pub(crate) env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub(crate) url: Option<String>,
+    #[serde(default = "default_mcp_server_enabled")]
+    pub(crate) enabled: bool,
+    /// Configs that define the server (`codex`, `gemini`). Ignored on add.
+    #[serde(default)]
+    pub(crate) sources: Vec<String>,
+}
+
+/// Starting point for `workspace_create_from_template`, kept in
+/// `templates.json` next to `workspaces.json`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceTemplate {
+    pub(crate) name: String,
+    /// CLI whose args `cli_args` sets; defaults to the app's current CLI.
+    #[serde(default)]
+    pub(crate) cli_type: Option<String>,
+    #[serde(default)]
+    pub(crate) cli_args: Option<String>,
+    #[serde(default)]
+    pub(crate) agent_profile: Option<String>,
+    #[serde(default)]
+    pub(crate) env: HashMap<String, String>,
+    /// Written to `AGENTS.md` unless the folder already has one.
+    #[serde(default)]
+    pub(crate) agents_md: Option<String>,
+}
+
+/// A saved chain of agent prompts and shell checks, run in order by
+/// `workflow_run`. Kept in `workflows.json` next to `workspaces.json`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Workflow {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) steps: Vec<WorkflowStep>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkflowStep {
+    pub(crate) workspace_id: String,
+    #[serde(flatten)]
+    pub(crate) action: WorkflowAction,
+    #[serde(default)]
+    pub(crate) on_failure: WorkflowFailurePolicy,
+    /// Extra attempts before `on_failure` applies.
+    #[serde(default)]
+    pub(crate) retries: u32,
+}
+
+/// What a step does. `{{previous}}` in a prompt or command is replaced with
+/// the previous step's output, and `{{stepN}}` with step N's (from 1).
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(crate) enum WorkflowAction {
+    /// Runs the prompt as a hidden, read-only turn; the output is the
+    /// assistant's reply.
+    Prompt { prompt: String },
+    /// Runs the command in the workspace folder; it fails on a non-zero
+    /// exit, and the output is its stdout.
+    #[serde(rename_all = "camelCase")]
+    Shell {
+        command: String,
+        #[serde(default)]
+        timeout_secs: Option<u64>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum WorkflowFailurePolicy {
+    /// End the run as failed.
+    #[default]
+    Stop,
+    /// Carry on with the next step; the failed step's output is empty.
+    Continue,
+}
+
+/// A git repository found by `workspaces_discover` that isn't registered yet.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct WorkspaceCandidate {
+    pub(crate) path: String,
+    pub(crate) name: String,
+    pub(crate) has_agents_md: bool,
+    pub(crate) has_claude_md: bool,
+    /// Agent profile whose instructions file is already in place, if any.
+    pub(crate) agent_profile: Option<String>,
+}
+
+/// A rollout from the Codex CLI's own session history
+/// (`$CODEX_HOME/sessions`), e.g. a session run in a terminal.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexSessionSummary {
+    pub(crate) id: String,
+    pub(crate) path: String,
+    pub(crate) cwd: String,
+    /// First prompt of the session.
+    pub(crate) preview: Option<String>,
+    /// Where the session ran (`cli`, `vscode`, `exec`, ...).
+    pub(crate) source: Option<String>,
+    pub(crate) created_at: u64,
+    pub(crate) updated_at: u64,
+}
+
+/// A `[profiles.<name>]` preset from the Codex `config.toml`. `active` is set
+/// for the profile named by the top-level `profile` key.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodexProfile {
+    pub(crate) name: String,
+    #[serde(default)]
+    pub(crate) model: Option<String>,
+    #[serde(default)]
+    pub(crate) model_provider: Option<String>,
+    #[serde(default)]
+    pub(crate) model_reasoning_effort: Option<String>,
+    #[serde(default)]
+    pub(crate) approval_policy: Option<String>,
+    #[serde(default)]
+    pub(crate) sandbox_mode: Option<String>,
+    #[serde(default)]
+    pub(crate) active: bool,
+}
+
+/// One finding from `codex_config_validate`. `line` is 1-based.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ConfigDiagnostic {
+    pub(crate) severity: String,
+    pub(crate) message: String,
+    #[serde(default)]
+    pub(crate) line: Option<usize>,
+    #[serde(default)]
+    pub(crate) key: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -331,6 +719,17 @@ pub(crate) struct OpenAppTarget {
     pub(crate) args: Vec<String>,
 }
 
+/// What quitting does with turns that are still running.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub(crate) enum ShutdownTurnPolicy {
+    /// Wait up to `shutdownTimeoutSecs` for them to finish, then interrupt.
+    #[default]
+    Wait,
+    /// Interrupt them right away.
+    Interrupt,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub(crate) struct AppSettings {
     #[serde(default = "default_cli_type", rename = "cliType")]
@@ -380,6 +779,87 @@ pub(crate) struct AppSettings {
     pub(crate) remote_backend_host: String,
     #[serde(default, rename = "remoteBackendToken")]
     pub(crate) remote_backend_token: Option<String>,
+    #[serde(default, rename = "remoteBackendTls")]
+    pub(crate) remote_backend_tls: bool,
+    #[serde(default, rename = "remoteBackendTlsCaPath")]
+    pub(crate) remote_backend_tls_ca_path: Option<String>,
+    /// Serve the read-only HTTP API on loopback; applies after a restart.
+    #[serde(default, rename = "httpApiEnabled")]
+    pub(crate) http_api_enabled: bool,
+    #[serde(default = "default_http_api_port", rename = "httpApiPort")]
+    pub(crate) http_api_port: u16,
+    /// Bearer token the HTTP API requires.
+    #[serde(default, rename = "httpApiToken")]
+    pub(crate) http_api_token: Option<String>,
+    #[serde(
+        default = "default_event_replay_buffer_size",
+        rename = "eventReplayBufferSize"
+    )]
+    pub(crate) event_replay_buffer_size: usize,
+    /// How long deltas for one item are collected into a single event; 0
+    /// sends each delta on its own.
+    #[serde(
+        default = "default_delta_batch_window_ms",
+        rename = "deltaBatchWindowMs"
+    )]
+    pub(crate) delta_batch_window_ms: u64,
+    #[serde(default, rename = "debugRpcInspector")]
+    pub(crate) debug_rpc_inspector: bool,
+    #[serde(
+        default = "default_auto_restart_sessions",
+        rename = "autoRestartSessions"
+    )]
+    pub(crate) auto_restart_sessions: bool,
+    #[serde(default, rename = "shutdownTurnPolicy")]
+    pub(crate) shutdown_turn_policy: ShutdownTurnPolicy,
+    #[serde(
+        default = "default_shutdown_timeout_secs",
+        rename = "shutdownTimeoutSecs"
+    )]
+    pub(crate) shutdown_timeout_secs: u64,
+    #[serde(default, rename = "maxConcurrentTurns")]
+    pub(crate) max_concurrent_turns: usize,
+    /// Regexes masked in agent output before it reaches the UI or logs.
+    #[serde(
+        default = "default_redaction_patterns",
+        rename = "redactionPatterns"
+    )]
+    pub(crate) redaction_patterns: Vec<String>,
+    /// Archive CLI adapter threads idle for this many days (0 = never).
+    #[serde(default, rename = "threadAutoArchiveDays")]
+    pub(crate) thread_auto_archive_days: u32,
+    /// Delete archived threads and their transcripts after this many days
+    /// (0 = never).
+    #[serde(default, rename = "threadPurgeArchivedDays")]
+    pub(crate) thread_purge_archived_days: u32,
+    /// Keep removed workspaces restorable for this many days (0 = remove
+    /// them right away).
+    #[serde(
+        default = "default_workspace_trash_retention_days",
+        rename = "workspaceTrashRetentionDays"
+    )]
+    pub(crate) workspace_trash_retention_days: u32,
+    /// Times a Claude/Gemini/Cursor turn is retried after a rate-limit error
+    /// (0 = never).
+    #[serde(default, rename = "adapterRateLimitRetries")]
+    pub(crate) adapter_rate_limit_retries: u32,
+    /// Stream Claude's extended-thinking blocks as reasoning items.
+    #[serde(default, rename = "claudeShowThinking")]
+    pub(crate) claude_show_thinking: bool,
+    /// Seconds a Claude/Gemini/Cursor turn may run before its CLI is killed
+    /// (0 = no timeout).
+    #[serde(default, rename = "adapterTurnTimeoutSecs")]
+    pub(crate) adapter_turn_timeout_secs: u64,
+    /// Add the PATH of the user's login shell (after its rc files run) to
+    /// the PATH CLIs are spawned with.
+    #[serde(default, rename = "resolveLoginShellPath")]
+    pub(crate) resolve_login_shell_path: bool,
+    /// Commands run with matching events on stdin.
+    #[serde(default, rename = "eventHooks")]
+    pub(crate) event_hooks: Vec<EventHook>,
+    /// The settings profile last switched to.
+    #[serde(default, rename = "settingsProfile")]
+    pub(crate) settings_profile: Option<String>,
     #[serde(default = "default_access_mode", rename = "defaultAccessMode")]
     pub(crate) default_access_mode: String,
     #[serde(
@@ -387,6 +867,8 @@ pub(crate) struct AppSettings {
         rename = "reviewDeliveryMode"
     )]
     pub(crate) review_delivery_mode: String,
+    #[serde(default, rename = "worktreesRoot")]
+    pub(crate) worktrees_root: Option<String>,
     #[serde(
         default = "default_composer_model_shortcut",
         rename = "composerModelShortcut"
@@ -502,6 +984,10 @@ pub(crate) struct AppSettings {
         rename = "systemNotificationsEnabled"
     )]
     pub(crate) system_notifications_enabled: bool,
+    /// Closing the main window hides it to the tray and leaves sessions and
+    /// turns running, instead of quitting.
+    #[serde(default, rename = "backgroundMode")]
+    pub(crate) background_mode: bool,
     #[serde(
         default = "default_experimental_collab_enabled",
         rename = "experimentalCollabEnabled"
@@ -618,6 +1104,10 @@ fn default_cli_type() -> String {
     "codex".to_string()
 }
 
+fn default_mcp_server_enabled() -> bool {
+    true
+}
+
 fn default_cursor_vim_mode() -> bool {
     false
 }
@@ -650,6 +1140,44 @@ fn default_remote_backend_host() -> String {
     "127.0.0.1:4732".to_string()
 }
 
+fn default_http_api_port() -> u16 {
+    4733
+}
+
+fn default_workspace_trash_retention_days() -> u32 {
+    30
+}
+
+fn default_auto_restart_sessions() -> bool {
+    true
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    30
+}
+
+fn default_redaction_patterns() -> Vec<String> {
+    [
+        r"sk-(?:ant-|proj-)?[A-Za-z0-9_-]{20,}",
+        r"gh[pousr]_[A-Za-z0-9]{36,}",
+        r"github_pat_[A-Za-z0-9_]{22,}",
+        r"AKIA[0-9A-Z]{16}",
+        r"AIza[0-9A-Za-z_-]{35}",
+        r"xox[abposr]-[A-Za-z0-9-]{10,}",
+    ]
+    .iter()
+    .map(|pattern| pattern.to_string())
+    .collect()
+}
+
+fn default_event_replay_buffer_size() -> usize {
+    500
+}
+
+fn default_delta_batch_window_ms() -> u64 {
+    16
+}
+
 fn default_ui_scale() -> f64 {
     1.0
 }
@@ -1060,8 +1588,31 @@ impl Default for AppSettings {
             backend_mode: BackendMode::Local,
             remote_backend_host: default_remote_backend_host(),
             remote_backend_token: None,
+            remote_backend_tls: false,
+            remote_backend_tls_ca_path: None,
+            http_api_enabled: false,
+            http_api_port: default_http_api_port(),
+            http_api_token: None,
+            event_replay_buffer_size: default_event_replay_buffer_size(),
+            delta_batch_window_ms: default_delta_batch_window_ms(),
+            debug_rpc_inspector: false,
+            auto_restart_sessions: default_auto_restart_sessions(),
+            shutdown_turn_policy: ShutdownTurnPolicy::default(),
+            shutdown_timeout_secs: default_shutdown_timeout_secs(),
+            max_concurrent_turns: 0,
+            redaction_patterns: default_redaction_patterns(),
+            thread_auto_archive_days: 0,
+            thread_purge_archived_days: 0,
+            workspace_trash_retention_days: default_workspace_trash_retention_days(),
+            adapter_rate_limit_retries: 0,
+            claude_show_thinking: false,
+            adapter_turn_timeout_secs: 0,
+            resolve_login_shell_path: false,
+            event_hooks: Vec::new(),
+            settings_profile: None,
             default_access_mode: "current".to_string(),
             review_delivery_mode: default_review_delivery_mode(),
+            worktrees_root: None,
             composer_model_shortcut: default_composer_model_shortcut(),
             composer_access_shortcut: default_composer_access_shortcut(),
             composer_reasoning_shortcut: default_composer_reasoning_shortcut(),
@@ -1089,6 +1640,7 @@ impl Default for AppSettings {
             code_font_size: default_code_font_size(),
             notification_sounds_enabled: true,
             system_notifications_enabled: true,
+            background_mode: false,
             preload_git_diffs: default_preload_git_diffs(),
             git_diff_ignore_whitespace_changes: default_git_diff_ignore_whitespace_changes(),
             experimental_collab_enabled: false,
@@ -1123,7 +1675,8 @@ impl Default for AppSettings {
 #[cfg(test)]
 mod tests {
     use super::{
-        AppSettings, BackendMode, WorkspaceEntry, WorkspaceGroup, WorkspaceKind, WorkspaceSettings,
+        AppSettings, ApprovalTimeoutAction, BackendMode, ShutdownTurnPolicy, WorkspaceEntry,
+        WorkspaceGroup, WorkspaceKind, WorkspaceSettings,
     };
 
     #[test]
@@ -1148,8 +1701,29 @@ mod tests {
         assert!(matches!(settings.backend_mode, BackendMode::Local));
         assert_eq!(settings.remote_backend_host, "127.0.0.1:4732");
         assert!(settings.remote_backend_token.is_none());
+        assert!(!settings.remote_backend_tls);
+        assert!(settings.remote_backend_tls_ca_path.is_none());
+        assert_eq!(settings.event_replay_buffer_size, 500);
+        assert_eq!(settings.delta_batch_window_ms, 16);
+        assert!(!settings.debug_rpc_inspector);
+        assert!(settings.auto_restart_sessions);
+        assert_eq!(settings.shutdown_turn_policy, ShutdownTurnPolicy::Wait);
+        assert_eq!(settings.shutdown_timeout_secs, 30);
+        assert_eq!(settings.max_concurrent_turns, 0);
+        assert!(settings
+            .redaction_patterns
+            .iter()
+            .any(|pattern| pattern.starts_with("AKIA")));
+        assert_eq!(settings.thread_auto_archive_days, 0);
+        assert_eq!(settings.thread_purge_archived_days, 0);
+        assert_eq!(settings.adapter_rate_limit_retries, 0);
+        assert!(!settings.claude_show_thinking);
+        assert_eq!(settings.adapter_turn_timeout_secs, 0);
+        assert!(!settings.resolve_login_shell_path);
+        assert!(settings.settings_profile.is_none());
         assert_eq!(settings.default_access_mode, "current");
         assert_eq!(settings.review_delivery_mode, "inline");
+        assert!(settings.worktrees_root.is_none());
         let expected_primary = if cfg!(target_os = "macos") {
             "cmd"
         } else {
@@ -1243,6 +1817,7 @@ mod tests {
         assert_eq!(settings.code_font_size, 11);
         assert!(settings.notification_sounds_enabled);
         assert!(settings.system_notifications_enabled);
+        assert!(!settings.background_mode);
         assert!(settings.preload_git_diffs);
         assert!(!settings.git_diff_ignore_whitespace_changes);
         assert!(settings.collaboration_modes_enabled);
@@ -1412,6 +1987,18 @@ mod tests {
         assert!(settings.gemini_bin.is_none());
         assert!(settings.cursor_bin.is_none());
         assert!(settings.claude_bin.is_none());
+        assert!(settings.approval_timeout.is_none());
+        assert!(!settings.auto_commit);
+        assert!(!settings.turn_snapshots);
+        assert!(settings.process_limits.is_none());
+        assert!(settings.reasoning_effort.is_none());
+        assert!(settings.monthly_budget.is_none());
+        assert!(settings.file_watch_debounce_ms.is_none());
+        assert!(settings.turn_timeout_secs.is_none());
+        assert!(settings.account_profiles.is_empty());
+        assert!(settings.ssh.is_none());
+        assert!(settings.docker.is_none());
+        assert!(settings.env.is_empty());
     }
 
     #[test]
@@ -1442,4 +2029,23 @@ mod tests {
         assert_eq!(settings.claude_home.as_deref(), Some(".claude"));
         assert_eq!(settings.claude_args.as_deref(), Some("--model sonnet"));
     }
+
+    #[test]
+    fn workspace_settings_deserialize_approval_timeout() {
+        let settings: WorkspaceSettings =
+            serde_json::from_str(r#"{"approvalTimeout":{"timeoutSeconds":120}}"#)
+                .expect("workspace settings deserialize");
+        let policy = settings.approval_timeout.expect("approval timeout");
+        assert_eq!(policy.timeout_seconds, 120);
+        assert_eq!(policy.action, ApprovalTimeoutAction::Wait);
+
+        let settings: WorkspaceSettings = serde_json::from_str(
+            r#"{"approvalTimeout":{"timeoutSeconds":30,"action":"approveLowRisk"}}"#,
+        )
+        .expect("workspace settings deserialize");
+        assert_eq!(
+            settings.approval_timeout.map(|policy| policy.action),
+            Some(ApprovalTimeoutAction::ApproveLowRisk)
+        );
+    }
 }
@@ -2,6 +2,8 @@ use std::env;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
+use crate::shared::path_env::{current_path_entries, merge_path_entries};
+
 #[allow(dead_code)]
 pub(crate) fn normalize_git_path(path: &str) -> String {
     path.replace('\\', "/")
@@ -57,10 +59,6 @@ pub(crate) fn resolve_git_binary() -> Result<PathBuf, String> {
 }
 
 pub(crate) fn git_env_path() -> String {
-    let mut paths: Vec<PathBuf> = env::var_os("PATH")
-        .map(|value| env::split_paths(&value).collect())
-        .unwrap_or_default();
-
     let defaults: &[&str] = if cfg!(windows) {
         &["C:\\Windows\\System32"]
     } else {
@@ -73,13 +71,7 @@ pub(crate) fn git_env_path() -> String {
             "/run/current-system/sw/bin",
         ]
     };
-
-    for candidate in defaults {
-        let path = PathBuf::from(candidate);
-        if !paths.contains(&path) {
-            paths.push(path);
-        }
-    }
+    let paths = merge_path_entries(current_path_entries(), defaults.iter().map(PathBuf::from));
 
     let joined = env::join_paths(paths).unwrap_or_else(|_| OsString::new());
     joined.to_string_lossy().to_string()
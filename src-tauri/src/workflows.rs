@@ -0,0 +1,83 @@
+use serde_json::json;
+use tauri::{AppHandle, State};
+
+use crate::event_sink::TauriEventSink;
+use crate::remote_backend;
+use crate::shared::workflows_core;
+use crate::state::AppState;
+use crate::types::Workflow;
+
+#[tauri::command]
+pub(crate) async fn workflows_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Workflow>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "workflows_list", json!({})).await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workflows_core::workflows_list_core(&state.storage_path)
+}
+
+#[tauri::command]
+pub(crate) async fn workflow_save(
+    workflow: Workflow,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Workflow>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workflow_save",
+            json!({ "workflow": workflow }),
+        )
+        .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workflows_core::workflow_save_core(&state.storage_path, workflow)
+}
+
+#[tauri::command]
+pub(crate) async fn workflow_delete(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<Vec<Workflow>, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "workflow_delete", json!({ "name": name }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workflows_core::workflow_delete_core(&state.storage_path, &name)
+}
+
+/// Starts a saved workflow and returns its run id; progress arrives as
+/// `workflow/step` and `workflow/completed` events.
+#[tauri::command]
+pub(crate) async fn workflow_run(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "workflow_run", json!({ "name": name }))
+                .await?;
+        return serde_json::from_value(response).map_err(|err| err.to_string());
+    }
+
+    workflows_core::workflow_run_core(
+        &name,
+        &state.storage_path,
+        &state.workspaces,
+        &state.sessions,
+        TauriEventSink::new(app),
+    )
+    .await
+}
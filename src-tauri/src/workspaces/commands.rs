@@ -28,15 +28,21 @@ use crate::backend::app_server::{CliSpawnConfig, WorkspaceSession};
 use crate::codex::spawn_workspace_session;
 use crate::git_utils::resolve_git_root;
 use crate::remote_backend;
+use crate::shared::app_error::{AppError, AppResult};
 #[cfg(target_os = "windows")]
 use crate::shared::process_core::{build_cmd_c_command, resolve_windows_executable};
 use crate::shared::process_core::{kill_child_process_tree, tokio_command};
 use crate::shared::sandbox_setup_core;
+use crate::shared::workspace_discovery_core;
+use crate::shared::workspace_templates_core;
+use crate::shared::workspace_trash_core;
 use crate::shared::workspaces_core;
 use crate::state::AppState;
 use crate::storage::write_workspaces;
+use crate::tray;
 use crate::types::{
-    WorkspaceEntry, WorkspaceInfo, WorkspaceKind, WorkspaceSettings, WorktreeSetupStatus,
+    TrashedWorkspace, WorkspaceCandidate, WorkspaceEntry, WorkspaceInfo, WorkspaceKind,
+    WorkspaceSettings, WorkspaceTemplate, WorktreeSetupStatus,
 };
 use crate::utils::{git_env_path, resolve_git_binary};
 
@@ -92,7 +98,7 @@ pub(crate) async fn read_workspace_file(
     path: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceFileResponse, String> {
+) -> AppResult<WorkspaceFileResponse> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -100,31 +106,40 @@ pub(crate) async fn read_workspace_file(
             "read_workspace_file",
             json!({ "workspaceId": workspace_id, "path": path }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    workspaces_core::read_workspace_file_core(
+    Ok(workspaces_core::read_workspace_file_core(
         &state.workspaces,
         &workspace_id,
         &path,
         |root, rel_path| read_workspace_file_inner(root, rel_path),
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
 pub(crate) async fn list_workspaces(
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Vec<WorkspaceInfo>, String> {
+) -> AppResult<Vec<WorkspaceInfo>> {
     if remote_backend::is_remote_mode(&*state).await {
         let response =
-            remote_backend::call_remote(&*state, app, "list_workspaces", json!({})).await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+            remote_backend::call_remote(&*state, app.clone(), "list_workspaces", json!({}))
+                .await
+                .map_err(AppError::Remote)?;
+        let workspaces: Vec<WorkspaceInfo> =
+            serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()))?;
+        tray::set_workspaces(&app, &workspaces);
+        return Ok(workspaces);
     }
 
-    Ok(workspaces_core::list_workspaces_core(&state.workspaces, &state.sessions).await)
+    let workspaces =
+        workspaces_core::list_workspaces_core(&state.workspaces, &state.sessions).await;
+    tray::set_workspaces(&app, &workspaces);
+    Ok(workspaces)
 }
 
 #[tauri::command]
@@ -132,7 +147,7 @@ pub(crate) async fn is_workspace_path_dir(
     path: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<bool, String> {
+) -> AppResult<bool> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -140,8 +155,9 @@ pub(crate) async fn is_workspace_path_dir(
             "is_workspace_path_dir",
             json!({ "path": path }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
     Ok(workspaces_core::is_workspace_path_dir_core(&path))
 }
@@ -152,7 +168,7 @@ pub(crate) async fn add_workspace(
     codex_bin: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     if remote_backend::is_remote_mode(&*state).await {
         let path = remote_backend::normalize_path_for_remote(path);
         let codex_bin = codex_bin.map(remote_backend::normalize_path_for_remote);
@@ -162,22 +178,167 @@ pub(crate) async fn add_workspace(
             "add_workspace",
             json!({ "path": path, "codex_bin": codex_bin }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    workspaces_core::add_workspace_core(
+    Ok(workspaces_core::add_workspace_core(
         path,
         codex_bin,
         &state.workspaces,
         &state.sessions,
         &state.app_settings,
         &state.storage_path,
+        &state.services.audit_log,
         |entry, config| {
             spawn_with_app(&app, entry, config)
         },
     )
-    .await
+    .await?)
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_templates_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<WorkspaceTemplate>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response =
+            remote_backend::call_remote(&*state, app, "workspace_templates_list", json!({}))
+                .await
+                .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_templates_core::workspace_templates_list_core(
+        &state.storage_path,
+    )?)
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_template_save(
+    template: WorkspaceTemplate,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<WorkspaceTemplate>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_template_save",
+            json!({ "template": template }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_templates_core::workspace_template_save_core(
+        &state.storage_path,
+        template,
+    )?)
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_template_delete(
+    name: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<WorkspaceTemplate>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_template_delete",
+            json!({ "name": name }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_templates_core::workspace_template_delete_core(
+        &state.storage_path,
+        &name,
+    )?)
+}
+
+/// Creates (or clones) a folder from a saved template and registers it as a
+/// workspace in one call.
+#[tauri::command]
+pub(crate) async fn workspace_create_from_template(
+    template: String,
+    path: String,
+    git_url: Option<String>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<WorkspaceInfo> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let path = remote_backend::normalize_path_for_remote(path);
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_create_from_template",
+            json!({ "template": template, "path": path, "gitUrl": git_url }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(
+        workspace_templates_core::workspace_create_from_template_core(
+            &template,
+            path,
+            git_url,
+            &state.workspaces,
+            &state.sessions,
+            &state.app_settings,
+            &state.storage_path,
+            &state.services.audit_log,
+            |root, args| {
+                workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
+                    run_git_command_owned(repo, args_owned)
+                })
+            },
+            |entry, config| spawn_with_app(&app, entry, config),
+        )
+        .await?,
+    )
+}
+
+/// Lists git repositories under `root_paths` that aren't workspaces yet.
+#[tauri::command]
+pub(crate) async fn workspaces_discover(
+    root_paths: Vec<String>,
+    max_depth: Option<u32>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<WorkspaceCandidate>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let root_paths: Vec<String> = root_paths
+            .into_iter()
+            .map(remote_backend::normalize_path_for_remote)
+            .collect();
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspaces_discover",
+            json!({ "rootPaths": root_paths, "maxDepth": max_depth }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_discovery_core::workspaces_discover_core(
+        root_paths,
+        max_depth,
+        &state.workspaces,
+        &state.app_settings,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -187,21 +348,25 @@ pub(crate) async fn add_clone(
     copies_folder: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     let copy_name = copy_name.trim().to_string();
     if copy_name.is_empty() {
-        return Err("Copy name is required.".to_string());
+        return Err(AppError::InvalidInput("Copy name is required.".to_string()));
     }
 
     let copies_folder = copies_folder.trim().to_string();
     if copies_folder.is_empty() {
-        return Err("Copies folder is required.".to_string());
+        return Err(AppError::InvalidInput(
+            "Copies folder is required.".to_string(),
+        ));
     }
     let copies_folder_path = PathBuf::from(&copies_folder);
     std::fs::create_dir_all(&copies_folder_path)
         .map_err(|e| format!("Failed to create copies folder: {e}"))?;
     if !copies_folder_path.is_dir() {
-        return Err("Copies folder must be a directory.".to_string());
+        return Err(AppError::InvalidInput(
+            "Copies folder must be a directory.".to_string(),
+        ));
     }
 
     let (source_entry, inherited_group_id) = {
@@ -209,7 +374,7 @@ pub(crate) async fn add_clone(
         let source_entry = workspaces
             .get(&source_workspace_id)
             .cloned()
-            .ok_or("source workspace not found")?;
+            .ok_or(AppError::WorkspaceNotFound)?;
         let inherited_group_id = if source_entry.kind.is_worktree() {
             source_entry
                 .parent_id
@@ -232,7 +397,7 @@ pub(crate) async fn add_clone(
     .await
     {
         let _ = tokio::fs::remove_dir_all(&destination_path).await;
-        return Err(error);
+        return Err(error.into());
     }
 
     if let Some(origin_url) = git_get_origin_url(&PathBuf::from(&source_entry.path)).await {
@@ -273,7 +438,7 @@ pub(crate) async fn add_clone(
         Ok(session) => session,
         Err(error) => {
             let _ = tokio::fs::remove_dir_all(&destination_path).await;
-            return Err(error);
+            return Err(error.into());
         }
     };
 
@@ -289,7 +454,7 @@ pub(crate) async fn add_clone(
         }
         session.kill().await;
         let _ = tokio::fs::remove_dir_all(&destination_path).await;
-        return Err(error);
+        return Err(error.into());
     }
 
     state
@@ -319,7 +484,7 @@ pub(crate) async fn add_worktree(
     copy_agents_md: Option<bool>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     let copy_agents_md = copy_agents_md.unwrap_or(true);
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
@@ -333,8 +498,9 @@ pub(crate) async fn add_worktree(
                 "copyAgentsMd": copy_agents_md
             }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
     let data_dir = app
@@ -342,7 +508,7 @@ pub(crate) async fn add_worktree(
         .app_data_dir()
         .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
 
-    workspaces_core::add_worktree_core(
+    Ok(workspaces_core::add_worktree_core(
         parent_id,
         branch,
         name,
@@ -369,7 +535,49 @@ pub(crate) async fn add_worktree(
             spawn_with_app(&app, entry, config)
         },
     )
-    .await
+    .await?)
+}
+
+#[tauri::command]
+pub(crate) async fn worktree_create(
+    parent_id: String,
+    branch: String,
+    name: Option<String>,
+    copy_agents_md: Option<bool>,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<WorkspaceInfo> {
+    add_worktree(parent_id, branch, name, copy_agents_md, state, app).await
+}
+
+#[tauri::command]
+pub(crate) async fn worktree_list(
+    parent_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<WorkspaceInfo>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "worktree_list",
+            json!({ "parentId": parent_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspaces_core::list_worktrees_core(parent_id, &state.workspaces, &state.sessions).await?)
+}
+
+#[tauri::command]
+pub(crate) async fn worktree_remove(
+    id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<()> {
+    remove_worktree(id, state, app).await
 }
 
 #[tauri::command]
@@ -377,7 +585,7 @@ pub(crate) async fn worktree_setup_status(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorktreeSetupStatus, String> {
+) -> AppResult<WorktreeSetupStatus> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -385,15 +593,19 @@ pub(crate) async fn worktree_setup_status(
             "worktree_setup_status",
             json!({ "workspaceId": workspace_id }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
     let data_dir = app
         .path()
         .app_data_dir()
         .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
-    workspaces_core::worktree_setup_status_core(&state.workspaces, &workspace_id, &data_dir).await
+    Ok(
+        workspaces_core::worktree_setup_status_core(&state.workspaces, &workspace_id, &data_dir)
+            .await?,
+    )
 }
 
 #[tauri::command]
@@ -401,7 +613,7 @@ pub(crate) async fn worktree_setup_mark_ran(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(&*state).await {
         remote_backend::call_remote(
             &*state,
@@ -409,7 +621,8 @@ pub(crate) async fn worktree_setup_mark_ran(
             "worktree_setup_mark_ran",
             json!({ "workspaceId": workspace_id }),
         )
-        .await?;
+        .await
+        .map_err(AppError::Remote)?;
         return Ok(());
     }
 
@@ -417,7 +630,10 @@ pub(crate) async fn worktree_setup_mark_ran(
         .path()
         .app_data_dir()
         .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
-    workspaces_core::worktree_setup_mark_ran_core(&state.workspaces, &workspace_id, &data_dir).await
+    Ok(
+        workspaces_core::worktree_setup_mark_ran_core(&state.workspaces, &workspace_id, &data_dir)
+            .await?,
+    )
 }
 
 #[tauri::command]
@@ -425,17 +641,21 @@ pub(crate) async fn remove_workspace(
     id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(&*state, app, "remove_workspace", json!({ "id": id })).await?;
+        remote_backend::call_remote(&*state, app, "remove_workspace", json!({ "id": id }))
+            .await
+            .map_err(AppError::Remote)?;
         return Ok(());
     }
 
-    workspaces_core::remove_workspace_core(
+    Ok(workspaces_core::remove_workspace_core(
         id,
         &state.workspaces,
         &state.sessions,
+        &state.app_settings,
         &state.storage_path,
+        &state.services.audit_log,
         |root, args| {
             workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
                 run_git_command_owned(repo, args_owned)
@@ -449,7 +669,52 @@ pub(crate) async fn remove_workspace(
         true,
         true,
     )
-    .await
+    .await?)
+}
+
+/// Removed workspaces that can still be restored.
+#[tauri::command]
+pub(crate) async fn workspace_trash_list(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<Vec<TrashedWorkspace>> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(&*state, app, "workspace_trash_list", json!({}))
+            .await
+            .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_trash_core::workspace_trash_list_core(
+        &state.storage_path,
+    )?)
+}
+
+#[tauri::command]
+pub(crate) async fn workspace_restore(
+    workspace_id: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<WorkspaceInfo> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_restore",
+            json!({ "workspaceId": workspace_id }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspace_trash_core::workspace_restore_core(
+        workspace_id,
+        &state.workspaces,
+        &state.storage_path,
+        &state.services.audit_log,
+    )
+    .await?)
 }
 
 #[tauri::command]
@@ -457,13 +722,15 @@ pub(crate) async fn remove_worktree(
     id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(&*state, app, "remove_worktree", json!({ "id": id })).await?;
+        remote_backend::call_remote(&*state, app, "remove_worktree", json!({ "id": id }))
+            .await
+            .map_err(AppError::Remote)?;
         return Ok(());
     }
 
-    workspaces_core::remove_worktree_core(
+    Ok(workspaces_core::remove_worktree_core(
         id,
         &state.workspaces,
         &state.sessions,
@@ -479,7 +746,7 @@ pub(crate) async fn remove_worktree(
                 .map_err(|err| format!("Failed to remove worktree folder: {err}"))
         },
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
@@ -488,7 +755,7 @@ pub(crate) async fn rename_worktree(
     branch: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -496,8 +763,9 @@ pub(crate) async fn rename_worktree(
             "rename_worktree",
             json!({ "id": id, "branch": branch }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
     let data_dir = app
@@ -505,7 +773,7 @@ pub(crate) async fn rename_worktree(
         .app_data_dir()
         .map_err(|err| format!("Failed to resolve app data dir: {err}"))?;
 
-    workspaces_core::rename_worktree_core(
+    Ok(workspaces_core::rename_worktree_core(
         id,
         branch,
         &data_dir,
@@ -534,7 +802,7 @@ pub(crate) async fn rename_worktree(
             spawn_with_app(&app, entry, config)
         },
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
@@ -544,7 +812,7 @@ pub(crate) async fn rename_worktree_upstream(
     new_branch: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(&*state).await {
         remote_backend::call_remote(
             &*state,
@@ -552,11 +820,12 @@ pub(crate) async fn rename_worktree_upstream(
             "rename_worktree_upstream",
             json!({ "id": id, "oldBranch": old_branch, "newBranch": new_branch }),
         )
-        .await?;
+        .await
+        .map_err(AppError::Remote)?;
         return Ok(());
     }
 
-    workspaces_core::rename_worktree_upstream_core(
+    Ok(workspaces_core::rename_worktree_upstream_core(
         id,
         old_branch,
         new_branch,
@@ -589,28 +858,33 @@ pub(crate) async fn rename_worktree_upstream(
             })
         },
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
 pub(crate) async fn apply_worktree_changes(
     workspace_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let (entry, parent) = {
         let workspaces = state.workspaces.lock().await;
         let entry = workspaces
             .get(&workspace_id)
             .cloned()
-            .ok_or("workspace not found")?;
+            .ok_or(AppError::WorkspaceNotFound)?;
         if !entry.kind.is_worktree() {
-            return Err("Not a worktree workspace.".to_string());
+            return Err(AppError::InvalidInput(
+                "Not a worktree workspace.".to_string(),
+            ));
         }
-        let parent_id = entry.parent_id.clone().ok_or("worktree parent not found")?;
+        let parent_id = entry
+            .parent_id
+            .clone()
+            .ok_or_else(|| "worktree parent not found".to_string())?;
         let parent = workspaces
             .get(&parent_id)
             .cloned()
-            .ok_or("worktree parent not found")?;
+            .ok_or_else(|| "worktree parent not found".to_string())?;
         (entry, parent)
     };
 
@@ -619,10 +893,10 @@ pub(crate) async fn apply_worktree_changes(
 
     let parent_status = run_git_command_bytes(&parent_root, &["status", "--porcelain"]).await?;
     if !String::from_utf8_lossy(&parent_status).trim().is_empty() {
-        return Err(
+        return Err(AppError::InvalidInput(
             "Your current branch has uncommitted changes. Please commit, stash, or discard them before applying worktree changes."
                 .to_string(),
-        );
+        ));
     }
 
     let mut patch: Vec<u8> = Vec::new();
@@ -662,7 +936,7 @@ pub(crate) async fn apply_worktree_changes(
     }
 
     if String::from_utf8_lossy(&patch).trim().is_empty() {
-        return Err("No changes to apply.".to_string());
+        return Err(AppError::InvalidInput("No changes to apply.".to_string()));
     }
 
     let git_bin = resolve_git_binary().map_err(|e| format!("Failed to run git: {e}"))?;
@@ -700,23 +974,25 @@ pub(crate) async fn apply_worktree_changes(
         stderr.trim()
     };
     if detail.is_empty() {
-        return Err("Git apply failed.".to_string());
+        return Err("Git apply failed.".to_string().into());
     }
 
     if detail.contains("Applied patch to") {
         if detail.contains("with conflicts") {
             return Err(
                 "Applied with conflicts. Resolve conflicts in the parent repo before retrying."
-                    .to_string(),
+                    .to_string()
+                    .into(),
             );
         }
         return Err(
             "Patch applied partially. Resolve changes in the parent repo before retrying."
-                .to_string(),
+                .to_string()
+                .into(),
         );
     }
 
-    Err(detail.to_string())
+    Err(detail.to_string().into())
 }
 
 #[tauri::command]
@@ -725,19 +1001,29 @@ pub(crate) async fn update_workspace_settings(
     settings: WorkspaceSettings,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     if remote_backend::is_remote_mode(&*state).await {
-        let response = remote_backend::call_remote(
+        let settings = serde_json::to_value(&settings).map_err(|err| err.to_string())?;
+        let response = match remote_backend::call_remote_or_queue(
             &*state,
             app,
             "update_workspace_settings",
             json!({ "id": id, "settings": settings }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?
+        {
+            Some(response) => response,
+            None => remote_backend::cached_workspace_with_settings(&*state, &id, settings)
+                .await
+                .ok_or_else(|| {
+                    "Remote backend is offline; settings will sync on reconnect.".to_string()
+                })?,
+        };
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    workspaces_core::update_workspace_settings_core(
+    Ok(workspaces_core::update_workspace_settings_core(
         id,
         settings,
         &state.workspaces,
@@ -751,7 +1037,7 @@ pub(crate) async fn update_workspace_settings(
             spawn_with_app(&app, entry, config)
         },
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
@@ -760,7 +1046,7 @@ pub(crate) async fn update_workspace_cli_bin(
     codex_bin: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     if remote_backend::is_remote_mode(&*state).await {
         let codex_bin = codex_bin.map(remote_backend::normalize_path_for_remote);
         let response = remote_backend::call_remote(
@@ -769,11 +1055,12 @@ pub(crate) async fn update_workspace_cli_bin(
             "update_workspace_cli_bin",
             json!({ "id": id, "codex_bin": codex_bin }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    workspaces_core::update_workspace_codex_bin_core(
+    Ok(workspaces_core::update_workspace_codex_bin_core(
         id,
         codex_bin,
         &state.workspaces,
@@ -781,7 +1068,7 @@ pub(crate) async fn update_workspace_cli_bin(
         &state.app_settings,
         &state.storage_path,
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
@@ -790,18 +1077,57 @@ pub(crate) async fn update_workspace_codex_bin(
     codex_bin: Option<String>,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<WorkspaceInfo, String> {
+) -> AppResult<WorkspaceInfo> {
     update_workspace_cli_bin(id, codex_bin, state, app).await
 }
 
+#[tauri::command]
+pub(crate) async fn workspace_relocate(
+    workspace_id: String,
+    new_path: String,
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> AppResult<WorkspaceInfo> {
+    if remote_backend::is_remote_mode(&*state).await {
+        let new_path = remote_backend::normalize_path_for_remote(new_path);
+        let response = remote_backend::call_remote(
+            &*state,
+            app,
+            "workspace_relocate",
+            json!({ "workspaceId": workspace_id, "newPath": new_path }),
+        )
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
+    }
+
+    Ok(workspaces_core::workspace_relocate_core(
+        workspace_id,
+        new_path,
+        &state.workspaces,
+        &state.sessions,
+        &state.app_settings,
+        &state.storage_path,
+        |root, args| {
+            workspaces_core::run_git_command_unit(root, args, |repo, args_owned| {
+                run_git_command_owned(repo, args_owned)
+            })
+        },
+        |entry, config| spawn_with_app(&app, entry, config),
+    )
+    .await?)
+}
+
 #[tauri::command]
 pub(crate) async fn connect_workspace(
     id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> AppResult<()> {
     if remote_backend::is_remote_mode(&*state).await {
-        remote_backend::call_remote(&*state, app, "connect_workspace", json!({ "id": id })).await?;
+        remote_backend::call_remote(&*state, app, "connect_workspace", json!({ "id": id }))
+            .await
+            .map_err(AppError::Remote)?;
         return Ok(());
     }
 
@@ -809,7 +1135,7 @@ pub(crate) async fn connect_workspace(
         eprintln!("sandbox setup skipped for workspace {}: {}", id, error);
     }
 
-    workspaces_core::connect_workspace_core(
+    Ok(workspaces_core::connect_workspace_core(
         id,
         &state.workspaces,
         &state.sessions,
@@ -818,7 +1144,7 @@ pub(crate) async fn connect_workspace(
             spawn_with_app(&app, entry, config)
         },
     )
-    .await
+    .await?)
 }
 
 #[tauri::command]
@@ -826,7 +1152,7 @@ pub(crate) async fn list_workspace_files(
     workspace_id: String,
     state: State<'_, AppState>,
     app: AppHandle,
-) -> Result<Vec<String>, String> {
+) -> AppResult<Vec<String>> {
     if remote_backend::is_remote_mode(&*state).await {
         let response = remote_backend::call_remote(
             &*state,
@@ -834,14 +1160,17 @@ pub(crate) async fn list_workspace_files(
             "list_workspace_files",
             json!({ "workspaceId": workspace_id }),
         )
-        .await?;
-        return serde_json::from_value(response).map_err(|err| err.to_string());
+        .await
+        .map_err(AppError::Remote)?;
+        return serde_json::from_value(response).map_err(|err| AppError::Remote(err.to_string()));
     }
 
-    workspaces_core::list_workspace_files_core(&state.workspaces, &workspace_id, |root| {
-        list_workspace_files_inner(root, usize::MAX)
-    })
-    .await
+    Ok(
+        workspaces_core::list_workspace_files_core(&state.workspaces, &workspace_id, |root| {
+            list_workspace_files_inner(root, usize::MAX)
+        })
+        .await?,
+    )
 }
 
 #[tauri::command]
@@ -850,7 +1179,7 @@ pub(crate) async fn open_workspace_in(
     app: Option<String>,
     args: Vec<String>,
     command: Option<String>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let target_label = command
         .as_ref()
         .map(|value| format!("command `{value}`"))
@@ -860,7 +1189,7 @@ pub(crate) async fn open_workspace_in(
     let status = if let Some(command) = command {
         let trimmed = command.trim();
         if trimmed.is_empty() {
-            return Err("Missing app or command".to_string());
+            return Err(AppError::InvalidInput("Missing app or command".to_string()));
         }
 
         #[cfg(target_os = "windows")]
@@ -902,7 +1231,7 @@ pub(crate) async fn open_workspace_in(
     } else if let Some(app) = app {
         let trimmed = app.trim();
         if trimmed.is_empty() {
-            return Err("Missing app or command".to_string());
+            return Err(AppError::InvalidInput("Missing app or command".to_string()));
         }
 
         #[cfg(target_os = "macos")]
@@ -926,7 +1255,7 @@ pub(crate) async fn open_workspace_in(
             .await
             .map_err(|error| format!("Failed to open app ({target_label}): {error}"))?
     } else {
-        return Err("Missing app or command".to_string());
+        return Err(AppError::InvalidInput("Missing app or command".to_string()));
     };
 
     if status.success() {
@@ -937,13 +1266,11 @@ pub(crate) async fn open_workspace_in(
         .code()
         .map(|code| format!("exit code {code}"))
         .unwrap_or_else(|| "terminated by signal".to_string());
-    Err(format!(
-        "Failed to open app ({target_label} returned {exit_detail})."
-    ))
+    Err(format!("Failed to open app ({target_label} returned {exit_detail}).").into())
 }
 
 #[tauri::command]
-pub(crate) async fn get_open_app_icon(app_name: String) -> Result<Option<String>, String> {
+pub(crate) async fn get_open_app_icon(app_name: String) -> AppResult<Option<String>> {
     #[cfg(target_os = "macos")]
     {
         let trimmed = app_name.trim().to_string();
@@ -66,6 +66,23 @@ fn workspace_with_id_and_kind(
             launch_script: None,
             launch_scripts: None,
             worktree_setup_script: None,
+            approval_timeout: None,
+            auto_commit: false,
+            turn_snapshots: false,
+            process_limits: None,
+            reasoning_effort: None,
+            monthly_budget: None,
+            file_watch_debounce_ms: None,
+            auto_compact: None,
+            turn_timeout_secs: None,
+            account_profiles: HashMap::new(),
+            ssh: None,
+            docker: None,
+            env: HashMap::new(),
+            codex_config: None,
+            guardrails: Vec::new(),
+            post_turn_hooks: Vec::new(),
+            pre_turn_hooks: Vec::new(),
         },
     }
 }
@@ -1755,6 +1755,7 @@ function MainApp() {
     onOpenSettings: () => openSettings(),
     onCycleAgent: handleCycleAgent,
     onCycleWorkspace: handleCycleWorkspace,
+    onOpenWorkspace: selectWorkspace,
     onToggleDebug: handleDebugClick,
     onToggleTerminal: handleToggleTerminal,
     sidebarCollapsed,
@@ -126,6 +126,11 @@ export function useAccountSwitching({
         return;
       }
 
+      if (method === "account/changed") {
+        void refreshAccountInfoRef.current(matchWorkspaceId);
+        return;
+      }
+
       if (method === "account/updated") {
         if (!accountSwitchingRef.current || accountSwitchCanceledRef.current) {
           return;
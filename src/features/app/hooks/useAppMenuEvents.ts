@@ -14,6 +14,7 @@ import {
   subscribeMenuToggleGitSidebar,
   subscribeMenuToggleProjectsSidebar,
   subscribeMenuToggleTerminal,
+  subscribeTrayOpenWorkspace,
 } from "../../../services/events";
 import type { WorkspaceInfo } from "../../../types";
 
@@ -27,6 +28,7 @@ type Params = {
   onOpenSettings: () => void;
   onCycleAgent: (direction: "next" | "prev") => void;
   onCycleWorkspace: (direction: "next" | "prev") => void;
+  onOpenWorkspace: (workspaceId: string) => void;
   onToggleDebug: () => void;
   onToggleTerminal: () => void;
   sidebarCollapsed: boolean;
@@ -47,6 +49,7 @@ export function useAppMenuEvents({
   onOpenSettings,
   onCycleAgent,
   onCycleWorkspace,
+  onOpenWorkspace,
   onToggleDebug,
   onToggleTerminal,
   sidebarCollapsed,
@@ -101,6 +104,10 @@ export function useAppMenuEvents({
     onCycleWorkspace("prev");
   });
 
+  useTauriEvent(subscribeTrayOpenWorkspace, (workspaceId) => {
+    onOpenWorkspace(workspaceId);
+  });
+
   useTauriEvent(subscribeMenuToggleDebugPanel, () => {
     onToggleDebug();
   });
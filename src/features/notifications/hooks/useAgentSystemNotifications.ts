@@ -124,6 +124,10 @@ export function useAgentSystemNotifications({
       if (isWindowFocused) {
         return false;
       }
+      // The tray notifies while the window is hidden.
+      if (document.visibilityState === "hidden") {
+        return false;
+      }
       const lastNotifiedAt = lastNotifiedAtByThread.current.get(threadKey);
       if (lastNotifiedAt && Date.now() - lastNotifiedAt < 1500) {
         return false;
@@ -5,6 +5,7 @@ import X from "lucide-react/dist/esm/icons/x";
 import type {
   AgentProfile,
   AgentProfileApplyMode,
+  AgentProfileTargetFile,
   AppSettings,
   CodexDoctorResult,
   DictationModelStatus,
@@ -38,6 +39,7 @@ import {
   isMobilePlatform,
   isWindowsPlatform,
 } from "../../../utils/platformPaths";
+import { profileSupportsTargetFile } from "../utils/agentProfiles";
 import { buildShortcutValue } from "../../../utils/shortcuts";
 import { clampUiScale } from "../../../utils/uiScale";
 import {
@@ -144,11 +146,6 @@ const normalizeOverrideValue = (value: string): string | null => {
   return trimmed ? trimmed : null;
 };
 
-const profileSupportsTargetFile = (
-  profile: AgentProfile,
-  targetFile: "AGENTS.md" | "CLAUDE.md",
-) => (targetFile === "CLAUDE.md" ? profile.hasClaude : profile.hasAgents);
-
 const normalizeWorktreeSetupScript = (
   value: string | null | undefined,
 ): string | null => {
@@ -410,9 +407,8 @@ export function SettingsView({
   const [activeAgentProfileMode, setActiveAgentProfileMode] = useState<
     "symlink" | "copy" | null
   >(null);
-  const [agentProfileTargetFile, setAgentProfileTargetFile] = useState<
-    "AGENTS.md" | "CLAUDE.md"
-  >("AGENTS.md");
+  const [agentProfileTargetFile, setAgentProfileTargetFile] =
+    useState<AgentProfileTargetFile>("AGENTS.md");
   const [agentProfilesLoading, setAgentProfilesLoading] = useState(false);
   const [agentProfilesApplying, setAgentProfilesApplying] = useState(false);
   const [agentProfilesError, setAgentProfilesError] = useState<string | null>(null);
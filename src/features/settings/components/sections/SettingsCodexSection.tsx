@@ -4,6 +4,7 @@ import type {
   AgentProfile,
   AgentProfileApplyMode,
   AgentProfileMode,
+  AgentProfileTargetFile,
   AppSettings,
   CliType,
   CodexDoctorResult,
@@ -20,6 +21,7 @@ import {
   withWorkspaceCliArgsOverride,
   withWorkspaceCliHomeOverride,
 } from "../../utils/cliBackend";
+import { profileSupportsTargetFile } from "../../utils/agentProfiles";
 
 type SettingsCodexSectionProps = {
   appSettings: AppSettings;
@@ -118,7 +120,7 @@ type SettingsCodexSectionProps = {
   agentProfiles: AgentProfile[];
   activeAgentProfile: string | null;
   activeAgentProfileMode: AgentProfileMode | null;
-  agentProfileTargetFile: "AGENTS.md" | "CLAUDE.md";
+  agentProfileTargetFile: AgentProfileTargetFile;
   selectedAgentProfile: string;
   onSetAgentProfilesWorkspaceId: Dispatch<SetStateAction<string | null>>;
   onSetSelectedAgentProfile: Dispatch<SetStateAction<string>>;
@@ -309,7 +311,7 @@ export function SettingsCodexSection({
             </option>
             {agentProfiles
               .filter((profile) =>
-                agentProfileTargetFile === "CLAUDE.md" ? profile.hasClaude : profile.hasAgents,
+                profileSupportsTargetFile(profile, agentProfileTargetFile),
               )
               .map((profile) => (
                 <option key={profile.name} value={profile.name}>
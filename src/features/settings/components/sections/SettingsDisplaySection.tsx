@@ -333,6 +333,28 @@ export function SettingsDisplaySection({
           <span className="settings-toggle-knob" />
         </button>
       </div>
+      <div className="settings-toggle-row">
+        <div>
+          <div className="settings-toggle-title">Keep running in the tray</div>
+          <div className="settings-toggle-subtitle">
+            Closing the window hides it to the tray and keeps agents running. Quit from the
+            tray menu.
+          </div>
+        </div>
+        <button
+          type="button"
+          className={`settings-toggle ${appSettings.backgroundMode ? "on" : ""}`}
+          onClick={() =>
+            void onUpdateAppSettings({
+              ...appSettings,
+              backgroundMode: !appSettings.backgroundMode,
+            })
+          }
+          aria-pressed={Boolean(appSettings.backgroundMode)}
+        >
+          <span className="settings-toggle-knob" />
+        </button>
+      </div>
       <div className="settings-sound-actions">
         <button
           type="button"
@@ -0,0 +1,17 @@
+import type { AgentProfile, AgentProfileTargetFile } from "../../../types";
+
+export const profileSupportsTargetFile = (
+  profile: AgentProfile,
+  targetFile: AgentProfileTargetFile,
+) => {
+  switch (targetFile) {
+    case "CLAUDE.md":
+      return profile.hasClaude;
+    case "GEMINI.md":
+      return profile.hasGemini;
+    case ".cursorrules":
+      return profile.hasCursorRules;
+    default:
+      return profile.hasAgents;
+  }
+};
@@ -39,7 +39,10 @@ function normalizeAccountSnapshot(
 
   const typeRaw =
     typeof account.type === "string" ? account.type.toLowerCase() : "unknown";
-  const type = typeRaw === "chatgpt" || typeRaw === "apikey" ? typeRaw : "unknown";
+  const type =
+    typeRaw === "chatgpt" || typeRaw === "apikey" || typeRaw === "oauth"
+      ? typeRaw
+      : "unknown";
   const emailRaw = typeof account.email === "string" ? account.email.trim() : "";
   const planRaw =
     typeof account.planType === "string" ? account.planType.trim() : "";
@@ -120,7 +120,8 @@ export function useThreadEventHandlers({
   const onAppServerEvent = useCallback(
     (event: AppServerEvent) => {
       const method = getAppServerRawMethod(event) ?? "";
-      const inferredSource = method === "codex/stderr" ? "stderr" : "event";
+      const inferredSource =
+        method === "codex/stderr" || method === "cli/stderr" ? "stderr" : "event";
       onDebug?.({
         id: `${Date.now()}-server-event`,
         timestamp: Date.now(),
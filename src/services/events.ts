@@ -92,6 +92,7 @@ const menuNewWorktreeAgentHub = createEventHub<void>("menu-new-worktree-agent");
 const menuNewCloneAgentHub = createEventHub<void>("menu-new-clone-agent");
 const menuAddWorkspaceHub = createEventHub<void>("menu-add-workspace");
 const menuOpenSettingsHub = createEventHub<void>("menu-open-settings");
+const trayOpenWorkspaceHub = createEventHub<string>("tray-open-workspace");
 const menuToggleProjectsSidebarHub = createEventHub<void>("menu-toggle-projects-sidebar");
 const menuToggleGitSidebarHub = createEventHub<void>("menu-toggle-git-sidebar");
 const menuToggleDebugPanelHub = createEventHub<void>("menu-toggle-debug-panel");
@@ -254,6 +255,13 @@ export function subscribeMenuPrevAgent(
   }, options);
 }
 
+export function subscribeTrayOpenWorkspace(
+  onEvent: (workspaceId: string) => void,
+  options?: SubscriptionOptions,
+): Unsubscribe {
+  return trayOpenWorkspaceHub.subscribe(onEvent, options);
+}
+
 export function subscribeMenuNextWorkspace(
   onEvent: () => void,
   options?: SubscriptionOptions,
@@ -2,8 +2,10 @@ import { beforeEach, describe, expect, it, vi } from "vitest";
 import { invoke } from "@tauri-apps/api/core";
 import * as notification from "@tauri-apps/plugin-notification";
 import {
+  AppError,
   addWorkspace,
   compactThread,
+  detectInstalledClis,
   fetchGit,
   forkThread,
   getGitHubIssues,
@@ -551,4 +553,25 @@ describe("tauri invoke wrappers", () => {
     });
     expect(isPermissionGrantedMock).not.toHaveBeenCalled();
   });
+
+  it("rethrows structured command errors as AppError", async () => {
+    const invokeMock = vi.mocked(invoke);
+    invokeMock.mockRejectedValueOnce({
+      code: "cli_not_found",
+      message: "Codex CLI not found.",
+      retriable: false,
+    });
+
+    const error = await detectInstalledClis().catch((err: unknown) => err);
+
+    expect(invokeMock).toHaveBeenCalledWith("detect_installed_clis", {
+      forceRefresh: false,
+    });
+    expect(error).toBeInstanceOf(AppError);
+    expect((error as AppError).code).toBe("cli_not_found");
+    expect(String(error)).toBe("Codex CLI not found.");
+
+    invokeMock.mockRejectedValueOnce("workspace not found");
+    await expect(detectInstalledClis()).rejects.toBe("workspace not found");
+  });
 });
@@ -1,27 +1,46 @@
-import { invoke } from "@tauri-apps/api/core";
+import { invoke as tauriInvoke, type InvokeArgs } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 import type { Options as NotificationOptions } from "@tauri-apps/plugin-notification";
 import type {
+  AccountProfile,
+  AccountProfileProvider,
   AgentProfileApplyMode,
   AgentProfileApplyResponse,
   AgentProfileListResponse,
+  AgentProfileStatus,
+  AgentProfileSyncAction,
   AgentDoctorResult,
+  AgentsMdSectionsResponse,
+  AppErrorCode,
+  AppErrorPayload,
   AppSettings,
+  AppStateTransfer,
+  AuditLogQueryResult,
+  BackgroundCallbackInfo,
+  BackgroundTurnResult,
+  CliPlugins,
+  CliUpdateStatus,
   DetectedClis,
   DictationModelStatus,
   DictationSessionState,
   LocalUsageCliFilter,
   LocalUsageSnapshot,
+  MonthlyBudget,
   OrbitConnectTestResult,
   OrbitDeviceCodeStart,
   OrbitRunnerStatus,
   OrbitSignInPollResult,
   OrbitSignOutResult,
+  PendingRequestInfo,
+  SettingsProfile,
   TailscaleDaemonCommandPreview,
   TailscaleStatus,
+  TrashedWorkspace,
   WorkspaceInfo,
   WorkspaceSettings,
+  WorkspaceTreeResponse,
 } from "../types";
+
 import type {
   GitFileDiff,
   GitFileStatus,
@@ -34,6 +53,43 @@ import type {
   ReviewTarget,
 } from "../types";
 
+/** A command failure carrying the backend's error code. */
+export class AppError extends Error {
+  readonly code: AppErrorCode;
+  readonly retriable: boolean;
+
+  constructor(payload: AppErrorPayload) {
+    super(payload.message);
+    this.name = "AppError";
+    this.code = payload.code;
+    this.retriable = payload.retriable;
+  }
+
+  toString() {
+    return this.message;
+  }
+}
+
+function isAppErrorPayload(value: unknown): value is AppErrorPayload {
+  return (
+    typeof value === "object" &&
+    value !== null &&
+    typeof (value as AppErrorPayload).code === "string" &&
+    typeof (value as AppErrorPayload).message === "string"
+  );
+}
+
+// Commands returning `AppError` reject with `{ code, message, retriable }`;
+// those are rethrown as `AppError` so `String(error)` and `error.message`
+// keep working. Commands still rejecting with a string are passed through.
+async function invoke<T>(cmd: string, args?: InvokeArgs): Promise<T> {
+  try {
+    return await (args === undefined ? tauriInvoke<T>(cmd) : tauriInvoke<T>(cmd, args));
+  } catch (error) {
+    throw isAppErrorPayload(error) ? new AppError(error) : error;
+  }
+}
+
 function isMissingTauriInvokeError(error: unknown) {
   return (
     error instanceof TypeError &&
@@ -84,10 +140,64 @@ export async function getCodexConfigPath(): Promise<string> {
   return invoke<string>("get_codex_config_path");
 }
 
+export type ConfigDiagnostic = {
+  severity: "error" | "warning";
+  message: string;
+  line?: number | null;
+  key?: string | null;
+};
+
+export async function codexConfigValidate(): Promise<ConfigDiagnostic[]> {
+  return invoke<ConfigDiagnostic[]>("codex_config_validate");
+}
+
+export type CodexProfile = {
+  name: string;
+  model?: string | null;
+  modelProvider?: string | null;
+  modelReasoningEffort?: string | null;
+  approvalPolicy?: string | null;
+  sandboxMode?: string | null;
+  active: boolean;
+};
+
+export async function codexProfilesList(): Promise<CodexProfile[]> {
+  return invoke<CodexProfile[]>("codex_profiles_list");
+}
+
+export async function codexProfileApply(
+  name: string | null,
+): Promise<CodexProfile[]> {
+  return invoke<CodexProfile[]>("codex_profile_apply", { name });
+}
+
+export async function codexProfileSaveCurrent(
+  name: string,
+): Promise<CodexProfile[]> {
+  return invoke<CodexProfile[]>("codex_profile_save_current", { name });
+}
+
+export type TextEncoding = "utf-8" | "utf-8-bom" | "utf-16le" | "utf-16be" | "latin-1";
+
 export type TextFileResponse = {
   exists: boolean;
   content: string;
   truncated: boolean;
+  encoding?: TextEncoding;
+};
+
+export type TextRangeResponse = {
+  exists: boolean;
+  content: string;
+  offset: number;
+  nextOffset: number;
+  fileSize: number;
+  encoding: TextEncoding;
+};
+
+export type FileTailSubscription = {
+  subscriptionId: string;
+  offset: number;
 };
 
 export type GlobalAgentsResponse = TextFileResponse;
@@ -95,7 +205,14 @@ export type GlobalCodexConfigResponse = TextFileResponse;
 export type AgentMdResponse = TextFileResponse;
 
 type FileScope = "workspace" | "global";
-type FileKind = "agents" | "config";
+type FileKind =
+  | "agents"
+  | "config"
+  | "claude"
+  | "gemini"
+  | "cursor_rules"
+  | "codex_instructions"
+  | "mcp";
 
 async function fileRead(
   scope: FileScope,
@@ -149,6 +266,92 @@ export async function addWorkspace(
   return invoke<WorkspaceInfo>("add_workspace", { path, codex_bin });
 }
 
+export type WorkspaceTemplate = {
+  name: string;
+  cliType?: string | null;
+  cliArgs?: string | null;
+  agentProfile?: string | null;
+  env?: Record<string, string>;
+  agentsMd?: string | null;
+};
+
+export async function workspaceTemplatesList(): Promise<WorkspaceTemplate[]> {
+  return invoke<WorkspaceTemplate[]>("workspace_templates_list");
+}
+
+export async function workspaceTemplateSave(
+  template: WorkspaceTemplate,
+): Promise<WorkspaceTemplate[]> {
+  return invoke<WorkspaceTemplate[]>("workspace_template_save", { template });
+}
+
+export async function workspaceTemplateDelete(
+  name: string,
+): Promise<WorkspaceTemplate[]> {
+  return invoke<WorkspaceTemplate[]>("workspace_template_delete", { name });
+}
+
+export type WorkflowAction =
+  | { type: "prompt"; prompt: string }
+  | { type: "shell"; command: string; timeoutSecs?: number | null };
+
+export type WorkflowStep = WorkflowAction & {
+  workspaceId: string;
+  onFailure?: "stop" | "continue";
+  retries?: number;
+};
+
+export type Workflow = {
+  name: string;
+  steps: WorkflowStep[];
+};
+
+export async function workflowsList(): Promise<Workflow[]> {
+  return invoke<Workflow[]>("workflows_list");
+}
+
+export async function workflowSave(workflow: Workflow): Promise<Workflow[]> {
+  return invoke<Workflow[]>("workflow_save", { workflow });
+}
+
+export async function workflowDelete(name: string): Promise<Workflow[]> {
+  return invoke<Workflow[]>("workflow_delete", { name });
+}
+
+export async function workflowRun(name: string): Promise<string> {
+  return invoke<string>("workflow_run", { name });
+}
+
+export async function workspaceCreateFromTemplate(
+  template: string,
+  path: string,
+  gitUrl?: string | null,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("workspace_create_from_template", {
+    template,
+    path,
+    gitUrl: gitUrl ?? null,
+  });
+}
+
+export type WorkspaceCandidate = {
+  path: string;
+  name: string;
+  hasAgentsMd: boolean;
+  hasClaudeMd: boolean;
+  agentProfile: string | null;
+};
+
+export async function workspacesDiscover(
+  rootPaths: string[],
+  maxDepth?: number | null,
+): Promise<WorkspaceCandidate[]> {
+  return invoke<WorkspaceCandidate[]>("workspaces_discover", {
+    rootPaths,
+    maxDepth: maxDepth ?? null,
+  });
+}
+
 export async function isWorkspacePathDir(path: string): Promise<boolean> {
   return invoke<boolean>("is_workspace_path_dir", { path });
 }
@@ -210,10 +413,27 @@ export async function updateWorkspaceCodexBin(
   return updateWorkspaceCliBin(id, codex_bin);
 }
 
+export async function relocateWorkspace(
+  workspaceId: string,
+  newPath: string,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("workspace_relocate", { workspaceId, newPath });
+}
+
 export async function removeWorkspace(id: string): Promise<void> {
   return invoke("remove_workspace", { id });
 }
 
+export async function workspaceTrashList(): Promise<TrashedWorkspace[]> {
+  return invoke<TrashedWorkspace[]>("workspace_trash_list");
+}
+
+export async function restoreWorkspace(
+  workspaceId: string,
+): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>("workspace_restore", { workspaceId });
+}
+
 export async function removeWorktree(id: string): Promise<void> {
   return invoke("remove_worktree", { id });
 }
@@ -308,6 +528,20 @@ export async function interruptTurn(
   return invoke("turn_interrupt", { workspaceId, threadId, turnId });
 }
 
+export async function cancelRequest(workspaceId: string, requestId: number) {
+  return invoke<boolean>("request_cancel", { workspaceId, requestId });
+}
+
+export async function listPendingRequests(workspaceId: string) {
+  return invoke<PendingRequestInfo[]>("pending_requests", { workspaceId });
+}
+
+export async function listBackgroundCallbacks(workspaceId: string) {
+  return invoke<BackgroundCallbackInfo[]>("background_callbacks", {
+    workspaceId,
+  });
+}
+
 export async function startReview(
   workspaceId: string,
   threadId: string,
@@ -425,6 +659,34 @@ export async function pushGit(workspaceId: string): Promise<void> {
   return invoke("push_git", { workspaceId });
 }
 
+export type GitPullRequestCreateOptions = {
+  title?: string | null;
+  body?: string | null;
+  transcript?: string | null;
+  base?: string | null;
+  draft?: boolean;
+};
+
+export type GitHubPullRequestCreated = {
+  url: string;
+  branch: string;
+  title: string | null;
+};
+
+export async function gitPrCreate(
+  workspaceId: string,
+  options: GitPullRequestCreateOptions = {},
+): Promise<GitHubPullRequestCreated> {
+  return invoke("git_pr_create", {
+    workspaceId,
+    title: options.title ?? null,
+    body: options.body ?? null,
+    transcript: options.transcript ?? null,
+    base: options.base ?? null,
+    draft: options.draft ?? false,
+  });
+}
+
 export async function pullGit(workspaceId: string): Promise<void> {
   return invoke("pull_git", { workspaceId });
 }
@@ -499,6 +761,22 @@ export async function generateRunMetadata(workspaceId: string, prompt: string) {
   });
 }
 
+export async function startBackgroundTurn(
+  workspaceId: string,
+  prompt: string,
+  cliType?: string,
+) {
+  return invoke<string>("background_turn_start", {
+    workspaceId,
+    prompt,
+    cliType: cliType ?? null,
+  });
+}
+
+export async function getBackgroundTurnResult(turnId: string) {
+  return invoke<BackgroundTurnResult>("background_turn_result", { turnId });
+}
+
 export async function getCollaborationModes(workspaceId: string) {
   return invoke<any>("collaboration_mode_list", { workspaceId });
 }
@@ -611,8 +889,276 @@ export async function updateAppSettings(settings: AppSettings): Promise<AppSetti
   return invoke<AppSettings>("update_app_settings", { settings });
 }
 
-export async function detectInstalledClis(): Promise<DetectedClis> {
-  return invoke<DetectedClis>("detect_installed_clis");
+export async function detectInstalledClis(
+  forceRefresh = false,
+): Promise<DetectedClis> {
+  return invoke<DetectedClis>("detect_installed_clis", { forceRefresh });
+}
+
+export async function listCliPlugins(): Promise<CliPlugins> {
+  return invoke<CliPlugins>("cli_plugins_list");
+}
+
+export async function checkCliUpdates(): Promise<CliUpdateStatus[]> {
+  return invoke<CliUpdateStatus[]>("cli_updates_check");
+}
+
+export async function updateCli(name: CliUpdateStatus["name"]): Promise<CliUpdateStatus> {
+  return invoke<CliUpdateStatus>("cli_update", { name });
+}
+
+export async function listAccountProfiles(
+  provider?: AccountProfileProvider,
+): Promise<AccountProfile[]> {
+  return invoke<AccountProfile[]>("account_profiles_list", { provider: provider ?? null });
+}
+
+export async function createAccountProfile(
+  provider: AccountProfileProvider,
+  name: string,
+): Promise<AccountProfile> {
+  return invoke<AccountProfile>("account_profile_create", { provider, name });
+}
+
+export async function deleteAccountProfile(
+  provider: AccountProfileProvider,
+  name: string,
+): Promise<void> {
+  return invoke("account_profile_delete", { provider, name });
+}
+
+export async function exportAppState(path: string): Promise<AppStateTransfer> {
+  return invoke<AppStateTransfer>("app_state_export", { path });
+}
+
+export async function importAppState(path: string): Promise<AppStateTransfer> {
+  return invoke<AppStateTransfer>("app_state_import", { path });
+}
+
+export async function queryAuditLog(query: {
+  action?: string;
+  since?: string;
+  limit?: number;
+} = {}): Promise<AuditLogQueryResult> {
+  return invoke<AuditLogQueryResult>("audit_log_query", query);
+}
+
+export async function listSettingsProfiles(): Promise<SettingsProfile[]> {
+  return invoke<SettingsProfile[]>("settings_profiles_list");
+}
+
+export async function saveSettingsProfile(name: string): Promise<SettingsProfile[]> {
+  return invoke<SettingsProfile[]>("settings_profile_save", { name });
+}
+
+export async function deleteSettingsProfile(name: string): Promise<SettingsProfile[]> {
+  return invoke<SettingsProfile[]>("settings_profile_delete", { name });
+}
+
+export async function switchSettingsProfile(name: string): Promise<AppSettings> {
+  return invoke<AppSettings>("settings_profile_switch", { name });
+}
+
+export type RemoteBackendConnectionInfo = {
+  host: string;
+  tls: boolean;
+  authenticated: boolean;
+  /** `observer` connections can only read; writes are rejected. */
+  role: "operator" | "observer";
+  serverVersion: string | null;
+  latencyMs: number;
+};
+
+export type RemoteBackendConnectionOverrides = {
+  host?: string;
+  token?: string | null;
+  tls?: boolean;
+  tlsCaPath?: string | null;
+};
+
+export async function remoteBackendTestConnection(
+  overrides: RemoteBackendConnectionOverrides = {},
+): Promise<RemoteBackendConnectionInfo> {
+  return invoke<RemoteBackendConnectionInfo>("remote_backend_test_connection", {
+    host: overrides.host ?? null,
+    token: overrides.token ?? null,
+    tls: overrides.tls ?? null,
+    tlsCaPath: overrides.tlsCaPath ?? null,
+  });
+}
+
+export type ReplayedAppServerEvent = {
+  seq: number;
+  workspace_id: string;
+  message: Record<string, unknown>;
+};
+
+export type EventReplay = {
+  events: ReplayedAppServerEvent[];
+  latestSeq: number;
+  truncated: boolean;
+};
+
+export async function eventsReplay(
+  workspaceId: string,
+  sinceSeq = 0,
+): Promise<EventReplay> {
+  return invoke<EventReplay>("events_replay", { workspaceId, sinceSeq });
+}
+
+export async function openLogDir(): Promise<void> {
+  return invoke("open_log_dir");
+}
+
+export async function logTail(
+  workspaceId: string | null,
+  n = 200,
+): Promise<string[]> {
+  return invoke<string[]>("log_tail", { workspaceId, n });
+}
+
+export type TurnMetrics = {
+  threadId: string;
+  turnId: string | null;
+  startedAt: number;
+  durationMs: number;
+  toolCalls: number;
+  outputBytes: number;
+  inputTokens: number | null;
+  outputTokens: number | null;
+  costUsd: number | null;
+  exitCode: number | null;
+  timedOut: boolean;
+};
+
+export async function turnMetricsList(
+  workspaceId: string,
+  threadId?: string | null,
+  limit?: number | null,
+): Promise<TurnMetrics[]> {
+  return invoke<TurnMetrics[]>("turn_metrics_list", {
+    workspaceId,
+    threadId: threadId ?? null,
+    limit: limit ?? null,
+  });
+}
+
+export type BudgetStatus = {
+  month: string;
+  spentUsd: number;
+  tokens: number;
+  budget: MonthlyBudget | null;
+  usedFraction: number | null;
+  overridden: boolean;
+};
+
+export async function workspaceBudgetStatus(
+  workspaceId: string,
+): Promise<BudgetStatus> {
+  return invoke<BudgetStatus>("workspace_budget_status", { workspaceId });
+}
+
+export async function overrideWorkspaceBudget(
+  workspaceId: string,
+): Promise<BudgetStatus> {
+  return invoke<BudgetStatus>("workspace_budget_override", { workspaceId });
+}
+
+export type ActivityStats = {
+  turns: number;
+  succeeded: number;
+  failed: number;
+  successRate: number | null;
+  inputTokens: number;
+  outputTokens: number;
+  costUsd: number;
+  durationMs: number;
+};
+
+export type ActivityDay = ActivityStats & {
+  day: string;
+  workspaceId: string;
+  provider: string;
+};
+
+export type ActivityTimeline = {
+  days: string[];
+  activity: ActivityDay[];
+  workspaces: (ActivityStats & { workspaceId: string; provider: string })[];
+};
+
+export async function activityTimeline(
+  days?: number | null,
+): Promise<ActivityTimeline> {
+  return invoke<ActivityTimeline>("activity_timeline", { days: days ?? null });
+}
+
+export type PatchApplyResult = {
+  applied: boolean;
+  files: string[];
+  conflicts: string[];
+  error: string | null;
+};
+
+export async function applyPatch(
+  workspaceId: string,
+  itemId: string,
+): Promise<PatchApplyResult> {
+  return invoke<PatchApplyResult>("patch_apply", { workspaceId, itemId });
+}
+
+export type McpServer = {
+  name: string;
+  command?: string | null;
+  args?: string[];
+  env?: Record<string, string>;
+  url?: string | null;
+  enabled?: boolean;
+  sources?: string[];
+};
+
+export async function mcpServersList(): Promise<McpServer[]> {
+  return invoke<McpServer[]>("mcp_servers_list");
+}
+
+export async function mcpServerAdd(server: McpServer): Promise<McpServer[]> {
+  return invoke<McpServer[]>("mcp_server_add", { server });
+}
+
+export async function mcpServerRemove(name: string): Promise<McpServer[]> {
+  return invoke<McpServer[]>("mcp_server_remove", { name });
+}
+
+export async function mcpServerToggle(
+  name: string,
+  enabled: boolean,
+): Promise<McpServer[]> {
+  return invoke<McpServer[]>("mcp_server_toggle", { name, enabled });
+}
+
+export type ClaudeSettings = {
+  model?: string;
+  permissions?: {
+    allow?: string[];
+    deny?: string[];
+    ask?: string[];
+    defaultMode?: string;
+    additionalDirectories?: string[];
+    [key: string]: unknown;
+  };
+  hooks?: Record<string, unknown>;
+  env?: Record<string, string>;
+  [key: string]: unknown;
+};
+
+export async function getClaudeSettings(): Promise<ClaudeSettings> {
+  return invoke<ClaudeSettings>("get_claude_settings");
+}
+
+export async function updateClaudeSettings(
+  settings: ClaudeSettings,
+): Promise<ClaudeSettings> {
+  return invoke<ClaudeSettings>("update_claude_settings", { settings });
 }
 
 export async function orbitConnectTest(): Promise<OrbitConnectTestResult> {
@@ -690,6 +1236,59 @@ export async function readWorkspaceFile(
   });
 }
 
+export async function readWorkspaceTextFile(
+  workspaceId: string,
+  path: string,
+): Promise<TextFileResponse> {
+  return invoke<TextFileResponse>("workspace_file_read", { workspaceId, path });
+}
+
+export async function writeWorkspaceTextFile(
+  workspaceId: string,
+  path: string,
+  content: string,
+): Promise<void> {
+  return invoke("workspace_file_write", { workspaceId, path, content });
+}
+
+export async function readFileRange(
+  workspaceId: string,
+  path: string,
+  offset: number | null,
+  length: number,
+): Promise<TextRangeResponse> {
+  return invoke<TextRangeResponse>("file_read_range", {
+    workspaceId,
+    path,
+    offset,
+    length,
+  });
+}
+
+export async function subscribeFileTail(
+  workspaceId: string,
+  path: string,
+  offset?: number,
+): Promise<FileTailSubscription> {
+  return invoke<FileTailSubscription>("file_tail_subscribe", {
+    workspaceId,
+    path,
+    offset,
+  });
+}
+
+export async function unsubscribeFileTail(subscriptionId: string): Promise<boolean> {
+  return invoke<boolean>("file_tail_unsubscribe", { subscriptionId });
+}
+
+export async function getWorkspaceTree(
+  workspaceId: string,
+  path?: string,
+  depth?: number,
+): Promise<WorkspaceTreeResponse> {
+  return invoke<WorkspaceTreeResponse>("workspace_tree", { workspaceId, path, depth });
+}
+
 export async function readAgentMd(workspaceId: string): Promise<AgentMdResponse> {
   return fileRead("workspace", "agents", workspaceId);
 }
@@ -704,6 +1303,46 @@ export async function listAgentProfiles(
   return invoke<AgentProfileListResponse>("agent_profiles_list", { workspaceId });
 }
 
+export async function getAgentProfileStatus(
+  workspaceId: string,
+): Promise<AgentProfileStatus> {
+  return invoke<AgentProfileStatus>("agent_profile_status", { workspaceId });
+}
+
+export async function syncAgentProfile(
+  workspaceId: string,
+  action: AgentProfileSyncAction,
+): Promise<AgentProfileStatus> {
+  return invoke<AgentProfileStatus>("agent_profile_sync", { workspaceId, action });
+}
+
+export async function saveAgentProfile(
+  workspaceId: string,
+  profile: string,
+): Promise<AgentProfileStatus> {
+  return invoke<AgentProfileStatus>("agent_profile_save", { workspaceId, profile });
+}
+
+export async function listAgentsMdSections(
+  workspaceId: string,
+): Promise<AgentsMdSectionsResponse> {
+  return invoke<AgentsMdSectionsResponse>("agents_md_sections_list", {
+    workspaceId,
+  });
+}
+
+export async function updateAgentsMdSection(
+  workspaceId: string,
+  heading: string,
+  content: string,
+): Promise<AgentsMdSectionsResponse> {
+  return invoke<AgentsMdSectionsResponse>("agents_md_section_update", {
+    workspaceId,
+    heading,
+    content,
+  });
+}
+
 export async function applyAgentProfile(
   workspaceId: string,
   profile: string,
@@ -824,8 +1463,35 @@ export async function listThreads(
   cursor?: string | null,
   limit?: number | null,
   sortKey?: "created_at" | "updated_at" | null,
+  archived?: boolean | null,
 ) {
-  return invoke<any>("list_threads", { workspaceId, cursor, limit, sortKey });
+  return invoke<any>("list_threads", {
+    workspaceId,
+    cursor,
+    limit,
+    sortKey,
+    archived,
+  });
+}
+
+export type CodexSessionSummary = {
+  id: string;
+  path: string;
+  cwd: string;
+  preview: string | null;
+  source: string | null;
+  createdAt: number;
+  updatedAt: number;
+};
+
+export async function codexSessionsList(
+  workspaceId: string,
+): Promise<CodexSessionSummary[]> {
+  return invoke<CodexSessionSummary[]>("codex_sessions_list", { workspaceId });
+}
+
+export async function codexSessionRead(workspaceId: string, sessionId: string) {
+  return invoke<any>("codex_session_read", { workspaceId, sessionId });
 }
 
 export async function listMcpServerStatus(
@@ -844,6 +1510,14 @@ export async function archiveThread(workspaceId: string, threadId: string) {
   return invoke<any>("archive_thread", { workspaceId, threadId });
 }
 
+export async function unarchiveThread(workspaceId: string, threadId: string) {
+  return invoke<any>("unarchive_thread", { workspaceId, threadId });
+}
+
+export async function deleteThread(workspaceId: string, threadId: string) {
+  return invoke<any>("delete_thread", { workspaceId, threadId });
+}
+
 export async function setThreadName(
   workspaceId: string,
   threadId: string,
@@ -18,6 +18,29 @@ export type WorkspaceSettings = {
   launchScript?: string | null;
   launchScripts?: LaunchScriptEntry[] | null;
   worktreeSetupScript?: string | null;
+  reasoningEffort?: string | null;
+  monthlyBudget?: MonthlyBudget | null;
+  fileWatchDebounceMs?: number | null;
+  autoCompact?: AutoCompactPolicy | null;
+  turnTimeoutSecs?: number | null;
+  accountProfiles?: Partial<Record<AccountProfileProvider, string>>;
+  ssh?: SshTarget | null;
+  docker?: DockerTarget | null;
+  env?: Record<string, string>;
+  codexConfig?: CodexConfigOverrides | null;
+  guardrails?: GuardrailRule[];
+  postTurnHooks?: PostTurnHook[];
+  preTurnHooks?: PreTurnHook[];
+};
+
+export type MonthlyBudget = {
+  maxUsd?: number | null;
+  maxTokens?: number | null;
+};
+
+export type AutoCompactPolicy = {
+  thresholdPercent: number;
+  contextWindow?: number | null;
 };
 
 export type LaunchScriptIconId =
@@ -69,26 +92,89 @@ export type WorkspaceInfo = {
   settings: WorkspaceSettings;
 };
 
+/** A removed workspace that `workspace_restore` can bring back. */
+export type TrashedWorkspace = {
+  entry: Omit<WorkspaceInfo, "connected">;
+  removedAt: number;
+  purgeAt: number;
+};
+
 export type AgentProfileMode = "symlink" | "copy";
 export type AgentProfileApplyMode = "auto" | "symlink" | "copy";
 
+export type AgentProfileTargetFile =
+  | "AGENTS.md"
+  | "CLAUDE.md"
+  | "GEMINI.md"
+  | ".cursorrules";
+
 export type AgentProfile = {
   name: string;
   label: string;
   hasAgents: boolean;
   hasClaude: boolean;
+  hasGemini: boolean;
+  hasCursorRules: boolean;
+  extends: string[];
 };
 
 export type AgentProfileListResponse = {
   profiles: AgentProfile[];
   activeProfile: string | null;
-  targetFile: "AGENTS.md" | "CLAUDE.md";
+  targetFile: AgentProfileTargetFile;
+  activeMode: AgentProfileMode | null;
+};
+
+export type AgentProfileDrift =
+  | "unmanaged"
+  | "inSync"
+  | "modified"
+  | "missing"
+  | "sourceMissing";
+
+export type AgentProfileSyncAction = "reapply" | "save-back-to-profile";
+
+export type AgentProfileStatus = {
+  profile: string | null;
+  targetFile: AgentProfileTargetFile;
   activeMode: AgentProfileMode | null;
+  drift: AgentProfileDrift;
+  diff: string | null;
+  actions: AgentProfileSyncAction[];
+};
+
+export type AgentsMdSection = {
+  heading: string;
+  level: number;
+  line: number;
+  content: string;
+};
+
+export type AgentsMdSectionsResponse = {
+  exists: boolean;
+  preamble: string;
+  sections: AgentsMdSection[];
+};
+
+export type WorkspaceTreeEntry = {
+  name: string;
+  path: string;
+  kind: "file" | "dir" | "symlink";
+  size: number | null;
+  modifiedMs: number | null;
+  children: WorkspaceTreeEntry[] | null;
+};
+
+export type WorkspaceTreeResponse = {
+  path: string;
+  depth: number;
+  entries: WorkspaceTreeEntry[];
+  truncated: boolean;
 };
 
 export type AgentProfileApplyResponse = {
   activeProfile: string;
-  targetFile: "AGENTS.md" | "CLAUDE.md";
+  targetFile: AgentProfileTargetFile;
   activeMode: AgentProfileMode;
   fallbackUsed: boolean;
 };
@@ -185,6 +271,163 @@ export type DetectedClis = {
   claude: string | null;
   gemini: string | null;
   cursor: string | null;
+  aider: string | null;
+  opencode: string | null;
+  qwen: string | null;
+  copilot: string | null;
+  goose: string | null;
+  paths: Record<string, string>;
+  detectedAt: Record<string, string>;
+  installHints: Record<string, CliInstallHint>;
+};
+
+export type CliInstallHint = {
+  packageManager: "npm" | "brew" | "pip" | "script" | "download";
+  command: string | null;
+  docsUrl: string;
+};
+
+export type CliPluginInfo = {
+  id: string;
+  name: string;
+  command: string;
+  path: string;
+};
+
+export type CliPlugins = {
+  dir: string;
+  plugins: CliPluginInfo[];
+  errors: { path: string; error: string }[];
+};
+
+export type CliUpdateStatus = {
+  name: "codex" | "claude" | "gemini" | "cursor";
+  installed: string | null;
+  latest: string | null;
+  updateAvailable: boolean;
+  error: string | null;
+};
+
+export type GuardrailRule = {
+  pattern: string;
+  action?: "warn" | "interrupt";
+};
+
+export type EventHook = {
+  method: string;
+  workspaceId?: string | null;
+  command: string;
+  timeoutSecs?: number | null;
+};
+
+export type PostTurnHook = {
+  command: string;
+  timeoutSecs?: number | null;
+};
+
+export type PreTurnHook = {
+  command: string;
+  timeoutSecs?: number | null;
+  onFailure?: "abort" | "continue";
+};
+
+export type CodexConfigOverrides = {
+  model?: string | null;
+  approvalPolicy?: "untrusted" | "on-failure" | "on-request" | "never" | null;
+  sandboxMode?: "read-only" | "workspace-write" | "danger-full-access" | null;
+};
+
+export type SshTarget = {
+  destination: string;
+  port?: number | null;
+  identityFile?: string | null;
+};
+
+export type DockerTarget = {
+  image?: string | null;
+  container?: string | null;
+  mounts?: string[];
+  env?: Record<string, string>;
+  workdir?: string | null;
+};
+
+export type AccountProfileProvider = "codex" | "claude" | "gemini" | "cursor";
+
+export type AccountProfile = {
+  provider: AccountProfileProvider;
+  name: string;
+  home: string;
+};
+
+export type AppStateTransfer = {
+  workspaces: number;
+  threadFiles: number;
+  agentProfileStates: number;
+  excludedSecrets: string[];
+};
+
+export type AuditEntry = {
+  seq: number;
+  timestamp: string;
+  action: string;
+  origin: "local" | "remote";
+  details: Record<string, unknown>;
+  prevHash: string;
+  hash: string;
+};
+
+/** An app-server request still waiting for its response. */
+export type PendingRequestInfo = {
+  id: number;
+  method: string;
+  elapsedMs: number;
+};
+
+export type BackgroundCallbackInfo = {
+  threadId: string;
+  depth: number;
+  dropped: number;
+};
+
+export type BackgroundTurnResult = {
+  turnId: string;
+  workspaceId: string;
+  status: "running" | "completed" | "failed";
+  text: string | null;
+  items: Record<string, unknown>[];
+  truncated: boolean;
+  error: string | null;
+  startedAt: string;
+  finishedAt: string | null;
+};
+
+export type AppErrorCode =
+  | "cli_not_found"
+  | "workspace_not_found"
+  | "workspace_not_connected"
+  | "invalid_input"
+  | "io"
+  | "timeout"
+  | "remote"
+  | "internal";
+
+/** What commands returning `AppError` reject with. */
+export type AppErrorPayload = {
+  code: AppErrorCode;
+  message: string;
+  retriable: boolean;
+};
+
+export type AuditLogQueryResult = {
+  entries: AuditEntry[];
+  verified: boolean;
+  brokenAtLine: number | null;
+};
+
+export type SettingsProfile = {
+  name: string;
+  codexOverlay: Record<string, string>;
+  active: boolean;
 };
 
 export type AppSettings = {
@@ -217,6 +460,7 @@ export type AppSettings = {
   orbitAccessClientSecretRef: string | null;
   defaultAccessMode: AccessMode;
   reviewDeliveryMode: "inline" | "detached";
+  settingsProfile?: string | null;
   composerModelShortcut: string | null;
   composerAccessShortcut: string | null;
   composerReasoningShortcut: string | null;
@@ -246,6 +490,15 @@ export type AppSettings = {
   codeFontSize: number;
   notificationSoundsEnabled: boolean;
   systemNotificationsEnabled: boolean;
+  backgroundMode?: boolean;
+  shutdownTurnPolicy?: "wait" | "interrupt";
+  shutdownTimeoutSecs?: number;
+  deltaBatchWindowMs?: number;
+  eventHooks?: EventHook[];
+  httpApiEnabled?: boolean;
+  httpApiPort?: number;
+  httpApiToken?: string | null;
+  workspaceTrashRetentionDays?: number;
   preloadGitDiffs: boolean;
   gitDiffIgnoreWhitespaceChanges: boolean;
   experimentalCollabEnabled: boolean;
@@ -573,7 +826,7 @@ export type RateLimitSnapshot = {
 };
 
 export type AccountSnapshot = {
-  type: "chatgpt" | "apikey" | "unknown";
+  type: "chatgpt" | "apikey" | "oauth" | "unknown";
   email: string | null;
   planType: string | null;
   requiresOpenaiAuth: boolean | null;